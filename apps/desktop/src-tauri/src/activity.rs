@@ -0,0 +1,235 @@
+// 写作活动统计
+// 每次保存文档时按字数差值累计到当天（按项目细分）；
+// 写作时长由相邻两次保存的间隔推算，间隔超过 IDLE_GAP_SECS 视为中途离开，不计入
+
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+/// 两次保存间隔超过此值（秒）不计入写作时长
+const IDLE_GAP_SECS: i64 = 300;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Counters {
+    pub words_added: usize,
+    pub words_removed: usize,
+    pub active_seconds: i64,
+    pub saves: usize,
+}
+
+impl Counters {
+    fn add(&mut self, delta: i64, active: i64) {
+        if delta > 0 {
+            self.words_added += delta as usize;
+        } else {
+            self.words_removed += delta.unsigned_abs() as usize;
+        }
+        self.active_seconds += active;
+        self.saves += 1;
+    }
+
+    fn merge(&mut self, other: &Counters) {
+        self.words_added += other.words_added;
+        self.words_removed += other.words_removed;
+        self.active_seconds += other.active_seconds;
+        self.saves += other.saves;
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DayStats {
+    #[serde(flatten)]
+    pub total: Counters,
+    pub projects: BTreeMap<String, Counters>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ActivityStore {
+    /// 日期（YYYY-MM-DD，本地时区）→ 当天统计
+    days: BTreeMap<String, DayStats>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DayActivity {
+    pub date: String,
+    #[serde(flatten)]
+    pub counters: Counters,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectActivity {
+    pub project_id: String,
+    #[serde(flatten)]
+    pub counters: Counters,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WritingActivity {
+    /// 区间内有记录的日期（升序）
+    pub days: Vec<DayActivity>,
+    pub total: Counters,
+    pub by_project: Vec<ProjectActivity>,
+    /// 截至今天的连续写作天数（今天尚未写作时从昨天算起）
+    pub current_streak: usize,
+    pub longest_streak: usize,
+}
+
+/// 查询区间（包含两端，YYYY-MM-DD），为空表示不限
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ActivityRange {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// 统计文件的读写锁，避免并发保存时互相覆盖
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 文档上次保存时间，用于推算写作时长
+static LAST_SAVE: OnceLock<Mutex<HashMap<String, i64>>> = OnceLock::new();
+
+/// 字数：中日韩字符每字计 1，其余按空白分隔的词计
+pub fn count_words(text: &str) -> usize {
+    let mut count = 0;
+    let mut in_word = false;
+    for c in text.chars() {
+        if is_cjk(c) {
+            count += 1;
+            in_word = false;
+        } else if c.is_alphanumeric() {
+            if !in_word {
+                count += 1;
+                in_word = true;
+            }
+        } else {
+            // 单词内的 ' 和 - 不断开（don't、well-known）
+            in_word = in_word && (c == '\'' || c == '-');
+        }
+    }
+    count
+}
+
+pub(crate) fn is_cjk(c: char) -> bool {
+    matches!(c as u32, 0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF)
+}
+
+fn load_store(path: &Path) -> ActivityStore {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_store(path: &Path, store: &ActivityStore) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string(store).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("保存写作统计失败: {}", e))
+}
+
+pub fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// 记录一次保存：字数差值计入当天，返回当天（全部项目）的统计
+pub fn record_save(path: &Path, project_id: &str, document_id: &str, old_words: usize, new_words: usize) -> Result<DayStats, String> {
+    let now = chrono::Utc::now().timestamp();
+    let active = LAST_SAVE
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .ok()
+        .and_then(|mut last| last.insert(document_id.to_string(), now))
+        .map(|prev| now - prev)
+        .filter(|gap| (0..=IDLE_GAP_SECS).contains(gap))
+        .unwrap_or(0);
+    let delta = new_words as i64 - old_words as i64;
+
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut store = load_store(path);
+    let day = store.days.entry(today()).or_default();
+    day.total.add(delta, active);
+    day.projects.entry(project_id.to_string()).or_default().add(delta, active);
+    let snapshot = day.clone();
+    save_store(path, &store)?;
+    Ok(snapshot)
+}
+
+/// 某天的统计（无记录时为空）
+pub fn day_stats(path: &Path, date: &str) -> DayStats {
+    let _guard = STORE_LOCK.lock();
+    load_store(path).days.remove(date).unwrap_or_default()
+}
+
+/// 查询区间内的写作活动；指定项目时只统计该项目
+pub fn query(path: &Path, range: &ActivityRange, project_id: Option<&str>) -> WritingActivity {
+    let store = {
+        let _guard = STORE_LOCK.lock();
+        load_store(path)
+    };
+    let in_range = |date: &str| {
+        range.from.as_deref().is_none_or(|from| date >= from) && range.to.as_deref().is_none_or(|to| date <= to)
+    };
+    let counters_of = |day: &DayStats| match project_id {
+        Some(pid) => day.projects.get(pid).cloned().unwrap_or_default(),
+        None => day.total.clone(),
+    };
+
+    let mut days = Vec::new();
+    let mut total = Counters::default();
+    let mut projects: BTreeMap<String, Counters> = BTreeMap::new();
+    for (date, day) in store.days.iter().filter(|(d, _)| in_range(d)) {
+        let counters = counters_of(day);
+        if counters.saves == 0 {
+            continue;
+        }
+        total.merge(&counters);
+        for (pid, c) in day.projects.iter().filter(|(pid, _)| project_id.is_none_or(|p| p == pid.as_str())) {
+            projects.entry(pid.clone()).or_default().merge(c);
+        }
+        days.push(DayActivity { date: date.clone(), counters });
+    }
+
+    // 连续天数基于全部历史计算，不受查询区间影响
+    let writing_days: Vec<NaiveDate> = store
+        .days
+        .iter()
+        .filter(|(_, day)| counters_of(day).words_added > 0)
+        .filter_map(|(date, _)| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+        .collect();
+    let (current_streak, longest_streak) = streaks(&writing_days, Local::now().date_naive());
+
+    let mut by_project: Vec<ProjectActivity> =
+        projects.into_iter().map(|(project_id, counters)| ProjectActivity { project_id, counters }).collect();
+    by_project.sort_by_key(|p| std::cmp::Reverse(p.counters.words_added));
+    WritingActivity { days, total, by_project, current_streak, longest_streak }
+}
+
+/// days 为升序日期
+fn streaks(days: &[NaiveDate], today: NaiveDate) -> (usize, usize) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut prev: Option<NaiveDate> = None;
+    for &day in days {
+        run = match prev {
+            Some(p) if day.signed_duration_since(p).num_days() == 1 => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        prev = Some(day);
+    }
+    let current = match prev {
+        Some(last) if (today.signed_duration_since(last).num_days()) <= 1 => run,
+        _ => 0,
+    };
+    (current, longest)
+}
@@ -2,12 +2,32 @@
 
 use serde::{Deserialize, Serialize};
 
+/// Azure OpenAI 默认 API 版本；终结点地址中带 api-version 参数时以地址为准
+pub const AZURE_API_VERSION: &str = "2024-10-21";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AIConfig {
     pub provider: String,
     pub api_key: Option<String>,
     pub base_url: Option<String>,
     pub model: Option<String>,
+    /// 项目或全局设置中的采样温度，未设置时使用 0.7
+    pub temperature: Option<f64>,
+    /// 出站代理（含密码，不序列化）
+    #[serde(skip)]
+    pub proxy: Option<ProxyConfig>,
+    /// 联网搜索后备使用的搜索服务（含 API Key，不序列化）
+    #[serde(skip)]
+    pub search: crate::web_search::SearchConfig,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ProxyConfig {
+    pub url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// 不走代理的主机
+    pub no_proxy: Vec<String>,
 }
 
 impl Default for AIConfig {
@@ -17,6 +37,9 @@ impl Default for AIConfig {
             api_key: None,
             base_url: None,
             model: None,
+            temperature: None,
+            proxy: None,
+            search: crate::web_search::SearchConfig::default(),
         }
     }
 }
@@ -25,6 +48,85 @@ impl Default for AIConfig {
 pub struct ChatMessage {
     pub role: String,
     pub content: String,
+    /// 附带的图片（data URI 或纯 base64，如 read_file_base64 的返回值），用于视觉模型
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<String>,
+}
+
+/// 拆分图片为 (MIME 类型, base64 数据)；纯 base64 按文件头推断类型
+pub fn split_image(image: &str) -> (String, String) {
+    if let Some((header, data)) = image.strip_prefix("data:").and_then(|rest| rest.split_once(',')) {
+        let mime = header.split(';').next().filter(|m| !m.is_empty()).unwrap_or("image/png");
+        return (mime.to_string(), data.to_string());
+    }
+    let mime = match image.get(..4) {
+        Some("/9j/") => "image/jpeg",
+        Some("R0lG") => "image/gif",
+        Some("UklG") => "image/webp",
+        _ => "image/png",
+    };
+    (mime.to_string(), image.to_string())
+}
+
+impl ChatMessage {
+    fn data_uris(&self) -> impl Iterator<Item = String> + '_ {
+        self.images.iter().map(|image| {
+            let (mime, data) = split_image(image);
+            format!("data:{};base64,{}", mime, data)
+        })
+    }
+
+    /// OpenAI Chat Completions 格式的 content（含图片时为多段数组）
+    pub fn openai_content(&self) -> serde_json::Value {
+        if self.images.is_empty() {
+            return serde_json::json!(self.content);
+        }
+        let mut parts = vec![serde_json::json!({ "type": "text", "text": self.content })];
+        parts.extend(self.data_uris().map(|url| serde_json::json!({ "type": "image_url", "image_url": { "url": url } })));
+        serde_json::Value::Array(parts)
+    }
+
+    /// OpenAI Responses API 格式的 content
+    pub fn responses_content(&self) -> serde_json::Value {
+        if self.images.is_empty() {
+            return serde_json::json!(self.content);
+        }
+        let mut parts = vec![serde_json::json!({ "type": "input_text", "text": self.content })];
+        parts.extend(self.data_uris().map(|url| serde_json::json!({ "type": "input_image", "image_url": url })));
+        serde_json::Value::Array(parts)
+    }
+
+    /// Anthropic Messages API 格式的 content（图片放在文本前面，官方建议的顺序）
+    pub fn anthropic_content(&self) -> serde_json::Value {
+        if self.images.is_empty() {
+            return serde_json::json!(self.content);
+        }
+        let mut parts: Vec<serde_json::Value> = self
+            .images
+            .iter()
+            .map(|image| {
+                let (mime, data) = split_image(image);
+                serde_json::json!({ "type": "image", "source": { "type": "base64", "media_type": mime, "data": data } })
+            })
+            .collect();
+        parts.push(serde_json::json!({ "type": "text", "text": self.content }));
+        serde_json::Value::Array(parts)
+    }
+
+    /// Gemini 原生接口的 parts（文本在前，图片为 inlineData）
+    pub fn gemini_parts(&self) -> serde_json::Value {
+        let mut parts = vec![serde_json::json!({ "text": self.content })];
+        parts.extend(self.images.iter().map(|image| {
+            let (mime, data) = split_image(image);
+            serde_json::json!({ "inlineData": { "mimeType": mime, "data": data } })
+        }));
+        serde_json::Value::Array(parts)
+    }
+
+    /// OpenAI 兼容格式的完整消息
+    pub fn to_openai(&self) -> serde_json::Value {
+        serde_json::json!({ "role": self.role, "content": self.openai_content() })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +153,45 @@ pub struct Usage {
     pub total_tokens: u32,
 }
 
+impl Usage {
+    pub fn new(prompt_tokens: u32, completion_tokens: u32) -> Self {
+        Self { prompt_tokens, completion_tokens, total_tokens: prompt_tokens + completion_tokens }
+    }
+
+    /// 从 JSON 中读取用量：OpenAI 为 prompt_tokens / completion_tokens，
+    /// Anthropic 和 Responses API 为 input_tokens / output_tokens
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        let field = |names: &[&str]| names.iter().find_map(|n| value.get(*n).and_then(|v| v.as_u64())).map(|v| v as u32);
+        let prompt = field(&["prompt_tokens", "input_tokens"]);
+        let completion = field(&["completion_tokens", "output_tokens"]);
+        if prompt.is_none() && completion.is_none() {
+            return None;
+        }
+        let mut usage = Self::new(prompt.unwrap_or(0), completion.unwrap_or(0));
+        if let Some(total) = field(&["total_tokens"]) {
+            usage.total_tokens = total;
+        }
+        Some(usage)
+    }
+}
+
+/// 服务商提供的模型；上下文长度等信息仅在接口返回时才有
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelInfo {
+    pub id: String,
+    pub name: Option<String>,
+    pub context_window: Option<u64>,
+    pub max_output_tokens: Option<u64>,
+}
+
+/// 流式调用结果：完整内容和用量（服务商未返回用量或流被中止时为空）
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StreamResult {
+    pub content: String,
+    pub usage: Option<Usage>,
+}
+
 // OpenAI compatible API response format
 #[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
@@ -118,6 +259,10 @@ impl AIConfig {
             "kimi" => "https://api.moonshot.cn/v1".to_string(),
             "kimi-code" => "https://api.kimi.com/coding/v1".to_string(),
             "litellm" => "http://localhost:4000".to_string(),
+            // Ollama 原生接口（/api/chat），地址不含 /v1
+            "ollama" => "http://localhost:11434".to_string(),
+            // Azure OpenAI 的终结点随资源名而定，必须由用户填写
+            "azure" => String::new(),
             _ => "https://api.openai.com/v1".to_string(),
         }
     }
@@ -140,7 +285,79 @@ impl AIConfig {
             "kimi" => "kimi-k2.5".to_string(),
             "kimi-code" => "kimi-for-coding".to_string(),
             "litellm" => "gpt-4.1".to_string(),
+            "ollama" => "llama3.2".to_string(),
+            // Azure OpenAI 的“模型”为部署名
+            "azure" => "gpt-4.1".to_string(),
             _ => "gpt-4.1".to_string(),
         }
     }
+
+    /// 按代理设置构造 HTTP 客户端（支持 HTTP(S) 和 SOCKS5 代理）；未配置代理时沿用系统环境变量中的代理
+    pub fn http_client(&self) -> Result<reqwest::Client, String> {
+        if self.provider == "azure" && self.get_base_url().trim().is_empty() {
+            return Err("Azure OpenAI 需要填写资源终结点，如 https://<资源名>.openai.azure.com".to_string());
+        }
+        let mut builder = reqwest::Client::builder();
+        if let Some(proxy) = &self.proxy {
+            let mut p = reqwest::Proxy::all(&proxy.url).map_err(|e| format!("代理地址无效: {}", e))?;
+            if let Some(username) = proxy.username.as_deref().filter(|u| !u.is_empty()) {
+                p = p.basic_auth(username, proxy.password.as_deref().unwrap_or_default());
+            }
+            if !proxy.no_proxy.is_empty() {
+                p = p.no_proxy(reqwest::NoProxy::from_string(&proxy.no_proxy.join(",")));
+            }
+            builder = builder.proxy(p);
+        }
+        builder.build().map_err(|e| format!("创建 HTTP 客户端失败: {}", e))
+    }
+
+    /// OpenAI 兼容接口地址：一般为 {base_url}/{path}；
+    /// Azure OpenAI 为 {终结点}/openai/deployments/{部署名}/{path}?api-version=...，部署名取模型名
+    pub fn endpoint_url(&self, path: &str) -> String {
+        self.deployment_url(&self.get_default_model(), path)
+    }
+
+    /// 同 endpoint_url，指定 Azure 部署名（如语音转写使用单独的部署）
+    pub fn deployment_url(&self, deployment: &str, path: &str) -> String {
+        let base = self.get_base_url();
+        if self.provider != "azure" {
+            return format!("{}/{}", base.trim_end_matches('/'), path);
+        }
+        let (endpoint, version) = self.azure_endpoint();
+        format!("{}/openai/deployments/{}/{}?api-version={}", endpoint, deployment, path, version)
+    }
+
+    /// Azure 终结点（去掉末尾的 /openai 和查询参数）及 API 版本
+    pub fn azure_endpoint(&self) -> (String, String) {
+        let base = self.get_base_url();
+        let (endpoint, query) = base.split_once('?').unwrap_or((&base, ""));
+        let endpoint = endpoint.trim_end_matches('/');
+        let endpoint = endpoint.strip_suffix("/openai").unwrap_or(endpoint);
+        let version = query.split('&').find_map(|p| p.strip_prefix("api-version=")).unwrap_or(AZURE_API_VERSION);
+        (endpoint.to_string(), version.to_string())
+    }
+
+    /// 按服务商添加鉴权请求头：Anthropic 为 x-api-key，Azure 为 api-key，其他为 Bearer
+    pub fn authorize(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let Some(key) = &self.api_key else { return builder };
+        match self.provider.as_str() {
+            "anthropic" => builder.header("x-api-key", key),
+            "azure" => builder.header("api-key", key),
+            _ => builder.header("Authorization", format!("Bearer {}", key)),
+        }
+    }
+
+    /// Gemini 原生接口根地址：去掉 OpenAI 兼容地址末尾的 /openai
+    pub fn gemini_root(&self) -> String {
+        let url = self.get_base_url();
+        let url = url.trim_end_matches('/');
+        url.strip_suffix("/openai").unwrap_or(url).to_string()
+    }
+
+    /// Ollama 服务根地址：兼容用户填写的 OpenAI 兼容地址（末尾的 /v1）
+    pub fn ollama_root(&self) -> String {
+        let url = self.get_base_url();
+        let url = url.trim_end_matches('/');
+        url.strip_suffix("/v1").unwrap_or(url).to_string()
+    }
 }
@@ -0,0 +1,54 @@
+// AI 响应缓存：相同请求（服务商、模型、消息、参数）在有效期内直接返回上次结果，不再消耗 token
+// 每条缓存一个 JSON 文件，文件名为请求内容的 SHA-256；设置中有效期为 0 时不启用
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheEntry {
+    created_at: i64,
+    content: String,
+}
+
+/// 请求的缓存键：对规范化后的请求 JSON 取 SHA-256
+pub fn key(request: &serde_json::Value) -> String {
+    Sha256::digest(request.to_string().as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn entry_path(dir: &Path, key: &str) -> PathBuf {
+    dir.join(format!("{}.json", key))
+}
+
+/// 读取有效期内的缓存；过期的条目顺便删除
+pub fn get(dir: &Path, key: &str, ttl_secs: u64) -> Option<String> {
+    let path = entry_path(dir, key);
+    let entry: CacheEntry = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+    if chrono::Utc::now().timestamp() - entry.created_at > ttl_secs as i64 {
+        let _ = fs::remove_file(path);
+        return None;
+    }
+    Some(entry.content)
+}
+
+pub fn put(dir: &Path, key: &str, content: &str) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("创建缓存目录失败: {}", e))?;
+    let entry = CacheEntry { created_at: chrono::Utc::now().timestamp(), content: content.to_string() };
+    let json = serde_json::to_string(&entry).map_err(|e| e.to_string())?;
+    fs::write(entry_path(dir, key), json).map_err(|e| format!("写入缓存失败: {}", e))
+}
+
+/// 清空缓存，返回删除的条目数
+pub fn clear(dir: &Path) -> Result<usize, String> {
+    let Ok(entries) = fs::read_dir(dir) else { return Ok(0) };
+    let mut removed = 0;
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.extension().and_then(|s| s.to_str()) == Some("json") {
+            fs::remove_file(&path).map_err(|e| format!("删除缓存失败: {}", e))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
@@ -0,0 +1,192 @@
+// AI 局部编辑：让模型只返回修改块（SEARCH/REPLACE 或 unified diff），后端校验后一次性应用
+// 任一修改块无法唯一定位时整体失败，文档保持不变；成功时返回修改后的范围供前端高亮
+
+use crate::ai::ChatMessage;
+use serde::Serialize;
+
+const SEARCH_MARKER: &str = "<<<<<<< SEARCH";
+const DIVIDER: &str = "=======";
+const REPLACE_MARKER: &str = ">>>>>>> REPLACE";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditBlock {
+    pub search: String,
+    pub replace: String,
+}
+
+/// 修改后的范围（新正文中的位置；行号从 1 开始，偏移量按字符计）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedRange {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub start: usize,
+    pub end: usize,
+    pub removed: String,
+    pub inserted: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiEditResult {
+    pub content: String,
+    pub changes: Vec<ChangedRange>,
+    /// 是否已写入文档（dryRun 时为 false）
+    pub applied: bool,
+    /// 模型没有给出任何修改
+    pub unchanged: bool,
+}
+
+/// 构造请求消息：正文带行号会干扰 SEARCH 原文匹配，原样发送
+pub fn build_messages(content: &str, instruction: &str) -> Vec<ChatMessage> {
+    let system = format!(
+        "你是文档编辑助手。按用户的要求修改文档，只输出需要修改的部分，格式为一个或多个修改块：\n\n\
+         {}\n需要替换的原文（必须与文档逐字一致，包含足够的上下文以唯一定位）\n{}\n替换后的内容\n{}\n\n\
+         删除内容时替换部分留空。不要输出修改块以外的解释；无需修改时输出“无需修改”。",
+        SEARCH_MARKER, DIVIDER, REPLACE_MARKER
+    );
+    let user = format!("修改要求：{}\n\n文档：\n{}", instruction, content);
+    vec![
+        ChatMessage { role: "system".to_string(), content: system, images: Vec::new() },
+        ChatMessage { role: "user".to_string(), content: user, images: Vec::new() },
+    ]
+}
+
+/// 解析 SEARCH/REPLACE 修改块
+fn parse_search_replace(output: &str) -> Result<Vec<EditBlock>, String> {
+    let mut blocks = Vec::new();
+    let mut lines = output.lines();
+    while let Some(line) = lines.next() {
+        if line.trim_end() != SEARCH_MARKER {
+            continue;
+        }
+        let mut search = Vec::new();
+        let mut replace = Vec::new();
+        let mut in_replace = false;
+        let mut closed = false;
+        for line in lines.by_ref() {
+            match line.trim_end() {
+                DIVIDER if !in_replace => in_replace = true,
+                REPLACE_MARKER if in_replace => {
+                    closed = true;
+                    break;
+                }
+                _ if in_replace => replace.push(line),
+                _ => search.push(line),
+            }
+        }
+        if !closed {
+            return Err(format!("第 {} 个修改块不完整", blocks.len() + 1));
+        }
+        blocks.push(EditBlock { search: search.join("\n"), replace: replace.join("\n") });
+    }
+    Ok(blocks)
+}
+
+/// 解析 unified diff：每个 @@ 片段转为一个修改块（上下文行和删除行为原文，上下文行和新增行为新内容）
+fn parse_unified_diff(output: &str) -> Vec<EditBlock> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(Vec<&str>, Vec<&str>)> = None;
+    for line in output.lines() {
+        if line.starts_with("@@") {
+            if let Some((search, replace)) = current.take() {
+                blocks.push(EditBlock { search: search.join("\n"), replace: replace.join("\n") });
+            }
+            current = Some((Vec::new(), Vec::new()));
+            continue;
+        }
+        let Some((search, replace)) = current.as_mut() else { continue };
+        if line.starts_with("---") || line.starts_with("+++") || line.starts_with("```") {
+            continue;
+        }
+        match line.chars().next() {
+            Some('-') => search.push(&line[1..]),
+            Some('+') => replace.push(&line[1..]),
+            Some(' ') => {
+                search.push(&line[1..]);
+                replace.push(&line[1..]);
+            }
+            None => {
+                search.push("");
+                replace.push("");
+            }
+            _ => {}
+        }
+    }
+    if let Some((search, replace)) = current {
+        blocks.push(EditBlock { search: search.join("\n"), replace: replace.join("\n") });
+    }
+    blocks
+}
+
+/// 解析模型输出：优先 SEARCH/REPLACE，其次 unified diff
+pub fn parse_blocks(output: &str) -> Result<Vec<EditBlock>, String> {
+    let blocks = parse_search_replace(output)?;
+    if !blocks.is_empty() {
+        return Ok(blocks);
+    }
+    Ok(parse_unified_diff(output))
+}
+
+fn line_of(text: &str, byte: usize) -> usize {
+    text[..byte].matches('\n').count() + 1
+}
+
+/// 应用修改块：所有块都在原文中定位，任一块找不到、出现多处或与其他块重叠时返回错误，不做部分修改
+pub fn apply(content: &str, blocks: &[EditBlock]) -> Result<(String, Vec<ChangedRange>), String> {
+    // (块序号, 原文中的起始字节, 修改块)
+    let mut located: Vec<(usize, usize, &EditBlock)> = Vec::new();
+    for (i, block) in blocks.iter().enumerate() {
+        if block.search.is_empty() {
+            if !content.is_empty() {
+                return Err(format!("第 {} 个修改块缺少要替换的原文", i + 1));
+            }
+            located.push((i, 0, block));
+            continue;
+        }
+        let mut matches = content.match_indices(&block.search);
+        let Some((start, _)) = matches.next() else {
+            return Err(format!("第 {} 个修改块的原文在文档中找不到：{}", i + 1, block.search.chars().take(60).collect::<String>()));
+        };
+        if matches.next().is_some() {
+            return Err(format!("第 {} 个修改块的原文在文档中出现多次，无法确定位置", i + 1));
+        }
+        located.push((i, start, block));
+    }
+
+    located.sort_by_key(|(_, start, _)| *start);
+    for pair in located.windows(2) {
+        let ((a, a_start, a_block), (b, b_start, _)) = (pair[0], pair[1]);
+        if b_start < a_start + a_block.search.len() || b_start == a_start {
+            return Err(format!("第 {} 个和第 {} 个修改块的原文重叠", a.min(b) + 1, a.max(b) + 1));
+        }
+    }
+
+    // 从后往前替换，前面块的位置不受影响
+    let mut text = content.to_string();
+    for (_, start, block) in located.iter().rev() {
+        text.replace_range(*start..start + block.search.len(), &block.replace);
+    }
+
+    // 新正文中的位置 = 原位置 + 之前各块的长度变化
+    let mut shift: isize = 0;
+    let mut edits: Vec<(usize, usize, EditBlock)> = Vec::new();
+    for (_, start, block) in located {
+        let new_start = (start as isize + shift) as usize;
+        edits.push((new_start, new_start + block.replace.len(), block.clone()));
+        shift += block.replace.len() as isize - block.search.len() as isize;
+    }
+
+    let changes = edits
+        .into_iter()
+        .map(|(start, end, block)| ChangedRange {
+            start_line: line_of(&text, start),
+            end_line: line_of(&text, end),
+            start: text[..start].chars().count(),
+            end: text[..end].chars().count(),
+            removed: block.search,
+            inserted: block.replace,
+        })
+        .collect();
+    Ok((text, changes))
+}
@@ -0,0 +1,444 @@
+// 本地 HTTP API：仅监听 127.0.0.1，需携带令牌，供外部脚本、Alfred/Raycast 等工具读写文档
+//
+//   GET  /api/v1/projects
+//   GET  /api/v1/projects/{项目ID}/documents
+//   POST /api/v1/projects/{项目ID}/documents            {"title", "content"}
+//   GET  /api/v1/projects/{项目ID}/documents/{文档ID}
+//   POST /api/v1/projects/{项目ID}/documents/{文档ID}/append   {"text"}（已锁定的文档返回 423）
+//   GET  /api/v1/projects/{项目ID}/search?q=关键词&content=true
+//   GET  /api/v1/projects/{项目ID}/documents/{文档ID}/export?format=docx
+//
+// 认证：Authorization: Bearer <令牌>
+
+use crate::commands::search::{search_project, SearchOptions};
+use crate::config::{is_safe_id, AppState};
+use crate::document::Document;
+use crate::project::Project;
+use crate::secrets;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fs;
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+/// 令牌在密钥存储中的键名
+const TOKEN_KEY: &str = "api-server:token";
+
+/// 请求头最大长度
+const MAX_HEADER_BYTES: usize = 64 * 1024;
+
+/// 请求体最大长度
+const MAX_BODY_BYTES: usize = 10 * 1024 * 1024;
+
+struct RunningServer {
+    port: u16,
+    shutdown: oneshot::Sender<()>,
+}
+
+static SERVER: OnceLock<Mutex<Option<RunningServer>>> = OnceLock::new();
+
+fn server() -> &'static Mutex<Option<RunningServer>> {
+    SERVER.get_or_init(|| Mutex::new(None))
+}
+
+/// 当前监听的端口（未运行时为 None）
+pub fn running_port() -> Option<u16> {
+    server().lock().ok().and_then(|s| s.as_ref().map(|r| r.port))
+}
+
+/// 按设置启动、重启或停止服务
+pub async fn apply(app: &AppHandle, enabled: bool, port: u16) -> Result<(), String> {
+    if running_port() == Some(port) && enabled {
+        return Ok(());
+    }
+    stop();
+    if !enabled {
+        return Ok(());
+    }
+    ensure_token()?;
+
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .await
+        .map_err(|e| format!("本地 API 端口 {} 监听失败: {}", port, e))?;
+    let (tx, mut rx) = oneshot::channel();
+    if let Ok(mut guard) = server().lock() {
+        *guard = Some(RunningServer { port, shutdown: tx });
+    }
+    tracing::info!("本地 API 已启动: http://127.0.0.1:{}", port);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = &mut rx => break,
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => {
+                        let app = app.clone();
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(e) = handle_connection(&app, stream).await {
+                                tracing::debug!("本地 API 连接处理失败: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => tracing::warn!("本地 API 接受连接失败: {}", e),
+                },
+            }
+        }
+        tracing::info!("本地 API 已停止");
+    });
+    Ok(())
+}
+
+pub fn stop() {
+    if let Some(running) = server().lock().ok().and_then(|mut s| s.take()) {
+        let _ = running.shutdown.send(());
+    }
+}
+
+/// 启动服务时确保令牌存在，缺失时生成
+fn ensure_token() -> Result<String, String> {
+    match secrets::get(TOKEN_KEY)? {
+        Some(token) if !token.is_empty() => Ok(token),
+        _ => regenerate_token(),
+    }
+}
+
+/// 生成新令牌（旧令牌立即失效），返回明文供用户配置外部工具
+pub fn regenerate_token() -> Result<String, String> {
+    let token = format!("adp_{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+    secrets::store(TOKEN_KEY, &token, Some("本地 API 令牌".to_string()))?;
+    Ok(token)
+}
+
+pub fn masked_token() -> Option<String> {
+    secrets::entry(TOKEN_KEY).map(|e| e.masked)
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+
+    fn query(&self, name: &str) -> Option<&str> {
+        self.query.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    fn json<T: for<'de> Deserialize<'de>>(&self) -> Result<T, Response> {
+        serde_json::from_slice(&self.body).map_err(|e| Response::error(400, &format!("请求体格式错误: {}", e)))
+    }
+}
+
+struct Response {
+    status: u16,
+    content_type: String,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn json(status: u16, value: Value) -> Self {
+        Self { status, content_type: "application/json; charset=utf-8".to_string(), body: value.to_string().into_bytes() }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::json(status, json!({ "error": message }))
+    }
+}
+
+async fn handle_connection(app: &AppHandle, stream: TcpStream) -> Result<(), String> {
+    let mut reader = BufReader::new(stream);
+    let response = match read_request(&mut reader).await {
+        Ok(request) => route(app, request).await.unwrap_or_else(|e| e),
+        Err(e) => Response::error(400, &e),
+    };
+    let reason = match response.status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        413 => "Payload Too Large",
+        423 => "Locked",
+        501 => "Not Implemented",
+        _ => "Internal Server Error",
+    };
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason,
+        response.content_type,
+        response.body.len()
+    );
+    let stream = reader.get_mut();
+    stream.write_all(head.as_bytes()).await.map_err(|e| e.to_string())?;
+    stream.write_all(&response.body).await.map_err(|e| e.to_string())?;
+    stream.shutdown().await.map_err(|e| e.to_string())
+}
+
+async fn read_request(reader: &mut BufReader<TcpStream>) -> Result<Request, String> {
+    let mut head = Vec::new();
+    let mut byte = [0u8; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        if reader.read(&mut byte).await.map_err(|e| e.to_string())? == 0 {
+            return Err("连接已关闭".to_string());
+        }
+        head.push(byte[0]);
+        if head.len() > MAX_HEADER_BYTES {
+            return Err("请求头过大".to_string());
+        }
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_uppercase();
+    let target = request_line.next().unwrap_or_default();
+
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|l| l.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect();
+    let content_length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > MAX_BODY_BYTES {
+        return Err("请求体过大".to_string());
+    }
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).await.map_err(|e| e.to_string())?;
+
+    let url = reqwest::Url::parse(&format!("http://localhost{}", target)).map_err(|_| "无效的请求路径".to_string())?;
+    Ok(Request {
+        method,
+        path: url.path().to_string(),
+        query: url.query_pairs().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        headers,
+        body,
+    })
+}
+
+/// 防止 DNS 重绑定：只接受 Host 为本机的请求
+fn is_local_host(host: Option<&str>) -> bool {
+    let host = host.unwrap_or_default();
+    let name = host.rsplit_once(':').map_or(host, |(h, _)| h);
+    matches!(name, "127.0.0.1" | "localhost" | "[::1]")
+}
+
+fn authorized(request: &Request, token: &str) -> bool {
+    let provided = request
+        .header("Authorization")
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or_default()
+        .trim();
+    // 逐字节比较全部内容，避免按耗时推测令牌
+    provided.len() == token.len()
+        && provided.bytes().zip(token.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+#[derive(Deserialize)]
+struct CreateDocumentBody {
+    title: String,
+    #[serde(default)]
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct AppendBody {
+    text: String,
+}
+
+async fn route(app: &AppHandle, request: Request) -> Result<Response, Response> {
+    if !is_local_host(request.header("Host")) {
+        return Err(Response::error(403, "只允许本机访问"));
+    }
+    // 令牌只在启动服务时创建，这里只读取缓存，读不到时拒绝而不是重新生成
+    let token = match secrets::get_cached(TOKEN_KEY) {
+        Ok(Some(token)) if !token.is_empty() => token,
+        Ok(_) => return Err(Response::error(500, "本地 API 令牌不存在，请在设置中重新生成")),
+        Err(e) => return Err(Response::error(500, &e)),
+    };
+    if !authorized(&request, &token) {
+        return Err(Response::error(401, "令牌无效"));
+    }
+
+    let state = app.state::<AppState>().inner().clone();
+    let app = app.clone();
+
+    crate::error::run_blocking(move || -> Result<Result<Response, Response>, String> {
+        let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+        Ok(match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["api", "v1", "projects"]) => list_projects(&state),
+            ("GET", ["api", "v1", "projects", pid, "documents"]) => list_documents(&state, pid),
+            ("POST", ["api", "v1", "projects", pid, "documents"]) => {
+                request.json::<CreateDocumentBody>().and_then(|body| create_document(&app, &state, pid, body))
+            }
+            ("GET", ["api", "v1", "projects", pid, "documents", did]) => load_document(&state, pid, did)
+                .map(|doc| Response::json(200, serde_json::to_value(doc).unwrap_or_default())),
+            ("POST", ["api", "v1", "projects", pid, "documents", did, "append"]) => {
+                request.json::<AppendBody>().and_then(|body| append_document(&app, &state, pid, did, body))
+            }
+            ("GET", ["api", "v1", "projects", pid, "search"]) => search(&state, pid, &request),
+            ("GET", ["api", "v1", "projects", pid, "documents", did, "export"]) => {
+                export_document(&app, &state, pid, did, request.query("format").unwrap_or("md"))
+            }
+            _ => Err(Response::error(404, "接口不存在")),
+        })
+    })
+    .await
+    .map_err(|e| Response::error(500, &e))?
+}
+
+fn list_projects(state: &AppState) -> Result<Response, Response> {
+    let entries = fs::read_dir(&state.config.projects_dir).map_err(|e| Response::error(500, &e.to_string()))?;
+    let mut projects: Vec<Project> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("json"))
+        .filter_map(|p| fs::read_to_string(p).ok())
+        .filter_map(|json| serde_json::from_str(&json).ok())
+        .collect();
+    projects.sort_by_key(|p| std::cmp::Reverse(p.updated_at));
+    let items: Vec<Value> = projects
+        .iter()
+        .map(|p| json!({ "id": p.id, "name": p.name, "updatedAt": p.updated_at }))
+        .collect();
+    Ok(Response::json(200, json!(items)))
+}
+
+fn list_documents(state: &AppState, project_id: &str) -> Result<Response, Response> {
+    ensure_project(state, project_id)?;
+    let docs_dir = state.config.projects_dir.join(project_id).join("documents");
+    let mut documents: Vec<Document> = fs::read_dir(&docs_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("json"))
+                .filter_map(|p| Document::load(&p).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    documents.sort_by_key(|d| std::cmp::Reverse(d.metadata.updated_at));
+    let items: Vec<Value> = documents
+        .iter()
+        .map(|d| json!({ "id": d.id, "title": d.title, "updatedAt": d.metadata.updated_at, "wordCount": d.metadata.word_count }))
+        .collect();
+    Ok(Response::json(200, json!(items)))
+}
+
+fn ensure_project(state: &AppState, project_id: &str) -> Result<(), Response> {
+    if !is_safe_id(project_id) || !state.get_project_path(project_id).exists() {
+        return Err(Response::error(404, &crate::i18n::tf("error.project_not_found", &[&project_id])));
+    }
+    Ok(())
+}
+
+fn load_document(state: &AppState, project_id: &str, document_id: &str) -> Result<Document, Response> {
+    ensure_project(state, project_id)?;
+    let path = state.get_document_path(project_id, document_id);
+    if !is_safe_id(document_id) || !path.exists() {
+        return Err(Response::error(404, &crate::i18n::tf("error.document_not_found", &[&document_id])));
+    }
+    Document::load(&path).map_err(|e| Response::error(500, &e.to_string()))
+}
+
+fn create_document(app: &AppHandle, state: &AppState, project_id: &str, body: CreateDocumentBody) -> Result<Response, Response> {
+    ensure_project(state, project_id)?;
+    let title = body.title.trim();
+    if title.is_empty() {
+        return Err(Response::error(400, "标题不能为空"));
+    }
+    let mut document = Document::new(project_id.to_string(), title.to_string(), String::new());
+    document.metadata.word_count = body.content.split_whitespace().count();
+    document.metadata.character_count = body.content.chars().count();
+    document.content = body.content;
+    save_and_notify(app, state, &document)?;
+    Ok(Response::json(201, serde_json::to_value(&document).unwrap_or_default()))
+}
+
+fn append_document(app: &AppHandle, state: &AppState, project_id: &str, document_id: &str, body: AppendBody) -> Result<Response, Response> {
+    let mut document = load_document(state, project_id, document_id)?;
+    document.ensure_unlocked().map_err(|e| Response::error(423, &e.localized()))?;
+    if document.content.trim().is_empty() {
+        document.content = body.text;
+    } else {
+        document.content = format!("{}\n\n{}", document.content.trim_end(), body.text);
+    }
+    document.metadata.updated_at = chrono::Utc::now().timestamp();
+    document.metadata.word_count = document.content.split_whitespace().count();
+    document.metadata.character_count = document.content.chars().count();
+    save_and_notify(app, state, &document)?;
+    Ok(Response::json(200, serde_json::to_value(&document).unwrap_or_default()))
+}
+
+fn save_and_notify(app: &AppHandle, state: &AppState, document: &Document) -> Result<(), Response> {
+    document
+        .save(&state.get_document_path(&document.project_id, &document.id))
+        .map_err(|e| Response::error(500, &e.to_string()))?;
+    let _ = app.emit("api:document-changed", json!({ "projectId": document.project_id, "documentId": document.id }));
+    Ok(())
+}
+
+fn search(state: &AppState, project_id: &str, request: &Request) -> Result<Response, Response> {
+    ensure_project(state, project_id)?;
+    let query = request.query("q").filter(|q| !q.trim().is_empty()).ok_or_else(|| Response::error(400, "缺少 q 参数"))?;
+    let options = SearchOptions {
+        query: query.to_string(),
+        search_content: request.query("content") != Some("false"),
+        match_case: request.query("case") == Some("true"),
+        match_whole_word: false,
+        use_regex: false,
+        limit: request.query("limit").and_then(|l| l.parse().ok()),
+    };
+    let results = search_project(state, project_id, &options).map_err(|e| Response::error(400, &e))?;
+    Ok(Response::json(200, serde_json::to_value(results).unwrap_or_default()))
+}
+
+fn export_document(app: &AppHandle, state: &AppState, project_id: &str, document_id: &str, format: &str) -> Result<Response, Response> {
+    let content_type = match format {
+        "md" => "text/markdown; charset=utf-8",
+        "html" => "text/html; charset=utf-8",
+        "txt" => "text/plain; charset=utf-8",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "pdf" => "application/pdf",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        _ => return Err(Response::error(400, &format!("不支持的导出格式: {}", format))),
+    };
+    let document = load_document(state, project_id, document_id)?;
+    let output = std::env::temp_dir()
+        .join("aidocplus_api")
+        .join(format!("{}.{}", document.id, format));
+    let output_str = output.to_string_lossy().to_string();
+    let written = crate::native_export::export_native(
+        &document.content,
+        &document.title,
+        &output_str,
+        format,
+        &crate::native_export::ExportOptions {
+            info: crate::commands::export::document_info(&document),
+            ..crate::commands::export::export_options(app, format)
+        },
+    )
+        .map_err(|e| Response::error(500, &e))?;
+    // 找不到浏览器时 PDF 导出退回为打印页 HTML，不能当作 PDF 返回
+    if written != output_str {
+        let _ = fs::remove_file(&written);
+        return Err(Response::error(501, "未找到可用于生成 PDF 的浏览器（Chrome / Edge / Chromium）"));
+    }
+    let body = fs::read(&written).map_err(|e| Response::error(500, &e.to_string()))?;
+    let _ = fs::remove_file(&written);
+    Ok(Response { status: 200, content_type: content_type.to_string(), body })
+}
@@ -0,0 +1,100 @@
+// AI 自动标签：让模型从项目已有的标签中为文档挑选 3–7 个，结果以 JSON 返回
+// 项目还没有标签（或允许新标签）时，模型可以提出新标签
+
+use crate::ai::ChatMessage;
+use crate::document::Document;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+pub const MIN_TAGS: usize = 3;
+pub const MAX_TAGS: usize = 7;
+/// 发送给模型的正文上限（字符）
+const MAX_CONTENT_CHARS: usize = 6000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoTagResult {
+    pub tags: Vec<String>,
+    /// 不在项目已有标签中的新标签
+    pub new_tags: Vec<String>,
+    /// 是否已写入文档
+    pub applied: bool,
+}
+
+/// 项目中已使用的标签，按使用次数从多到少排列
+pub fn project_tags(docs_dir: &Path) -> Vec<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let Ok(entries) = std::fs::read_dir(docs_dir) else { return Vec::new() };
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        if let Ok(document) = Document::load_meta(&path) {
+            for tag in document.metadata.tags {
+                *counts.entry(tag).or_default() += 1;
+            }
+        }
+    }
+    let mut tags: Vec<(String, usize)> = counts.into_iter().collect();
+    tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    tags.into_iter().map(|(tag, _)| tag).collect()
+}
+
+/// 构造请求消息
+pub fn build_messages(document: &Document, vocabulary: &[String], allow_new: bool) -> Vec<ChatMessage> {
+    let rule = if vocabulary.is_empty() {
+        format!("为文档拟定 {}–{} 个简短的标签。", MIN_TAGS, MAX_TAGS)
+    } else if allow_new {
+        format!(
+            "优先从已有标签中选择 {}–{} 个，确有必要时可以补充新标签。\n已有标签：{}",
+            MIN_TAGS,
+            MAX_TAGS,
+            vocabulary.join("、")
+        )
+    } else {
+        format!("只能从已有标签中选择 {}–{} 个，不要创造新标签。\n已有标签：{}", MIN_TAGS, MAX_TAGS, vocabulary.join("、"))
+    };
+    let system = format!(
+        "你是文档分类助手。{}\n只输出 JSON 对象，格式为 {{\"tags\": [\"标签1\", \"标签2\"]}}，不要输出其他内容。",
+        rule
+    );
+
+    let body = if document.ai_generated_content.trim().is_empty() { &document.content } else { &document.ai_generated_content };
+    let body: String = body.chars().take(MAX_CONTENT_CHARS).collect();
+    let user = format!("标题：{}\n\n正文：\n{}", document.title, body);
+    vec![
+        ChatMessage { role: "system".to_string(), content: system, images: Vec::new() },
+        ChatMessage { role: "user".to_string(), content: user, images: Vec::new() },
+    ]
+}
+
+/// 解析模型返回的标签；不允许新标签时丢弃词表外的标签（大小写不敏感地对齐到已有写法）
+pub fn parse_tags(response: &str, vocabulary: &[String], allow_new: bool) -> Result<Vec<String>, String> {
+    // 部分模型不支持 JSON 模式，会用代码块包裹或附带说明文字
+    let start = response.find('{').ok_or("AI 未返回有效的标签 JSON")?;
+    let end = response.rfind('}').filter(|&e| e > start).ok_or("AI 未返回有效的标签 JSON")?;
+    let value: serde_json::Value =
+        serde_json::from_str(&response[start..=end]).map_err(|e| format!("解析标签失败: {}", e))?;
+    let raw = value.get("tags").and_then(|t| t.as_array()).ok_or("AI 返回的 JSON 中没有 tags")?;
+
+    let mut tags: Vec<String> = Vec::new();
+    for tag in raw.iter().filter_map(|t| t.as_str()).map(|t| t.trim().trim_start_matches('#').trim()) {
+        if tag.is_empty() {
+            continue;
+        }
+        let tag = match vocabulary.iter().find(|v| v.to_lowercase() == tag.to_lowercase()) {
+            Some(existing) => existing.clone(),
+            None if allow_new || vocabulary.is_empty() => tag.to_string(),
+            None => continue,
+        };
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags.truncate(MAX_TAGS);
+    if tags.is_empty() {
+        return Err("AI 未给出可用的标签".to_string());
+    }
+    Ok(tags)
+}
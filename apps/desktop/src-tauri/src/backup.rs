@@ -0,0 +1,173 @@
+// 全量应用数据备份 / 迁移
+// 一个 ZIP 包含项目、模板、插件、资源、设置和统计数据，根目录 manifest.json 描述内容；
+// 密钥在系统钥匙串中，不随备份迁移，需要在新机器上重新填写
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const MANIFEST_FILE: &str = "manifest.json";
+const FORMAT: &str = "aidocplus-backup";
+const FORMAT_VERSION: u32 = 1;
+
+/// 不备份的文件：日志、崩溃报告、可重建的资源索引、仅含钥匙串索引的密钥列表
+const EXCLUDED_DIRS: &[&str] = &["logs", "crashes"];
+const EXCLUDED_FILES: &[&str] = &["index.db", "index.db-wal", "index.db-shm", "secrets-index.json"];
+
+/// 备份的一个数据目录，name 即 ZIP 中的顶层目录名
+pub struct Section {
+    pub name: &'static str,
+    pub dir: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionSummary {
+    pub name: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    pub format: String,
+    pub format_version: u32,
+    pub app_version: String,
+    pub platform: String,
+    pub created_at: i64,
+    pub sections: Vec<SectionSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    pub manifest: BackupManifest,
+    pub imported: usize,
+    /// 目标已存在且未选择覆盖而跳过的文件
+    pub skipped: usize,
+}
+
+fn is_excluded(path: &Path) -> bool {
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    if path.is_dir() {
+        EXCLUDED_DIRS.contains(&name.as_ref())
+    } else {
+        EXCLUDED_FILES.contains(&name.as_ref())
+    }
+}
+
+/// 导出全部数据到 output_path
+pub fn export(sections: &[Section], app_version: &str, output_path: &Path) -> Result<BackupManifest, String> {
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建输出目录失败: {}", e))?;
+    }
+    let file = fs::File::create(output_path).map_err(|e| format!("创建备份文件失败: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::FileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .large_file(true);
+
+    let mut summaries = Vec::new();
+    for section in sections {
+        let mut summary = SectionSummary { name: section.name.to_string(), files: 0, bytes: 0 };
+        if section.dir.is_dir() {
+            add_dir(&mut zip, &section.dir, section.name, options, &mut summary)?;
+        }
+        summaries.push(summary);
+    }
+
+    let manifest = BackupManifest {
+        format: FORMAT.to_string(),
+        format_version: FORMAT_VERSION,
+        app_version: app_version.to_string(),
+        platform: std::env::consts::OS.to_string(),
+        created_at: chrono::Utc::now().timestamp(),
+        sections: summaries,
+    };
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    zip.start_file(MANIFEST_FILE, options).map_err(|e| format!("ZIP 写入失败: {}", e))?;
+    zip.write_all(json.as_bytes()).map_err(|e| format!("ZIP 写入失败: {}", e))?;
+    zip.finish().map_err(|e| format!("ZIP 完成失败: {}", e))?;
+    Ok(manifest)
+}
+
+fn add_dir(
+    zip: &mut zip::ZipWriter<fs::File>,
+    dir: &Path,
+    prefix: &str,
+    options: zip::write::FileOptions,
+    summary: &mut SectionSummary,
+) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("读取目录失败: {}", e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if is_excluded(&path) || path.is_symlink() {
+            continue;
+        }
+        let zip_path = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+        if path.is_dir() {
+            add_dir(zip, &path, &zip_path, options, summary)?;
+        } else {
+            let mut src = fs::File::open(&path).map_err(|e| format!("读取文件失败: {}", e))?;
+            zip.start_file(&zip_path, options).map_err(|e| format!("ZIP 写入失败: {}", e))?;
+            summary.bytes += io::copy(&mut src, zip).map_err(|e| format!("ZIP 写入失败: {}", e))?;
+            summary.files += 1;
+        }
+    }
+    Ok(())
+}
+
+/// 读取备份清单（导入前预览）
+pub fn read_manifest(path: &Path) -> Result<BackupManifest, String> {
+    let file = fs::File::open(path).map_err(|e| format!("打开备份文件失败: {}", e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("解析备份文件失败: {}", e))?;
+    manifest_of(&mut archive)
+}
+
+fn manifest_of(archive: &mut zip::ZipArchive<fs::File>) -> Result<BackupManifest, String> {
+    let entry = archive.by_name(MANIFEST_FILE).map_err(|_| "备份中缺少 manifest.json，不是有效的备份文件".to_string())?;
+    let manifest: BackupManifest = serde_json::from_reader(entry).map_err(|e| format!("解析备份清单失败: {}", e))?;
+    if manifest.format != FORMAT {
+        return Err("不是有效的 AiDocPlus 备份文件".to_string());
+    }
+    if manifest.format_version > FORMAT_VERSION {
+        return Err(format!("备份格式版本 {} 过新，请先升级应用", manifest.format_version));
+    }
+    Ok(manifest)
+}
+
+/// 从备份恢复；overwrite 为 false 时保留已存在的文件
+pub fn import(sections: &[Section], path: &Path, overwrite: bool) -> Result<ImportSummary, String> {
+    let file = fs::File::open(path).map_err(|e| format!("打开备份文件失败: {}", e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("解析备份文件失败: {}", e))?;
+    let manifest = manifest_of(&mut archive)?;
+
+    let mut imported = 0;
+    let mut skipped = 0;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        if entry.is_dir() {
+            continue;
+        }
+        // enclosed_name 拒绝绝对路径和 ..，防止写出目标目录
+        let Some(relative) = entry.enclosed_name().map(Path::to_path_buf) else { continue };
+        let mut components = relative.components();
+        let Some(top) = components.next().map(|c| c.as_os_str().to_string_lossy().to_string()) else { continue };
+        let Some(section) = sections.iter().find(|s| s.name == top) else { continue };
+
+        let target = section.dir.join(components.as_path());
+        if target.exists() && !overwrite {
+            skipped += 1;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        let mut out = fs::File::create(&target).map_err(|e| format!("写入文件失败: {}", e))?;
+        io::copy(&mut entry, &mut out).map_err(|e| format!("写入文件失败: {}", e))?;
+        imported += 1;
+    }
+    Ok(ImportSummary { manifest, imported, skipped })
+}
@@ -0,0 +1,284 @@
+// 批量文档操作（移动、复制、删除、打标签）：全部成功或全部回滚
+// 每次操作是一个事务，目录 Projects/.transactions/{事务ID}/ 下保存日志 journal.json 和被替换文件的备份；
+// 修改文件前先写日志，失败时按日志逆序撤销；程序中途退出时，下次启动由 recover 回滚未完成的事务
+
+use crate::config::AppState;
+use crate::document::{self, Document};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TRANSACTIONS_DIR: &str = ".transactions";
+const JOURNAL_FILE: &str = "journal.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BulkOp {
+    Move,
+    Copy,
+    Delete,
+    /// 添加标签
+    Tag,
+    /// 移除标签
+    Untag,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkResult {
+    pub affected: usize,
+    /// 操作后的文档（删除时为空；复制时为新文档）
+    pub documents: Vec<Document>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+enum JournalEntry {
+    /// 新建的文件或目录，回滚时删除
+    Created { path: PathBuf },
+    /// 原文件或目录已移到 moved_to（备份或移动目标），回滚时移回
+    Moved { original: PathBuf, moved_to: PathBuf },
+}
+
+struct Transaction {
+    dir: PathBuf,
+    entries: Vec<JournalEntry>,
+}
+
+impl Transaction {
+    fn begin(projects_dir: &Path) -> Result<Self, String> {
+        let dir = projects_dir.join(TRANSACTIONS_DIR).join(uuid::Uuid::new_v4().to_string());
+        fs::create_dir_all(&dir).map_err(|e| format!("创建事务目录失败: {}", e))?;
+        let tx = Transaction { dir, entries: Vec::new() };
+        tx.write_journal()?;
+        Ok(tx)
+    }
+
+    fn write_journal(&self) -> Result<(), String> {
+        let json = serde_json::to_vec_pretty(&self.entries).map_err(|e| e.to_string())?;
+        fs::write(self.dir.join(JOURNAL_FILE), json).map_err(|e| format!("写入事务日志失败: {}", e))
+    }
+
+    /// 先记录再操作，保证中途退出时日志覆盖所有改动
+    fn record(&mut self, entry: JournalEntry) -> Result<(), String> {
+        self.entries.push(entry);
+        self.write_journal()
+    }
+
+    /// 即将新建的文件或目录；已存在时报错，避免覆盖
+    fn create(&mut self, path: &Path) -> Result<(), String> {
+        if path.exists() {
+            return Err(format!("目标已存在: {}", path.display()));
+        }
+        self.record(JournalEntry::Created { path: path.to_path_buf() })
+    }
+
+    /// 将文件或目录移到目标位置（目标为空时移入事务备份），不存在时忽略
+    fn move_path(&mut self, original: &Path, target: Option<&Path>) -> Result<(), String> {
+        if !original.exists() {
+            return Ok(());
+        }
+        let moved_to = match target {
+            Some(t) => t.to_path_buf(),
+            None => self.dir.join(self.entries.len().to_string()),
+        };
+        self.record(JournalEntry::Moved { original: original.to_path_buf(), moved_to: moved_to.clone() })?;
+        if let Some(parent) = moved_to.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::rename(original, &moved_to).map_err(|e| format!("移动 {} 失败: {}", original.display(), e))
+    }
+
+    /// 即将被改写的文件：复制一份到备份，回滚时还原
+    fn preserve(&mut self, path: &Path) -> Result<(), String> {
+        if !path.exists() {
+            return self.record(JournalEntry::Created { path: path.to_path_buf() });
+        }
+        let backup = self.dir.join(self.entries.len().to_string());
+        fs::copy(path, &backup).map_err(|e| format!("备份 {} 失败: {}", path.display(), e))?;
+        self.record(JournalEntry::Moved { original: path.to_path_buf(), moved_to: backup })
+    }
+
+    fn commit(self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+
+    fn rollback(self) -> Result<(), String> {
+        undo(&self.entries)?;
+        let _ = fs::remove_dir_all(&self.dir);
+        Ok(())
+    }
+}
+
+fn undo(entries: &[JournalEntry]) -> Result<(), String> {
+    let mut errors = Vec::new();
+    for entry in entries.iter().rev() {
+        let result = match entry {
+            JournalEntry::Created { path } if path.is_dir() => fs::remove_dir_all(path),
+            JournalEntry::Created { path } if path.exists() => fs::remove_file(path),
+            JournalEntry::Created { .. } => Ok(()),
+            JournalEntry::Moved { original, moved_to } if moved_to.exists() => {
+                if original.is_file() {
+                    let _ = fs::remove_file(original);
+                }
+                fs::rename(moved_to, original)
+            }
+            JournalEntry::Moved { .. } => Ok(()),
+        };
+        if let Err(e) = result {
+            errors.push(e.to_string());
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("回滚未完全成功: {}", errors.join("; ")))
+    }
+}
+
+/// 启动时回滚上次未完成的事务，返回回滚的事务数
+pub fn recover(projects_dir: &Path) -> Result<usize, String> {
+    let Ok(entries) = fs::read_dir(projects_dir.join(TRANSACTIONS_DIR)) else { return Ok(0) };
+    let mut count = 0;
+    for dir in entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()) {
+        let journal: Vec<JournalEntry> = fs::read(dir.join(JOURNAL_FILE))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        undo(&journal)?;
+        let _ = fs::remove_dir_all(&dir);
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// 执行批量操作；任一文档失败时撤销已完成的部分
+pub fn run(
+    state: &AppState,
+    project_id: &str,
+    op: BulkOp,
+    document_ids: &[String],
+    target_project_id: Option<&str>,
+    tags: &[String],
+) -> Result<BulkResult, String> {
+    let mut seen = HashSet::new();
+    let ids: Vec<&String> = document_ids.iter().filter(|id| seen.insert(id.as_str())).collect();
+
+    // 先整体校验，尽量在改动任何文件之前发现问题
+    let target = match op {
+        BulkOp::Move | BulkOp::Copy => {
+            let target = target_project_id.unwrap_or(project_id);
+            if op == BulkOp::Move && target == project_id {
+                return Err("目标项目与源项目相同".to_string());
+            }
+            if !state.get_project_path(target).exists() {
+                return Err(format!("目标项目不存在: {}", target));
+            }
+            Some(target)
+        }
+        BulkOp::Tag | BulkOp::Untag if tags.iter().all(|t| t.trim().is_empty()) => {
+            return Err("未指定标签".to_string());
+        }
+        _ => None,
+    };
+    let mut documents = Vec::new();
+    for id in &ids {
+        let path = state.get_document_path(project_id, id);
+        let document = Document::load(&path).map_err(|_| format!("文档不存在: {}", id))?;
+        if op != BulkOp::Copy {
+            document.ensure_unlocked().map_err(|e| e.localized())?;
+        }
+        documents.push((path, document));
+    }
+
+    let mut tx = Transaction::begin(&state.config.projects_dir)?;
+    let result = apply(&mut tx, state, project_id, op, documents, target, tags);
+    match result {
+        Ok(documents) => {
+            tx.commit();
+            Ok(BulkResult { affected: ids.len(), documents })
+        }
+        Err(e) => match tx.rollback() {
+            Ok(()) => Err(format!("{}（已撤销全部更改）", e)),
+            Err(rollback_error) => Err(format!("{}；{}", e, rollback_error)),
+        },
+    }
+}
+
+fn apply(
+    tx: &mut Transaction,
+    state: &AppState,
+    project_id: &str,
+    op: BulkOp,
+    documents: Vec<(PathBuf, Document)>,
+    target: Option<&str>,
+    tags: &[String],
+) -> Result<Vec<Document>, String> {
+    let tags: Vec<&str> = tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()).collect();
+    let now = chrono::Utc::now().timestamp();
+    let mut results = Vec::new();
+
+    for (path, mut document) in documents {
+        match op {
+            BulkOp::Delete => {
+                tx.move_path(&path, None)?;
+                tx.move_path(&document::content_path(&path), None)?;
+                tx.move_path(&state.get_versions_path(project_id, &document.id), None)?;
+            }
+            BulkOp::Move => {
+                let target = target.unwrap_or(project_id);
+                let dst = state.get_document_path(target, &document.id);
+                tx.create(&dst)?;
+                tx.create(&document::content_path(&dst))?;
+                document.project_id = target.to_string();
+                document.metadata.updated_at = now;
+                document.save(&dst).map_err(|e| e.to_string())?;
+                tx.move_path(&path, None)?;
+                tx.move_path(&document::content_path(&path), None)?;
+                let versions = state.get_versions_path(target, &document.id);
+                if versions.exists() {
+                    return Err(format!("目标项目中已有该文档的版本历史: {}", document.title));
+                }
+                tx.move_path(&state.get_versions_path(project_id, &document.id), Some(&versions))?;
+                results.push(document);
+            }
+            BulkOp::Copy => {
+                // 与 copy_document 一致：新 ID，不复制版本历史
+                let target = target.unwrap_or(project_id);
+                document.id = uuid::Uuid::new_v4().to_string();
+                document.project_id = target.to_string();
+                document.title = format!("{} (副本)", document.title);
+                document.metadata.created_at = now;
+                document.metadata.updated_at = now;
+                document.versions = Vec::new();
+                document.current_version_id = String::new();
+                let dst = state.get_document_path(target, &document.id);
+                tx.create(&dst)?;
+                tx.create(&document::content_path(&dst))?;
+                document.save(&dst).map_err(|e| e.to_string())?;
+                results.push(document);
+            }
+            BulkOp::Tag | BulkOp::Untag => {
+                let before = document.metadata.tags.clone();
+                if op == BulkOp::Tag {
+                    for tag in &tags {
+                        if !document.metadata.tags.iter().any(|t| t == tag) {
+                            document.metadata.tags.push(tag.to_string());
+                        }
+                    }
+                } else {
+                    document.metadata.tags.retain(|t| !tags.contains(&t.as_str()));
+                }
+                if document.metadata.tags != before {
+                    tx.preserve(&path)?;
+                    tx.preserve(&document::content_path(&path))?;
+                    document.metadata.updated_at = now;
+                    document.save(&path).map_err(|e| e.to_string())?;
+                }
+                results.push(document);
+            }
+        }
+    }
+    Ok(results)
+}
@@ -0,0 +1,182 @@
+// 内置资源本地化
+// 安装包中的 bundled-resources 只读，首次启动（及每次应用升级后）复制到 Resources/{type}/builtin 供用户修改。
+// 每个内置文件的上次版本保存在 Resources/{type}/.bundled-base，升级时据此三方合并：
+// 用户未改的文件直接更新，用户改过而内置未变的保留用户版本，两边都改的 JSON 按字段合并，冲突时保留用户版本
+
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 需要本地化的资源类型目录
+pub const BUNDLED_TYPES: &[&str] = &["roles", "prompt-templates", "document-templates", "project-templates", "ai-providers"];
+
+/// 记录上次本地化时的应用版本
+const VERSION_MARKER: &str = ".bundled-version";
+const BASE_DIR: &str = ".bundled-base";
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaterializeReport {
+    /// 新复制的文件
+    pub copied: usize,
+    /// 用户未修改、直接更新为新内置版本的文件
+    pub updated: usize,
+    /// 双方都有修改并自动合并的 JSON 文件
+    pub merged: usize,
+    /// 无法自动合并、保留了用户版本的文件（相对 Resources 的路径）
+    pub conflicts: Vec<String>,
+}
+
+/// 安装包内置资源目录（可执行文件旁的 bundled-resources）
+pub fn bundled_dir() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+        .unwrap_or_default()
+        .join("bundled-resources")
+}
+
+/// 当前版本是否已经本地化过
+pub fn is_materialized(resources_root: &Path, app_version: &str) -> bool {
+    fs::read_to_string(resources_root.join(VERSION_MARKER)).is_ok_and(|v| v.trim() == app_version)
+}
+
+/// 将内置资源复制 / 合并到本地；reset 为 true 时丢弃用户修改，恢复为内置版本
+pub fn materialize(bundled: &Path, resources_root: &Path, app_version: &str, reset: bool) -> Result<MaterializeReport, String> {
+    let mut report = MaterializeReport::default();
+    if !bundled.is_dir() {
+        return Ok(report);
+    }
+    for type_name in BUNDLED_TYPES {
+        let type_dir = bundled.join(type_name);
+        let Ok(entries) = fs::read_dir(&type_dir) else { continue };
+        for entry in entries.flatten() {
+            let src = entry.path();
+            if !src.join("manifest.json").is_file() {
+                continue;
+            }
+            let name = entry.file_name();
+            let local = resources_root.join(type_name).join("builtin").join(&name);
+            let base = resources_root.join(type_name).join(BASE_DIR).join(&name);
+            for rel in list_files(&src) {
+                let display = Path::new(type_name).join("builtin").join(&name).join(&rel);
+                sync_file(&src.join(&rel), &local.join(&rel), &base.join(&rel), reset, &display, &mut report)?;
+            }
+        }
+    }
+    fs::create_dir_all(resources_root).map_err(|e| format!("创建资源目录失败: {}", e))?;
+    fs::write(resources_root.join(VERSION_MARKER), app_version).map_err(|e| format!("写入资源版本失败: {}", e))?;
+    Ok(report)
+}
+
+/// 目录下所有文件的相对路径
+fn list_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut stack = vec![PathBuf::new()];
+    while let Some(rel) = stack.pop() {
+        let Ok(entries) = fs::read_dir(dir.join(&rel)) else { continue };
+        for entry in entries.flatten() {
+            let child = rel.join(entry.file_name());
+            if entry.path().is_dir() {
+                stack.push(child);
+            } else {
+                files.push(child);
+            }
+        }
+    }
+    files
+}
+
+fn sync_file(
+    incoming: &Path,
+    local: &Path,
+    base: &Path,
+    reset: bool,
+    display: &Path,
+    report: &mut MaterializeReport,
+) -> Result<(), String> {
+    let incoming_bytes = fs::read(incoming).map_err(|e| format!("读取内置资源失败: {}", e))?;
+    let local_bytes = fs::read(local).ok();
+    let base_bytes = fs::read(base).ok();
+
+    let output = match (&local_bytes, &base_bytes) {
+        _ if reset => Some(incoming_bytes.clone()),
+        (None, Some(base)) if *base == incoming_bytes => None, // 用户删除了该文件，且内置版本未变
+        (None, _) => {
+            report.copied += 1;
+            Some(incoming_bytes.clone())
+        }
+        (Some(local), _) if *local == incoming_bytes => None,
+        (Some(local), Some(base)) if local == base => {
+            report.updated += 1;
+            Some(incoming_bytes.clone())
+        }
+        (Some(_), Some(base)) if *base == incoming_bytes => None, // 仅用户修改过
+        (Some(local), base) => match merge_json(base.as_deref(), local, &incoming_bytes) {
+            Some(merged) => {
+                report.merged += 1;
+                Some(merged)
+            }
+            None => {
+                report.conflicts.push(display.to_string_lossy().replace('\\', "/"));
+                None
+            }
+        },
+    };
+
+    if let Some(bytes) = output {
+        write_file(local, &bytes)?;
+    }
+    // 基线始终记录最近一次的内置版本，下次升级以此为准
+    if base_bytes.as_deref() != Some(incoming_bytes.as_slice()) {
+        write_file(base, &incoming_bytes)?;
+    }
+    Ok(())
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    fs::write(path, bytes).map_err(|e| format!("写入资源失败: {}", e))
+}
+
+/// JSON 文件按字段三方合并；非 JSON 或存在冲突时返回 None
+fn merge_json(base: Option<&[u8]>, local: &[u8], incoming: &[u8]) -> Option<Vec<u8>> {
+    let base: Value = serde_json::from_slice(base?).ok()?;
+    let local: Value = serde_json::from_slice(local).ok()?;
+    let incoming: Value = serde_json::from_slice(incoming).ok()?;
+    let mut conflict = false;
+    let merged = merge3(Some(&base), Some(&local), Some(&incoming), &mut conflict);
+    if conflict {
+        return None;
+    }
+    serde_json::to_vec_pretty(&merged?).ok()
+}
+
+fn merge3(base: Option<&Value>, local: Option<&Value>, incoming: Option<&Value>, conflict: &mut bool) -> Option<Value> {
+    if local == base {
+        return incoming.cloned();
+    }
+    if incoming == base || incoming == local {
+        return local.cloned();
+    }
+    match (base, local, incoming) {
+        (Some(Value::Object(b)), Some(Value::Object(l)), Some(Value::Object(i))) => {
+            let mut keys: Vec<&String> = l.keys().collect();
+            keys.extend(i.keys().filter(|k| !l.contains_key(*k)));
+            let mut merged = serde_json::Map::new();
+            for key in keys {
+                if let Some(value) = merge3(b.get(key), l.get(key), i.get(key), conflict) {
+                    merged.insert(key.clone(), value);
+                }
+            }
+            Some(Value::Object(merged))
+        }
+        _ => {
+            *conflict = true;
+            local.cloned()
+        }
+    }
+}
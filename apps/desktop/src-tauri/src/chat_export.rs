@@ -0,0 +1,83 @@
+// AI 对话导出为文档
+// 将聊天面板中的对话按角色整理为 Markdown，思考过程（<think> 标签）可去除或保留为引用块
+
+use serde::Deserialize;
+
+/// 自动生成标题时最多取首条提问的字符数
+const MAX_TITLE_CHARS: usize = 30;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TranscriptMessage {
+    pub role: String,
+    pub content: String,
+    /// Unix 时间戳（秒）
+    #[serde(default)]
+    pub timestamp: Option<i64>,
+}
+
+/// 对话转为 Markdown；系统消息和空消息不导出
+pub fn to_markdown(messages: &[TranscriptMessage], strip_thinking: bool) -> String {
+    let mut sections = Vec::new();
+    for message in messages {
+        let label = match message.role.as_str() {
+            "user" => "用户",
+            "assistant" => "AI 助手",
+            _ => continue,
+        };
+        let (thinking, answer) = split_thinking(&message.content);
+        if answer.is_empty() && (strip_thinking || thinking.is_empty()) {
+            continue;
+        }
+        let heading = match message.timestamp.and_then(|ts| chrono::DateTime::from_timestamp(ts, 0)) {
+            Some(time) => format!("### {}（{}）", label, time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")),
+            None => format!("### {}", label),
+        };
+        let mut section = heading;
+        if !strip_thinking && !thinking.is_empty() {
+            let quoted: Vec<String> = thinking.lines().map(|l| format!("> {}", l).trim_end().to_string()).collect();
+            section.push_str(&format!("\n\n> **思考过程**\n>\n{}", quoted.join("\n")));
+        }
+        if !answer.is_empty() {
+            section.push_str(&format!("\n\n{}", answer));
+        }
+        sections.push(section);
+    }
+    format!("{}\n", sections.join("\n\n---\n\n"))
+}
+
+/// 拆出 <think>…</think> 中的思考过程和其余正文；流式中断时可能没有闭合标签，此时其后内容均视为思考过程
+fn split_thinking(content: &str) -> (String, String) {
+    let mut thinking = Vec::new();
+    let mut answer = String::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("<think>") {
+        answer.push_str(&rest[..start]);
+        let after = &rest[start + "<think>".len()..];
+        match after.find("</think>") {
+            Some(end) => {
+                thinking.push(after[..end].trim().to_string());
+                rest = &after[end + "</think>".len()..];
+            }
+            None => {
+                thinking.push(after.trim().to_string());
+                rest = "";
+            }
+        }
+    }
+    answer.push_str(rest);
+    let thinking: Vec<String> = thinking.into_iter().filter(|t| !t.is_empty()).collect();
+    (thinking.join("\n\n"), answer.trim().to_string())
+}
+
+/// 未指定标题时取首条提问的第一行
+pub fn default_title(messages: &[TranscriptMessage]) -> String {
+    messages
+        .iter()
+        .find(|m| m.role == "user")
+        .and_then(|m| m.content.lines().map(str::trim).find(|l| !l.is_empty()))
+        .map(|line| {
+            let title: String = line.chars().take(MAX_TITLE_CHARS).collect();
+            if line.chars().count() > MAX_TITLE_CHARS { format!("{}…", title) } else { title }
+        })
+        .unwrap_or_else(|| format!("AI 对话 {}", chrono::Local::now().format("%Y-%m-%d %H:%M")))
+}
@@ -0,0 +1,452 @@
+// 参考文献管理
+// 每个项目一份 BibTeX 文件（Projects/{项目ID}/references.bib），Pandoc 导出时直接用于 --citeproc；
+// 原生导出时把 [@key] 引用替换为顺序编号，并在文末生成 GB/T 7714 风格的参考文献列表
+
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// 项目参考文献文件名
+pub const BIB_FILE: &str = "references.bib";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reference {
+    pub key: String,
+    pub entry_type: String,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub year: String,
+    /// 期刊 / 会议 / 出版社
+    pub container: String,
+    /// 原始字段（未清理的 BibTeX 值）
+    pub fields: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub total: usize,
+}
+
+pub fn bib_path(project_dir: &Path) -> PathBuf {
+    project_dir.join(BIB_FILE)
+}
+
+// ============================================================
+// BibTeX 解析
+// ============================================================
+
+/// 解析 BibTeX 文本；支持 {..} / ".." / 数字 / @string 宏与 # 拼接，跳过 @comment 与 @preamble
+pub fn parse_bibtex(input: &str) -> Vec<Reference> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut macros: HashMap<String, String> = HashMap::new();
+    let mut refs = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i] != '@' {
+            i += 1;
+            continue;
+        }
+        i += 1;
+        let type_start = i;
+        while i < chars.len() && chars[i].is_alphanumeric() {
+            i += 1;
+        }
+        let entry_type: String = chars[type_start..i].iter().collect::<String>().to_lowercase();
+        skip_ws(&chars, &mut i);
+        let Some(&open) = chars.get(i) else { break };
+        if open != '{' && open != '(' {
+            continue;
+        }
+        let close = if open == '{' { '}' } else { ')' };
+        i += 1;
+
+        match entry_type.as_str() {
+            "comment" | "preamble" => {
+                skip_balanced(&chars, &mut i, open, close);
+            }
+            "string" => {
+                skip_ws(&chars, &mut i);
+                let name = read_ident(&chars, &mut i).to_lowercase();
+                skip_ws(&chars, &mut i);
+                if chars.get(i) == Some(&'=') {
+                    i += 1;
+                    let value = read_value(&chars, &mut i, &macros);
+                    macros.insert(name, value);
+                }
+                skip_balanced(&chars, &mut i, open, close);
+            }
+            _ => {
+                skip_ws(&chars, &mut i);
+                let key_start = i;
+                while i < chars.len() && chars[i] != ',' && chars[i] != close && !chars[i].is_whitespace() {
+                    i += 1;
+                }
+                let key: String = chars[key_start..i].iter().collect();
+                let mut fields = BTreeMap::new();
+                loop {
+                    skip_ws(&chars, &mut i);
+                    match chars.get(i) {
+                        Some(',') => i += 1,
+                        Some(c) if *c == close => {
+                            i += 1;
+                            break;
+                        }
+                        None => break,
+                        _ => {}
+                    }
+                    skip_ws(&chars, &mut i);
+                    if chars.get(i) == Some(&close) {
+                        continue;
+                    }
+                    let name = read_ident(&chars, &mut i).to_lowercase();
+                    skip_ws(&chars, &mut i);
+                    if name.is_empty() || chars.get(i) != Some(&'=') {
+                        // 格式错误：跳到下一个字段
+                        while i < chars.len() && chars[i] != ',' && chars[i] != close {
+                            i += 1;
+                        }
+                        continue;
+                    }
+                    i += 1;
+                    let value = read_value(&chars, &mut i, &macros);
+                    fields.insert(name, value);
+                }
+                if !key.is_empty() {
+                    refs.push(Reference::from_fields(key, entry_type, fields));
+                }
+            }
+        }
+    }
+    refs
+}
+
+fn skip_ws(chars: &[char], i: &mut usize) {
+    while *i < chars.len() && chars[*i].is_whitespace() {
+        *i += 1;
+    }
+}
+
+fn read_ident(chars: &[char], i: &mut usize) -> String {
+    let start = *i;
+    while *i < chars.len() && (chars[*i].is_alphanumeric() || "_-:.".contains(chars[*i])) {
+        *i += 1;
+    }
+    chars[start..*i].iter().collect()
+}
+
+/// 跳过到与已消费的开括号配对的闭括号之后
+fn skip_balanced(chars: &[char], i: &mut usize, open: char, close: char) {
+    let mut depth = 1;
+    while *i < chars.len() && depth > 0 {
+        if chars[*i] == open {
+            depth += 1;
+        } else if chars[*i] == close {
+            depth -= 1;
+        }
+        *i += 1;
+    }
+}
+
+fn read_value(chars: &[char], i: &mut usize, macros: &HashMap<String, String>) -> String {
+    let mut value = String::new();
+    loop {
+        skip_ws(chars, i);
+        match chars.get(*i) {
+            Some('{') => {
+                *i += 1;
+                let start = *i;
+                skip_balanced(chars, i, '{', '}');
+                value.extend(&chars[start..i.saturating_sub(1)]);
+            }
+            Some('"') => {
+                *i += 1;
+                let start = *i;
+                let mut depth = 0;
+                while *i < chars.len() && !(chars[*i] == '"' && depth == 0) {
+                    match chars[*i] {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    *i += 1;
+                }
+                value.extend(&chars[start..*i]);
+                *i += 1;
+            }
+            Some(_) => {
+                let ident = read_ident(chars, i);
+                if ident.is_empty() {
+                    break;
+                }
+                value.push_str(macros.get(&ident.to_lowercase()).map(String::as_str).unwrap_or(&ident));
+            }
+            None => break,
+        }
+        skip_ws(chars, i);
+        if chars.get(*i) == Some(&'#') {
+            *i += 1;
+        } else {
+            break;
+        }
+    }
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 去掉保护大括号与常见 LaTeX 转义
+fn clean(value: &str) -> String {
+    let mut out = value.replace("\\&", "&").replace("\\%", "%").replace("\\$", "$").replace("\\_", "_").replace("\\#", "#");
+    out.retain(|c| c != '{' && c != '}');
+    out.replace("--", "–").trim().to_string()
+}
+
+impl Reference {
+    fn from_fields(key: String, entry_type: String, fields: BTreeMap<String, String>) -> Self {
+        let get = |name: &str| fields.get(name).map(|v| clean(v)).unwrap_or_default();
+        let authors = fields
+            .get("author")
+            .or_else(|| fields.get("editor"))
+            .map(|a| a.split(" and ").map(clean).filter(|a| !a.is_empty()).collect())
+            .unwrap_or_default();
+        let container = ["journal", "journaltitle", "booktitle", "publisher", "school", "institution"]
+            .iter()
+            .map(|f| get(f))
+            .find(|v| !v.is_empty())
+            .unwrap_or_default();
+        let year = Some(get("year"))
+            .filter(|y| !y.is_empty())
+            .unwrap_or_else(|| get("date").chars().take(4).collect());
+        Self { title: get("title"), authors, year, container, key, entry_type, fields }
+    }
+
+    fn matches(&self, terms: &[String]) -> bool {
+        let haystack = format!(
+            "{} {} {} {} {}",
+            self.key,
+            self.title,
+            self.authors.join(" "),
+            self.year,
+            self.container
+        )
+        .to_lowercase();
+        terms.iter().all(|t| haystack.contains(t))
+    }
+}
+
+/// 序列化为 BibTeX（保留原始字段值）
+fn to_bibtex(refs: &[Reference]) -> String {
+    refs.iter()
+        .map(|r| {
+            let fields: Vec<String> = r.fields.iter().map(|(k, v)| format!("  {} = {{{}}}", k, v)).collect();
+            format!("@{}{{{},\n{}\n}}\n", r.entry_type, r.key, fields.join(",\n"))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// ============================================================
+// 项目存储
+// ============================================================
+
+pub fn load(project_dir: &Path) -> Vec<Reference> {
+    fs::read_to_string(bib_path(project_dir)).map(|s| parse_bibtex(&s)).unwrap_or_default()
+}
+
+fn save(project_dir: &Path, refs: &[Reference]) -> Result<(), String> {
+    fs::create_dir_all(project_dir).map_err(|e| e.to_string())?;
+    fs::write(bib_path(project_dir), to_bibtex(refs)).map_err(|e| format!("保存参考文献失败: {}", e))
+}
+
+/// 导入 BibTeX 文本，键相同的条目覆盖旧条目
+pub fn import(project_dir: &Path, bibtex: &str) -> Result<ImportSummary, String> {
+    let incoming = parse_bibtex(bibtex);
+    if incoming.is_empty() {
+        return Err("没有找到可导入的 BibTeX 条目".to_string());
+    }
+    let mut refs = load(project_dir);
+    let (mut added, mut updated) = (0, 0);
+    for r in incoming {
+        match refs.iter_mut().find(|e| e.key == r.key) {
+            Some(existing) => {
+                *existing = r;
+                updated += 1;
+            }
+            None => {
+                refs.push(r);
+                added += 1;
+            }
+        }
+    }
+    save(project_dir, &refs)?;
+    Ok(ImportSummary { added, updated, total: refs.len() })
+}
+
+pub fn search(project_dir: &Path, query: &str) -> Vec<Reference> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    load(project_dir).into_iter().filter(|r| r.matches(&terms)).collect()
+}
+
+pub fn delete(project_dir: &Path, key: &str) -> Result<(), String> {
+    let mut refs = load(project_dir);
+    let before = refs.len();
+    refs.retain(|r| r.key != key);
+    if refs.len() == before {
+        return Err(format!("参考文献不存在: {}", key));
+    }
+    save(project_dir, &refs)
+}
+
+/// 生成插入正文的引用标记，如 [@a; @b, p. 12]
+pub fn cite(project_dir: &Path, keys: &[String], locator: Option<&str>) -> Result<String, String> {
+    if keys.is_empty() {
+        return Err("请选择要引用的文献".to_string());
+    }
+    let refs = load(project_dir);
+    if let Some(missing) = keys.iter().find(|k| !refs.iter().any(|r| &r.key == *k)) {
+        return Err(format!("参考文献不存在: {}", missing));
+    }
+    let mut citation = keys.iter().map(|k| format!("@{}", k)).collect::<Vec<_>>().join("; ");
+    if let Some(loc) = locator.map(str::trim).filter(|l| !l.is_empty()) {
+        citation = format!("{}, {}", citation, loc);
+    }
+    Ok(format!("[{}]", citation))
+}
+
+// ============================================================
+// 原生导出渲染
+// ============================================================
+
+fn citation_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\[((?:-?@[\w:.#$%&+?<>~/-]+[^\]@;]*;?\s*)+)\]").expect("invalid citation regex"))
+}
+
+fn key_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^-?@([\w:.#$%&+?<>~/-]+?)[.,]?(?:,\s*(.*))?$").expect("invalid key regex"))
+}
+
+pub fn has_citations(markdown: &str) -> bool {
+    citation_re().is_match(markdown)
+}
+
+/// 把 [@key] 替换为 [n]，并在文末追加参考文献列表；未知的键保持原样
+pub fn render(markdown: &str, refs: &[Reference]) -> String {
+    let mut order: Vec<&Reference> = Vec::new();
+    let body = citation_re().replace_all(markdown, |caps: &regex::Captures| {
+        let mut parts = Vec::new();
+        for item in caps[1].split(';').map(str::trim).filter(|s| !s.is_empty()) {
+            let Some(m) = key_re().captures(item) else { return caps[0].to_string() };
+            let Some(r) = refs.iter().find(|r| r.key == m[1]) else { return caps[0].to_string() };
+            let n = match order.iter().position(|o| o.key == r.key) {
+                Some(pos) => pos + 1,
+                None => {
+                    order.push(r);
+                    order.len()
+                }
+            };
+            match m.get(2).map(|l| l.as_str().trim()).filter(|l| !l.is_empty()) {
+                Some(loc) => parts.push(format!("{}, {}", n, loc)),
+                None => parts.push(n.to_string()),
+            }
+        }
+        format!("[{}]", parts.join(", "))
+    });
+    if order.is_empty() {
+        return markdown.to_string();
+    }
+    let list: Vec<String> = order.iter().enumerate().map(|(i, r)| format!("\\[{}\\] {}", i + 1, format_entry(r))).collect();
+    format!(
+        "{}\n\n## {}\n\n{}\n",
+        body.trim_end(),
+        crate::i18n::t("citation.bibliography"),
+        list.join("\n\n")
+    )
+}
+
+fn is_cjk(s: &str) -> bool {
+    s.chars().any(|c| ('\u{4e00}'..='\u{9fff}').contains(&c))
+}
+
+/// 作者名：西文 “Last, First” → “Last F”，中文原样
+fn format_author(name: &str) -> String {
+    if is_cjk(name) {
+        return name.replace([',', ' '], "");
+    }
+    let (last, first) = match name.split_once(',') {
+        Some((last, first)) => (last.trim().to_string(), first.trim().to_string()),
+        None => {
+            let mut parts: Vec<&str> = name.split_whitespace().collect();
+            let last = parts.pop().unwrap_or_default().to_string();
+            (last, parts.join(" "))
+        }
+    };
+    let initials: String = first
+        .split([' ', '-', '.'])
+        .filter_map(|p| p.chars().next())
+        .filter(|c| c.is_alphabetic())
+        .map(|c| c.to_uppercase().to_string())
+        .collect();
+    if initials.is_empty() { last } else { format!("{} {}", last, initials) }
+}
+
+/// GB/T 7714 顺序编码制条目
+fn format_entry(r: &Reference) -> String {
+    let get = |name: &str| r.fields.get(name).map(|v| clean(v)).unwrap_or_default();
+    let type_code = match r.entry_type.as_str() {
+        "article" => "J",
+        "book" | "inbook" | "incollection" => "M",
+        "inproceedings" | "conference" | "proceedings" => "C",
+        "phdthesis" | "mastersthesis" | "thesis" => "D",
+        "techreport" | "report" => "R",
+        "online" | "misc" if !get("url").is_empty() => "EB/OL",
+        _ => "Z",
+    };
+    let mut authors: Vec<String> = r.authors.iter().take(3).map(|a| format_author(a)).collect();
+    if r.authors.len() > 3 {
+        authors.push(if r.authors.iter().any(|a| is_cjk(a)) { "等".to_string() } else { "et al".to_string() });
+    }
+
+    let mut entry = String::new();
+    if !authors.is_empty() {
+        entry.push_str(&format!("{}. ", authors.join(", ")));
+    }
+    entry.push_str(&format!("{}[{}]", r.title, type_code));
+    let mut tail = Vec::new();
+    if !r.container.is_empty() {
+        tail.push(r.container.clone());
+    }
+    let mut when = r.year.clone();
+    let (volume, number, pages) = (get("volume"), get("number"), get("pages"));
+    if !volume.is_empty() {
+        when.push_str(&format!(", {}", volume));
+    }
+    if !number.is_empty() {
+        when.push_str(&format!("({})", number));
+    }
+    if !pages.is_empty() {
+        when.push_str(&format!(": {}", pages));
+    }
+    if !when.is_empty() {
+        tail.push(when);
+    }
+    if !tail.is_empty() {
+        entry.push_str(&format!(". {}", tail.join(", ")));
+    }
+    entry.push('.');
+    let doi = get("doi");
+    let url = get("url");
+    if !doi.is_empty() {
+        entry.push_str(&format!(" DOI: {}.", doi));
+    } else if !url.is_empty() {
+        entry.push_str(&format!(" {}.", url));
+    }
+    entry
+}
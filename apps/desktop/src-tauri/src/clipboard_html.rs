@@ -0,0 +1,173 @@
+// 系统剪贴板的富文本读写：写入 HTML（同时附带纯文本），读取 HTML / RTF
+// 与 clipboard_image 相同，按平台调用系统自带工具：
+// macOS osascript、Windows PowerShell Set-Clipboard / Get-Clipboard、Linux wl-copy / xclip
+
+use std::process::Command;
+
+/// 写入 HTML 富文本；不支持富文本的粘贴目标使用 plain
+pub fn write_html(html: &str, plain: &str) -> Result<(), String> {
+    write_platform(html, plain)
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn temp_file(ext: &str, content: &str) -> Result<std::path::PathBuf, String> {
+    let path = std::env::temp_dir().join(format!("aidocplus-clipboard-{}.{}", uuid::Uuid::new_v4(), ext));
+    std::fs::write(&path, content).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+#[cfg(target_os = "macos")]
+fn write_platform(html: &str, plain: &str) -> Result<(), String> {
+    // 内容可能很大（内嵌图片），经临时文件读入，避免命令行长度限制
+    let html_file = temp_file("html", html)?;
+    let text_file = temp_file("txt", plain)?;
+    let script = format!(
+        "set the clipboard to {{«class HTML»:(read (POSIX file \"{}\") as «class HTML»), \
+         string:(read (POSIX file \"{}\") as «class utf8»)}}",
+        html_file.to_string_lossy(),
+        text_file.to_string_lossy()
+    );
+    let output = Command::new("osascript").arg("-e").arg(script).output().map_err(|e| e.to_string());
+    let _ = std::fs::remove_file(&html_file);
+    let _ = std::fs::remove_file(&text_file);
+    check(output?)
+}
+
+#[cfg(target_os = "windows")]
+fn write_platform(html: &str, _plain: &str) -> Result<(), String> {
+    // Set-Clipboard -AsHtml 会同时写入 CF_HTML 与纯文本
+    let html_file = temp_file("html", html)?;
+    let script = format!(
+        "Get-Content -Raw -Encoding UTF8 -LiteralPath '{}' | Set-Clipboard -AsHtml",
+        html_file.to_string_lossy().replace('\'', "''")
+    );
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-STA", "-Command", &script])
+        .output()
+        .map_err(|e| e.to_string());
+    let _ = std::fs::remove_file(&html_file);
+    check(output?)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn write_platform(html: &str, _plain: &str) -> Result<(), String> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let candidates: [(&str, &[&str]); 2] = [
+        ("wl-copy", &["--type", "text/html"]),
+        ("xclip", &["-selection", "clipboard", "-t", "text/html", "-i"]),
+    ];
+    for (program, args) in candidates {
+        // 两者都会转入后台持有剪贴板，输出不能接管道，否则 wait 会等到后台进程退出
+        let Ok(mut child) = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(html.as_bytes());
+        }
+        if child.wait().is_ok_and(|s| s.success()) {
+            return Ok(());
+        }
+    }
+    Err("无法写入剪贴板（需要安装 wl-clipboard 或 xclip）".to_string())
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn check(output: std::process::Output) -> Result<(), String> {
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("写入剪贴板失败: {}", String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RichFormat {
+    Html,
+    Rtf,
+}
+
+/// 读取剪贴板中的 HTML 或 RTF；剪贴板中没有该格式时返回 None
+pub fn read_rich(format: RichFormat) -> Option<String> {
+    let data = read_platform(format)?;
+    let text = String::from_utf8_lossy(&data).to_string();
+    let text = match format {
+        RichFormat::Html => strip_cf_html_header(&text),
+        RichFormat::Rtf => text,
+    };
+    (!text.trim().is_empty()).then_some(text)
+}
+
+/// Windows 的 CF_HTML 带有 "Version:0.9 StartHTML:..." 头部，只取片段部分
+fn strip_cf_html_header(html: &str) -> String {
+    if let (Some(start), Some(end)) = (html.find("<!--StartFragment-->"), html.find("<!--EndFragment-->")) {
+        if start < end {
+            return html[start + "<!--StartFragment-->".len()..end].to_string();
+        }
+    }
+    match html.find('<') {
+        Some(pos) if html.starts_with("Version:") => html[pos..].to_string(),
+        _ => html.to_string(),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn read_platform(format: RichFormat) -> Option<Vec<u8>> {
+    // 输出形如 «data HTML3C6D657461...»，需要把十六进制解码
+    let class = match format {
+        RichFormat::Html => "HTML",
+        RichFormat::Rtf => "RTF ",
+    };
+    let script = format!("the clipboard as «class {}»", class);
+    let output = Command::new("osascript").arg("-e").arg(script).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let hex = text.trim().strip_prefix("«data ")?.strip_suffix('»')?.get(4..)?;
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|h| u8::from_str_radix(h, 16).ok()))
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn read_platform(format: RichFormat) -> Option<Vec<u8>> {
+    let kind = match format {
+        RichFormat::Html => "Html",
+        RichFormat::Rtf => "Rtf",
+    };
+    let script = format!(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; \
+         Get-Clipboard -Format Text -TextFormatType {} -Raw",
+        kind
+    );
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-STA", "-Command", &script])
+        .output()
+        .ok()?;
+    (output.status.success() && !output.stdout.is_empty()).then_some(output.stdout)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn read_platform(format: RichFormat) -> Option<Vec<u8>> {
+    let mime = match format {
+        RichFormat::Html => "text/html",
+        RichFormat::Rtf => "text/rtf",
+    };
+    let candidates: [(&str, Vec<&str>); 2] = [
+        ("wl-paste", vec!["--no-newline", "--type", mime]),
+        ("xclip", vec!["-selection", "clipboard", "-t", mime, "-o"]),
+    ];
+    candidates.into_iter().find_map(|(program, args)| {
+        let output = Command::new(program).args(args).output().ok()?;
+        (output.status.success() && !output.stdout.is_empty()).then_some(output.stdout)
+    })
+}
@@ -0,0 +1,140 @@
+// 读取系统剪贴板中的图片并保存为文档附件
+// 没有跨平台剪贴板库，按平台调用系统自带工具：
+// macOS osascript、Windows PowerShell、Linux wl-paste / xclip
+
+use crate::document::{Attachment, Document};
+use serde::Serialize;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// 剪贴板图片大小上限
+const MAX_IMAGE_BYTES: usize = 50 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PastedImage {
+    /// 可直接插入编辑器的 Markdown 图片引用
+    pub markdown: String,
+    pub attachment: Attachment,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// 读取剪贴板图片，统一转为 PNG
+pub fn read_png() -> Result<(Vec<u8>, u32, u32), String> {
+    let raw = read_platform()?;
+    if raw.is_empty() {
+        return Err("剪贴板中没有图片".to_string());
+    }
+    if raw.len() > MAX_IMAGE_BYTES {
+        return Err("剪贴板图片过大".to_string());
+    }
+    let img = image::load_from_memory(&raw).map_err(|e| format!("无法识别剪贴板图片: {}", e))?;
+    let (width, height) = (img.width(), img.height());
+    if raw.starts_with(b"\x89PNG") {
+        return Ok((raw, width, height));
+    }
+    let mut png = Vec::new();
+    img.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("图片转换失败: {}", e))?;
+    Ok((png, width, height))
+}
+
+#[cfg(target_os = "macos")]
+fn read_platform() -> Result<Vec<u8>, String> {
+    let tmp = temp_file();
+    let script = format!(
+        "set png to (the clipboard as «class PNGf»)\n\
+         set f to open for access POSIX file \"{}\" with write permission\n\
+         write png to f\n\
+         close access f",
+        tmp.to_string_lossy()
+    );
+    let output = Command::new("osascript").arg("-e").arg(script).output().map_err(|e| e.to_string())?;
+    read_temp(&tmp, output.status.success())
+}
+
+#[cfg(target_os = "windows")]
+fn read_platform() -> Result<Vec<u8>, String> {
+    let tmp = temp_file();
+    let script = format!(
+        "Add-Type -AssemblyName System.Windows.Forms; Add-Type -AssemblyName System.Drawing; \
+         $img = [System.Windows.Forms.Clipboard]::GetImage(); \
+         if ($img -eq $null) {{ exit 1 }}; \
+         $img.Save('{}', [System.Drawing.Imaging.ImageFormat]::Png)",
+        tmp.to_string_lossy().replace('\'', "''")
+    );
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-STA", "-Command", &script])
+        .output()
+        .map_err(|e| e.to_string())?;
+    read_temp(&tmp, output.status.success())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn read_platform() -> Result<Vec<u8>, String> {
+    let candidates: [(&str, &[&str]); 2] = [
+        ("wl-paste", &["--no-newline", "--type", "image/png"]),
+        ("xclip", &["-selection", "clipboard", "-t", "image/png", "-o"]),
+    ];
+    for (program, args) in candidates {
+        if let Ok(output) = Command::new(program).args(args).output() {
+            if output.status.success() && !output.stdout.is_empty() {
+                return Ok(output.stdout);
+            }
+        }
+    }
+    Err("剪贴板中没有图片（需要安装 wl-clipboard 或 xclip）".to_string())
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn temp_file() -> PathBuf {
+    std::env::temp_dir().join(format!("aidocplus-clipboard-{}.png", uuid::Uuid::new_v4()))
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn read_temp(path: &Path, success: bool) -> Result<Vec<u8>, String> {
+    let data = if success { fs::read(path).ok() } else { None };
+    let _ = fs::remove_file(path);
+    data.ok_or_else(|| "剪贴板中没有图片".to_string())
+}
+
+/// 把图片保存到 attachments/{文档ID}/ 并登记为文档附件
+pub fn save_to_document(
+    project_dir: &Path,
+    doc_path: &PathBuf,
+    png: &[u8],
+    width: u32,
+    height: u32,
+) -> Result<PastedImage, String> {
+    let mut document = Document::load(doc_path).map_err(|e| e.to_string())?;
+    let dir = project_dir.join("attachments").join(&document.id);
+    fs::create_dir_all(&dir).map_err(|e| format!("创建附件目录失败: {}", e))?;
+
+    let stem = format!("paste-{}", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+    let path = (1..)
+        .map(|i| if i == 1 { dir.join(format!("{}.png", stem)) } else { dir.join(format!("{}-{}.png", stem, i)) })
+        .find(|p| !p.exists())
+        .unwrap_or_else(|| dir.join(format!("{}.png", stem)));
+    fs::write(&path, png).map_err(|e| format!("保存图片失败: {}", e))?;
+
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let file_path = path.to_string_lossy().to_string();
+    let attachment = Attachment {
+        id: uuid::Uuid::new_v4().to_string(),
+        file_name: file_name.clone(),
+        file_path: file_path.clone(),
+        file_size: png.len() as u64,
+        file_type: "png".to_string(),
+        added_at: chrono::Utc::now().timestamp(),
+    };
+    document.attachments.push(attachment.clone());
+    document.metadata.updated_at = chrono::Utc::now().timestamp();
+    document.save(doc_path).map_err(|e| e.to_string())?;
+
+    // 尖括号包裹路径，兼容含空格的路径
+    let markdown = format!("![{}](<{}>)", file_name.trim_end_matches(".png"), file_path.replace('\\', "/"));
+    Ok(PastedImage { markdown, attachment, width, height })
+}
@@ -0,0 +1,86 @@
+#![allow(non_snake_case)]
+
+use crate::activity::{self, ActivityRange, WritingActivity};
+use super::document::writing_words;
+use crate::config::{self, AppState};
+use crate::document::Document;
+use crate::focus::{self, FocusKind, FocusSession};
+use crate::goals::{self, GoalStatus};
+use crate::settings;
+use crate::error::{run_blocking, Result};
+use crate::{i18n, plugin_bus};
+use tauri::{AppHandle, Emitter, State};
+
+/// 写作活动统计（按天、按项目、连续天数）；未指定区间时返回全部历史
+#[tauri::command]
+pub async fn get_writing_activity(
+    app: AppHandle,
+    range: Option<ActivityRange>,
+    projectId: Option<String>,
+) -> Result<WritingActivity> {
+    let path = config::get_activity_path(&app);
+    run_blocking(move || Ok(activity::query(&path, &range.unwrap_or_default(), projectId.as_deref()))).await
+}
+
+/// 今天的写作目标进度（状态栏使用）；指定项目时同时返回项目目标
+#[tauri::command]
+pub async fn get_goal_status(app: AppHandle, projectId: Option<String>) -> Result<GoalStatus> {
+    let path = config::get_activity_path(&app);
+    let goals = settings::load(&config::get_settings_path(&app)).goals;
+    run_blocking(move || {
+        let date = activity::today();
+        let day = activity::day_stats(&path, &date);
+        Ok(goals::status(&goals, &date, &day, projectId.as_deref()))
+    })
+    .await
+}
+
+/// 开始专注时段（番茄钟或字数冲刺），绑定到指定文档
+#[tauri::command]
+pub fn start_focus_session(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    projectId: String,
+    documentId: String,
+    kind: FocusKind,
+    plannedMinutes: Option<u32>,
+    wordTarget: Option<usize>,
+) -> Result<FocusSession> {
+    let doc_path = state.get_document_path(&projectId, &documentId);
+    let document = Document::load(&doc_path).map_err(|_| i18n::tf("error.document_not_found", &[&documentId]))?;
+    let session = focus::start(&projectId, &documentId, kind, plannedMinutes, wordTarget, writing_words(&document))?;
+    let _ = app.emit("focus:started", &session);
+    Ok(session)
+}
+
+/// 结束当前专注时段并写入历史
+#[tauri::command]
+pub fn stop_focus_session(app: AppHandle, state: State<'_, AppState>) -> Result<FocusSession> {
+    // 文档可能已被删除，此时不计算净变化
+    let end_words = focus::active().and_then(|s| {
+        Document::load(&state.get_document_path(&s.project_id, &s.document_id))
+            .ok()
+            .map(|doc| writing_words(&doc))
+    });
+    let session = focus::stop(&config::get_focus_history_path(&app), end_words)?;
+    let _ = app.emit("focus:ended", &session);
+    plugin_bus::publish(&app, "focus:ended", serde_json::json!(session));
+    Ok(session)
+}
+
+/// 当前进行中的专注时段
+#[tauri::command]
+pub fn get_active_focus_session() -> Option<FocusSession> {
+    focus::active()
+}
+
+/// 专注历史（最近的在前），默认 50 条
+#[tauri::command]
+pub async fn get_focus_history(
+    app: AppHandle,
+    projectId: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<FocusSession>> {
+    let path = config::get_focus_history_path(&app);
+    run_blocking(move || Ok(focus::history(&path, projectId.as_deref(), limit.unwrap_or(50)))).await
+}
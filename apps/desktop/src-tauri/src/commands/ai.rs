@@ -1,12 +1,32 @@
-use crate::ai::{AIConfig, ChatMessage, OpenAIResponse};
+use crate::ai::{split_image, AIConfig, ChatMessage, ModelInfo, OpenAIResponse, ProxyConfig, StreamResult, Usage};
+use crate::ai_cache;
+use crate::ai_edit::{self, AiEditResult};
+use crate::auto_tag::{self, AutoTagResult};
+use crate::config::{self, get_provider_health_path, AppState};
+use crate::context_window;
+use crate::document::Document;
+use crate::glossary;
+use crate::jobs;
 use crate::error::AppError;
+use crate::outline::{self, OutlineItem};
+use crate::plugin;
+use crate::plugin_bus;
+use crate::pricing::{self, CostEstimate, ProjectSpend};
+use crate::resource_engine::ResourceEngineState;
+use crate::project::{Project, ProjectAiSettings};
+use crate::provider_health::{self, CallKind, ProviderHealth};
+use crate::summarize;
 use crate::tools;
+use crate::transcription;
+use crate::translate::{self, TranslationResult};
+use crate::web_search::{self, SearchHit};
+use crate::{secrets, settings};
 use serde_json::json;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Mutex, OnceLock};
-use std::time::Duration;
-use tauri::{AppHandle, Emitter};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
 
 /// 流式状态管理：使用 request_id 作为 key，支持多个并发流独立控制
 static STREAM_STATES: OnceLock<Mutex<HashMap<String, AtomicBool>>> = OnceLock::new();
@@ -70,25 +90,99 @@ pub async fn chat(
     temperature: Option<f64>,
     max_tokens: Option<u32>,
     enable_web_search: Option<bool>,
+    project_id: Option<String>,
+) -> Result<String> {
+    let project = project_ai_settings(&app, project_id.as_deref());
+    let config = get_ai_config(&app, project.as_ref(), provider, api_key, model, base_url);
+    let mut messages = with_project_system_prompt(&app, messages, project_id.as_deref(), project.as_ref());
+    if let Some(info) = fit_context_window(&config, &mut messages, max_tokens) {
+        let _ = app.emit("ai:context:truncated", json!({ "request_id": null, "info": info }));
+    }
+
+    // 联网搜索的结果随时间变化，不缓存
+    let ttl = settings::load(&config::get_settings_path(&app)).ai.cache_ttl_secs;
+    let cache_key = (ttl > 0 && !enable_web_search.unwrap_or(false)).then(|| {
+        ai_cache::key(&json!({
+            "provider": config.provider,
+            "baseUrl": config.get_base_url(),
+            "model": config.get_default_model(),
+            "messages": messages,
+            "temperature": temperature.or(config.temperature),
+            "maxTokens": max_tokens,
+        }))
+    });
+    let cache_dir = config::get_ai_cache_dir(&app);
+    if let Some(content) = cache_key.as_deref().and_then(|key| ai_cache::get(&cache_dir, key, ttl)) {
+        tracing::debug!("AI 响应命中缓存");
+        return Ok(content);
+    }
+
+    let started = Instant::now();
+    let result = chat_with_config(&config, messages, temperature, max_tokens, enable_web_search, false).await;
+    record_health(&app, &config, CallKind::Chat, started, &result);
+    if let (Some(key), Ok(content)) = (&cache_key, &result) {
+        if let Err(e) = ai_cache::put(&cache_dir, key, content) {
+            tracing::warn!("{}", e);
+        }
+    }
+    result
+}
+
+/// 清空 AI 响应缓存，返回删除的条目数
+#[tauri::command]
+pub fn clear_ai_cache(app: AppHandle) -> std::result::Result<usize, String> {
+    ai_cache::clear(&config::get_ai_cache_dir(&app))
+}
+
+/// 用设置中的搜索服务执行一次搜索（设置页“测试搜索”使用）
+#[tauri::command]
+pub async fn test_web_search(app: AppHandle, query: String) -> std::result::Result<Vec<SearchHit>, String> {
+    let config = get_ai_config(&app, None, None, None, None, None);
+    let client = config.http_client()?;
+    web_search::search(&client, &config.search, &query).await
+}
+
+async fn chat_with_config(
+    config: &AIConfig,
+    messages: Vec<ChatMessage>,
+    temperature: Option<f64>,
+    max_tokens: Option<u32>,
+    enable_web_search: Option<bool>,
+    json_mode: bool,
 ) -> Result<String> {
-    let config = get_ai_config(&app, provider, api_key, model, base_url);
     let web_search = enable_web_search.unwrap_or(false);
-    let client = reqwest::Client::new();
+    let client = config.http_client().map_err(AppError::AIError)?;
 
     // OpenAI + 联网搜索 → Responses API（非流式）
     if config.provider == "openai" && web_search {
-        return call_openai_responses(&config, &client, &messages, max_tokens).await;
+        return call_openai_responses(config, &client, &messages, max_tokens).await;
     }
 
     // Anthropic + 联网搜索 → Anthropic Messages API（非流式）
     if config.provider == "anthropic" && web_search {
-        return call_anthropic_with_search(&config, &client, &messages, max_tokens).await;
+        return call_anthropic_with_search(config, &client, &messages, max_tokens).await;
     }
 
+    // Gemini + 联网搜索 → 原生 generateContent 接口（Google Search grounding）
+    if config.provider == "gemini" && web_search {
+        return call_gemini_generate(config, &client, &messages, temperature, max_tokens, web_search).await;
+    }
+
+    // 没有内置联网搜索的服务商 → 通过 web_search 工具调用设置中的搜索服务
+    if web_search && !native_web_search(&config.provider) {
+        return chat_with_search_tool(config, &client, &messages, temperature, max_tokens, json_mode).await;
+    }
+
+    // Ollama → 原生 /api/chat 接口（本地模型，无需 API Key）
+    if config.provider == "ollama" {
+        return call_ollama_chat(config, &client, &messages, temperature, max_tokens, json_mode).await;
+    }
+
+    let messages: Vec<serde_json::Value> = messages.iter().map(ChatMessage::to_openai).collect();
     let mut request_body = json!({
         "messages": messages,
         "model": config.get_default_model(),
-        "temperature": temperature.unwrap_or(0.7),
+        "temperature": temperature.or(config.temperature).unwrap_or(0.7),
         "stream": false
     });
 
@@ -98,24 +192,18 @@ pub async fn chat(
 
     // 联网搜索：根据 provider 注入正确的参数格式
     if web_search {
-        inject_web_search_params(&mut request_body, &config);
+        inject_web_search_params(&mut request_body, config);
     }
 
-    let url = format!("{}/chat/completions", config.get_base_url());
+    // JSON 模式：Anthropic 兼容接口不支持 response_format，依赖提示词约束
+    if json_mode && config.provider != "anthropic" {
+        request_body["response_format"] = json!({ "type": "json_object" });
+    }
 
-    let mut request_builder = client.post(&url).json(&request_body);
+    let url = config.endpoint_url("chat/completions");
 
-    // Set API key based on provider
-    if let Some(key) = config.api_key {
-        match config.provider.as_str() {
-            "anthropic" => {
-                request_builder = request_builder.header("x-api-key", key);
-            }
-            _ => {
-                request_builder = request_builder.header("Authorization", format!("Bearer {}", key));
-            }
-        }
-    }
+    // 按服务商设置鉴权请求头
+    let request_builder = config.authorize(client.post(&url).json(&request_body));
 
     let response = request_builder
         .header("Content-Type", "application/json")
@@ -158,6 +246,84 @@ pub async fn chat(
     }
 }
 
+/// 非流式 Function Calling 循环，只提供 web_search 工具（联网搜索后备）
+async fn chat_with_search_tool(
+    config: &AIConfig,
+    client: &reqwest::Client,
+    messages: &[ChatMessage],
+    temperature: Option<f64>,
+    max_tokens: Option<u32>,
+    json_mode: bool,
+) -> Result<String> {
+    let url = if config.provider == "ollama" {
+        format!("{}/v1/chat/completions", config.ollama_root())
+    } else {
+        config.endpoint_url("chat/completions")
+    };
+    let mut current_messages: Vec<serde_json::Value> = messages.iter().map(ChatMessage::to_openai).collect();
+
+    for round in 0..=MAX_TOOL_ROUNDS {
+        let mut request_body = json!({
+            "messages": current_messages,
+            "model": config.get_default_model(),
+            "temperature": temperature.or(config.temperature).unwrap_or(0.7),
+            "stream": false
+        });
+        if let Some(mt) = max_tokens {
+            request_body["max_tokens"] = json!(mt);
+        }
+        // 最后一轮不再提供工具，让模型直接作答
+        if round < MAX_TOOL_ROUNDS {
+            request_body["tools"] = json!([tools::web_search_tool_definition()]);
+        }
+        if json_mode && config.provider != "anthropic" {
+            request_body["response_format"] = json!({ "type": "json_object" });
+        }
+
+        let response = config
+            .authorize(client.post(&url).json(&request_body))
+            .timeout(Duration::from_secs(120))
+            .send()
+            .await
+            .map_err(|e| AppError::AIError(format!("Failed to connect to AI service: {}", e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(AppError::AIError(format!("AI API error ({}): {}", status, error_text)));
+        }
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::AIError(format!("Failed to parse response: {}", e)))?;
+
+        let message = body.pointer("/choices/0/message").cloned().unwrap_or_default();
+        let content = message.get("content").and_then(|c| c.as_str()).unwrap_or_default().to_string();
+        let tool_calls: Vec<tools::ToolCall> = message
+            .get("tool_calls")
+            .and_then(|calls| serde_json::from_value(calls.clone()).ok())
+            .unwrap_or_default();
+        if tool_calls.is_empty() {
+            return Ok(content);
+        }
+
+        current_messages.push(json!({
+            "role": "assistant",
+            "content": strip_think(&content),
+            "tool_calls": message["tool_calls"]
+        }));
+        for tool_call in &tool_calls {
+            let tool_result = tools::execute_web_search(tool_call, config).await;
+            current_messages.push(json!({
+                "role": "tool",
+                "tool_call_id": tool_result.tool_call_id,
+                "content": tool_result.content
+            }));
+        }
+    }
+
+    Err(AppError::AIError("联网搜索轮数超过上限".to_string()))
+}
+
 #[tauri::command]
 pub async fn chat_stream(
     app: AppHandle,
@@ -172,7 +338,8 @@ pub async fn chat_stream(
     enable_tools: Option<bool>,
     project_documents: Option<Vec<serde_json::Value>>,
     request_id: Option<String>,
-) -> Result<String> {
+    project_id: Option<String>,
+) -> Result<StreamResult> {
     let req_id = request_id.clone().unwrap_or_default();
 
     // 注册新的流
@@ -191,177 +358,271 @@ pub async fn chat_stream(
     }
     let _guard = StreamGuard { request_id: req_id.clone() };
 
-    let config = get_ai_config(&app, provider, api_key, model, base_url);
-    let web_search = enable_web_search.unwrap_or(false);
-    let use_tools = enable_tools.unwrap_or(false);
-
-    // OpenAI + 联网搜索 → Responses API
-    if config.provider == "openai" && web_search {
-        return stream_openai_responses(&config, &messages, &req_id, &window).await;
+    let project = project_ai_settings(&app, project_id.as_deref());
+    let config = get_ai_config(&app, project.as_ref(), provider, api_key, model, base_url);
+    let mut messages = with_project_system_prompt(&app, messages, project_id.as_deref(), project.as_ref());
+    if let Some(info) = fit_context_window(&config, &mut messages, None) {
+        let _ = window.emit("ai:context:truncated", json!({ "request_id": req_id, "info": info }));
     }
+    let started = Instant::now();
+    let result = chat_stream_with_config(
+        &config, messages, &window, enable_web_search, enable_thinking, enable_tools, project_documents, &req_id,
+    )
+    .await;
+    // 用户主动停止的流耗时没有参考意义，不计入统计
+    if !is_stream_cancelled(&req_id) {
+        record_health(&app, &config, CallKind::Stream, started, &result);
+    }
+    if let Some(usage) = result.as_ref().ok().and_then(|r| r.usage.as_ref()) {
+        let cost = usage_cost(&app, &config, usage, project_id.as_deref());
+        let _ = window.emit("ai:stream:usage", json!({
+            "request_id": req_id,
+            "usage": usage,
+            "cost": cost
+        }));
+    }
+    result
+}
 
-    // Anthropic + 联网搜索 → Anthropic Messages API（原生格式）
-    if config.provider == "anthropic" && web_search {
-        return stream_anthropic_with_search(&config, &messages, &req_id, &window).await;
+/// 按模型上下文长度裁剪历史消息，避免长对话超出上限直接报错
+fn fit_context_window(config: &AIConfig, messages: &mut Vec<ChatMessage>, max_tokens: Option<u32>) -> Option<context_window::TruncationInfo> {
+    let reserve = max_tokens.map_or(context_window::DEFAULT_OUTPUT_RESERVE, |t| t as usize);
+    let info = context_window::fit(messages, context_window::window_for(&config.get_default_model()), reserve)?;
+    tracing::info!(
+        "上下文超出 {} tokens，已省略 {} 条历史消息{}",
+        info.context_window,
+        info.dropped_messages,
+        if info.content_trimmed { "并截断最后一条消息" } else { "" }
+    );
+    Some(info)
+}
+
+/// 估算本次调用费用，并计入项目累计花费
+fn usage_cost(app: &AppHandle, config: &AIConfig, usage: &Usage, project_id: Option<&str>) -> CostEstimate {
+    let overrides = settings::load(&config::get_settings_path(app)).ai.pricing;
+    let model = config.get_default_model();
+    let cost = pricing::estimate(pricing::price_for(&config.provider, &model, &overrides), usage.prompt_tokens, usage.completion_tokens);
+    if let Some(project_id) = project_id.filter(|id| !id.is_empty()) {
+        let project_dir = app.state::<AppState>().config.projects_dir.join(project_id);
+        if let Err(e) = pricing::record_spend(&project_dir, &model, usage, &cost) {
+            tracing::warn!("记录 AI 用量失败: {}", e);
+        }
     }
+    cost
+}
 
-    let client = reqwest::Client::new();
-    let url = format!("{}/chat/completions", config.get_base_url());
-    let docs = project_documents.unwrap_or_default();
+/// 估算一次调用的费用（美元）；未指定服务商和模型时使用当前 AI 配置
+#[tauri::command]
+pub fn estimate_request_cost(
+    app: AppHandle,
+    provider: Option<String>,
+    model: Option<String>,
+    prompt_tokens: u32,
+    completion_tokens: u32,
+) -> CostEstimate {
+    let config = get_ai_config(&app, None, provider, None, model, None);
+    let overrides = settings::load(&config::get_settings_path(&app)).ai.pricing;
+    pricing::estimate(pricing::price_for(&config.provider, &config.get_default_model(), &overrides), prompt_tokens, completion_tokens)
+}
 
-    // Function Calling 循环：先用非流式检测 tool_calls，执行工具后再次调用
-    let mut current_messages: Vec<serde_json::Value> = messages.iter().map(|m| {
-        json!({ "role": m.role, "content": m.content })
-    }).collect();
+/// 项目累计 AI 用量和花费
+#[tauri::command]
+pub fn get_project_ai_spend(app: AppHandle, project_id: String) -> std::result::Result<ProjectSpend, String> {
+    let project_dir = app.state::<AppState>().config.projects_dir.join(&project_id);
+    if !project_dir.is_dir() {
+        return Err(crate::i18n::tf("error.project_not_found", &[&project_id]));
+    }
+    Ok(pricing::load_spend(&project_dir))
+}
 
-    if use_tools {
-        let tool_defs = tools::get_builtin_tool_definitions();
-        let max_rounds = 5;
+#[allow(clippy::too_many_arguments)]
+async fn chat_stream_with_config(
+    config: &AIConfig,
+    messages: Vec<ChatMessage>,
+    window: &tauri::Window,
+    enable_web_search: Option<bool>,
+    enable_thinking: Option<bool>,
+    enable_tools: Option<bool>,
+    project_documents: Option<Vec<serde_json::Value>>,
+    req_id: &str,
+) -> Result<StreamResult> {
+    let web_search = enable_web_search.unwrap_or(false);
+    let use_tools = enable_tools.unwrap_or(false);
 
-        for _round in 0..max_rounds {
-            if is_stream_cancelled(&req_id) { break; }
+    // OpenAI + 联网搜索 → Responses API
+    if config.provider == "openai" && web_search {
+        return stream_openai_responses(config, &messages, req_id, window).await;
+    }
 
-            let mut tool_request = json!({
-                "messages": current_messages,
-                "model": config.get_default_model(),
-                "temperature": 0.7,
-                "stream": false,
-                "tools": tool_defs
-            });
+    // Anthropic + 联网搜索或深度思考 → Anthropic Messages API（原生格式）
+    // 启用工具调用时仍走兼容接口的 Function Calling 循环（不含思考）
+    let thinking = enable_thinking.unwrap_or(false);
+    if config.provider == "anthropic" && (web_search || (thinking && !use_tools)) {
+        return stream_anthropic_messages(config, &messages, web_search, thinking, req_id, window).await;
+    }
 
-            if web_search {
-                inject_web_search_params(&mut tool_request, &config);
-            }
+    // Gemini + 联网搜索或深度思考 → 原生 streamGenerateContent 接口（兼容接口不返回搜索来源和思考内容）
+    if config.provider == "gemini" && (web_search || (thinking && !use_tools)) {
+        return stream_gemini_generate(config, &messages, web_search, thinking, req_id, window).await;
+    }
 
-            let mut req_builder = client
-                .post(&url)
-                .header("Content-Type", "application/json")
-                .json(&tool_request);
+    let client = config.http_client().map_err(AppError::AIError)?;
+    let ollama = config.provider == "ollama";
+    let current_messages: Vec<serde_json::Value> = messages.iter().map(ChatMessage::to_openai).collect();
 
-            if let Some(key) = &config.api_key {
-                match config.provider.as_str() {
-                    "anthropic" => { req_builder = req_builder.header("x-api-key", key); }
-                    _ => { req_builder = req_builder.header("Authorization", format!("Bearer {}", key)); }
-                }
-            }
+    // Ollama 不用工具时走原生流式接口；需要工具调用（含联网搜索后备）时走其 OpenAI 兼容接口
+    if ollama && !use_tools && !web_search {
+        return stream_ollama_chat(config, &client, &current_messages, thinking, req_id, window).await;
+    }
+    let url = if ollama {
+        format!("{}/v1/chat/completions", config.ollama_root())
+    } else {
+        config.endpoint_url("chat/completions")
+    };
+    let docs = project_documents.unwrap_or_default();
+    stream_with_tools(config, &client, &url, current_messages, web_search, thinking, use_tools, &docs, req_id, window).await
+}
 
-            let resp = req_builder
-                .timeout(Duration::from_secs(120))
-                .send()
-                .await
-                .map_err(|e| AppError::AIError(format!("Tool call failed: {}", e)))?;
+/// 工具调用最多轮数，超过后不再提供工具，让模型直接作答
+const MAX_TOOL_ROUNDS: usize = 5;
 
-            if !resp.status().is_success() {
-                let status = resp.status();
-                let err = resp.text().await.unwrap_or_default();
-                return Err(AppError::AIError(format!("Tool call error ({}): {}", status, err)));
-            }
+/// 流式 Function Calling 循环：每一轮都是流式请求，正文实时推送给前端，
+/// 同时从 SSE 增量中拼出 tool_calls；模型请求工具时执行工具并把结果加入对话，再开始下一轮
+#[allow(clippy::too_many_arguments)]
+async fn stream_with_tools(
+    config: &AIConfig,
+    client: &reqwest::Client,
+    url: &str,
+    mut current_messages: Vec<serde_json::Value>,
+    web_search: bool,
+    thinking: bool,
+    use_tools: bool,
+    docs: &[serde_json::Value],
+    req_id: &str,
+    window: &tauri::Window,
+) -> Result<StreamResult> {
+    // 没有内置联网搜索的服务商由 web_search 工具代替；未开启工具调用时只提供这一个工具
+    let search_tool = web_search && !native_web_search(&config.provider);
+    let mut tool_defs = if use_tools {
+        tools::get_builtin_tool_definitions(search_tool)
+    } else if search_tool {
+        vec![tools::web_search_tool_definition()]
+    } else {
+        Vec::new()
+    };
+    let plugin_tools = if use_tools {
+        tools::plugin_tools(&plugin::list_plugins(&window.app_handle().state::<ResourceEngineState>()))
+    } else {
+        Vec::new()
+    };
+    tool_defs.extend(plugin_tools.iter().map(|t| t.definition.clone()));
+    let mut result = StreamResult::default();
+
+    for round in 0..=MAX_TOOL_ROUNDS {
+        let mut request_body = json!({
+            "messages": current_messages,
+            "model": config.get_default_model(),
+            "temperature": config.temperature.unwrap_or(0.7),
+            "stream": true,
+            // 在最后一个数据块中返回用量
+            "stream_options": { "include_usage": true }
+        });
 
-            let json_resp: serde_json::Value = resp.json().await
-                .map_err(|e| AppError::AIError(format!("Parse tool response failed: {}", e)))?;
+        if !tool_defs.is_empty() && round < MAX_TOOL_ROUNDS {
+            request_body["tools"] = json!(tool_defs);
+        }
 
-            let choice = json_resp.get("choices")
-                .and_then(|c| c.get(0));
+        // 联网搜索：根据 provider 注入正确的参数格式
+        if web_search {
+            inject_web_search_params(&mut request_body, config);
+        }
 
-            let finish_reason = choice
-                .and_then(|c| c.get("finish_reason"))
-                .and_then(|f| f.as_str())
-                .unwrap_or("");
+        // 深度思考：根据 provider 注入思考模式参数
+        inject_thinking_params(&mut request_body, config, thinking);
 
-            if finish_reason != "tool_calls" {
-                // AI 没有请求工具调用，跳出循环进入流式输出
-                break;
-            }
+        let req_builder = config.authorize(
+            client
+                .post(url)
+                .header("Content-Type", "application/json")
+                .body(request_body.to_string()),
+        );
 
-            // 提取 tool_calls 并执行
-            let tool_calls = choice
-                .and_then(|c| c.get("message"))
-                .and_then(|m| m.get("tool_calls"))
-                .and_then(|tc| tc.as_array());
+        let response = req_builder
+            .send()
+            .await
+            .map_err(|e| AppError::AIError(format!("Stream connection failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown".to_string());
+            return Err(AppError::AIError(format!(
+                "Stream failed ({}): {}", status, error_text
+            )));
+        }
 
-            if let Some(calls) = tool_calls {
-                // 将 assistant 消息（含 tool_calls）加入对话
-                if let Some(assistant_msg) = choice.and_then(|c| c.get("message")) {
-                    current_messages.push(assistant_msg.clone());
-                }
+        let (turn, tool_calls) = stream_sse_chat_completions(response, req_id, window).await?;
+        result.content.push_str(&turn.content);
+        result.usage = match (result.usage.take(), turn.usage) {
+            (Some(a), Some(b)) => Some(Usage::new(a.prompt_tokens + b.prompt_tokens, a.completion_tokens + b.completion_tokens)),
+            (a, b) => a.or(b),
+        };
 
-                // 通知前端正在执行工具
-                let _ = window.emit("ai:stream:chunk", json!({
-                    "request_id": req_id,
-                    "content": "\n\n> 🔧 正在调用工具...\n\n"
-                }));
-
-                for call_val in calls {
-                    let tool_call: tools::ToolCall = match serde_json::from_value(call_val.clone()) {
-                        Ok(tc) => tc,
-                        Err(_) => continue,
-                    };
-
-                    let result = tools::execute_tool(&tool_call, &docs);
-
-                    // 将工具结果加入对话
-                    current_messages.push(json!({
-                        "role": "tool",
-                        "tool_call_id": result.tool_call_id,
-                        "content": result.content
-                    }));
-                }
-            } else {
-                break;
-            }
+        if tool_calls.is_empty() || is_stream_cancelled(req_id) {
+            break;
         }
-    }
 
-    // 最终流式输出
-    let mut request_body = json!({
-        "messages": current_messages,
-        "model": config.get_default_model(),
-        "temperature": 0.7,
-        "stream": true
-    });
+        // 将 assistant 消息（含 tool_calls）加入对话；思考内容不回传
+        current_messages.push(json!({
+            "role": "assistant",
+            "content": strip_think(&turn.content),
+            "tool_calls": tool_calls
+        }));
 
-    // 联网搜索：根据 provider 注入正确的参数格式
-    if web_search {
-        inject_web_search_params(&mut request_body, &config);
-    }
+        // 通知前端正在执行工具
+        let notice = "\n\n> 🔧 正在调用工具...\n\n";
+        result.content.push_str(notice);
+        let _ = window.emit("ai:stream:chunk", json!({
+            "request_id": req_id,
+            "content": notice
+        }));
 
-    // 深度思考：根据 provider 注入思考模式参数
-    let thinking = enable_thinking.unwrap_or(false);
-    inject_thinking_params(&mut request_body, &config, thinking);
+        for call_val in &tool_calls {
+            let tool_call: tools::ToolCall = match serde_json::from_value(call_val.clone()) {
+                Ok(tc) => tc,
+                Err(_) => continue,
+            };
 
-    let mut req_builder = client
-        .post(&url)
-        .header("Content-Type", "application/json")
-        .body(request_body.to_string());
+            let tool_result = tools::execute_tool_call(window, &tool_call, docs, &plugin_tools, config).await;
 
-    if let Some(key) = &config.api_key {
-        match config.provider.as_str() {
-            "anthropic" => {
-                req_builder = req_builder.header("x-api-key", key);
-            }
-            _ => {
-                req_builder = req_builder.header("Authorization", format!("Bearer {}", key));
-            }
+            // 将工具结果加入对话
+            current_messages.push(json!({
+                "role": "tool",
+                "tool_call_id": tool_result.tool_call_id,
+                "content": tool_result.content
+            }));
         }
     }
 
-    let response = req_builder
-        .send()
-        .await
-        .map_err(|e| AppError::AIError(format!("Stream connection failed: {}", e)))?;
+    Ok(result)
+}
 
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown".to_string());
-        return Err(AppError::AIError(format!(
-            "Stream failed ({}): {}", status, error_text
-        )));
+/// 去掉 <think>…</think> 思考内容
+fn strip_think(content: &str) -> String {
+    let mut text = String::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("<think>") {
+        text.push_str(&rest[..start]);
+        rest = match rest[start..].find("</think>") {
+            Some(end) => &rest[start + end + "</think>".len()..],
+            None => "",
+        };
     }
-
-    stream_sse_chat_completions(response, &req_id, &window).await
+    text.push_str(rest);
+    text.trim().to_string()
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn generate_content(
     app: AppHandle,
     author_notes: String,
@@ -370,6 +631,7 @@ pub async fn generate_content(
     api_key: Option<String>,
     model: Option<String>,
     base_url: Option<String>,
+    project_id: Option<String>,
 ) -> Result<String> {
     let user_prompt = if current_content.is_empty() {
         author_notes.clone()
@@ -384,14 +646,311 @@ pub async fn generate_content(
         ChatMessage {
             role: "user".to_string(),
             content: user_prompt,
+            images: Vec::new(),
         },
     ];
 
-    let response = chat(app, messages, provider, api_key, model, base_url, None, None, None).await?;
+    let response = chat(app, messages, provider, api_key, model, base_url, None, None, None, project_id).await?;
 
     Ok(response)
 }
 
+/// AI 自动标签：从项目已有标签中为文档挑选 3–7 个（项目尚无标签或 allow_new_tags 时可提出新标签）；
+/// apply 为 false 时只返回建议，由前端确认后调用 set_document_tags 写入
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn auto_tag_document(
+    app: AppHandle,
+    project_id: String,
+    document_id: String,
+    apply: Option<bool>,
+    allow_new_tags: Option<bool>,
+    provider: Option<String>,
+    api_key: Option<String>,
+    model: Option<String>,
+    base_url: Option<String>,
+) -> Result<AutoTagResult> {
+    let state = app.state::<AppState>().inner().clone();
+    let doc_path = state.get_document_path(&project_id, &document_id);
+    let document = Document::load(&doc_path).map_err(|_| AppError::DocumentNotFound(document_id.clone()))?;
+    let vocabulary = auto_tag::project_tags(&state.config.projects_dir.join(&project_id).join("documents"));
+    let allow_new = allow_new_tags.unwrap_or(false);
+
+    let project = project_ai_settings(&app, Some(&project_id));
+    let config = get_ai_config(&app, project.as_ref(), provider, api_key, model, base_url);
+    let messages = auto_tag::build_messages(&document, &vocabulary, allow_new);
+    let started = Instant::now();
+    let result = chat_with_config(&config, messages, Some(0.2), None, None, true).await;
+    record_health(&app, &config, CallKind::Chat, started, &result);
+    let tags = auto_tag::parse_tags(&result?, &vocabulary, allow_new).map_err(AppError::AIError)?;
+    let new_tags = tags.iter().filter(|t| !vocabulary.contains(t)).cloned().collect();
+
+    let applied = apply.unwrap_or(true);
+    if applied {
+        let mut document = document;
+        document.ensure_unlocked()?;
+        for tag in &tags {
+            if !document.metadata.tags.contains(tag) {
+                document.metadata.tags.push(tag.clone());
+            }
+        }
+        document.metadata.updated_at = chrono::Utc::now().timestamp();
+        document.save(&doc_path)?;
+    }
+    Ok(AutoTagResult { tags, new_tags, applied })
+}
+
+/// 文档摘要：长文档分块提炼要点后再合并（map-reduce），返回 Markdown
+/// length 为 short / medium / long；style 为 bullets / paragraph / executive 或自定义要求；
+/// 执行期间发送 job:progress 事件，可用 cancel_job 取消
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn summarize_document(
+    app: AppHandle,
+    project_id: String,
+    document_id: String,
+    length: Option<String>,
+    style: Option<String>,
+    provider: Option<String>,
+    api_key: Option<String>,
+    model: Option<String>,
+    base_url: Option<String>,
+    job_id: Option<String>,
+) -> Result<String> {
+    let state = app.state::<AppState>().inner().clone();
+    let document = Document::load(&state.get_document_path(&project_id, &document_id))
+        .map_err(|_| AppError::DocumentNotFound(document_id.clone()))?;
+    let body = if document.content.trim().is_empty() { &document.ai_generated_content } else { &document.content };
+    if body.trim().is_empty() {
+        return Err(AppError::AIError("文档内容为空，无法生成摘要".to_string()));
+    }
+
+    let project = project_ai_settings(&app, Some(&project_id));
+    let config = get_ai_config(&app, project.as_ref(), provider, api_key, model, base_url);
+    let (length, style) = (length.unwrap_or_default(), style.unwrap_or_default());
+    let job = jobs::start(&app, "summarize_document", job_id);
+    let result = summarize_chunks(&app, &config, &job, &document.title, body, &length, &style).await;
+    jobs::finish(&job, &result.as_ref().map_err(|e| e.to_string()));
+    result
+}
+
+async fn summarize_chunks(
+    app: &AppHandle,
+    config: &AIConfig,
+    job: &jobs::Job,
+    title: &str,
+    body: &str,
+    length: &str,
+    style: &str,
+) -> Result<String> {
+    let max_tokens = summarize::chunk_tokens(&config.get_default_model());
+    let call = |messages: Vec<ChatMessage>| async move {
+        let started = Instant::now();
+        let result = chat_with_config(config, messages, Some(0.3), None, None, false).await;
+        record_health(app, config, CallKind::Chat, started, &result);
+        result
+    };
+
+    let mut source = body.to_string();
+    let mut partial = false;
+    let mut round = 0;
+    loop {
+        let chunks = summarize::split_chunks(&source, max_tokens);
+        if chunks.len() <= 1 {
+            break;
+        }
+        round += 1;
+        let mut notes = Vec::with_capacity(chunks.len());
+        for (i, chunk) in chunks.iter().enumerate() {
+            job.check().map_err(AppError::AIError)?;
+            // 提炼阶段占 90%：第一轮占前一半，之后每轮占剩余部分的一半
+            let percent = 90.0 * (1.0 - 0.5f32.powi(round - 1) * (1.0 - 0.5 * i as f32 / chunks.len() as f32));
+            job.progress(percent, format!("正在提炼第 {}/{} 部分", i + 1, chunks.len()));
+            notes.push(call(summarize::chunk_messages(title, chunk, i, chunks.len())).await?);
+        }
+        let merged = notes.join("\n\n");
+        let shrunk = context_window::estimate_tokens(&merged) < context_window::estimate_tokens(&source);
+        source = merged;
+        partial = true;
+        // 要点没有变短（模型几乎原样输出）时不再继续合并，避免无限循环
+        if !shrunk {
+            break;
+        }
+    }
+
+    job.check().map_err(AppError::AIError)?;
+    job.progress(90.0, "正在生成摘要");
+    call(summarize::final_messages(title, &source, partial, length, style)).await
+}
+
+/// 文档翻译：按标题和段落分段翻译并保留 Markdown 结构；每段完成后发送 ai:translate:segment 事件，
+/// saveAsNew 为 true 时把译文另存为新文档；可用 cancel_job 取消
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn translate_document(
+    app: AppHandle,
+    project_id: String,
+    document_id: String,
+    target_lang: String,
+    save_as_new: Option<bool>,
+    provider: Option<String>,
+    api_key: Option<String>,
+    model: Option<String>,
+    base_url: Option<String>,
+    job_id: Option<String>,
+) -> Result<TranslationResult> {
+    let state = app.state::<AppState>().inner().clone();
+    let document = Document::load(&state.get_document_path(&project_id, &document_id))
+        .map_err(|_| AppError::DocumentNotFound(document_id.clone()))?;
+    if document.content.trim().is_empty() {
+        return Err(AppError::AIError("文档内容为空，无法翻译".to_string()));
+    }
+
+    let project = project_ai_settings(&app, Some(&project_id));
+    let config = get_ai_config(&app, project.as_ref(), provider, api_key, model, base_url);
+    let glossary = glossary::prompt(&glossary::load(&state.config.projects_dir.join(&project_id)));
+    let segments = translate::split_segments(&document.content, translate::MAX_SEGMENT_TOKENS);
+    let job = jobs::start(&app, "translate_document", job_id);
+
+    let mut translated = Vec::with_capacity(segments.len());
+    let mut result = Ok(());
+    for (i, segment) in segments.iter().enumerate() {
+        if let Err(e) = job.check() {
+            result = Err(AppError::AIError(e));
+            break;
+        }
+        job.progress(100.0 * i as f32 / segments.len() as f32, format!("正在翻译第 {}/{} 段", i + 1, segments.len()));
+        let text = if segment.translate {
+            let started = Instant::now();
+            let messages = translate::build_messages(&segment.text, &target_lang, glossary.clone());
+            let output = chat_with_config(&config, messages, Some(0.2), None, None, false).await;
+            record_health(&app, &config, CallKind::Chat, started, &output);
+            match output {
+                Ok(output) => translate::clean_output(&output),
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        } else {
+            segment.text.clone()
+        };
+        let _ = app.emit("ai:translate:segment", json!({
+            "jobId": job.id(),
+            "index": i,
+            "total": segments.len(),
+            "content": text
+        }));
+        translated.push(text);
+    }
+    jobs::finish(&job, &result.as_ref().map(|_| ()).map_err(|e| e.to_string()));
+    result?;
+
+    let content = translated.join("\n\n");
+    let new_document = if save_as_new.unwrap_or(false) {
+        let title = format!("{}（{}）", document.title, translate::language_name(&target_lang));
+        let mut new_document = Document::new(project_id.clone(), title, document.metadata.author.clone());
+        new_document.metadata.tags = document.metadata.tags.clone();
+        new_document.metadata.word_count = content.split_whitespace().count();
+        new_document.metadata.character_count = content.chars().count();
+        new_document.content = content.clone();
+        new_document.save(&state.get_document_path(&project_id, &new_document.id))?;
+        Some(new_document)
+    } else {
+        None
+    };
+    Ok(TranslationResult { content, segments: segments.len(), document: new_document })
+}
+
+/// AI 局部编辑：模型只返回修改块（SEARCH/REPLACE 或 unified diff），校验全部可以唯一定位后一次性写入，
+/// 并记录为新版本；dryRun 为 true 时只返回修改结果不写入
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn apply_ai_edit(
+    app: AppHandle,
+    project_id: String,
+    document_id: String,
+    instruction: String,
+    dry_run: Option<bool>,
+    provider: Option<String>,
+    api_key: Option<String>,
+    model: Option<String>,
+    base_url: Option<String>,
+) -> Result<AiEditResult> {
+    let state = app.state::<AppState>().inner().clone();
+    let doc_path = state.get_document_path(&project_id, &document_id);
+    let document = Document::load(&doc_path).map_err(|_| AppError::DocumentNotFound(document_id.clone()))?;
+    let dry_run = dry_run.unwrap_or(false);
+    if !dry_run {
+        document.ensure_unlocked()?;
+    }
+
+    let project = project_ai_settings(&app, Some(&project_id));
+    let config = get_ai_config(&app, project.as_ref(), provider, api_key, model, base_url);
+    let messages = with_project_system_prompt(&app, ai_edit::build_messages(&document.content, &instruction), Some(&project_id), project.as_ref());
+    let started = Instant::now();
+    let result = chat_with_config(&config, messages, Some(0.2), None, None, false).await;
+    record_health(&app, &config, CallKind::Chat, started, &result);
+
+    let blocks = ai_edit::parse_blocks(&strip_think(&result?)).map_err(AppError::AIError)?;
+    if blocks.is_empty() {
+        return Ok(AiEditResult { content: document.content, changes: Vec::new(), applied: false, unchanged: true });
+    }
+    let (content, changes) = ai_edit::apply(&document.content, &blocks).map_err(AppError::AIError)?;
+    if dry_run {
+        return Ok(AiEditResult { content, changes, applied: false, unchanged: false });
+    }
+
+    // 重新读取后再写入，避免覆盖模型生成期间的其他修改
+    let original = document.content;
+    let mut document = Document::load(&doc_path)?;
+    if document.content != original {
+        return Err(AppError::AIError("文档在 AI 编辑期间已被修改，请重试".to_string()));
+    }
+    let description: String = instruction.chars().take(50).collect();
+    document.create_version(
+        content.clone(),
+        document.author_notes.clone(),
+        document.ai_generated_content.clone(),
+        "ai".to_string(),
+        Some(format!("AI 编辑：{}", description)),
+        document.plugin_data.clone(),
+        document.enabled_plugins.clone(),
+        document.composed_content.clone(),
+    );
+    document.content = content.clone();
+    document.metadata.word_count = content.split_whitespace().count();
+    document.metadata.character_count = content.chars().count();
+    document.save(&doc_path)?;
+    Ok(AiEditResult { content, changes, applied: true, unchanged: false })
+}
+
+/// 由 AI 为文档提出新的标题结构；条目的 id 引用原章节，配合 restructure_by_outline 重组文档
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn generate_outline(
+    app: AppHandle,
+    content: String,
+    instruction: Option<String>,
+    provider: Option<String>,
+    api_key: Option<String>,
+    model: Option<String>,
+    base_url: Option<String>,
+    project_id: Option<String>,
+) -> Result<Vec<OutlineItem>> {
+    let sections = outline::split_sections(&content);
+    if sections.sections.is_empty() {
+        return Err(AppError::AIError("文档中没有标题，无法生成大纲".to_string()));
+    }
+    let project = project_ai_settings(&app, project_id.as_deref());
+    let config = get_ai_config(&app, project.as_ref(), provider, api_key, model, base_url);
+    let messages = outline::build_messages(&sections, instruction.as_deref());
+    let started = Instant::now();
+    let result = chat_with_config(&config, messages, Some(0.3), None, None, true).await;
+    record_health(&app, &config, CallKind::Chat, started, &result);
+    outline::parse_outline(&strip_think(&result?), &sections).map_err(AppError::AIError)
+}
+
 #[tauri::command]
 pub async fn generate_content_stream(
     app: AppHandle,
@@ -407,7 +966,8 @@ pub async fn generate_content_stream(
     enable_web_search: Option<bool>,
     enable_thinking: Option<bool>,
     request_id: Option<String>,
-) -> Result<String> {
+    project_id: Option<String>,
+) -> Result<StreamResult> {
     let user_prompt = if current_content.is_empty() {
         author_notes.clone()
     } else {
@@ -423,23 +983,50 @@ pub async fn generate_content_stream(
         messages.push(ChatMessage {
             role: "system".to_string(),
             content: sp,
+            images: Vec::new(),
         });
     }
 
     // Add conversation history if provided (exclude the last message as it will be the current user prompt)
-    if let Some(history) = conversation_history {
+    let mut images = Vec::new();
+    if let Some(mut history) = conversation_history {
         // Take all but the last message if there's history, since the current user message will be added
-        let history_len = history.len().saturating_sub(1);
-        messages.extend_from_slice(&history[..history_len]);
+        // 当前用户消息附带的图片随新的提示词一起发送
+        if let Some(current) = history.pop().filter(|m| m.role == "user") {
+            images = current.images;
+        }
+        messages.extend(history);
     }
 
     // Add current user message
     messages.push(ChatMessage {
         role: "user".to_string(),
         content: user_prompt,
+        images,
     });
 
-    chat_stream(app, messages, provider, api_key, model, base_url, window, enable_web_search, enable_thinking, None, None, request_id).await
+    let result = chat_stream(app.clone(), messages, provider, api_key, model, base_url, window, enable_web_search, enable_thinking, None, None, request_id.clone(), project_id).await;
+
+    // 通知订阅了 ai:generation-completed 的插件（如大纲插件刷新）
+    if let Ok(stream) = &result {
+        plugin_bus::publish(&app, "ai:generation-completed", json!({
+            "requestId": request_id,
+            "contentLength": stream.content.chars().count(),
+        }));
+    }
+
+    result
+}
+
+/// 各 AI 服务端点的成功率与延迟统计
+#[tauri::command]
+pub fn get_provider_health(app: AppHandle) -> Vec<ProviderHealth> {
+    provider_health::summary(&get_provider_health_path(&app))
+}
+
+#[tauri::command]
+pub fn clear_provider_health(app: AppHandle) -> std::result::Result<(), String> {
+    provider_health::clear(&get_provider_health_path(&app))
 }
 
 #[tauri::command]
@@ -450,9 +1037,19 @@ pub async fn test_api_connection(
     model: Option<String>,
     base_url: Option<String>,
 ) -> Result<String> {
-    let config = get_ai_config(&app, provider, api_key, model, base_url);
-    let client = reqwest::Client::new();
-    let url = format!("{}/chat/completions", config.get_base_url());
+    let config = get_ai_config(&app, None, provider, api_key, model, base_url);
+    let started = Instant::now();
+    let result = test_connection_with_config(&config).await;
+    record_health(&app, &config, CallKind::Test, started, &result);
+    result
+}
+
+async fn test_connection_with_config(config: &AIConfig) -> Result<String> {
+    let client = config.http_client().map_err(AppError::AIError)?;
+    if config.provider == "ollama" {
+        return test_ollama_connection(config, &client).await;
+    }
+    let url = config.endpoint_url("chat/completions");
 
     let request_body = json!({
         "messages": [{"role": "user", "content": "Hi"}],
@@ -461,35 +1058,215 @@ pub async fn test_api_connection(
         "stream": false
     });
 
-    let mut req_builder = client
-        .post(&url)
-        .header("Content-Type", "application/json")
-        .json(&request_body);
+    let req_builder = config.authorize(
+        client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .json(&request_body),
+    );
 
-    if let Some(key) = &config.api_key {
-        match config.provider.as_str() {
-            "anthropic" => {
+    let response = req_builder
+        .timeout(Duration::from_secs(15))
+        .send()
+        .await
+        .map_err(|e| AppError::AIError(format!("连接失败: {}", e)))?;
+
+    if response.status().is_success() {
+        Ok(format!("连接成功！模型: {}", config.get_default_model()))
+    } else {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        Err(AppError::AIError(format!("API 返回错误 ({}): {}", status, error_text)))
+    }
+}
+
+/// 列出服务商可用的模型，供设置页选择；未传入密钥时使用钥匙串中保存的密钥
+#[tauri::command]
+pub async fn list_models(
+    app: AppHandle,
+    provider: Option<String>,
+    api_key: Option<String>,
+    base_url: Option<String>,
+) -> Result<Vec<ModelInfo>> {
+    let config = get_ai_config(&app, None, provider, api_key, None, base_url);
+    let client = config.http_client().map_err(AppError::AIError)?;
+
+    let (url, value) = match config.provider.as_str() {
+        // Anthropic：原生 /models 接口，分页返回
+        "anthropic" => {
+            let url = format!("{}/models?limit=1000", config.get_base_url());
+            let mut req_builder = client.get(&url).header("anthropic-version", "2023-06-01");
+            if let Some(key) = &config.api_key {
                 req_builder = req_builder.header("x-api-key", key);
             }
-            _ => {
+            (url, fetch_models_json(req_builder).await?)
+        }
+        // Gemini：原生接口才返回 inputTokenLimit / outputTokenLimit
+        "gemini" => {
+            let url = format!("{}/models?pageSize=1000", config.gemini_root());
+            let mut req_builder = client.get(&url);
+            if let Some(key) = &config.api_key {
+                req_builder = req_builder.header("x-goog-api-key", key);
+            }
+            (url, fetch_models_json(req_builder).await?)
+        }
+        // Ollama：本地已下载的模型
+        "ollama" => {
+            let url = format!("{}/api/tags", config.ollama_root());
+            (url.clone(), fetch_models_json(client.get(&url)).await?)
+        }
+        // Azure OpenAI：资源可用的模型（部署名由用户在 Azure 门户中设置）
+        "azure" => {
+            let (endpoint, version) = config.azure_endpoint();
+            let url = format!("{}/openai/models?api-version={}", endpoint, version);
+            (url.clone(), fetch_models_json(config.authorize(client.get(&url))).await?)
+        }
+        _ => {
+            let url = format!("{}/models", config.get_base_url().trim_end_matches('/'));
+            let mut req_builder = client.get(&url);
+            if let Some(key) = &config.api_key {
                 req_builder = req_builder.header("Authorization", format!("Bearer {}", key));
             }
+            (url, fetch_models_json(req_builder).await?)
         }
+    };
+
+    let mut models = parse_models(&value);
+    if models.is_empty() {
+        return Err(AppError::AIError(format!("{} 未返回模型列表", url)));
     }
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    models.dedup_by(|a, b| a.id == b.id);
+    Ok(models)
+}
 
+/// 语音转写：上传音频到 Whisper 兼容接口，返回带时间戳的 Markdown 文本
+/// model 为转写模型（默认 whisper-1），与对话模型无关；language 为 ISO-639-1 语言代码
+#[tauri::command]
+pub async fn transcribe_audio(
+    app: AppHandle,
+    path: String,
+    provider: Option<String>,
+    api_key: Option<String>,
+    base_url: Option<String>,
+    model: Option<String>,
+    language: Option<String>,
+) -> Result<String> {
+    let config = get_ai_config(&app, None, provider, api_key, None, base_url);
+    if matches!(config.provider.as_str(), "anthropic" | "ollama") {
+        return Err(AppError::AIError(format!("{} 不提供语音转写接口，请使用 OpenAI 兼容服务", config.provider)));
+    }
+    let file_path = std::path::PathBuf::from(&path);
+    let mime = transcription::audio_mime(&file_path)
+        .ok_or_else(|| AppError::AIError(format!("不支持的音频格式: {}", path)))?;
+    let size = std::fs::metadata(&file_path).map_err(|e| AppError::AIError(format!("读取音频失败: {}", e)))?.len();
+    if size > transcription::MAX_AUDIO_BYTES {
+        return Err(AppError::AIError(format!(
+            "音频文件过大（{:.1} MB），转写接口上限为 {} MB",
+            size as f64 / 1024.0 / 1024.0,
+            transcription::MAX_AUDIO_BYTES / 1024 / 1024
+        )));
+    }
+    let data = tokio::fs::read(&file_path).await.map_err(|e| AppError::AIError(format!("读取音频失败: {}", e)))?;
+    let file_name = file_path.file_name().and_then(|n| n.to_str()).unwrap_or("audio");
+
+    let model = model.filter(|m| !m.trim().is_empty()).unwrap_or_else(|| transcription::DEFAULT_MODEL.to_string());
+    let mut fields = vec![("model", model.as_str()), ("response_format", "verbose_json")];
+    if let Some(language) = language.as_deref().filter(|l| !l.is_empty()) {
+        fields.push(("language", language));
+    }
+    let (content_type, body) = transcription::multipart_body(&fields, file_name, mime, &data);
+
+    // Azure 按转写模型名查找部署
+    let url = config.deployment_url(&model, "audio/transcriptions");
+    let req_builder = config
+        .http_client()
+        .map_err(AppError::AIError)?
+        .post(&url)
+        .header("Content-Type", content_type)
+        .body(body)
+        .timeout(Duration::from_secs(600));
+    let response = config
+        .authorize(req_builder)
+        .send()
+        .await
+        .map_err(|e| AppError::AIError(format!("Failed to connect to AI service: {}", e)))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(AppError::AIError(format!("语音转写失败 ({}): {}", status, error_text)));
+    }
+    let value: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| AppError::AIError(format!("Failed to parse response: {}", e)))?;
+    Ok(transcription::to_markdown(&value))
+}
+
+async fn fetch_models_json(req_builder: reqwest::RequestBuilder) -> Result<serde_json::Value> {
     let response = req_builder
         .timeout(Duration::from_secs(15))
         .send()
         .await
         .map_err(|e| AppError::AIError(format!("连接失败: {}", e)))?;
-
-    if response.status().is_success() {
-        Ok(format!("连接成功！模型: {}", config.get_default_model()))
-    } else {
+    if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_default();
-        Err(AppError::AIError(format!("API 返回错误 ({}): {}", status, error_text)))
+        return Err(AppError::AIError(format!("API 返回错误 ({}): {}", status, error_text)));
     }
+    response
+        .json()
+        .await
+        .map_err(|e| AppError::AIError(format!("Failed to parse response: {}", e)))
+}
+
+/// 解析各服务商的模型列表：OpenAI 兼容接口为 data[]，Gemini 和 Ollama 为 models[]
+fn parse_models(value: &serde_json::Value) -> Vec<ModelInfo> {
+    let items = value
+        .get("data")
+        .or_else(|| value.get("models"))
+        .and_then(|d| d.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let number = |item: &serde_json::Value, keys: &[&str]| keys.iter().find_map(|k| item.get(*k).and_then(|v| v.as_u64()));
+    let text = |item: &serde_json::Value, keys: &[&str]| {
+        keys.iter().find_map(|k| item.get(*k).and_then(|v| v.as_str())).map(str::to_string)
+    };
+
+    items
+        .iter()
+        .filter(|item| {
+            // Gemini 列表中包含嵌入等模型，只保留可对话的
+            item.get("supportedGenerationMethods")
+                .and_then(|m| m.as_array())
+                .is_none_or(|methods| methods.iter().any(|m| m.as_str() == Some("generateContent")))
+        })
+        .filter_map(|item| {
+            let id = text(item, &["id", "name", "model"])?;
+            Some(ModelInfo {
+                // Gemini 的 name 形如 models/gemini-2.5-pro
+                id: id.strip_prefix("models/").unwrap_or(&id).to_string(),
+                name: text(item, &["display_name", "displayName"]),
+                context_window: number(item, &["context_window", "context_length", "inputTokenLimit", "max_context_length"])
+                    .or_else(|| item.pointer("/top_provider/context_length").and_then(|v| v.as_u64())),
+                max_output_tokens: number(item, &["max_output_tokens", "outputTokenLimit", "max_completion_tokens"]),
+            })
+        })
+        .collect()
+}
+
+/// 记录调用结果到服务健康度统计
+fn record_health<T>(app: &AppHandle, config: &AIConfig, kind: CallKind, started: Instant, result: &Result<T>) {
+    let error = result.as_ref().err().map(|e| e.to_string());
+    provider_health::record(
+        &get_provider_health_path(app),
+        &config.provider,
+        &config.get_base_url(),
+        &config.get_default_model(),
+        kind,
+        started,
+        error.as_deref(),
+    );
 }
 
 /// OpenAI Responses API 非流式调用
@@ -502,7 +1279,7 @@ async fn call_openai_responses(
     let url = format!("{}/responses", config.get_base_url());
 
     let input: Vec<serde_json::Value> = messages.iter().map(|m| {
-        json!({ "role": m.role, "content": m.content })
+        json!({ "role": m.role, "content": m.responses_content() })
     }).collect();
 
     let mut request_body = json!({
@@ -563,7 +1340,7 @@ async fn call_anthropic_with_search(
         if msg.role == "system" {
             system_content = msg.content.clone();
         } else {
-            api_messages.push(json!({ "role": msg.role, "content": msg.content }));
+            api_messages.push(json!({ "role": msg.role, "content": msg.anthropic_content() }));
         }
     }
 
@@ -631,13 +1408,16 @@ async fn stream_sse_chat_completions(
     response: reqwest::Response,
     req_id: &str,
     window: &tauri::Window,
-) -> Result<String> {
+) -> Result<(StreamResult, Vec<serde_json::Value>)> {
     let mut stream = response.bytes_stream();
     use futures_util::StreamExt;
 
     let mut full_content = String::new();
     let mut buffer = Vec::new();
     let mut in_reasoning = false;
+    let mut usage = None;
+    // tool_calls 按 index 分片到达：首片带 id 和函数名，之后是 arguments 片段
+    let mut tool_calls: Vec<serde_json::Value> = Vec::new();
 
     while let Some(chunk_result) = stream.next().await {
         if is_stream_cancelled(req_id) {
@@ -677,6 +1457,11 @@ async fn stream_sse_chat_completions(
                 }
 
                 if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(data) {
+                    // include_usage 时最后一个数据块的 choices 为空，只带 usage
+                    if let Some(u) = json_val.get("usage").and_then(Usage::from_json) {
+                        usage = Some(u);
+                    }
+
                     let delta = json_val
                         .get("choices")
                         .and_then(|c| c.get(0))
@@ -687,6 +1472,12 @@ async fn stream_sse_chat_completions(
                             break;
                         }
 
+                        if let Some(calls) = delta.get("tool_calls").and_then(|t| t.as_array()) {
+                            for call in calls {
+                                merge_tool_call_delta(&mut tool_calls, call);
+                            }
+                        }
+
                         // 处理 reasoning_content（Qwen/DeepSeek/xAI 思考内容）
                         if let Some(reasoning) = delta.get("reasoning_content").and_then(|r| r.as_str()) {
                             if !reasoning.is_empty() {
@@ -741,7 +1532,242 @@ async fn stream_sse_chat_completions(
         full_content.push_str("</think>");
     }
 
-    Ok(full_content)
+    Ok((StreamResult { content: full_content, usage }, tool_calls))
+}
+
+/// 合并一个 tool_call 增量片段
+fn merge_tool_call_delta(tool_calls: &mut Vec<serde_json::Value>, delta: &serde_json::Value) {
+    let index = delta.get("index").and_then(|i| i.as_u64()).map(|i| i as usize).unwrap_or(tool_calls.len());
+    while tool_calls.len() <= index {
+        tool_calls.push(json!({
+            "id": "",
+            "type": "function",
+            "function": { "name": "", "arguments": "" }
+        }));
+    }
+    let call = &mut tool_calls[index];
+    if let Some(id) = delta.get("id").and_then(|v| v.as_str()).filter(|v| !v.is_empty()) {
+        call["id"] = json!(id);
+    }
+    for field in ["name", "arguments"] {
+        if let Some(part) = delta.pointer(&format!("/function/{}", field)).and_then(|v| v.as_str()) {
+            let merged = format!("{}{}", call["function"][field].as_str().unwrap_or_default(), part);
+            call["function"][field] = json!(merged);
+        }
+    }
+}
+
+/// Ollama 请求体：采样参数放在 options 中，思考模式仅在开启时传入（不支持思考的模型会报错）
+fn ollama_request_body(
+    config: &AIConfig,
+    messages: Vec<serde_json::Value>,
+    temperature: Option<f64>,
+    max_tokens: Option<u32>,
+    stream: bool,
+) -> serde_json::Value {
+    let mut options = json!({ "temperature": temperature.or(config.temperature).unwrap_or(0.7) });
+    if let Some(mt) = max_tokens {
+        options["num_predict"] = json!(mt);
+    }
+    json!({
+        "model": config.get_default_model(),
+        "messages": messages,
+        "stream": stream,
+        "options": options
+    })
+}
+
+/// OpenAI 格式的消息转为 Ollama 格式：tool_calls 的 arguments 为 JSON 对象而不是字符串，
+/// 多段 content 拆为文本和 images（纯 base64）
+fn to_ollama_messages(messages: &[serde_json::Value]) -> Vec<serde_json::Value> {
+    messages
+        .iter()
+        .map(|m| {
+            let mut m = m.clone();
+            if let Some(parts) = m.get("content").and_then(|c| c.as_array()).cloned() {
+                let text: Vec<&str> = parts.iter().filter_map(|p| p.get("text").and_then(|t| t.as_str())).collect();
+                let images: Vec<String> = parts
+                    .iter()
+                    .filter_map(|p| p.pointer("/image_url/url").and_then(|u| u.as_str()))
+                    .map(|url| split_image(url).1)
+                    .collect();
+                m["content"] = json!(text.join("\n"));
+                m["images"] = json!(images);
+            }
+            if let Some(calls) = m.get_mut("tool_calls").and_then(|c| c.as_array_mut()) {
+                for call in calls {
+                    let args = call.pointer("/function/arguments").and_then(|a| a.as_str()).map(str::to_string);
+                    if let Some(args) = args {
+                        call["function"]["arguments"] = serde_json::from_str(&args).unwrap_or_else(|_| json!({}));
+                    }
+                }
+            }
+            m
+        })
+        .collect()
+}
+
+async fn send_ollama_chat(client: &reqwest::Client, config: &AIConfig, body: &serde_json::Value, timeout: Option<Duration>) -> Result<reqwest::Response> {
+    let url = format!("{}/api/chat", config.ollama_root());
+    let mut req_builder = client.post(&url).json(body);
+    if let Some(timeout) = timeout {
+        req_builder = req_builder.timeout(timeout);
+    }
+    // 经反向代理暴露的 Ollama 可能需要鉴权
+    if let Some(key) = config.api_key.as_ref().filter(|k| !k.is_empty()) {
+        req_builder = req_builder.header("Authorization", format!("Bearer {}", key));
+    }
+    let response = req_builder.send().await.map_err(|e| {
+        AppError::AIError(format!("无法连接 Ollama（{}），请确认已启动 ollama serve: {}", config.ollama_root(), e))
+    })?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(AppError::AIError(format!("Ollama 返回错误 ({}): {}", status, error_text)));
+    }
+    Ok(response)
+}
+
+/// Ollama 非流式对话
+async fn call_ollama_chat(
+    config: &AIConfig,
+    client: &reqwest::Client,
+    messages: &[ChatMessage],
+    temperature: Option<f64>,
+    max_tokens: Option<u32>,
+    json_mode: bool,
+) -> Result<String> {
+    let messages: Vec<serde_json::Value> = messages.iter().map(ChatMessage::to_openai).collect();
+    let mut body = ollama_request_body(config, to_ollama_messages(&messages), temperature, max_tokens, false);
+    if json_mode {
+        body["format"] = json!("json");
+    }
+    // 本地模型首次加载可能较慢
+    let response = send_ollama_chat(client, config, &body, Some(Duration::from_secs(300))).await?;
+    let json_resp: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| AppError::AIError(format!("Failed to parse response: {}", e)))?;
+    Ok(json_resp.pointer("/message/content").and_then(|c| c.as_str()).unwrap_or_default().to_string())
+}
+
+/// Ollama 流式对话：响应为逐行 JSON（NDJSON），不是 SSE
+async fn stream_ollama_chat(
+    config: &AIConfig,
+    client: &reqwest::Client,
+    messages: &[serde_json::Value],
+    thinking: bool,
+    req_id: &str,
+    window: &tauri::Window,
+) -> Result<StreamResult> {
+    let mut body = ollama_request_body(config, to_ollama_messages(messages), None, None, true);
+    if thinking {
+        body["think"] = json!(true);
+    }
+    let response = send_ollama_chat(client, config, &body, None).await?;
+
+    let mut stream = response.bytes_stream();
+    use futures_util::StreamExt;
+
+    let mut full_content = String::new();
+    let mut buffer = Vec::new();
+    let mut in_reasoning = false;
+    let mut usage = None;
+    let emit = |full_content: &mut String, text: &str| {
+        full_content.push_str(text);
+        let _ = window.emit("ai:stream:chunk", json!({
+            "request_id": req_id,
+            "content": text
+        }));
+    };
+
+    'outer: while let Some(chunk_result) = stream.next().await {
+        if is_stream_cancelled(req_id) {
+            break;
+        }
+
+        let chunk = chunk_result
+            .map_err(|e| AppError::AIError(format!("Stream error: {}", e)))?;
+
+        if buffer.len() + chunk.len() > MAX_BUFFER_SIZE {
+            return Err(AppError::AIError("Response too large, exceeded buffer limit".to_string()));
+        }
+
+        buffer.extend_from_slice(&chunk);
+
+        while let Some(pos) = buffer.iter().position(|&b| b == b'\n') {
+            let line_bytes: Vec<u8> = buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line_bytes);
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Ok(json_val) = serde_json::from_str::<serde_json::Value>(line) else { continue };
+
+            if let Some(error) = json_val.get("error").and_then(|e| e.as_str()) {
+                return Err(AppError::AIError(format!("Ollama 返回错误: {}", error)));
+            }
+
+            // 思考内容（think 模式）用 <think> 标签包裹，与其他服务商一致
+            let message = json_val.get("message");
+            if let Some(reasoning) = message.and_then(|m| m.get("thinking")).and_then(|t| t.as_str()).filter(|t| !t.is_empty()) {
+                if !in_reasoning {
+                    emit(&mut full_content, "<think>");
+                    in_reasoning = true;
+                }
+                emit(&mut full_content, reasoning);
+            }
+            if let Some(content) = message.and_then(|m| m.get("content")).and_then(|c| c.as_str()).filter(|c| !c.is_empty()) {
+                if in_reasoning {
+                    emit(&mut full_content, "</think>");
+                    in_reasoning = false;
+                }
+                emit(&mut full_content, content);
+            }
+
+            if json_val.get("done").and_then(|d| d.as_bool()).unwrap_or(false) {
+                // 最后一行带 prompt_eval_count / eval_count
+                let count = |key: &str| json_val.get(key).and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+                usage = Some(Usage::new(count("prompt_eval_count"), count("eval_count")));
+                break 'outer;
+            }
+        }
+    }
+
+    if in_reasoning {
+        emit(&mut full_content, "</think>");
+    }
+
+    Ok(StreamResult { content: full_content, usage })
+}
+
+/// Ollama 连接测试：检查服务是否运行、模型是否已下载
+async fn test_ollama_connection(config: &AIConfig, client: &reqwest::Client) -> Result<String> {
+    let response = client
+        .get(format!("{}/api/tags", config.ollama_root()))
+        .timeout(Duration::from_secs(15))
+        .send()
+        .await
+        .map_err(|e| AppError::AIError(format!("连接失败，请确认已启动 ollama serve: {}", e)))?;
+    if !response.status().is_success() {
+        return Err(AppError::AIError(format!("Ollama 返回错误 ({})", response.status())));
+    }
+    let tags: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| AppError::AIError(format!("Failed to parse response: {}", e)))?;
+    let model = config.get_default_model();
+    // 未写标签的模型名等同于 :latest
+    let wanted = if model.contains(':') { model.clone() } else { format!("{}:latest", model) };
+    let installed = tags
+        .get("models")
+        .and_then(|m| m.as_array())
+        .is_some_and(|models| models.iter().filter_map(|m| m.get("name").and_then(|n| n.as_str())).any(|n| n == model || n == wanted));
+    if installed {
+        Ok(format!("连接成功！模型: {}", model))
+    } else {
+        Err(AppError::AIError(format!("Ollama 已连接，但未找到模型 {}，请先运行 ollama pull {}", model, model)))
+    }
 }
 
 /// OpenAI Responses API 流式调用（支持内置 web_search 工具）
@@ -750,8 +1776,8 @@ async fn stream_openai_responses(
     messages: &[ChatMessage],
     req_id: &str,
     window: &tauri::Window,
-) -> Result<String> {
-    let client = reqwest::Client::new();
+) -> Result<StreamResult> {
+    let client = config.http_client().map_err(AppError::AIError)?;
     let base_url = config.get_base_url();
     let url = format!("{}/responses", base_url);
 
@@ -759,7 +1785,7 @@ async fn stream_openai_responses(
     let input: Vec<serde_json::Value> = messages.iter().map(|m| {
         json!({
             "role": m.role,
-            "content": m.content
+            "content": m.responses_content()
         })
     }).collect();
 
@@ -798,6 +1824,7 @@ async fn stream_openai_responses(
 
     let mut full_content = String::new();
     let mut buffer = Vec::new();
+    let mut usage = None;
 
     while let Some(chunk_result) = stream.next().await {
         if is_stream_cancelled(req_id) {
@@ -858,6 +1885,10 @@ async fn stream_openai_responses(
                                 }
                             }
                         }
+                        // 完成事件带本次用量
+                        "response.completed" => {
+                            usage = json_val.pointer("/response/usage").and_then(Usage::from_json);
+                        }
                         _ => {}
                     }
                 }
@@ -865,17 +1896,22 @@ async fn stream_openai_responses(
         }
     }
 
-    Ok(full_content)
+    Ok(StreamResult { content: full_content, usage })
 }
 
-/// Anthropic Claude 原生 Messages API 流式调用（支持 web_search server tool）
-async fn stream_anthropic_with_search(
+/// Anthropic Extended Thinking 的思考预算（token），max_tokens 须大于该值
+const ANTHROPIC_THINKING_BUDGET: u32 = 10000;
+
+/// Anthropic Claude 原生 Messages API 流式调用（支持 web_search server tool 和 Extended Thinking）
+async fn stream_anthropic_messages(
     config: &AIConfig,
     messages: &[ChatMessage],
+    web_search: bool,
+    thinking: bool,
     req_id: &str,
     window: &tauri::Window,
-) -> Result<String> {
-    let client = reqwest::Client::new();
+) -> Result<StreamResult> {
+    let client = config.http_client().map_err(AppError::AIError)?;
     let base_url = config.get_base_url();
     let url = format!("{}/messages", base_url);
 
@@ -889,22 +1925,30 @@ async fn stream_anthropic_with_search(
         } else {
             api_messages.push(json!({
                 "role": msg.role,
-                "content": msg.content
+                "content": msg.anthropic_content()
             }));
         }
     }
 
     let mut request_body = json!({
         "model": config.get_default_model(),
-        "max_tokens": 8192,
+        "max_tokens": if thinking { ANTHROPIC_THINKING_BUDGET + 8192 } else { 8192 },
         "messages": api_messages,
-        "tools": [{
+        "stream": true
+    });
+
+    if web_search {
+        request_body["tools"] = json!([{
             "type": "web_search_20250305",
             "name": "web_search",
             "max_uses": 5
-        }],
-        "stream": true
-    });
+        }]);
+    }
+
+    // 开启思考时不能指定 temperature（固定为 1）
+    if thinking {
+        request_body["thinking"] = json!({ "type": "enabled", "budget_tokens": ANTHROPIC_THINKING_BUDGET });
+    }
 
     if !system_content.is_empty() {
         request_body["system"] = json!(system_content);
@@ -914,9 +1958,12 @@ async fn stream_anthropic_with_search(
         .post(&url)
         .header("Content-Type", "application/json")
         .header("anthropic-version", "2023-06-01")
-        .header("anthropic-beta", "web-search-2025-03-05")
         .body(request_body.to_string());
 
+    if web_search {
+        req_builder = req_builder.header("anthropic-beta", "web-search-2025-03-05");
+    }
+
     if let Some(key) = &config.api_key {
         req_builder = req_builder.header("x-api-key", key);
     }
@@ -940,6 +1987,10 @@ async fn stream_anthropic_with_search(
 
     let mut full_content = String::new();
     let mut buffer = Vec::new();
+    let mut in_reasoning = false;
+    // 输入用量在 message_start 中，输出用量在 message_delta 中（累计值）
+    let mut input_tokens = None;
+    let mut output_tokens = None;
 
     while let Some(chunk_result) = stream.next().await {
         if is_stream_cancelled(req_id) {
@@ -977,6 +2028,15 @@ async fn stream_anthropic_with_search(
                                     "text_delta" => {
                                         if let Some(text) = delta.get("text").and_then(|t| t.as_str()) {
                                             if !text.is_empty() && !is_stream_cancelled(req_id) {
+                                                // 从思考切换到正文，关闭 think 标签
+                                                if in_reasoning {
+                                                    full_content.push_str("</think>");
+                                                    let _ = window.emit("ai:stream:chunk", json!({
+                                                        "request_id": req_id,
+                                                        "content": "</think>"
+                                                    }));
+                                                    in_reasoning = false;
+                                                }
                                                 full_content.push_str(text);
                                                 let _ = window.emit("ai:stream:chunk", json!({
                                                     "request_id": req_id,
@@ -988,7 +2048,12 @@ async fn stream_anthropic_with_search(
                                     "thinking_delta" => {
                                         if let Some(thinking) = delta.get("thinking").and_then(|t| t.as_str()) {
                                             if !thinking.is_empty() && !is_stream_cancelled(req_id) {
-                                                let think_text = format!("<think>{}</think>", thinking);
+                                                let think_text = if in_reasoning {
+                                                    thinking.to_string()
+                                                } else {
+                                                    in_reasoning = true;
+                                                    format!("<think>{}", thinking)
+                                                };
                                                 full_content.push_str(&think_text);
                                                 let _ = window.emit("ai:stream:chunk", json!({
                                                     "request_id": req_id,
@@ -1001,6 +2066,14 @@ async fn stream_anthropic_with_search(
                                 }
                             }
                         }
+                        "message_start" => {
+                            input_tokens = json_val.pointer("/message/usage/input_tokens").and_then(|v| v.as_u64());
+                        }
+                        "message_delta" => {
+                            if let Some(tokens) = json_val.pointer("/usage/output_tokens").and_then(|v| v.as_u64()) {
+                                output_tokens = Some(tokens);
+                            }
+                        }
                         _ => {}
                     }
                 }
@@ -1008,7 +2081,224 @@ async fn stream_anthropic_with_search(
         }
     }
 
-    Ok(full_content)
+    // 安全关闭：流结束时仍在思考状态
+    if in_reasoning {
+        full_content.push_str("</think>");
+        let _ = window.emit("ai:stream:chunk", json!({
+            "request_id": req_id,
+            "content": "</think>"
+        }));
+    }
+
+    let usage = (input_tokens.is_some() || output_tokens.is_some())
+        .then(|| Usage::new(input_tokens.unwrap_or(0) as u32, output_tokens.unwrap_or(0) as u32));
+    Ok(StreamResult { content: full_content, usage })
+}
+
+/// Gemini 原生请求体：system 消息放在 systemInstruction，assistant 角色为 model
+fn gemini_request_body(
+    config: &AIConfig,
+    messages: &[ChatMessage],
+    temperature: Option<f64>,
+    max_tokens: Option<u32>,
+    web_search: bool,
+    thinking: bool,
+) -> serde_json::Value {
+    let system: Vec<&str> = messages.iter().filter(|m| m.role == "system").map(|m| m.content.as_str()).collect();
+    let contents: Vec<serde_json::Value> = messages
+        .iter()
+        .filter(|m| m.role != "system")
+        .map(|m| json!({ "role": if m.role == "assistant" { "model" } else { "user" }, "parts": m.gemini_parts() }))
+        .collect();
+
+    let mut generation_config = json!({ "temperature": temperature.or(config.temperature).unwrap_or(0.7) });
+    if let Some(mt) = max_tokens {
+        generation_config["maxOutputTokens"] = json!(mt);
+    }
+    if thinking {
+        generation_config["thinkingConfig"] = json!({ "includeThoughts": true });
+    }
+    let mut body = json!({ "contents": contents, "generationConfig": generation_config });
+    if !system.is_empty() {
+        body["systemInstruction"] = json!({ "parts": [{ "text": system.join("\n\n") }] });
+    }
+    if web_search {
+        body["tools"] = json!([{ "google_search": {} }]);
+    }
+    body
+}
+
+/// Gemini 接口地址，如 {根地址}/models/gemini-2.5-flash:generateContent
+fn gemini_url(config: &AIConfig, method: &str) -> String {
+    let model = config.get_default_model();
+    format!("{}/models/{}:{}", config.gemini_root(), model.trim_start_matches("models/"), method)
+}
+
+/// 联网搜索引用的网页，整理为 Markdown 来源列表（按 uri 去重）
+fn gemini_sources(chunks: &[serde_json::Value]) -> String {
+    let mut seen = Vec::new();
+    let mut lines = Vec::new();
+    for web in chunks.iter().filter_map(|c| c.get("web")) {
+        let Some(uri) = web.get("uri").and_then(|u| u.as_str()) else { continue };
+        if seen.contains(&uri) {
+            continue;
+        }
+        seen.push(uri);
+        let title = web.get("title").and_then(|t| t.as_str()).unwrap_or(uri);
+        lines.push(format!("{}. [{}]({})", lines.len() + 1, title, uri));
+    }
+    if lines.is_empty() {
+        return String::new();
+    }
+    format!("\n\n**参考来源**\n\n{}", lines.join("\n"))
+}
+
+fn gemini_usage(value: &serde_json::Value) -> Option<Usage> {
+    let meta = value.get("usageMetadata")?;
+    let count = |key: &str| meta.get(key).and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+    // 思考消耗的 token 按输出计费
+    Some(Usage::new(count("promptTokenCount"), count("candidatesTokenCount") + count("thoughtsTokenCount")))
+}
+
+async fn send_gemini(client: &reqwest::Client, config: &AIConfig, url: &str, body: &serde_json::Value) -> Result<reqwest::Response> {
+    let mut req_builder = client.post(url).header("Content-Type", "application/json").body(body.to_string());
+    if let Some(key) = &config.api_key {
+        req_builder = req_builder.header("x-goog-api-key", key);
+    }
+    let response = req_builder
+        .send()
+        .await
+        .map_err(|e| AppError::AIError(format!("Gemini API connection failed: {}", e)))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown".to_string());
+        return Err(AppError::AIError(format!("Gemini API failed ({}): {}", status, error_text)));
+    }
+    Ok(response)
+}
+
+/// Gemini 原生 generateContent 非流式调用（带 Google Search grounding）
+async fn call_gemini_generate(
+    config: &AIConfig,
+    client: &reqwest::Client,
+    messages: &[ChatMessage],
+    temperature: Option<f64>,
+    max_tokens: Option<u32>,
+    web_search: bool,
+) -> Result<String> {
+    let body = gemini_request_body(config, messages, temperature, max_tokens, web_search, false);
+    let response = send_gemini(client, config, &gemini_url(config, "generateContent"), &body).await?;
+    let value: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| AppError::AIError(format!("Failed to parse response: {}", e)))?;
+    let candidate = value.pointer("/candidates/0").cloned().unwrap_or_default();
+    let mut content: String = candidate
+        .pointer("/content/parts")
+        .and_then(|p| p.as_array())
+        .map(|parts| {
+            parts
+                .iter()
+                .filter(|p| !p.get("thought").and_then(|t| t.as_bool()).unwrap_or(false))
+                .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Some(chunks) = candidate.pointer("/groundingMetadata/groundingChunks").and_then(|c| c.as_array()) {
+        content.push_str(&gemini_sources(chunks));
+    }
+    Ok(content)
+}
+
+/// Gemini 原生 streamGenerateContent 流式调用（SSE），支持 Google Search grounding 和思考内容
+async fn stream_gemini_generate(
+    config: &AIConfig,
+    messages: &[ChatMessage],
+    web_search: bool,
+    thinking: bool,
+    req_id: &str,
+    window: &tauri::Window,
+) -> Result<StreamResult> {
+    let client = config.http_client().map_err(AppError::AIError)?;
+    let body = gemini_request_body(config, messages, None, None, web_search, thinking);
+    let response = send_gemini(&client, config, &gemini_url(config, "streamGenerateContent?alt=sse"), &body).await?;
+
+    let mut stream = response.bytes_stream();
+    use futures_util::StreamExt;
+
+    let mut full_content = String::new();
+    let mut buffer = Vec::new();
+    let mut in_reasoning = false;
+    let mut usage = None;
+    // 引用来源在后续分块中陆续给出，结束后统一追加
+    let mut grounding_chunks: Vec<serde_json::Value> = Vec::new();
+    let emit = |content: &str| {
+        let _ = window.emit("ai:stream:chunk", json!({ "request_id": req_id, "content": content }));
+    };
+
+    while let Some(chunk_result) = stream.next().await {
+        if is_stream_cancelled(req_id) {
+            break;
+        }
+
+        let chunk = chunk_result
+            .map_err(|e| AppError::AIError(format!("Stream error: {}", e)))?;
+
+        if buffer.len() + chunk.len() > MAX_BUFFER_SIZE {
+            return Err(AppError::AIError("Response too large".to_string()));
+        }
+
+        buffer.extend_from_slice(&chunk);
+
+        while let Some(pos) = buffer.iter().position(|&b| b == b'\n') {
+            let line_bytes: Vec<u8> = buffer.drain(..=pos).collect();
+            let line_str = String::from_utf8_lossy(&line_bytes);
+            let Some(data) = line_str.trim_end().strip_prefix("data: ") else { continue };
+            let Ok(json_val) = serde_json::from_str::<serde_json::Value>(data) else { continue };
+
+            if let Some(u) = gemini_usage(&json_val) {
+                usage = Some(u);
+            }
+            let candidate = json_val.pointer("/candidates/0");
+            if let Some(chunks) = candidate.and_then(|c| c.pointer("/groundingMetadata/groundingChunks")).and_then(|c| c.as_array()) {
+                grounding_chunks.extend(chunks.iter().cloned());
+            }
+            let parts = candidate.and_then(|c| c.pointer("/content/parts")).and_then(|p| p.as_array());
+            for part in parts.into_iter().flatten() {
+                let Some(text) = part.get("text").and_then(|t| t.as_str()).filter(|t| !t.is_empty()) else { continue };
+                if is_stream_cancelled(req_id) {
+                    break;
+                }
+                let is_thought = part.get("thought").and_then(|t| t.as_bool()).unwrap_or(false);
+                let text = match (is_thought, in_reasoning) {
+                    (true, false) => {
+                        in_reasoning = true;
+                        format!("<think>{}", text)
+                    }
+                    (false, true) => {
+                        in_reasoning = false;
+                        format!("</think>{}", text)
+                    }
+                    _ => text.to_string(),
+                };
+                full_content.push_str(&text);
+                emit(&text);
+            }
+        }
+    }
+
+    // 安全关闭：流结束时仍在思考状态
+    if in_reasoning {
+        full_content.push_str("</think>");
+        emit("</think>");
+    }
+    let sources = gemini_sources(&grounding_chunks);
+    if !sources.is_empty() && !is_stream_cancelled(req_id) {
+        full_content.push_str(&sources);
+        emit(&sources);
+    }
+
+    Ok(StreamResult { content: full_content, usage })
 }
 
 /// 根据 provider 注入联网搜索参数（Chat Completions 层）
@@ -1037,25 +2327,28 @@ fn inject_web_search_params(request_body: &mut serde_json::Value, config: &AICon
                 }
             }]);
         }
-        // Gemini: Google Search grounding
-        "gemini" => {
-            request_body["tools"] = json!([{
-                "google_search": {}
-            }]);
-        }
         // xAI: web_search tool（OpenAI 兼容格式）
         "xai" => {
             request_body["tools"] = json!([{
                 "type": "web_search"
             }]);
         }
-        // DeepSeek/MiniMax: 无内置联网搜索（将在 Function Calling 阶段通过自定义工具实现）
+        // DeepSeek/MiniMax 等: 无内置联网搜索，由 web_search 工具调用设置中的搜索服务（见 native_web_search）
         // OpenAI: 需要 Responses API（单独处理）
         // Anthropic: 需要原生 Messages API（单独处理）
+        // Gemini: Google Search grounding 需要原生 generateContent 接口（单独处理）
         _ => {}
     }
 }
 
+/// 服务商是否有内置联网搜索；没有时改用 web_search 工具
+fn native_web_search(provider: &str) -> bool {
+    matches!(
+        provider,
+        "openai" | "anthropic" | "gemini" | "glm" | "glm-code" | "qwen" | "kimi" | "kimi-code" | "xai"
+    )
+}
+
 /// 根据 provider 注入深度思考参数
 fn inject_thinking_params(request_body: &mut serde_json::Value, config: &AIConfig, enabled: bool) {
     match config.provider.as_str() {
@@ -1082,37 +2375,101 @@ fn inject_thinking_params(request_body: &mut serde_json::Value, config: &AIConfi
         "openai" => {}
         // xAI: Grok 推理模型自动启用
         "xai" => {}
-        // Gemini: 2.5+ 自动启用思考
+        // Gemini: 2.5+ 自动启用思考，思考内容只在原生接口中返回（stream_gemini_generate）
         "gemini" => {}
-        // Anthropic: Extended Thinking 需要特殊参数（在原生 API 中处理）
+        // Anthropic: Extended Thinking 走原生 Messages API（stream_anthropic_messages）
         "anthropic" => {}
         _ => {}
     }
 }
 
+/// 解析本次调用使用的 AI 配置：项目设置 → 调用参数（前端当前服务）→ 后端 AI 默认值 → 环境变量
 fn get_ai_config(
-    _app: &AppHandle,
+    app: &AppHandle,
+    project: Option<&ProjectAiSettings>,
     provider: Option<String>,
     api_key: Option<String>,
     model: Option<String>,
     base_url: Option<String>,
 ) -> AIConfig {
-    let provider_val = provider.unwrap_or_else(|| {
-        std::env::var("AI_PROVIDER").unwrap_or_else(|_| "openai".to_string())
-    });
+    let app_settings = settings::load(&config::get_settings_path(app));
+    let defaults = app_settings.ai;
+    let project = project.cloned().unwrap_or_default();
+    let non_empty = |v: Option<String>| v.filter(|s| !s.trim().is_empty());
+
+    // 项目指定了其他服务商时，调用方传入的密钥、地址和模型属于全局服务商，不能沿用
+    let project_provider = non_empty(project.provider);
+    let switched = project_provider.is_some() && project_provider != provider;
+    let (api_key, model, base_url) = if switched { (None, None, None) } else { (api_key, model, base_url) };
+
+    let provider_val = project_provider
+        .or(non_empty(provider))
+        .or(non_empty(defaults.provider))
+        .unwrap_or_else(|| std::env::var("AI_PROVIDER").unwrap_or_else(|_| "openai".to_string()));
+
+    let api_key_val = non_empty(api_key)
+        .or_else(|| secrets::get_cached(&format!("ai:{}", provider_val.trim())).ok().flatten())
+        .or_else(|| std::env::var("AI_API_KEY").ok());
+
+    let base_url_val = non_empty(project.base_url)
+        .or(non_empty(base_url))
+        .or_else(|| std::env::var("AI_BASE_URL").ok());
 
-    let api_key_val = api_key.or_else(|| {
-        std::env::var("AI_API_KEY").ok()
+    let proxy = app_settings.proxy.enabled.then_some(app_settings.proxy).and_then(|p| {
+        Some(ProxyConfig {
+            url: non_empty(p.url)?,
+            username: non_empty(p.username),
+            password: secrets::get_cached(settings::PROXY_PASSWORD_KEY).ok().flatten(),
+            no_proxy: p.no_proxy,
+        })
     });
 
-    let base_url_val = base_url
-        .filter(|s| !s.is_empty())
-        .or_else(|| std::env::var("AI_BASE_URL").ok());
-
     AIConfig {
         provider: provider_val,
         api_key: api_key_val,
         base_url: base_url_val,
-        model,
+        model: non_empty(project.model).or(non_empty(model)).or(non_empty(defaults.model)),
+        temperature: project.temperature.or(defaults.temperature.map(f64::from)),
+        proxy,
+        search: web_search::SearchConfig::from_settings(
+            app_settings.web_search,
+            secrets::get(settings::WEB_SEARCH_KEY).ok().flatten(),
+        ),
+    }
+}
+
+/// 读取项目文件中的 AI 配置；未指定项目或项目未配置时返回 None
+fn project_ai_settings(app: &AppHandle, project_id: Option<&str>) -> Option<ProjectAiSettings> {
+    let path = app.state::<AppState>().get_project_path(project_id?);
+    let json = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str::<Project>(&json).ok()?.settings.ai
+}
+
+/// 项目系统提示词和术语表放在最前面；已有系统消息时合并为一条
+fn with_project_system_prompt(
+    app: &AppHandle,
+    mut messages: Vec<ChatMessage>,
+    project_id: Option<&str>,
+    project: Option<&ProjectAiSettings>,
+) -> Vec<ChatMessage> {
+    let terms = project_id
+        .map(|pid| glossary::load(&app.state::<AppState>().config.projects_dir.join(pid)))
+        .unwrap_or_default();
+    let parts: Vec<String> = project
+        .and_then(|p| p.system_prompt.as_deref())
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .into_iter()
+        .chain(glossary::prompt(&terms))
+        .collect();
+    if parts.is_empty() {
+        return messages;
+    }
+    let prompt = parts.join("\n\n");
+    match messages.first_mut() {
+        Some(first) if first.role == "system" => first.content = format!("{}\n\n{}", prompt, first.content),
+        _ => messages.insert(0, ChatMessage { role: "system".to_string(), content: prompt, images: Vec::new() }),
     }
+    messages
 }
@@ -0,0 +1,29 @@
+use crate::api_server;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiServerStatus {
+    pub running: bool,
+    pub port: Option<u16>,
+    pub url: Option<String>,
+    /// 脱敏后的令牌
+    pub token_masked: Option<String>,
+}
+
+#[tauri::command]
+pub fn get_api_server_status() -> Result<ApiServerStatus, String> {
+    let port = api_server::running_port();
+    Ok(ApiServerStatus {
+        running: port.is_some(),
+        port,
+        url: port.map(|p| format!("http://127.0.0.1:{}/api/v1", p)),
+        token_masked: api_server::masked_token(),
+    })
+}
+
+/// 重新生成令牌，明文只在此处返回一次
+#[tauri::command]
+pub fn regenerate_api_token() -> Result<String, String> {
+    api_server::regenerate_token()
+}
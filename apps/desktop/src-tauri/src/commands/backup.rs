@@ -0,0 +1,57 @@
+#![allow(non_snake_case)]
+
+use crate::backup::{self, BackupManifest, ImportSummary, Section};
+use crate::config::{self, AppState};
+use crate::error::{run_blocking, Result};
+use crate::{plugin, template};
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+
+fn sections(app: &AppHandle, state: &AppState) -> Vec<Section> {
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    let config_dir = config::get_config_dir(app);
+    let data_dir = config::get_data_dir(app);
+    let mut sections = vec![
+        Section { name: "projects", dir: state.config.projects_dir.clone() },
+        Section { name: "templates", dir: template::get_templates_dir() },
+        Section { name: "plugins", dir: plugin::get_plugins_dir() },
+        Section { name: "resources", dir: home.join("AiDocPlus").join("Resources") },
+        Section { name: "config", dir: config_dir.clone() },
+    ];
+    // macOS 上配置目录与数据目录相同，只备份一次
+    if data_dir != config_dir {
+        sections.push(Section { name: "data", dir: data_dir });
+    }
+    sections
+}
+
+/// 导出全部应用数据（项目、模板、插件、资源、设置）到一个备份文件，用于迁移到新机器
+#[tauri::command]
+pub async fn export_app_data(app: AppHandle, state: State<'_, AppState>, outputPath: String) -> Result<BackupManifest> {
+    let sections = sections(&app, state.inner());
+    let version = app.package_info().version.to_string();
+    run_blocking(move || backup::export(&sections, &version, &PathBuf::from(outputPath))).await
+}
+
+/// 读取备份文件的清单，用于导入前确认
+#[tauri::command]
+pub async fn read_app_data_manifest(path: String) -> Result<BackupManifest> {
+    run_blocking(move || backup::read_manifest(&PathBuf::from(path))).await
+}
+
+/// 从备份文件恢复应用数据；默认不覆盖已存在的文件，恢复后需重启应用生效
+#[tauri::command]
+pub async fn import_app_data(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    overwrite: Option<bool>,
+) -> Result<ImportSummary> {
+    let sections = sections(&app, state.inner());
+    run_blocking(move || {
+        let summary = backup::import(&sections, &PathBuf::from(path), overwrite.unwrap_or(false))?;
+        tracing::info!("已从备份恢复 {} 个文件，跳过 {} 个", summary.imported, summary.skipped);
+        Ok(summary)
+    })
+    .await
+}
@@ -0,0 +1,33 @@
+#![allow(non_snake_case)]
+
+use crate::config::{get_workspace_state_path, AppState};
+use crate::document::Document;
+use crate::error::{run_blocking, Result};
+use crate::quick_capture;
+use crate::workspace::load_workspace_state;
+use tauri::{AppHandle, Emitter, State};
+
+#[tauri::command]
+pub fn open_quick_capture(app: AppHandle) -> Result<()> {
+    quick_capture::open_window(&app)
+}
+
+/// 追加快速记录到 Inbox 文档；未指定项目时使用当前打开的项目
+#[tauri::command]
+pub async fn append_to_inbox(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    projectId: Option<String>,
+    text: String,
+) -> Result<Document> {
+    let project_id = match projectId {
+        Some(id) => id,
+        None => load_workspace_state(&get_workspace_state_path(&app))?
+            .and_then(|ws| ws.current_project_id)
+            .ok_or("没有打开的项目，请先在主窗口中打开项目")?,
+    };
+    let projects_dir = state.config.projects_dir.clone();
+    let document = run_blocking(move || quick_capture::append_to_inbox(&projects_dir, &project_id, &text)).await?;
+    let _ = app.emit("quick-capture:appended", &document);
+    Ok(document)
+}
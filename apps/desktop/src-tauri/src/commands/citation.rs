@@ -0,0 +1,53 @@
+#![allow(non_snake_case)]
+
+use crate::citations::{self, ImportSummary, Reference};
+use crate::config::AppState;
+use crate::error::Result;
+use crate::i18n;
+use std::path::PathBuf;
+use tauri::State;
+
+fn project_dir(state: &AppState, project_id: &str) -> Result<PathBuf> {
+    if !state.get_project_path(project_id).exists() {
+        return Err(i18n::tf("error.project_not_found", &[&project_id]));
+    }
+    Ok(state.config.projects_dir.join(project_id))
+}
+
+/// 导入 .bib 文件到项目参考文献库
+#[tauri::command]
+pub fn import_bibtex(state: State<'_, AppState>, projectId: String, path: String) -> Result<ImportSummary> {
+    let dir = project_dir(&state, &projectId)?;
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("读取 {} 失败: {}", path, e))?;
+    citations::import(&dir, &content)
+}
+
+/// 搜索参考文献（按键、标题、作者、年份、期刊匹配，空查询返回全部）
+#[tauri::command]
+pub fn search_references(state: State<'_, AppState>, projectId: String, query: Option<String>) -> Result<Vec<Reference>> {
+    let dir = project_dir(&state, &projectId)?;
+    Ok(citations::search(&dir, query.as_deref().unwrap_or_default()))
+}
+
+#[tauri::command]
+pub fn delete_reference(state: State<'_, AppState>, projectId: String, key: String) -> Result<()> {
+    citations::delete(&project_dir(&state, &projectId)?, &key)
+}
+
+/// 生成要插入正文的引用标记（Pandoc 语法，如 [@key, p. 12]）
+#[tauri::command]
+pub fn insert_citation(
+    state: State<'_, AppState>,
+    projectId: String,
+    keys: Vec<String>,
+    locator: Option<String>,
+) -> Result<String> {
+    citations::cite(&project_dir(&state, &projectId)?, &keys, locator.as_deref())
+}
+
+/// 预览替换引用并追加参考文献列表后的正文
+#[tauri::command]
+pub fn render_bibliography(state: State<'_, AppState>, projectId: String, content: String) -> Result<String> {
+    let refs = citations::load(&project_dir(&state, &projectId)?);
+    Ok(citations::render(&content, &refs))
+}
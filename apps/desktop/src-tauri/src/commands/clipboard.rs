@@ -0,0 +1,86 @@
+#![allow(non_snake_case)]
+
+use super::export::prepare_content;
+use crate::clipboard_image::{self, PastedImage};
+use crate::config::AppState;
+use crate::error::{run_blocking, Result};
+use crate::smart_paste::{self, SmartPaste};
+use crate::{clipboard_html, i18n, native_export};
+use tauri::{AppHandle, State};
+
+/// 读取剪贴板图片保存为文档附件，返回可插入的 Markdown 引用
+#[tauri::command]
+pub async fn paste_clipboard_image(
+    state: State<'_, AppState>,
+    projectId: String,
+    documentId: String,
+) -> Result<PastedImage> {
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let doc_path = state.get_document_path(&projectId, &documentId);
+        if !doc_path.exists() {
+            return Err(i18n::tf("error.document_not_found", &[&documentId]));
+        }
+        let (png, width, height) = clipboard_image::read_png()?;
+        let project_dir = state.config.projects_dir.join(&projectId);
+        clipboard_image::save_to_document(&project_dir, &doc_path, &png, width, height)
+    })
+    .await
+}
+
+/// 读取剪贴板中的 HTML / RTF（Word、网页、公众号）转为 Markdown；
+/// 指定文档时图片保存为该文档的附件，否则保留原图片地址
+#[tauri::command]
+pub async fn convert_clipboard_to_markdown(
+    state: State<'_, AppState>,
+    projectId: Option<String>,
+    documentId: Option<String>,
+) -> Result<SmartPaste> {
+    let (source, markdown) = run_blocking(smart_paste::read_clipboard).await?;
+    let (Some(project_id), Some(document_id)) = (projectId, documentId) else {
+        return Ok(SmartPaste { markdown, source, attachments: Vec::new(), failed_images: Vec::new() });
+    };
+    let doc_path = state.get_document_path(&project_id, &document_id);
+    if !doc_path.exists() {
+        return Err(i18n::tf("error.document_not_found", &[&document_id]));
+    }
+
+    let mut images = Vec::new();
+    let mut failed_images = Vec::new();
+    for src in smart_paste::image_sources(&markdown) {
+        match smart_paste::fetch_image(&src).await {
+            Some(data) => images.push((src, data)),
+            None => failed_images.push(src),
+        }
+    }
+    let project_dir = state.config.projects_dir.join(&project_id);
+    let (markdown, attachments) =
+        run_blocking(move || smart_paste::save_images(&project_dir, &doc_path, &markdown, images)).await?;
+    Ok(SmartPaste { markdown, source, attachments, failed_images })
+}
+
+/// 转换为公众号格式（行内样式）并以富文本复制到剪贴板，返回生成的 HTML
+#[tauri::command]
+pub async fn copy_wechat_html(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    documentId: String,
+    projectId: String,
+    contentOverride: Option<String>,
+) -> Result<String> {
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let doc_path = state.get_document_path(&projectId, &documentId);
+        if !doc_path.exists() {
+            return Err(i18n::tf("error.document_not_found", &[&documentId]));
+        }
+        let document = crate::document::Document::load(&doc_path).map_err(|e| e.to_string())?;
+        let content = contentOverride.as_deref().unwrap_or(&document.ai_generated_content);
+        let content = prepare_content(&app, &state, &projectId, content);
+        let html = native_export::wechat::export_to_wechat_html(&content)?;
+        let plain = native_export::txt::export_to_txt(&content)?;
+        clipboard_html::write_html(&html, &plain)?;
+        Ok(html)
+    })
+    .await
+}
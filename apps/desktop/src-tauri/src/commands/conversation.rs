@@ -0,0 +1,67 @@
+#![allow(non_snake_case)]
+
+use crate::config::AppState;
+use crate::conversations::{self, Conversation, ConversationMessage, ConversationSummary};
+use crate::error::Result;
+use crate::i18n;
+use std::path::PathBuf;
+use tauri::State;
+
+fn project_dir(state: &AppState, project_id: &str) -> Result<PathBuf> {
+    if !state.get_project_path(project_id).exists() {
+        return Err(i18n::tf("error.project_not_found", &[&project_id]));
+    }
+    Ok(state.config.projects_dir.join(project_id))
+}
+
+/// 新建 AI 会话；documentId 为空时为项目级会话
+#[tauri::command]
+pub fn create_conversation(
+    state: State<'_, AppState>,
+    projectId: String,
+    title: Option<String>,
+    documentId: Option<String>,
+) -> Result<Conversation> {
+    conversations::create(&project_dir(&state, &projectId)?, &projectId, title, documentId)
+}
+
+/// 追加消息（通常每轮对话结束后追加用户消息和 AI 回复）
+#[tauri::command]
+pub fn append_conversation_messages(
+    state: State<'_, AppState>,
+    projectId: String,
+    conversationId: String,
+    messages: Vec<ConversationMessage>,
+) -> Result<ConversationSummary> {
+    conversations::append(&project_dir(&state, &projectId)?, &conversationId, messages)
+}
+
+#[tauri::command]
+pub fn get_conversation(state: State<'_, AppState>, projectId: String, conversationId: String) -> Result<Conversation> {
+    conversations::load(&project_dir(&state, &projectId)?, &conversationId)
+}
+
+/// 会话列表（不含消息），最近更新的在前
+#[tauri::command]
+pub fn list_conversations(
+    state: State<'_, AppState>,
+    projectId: String,
+    documentId: Option<String>,
+) -> Result<Vec<ConversationSummary>> {
+    Ok(conversations::list(&project_dir(&state, &projectId)?, documentId.as_deref()))
+}
+
+#[tauri::command]
+pub fn rename_conversation(
+    state: State<'_, AppState>,
+    projectId: String,
+    conversationId: String,
+    title: String,
+) -> Result<ConversationSummary> {
+    conversations::rename(&project_dir(&state, &projectId)?, &conversationId, &title)
+}
+
+#[tauri::command]
+pub fn delete_conversation(state: State<'_, AppState>, projectId: String, conversationId: String) -> Result<()> {
+    conversations::delete(&project_dir(&state, &projectId)?, &conversationId)
+}
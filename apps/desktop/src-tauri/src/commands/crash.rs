@@ -0,0 +1,24 @@
+use crate::config;
+use crate::crash_report::{self, CrashReport};
+use tauri::AppHandle;
+
+/// 已保存的崩溃报告（最新在前），unseen_only 为 true 时只返回未查看的
+#[tauri::command]
+pub fn get_crash_reports(app: AppHandle, unseen_only: Option<bool>) -> Result<Vec<CrashReport>, String> {
+    let reports = crash_report::list(&config::get_crash_dir(&app));
+    Ok(if unseen_only.unwrap_or(false) {
+        reports.into_iter().filter(|r| !r.seen).collect()
+    } else {
+        reports
+    })
+}
+
+#[tauri::command]
+pub fn mark_crash_reports_seen(app: AppHandle, ids: Vec<String>) -> Result<(), String> {
+    crash_report::mark_seen(&config::get_crash_dir(&app), &ids)
+}
+
+#[tauri::command]
+pub fn delete_crash_report(app: AppHandle, id: String) -> Result<(), String> {
+    crash_report::delete(&config::get_crash_dir(&app), &id)
+}
@@ -0,0 +1,16 @@
+use crate::deep_link::{self, DeepLinkAction};
+use tauri::AppHandle;
+
+/// 取走待处理的链接动作（前端启动完成及收到 deep-link:received 事件时调用）
+#[tauri::command]
+pub fn take_pending_deep_links() -> Vec<DeepLinkAction> {
+    deep_link::take_pending()
+}
+
+/// 手动处理一个 aidocplus:// 链接（如从剪贴板粘贴）
+#[tauri::command]
+pub fn open_deep_link(app: AppHandle, url: String) -> Result<(), String> {
+    deep_link::parse(&url)?;
+    deep_link::handle(&app, &url);
+    Ok(())
+}
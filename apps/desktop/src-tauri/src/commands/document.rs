@@ -1,9 +1,22 @@
 #![allow(non_snake_case)]
 
-use crate::config::AppState;
-use crate::document::{Attachment, Document};
-use crate::error::Result;
-use tauri::State;
+use crate::activity;
+use crate::bulk_ops::{self, BulkOp, BulkResult};
+use crate::chat_export::{self, TranscriptMessage};
+use crate::config::{self, AppState};
+use crate::document::{self, Attachment, Document, DocumentVersion, VersionStore};
+use crate::error::{run_blocking, Result};
+use crate::focus;
+use crate::goals;
+use crate::i18n;
+use crate::plugin_migration;
+use crate::thumbnails;
+use tauri::{AppHandle, State};
+
+/// 计入写作统计的字数：素材与正文合计
+pub(crate) fn writing_words(document: &Document) -> usize {
+    activity::count_words(&document.content) + activity::count_words(&document.ai_generated_content)
+}
 
 #[tauri::command]
 pub fn create_document(
@@ -20,58 +33,118 @@ pub fn create_document(
     Ok(document)
 }
 
+/// 将 AI 对话保存为新文档；stripThinking 默认去除思考过程
 #[tauri::command]
-pub fn save_document(
+pub fn export_chat_to_document(
+    state: State<'_, AppState>,
+    projectId: String,
+    messages: Vec<TranscriptMessage>,
+    title: Option<String>,
+    stripThinking: Option<bool>,
+) -> Result<Document> {
+    if !state.config.projects_dir.join(format!("{}.json", projectId)).exists() {
+        return Err(i18n::tf("error.project_not_found", &[&projectId]));
+    }
+    let content = chat_export::to_markdown(&messages, stripThinking.unwrap_or(true));
+    if content.trim().is_empty() {
+        return Err("对话中没有可导出的内容".to_string());
+    }
+    let title = title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| chat_export::default_title(&messages));
+
+    let mut document = Document::new(projectId.clone(), title, String::new());
+    document.metadata.word_count = content.split_whitespace().count();
+    document.metadata.character_count = content.chars().count();
+    document.metadata.tags.push("AI 对话".to_string());
+    document.content = content;
+    document.save(&state.get_document_path(&projectId, &document.id)).map_err(|e| e.to_string())?;
+    Ok(document)
+}
+
+#[tauri::command]
+pub async fn save_document(
+    app: AppHandle,
     state: State<'_, AppState>,
     documentId: String,
     projectId: String,
     title: String,
-    content: String,
+    content: Option<String>,
     authorNotes: String,
     aiGeneratedContent: String,
     attachments: Option<Vec<Attachment>>,
     pluginData: Option<serde_json::Value>,
     enabledPlugins: Option<Vec<String>>,
     composedContent: Option<String>,
+    contentUploadId: Option<String>,
 ) -> Result<Document> {
-    let doc_path = state.get_document_path(&projectId, &documentId);
+    let state = state.inner().clone();
+    let activity_path = config::get_activity_path(&app);
+    run_blocking(move || {
+        let doc_path = state.get_document_path(&projectId, &documentId);
 
-    if !doc_path.exists() {
-        return Err(format!("Document not found: {}", documentId));
-    }
+        if !doc_path.exists() {
+            return Err(i18n::tf("error.document_not_found", &[&documentId]));
+        }
 
-    // Load existing document
-    let mut document = Document::load(&doc_path).map_err(|e| e.to_string())?;
+        // Load existing document
+        let mut document = Document::load(&doc_path).map_err(|e| e.to_string())?;
+        document.ensure_unlocked().map_err(|e| e.localized())?;
+        let old_words = writing_words(&document);
 
-    // Update document fields
-    document.title = title;
-    document.author_notes = authorNotes;
-    document.ai_generated_content = aiGeneratedContent;
-    if let Some(atts) = attachments {
-        document.attachments = atts;
-    }
-    if let Some(pd) = pluginData {
-        document.plugin_data = Some(pd);
-    }
-    if let Some(ep) = enabledPlugins {
-        document.enabled_plugins = Some(ep);
-    }
-    if let Some(cc) = composedContent {
-        document.composed_content = Some(cc);
-    }
+        // 大文档的正文通过 append_document_content 分段上传；都未提供时保留原正文
+        let content = match (contentUploadId, content) {
+            (Some(upload_id), _) => document::take_upload(&upload_id).map_err(|e| e.to_string())?,
+            (None, Some(content)) => content,
+            (None, None) => std::mem::take(&mut document.content),
+        };
 
-    // Update metadata
-    document.metadata.updated_at = chrono::Utc::now().timestamp();
-    document.metadata.word_count = content.split_whitespace().count();
-    document.metadata.character_count = content.chars().count();
+        // Update document fields
+        document.title = title;
+        document.author_notes = authorNotes;
+        document.ai_generated_content = aiGeneratedContent;
+        if let Some(atts) = attachments {
+            document.attachments = atts;
+        }
+        if let Some(mut pd) = pluginData {
+            plugin_migration::stamp_versions(&mut pd);
+            document.plugin_data = Some(pd);
+        }
+        if let Some(ep) = enabledPlugins {
+            document.enabled_plugins = Some(ep);
+        }
+        if let Some(cc) = composedContent {
+            document.composed_content = Some(cc);
+        }
 
-    // Update content last
-    document.content = content;
+        // Update metadata
+        document.metadata.updated_at = chrono::Utc::now().timestamp();
+        document.metadata.word_count = content.split_whitespace().count();
+        document.metadata.character_count = content.chars().count();
 
-    // Save document
-    document.save(&doc_path).map_err(|e| e.to_string())?;
+        // Update content last
+        document.content = content;
 
-    Ok(document)
+        // Save document
+        document.save(&doc_path).map_err(|e| e.to_string())?;
+
+        let new_words = writing_words(&document);
+        match activity::record_save(&activity_path, &projectId, &documentId, old_words, new_words) {
+            Ok(day) => goals::notify_reached(&app, &day, &projectId, new_words.saturating_sub(old_words)),
+            Err(e) => tracing::warn!("{}", e),
+        }
+        focus::on_save(&app, &documentId, new_words.saturating_sub(old_words));
+
+        // 大文档不回传正文，前端沿用本地内容
+        if document.content.len() > document::LARGE_CONTENT_BYTES {
+            document.content.clear();
+            document.content_external = true;
+        }
+
+        Ok(document)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -83,116 +156,208 @@ pub fn delete_document(
     let doc_path = state.get_document_path(&projectId, &documentId);
 
     if !doc_path.exists() {
-        return Err(format!("Document not found: {}", documentId));
+        return Err(i18n::tf("error.document_not_found", &[&documentId]));
     }
 
-    // Remove document file
+    Document::load_meta(&doc_path)
+        .map_err(|e| e.to_string())?
+        .ensure_unlocked()
+        .map_err(|e| e.localized())?;
+
+    // Remove document file, external content and version history
     std::fs::remove_file(&doc_path).map_err(|e| e.to_string())?;
+    let _ = std::fs::remove_file(document::content_path(&doc_path));
+    VersionStore::new(state.get_versions_path(&projectId, &documentId))
+        .remove()
+        .map_err(|e| e.to_string())?;
 
     Ok(())
 }
 
+/// 锁定 / 解锁文档；锁定后禁止保存、重命名、删除和恢复版本
+async fn set_locked(state: AppState, projectId: String, documentId: String, locked: bool) -> Result<Document> {
+    run_blocking(move || {
+        let doc_path = state.get_document_path(&projectId, &documentId);
+        if !doc_path.exists() {
+            return Err(i18n::tf("error.document_not_found", &[&documentId]));
+        }
+        let mut document = Document::load(&doc_path).map_err(|e| e.to_string())?;
+        if document.locked != locked {
+            document.locked = locked;
+            document.save(&doc_path).map_err(|e| e.to_string())?;
+        }
+        Ok(document)
+    })
+    .await
+}
+
 #[tauri::command]
-pub fn rename_document(
+pub async fn lock_document(state: State<'_, AppState>, projectId: String, documentId: String) -> Result<Document> {
+    set_locked(state.inner().clone(), projectId, documentId, true).await
+}
+
+#[tauri::command]
+pub async fn unlock_document(state: State<'_, AppState>, projectId: String, documentId: String) -> Result<Document> {
+    set_locked(state.inner().clone(), projectId, documentId, false).await
+}
+
+#[tauri::command]
+pub async fn rename_document(
     state: State<'_, AppState>,
     projectId: String,
     documentId: String,
     newTitle: String,
 ) -> Result<Document> {
-    let doc_path = state.get_document_path(&projectId, &documentId);
-
-    if !doc_path.exists() {
-        return Err(format!("Document not found: {}", documentId));
-    }
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let doc_path = state.get_document_path(&projectId, &documentId);
 
-    // Validate new title
-    let trimmed_title = newTitle.trim();
-    if trimmed_title.is_empty() {
-        return Err("Document title cannot be empty".to_string());
-    }
-
-    // Load existing document
-    let mut document = Document::load(&doc_path).map_err(|e| e.to_string())?;
-
-    // Check for duplicate titles in the same project
-    let project_dir = state.config.projects_dir.join(&projectId);
-    let docs_dir = project_dir.join("documents");
+        if !doc_path.exists() {
+            return Err(i18n::tf("error.document_not_found", &[&documentId]));
+        }
 
-    if docs_dir.exists() {
-        let entries = std::fs::read_dir(&docs_dir).map_err(|e| e.to_string())?;
-        for entry in entries {
-            let entry = entry.map_err(|e| e.to_string())?;
-            let path = entry.path();
+        // Validate new title
+        let trimmed_title = newTitle.trim();
+        if trimmed_title.is_empty() {
+            return Err("Document title cannot be empty".to_string());
+        }
 
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                if path != doc_path {
-                    if let Ok(other_doc) = Document::load(&path) {
-                        if other_doc.title == trimmed_title {
-                            return Err(format!("A document with title '{}' already exists", trimmed_title));
+        // Load existing document
+        let mut document = Document::load(&doc_path).map_err(|e| e.to_string())?;
+        document.ensure_unlocked().map_err(|e| e.localized())?;
+
+        // Check for duplicate titles in the same project
+        let project_dir = state.config.projects_dir.join(&projectId);
+        let docs_dir = project_dir.join("documents");
+
+        if docs_dir.exists() {
+            let entries = std::fs::read_dir(&docs_dir).map_err(|e| e.to_string())?;
+            for entry in entries {
+                let entry = entry.map_err(|e| e.to_string())?;
+                let path = entry.path();
+
+                if path.extension().and_then(|s| s.to_str()) == Some("json") {
+                    if path != doc_path {
+                        if let Ok(other_doc) = Document::load(&path) {
+                            if other_doc.title == trimmed_title {
+                                return Err(i18n::tf("error.document_title_exists", &[&trimmed_title]));
+                            }
                         }
                     }
                 }
             }
         }
-    }
 
-    // Update document title
-    document.title = trimmed_title.to_string();
-    document.metadata.updated_at = chrono::Utc::now().timestamp();
+        // Update document title
+        document.title = trimmed_title.to_string();
+        document.metadata.updated_at = chrono::Utc::now().timestamp();
 
-    // Save document
-    document.save(&doc_path).map_err(|e| e.to_string())?;
+        // Save document
+        document.save(&doc_path).map_err(|e| e.to_string())?;
 
-    Ok(document)
+        Ok(document)
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn get_document(
+pub async fn get_document(
     state: State<'_, AppState>,
     projectId: String,
     documentId: String,
 ) -> Result<Document> {
-    let doc_path = state.get_document_path(&projectId, &documentId);
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let doc_path = state.get_document_path(&projectId, &documentId);
 
-    if !doc_path.exists() {
-        return Err(format!("Document not found: {}", documentId));
-    }
+        if !doc_path.exists() {
+            return Err(i18n::tf("error.document_not_found", &[&documentId]));
+        }
 
-    Document::load(&doc_path).map_err(|e| e.to_string())
+        Document::load(&doc_path).map_err(|e| e.to_string())
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn list_documents(state: State<'_, AppState>, projectId: String) -> Result<Vec<Document>> {
-    let project_dir = state.config.projects_dir.join(&projectId);
-    let docs_dir = project_dir.join("documents");
-
-    if !docs_dir.exists() {
-        return Ok(Vec::new());
-    }
+pub async fn list_documents(state: State<'_, AppState>, projectId: String) -> Result<Vec<Document>> {
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let project_dir = state.config.projects_dir.join(&projectId);
+        let docs_dir = project_dir.join("documents");
+
+        if !docs_dir.exists() {
+            return Ok(Vec::new());
+        }
 
-    let mut documents = Vec::new();
+        let mut documents = Vec::new();
 
-    let entries = std::fs::read_dir(&docs_dir).map_err(|e| e.to_string())?;
+        let entries = std::fs::read_dir(&docs_dir).map_err(|e| e.to_string())?;
 
-    for entry in entries {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
+        for entry in entries {
+            let entry = entry.map_err(|e| e.to_string())?;
+            let path = entry.path();
 
-        if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            if let Ok(document) = Document::load(&path) {
-                documents.push(document);
+            // 大文档只返回元数据，正文由 read_document_content 分段读取
+            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+                if let Ok(document) = Document::load_meta(&path) {
+                    documents.push(document);
+                }
             }
         }
-    }
 
-    // Sort by updated_at (most recent first)
-    documents.sort_by(|a, b| b.metadata.updated_at.cmp(&a.metadata.updated_at));
+        // Sort by updated_at (most recent first)
+        documents.sort_by(|a, b| b.metadata.updated_at.cmp(&a.metadata.updated_at));
+
+        Ok(documents)
+    })
+    .await
+}
 
-    Ok(documents)
+/// 分段读取的正文
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentChunk {
+    pub text: String,
+    /// 下一段的起点（字节），读完时为空
+    pub next_offset: Option<usize>,
+    pub total_bytes: usize,
 }
 
+/// 每段默认大小，避免单次 IPC 负载过大
+const CONTENT_CHUNK_BYTES: usize = 256 * 1024;
+
+/// 从 offset（字节）起分段读取正文，打开大文档时循环调用直到 nextOffset 为空
 #[tauri::command]
-pub fn create_version(
+pub async fn read_document_content(
+    state: State<'_, AppState>,
+    projectId: String,
+    documentId: String,
+    offset: Option<usize>,
+    maxBytes: Option<usize>,
+) -> Result<ContentChunk> {
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let doc_path = state.get_document_path(&projectId, &documentId);
+        if !doc_path.exists() {
+            return Err(i18n::tf("error.document_not_found", &[&documentId]));
+        }
+        let document = Document::load(&doc_path).map_err(|e| e.to_string())?;
+        let (text, next_offset) =
+            document::content_range(&document.content, offset.unwrap_or(0), maxBytes.unwrap_or(CONTENT_CHUNK_BYTES));
+        Ok(ContentChunk { text: text.to_string(), next_offset, total_bytes: document.content.len() })
+    })
+    .await
+}
+
+/// 分段上传正文，全部上传后以 contentUploadId 调用 save_document
+#[tauri::command]
+pub async fn append_document_content(uploadId: String, chunk: String) -> Result<()> {
+    run_blocking(move || document::append_upload(&uploadId, &chunk).map_err(|e| e.to_string())).await
+}
+
+#[tauri::command]
+pub async fn create_version(
     state: State<'_, AppState>,
     documentId: String,
     projectId: String,
@@ -205,40 +370,43 @@ pub fn create_version(
     enabledPlugins: Option<Vec<String>>,
     composedContent: Option<String>,
 ) -> Result<String> {
-    let doc_path = state.get_document_path(&projectId, &documentId);
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let doc_path = state.get_document_path(&projectId, &documentId);
 
-    if !doc_path.exists() {
-        return Err(format!("Document not found: {}", documentId));
-    }
+        if !doc_path.exists() {
+            return Err(i18n::tf("error.document_not_found", &[&documentId]));
+        }
 
-    let mut document = Document::load(&doc_path).map_err(|e| e.to_string())?;
-    document.create_version(content, authorNotes, aiGeneratedContent, createdBy, changeDescription, pluginData, enabledPlugins, composedContent);
+        let mut document = Document::load(&doc_path).map_err(|e| e.to_string())?;
+        document.create_version(content, authorNotes, aiGeneratedContent, createdBy, changeDescription, pluginData, enabledPlugins, composedContent);
 
-    // Save document with new version
-    document.save(&doc_path).map_err(|e| e.to_string())?;
+        // Save document with new version
+        document.save(&doc_path).map_err(|e| e.to_string())?;
 
-    // Return the new version ID
-    if let Some(version) = document.versions.last() {
-        Ok(version.id.clone())
-    } else {
-        Err("Failed to create version".to_string())
-    }
+        // Return the new version ID
+        Ok(document.current_version_id)
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn list_versions(
+pub async fn list_versions(
     state: State<'_, AppState>,
     projectId: String,
     documentId: String,
-) -> Result<Vec<crate::document::DocumentVersion>> {
-    let doc_path = state.get_document_path(&projectId, &documentId);
+) -> Result<Vec<DocumentVersion>> {
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let doc_path = state.get_document_path(&projectId, &documentId);
 
-    if !doc_path.exists() {
-        return Err(format!("Document not found: {}", documentId));
-    }
+        if !doc_path.exists() {
+            return Err(i18n::tf("error.document_not_found", &[&documentId]));
+        }
 
-    let document = Document::load(&doc_path).map_err(|e| e.to_string())?;
-    Ok(document.versions)
+        Ok(VersionStore::new(state.get_versions_path(&projectId, &documentId)).list())
+    })
+    .await
 }
 
 #[tauri::command]
@@ -247,109 +415,108 @@ pub fn get_version(
     projectId: String,
     documentId: String,
     versionId: String,
-) -> Result<crate::document::DocumentVersion> {
+) -> Result<DocumentVersion> {
     let doc_path = state.get_document_path(&projectId, &documentId);
 
     if !doc_path.exists() {
-        return Err(format!("Document not found: {}", documentId));
+        return Err(i18n::tf("error.document_not_found", &[&documentId]));
     }
 
-    let document = Document::load(&doc_path).map_err(|e| e.to_string())?;
-
-    document
-        .versions
-        .into_iter()
-        .find(|v| v.id == versionId)
-        .ok_or_else(|| format!("Version not found: {}", versionId))
+    VersionStore::new(state.get_versions_path(&projectId, &documentId))
+        .get(&versionId)
+        .ok_or_else(|| i18n::tf("error.version_not_found", &[&versionId]))
 }
 
 #[tauri::command]
-pub fn restore_version(
+pub async fn restore_version(
     state: State<'_, AppState>,
     projectId: String,
     documentId: String,
     versionId: String,
     createBackup: bool,
 ) -> Result<Document> {
-    let doc_path = state.get_document_path(&projectId, &documentId);
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let doc_path = state.get_document_path(&projectId, &documentId);
 
-    if !doc_path.exists() {
-        return Err(format!("Document not found: {}", documentId));
-    }
+        if !doc_path.exists() {
+            return Err(i18n::tf("error.document_not_found", &[&documentId]));
+        }
 
-    let mut document = Document::load(&doc_path).map_err(|e| e.to_string())?;
+        let mut document = Document::load(&doc_path).map_err(|e| e.to_string())?;
+        document.ensure_unlocked().map_err(|e| e.localized())?;
+
+        // Create backup of current version if requested
+        if createBackup {
+            let backup_version = DocumentVersion {
+                id: format!("backup-{}", chrono::Utc::now().timestamp()),
+                document_id: documentId.clone(),
+                content: document.content.clone(),
+                author_notes: document.author_notes.clone(),
+                ai_generated_content: document.ai_generated_content.clone(),
+                created_at: chrono::Utc::now().timestamp(),
+                created_by: "system".to_string(),
+                change_description: Some("Backup before restore".to_string()),
+                plugin_data: document.plugin_data.clone(),
+                enabled_plugins: document.enabled_plugins.clone(),
+                composed_content: document.composed_content.clone(),
+            };
+
+            document.versions.push(backup_version);
+        }
 
-    // Create backup of current version if requested
-    if createBackup {
-        let backup_version = crate::document::DocumentVersion {
-            id: format!("backup-{}", chrono::Utc::now().timestamp()),
+        // Find the version to restore and clone its content
+        let (content, author_notes, ai_generated_content, plugin_data, enabled_plugins, composed_content) = {
+            let version_to_restore = VersionStore::new(state.get_versions_path(&projectId, &documentId))
+                .get(&versionId)
+                .ok_or_else(|| i18n::tf("error.version_not_found", &[&versionId]))?;
+            (
+                version_to_restore.content,
+                version_to_restore.author_notes,
+                version_to_restore.ai_generated_content,
+                version_to_restore.plugin_data,
+                version_to_restore.enabled_plugins,
+                version_to_restore.composed_content,
+            )
+        };
+
+        // Create a new version with the restored content
+        let new_version_id = uuid::Uuid::new_v4().to_string();
+        let restored_version = DocumentVersion {
+            id: new_version_id.clone(),
             document_id: documentId.clone(),
-            content: document.content.clone(),
-            author_notes: document.author_notes.clone(),
-            ai_generated_content: document.ai_generated_content.clone(),
+            content: content.clone(),
+            author_notes: author_notes.clone(),
+            ai_generated_content: ai_generated_content.clone(),
             created_at: chrono::Utc::now().timestamp(),
             created_by: "system".to_string(),
-            change_description: Some("Backup before restore".to_string()),
-            plugin_data: document.plugin_data.clone(),
-            enabled_plugins: document.enabled_plugins.clone(),
-            composed_content: document.composed_content.clone(),
+            change_description: Some(format!("Restored from version {}", versionId)),
+            plugin_data: plugin_data.clone(),
+            enabled_plugins: enabled_plugins.clone(),
+            composed_content: composed_content.clone(),
         };
 
-        document.versions.push(backup_version);
-    }
-
-    // Find the version to restore and clone its content
-    let (content, author_notes, ai_generated_content, plugin_data, enabled_plugins, composed_content) = {
-        let version_to_restore = document
-            .versions
-            .iter()
-            .find(|v| v.id == versionId)
-            .ok_or_else(|| format!("Version not found: {}", versionId))?;
-        (
-            version_to_restore.content.clone(),
-            version_to_restore.author_notes.clone(),
-            version_to_restore.ai_generated_content.clone(),
-            version_to_restore.plugin_data.clone(),
-            version_to_restore.enabled_plugins.clone(),
-            version_to_restore.composed_content.clone(),
-        )
-    };
-
-    // Create a new version with the restored content
-    let new_version_id = uuid::Uuid::new_v4().to_string();
-    let restored_version = crate::document::DocumentVersion {
-        id: new_version_id.clone(),
-        document_id: documentId.clone(),
-        content: content.clone(),
-        author_notes: author_notes.clone(),
-        ai_generated_content: ai_generated_content.clone(),
-        created_at: chrono::Utc::now().timestamp(),
-        created_by: "system".to_string(),
-        change_description: Some(format!("Restored from version {}", versionId)),
-        plugin_data: plugin_data.clone(),
-        enabled_plugins: enabled_plugins.clone(),
-        composed_content: composed_content.clone(),
-    };
-
-    // Add the new version and set it as current
-    document.versions.push(restored_version);
-    document.current_version_id = new_version_id;
-
-    // Update document content from the restored version
-    document.content = content;
-    document.author_notes = author_notes;
-    document.ai_generated_content = ai_generated_content;
-    document.plugin_data = plugin_data;
-    document.enabled_plugins = enabled_plugins;
-    document.composed_content = composed_content;
-    document.metadata.updated_at = chrono::Utc::now().timestamp();
-    document.metadata.word_count = document.content.split_whitespace().count();
-    document.metadata.character_count = document.content.chars().count();
-
-    // Save the restored document
-    document.save(&doc_path).map_err(|e| e.to_string())?;
-
-    Ok(document)
+        // Add the new version and set it as current
+        document.versions.push(restored_version);
+        document.current_version_id = new_version_id;
+
+        // Update document content from the restored version
+        document.content = content;
+        document.author_notes = author_notes;
+        document.ai_generated_content = ai_generated_content;
+        document.plugin_data = plugin_data;
+        document.enabled_plugins = enabled_plugins;
+        document.composed_content = composed_content;
+        document.metadata.updated_at = chrono::Utc::now().timestamp();
+        document.metadata.word_count = document.content.split_whitespace().count();
+        document.metadata.character_count = document.content.chars().count();
+
+        // Save the restored document
+        document.save(&doc_path).map_err(|e| e.to_string())?;
+
+        Ok(document)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -394,83 +561,171 @@ pub fn write_binary_file(path: String, data: Vec<u8>) -> Result<()> {
 
 /// 将文档移动到另一个项目
 #[tauri::command]
-pub fn move_document(
+pub async fn move_document(
     state: State<'_, AppState>,
     documentId: String,
     fromProjectId: String,
     toProjectId: String,
 ) -> Result<Document> {
-    let src_path = state.get_document_path(&fromProjectId, &documentId);
-    if !src_path.exists() {
-        return Err(format!("文档未找到: {}", documentId));
-    }
-
-    // 确保目标项目存在
-    let to_project_path = state.get_project_path(&toProjectId);
-    if !to_project_path.exists() {
-        return Err(format!("目标项目未找到: {}", toProjectId));
-    }
-
-    // 确保目标 documents 目录存在
-    let to_docs_dir = state.config.projects_dir.join(&toProjectId).join("documents");
-    std::fs::create_dir_all(&to_docs_dir).map_err(|e| e.to_string())?;
-
-    // 加载文档并更新 projectId
-    let mut document = Document::load(&src_path).map_err(|e| e.to_string())?;
-    document.project_id = toProjectId.clone();
-    document.metadata.updated_at = chrono::Utc::now().timestamp();
-
-    // 保存到目标位置
-    let dst_path = state.get_document_path(&toProjectId, &documentId);
-    document.save(&dst_path).map_err(|e| e.to_string())?;
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let src_path = state.get_document_path(&fromProjectId, &documentId);
+        if !src_path.exists() {
+            return Err(i18n::tf("error.document_not_found", &[&documentId]));
+        }
 
-    // 删除源文件
-    std::fs::remove_file(&src_path).map_err(|e| e.to_string())?;
+        // 确保目标项目存在
+        let to_project_path = state.get_project_path(&toProjectId);
+        if !to_project_path.exists() {
+            return Err(i18n::tf("error.target_project_not_found", &[&toProjectId]));
+        }
 
-    Ok(document)
+        // 确保目标 documents 目录存在
+        let to_docs_dir = state.config.projects_dir.join(&toProjectId).join("documents");
+        std::fs::create_dir_all(&to_docs_dir).map_err(|e| e.to_string())?;
+
+        // 加载文档并更新 projectId
+        let mut document = Document::load(&src_path).map_err(|e| e.to_string())?;
+        document.project_id = toProjectId.clone();
+        document.metadata.updated_at = chrono::Utc::now().timestamp();
+
+        // 保存到目标位置
+        let dst_path = state.get_document_path(&toProjectId, &documentId);
+        document.save(&dst_path).map_err(|e| e.to_string())?;
+
+        // 删除源文件，版本历史随文档移动
+        std::fs::remove_file(&src_path).map_err(|e| e.to_string())?;
+        let _ = std::fs::remove_file(document::content_path(&src_path));
+        VersionStore::new(state.get_versions_path(&fromProjectId, &documentId))
+            .move_to(&VersionStore::new(state.get_versions_path(&toProjectId, &documentId)))
+            .map_err(|e| e.to_string())?;
+
+        Ok(document)
+    })
+    .await
 }
 
 /// 将文档复制到另一个项目（生成新 ID）
 #[tauri::command]
-pub fn copy_document(
+pub async fn copy_document(
     state: State<'_, AppState>,
     documentId: String,
     fromProjectId: String,
     toProjectId: String,
 ) -> Result<Document> {
-    let src_path = state.get_document_path(&fromProjectId, &documentId);
-    if !src_path.exists() {
-        return Err(format!("文档未找到: {}", documentId));
-    }
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let src_path = state.get_document_path(&fromProjectId, &documentId);
+        if !src_path.exists() {
+            return Err(i18n::tf("error.document_not_found", &[&documentId]));
+        }
 
-    // 确保目标项目存在
-    let to_project_path = state.get_project_path(&toProjectId);
-    if !to_project_path.exists() {
-        return Err(format!("目标项目未找到: {}", toProjectId));
-    }
+        // 确保目标项目存在
+        let to_project_path = state.get_project_path(&toProjectId);
+        if !to_project_path.exists() {
+            return Err(i18n::tf("error.target_project_not_found", &[&toProjectId]));
+        }
+
+        // 确保目标 documents 目录存在
+        let to_docs_dir = state.config.projects_dir.join(&toProjectId).join("documents");
+        std::fs::create_dir_all(&to_docs_dir).map_err(|e| e.to_string())?;
+
+        // 加载源文档
+        let src_doc = Document::load(&src_path).map_err(|e| e.to_string())?;
+
+        // 创建新文档（新 ID）
+        let new_id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().timestamp();
+        let mut new_doc = src_doc;
+        new_doc.id = new_id.clone();
+        new_doc.project_id = toProjectId.clone();
+        new_doc.title = format!("{} (副本)", new_doc.title);
+        new_doc.metadata.created_at = now;
+        new_doc.metadata.updated_at = now;
+        new_doc.versions = Vec::new(); // 不复制版本历史
+        new_doc.current_version_id = String::new();
+
+        // 保存到目标位置
+        let dst_path = state.get_document_path(&toProjectId, &new_id);
+        new_doc.save(&dst_path).map_err(|e| e.to_string())?;
+
+        Ok(new_doc)
+    })
+    .await
+}
+
+/// 图片 / PDF 附件的缩略图（data URL），按尺寸缓存在项目的 thumbnails/ 目录；
+/// size 为最长边像素，限制在 32–1024
+#[tauri::command]
+pub async fn get_attachment_thumbnail(
+    state: State<'_, AppState>,
+    projectId: String,
+    attachmentId: String,
+    size: Option<u32>,
+) -> Result<String> {
+    let projects_dir = state.config.projects_dir.clone();
+    run_blocking(move || {
+        let project_dir = projects_dir.join(&projectId);
+        let entries = std::fs::read_dir(project_dir.join("documents")).map_err(|e| e.to_string())?;
+        let attachment = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("json"))
+            .filter_map(|p| Document::load_meta(&p).ok())
+            .flat_map(|d| d.attachments)
+            .find(|a| a.id == attachmentId)
+            .ok_or_else(|| format!("附件不存在: {}", attachmentId))?;
+        if !thumbnails::is_supported(&attachment) {
+            return Err(format!("不支持为 {} 生成缩略图", attachment.file_name));
+        }
+        thumbnails::thumbnail(&project_dir, &attachment, size.unwrap_or(256))
+    })
+    .await
+}
 
-    // 确保目标 documents 目录存在
-    let to_docs_dir = state.config.projects_dir.join(&toProjectId).join("documents");
-    std::fs::create_dir_all(&to_docs_dir).map_err(|e| e.to_string())?;
-
-    // 加载源文档
-    let src_doc = Document::load(&src_path).map_err(|e| e.to_string())?;
-
-    // 创建新文档（新 ID）
-    let new_id = uuid::Uuid::new_v4().to_string();
-    let now = chrono::Utc::now().timestamp();
-    let mut new_doc = src_doc;
-    new_doc.id = new_id.clone();
-    new_doc.project_id = toProjectId.clone();
-    new_doc.title = format!("{} (副本)", new_doc.title);
-    new_doc.metadata.created_at = now;
-    new_doc.metadata.updated_at = now;
-    new_doc.versions = Vec::new(); // 不复制版本历史
-    new_doc.current_version_id = String::new();
-
-    // 保存到目标位置
-    let dst_path = state.get_document_path(&toProjectId, &new_id);
-    new_doc.save(&dst_path).map_err(|e| e.to_string())?;
-
-    Ok(new_doc)
+/// 设置文档标签（去除空白和重复）
+#[tauri::command]
+pub async fn set_document_tags(
+    state: State<'_, AppState>,
+    projectId: String,
+    documentId: String,
+    tags: Vec<String>,
+) -> Result<Document> {
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let doc_path = state.get_document_path(&projectId, &documentId);
+        if !doc_path.exists() {
+            return Err(i18n::tf("error.document_not_found", &[&documentId]));
+        }
+        let mut document = Document::load(&doc_path).map_err(|e| e.to_string())?;
+        document.ensure_unlocked().map_err(|e| e.localized())?;
+        document.metadata.tags.clear();
+        for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            if !document.metadata.tags.iter().any(|t| t == tag) {
+                document.metadata.tags.push(tag.to_string());
+            }
+        }
+        document.metadata.updated_at = chrono::Utc::now().timestamp();
+        document.save(&doc_path).map_err(|e| e.to_string())?;
+        Ok(document)
+    })
+    .await
+}
+
+/// 批量移动 / 复制 / 删除 / 增删标签，全部成功或全部撤销；
+/// 移动、复制时 targetProjectId 为目标项目（复制默认当前项目），标签操作使用 tags
+#[tauri::command]
+pub async fn bulk_document_operation(
+    state: State<'_, AppState>,
+    projectId: String,
+    op: BulkOp,
+    documentIds: Vec<String>,
+    targetProjectId: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<BulkResult> {
+    let state = state.inner().clone();
+    run_blocking(move || {
+        bulk_ops::run(&state, &projectId, op, &documentIds, targetProjectId.as_deref(), &tags.unwrap_or_default())
+    })
+    .await
 }
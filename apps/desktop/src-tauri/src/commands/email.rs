@@ -1,21 +1,99 @@
-use lettre::message::{header::ContentType, Mailbox, MultiPart, SinglePart};
-use lettre::transport::smtp::authentication::Credentials;
+use lettre::message::{header::ContentType, Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::{Credentials, Mechanism};
 use lettre::transport::smtp::client::{Tls, TlsParameters};
 use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::config::{self, AppState};
+use crate::email_account::{self, EmailAccount, SmtpSettings};
+use crate::email_contacts::{self, Contact};
+use crate::email_images;
+use crate::email_import::{self, ImportedEmail};
+use crate::imap_client::{self, ImapSession};
+use crate::email_queue::{self, EmailRecord};
+use crate::template;
+
+/// 保存邮箱账户：配置写入应用配置目录，授权码写入系统钥匙串
+#[tauri::command]
+pub fn save_email_account(app: AppHandle, account: EmailAccount, password: Option<String>) -> Result<EmailAccount, String> {
+    email_account::save_account(&config::get_email_accounts_path(&app), account, password)
+}
+
+#[tauri::command]
+pub fn list_email_accounts(app: AppHandle) -> Result<Vec<EmailAccount>, String> {
+    Ok(email_account::list_accounts(&config::get_email_accounts_path(&app)))
+}
+
+/// OAuth2 账户授权：打开浏览器登录，令牌保存到钥匙串，发送时自动刷新
+#[tauri::command]
+#[allow(non_snake_case)]
+pub async fn authorize_email_account(app: AppHandle, accountId: String, clientSecret: Option<String>) -> Result<(), String> {
+    email_account::authorize_oauth(&config::get_email_accounts_path(&app), &accountId, clientSecret).await
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+pub fn delete_email_account(app: AppHandle, accountId: String) -> Result<(), String> {
+    email_account::delete_account(&config::get_email_accounts_path(&app), &accountId)
+}
+
+/// 解析 SMTP 参数：优先使用已保存的账户，否则使用调用方直接传入的参数（兼容旧调用）
+#[allow(clippy::too_many_arguments)]
+async fn resolve_smtp_settings(
+    app: &AppHandle,
+    account_id: Option<String>,
+    host: Option<String>,
+    port: Option<u16>,
+    encryption: Option<String>,
+    email: Option<String>,
+    password: Option<String>,
+    display_name: Option<String>,
+) -> Result<SmtpSettings, String> {
+    if let Some(id) = account_id.filter(|id| !id.is_empty()) {
+        return email_account::resolve_account(&config::get_email_accounts_path(app), &id).await;
+    }
+    match (host, email, password) {
+        (Some(host), Some(email), Some(password)) => Ok(SmtpSettings {
+            host,
+            port: port.unwrap_or(465),
+            encryption: encryption.unwrap_or_else(|| "tls".to_string()),
+            email,
+            password,
+            display_name,
+            xoauth2: false,
+        }),
+        _ => Err("缺少邮箱账户：请提供 accountId 或完整的 SMTP 参数".to_string()),
+    }
+}
+
+/// 展开收件人中的联系人分组引用（"group:分组名"）
+fn expand_recipients(app: &AppHandle, to: Vec<String>, cc: Vec<String>, bcc: Vec<String>) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let path = config::get_email_contacts_path(app);
+    (
+        email_contacts::expand_groups(&path, &to),
+        email_contacts::expand_groups(&path, &cc),
+        email_contacts::expand_groups(&path, &bcc),
+    )
+}
 
 /// 测试 SMTP 连接
 #[tauri::command]
 #[allow(non_snake_case)]
 pub async fn test_smtp_connection(
-    smtpHost: String,
-    smtpPort: u16,
-    encryption: String,
-    email: String,
-    password: String,
+    app: AppHandle,
+    accountId: Option<String>,
+    smtpHost: Option<String>,
+    smtpPort: Option<u16>,
+    encryption: Option<String>,
+    email: Option<String>,
+    password: Option<String>,
 ) -> Result<String, String> {
-    let creds = Credentials::new(email.clone(), password);
+    let smtp = resolve_smtp_settings(&app, accountId, smtpHost, smtpPort, encryption, email, password, None).await?;
+    let creds = Credentials::new(smtp.email.clone(), smtp.password);
 
-    let transport = build_smtp_transport(&smtpHost, smtpPort, &encryption, creds)
+    let transport = build_smtp_transport(&smtp.host, smtp.port, &smtp.encryption, creds, smtp.xoauth2)
         .map_err(|e| format!("构建 SMTP 连接失败: {}", e))?;
 
     transport
@@ -23,18 +101,20 @@ pub async fn test_smtp_connection(
         .await
         .map_err(|e| format!("SMTP 连接测试失败: {}", e))?;
 
-    Ok(format!("连接成功！SMTP 服务器 {}:{} 验证通过", smtpHost, smtpPort))
+    Ok(format!("连接成功！SMTP 服务器 {}:{} 验证通过", smtp.host, smtp.port))
 }
 
-/// 发送邮件
+/// 发送邮件：先写入发件箱，再按重试策略投递，结果记录在发送历史中
 #[tauri::command]
 #[allow(non_snake_case)]
 pub async fn send_email(
-    smtpHost: String,
-    smtpPort: u16,
-    encryption: String,
-    email: String,
-    password: String,
+    app: AppHandle,
+    accountId: Option<String>,
+    smtpHost: Option<String>,
+    smtpPort: Option<u16>,
+    encryption: Option<String>,
+    email: Option<String>,
+    password: Option<String>,
     displayName: Option<String>,
     to: Vec<String>,
     cc: Vec<String>,
@@ -44,27 +124,539 @@ pub async fn send_email(
     isHtml: bool,
     isRawHtml: Option<bool>,
 ) -> Result<String, String> {
+    let (to, cc, bcc) = expand_recipients(&app, to, cc, bcc);
+    if to.is_empty() {
+        return Err("收件人不能为空".to_string());
+    }
+
+    let account_id = accountId.filter(|id| !id.is_empty());
+    let smtp = resolve_smtp_settings(&app, account_id.clone(), smtpHost, smtpPort, encryption, email, password, displayName).await?;
+
+    let record = email_queue::enqueue(&config::get_email_outbox_path(&app), EmailRecord {
+        id: String::new(),
+        account_id,
+        from: smtp.email.clone(),
+        to,
+        cc,
+        bcc,
+        subject,
+        body,
+        is_html: isHtml,
+        is_raw_html: isRawHtml.unwrap_or(false),
+        status: String::new(),
+        send_at: None,
+        attempts: 0,
+        last_error: None,
+        created_at: 0,
+        sent_at: None,
+    })?;
+
+    deliver_record(&app, &smtp, &record).await
+}
+
+/// 保存联系人（id 为空时新增）
+#[tauri::command]
+pub fn save_email_contact(app: AppHandle, contact: Contact) -> Result<Contact, String> {
+    email_contacts::save_contact(&config::get_email_contacts_path(&app), contact)
+}
+
+#[tauri::command]
+pub fn list_email_contacts(app: AppHandle, group: Option<String>) -> Result<Vec<Contact>, String> {
+    let mut contacts = email_contacts::load_contacts(&config::get_email_contacts_path(&app));
+    if let Some(group) = group {
+        contacts.retain(|c| c.group.as_deref() == Some(group.as_str()));
+    }
+    contacts.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(contacts)
+}
+
+#[tauri::command]
+pub fn delete_email_contact(app: AppHandle, id: String) -> Result<(), String> {
+    email_contacts::delete_contact(&config::get_email_contacts_path(&app), &id)
+}
+
+#[tauri::command]
+pub fn list_email_contact_groups(app: AppHandle) -> Result<Vec<String>, String> {
+    Ok(email_contacts::list_groups(&config::get_email_contacts_path(&app)))
+}
+
+/// 收件人自动补全：按名称或邮箱匹配，常用联系人优先
+#[tauri::command]
+pub fn search_email_contacts(app: AppHandle, query: String, limit: Option<usize>) -> Result<Vec<Contact>, String> {
+    Ok(email_contacts::search(&config::get_email_contacts_path(&app), &query, limit.unwrap_or(10)))
+}
+
+/// 批量发送的收件人；name / title / email 及 variables 均可在模板中以 {{变量}} 引用
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchRecipient {
+    pub email: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchSendResult {
+    pub email: String,
+    pub record_id: Option<String>,
+    /// sent / failed
+    pub status: String,
+    pub error: Option<String>,
+}
+
+/// 两封邮件之间的默认间隔，避免触发服务商的频率限制
+const DEFAULT_BATCH_INTERVAL_MS: u64 = 2000;
+
+/// 邮件合并：按收件人渲染模板（正文与主题中的 {{name}}、{{title}} 等变量），
+/// 逐封写入发件箱并限速发送；contactGroup 可追加联系人分组中的所有成员
+/// 进度通过 email-batch:progress 事件推送，返回每位收件人的发送结果
+#[tauri::command]
+#[allow(non_snake_case)]
+pub async fn send_email_batch(
+    app: AppHandle,
+    accountId: String,
+    templateId: String,
+    recipients: Vec<BatchRecipient>,
+    contactGroup: Option<String>,
+    subject: Option<String>,
+    intervalMs: Option<u64>,
+) -> Result<Vec<BatchSendResult>, String> {
+    let mut recipients = recipients;
+    if let Some(group) = contactGroup.filter(|g| !g.is_empty()) {
+        let contacts = email_contacts::load_contacts(&config::get_email_contacts_path(&app));
+        for c in contacts.into_iter().filter(|c| c.group.as_deref() == Some(group.as_str())) {
+            if !recipients.iter().any(|r| r.email.eq_ignore_ascii_case(&c.email)) {
+                recipients.push(BatchRecipient { email: c.email, name: c.name, title: String::new(), variables: HashMap::new() });
+            }
+        }
+    }
+    if recipients.is_empty() {
+        return Err("收件人不能为空".to_string());
+    }
+
+    let content = template::get_template_content(&templateId)?;
+    let body = if content.content.trim().is_empty() { content.ai_generated_content } else { content.content };
+    let subject = match subject.filter(|s| !s.trim().is_empty()) {
+        Some(s) => s,
+        None => template::list_templates()
+            .into_iter()
+            .find(|t| t.id == templateId)
+            .map(|t| t.name)
+            .unwrap_or_default(),
+    };
+
+    let accounts_path = config::get_email_accounts_path(&app);
+    // 先解析一次，账户不存在或未授权时直接报错
+    email_account::resolve_account(&accounts_path, &accountId).await?;
+    let outbox = config::get_email_outbox_path(&app);
+    let interval = std::time::Duration::from_millis(intervalMs.unwrap_or(DEFAULT_BATCH_INTERVAL_MS));
+    let total = recipients.len();
+    let batch_id = uuid::Uuid::new_v4().to_string();
+
+    let mut results = Vec::with_capacity(total);
+    for (index, recipient) in recipients.into_iter().enumerate() {
+        if index > 0 {
+            tokio::time::sleep(interval).await;
+        }
+
+        let mut vars = recipient.variables.clone();
+        vars.insert("email".to_string(), recipient.email.clone());
+        vars.insert("name".to_string(), recipient.name.clone());
+        vars.insert("title".to_string(), recipient.title.clone());
+        let to = if recipient.name.trim().is_empty() {
+            recipient.email.clone()
+        } else {
+            format!("{} <{}>", recipient.name.trim(), recipient.email.trim())
+        };
+
+        // 每封邮件重新读取凭据，OAuth2 access token 在批量发送途中过期时自动刷新
+        let smtp = match email_account::resolve_account(&accounts_path, &accountId).await {
+            Ok(smtp) => smtp,
+            Err(e) => {
+                results.push(batch_progress(&app, &batch_id, index, total, BatchSendResult {
+                    email: recipient.email,
+                    record_id: None,
+                    status: "failed".to_string(),
+                    error: Some(e),
+                }));
+                continue;
+            }
+        };
+
+        let enqueued = email_queue::enqueue(&outbox, EmailRecord {
+            id: String::new(),
+            account_id: Some(accountId.clone()),
+            from: smtp.email.clone(),
+            to: vec![to],
+            cc: Vec::new(),
+            bcc: Vec::new(),
+            subject: template::render_variables(&subject, &vars),
+            body: template::render_variables(&body, &vars),
+            is_html: true,
+            is_raw_html: false,
+            status: String::new(),
+            send_at: None,
+            attempts: 0,
+            last_error: None,
+            created_at: 0,
+            sent_at: None,
+        });
+
+        let result = match enqueued {
+            Ok(record) => match deliver_record(&app, &smtp, &record).await {
+                Ok(_) => BatchSendResult { email: recipient.email, record_id: Some(record.id), status: "sent".to_string(), error: None },
+                Err(e) => BatchSendResult { email: recipient.email, record_id: Some(record.id), status: "failed".to_string(), error: Some(e) },
+            },
+            Err(e) => BatchSendResult { email: recipient.email, record_id: None, status: "failed".to_string(), error: Some(e) },
+        };
+
+        results.push(batch_progress(&app, &batch_id, index, total, result));
+    }
+
+    Ok(results)
+}
+
+/// 推送一位收件人的批量发送进度
+fn batch_progress(app: &AppHandle, batch_id: &str, index: usize, total: usize, result: BatchSendResult) -> BatchSendResult {
+    let _ = app.emit("email-batch:progress", serde_json::json!({
+        "batchId": batch_id,
+        "index": index + 1,
+        "total": total,
+        "email": result.email,
+        "status": result.status,
+        "error": result.error,
+    }));
+    result
+}
+
+/// 列出发送历史（最新在前），status 可选 queued / sending / sent / failed
+#[tauri::command]
+pub fn list_sent_emails(app: AppHandle, status: Option<String>, limit: Option<usize>) -> Result<Vec<EmailRecord>, String> {
+    Ok(email_queue::list(&config::get_email_outbox_path(&app), status.as_deref(), limit))
+}
+
+/// 重新发送失败的邮件（仅限关联了已保存账户的记录）
+#[tauri::command]
+pub async fn retry_email(app: AppHandle, id: String) -> Result<String, String> {
+    let path = config::get_email_outbox_path(&app);
+    let record = email_queue::get(&path, &id).ok_or_else(|| format!("发件记录不存在: {}", id))?;
+    // 定时、已取消和已发送的邮件都不能通过重试发送
+    if record.status != "failed" {
+        return Err(format!("只能重试发送失败的邮件（当前状态: {}）", record.status));
+    }
+    let account_id = record
+        .account_id
+        .clone()
+        .ok_or("该邮件未关联已保存的邮箱账户，无法重试，请重新发送")?;
+    // 同一封邮件被重复点击重试时只发送一次
+    if !email_queue::claim(&path, &id, "failed")? {
+        return Err("该邮件已在发送中".to_string());
+    }
+    let smtp = match email_account::resolve_account(&config::get_email_accounts_path(&app), &account_id).await {
+        Ok(smtp) => smtp,
+        Err(e) => {
+            email_queue::update_status(&path, &id, "failed", record.attempts, Some(e.clone()))?;
+            return Err(e);
+        }
+    };
+    deliver_record(&app, &smtp, &record).await
+}
+
+#[tauri::command]
+pub fn delete_sent_email(app: AppHandle, id: String) -> Result<(), String> {
+    email_queue::delete(&config::get_email_outbox_path(&app), &id)
+}
+
+/// 应用启动时恢复上次未完成的发送
+pub fn resume_pending_emails(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let path = config::get_email_outbox_path(&app);
+        if let Err(e) = email_queue::fail_orphaned(&path) {
+            tracing::warn!("更新发件箱失败: {}", e);
+        }
+        for record in email_queue::pending(&path) {
+            let Some(account_id) = record.account_id.clone() else { continue };
+            if !email_queue::claim(&path, &record.id, &record.status).unwrap_or(false) {
+                continue;
+            }
+            let result = match email_account::resolve_account(&config::get_email_accounts_path(&app), &account_id).await {
+                Ok(smtp) => deliver_record(&app, &smtp, &record).await,
+                Err(e) => email_queue::update_status(&path, &record.id, "failed", record.attempts, Some(e.clone()))
+                    .and(Err(e)),
+            };
+            if let Err(e) = result {
+                tracing::warn!("恢复发送 {} 失败: {}", record.id, e);
+            }
+        }
+    });
+}
+
+/// 定时发送的邮件
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledEmail {
+    /// 发送时间（Unix 秒）
+    pub send_at: i64,
+    pub account_id: String,
+    pub to: Vec<String>,
+    #[serde(default)]
+    pub cc: Vec<String>,
+    #[serde(default)]
+    pub bcc: Vec<String>,
+    pub subject: String,
+    pub body: String,
+    #[serde(default)]
+    pub is_html: bool,
+    #[serde(default)]
+    pub is_raw_html: bool,
+}
+
+/// 定时发送：邮件写入发件箱，由后台调度器在 sendAt（Unix 秒）到达后发送
+/// 需要使用已保存的账户，授权码在发送时才从钥匙串读取
+#[tauri::command]
+pub async fn schedule_email(app: AppHandle, email: ScheduledEmail) -> Result<EmailRecord, String> {
+    let (to, cc, bcc) = expand_recipients(&app, email.to, email.cc, email.bcc);
     if to.is_empty() {
         return Err("收件人不能为空".to_string());
     }
+    if email.send_at <= chrono::Utc::now().timestamp() {
+        return Err("定时发送时间必须晚于当前时间".to_string());
+    }
+    let account = email_account::load_accounts(&config::get_email_accounts_path(&app))
+        .into_iter()
+        .find(|a| a.id == email.account_id)
+        .ok_or_else(|| format!("邮箱账户不存在: {}", email.account_id))?;
+
+    email_queue::enqueue(&config::get_email_outbox_path(&app), EmailRecord {
+        id: String::new(),
+        account_id: Some(email.account_id),
+        from: account.email,
+        to,
+        cc,
+        bcc,
+        subject: email.subject,
+        body: email.body,
+        is_html: email.is_html,
+        is_raw_html: email.is_raw_html,
+        status: String::new(),
+        send_at: Some(email.send_at),
+        attempts: 0,
+        last_error: None,
+        created_at: 0,
+        sent_at: None,
+    })
+}
+
+#[tauri::command]
+pub fn list_scheduled_emails(app: AppHandle) -> Result<Vec<EmailRecord>, String> {
+    let mut records = email_queue::list(&config::get_email_outbox_path(&app), Some("scheduled"), None);
+    records.sort_by_key(|r| r.send_at);
+    Ok(records)
+}
+
+#[tauri::command]
+pub fn cancel_scheduled_email(app: AppHandle, id: String) -> Result<(), String> {
+    email_queue::cancel_scheduled(&config::get_email_outbox_path(&app), &id)
+}
+
+/// 定时邮件调度器检查间隔
+const SCHEDULER_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// 启动定时邮件调度器（应用启动时调用，错过的定时邮件会在启动后立即补发）
+pub fn start_email_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let path = config::get_email_outbox_path(&app);
+        loop {
+            for record in email_queue::due_scheduled(&path, chrono::Utc::now().timestamp()) {
+                let Some(account_id) = record.account_id.clone() else { continue };
+                // 快照之后用户可能已取消，认领失败时跳过
+                match email_queue::claim(&path, &record.id, "scheduled") {
+                    Ok(true) => {}
+                    Ok(false) => continue,
+                    Err(e) => {
+                        tracing::warn!("认领定时邮件 {} 失败: {}", record.id, e);
+                        continue;
+                    }
+                }
+                let result = match email_account::resolve_account(&config::get_email_accounts_path(&app), &account_id).await {
+                    Ok(smtp) => deliver_record(&app, &smtp, &record).await,
+                    Err(e) => email_queue::update_status(&path, &record.id, "failed", record.attempts, Some(e.clone()))
+                        .and(Err(e)),
+                };
+                if let Err(e) = result {
+                    tracing::warn!("定时邮件 {} 发送失败: {}", record.id, e);
+                }
+            }
+            tokio::time::sleep(SCHEDULER_INTERVAL).await;
+        }
+    });
+}
+
+/// 从 IMAP 文件夹导入邮件为文档：正文转为 Markdown，附件保存到项目目录
+/// since 格式为 YYYY-MM-DD；默认只导入未读邮件，同一封邮件在同一项目中只导入一次
+#[tauri::command]
+#[allow(non_snake_case)]
+#[allow(clippy::too_many_arguments)]
+pub async fn import_emails_from_imap(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    accountId: String,
+    projectId: String,
+    folder: Option<String>,
+    unseenOnly: Option<bool>,
+    since: Option<String>,
+    limit: Option<usize>,
+    markSeen: Option<bool>,
+) -> Result<Vec<ImportedEmail>, String> {
+    let project_dir = state.config.projects_dir.join(&projectId);
+    if !project_dir.exists() {
+        return Err(format!("项目不存在: {}", projectId));
+    }
+
+    let mut criteria = Vec::new();
+    if unseenOnly.unwrap_or(true) {
+        criteria.push("UNSEEN".to_string());
+    }
+    if let Some(since) = since.filter(|s| !s.is_empty()) {
+        let date = chrono::NaiveDate::parse_from_str(&since, "%Y-%m-%d")
+            .map_err(|_| format!("无效的日期: {}（格式应为 YYYY-MM-DD）", since))?;
+        criteria.push(format!("SINCE {}", imap_client::search_date(&date)));
+    }
+    if criteria.is_empty() {
+        criteria.push("ALL".to_string());
+    }
+
+    let settings = email_account::resolve_imap(&config::get_email_accounts_path(&app), &accountId).await?;
+    let folder = folder.filter(|f| !f.is_empty()).unwrap_or_else(|| "INBOX".to_string());
+    let mark_seen = markSeen.unwrap_or(false);
+    let imported_path = config::get_email_imported_path(&app);
+
+    let mut session = ImapSession::connect(&settings).await?;
+    session.select(&folder, !mark_seen).await?;
+    let mut uids = session.uid_search(&criteria.join(" ")).await?;
+    // 只取最新的 limit 封
+    let limit = limit.unwrap_or(50);
+    if uids.len() > limit {
+        uids.drain(..uids.len() - limit);
+    }
 
+    let mut results = Vec::new();
+    for uid in uids {
+        let raw = match session.fetch_raw(uid).await {
+            Ok(raw) => raw,
+            Err(e) => {
+                results.push(ImportedEmail { uid, subject: String::new(), document_id: None, status: "failed".to_string(), error: Some(e) });
+                continue;
+            }
+        };
+        let email = email_import::parse_email(&raw);
+        let mut result = ImportedEmail { uid, subject: email.subject.clone(), document_id: None, status: "imported".to_string(), error: None };
+
+        if let Some(message_id) = &email.message_id {
+            if email_import::is_imported(&imported_path, &projectId, message_id) {
+                result.status = "skipped".to_string();
+                results.push(result);
+                continue;
+            }
+        }
+
+        match email_import::create_document(&project_dir, &projectId, &email, |id| state.get_document_path(&projectId, id)) {
+            Ok(document) => {
+                result.document_id = Some(document.id);
+                if let Some(message_id) = &email.message_id {
+                    if let Err(e) = email_import::mark_imported(&imported_path, &projectId, message_id) {
+                        tracing::warn!("记录已导入邮件失败: {}", e);
+                    }
+                }
+                if mark_seen {
+                    if let Err(e) = session.mark_seen(uid).await {
+                        tracing::warn!("标记邮件 {} 已读失败: {}", uid, e);
+                    }
+                }
+            }
+            Err(e) => {
+                result.status = "failed".to_string();
+                result.error = Some(e);
+            }
+        }
+        results.push(result);
+    }
+
+    session.logout().await;
+    Ok(results)
+}
+
+/// 单封邮件最多尝试次数
+const MAX_SEND_ATTEMPTS: u32 = 3;
+
+/// 投递一条发件记录：暂时性错误（网络、4xx）按指数退避重试，永久性错误立即失败
+async fn deliver_record(app: &AppHandle, smtp: &SmtpSettings, record: &EmailRecord) -> Result<String, String> {
+    let path = config::get_email_outbox_path(app);
+    let message = match build_message(smtp, record) {
+        Ok(m) => m,
+        Err(e) => {
+            email_queue::update_status(&path, &record.id, "failed", record.attempts, Some(e.clone()))?;
+            return Err(e);
+        }
+    };
+
+    let creds = Credentials::new(smtp.email.clone(), smtp.password.clone());
+    let transport = build_smtp_transport(&smtp.host, smtp.port, &smtp.encryption, creds, smtp.xoauth2)
+        .map_err(|e| format!("构建 SMTP 连接失败: {}", e))?;
+
+    let mut attempts = record.attempts;
+    let mut last_error = String::new();
+    for round in 0..MAX_SEND_ATTEMPTS {
+        attempts += 1;
+        email_queue::update_status(&path, &record.id, "sending", attempts, None)?;
+
+        match transport.send(message.clone()).await {
+            Ok(_) => {
+                email_queue::update_status(&path, &record.id, "sent", attempts, None)?;
+                let recipients: Vec<String> = record.to.iter().chain(&record.cc).chain(&record.bcc).cloned().collect();
+                email_contacts::record_usage(&config::get_email_contacts_path(app), &recipients);
+                return Ok(format!("邮件已成功发送至 {}", record.to.join(", ")));
+            }
+            Err(e) => {
+                last_error = e.to_string();
+                let retryable = !(e.is_permanent() || e.is_client() || e.is_tls());
+                if !retryable || round + 1 == MAX_SEND_ATTEMPTS {
+                    break;
+                }
+                tracing::warn!("第 {} 次发送失败，稍后重试: {}", attempts, last_error);
+                tokio::time::sleep(std::time::Duration::from_secs(2u64 << round)).await;
+            }
+        }
+    }
+
+    email_queue::update_status(&path, &record.id, "failed", attempts, Some(last_error.clone()))?;
+    Err(format!("发送邮件失败: {}", last_error))
+}
+
+/// 根据发件记录构建邮件
+fn build_message(smtp: &SmtpSettings, record: &EmailRecord) -> Result<Message, String> {
     // 构建发件人
-    let from_mailbox: Mailbox = if let Some(ref name) = displayName {
-        format!("{} <{}>", name, email)
+    let from_mailbox: Mailbox = if let Some(ref name) = smtp.display_name {
+        format!("{} <{}>", name, smtp.email)
             .parse()
             .map_err(|e| format!("发件人地址格式错误: {}", e))?
     } else {
-        email
+        smtp.email
             .parse()
             .map_err(|e| format!("发件人地址格式错误: {}", e))?
     };
 
     let mut builder = Message::builder()
         .from(from_mailbox)
-        .subject(&subject);
+        .subject(&record.subject);
 
     // 添加收件人
-    for addr in &to {
+    for addr in &record.to {
         let mailbox: Mailbox = addr
             .trim()
             .parse()
@@ -73,7 +665,7 @@ pub async fn send_email(
     }
 
     // 添加抄送
-    for addr in &cc {
+    for addr in &record.cc {
         let trimmed = addr.trim();
         if trimmed.is_empty() {
             continue;
@@ -85,7 +677,7 @@ pub async fn send_email(
     }
 
     // 添加密送
-    for addr in &bcc {
+    for addr in &record.bcc {
         let trimmed = addr.trim();
         if trimmed.is_empty() {
             continue;
@@ -97,12 +689,11 @@ pub async fn send_email(
     }
 
     // 构建邮件正文
-    let raw_html = isRawHtml.unwrap_or(false);
-    let message = if raw_html {
+    let message = if record.is_raw_html {
         // body 已经是完整 HTML（富文本编辑器输出），包装邮件模板后直接发送
-        let html_body = wrap_html_email(&body);
+        let html_body = wrap_html_email(&record.body);
         // 生成纯文本备用版本（简单去标签）
-        let plain_text = strip_html_tags(&body);
+        let plain_text = strip_html_tags(&record.body);
         builder
             .multipart(
                 MultiPart::alternative()
@@ -111,96 +702,85 @@ pub async fn send_email(
                             .header(ContentType::TEXT_PLAIN)
                             .body(plain_text),
                     )
-                    .singlepart(
-                        SinglePart::builder()
-                            .header(ContentType::TEXT_HTML)
-                            .body(html_body),
-                    ),
+                    .multipart(html_with_inline_images(html_body)),
             )
             .map_err(|e| format!("构建邮件失败: {}", e))?
-    } else if isHtml {
+    } else if record.is_html {
         // Markdown → HTML 转换
-        let html_body = markdown_to_html(&body);
+        let html_body = markdown_to_html(&record.body);
         builder
             .multipart(
                 MultiPart::alternative()
                     .singlepart(
                         SinglePart::builder()
                             .header(ContentType::TEXT_PLAIN)
-                            .body(body.clone()),
+                            .body(record.body.clone()),
                     )
-                    .singlepart(
-                        SinglePart::builder()
-                            .header(ContentType::TEXT_HTML)
-                            .body(html_body),
-                    ),
+                    .multipart(html_with_inline_images(html_body)),
             )
             .map_err(|e| format!("构建邮件失败: {}", e))?
     } else {
         builder
-            .body(body.clone())
+            .body(record.body.clone())
             .map_err(|e| format!("构建邮件失败: {}", e))?
     };
 
-    // 发送
-    let creds = Credentials::new(email.clone(), password);
-    let transport = build_smtp_transport(&smtpHost, smtpPort, &encryption, creds)
-        .map_err(|e| format!("构建 SMTP 连接失败: {}", e))?;
-
-    transport
-        .send(message)
-        .await
-        .map_err(|e| format!("发送邮件失败: {}", e))?;
+    Ok(message)
+}
 
-    let recipients: Vec<&str> = to.iter().map(|s| s.as_str()).collect();
-    Ok(format!(
-        "邮件已成功发送至 {}",
-        recipients.join(", ")
-    ))
+/// HTML 正文：本地图片改为 cid 引用，与正文一起组成 multipart/related
+fn html_with_inline_images(html_body: String) -> MultiPart {
+    let (html, images) = email_images::embed_local_images(&html_body);
+    let mut related = MultiPart::related().singlepart(
+        SinglePart::builder()
+            .header(ContentType::TEXT_HTML)
+            .body(html),
+    );
+    for img in images {
+        let Ok(content_type) = ContentType::parse(&img.content_type) else { continue };
+        related = related.singlepart(Attachment::new_inline(img.cid).body(img.data, content_type));
+    }
+    related
 }
 
-/// 构建 SMTP 传输
+/// 构建 SMTP 传输；xoauth2 为 true 时 creds 中的密码是 OAuth2 access token
 fn build_smtp_transport(
     host: &str,
     port: u16,
     encryption: &str,
     creds: Credentials,
+    xoauth2: bool,
 ) -> Result<AsyncSmtpTransport<Tokio1Executor>, String> {
-    match encryption {
+    let builder = match encryption {
         "tls" => {
             let tls_params = TlsParameters::new(host.to_string())
                 .map_err(|e| format!("TLS 参数错误: {}", e))?;
-            Ok(
-                AsyncSmtpTransport::<Tokio1Executor>::relay(host)
-                    .map_err(|e| format!("SMTP relay 错误: {}", e))?
-                    .port(port)
-                    .tls(Tls::Wrapper(tls_params))
-                    .credentials(creds)
-                    .build(),
-            )
+            AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+                .map_err(|e| format!("SMTP relay 错误: {}", e))?
+                .port(port)
+                .tls(Tls::Wrapper(tls_params))
         }
         "starttls" => {
             let tls_params = TlsParameters::new(host.to_string())
                 .map_err(|e| format!("TLS 参数错误: {}", e))?;
-            Ok(
-                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
-                    .map_err(|e| format!("SMTP STARTTLS relay 错误: {}", e))?
-                    .port(port)
-                    .tls(Tls::Required(tls_params))
-                    .credentials(creds)
-                    .build(),
-            )
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+                .map_err(|e| format!("SMTP STARTTLS relay 错误: {}", e))?
+                .port(port)
+                .tls(Tls::Required(tls_params))
         }
         _ => {
             // 无加密
-            Ok(
-                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
-                    .port(port)
-                    .credentials(creds)
-                    .build(),
-            )
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host).port(port)
         }
-    }
+    };
+
+    let builder = builder.credentials(creds);
+    let builder = if xoauth2 {
+        builder.authentication(vec![Mechanism::Xoauth2])
+    } else {
+        builder
+    };
+    Ok(builder.build())
 }
 
 /// 将富文本编辑器输出的 HTML 片段包装为完整的邮件 HTML 模板
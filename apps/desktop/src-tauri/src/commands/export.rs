@@ -1,49 +1,399 @@
 #![allow(non_snake_case)]
 
+use crate::citations;
 use crate::config::AppState;
+use crate::document::Document;
+use crate::email_import::unique_path;
 use crate::error::Result;
+use crate::export_styles;
+use crate::i18n;
+use crate::jobs;
+use crate::markdown_format;
 use crate::native_export;
-use tauri::State;
+use crate::native_export::styles::StyleProfile;
+use crate::project::Project;
+use crate::resource_engine::ResourceEngineState;
+use crate::{config, settings};
+use std::borrow::Cow;
+use std::path::Path;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
 
-/// 原生导出（无需外部依赖，公文排版标准）
+/// 导出前处理正文：按设置规范化 Markdown，渲染引用与参考文献列表
+pub(crate) fn prepare_content<'a>(app: &AppHandle, state: &AppState, project_id: &str, content: &'a str) -> Cow<'a, str> {
+    let mut content = Cow::Borrowed(content);
+    if settings::load(&config::get_settings_path(app)).export.format_before_export {
+        content = Cow::Owned(markdown_format::format(&content, &Default::default()).content);
+    }
+    if citations::has_citations(&content) {
+        let refs = citations::load(&state.config.projects_dir.join(project_id));
+        if !refs.is_empty() {
+            content = Cow::Owned(citations::render(&content, &refs));
+        }
+    }
+    content
+}
+
+/// 按设置生成导出选项；仅 PDF 需要查找内嵌字体
+pub(crate) fn export_options(app: &AppHandle, format: &str) -> native_export::ExportOptions {
+    let export = settings::load(&config::get_settings_path(app)).export;
+    let mut options = native_export::ExportOptions {
+        style: export_style(app, &export.style_profile),
+        // 打印 PDF 和渲染 Mermaid 图表都使用该浏览器
+        pdf_browser: existing_file(export.pdf_browser_path, "浏览器程序").map(Into::into),
+        code_theme: Some(export.code_theme).filter(|t| !t.is_empty()),
+        pptx_theme: export.pptx_theme,
+        header_footer: export.header_footer,
+        cover: settings::CoverSettings {
+            logo_path: existing_file(export.cover.logo_path, "封面徽标文件"),
+            ..export.cover
+        },
+        watermark: settings::WatermarkSettings {
+            image_path: existing_file(export.watermark.image_path, "水印图片"),
+            ..export.watermark
+        },
+        images: export.images,
+        reference_docx: existing_file(export.reference_docx, "参考文档").map(Into::into),
+        ..Default::default()
+    };
+    if format == "pdf" && export.pdf_fonts.embed {
+        options.pdf_fonts = native_export::fonts::resolve(&export.pdf_fonts, &config::get_font_dirs(app));
+    }
+    options
+}
+
+/// 设置中的文件可能在保存后被移走，导出时缺失则警告并忽略
+fn existing_file(path: Option<String>, what: &str) -> Option<String> {
+    let path = path.filter(|p| !p.trim().is_empty())?;
+    if Path::new(&path).is_file() {
+        return Some(path);
+    }
+    tracing::warn!("{}不存在，已忽略: {}", what, path);
+    None
+}
+
+/// 写入导出文件属性的文档信息
+pub(crate) fn document_info(document: &Document) -> native_export::DocumentInfo {
+    native_export::DocumentInfo { author: document.metadata.author.clone(), created_at: document.metadata.created_at }
+}
+
+/// 按 ID 读取导出样式；找不到（如自定义样式已删除）时使用默认样式
+fn export_style(app: &AppHandle, id: &str) -> StyleProfile {
+    export_styles::find(&app.state::<ResourceEngineState>(), id).unwrap_or_else(|e| {
+        tracing::warn!("{}，使用默认导出样式", e);
+        StyleProfile::default()
+    })
+}
+
+/// 可选的导出样式方案（内置方案 + 自定义方案）
+#[tauri::command]
+pub fn list_export_styles(resourceState: State<'_, ResourceEngineState>) -> Vec<StyleProfile> {
+    export_styles::list(&resourceState)
+}
+
+/// 可选的代码高亮主题（HTML / PDF 导出）
+#[tauri::command]
+pub fn list_code_themes() -> Vec<String> {
+    native_export::html::CODE_THEMES.iter().map(|t| t.to_string()).collect()
+}
+
+/// 可选的 PPTX 幻灯片主题
 #[tauri::command]
-pub fn export_document_native(
+pub fn list_pptx_themes() -> Vec<native_export::pptx::PptxTheme> {
+    native_export::pptx::THEMES.to_vec()
+}
+
+/// 保存自定义导出样式；id 为空时新建，返回保存后的方案
+#[tauri::command]
+pub fn save_export_style(resourceState: State<'_, ResourceEngineState>, profile: StyleProfile) -> Result<StyleProfile> {
+    export_styles::save(&resourceState, profile)
+}
+
+#[tauri::command]
+pub fn delete_export_style(resourceState: State<'_, ResourceEngineState>, id: String) -> Result<()> {
+    export_styles::delete(&resourceState, &id)
+}
+
+/// 检测 PDF 导出可用的中文字体（设置页展示各字体来源）
+#[tauri::command]
+pub fn detect_pdf_fonts(app: AppHandle) -> Vec<native_export::fonts::ResolvedFont> {
+    let fonts = settings::load(&config::get_settings_path(&app)).export.pdf_fonts;
+    native_export::fonts::resolve(&fonts, &config::get_font_dirs(&app))
+}
+
+/// 按设置中的文件名模板生成导出文件名（含扩展名）
+fn export_file_name(app: &AppHandle, state: &AppState, document: &Document, format: &str, suffix: &str) -> String {
+    let template = settings::load(&config::get_settings_path(app)).export.filename_template;
+    let project_name = std::fs::read_to_string(state.get_project_path(&document.project_id))
+        .ok()
+        .and_then(|json| serde_json::from_str::<Project>(&json).ok())
+        .map(|p| p.name)
+        .unwrap_or_default();
+    let vars = native_export::filename::FilenameVars {
+        project: &project_name,
+        title: &document.title,
+        author: &document.metadata.author,
+        format,
+        suffix,
+    };
+    format!("{}.{}", native_export::filename::render(&template, &vars), format)
+}
+
+/// 导出对话框的默认文件名
+#[tauri::command]
+pub fn suggest_export_filename(
+    app: AppHandle,
     state: State<'_, AppState>,
     documentId: String,
     projectId: String,
     format: String,
-    outputPath: String,
-    contentOverride: Option<String>,
+    suffix: Option<String>,
 ) -> Result<String> {
     let doc_path = state.get_document_path(&projectId, &documentId);
-
     if !doc_path.exists() {
-        return Err(format!("文档未找到: {}", documentId));
+        return Err(i18n::tf("error.document_not_found", &[&documentId]));
     }
+    let document = Document::load_meta(&doc_path).map_err(|e| e.to_string())?;
+    Ok(export_file_name(&app, &state, &document, &format, suffix.as_deref().unwrap_or_default()))
+}
 
-    let document = crate::document::Document::load(&doc_path).map_err(|e| e.to_string())?;
-    let content = contentOverride.as_deref().unwrap_or(&document.ai_generated_content);
-    let title = &document.title;
+/// 原生导出（无需外部依赖）；styleProfile 指定样式方案，缺省时使用设置中的默认方案（公文）；
+/// includeToc 为 true 时按标题生成目录（HTML / PDF / DOCX / TXT），tocDepth 为目录层级，默认 3 级；
+/// includeCover 指定是否插入封面（HTML / PDF / DOCX），缺省时按设置；
+/// 以后台任务执行，各阶段发送 export:progress 事件，可通过 cancel_export(taskId) 取消，取消时删除未写完的文件
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_document_native(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    documentId: String,
+    projectId: String,
+    format: String,
+    outputPath: String,
+    contentOverride: Option<String>,
+    styleProfile: Option<String>,
+    includeToc: Option<bool>,
+    tocDepth: Option<u8>,
+    includeCover: Option<bool>,
+    taskId: Option<String>,
+) -> Result<String> {
+    let state = state.inner().clone();
+    let handle = app.clone();
+    jobs::run(&app, "native_export", taskId, move |job| {
+        let doc_path = state.get_document_path(&projectId, &documentId);
+        if !doc_path.exists() {
+            return Err(i18n::tf("error.document_not_found", &[&documentId]));
+        }
+
+        let document = Document::load(&doc_path).map_err(|e| e.to_string())?;
+        let content = contentOverride.as_deref().unwrap_or(&document.ai_generated_content);
+        let content = prepare_content(&handle, &state, &projectId, content);
+        let title = &document.title;
+
+        let mut options = export_options(&handle, &format);
+        if let Some(id) = styleProfile.filter(|p| !p.trim().is_empty()) {
+            options.style = export_styles::find(&handle.state::<ResourceEngineState>(), &id)?;
+        }
+        if includeToc.unwrap_or(false) {
+            options.toc_depth = Some(tocDepth.unwrap_or(native_export::toc::DEFAULT_DEPTH).clamp(1, 6));
+        }
+        if let Some(include) = includeCover {
+            options.cover.enabled = include;
+        }
+        options.info = document_info(&document);
 
-    native_export::export_native(content, title, &outputPath, &format)
+        let progress = |percent: f32, stage: &str| {
+            job.check()?;
+            job.progress(percent, stage);
+            let event = ExportProgress { task_id: job.id().to_string(), stage: stage.to_string(), progress: percent };
+            let _ = handle.emit("export:progress", &event);
+            Ok(())
+        };
+        // 找不到浏览器时 PDF 导出改为生成并立即打开 _print.html 打印页，它不会覆盖目标文件，直接写到最终位置
+        let print_page = format == "pdf" && options.pdf_browser.clone().or_else(native_export::pdf::find_browser).is_none();
+        // 其余格式先导出到同目录下带临时前缀的文件，完成后再替换目标；取消或失败时只删除临时文件，不影响已有文件
+        let prefix = if print_page { String::new() } else { format!(".{}.", &uuid::Uuid::new_v4().simple().to_string()[..8]) };
+        let target = Path::new(&outputPath);
+        let file_name = target.file_name().ok_or_else(|| format!("无效的导出路径: {}", outputPath))?;
+        let tmp_path = target.with_file_name(format!("{}{}", prefix, file_name.to_string_lossy()));
+        let written = match native_export::export_native_with_progress(&content, title, &tmp_path.to_string_lossy(), &format, &options, &progress) {
+            Ok(written) => written,
+            Err(e) => {
+                let _ = std::fs::remove_file(&tmp_path);
+                return Err(e);
+            }
+        };
+        let finish = || -> std::result::Result<String, String> {
+            job.check()?;
+            if prefix.is_empty() {
+                return Ok(written.clone());
+            }
+            let written = Path::new(&written);
+            let final_name = written
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_prefix(&prefix))
+                .ok_or_else(|| format!("导出结果路径异常: {}", written.display()))?;
+            let final_path = written.with_file_name(final_name);
+            std::fs::rename(written, &final_path).map_err(|e| format!("写入文件失败: {}", e))?;
+            Ok(final_path.to_string_lossy().to_string())
+        };
+        let result = finish();
+        if result.is_err() {
+            let _ = std::fs::remove_file(&written);
+        }
+        result
+    })
+    .await
+}
+
+/// export:progress 事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportProgress {
+    task_id: String,
+    /// 当前阶段，如“渲染 Mermaid 图表”“生成文档内容”“打印 PDF”
+    stage: String,
+    /// 0–100
+    progress: f32,
+}
+
+/// 取消 export_document_native 启动的导出；导出已结束时返回 false
+#[tauri::command]
+pub fn cancel_export(taskId: String) -> bool {
+    jobs::cancel(&taskId)
 }
 
 /// 导出文档（原生格式）
 #[tauri::command]
-pub fn export_document(
+#[allow(clippy::too_many_arguments)]
+pub async fn export_document(
+    app: AppHandle,
     state: State<'_, AppState>,
     documentId: String,
     projectId: String,
     format: String,
     outputPath: String,
     contentOverride: Option<String>,
+    styleProfile: Option<String>,
+    includeToc: Option<bool>,
+    tocDepth: Option<u8>,
+    includeCover: Option<bool>,
+    taskId: Option<String>,
 ) -> Result<String> {
-    export_document_native(state, documentId, projectId, format, outputPath, contentOverride)
+    export_document_native(
+        app,
+        state,
+        documentId,
+        projectId,
+        format,
+        outputPath,
+        contentOverride,
+        styleProfile,
+        includeToc,
+        tocDepth,
+        includeCover,
+        taskId,
+    )
+    .await
+}
+
+/// 批量导出项目中的多个文档到同一目录，文件名按设置中的模板生成，重名时自动编号；
+/// 以后台任务执行，可通过 cancel_job(jobId) 取消，已导出的文件保留
+#[tauri::command]
+pub async fn export_documents_batch(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    projectId: String,
+    documentIds: Vec<String>,
+    format: String,
+    outputDir: String,
+    jobId: Option<String>,
+) -> Result<Vec<String>> {
+    let state = state.inner().clone();
+    let handle = app.clone();
+    jobs::run(&app, "batch_export", jobId, move |job| {
+        let dir = Path::new(&outputDir);
+        std::fs::create_dir_all(dir).map_err(|e| format!("创建输出目录失败: {}", e))?;
+        let mut options = export_options(&handle, &format);
+        let mut exported = Vec::new();
+        for (i, document_id) in documentIds.iter().enumerate() {
+            job.check()?;
+            let doc_path = state.get_document_path(&projectId, document_id);
+            if !doc_path.exists() {
+                return Err(i18n::tf("error.document_not_found", &[document_id]));
+            }
+            let document = Document::load(&doc_path).map_err(|e| e.to_string())?;
+            job.progress(i as f32 * 100.0 / documentIds.len() as f32, format!("正在导出 {}", document.title));
+            let content = prepare_content(&handle, &state, &projectId, &document.ai_generated_content);
+            let output = unique_path(dir, &export_file_name(&handle, &state, &document, &format, ""));
+            options.info = document_info(&document);
+            exported.push(native_export::export_native(
+                &content,
+                &document.title,
+                &output.to_string_lossy(),
+                &format,
+                &options,
+            )?);
+        }
+        Ok(exported)
+    })
+    .await
+}
+
+/// 合并导出：按 documentIds 的顺序拼接多个文档，导出为一个 DOCX / PDF / HTML 文件；
+/// title 为合并后的文档标题（缺省时使用第一篇文档的标题），addTitles 为 true 时每篇文档以其标题作为一级标题，
+/// pageBreaks 为 true（默认）时每篇文档从新页开始
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn export_merged_documents(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    projectId: String,
+    documentIds: Vec<String>,
+    format: String,
+    outputPath: String,
+    title: Option<String>,
+    addTitles: Option<bool>,
+    pageBreaks: Option<bool>,
+) -> Result<String> {
+    if !["docx", "pdf", "html"].contains(&format.as_str()) {
+        return Err(format!("合并导出不支持该格式: {}", format));
+    }
+    if documentIds.is_empty() {
+        return Err("请选择要合并的文档".to_string());
+    }
+    let mut documents = Vec::new();
+    for document_id in &documentIds {
+        let doc_path = state.get_document_path(&projectId, document_id);
+        if !doc_path.exists() {
+            return Err(i18n::tf("error.document_not_found", &[document_id]));
+        }
+        documents.push(Document::load(&doc_path).map_err(|e| e.to_string())?);
+    }
+    let contents: Vec<Cow<str>> = documents
+        .iter()
+        .map(|d| prepare_content(&app, &state, &projectId, &d.ai_generated_content))
+        .collect();
+    let parts: Vec<native_export::merge::MergePart> = documents
+        .iter()
+        .zip(&contents)
+        .map(|(d, content)| native_export::merge::MergePart { title: &d.title, content })
+        .collect();
+    let merged = native_export::merge::merge(&parts, addTitles.unwrap_or(false), pageBreaks.unwrap_or(true));
+
+    let title = title.filter(|t| !t.trim().is_empty()).unwrap_or_else(|| documents[0].title.clone());
+    let options = native_export::ExportOptions {
+        info: native_export::DocumentInfo { author: documents[0].metadata.author.clone(), created_at: 0 },
+        ..export_options(&app, &format)
+    };
+    native_export::export_native(&merged, &title, &outputPath, &format, &options)
 }
 
 /// 导出到临时文件并用指定程序打开
 #[tauri::command]
 pub fn export_and_open(
+    app: AppHandle,
     state: State<'_, AppState>,
     documentId: String,
     projectId: String,
@@ -54,23 +404,23 @@ pub fn export_and_open(
     let doc_path = state.get_document_path(&projectId, &documentId);
 
     if !doc_path.exists() {
-        return Err(format!("文档未找到: {}", documentId));
+        return Err(i18n::tf("error.document_not_found", &[&documentId]));
     }
 
-    let document = crate::document::Document::load(&doc_path).map_err(|e| e.to_string())?;
+    let document = Document::load(&doc_path).map_err(|e| e.to_string())?;
     let title = &document.title;
-    let export_content = contentOverride.as_deref().unwrap_or(&document.ai_generated_content);
+    let export_content = prepare_content(&app, &state, &projectId, contentOverride.as_deref().unwrap_or(&document.ai_generated_content));
 
     // 构建临时文件路径
     let temp_dir = std::env::temp_dir().join("aidocplus_export");
     std::fs::create_dir_all(&temp_dir).map_err(|e| format!("创建临时目录失败: {}", e))?;
 
-    let safe_title = title.replace(['/', '\\', ':', '*', '?', '"', '<', '>', '|'], "_");
-    let output_path = temp_dir.join(format!("{}.{}", safe_title, format));
+    let output_path = temp_dir.join(export_file_name(&app, &state, &document, &format, ""));
     let output_str = output_path.to_string_lossy().to_string();
 
     // 导出文件
-    native_export::export_native(export_content, title, &output_str, &format)?;
+    let options = native_export::ExportOptions { info: document_info(&document), ..export_options(&app, &format) };
+    native_export::export_native(&export_content, title, &output_str, &format, &options)?;
 
     // 用指定程序或默认程序打开
     let open_result = match appName.as_deref() {
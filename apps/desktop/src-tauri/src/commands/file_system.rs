@@ -1,4 +1,5 @@
-use crate::error::Result;
+use crate::error::{run_blocking, Result};
+use crate::i18n;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -51,71 +52,74 @@ fn get_allowed_directories() -> Vec<PathBuf> {
 }
 
 #[tauri::command]
-pub fn read_directory(path: String) -> Result<FileSystemEntry> {
-    let path_obj = Path::new(&path);
+pub async fn read_directory(path: String) -> Result<FileSystemEntry> {
+    run_blocking(move || {
+        let path_obj = Path::new(&path);
 
-    if !path_obj.exists() {
-        return Err(format!("Path does not exist: {}", path));
-    }
+        if !path_obj.exists() {
+            return Err(format!("Path does not exist: {}", path));
+        }
+
+        let name = path_obj
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+            .to_string();
+
+        if path_obj.is_file() {
+            return Ok(FileSystemEntry {
+                path,
+                name,
+                is_directory: false,
+                is_file: true,
+                children: None,
+            });
+        }
 
-    let name = path_obj
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("")
-        .to_string();
+        let entries = fs::read_dir(&path)
+            .map_err(|e| e.to_string())?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                // Filter hidden files
+                entry
+                    .file_name()
+                    .to_str()
+                    .map(|n| !n.starts_with('.'))
+                    .unwrap_or(false)
+            })
+            .map(|entry| {
+                let entry_path = entry.path();
+                let entry_name = entry
+                    .file_name()
+                    .to_str()
+                    .unwrap_or("")
+                    .to_string();
+
+                Ok(FileSystemEntry {
+                    path: entry_path.to_string_lossy().to_string(),
+                    name: entry_name,
+                    is_directory: entry_path.is_dir(),
+                    is_file: entry_path.is_file(),
+                    children: None,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-    if path_obj.is_file() {
-        return Ok(FileSystemEntry {
+        Ok(FileSystemEntry {
             path,
             name,
-            is_directory: false,
-            is_file: true,
-            children: None,
-        });
-    }
-
-    let entries = fs::read_dir(&path)
-        .map_err(|e| e.to_string())?
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| {
-            // Filter hidden files
-            entry
-                .file_name()
-                .to_str()
-                .map(|n| !n.starts_with('.'))
-                .unwrap_or(false)
-        })
-        .map(|entry| {
-            let entry_path = entry.path();
-            let entry_name = entry
-                .file_name()
-                .to_str()
-                .unwrap_or("")
-                .to_string();
-
-            Ok(FileSystemEntry {
-                path: entry_path.to_string_lossy().to_string(),
-                name: entry_name,
-                is_directory: entry_path.is_dir(),
-                is_file: entry_path.is_file(),
-                children: None,
-            })
+            is_directory: true,
+            is_file: false,
+            children: Some(entries),
         })
-        .collect::<Result<Vec<_>>>()?;
-
-    Ok(FileSystemEntry {
-        path,
-        name,
-        is_directory: true,
-        is_file: false,
-        children: Some(entries),
     })
+    .await
 }
 
 #[tauri::command]
 pub fn read_file(path: String) -> Result<String> {
     if !Path::new(&path).exists() {
-        return Err(format!("File not found: {}", path));
+        return Err(i18n::tf("error.file_not_found", &[&path]));
     }
     Ok(fs::read_to_string(&path).map_err(|e| e.to_string())?)
 }
@@ -0,0 +1,28 @@
+use crate::error::{run_blocking, Result};
+use crate::markdown_format::{self, FormatResult, FormatRules};
+use crate::outline::{self, OutlineItem};
+use crate::readability::{self, ReadabilityReport};
+
+/// 规范化 Markdown：返回修正后的文本与问题列表（前端可只展示问题而不替换内容）
+#[tauri::command]
+pub fn format_markdown(content: String, rules: Option<FormatRules>) -> Result<FormatResult> {
+    Ok(markdown_format::format(&content, &rules.unwrap_or_default()))
+}
+
+/// 可读性分析：阅读时长、句长分布、长句、被动句和冗余词
+#[tauri::command]
+pub async fn analyze_readability(content: String) -> Result<ReadabilityReport> {
+    run_blocking(move || Ok(readability::analyze(&content))).await
+}
+
+/// 当前文档的标题结构（章节 ID、级别、标题）
+#[tauri::command]
+pub fn get_outline(content: String) -> Result<Vec<OutlineItem>> {
+    Ok(outline::current_outline(&outline::split_sections(&content)))
+}
+
+/// 按大纲重组文档：调整章节顺序和标题层级，章节正文原样保留
+#[tauri::command]
+pub async fn restructure_by_outline(content: String, outline: Vec<OutlineItem>) -> Result<String> {
+    run_blocking(move || Ok(outline::restructure(&content, &outline))).await
+}
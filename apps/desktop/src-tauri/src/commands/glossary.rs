@@ -0,0 +1,39 @@
+#![allow(non_snake_case)]
+
+use crate::config::AppState;
+use crate::error::Result;
+use crate::glossary::{self, GlossaryEntry};
+use crate::i18n;
+use crate::spellcheck::SpellingIssue;
+use std::path::PathBuf;
+use tauri::State;
+
+fn project_dir(state: &AppState, project_id: &str) -> Result<PathBuf> {
+    if !state.get_project_path(project_id).exists() {
+        return Err(i18n::tf("error.project_not_found", &[&project_id]));
+    }
+    Ok(state.config.projects_dir.join(project_id))
+}
+
+/// 项目术语表
+#[tauri::command]
+pub fn list_glossary(state: State<'_, AppState>, projectId: String) -> Result<Vec<GlossaryEntry>> {
+    Ok(glossary::load(&project_dir(&state, &projectId)?))
+}
+
+/// 新增或更新术语（id 为空时新增）
+#[tauri::command]
+pub fn save_glossary_entry(state: State<'_, AppState>, projectId: String, entry: GlossaryEntry) -> Result<GlossaryEntry> {
+    glossary::upsert(&project_dir(&state, &projectId)?, entry)
+}
+
+#[tauri::command]
+pub fn delete_glossary_entry(state: State<'_, AppState>, projectId: String, id: String) -> Result<()> {
+    glossary::delete(&project_dir(&state, &projectId)?, &id)
+}
+
+/// 检查文本中的禁用写法，返回位置（UTF-16 偏移）与规范术语
+#[tauri::command]
+pub fn check_terminology(state: State<'_, AppState>, projectId: String, text: String) -> Result<Vec<SpellingIssue>> {
+    Ok(glossary::check(&glossary::load(&project_dir(&state, &projectId)?), &text))
+}
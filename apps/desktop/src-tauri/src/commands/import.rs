@@ -1,11 +1,15 @@
-#![allow(unused_assignments, unused_variables)]
+#![allow(non_snake_case, unused_assignments, unused_variables)]
 
-use crate::error::Result;
+use crate::config::AppState;
+use crate::error::{run_blocking, Result};
+use crate::jobs;
+use crate::vault_import::{self, VaultImportReport};
 use quick_xml::events::Event;
 use quick_xml::reader::Reader;
 use std::fs;
 use std::io::Read;
 use std::path::Path;
+use tauri::AppHandle;
 
 /// ZIP 炸弹防护限制
 const MAX_UNCOMPRESSED_SIZE: u64 = 100 * 1024 * 1024; // 100MB
@@ -14,33 +18,63 @@ const MAX_SINGLE_FILE_SIZE: u64 = 50 * 1024 * 1024; // 50MB
 
 /// 导入文件并返回 Markdown 格式的内容
 /// 支持：.txt, .md, .csv, .html, .xml, .json, .docx
+/// 以后台任务执行，可通过 cancel_job(jobId) 取消
 #[tauri::command]
-pub fn import_file(path: String) -> Result<String> {
-    let file_path = Path::new(&path);
+pub async fn import_file(app: AppHandle, path: String, jobId: Option<String>) -> Result<String> {
+    jobs::run(&app, "import_file", jobId, move |job| {
+        let file_path = Path::new(&path);
 
-    if !file_path.exists() {
-        return Err(format!("文件不存在: {}", path));
-    }
+        if !file_path.exists() {
+            return Err(format!("文件不存在: {}", path));
+        }
 
-    let ext = file_path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_lowercase();
+        let ext = file_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
 
-    match ext.as_str() {
-        // 纯文本类文件：直接读取
-        "txt" | "md" | "markdown" | "json" | "xml" | "csv" | "html" | "htm" | "yaml" | "yml"
-        | "toml" | "ini" | "log" | "rst" | "tex" | "rtf" => {
-            fs::read_to_string(&path).map_err(|e| format!("读取文件失败: {}", e))
+        job.progress(10.0, "正在读取文件");
+        let content = match ext.as_str() {
+            // 纯文本类文件：直接读取
+            "txt" | "md" | "markdown" | "json" | "xml" | "csv" | "html" | "htm" | "yaml" | "yml"
+            | "toml" | "ini" | "log" | "rst" | "tex" | "rtf" => {
+                fs::read_to_string(&path).map_err(|e| format!("读取文件失败: {}", e))
+            }
+            // Word 文档
+            "docx" => import_docx(&path),
+            _ => Err(format!(
+                "不支持的文件格式: .{}\n\n支持的格式：txt, md, json, xml, csv, html, yaml, toml, docx",
+                ext
+            )),
+        }?;
+        // 解析完成后才取消时丢弃结果，前端不会再插入内容
+        job.check()?;
+        Ok(content)
+    })
+    .await
+}
+
+/// 导入 Obsidian / Typora 笔记库：每篇 Markdown 笔记创建一个文档，
+/// 双链转为文档链接，引用的图片等本地文件复制为文档附件
+#[tauri::command]
+pub async fn import_markdown_vault(
+    state: tauri::State<'_, AppState>,
+    projectId: String,
+    vaultPath: String,
+) -> Result<VaultImportReport> {
+    let projects_dir = state.config.projects_dir.clone();
+    run_blocking(move || {
+        let project_dir = projects_dir.join(&projectId);
+        if !projects_dir.join(format!("{}.json", projectId)).exists() {
+            return Err(format!("项目不存在: {}", projectId));
         }
-        // Word 文档
-        "docx" => import_docx(&path),
-        _ => Err(format!(
-            "不支持的文件格式: .{}\n\n支持的格式：txt, md, json, xml, csv, html, yaml, toml, docx",
-            ext
-        )),
-    }
+        let docs_dir = project_dir.join("documents");
+        vault_import::import_vault(Path::new(&vaultPath), &project_dir, &projectId, |id| {
+            docs_dir.join(format!("{}.json", id))
+        })
+    })
+    .await
 }
 
 /// 解析 DOCX 文件，提取文本内容并转换为 Markdown
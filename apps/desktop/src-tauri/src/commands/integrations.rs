@@ -0,0 +1,53 @@
+#![allow(non_snake_case)]
+
+use super::export::prepare_content;
+use crate::config::AppState;
+use crate::document::Document;
+use crate::error::Result;
+use crate::i18n;
+use crate::integrations::{self, Publication, PublishCredentials, PublishTarget};
+use tauri::{AppHandle, State};
+
+/// 发布文档到 Confluence 空间或 Notion 数据库 / 页面；已发布过则更新原页面
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn publish_document(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    documentId: String,
+    projectId: String,
+    target: PublishTarget,
+    credentials: PublishCredentials,
+    spaceOrDatabase: String,
+    contentOverride: Option<String>,
+) -> Result<Publication> {
+    let doc_path = state.get_document_path(&projectId, &documentId);
+    if !doc_path.exists() {
+        return Err(i18n::tf("error.document_not_found", &[&documentId]));
+    }
+    let document = Document::load(&doc_path).map_err(|e| e.to_string())?;
+    let content = contentOverride.as_deref().unwrap_or(&document.ai_generated_content);
+    let content = prepare_content(&app, &state, &projectId, content).into_owned();
+    let existing = document
+        .publications
+        .iter()
+        .find(|p| p.target == target && p.container == spaceOrDatabase)
+        .cloned();
+
+    let publication = integrations::publish(
+        target,
+        &credentials,
+        &spaceOrDatabase,
+        &document.title,
+        &content,
+        existing.as_ref(),
+    )
+    .await?;
+
+    // 发布期间文档可能已被保存，重新读取后再记录
+    let mut document = Document::load(&doc_path).map_err(|e| e.to_string())?;
+    document.publications.retain(|p| !(p.target == target && p.container == spaceOrDatabase));
+    document.publications.push(publication.clone());
+    document.save(&doc_path).map_err(|e| e.to_string())?;
+    Ok(publication)
+}
@@ -0,0 +1,15 @@
+#![allow(non_snake_case)]
+
+use crate::jobs::{self, JobInfo};
+
+/// 请求取消后台任务；任务已结束时返回 false
+#[tauri::command]
+pub fn cancel_job(jobId: String) -> bool {
+    jobs::cancel(&jobId)
+}
+
+/// 正在执行的后台任务
+#[tauri::command]
+pub fn list_jobs() -> Vec<JobInfo> {
+    jobs::list()
+}
@@ -0,0 +1,18 @@
+use crate::config;
+use crate::logging::{self, LogEntry};
+use tauri::AppHandle;
+
+/// 默认返回的日志条数
+const DEFAULT_LOG_LIMIT: usize = 200;
+
+/// 最近的日志（最新在前），level 为最低级别（如 "warn" 返回警告和错误），用于反馈 AI / 导出问题时附带诊断信息
+#[tauri::command]
+pub fn get_recent_logs(level: Option<String>, limit: Option<usize>) -> Result<Vec<LogEntry>, String> {
+    logging::recent(level.as_deref(), limit.unwrap_or(DEFAULT_LOG_LIMIT))
+}
+
+/// 日志文件所在目录
+#[tauri::command]
+pub fn get_log_directory(app: AppHandle) -> Result<String, String> {
+    Ok(config::get_log_dir(&app).to_string_lossy().to_string())
+}
@@ -0,0 +1,20 @@
+use crate::menu::{MenuItemUpdate, MenuState};
+use tauri::State;
+
+/// 启用/禁用原生菜单项（如无打开文档时禁用导出）
+#[tauri::command]
+pub fn set_menu_item_enabled(state: State<'_, MenuState>, id: String, enabled: bool) -> Result<(), String> {
+    state.apply(MenuItemUpdate { id, enabled: Some(enabled), checked: None })
+}
+
+/// 勾选/取消勾选菜单项（仅视图菜单中的勾选项）
+#[tauri::command]
+pub fn set_menu_item_checked(state: State<'_, MenuState>, id: String, checked: bool) -> Result<(), String> {
+    state.apply(MenuItemUpdate { id, enabled: None, checked: Some(checked) })
+}
+
+/// 批量更新菜单项状态，返回失败项的错误信息
+#[tauri::command]
+pub fn update_menu_items(state: State<'_, MenuState>, items: Vec<MenuItemUpdate>) -> Result<Vec<String>, String> {
+    Ok(items.into_iter().filter_map(|u| state.apply(u).err()).collect())
+}
@@ -1,13 +1,34 @@
+pub mod activity;
 pub mod ai;
+pub mod api_server;
+pub mod backup;
+pub mod capture;
+pub mod citation;
+pub mod clipboard;
+pub mod conversation;
+pub mod crash;
+pub mod deep_link;
 pub mod document;
 pub mod email;
 pub mod export;
 pub mod file_system;
+pub mod format;
+pub mod glossary;
 pub mod import;
+pub mod integrations;
+pub mod job;
+pub mod logging;
+pub mod menu;
 pub mod pandoc;
 pub mod plugin;
+pub mod print;
 pub mod project;
 pub mod resource;
 pub mod template;
+pub mod scripting;
 pub mod search;
+pub mod secrets;
+pub mod settings;
+pub mod spellcheck;
+pub mod updater;
 pub mod workspace;
@@ -1,6 +1,14 @@
+use crate::citations;
+use crate::config::AppState;
+use crate::jobs::{self, Job};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::process::Command;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, State};
 
 /// Pandoc 检测结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,87 +17,188 @@ pub struct PandocCheckResult {
     pub version: Option<String>,
     pub path: Option<String>,
     pub error: Option<String>,
+    /// `pandoc --list-output-formats` 支持的输出格式
+    #[serde(default)]
+    pub output_formats: Vec<String>,
+    /// 已安装的 PDF 引擎（按推荐顺序）
+    #[serde(default)]
+    pub pdf_engines: Vec<String>,
+    /// PATH 中可用的 Pandoc 过滤器可执行文件
+    #[serde(default)]
+    pub filters: Vec<String>,
+    /// 用户数据目录 filters/ 下的 Lua 过滤器
+    #[serde(default)]
+    pub lua_filters: Vec<String>,
+    /// 是否内置 citeproc（Pandoc 2.11+）
+    #[serde(default)]
+    pub citeproc: bool,
 }
 
-/// 检测 Pandoc 是否安装及版本
+/// 可用于 PDF 输出的引擎，靠前的优先（xelatex / tectonic 对中文支持较好）
+const PDF_ENGINES: &[&str] = &[
+    "xelatex", "tectonic", "lualatex", "pdflatex", "wkhtmltopdf", "weasyprint", "typst", "prince", "pagedjs-cli", "context",
+];
+
+/// 常见的第三方过滤器
+const KNOWN_FILTERS: &[&str] = &[
+    "pandoc-crossref", "mermaid-filter", "pandoc-plantuml", "pandoc-fignos", "pandoc-eqnos", "pandoc-tablenos", "pandoc-secnos",
+];
+
+/// 上次检测结果，导出前用于预检，check_pandoc 时刷新
+static LAST_CHECK: Mutex<Option<PandocCheckResult>> = Mutex::new(None);
+
+/// 检测 Pandoc 是否安装及版本、可用的输出格式、PDF 引擎和过滤器
 #[tauri::command]
 pub fn check_pandoc() -> PandocCheckResult {
+    let result = probe_pandoc();
+    if let Ok(mut last) = LAST_CHECK.lock() {
+        *last = Some(result.clone());
+    }
+    result
+}
+
+fn cached_check() -> PandocCheckResult {
+    if let Some(result) = LAST_CHECK.lock().ok().and_then(|l| l.clone()) {
+        return result;
+    }
+    check_pandoc()
+}
+
+fn unavailable(error: String) -> PandocCheckResult {
+    PandocCheckResult {
+        available: false,
+        version: None,
+        path: None,
+        error: Some(error),
+        output_formats: Vec::new(),
+        pdf_engines: Vec::new(),
+        filters: Vec::new(),
+        lua_filters: Vec::new(),
+        citeproc: false,
+    }
+}
+
+fn probe_pandoc() -> PandocCheckResult {
     // 尝试运行 pandoc --version
-    match Command::new("pandoc").arg("--version").output() {
-        Ok(output) => {
-            if output.status.success() {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                // 第一行通常是 "pandoc X.Y.Z"
-                let version = stdout
-                    .lines()
-                    .next()
-                    .and_then(|line| line.strip_prefix("pandoc "))
-                    .unwrap_or("unknown")
-                    .to_string();
-
-                // 尝试获取 pandoc 路径
-                let path = get_pandoc_path();
-
-                PandocCheckResult {
-                    available: true,
-                    version: Some(version),
-                    path,
-                    error: None,
-                }
+    let output = match Command::new("pandoc").arg("--version").output() {
+        Ok(output) => output,
+        Err(e) => return unavailable(format!("未找到 Pandoc: {}", e)),
+    };
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        return unavailable(format!("Pandoc 执行失败: {}", stderr));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // 第一行通常是 "pandoc X.Y.Z"
+    let version = stdout
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("pandoc "))
+        .unwrap_or("unknown")
+        .to_string();
+    // 如 "User data directory: /home/user/.local/share/pandoc"
+    let data_dir = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("User data directory:"))
+        .map(|dir| dir.trim().to_string());
+
+    let output_formats = Command::new("pandoc")
+        .arg("--list-output-formats")
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect())
+        .unwrap_or_default();
+    let citeproc = Command::new("pandoc")
+        .arg("--help")
+        .output()
+        .is_ok_and(|o| String::from_utf8_lossy(&o.stdout).contains("--citeproc"));
+
+    let lua_filters = data_dir
+        .and_then(|dir| fs::read_dir(std::path::Path::new(&dir).join("filters")).ok())
+        .map(|entries| {
+            let mut names: Vec<String> = entries
+                .flatten()
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .filter(|name| name.ends_with(".lua"))
+                .collect();
+            names.sort();
+            names
+        })
+        .unwrap_or_default();
+
+    PandocCheckResult {
+        available: true,
+        version: Some(version),
+        // 尝试获取 pandoc 路径
+        path: find_executable("pandoc"),
+        error: None,
+        output_formats,
+        pdf_engines: PDF_ENGINES.iter().filter(|e| find_executable(e).is_some()).map(|e| e.to_string()).collect(),
+        filters: KNOWN_FILTERS.iter().filter(|f| find_executable(f).is_some()).map(|f| f.to_string()).collect(),
+        lua_filters,
+        citeproc,
+    }
+}
+
+/// 在 PATH 中查找可执行文件
+fn find_executable(name: &str) -> Option<String> {
+    #[cfg(target_os = "windows")]
+    let finder = "where";
+    #[cfg(not(target_os = "windows"))]
+    let finder = "which";
+
+    Command::new(finder)
+        .arg(name)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| String::from_utf8_lossy(&o.stdout).lines().next().map(|l| l.trim().to_string()))
+        .filter(|p| !p.is_empty())
+}
+
+/// 导出前预检：格式不受支持或缺少 PDF 引擎时给出明确提示，而不是 Pandoc 的原始报错；
+/// 返回需要追加的参数（未指定 PDF 引擎时自动选择已安装的引擎）
+fn preflight(format: &str, extra_args: &[String]) -> Result<Vec<String>, String> {
+    let check = cached_check();
+    if !check.available {
+        return Err(check.error.unwrap_or_else(|| "未找到 Pandoc".to_string()));
+    }
+    // 旧版本 Pandoc 不支持 --list-output-formats 时跳过格式检查
+    if !check.output_formats.is_empty() && !check.output_formats.iter().any(|f| f == format) {
+        return Err(format!("当前安装的 Pandoc 不支持导出为 {}", format));
+    }
+    if format != "pdf" {
+        return Ok(Vec::new());
+    }
+
+    let requested = extra_args.iter().find_map(|a| a.trim().strip_prefix("--pdf-engine=").map(str::to_string));
+    match requested {
+        Some(engine) => {
+            // 引擎可以是完整路径
+            let name = std::path::Path::new(&engine).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            if check.pdf_engines.contains(&name) || std::path::Path::new(&engine).is_file() {
+                Ok(Vec::new())
             } else {
-                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-                PandocCheckResult {
-                    available: false,
-                    version: None,
-                    path: None,
-                    error: Some(format!("Pandoc 执行失败: {}", stderr)),
-                }
+                Err(format!("未找到 PDF 引擎 {}，已安装: {}", engine, engines_list(&check)))
             }
         }
-        Err(e) => PandocCheckResult {
-            available: false,
-            version: None,
-            path: None,
-            error: Some(format!("未找到 Pandoc: {}", e)),
+        None => match check.pdf_engines.first() {
+            Some(engine) => Ok(vec![format!("--pdf-engine={}", engine)]),
+            None => Err(format!("导出 PDF 需要安装 PDF 引擎（如 {}）", PDF_ENGINES[..3].join(" / "))),
         },
     }
 }
 
-/// 获取 pandoc 可执行文件路径
-fn get_pandoc_path() -> Option<String> {
-    #[cfg(target_os = "windows")]
-    {
-        Command::new("where")
-            .arg("pandoc")
-            .output()
-            .ok()
-            .and_then(|o| {
-                if o.status.success() {
-                    Some(String::from_utf8_lossy(&o.stdout).trim().to_string())
-                } else {
-                    None
-                }
-            })
-    }
-    #[cfg(not(target_os = "windows"))]
-    {
-        Command::new("which")
-            .arg("pandoc")
-            .output()
-            .ok()
-            .and_then(|o| {
-                if o.status.success() {
-                    Some(String::from_utf8_lossy(&o.stdout).trim().to_string())
-                } else {
-                    None
-                }
-            })
-    }
+fn engines_list(check: &PandocCheckResult) -> String {
+    if check.pdf_engines.is_empty() { "无".to_string() } else { check.pdf_engines.join(", ") }
 }
 
-/// 调用 Pandoc 导出文档
+/// 调用 Pandoc 导出文档；以后台任务执行，cancel_job(jobId) 会终止 Pandoc 进程并删除未完成的输出
 #[tauri::command]
-pub fn pandoc_export(
+#[allow(clippy::too_many_arguments)]
+pub async fn pandoc_export(
+    app: AppHandle,
+    state: State<'_, AppState>,
     markdown: String,
     #[allow(non_snake_case)]
     outputPath: String,
@@ -97,7 +206,31 @@ pub fn pandoc_export(
     #[allow(non_snake_case)]
     extraArgs: Option<Vec<String>>,
     title: Option<String>,
+    #[allow(non_snake_case)]
+    projectId: Option<String>,
+    #[allow(non_snake_case)]
+    jobId: Option<String>,
+) -> Result<String, String> {
+    let projects_dir = state.config.projects_dir.clone();
+    jobs::run(&app, "pandoc_export", jobId, move |job| {
+        run_pandoc(job, &projects_dir, markdown, outputPath, format, extraArgs, title, projectId)
+    })
+    .await
+}
+
+#[allow(non_snake_case, clippy::too_many_arguments)]
+fn run_pandoc(
+    job: &Job,
+    projects_dir: &Path,
+    markdown: String,
+    outputPath: String,
+    format: String,
+    extraArgs: Option<Vec<String>>,
+    title: Option<String>,
+    projectId: Option<String>,
 ) -> Result<String, String> {
+    let preflight_args = preflight(&format, extraArgs.as_deref().unwrap_or_default())?;
+
     // 确保输出目录存在
     if let Some(parent) = std::path::Path::new(&outputPath).parent() {
         fs::create_dir_all(parent).map_err(|e| format!("创建输出目录失败: {}", e))?;
@@ -107,7 +240,8 @@ pub fn pandoc_export(
     let temp_dir = std::env::temp_dir().join("aidocplus_pandoc");
     fs::create_dir_all(&temp_dir).map_err(|e| format!("创建临时目录失败: {}", e))?;
 
-    let temp_md = temp_dir.join("input.md");
+    // 每个任务单独的输入文件，避免并发导出互相覆盖
+    let temp_md = temp_dir.join(format!("input-{}.md", job.id()));
     fs::write(&temp_md, &markdown).map_err(|e| format!("写入临时文件失败: {}", e))?;
 
     // 构建 pandoc 命令
@@ -139,21 +273,64 @@ pub fn pandoc_export(
         }
     }
 
+    for arg in preflight_args {
+        cmd.arg(arg);
+    }
+
+    // 项目参考文献：正文含引用时交给 citeproc 处理
+    if let Some(pid) = projectId.as_deref().filter(|_| citations::has_citations(&markdown)) {
+        let bib = citations::bib_path(&projects_dir.join(pid));
+        if bib.exists() && cached_check().citeproc {
+            cmd.arg("--citeproc").arg("--bibliography").arg(&bib);
+        }
+    }
+
     // 输入文件
     cmd.arg(&temp_md);
 
-    // 执行
-    let output = cmd
-        .output()
-        .map_err(|e| format!("执行 Pandoc 失败: {}。请确认 Pandoc 已正确安装。", e))?;
+    // 执行；stderr 在单独线程中读取，避免输出过多时管道阻塞
+    job.progress(5.0, "正在调用 Pandoc");
+    let mut child = cmd
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            let _ = fs::remove_file(&temp_md);
+            format!("执行 Pandoc 失败: {}。请确认 Pandoc 已正确安装。", e)
+        })?;
+    let stderr_reader = child.stderr.take().map(|mut stderr| {
+        std::thread::spawn(move || {
+            let mut buf = String::new();
+            let _ = stderr.read_to_string(&mut buf);
+            buf
+        })
+    });
+
+    let status = loop {
+        if job.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            let _ = fs::remove_file(&temp_md);
+            let _ = fs::remove_file(&outputPath);
+            return Err(jobs::CANCELLED.to_string());
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => std::thread::sleep(Duration::from_millis(100)),
+            Err(e) => {
+                let _ = fs::remove_file(&temp_md);
+                return Err(format!("执行 Pandoc 失败: {}", e));
+            }
+        }
+    };
+    let stderr = stderr_reader.and_then(|h| h.join().ok()).unwrap_or_default();
 
     // 清理临时文件
     let _ = fs::remove_file(&temp_md);
 
-    if output.status.success() {
+    if status.success() {
         Ok(outputPath)
     } else {
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
         Err(format!("Pandoc 导出失败: {}", stderr))
     }
 }
@@ -1,19 +1,87 @@
 #![allow(non_snake_case)]
 
+use crate::i18n;
 use crate::plugin::{self, PluginManifest};
+use crate::plugin_bus;
+use crate::plugin_dev::{self, DevPluginLink};
+use crate::plugin_migration::{self, MigrationReport, PluginMigration};
 use crate::error::Result;
+use crate::resource_engine::ResourceEngineState;
+use crate::tools;
+use tauri::{AppHandle, State};
 
 #[tauri::command]
-pub fn list_plugins() -> Result<Vec<PluginManifest>> {
-    Ok(plugin::list_plugins())
+pub fn list_plugins(state: State<'_, ResourceEngineState>) -> Result<Vec<PluginManifest>> {
+    Ok(plugin::list_plugins(&state))
 }
 
 #[tauri::command]
-pub fn set_plugin_enabled(pluginId: String, enabled: bool) -> Result<()> {
-    plugin::set_plugin_enabled(&pluginId, enabled)
+pub fn set_plugin_enabled(state: State<'_, ResourceEngineState>, pluginId: String, enabled: bool) -> Result<Vec<String>> {
+    plugin::set_plugin_enabled(&state, &pluginId, enabled)
 }
 
 #[tauri::command]
-pub fn sync_plugin_manifests(manifests: Vec<PluginManifest>) -> Result<()> {
-    plugin::sync_plugin_manifests(manifests)
+pub fn sync_plugin_manifests(state: State<'_, ResourceEngineState>, manifests: Vec<PluginManifest>) -> Result<Vec<String>> {
+    plugin::sync_plugin_manifests(&state, manifests)
+}
+
+/// 开发模式：链接本地插件源目录，变更时自动重新同步并发出 plugin:reloaded 事件
+#[tauri::command]
+pub fn link_dev_plugin(app: AppHandle, sourceDir: String) -> Result<DevPluginLink> {
+    plugin_dev::link_dev_plugin(&app, &sourceDir)
+}
+
+#[tauri::command]
+pub fn unlink_dev_plugin(pluginId: String) -> Result<()> {
+    plugin_dev::unlink_dev_plugin(&pluginId)
+}
+
+#[tauri::command]
+pub fn list_dev_plugins() -> Result<Vec<DevPluginLink>> {
+    Ok(plugin_dev::list_dev_links())
+}
+
+/// 注册插件数据迁移（主版本升级时由 sync_plugin_manifests 自动执行）
+#[tauri::command]
+pub fn register_plugin_migration(pluginId: String, migration: PluginMigration) -> Result<()> {
+    plugin_migration::register_migration(&pluginId, migration)
+}
+
+/// 手动对所有文档执行插件数据迁移（迁移前为每个文档创建备份版本）
+#[tauri::command]
+pub fn run_plugin_migrations(state: State<'_, ResourceEngineState>, pluginId: String) -> Result<MigrationReport> {
+    let manifest = plugin::list_plugins(&state)
+        .into_iter()
+        .find(|p| p.id == pluginId)
+        .ok_or_else(|| i18n::tf("error.plugin_not_found", &[&pluginId]))?;
+    plugin_migration::run_migrations(&manifest)
+}
+
+/// 事件总线：订阅主题（"命名空间:事件名"，支持 "ai:*" 通配），返回投递消息的事件名 plugin-bus:{pluginId}
+#[tauri::command]
+pub fn plugin_subscribe(pluginId: String, topic: String) -> Result<String> {
+    plugin_bus::subscribe(&pluginId, &topic)
+}
+
+#[tauri::command]
+pub fn plugin_unsubscribe(pluginId: String, topic: Option<String>) -> Result<()> {
+    plugin_bus::unsubscribe(&pluginId, topic.as_deref())
+}
+
+/// 事件总线：发布自身命名空间下的事件，返回投递的订阅者数量
+#[tauri::command]
+pub fn plugin_emit(app: AppHandle, pluginId: String, topic: String, payload: Option<serde_json::Value>) -> Result<usize> {
+    plugin_bus::emit_from_plugin(&app, &pluginId, &topic, payload.unwrap_or(serde_json::Value::Null))
+}
+
+/// 插件工具：返回 ai:tool:call 事件的执行结果（result 为字符串时原样交给模型，其他值序列化为 JSON）
+#[tauri::command]
+pub fn respond_plugin_tool_call(callId: String, result: Option<serde_json::Value>, error: Option<String>) -> Result<()> {
+    let result = match (error, result) {
+        (Some(e), _) => Err(e),
+        (None, Some(serde_json::Value::String(text))) => Ok(text),
+        (None, Some(value)) => Ok(value.to_string()),
+        (None, None) => Ok(String::new()),
+    };
+    tools::resolve_plugin_tool_call(&callId, result)
 }
@@ -0,0 +1,40 @@
+#![allow(non_snake_case)]
+
+use crate::config::AppState;
+use crate::document::Document;
+use crate::error::{run_blocking, Result};
+use crate::i18n;
+use crate::print;
+use super::export::{export_options, prepare_content};
+use tauri::{AppHandle, State};
+
+/// 打印文档正文：未指定打印机时弹出系统打印对话框，指定时静默打印
+#[tauri::command]
+pub async fn print_document(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    documentId: String,
+    projectId: String,
+    printer: Option<String>,
+    contentOverride: Option<String>,
+) -> Result<()> {
+    let doc_path = state.get_document_path(&projectId, &documentId);
+    if !doc_path.exists() {
+        return Err(i18n::tf("error.document_not_found", &[&documentId]));
+    }
+    let document = Document::load(&doc_path).map_err(|e| e.to_string())?;
+    let content = contentOverride.unwrap_or(document.ai_generated_content);
+    let content = prepare_content(&app, &state, &projectId, &content).into_owned();
+    match printer.filter(|p| !p.trim().is_empty()) {
+        Some(printer) => {
+            let options = export_options(&app, "pdf");
+            run_blocking(move || print::print_silent(&content, &document.title, &printer, &options)).await
+        }
+        None => print::print_with_dialog(&app, &content, &document.title),
+    }
+}
+
+#[tauri::command]
+pub async fn list_printers() -> Result<Vec<String>> {
+    run_blocking(|| Ok(print::list_printers())).await
+}
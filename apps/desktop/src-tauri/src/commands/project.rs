@@ -1,11 +1,14 @@
 use crate::config::AppState;
-use crate::error::Result;
+use crate::error::{run_blocking, Result};
+use crate::i18n;
+use crate::jobs::{self, Job};
 use crate::project::{Project, ProjectSettings};
+use crate::workspace::{load_project_session, project_session_path};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::{Read, Write};
-use std::path::Path;
-use tauri::State;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
 use uuid::Uuid;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -32,6 +35,7 @@ pub fn create_project(
         updated_at: now,
         settings: ProjectSettings::default(),
         path: state.config.projects_dir.join(format!("{}.json", id)),
+        session: None,
     };
 
     // Create project directory
@@ -48,17 +52,24 @@ pub fn create_project(
 }
 
 #[tauri::command]
-pub fn open_project(state: State<'_, AppState>, project_id: String) -> Result<Project> {
-    let project_path = state.get_project_path(&project_id);
+pub async fn open_project(state: State<'_, AppState>, project_id: String) -> Result<Project> {
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let project_path = state.get_project_path(&project_id);
 
-    if !project_path.exists() {
-        return Err(format!("Project not found: {}", project_id));
-    }
+        if !project_path.exists() {
+            return Err(i18n::tf("error.project_not_found", &[&project_id]));
+        }
 
-    let json = fs::read_to_string(&project_path).map_err(|e| e.to_string())?;
-    let project: Project = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+        let json = fs::read_to_string(&project_path).map_err(|e| e.to_string())?;
+        let mut project: Project = serde_json::from_str(&json).map_err(|e| e.to_string())?;
 
-    Ok(project)
+        // 附带上次的标签页、光标与滚动位置
+        project.session = load_project_session(&project_session_path(&state.config.projects_dir, &project_id));
+
+        Ok(project)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -77,7 +88,7 @@ pub fn rename_project(state: State<'_, AppState>, project_id: String, new_name:
     let project_path = state.get_project_path(&project_id);
 
     if !project_path.exists() {
-        return Err(format!("Project not found: {}", project_id));
+        return Err(i18n::tf("error.project_not_found", &[&project_id]));
     }
 
     let json = fs::read_to_string(&project_path).map_err(|e| e.to_string())?;
@@ -93,193 +104,224 @@ pub fn rename_project(state: State<'_, AppState>, project_id: String, new_name:
 }
 
 #[tauri::command]
-pub fn delete_project(state: State<'_, AppState>, project_id: String) -> Result<()> {
-    let project_path = state.get_project_path(&project_id);
-    let project_dir = state.config.projects_dir.join(&project_id);
-
-    // Remove project metadata file
-    if project_path.exists() {
-        fs::remove_file(&project_path).map_err(|e| e.to_string())?;
-    }
+pub async fn delete_project(state: State<'_, AppState>, project_id: String) -> Result<()> {
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let project_path = state.get_project_path(&project_id);
+        let project_dir = state.config.projects_dir.join(&project_id);
+
+        // Remove project metadata file
+        if project_path.exists() {
+            fs::remove_file(&project_path).map_err(|e| e.to_string())?;
+        }
 
-    // Remove project directory
-    if project_dir.exists() {
-        fs::remove_dir_all(&project_dir).map_err(|e| e.to_string())?;
-    }
+        // Remove project directory
+        if project_dir.exists() {
+            fs::remove_dir_all(&project_dir).map_err(|e| e.to_string())?;
+        }
 
-    Ok(())
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn list_projects(state: State<'_, AppState>) -> Result<Vec<Project>> {
-    let mut projects = Vec::new();
+pub async fn list_projects(state: State<'_, AppState>) -> Result<Vec<Project>> {
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let mut projects = Vec::new();
 
-    let entries = fs::read_dir(&state.config.projects_dir).map_err(|e| e.to_string())?;
+        let entries = fs::read_dir(&state.config.projects_dir).map_err(|e| e.to_string())?;
 
-    for entry in entries {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
+        for entry in entries {
+            let entry = entry.map_err(|e| e.to_string())?;
+            let path = entry.path();
 
-        // Only process .json files (project metadata)
-        if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            if let Ok(json) = fs::read_to_string(&path) {
-                if let Ok(project) = serde_json::from_str::<Project>(&json) {
-                    projects.push(project);
+            // Only process .json files (project metadata)
+            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+                if let Ok(json) = fs::read_to_string(&path) {
+                    if let Ok(project) = serde_json::from_str::<Project>(&json) {
+                        projects.push(project);
+                    }
                 }
             }
         }
-    }
 
-    // Sort by updated_at (most recent first)
-    projects.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        // Sort by updated_at (most recent first)
+        projects.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
 
-    Ok(projects)
+        Ok(projects)
+    })
+    .await
 }
 
-/// 将项目导出为 ZIP 压缩包（包含项目元数据 + 所有文档）
+/// 将项目导出为 ZIP 压缩包（包含项目元数据 + 所有文档）；
+/// 以后台任务执行，可通过 cancel_job(jobId) 取消，取消或失败时删除未写完的压缩包
 #[allow(non_snake_case)]
 #[tauri::command]
-pub fn export_project_zip(
+pub async fn export_project_zip(
+    app: AppHandle,
     state: State<'_, AppState>,
     projectId: String,
     outputPath: String,
+    jobId: Option<String>,
 ) -> Result<String> {
-    let project_meta_path = state.get_project_path(&projectId);
-    let project_dir = state.config.projects_dir.join(&projectId);
-
-    if !project_meta_path.exists() {
-        return Err(format!("项目未找到: {}", projectId));
-    }
-
-    let output = Path::new(&outputPath);
-    let file = fs::File::create(output).map_err(|e| format!("创建 ZIP 文件失败: {}", e))?;
-    let mut zip_writer = zip::ZipWriter::new(file);
-    let options = zip::write::FileOptions::default()
-        .compression_method(zip::CompressionMethod::Deflated);
+    let state = state.inner().clone();
+    jobs::run(&app, "project_zip_export", jobId, move |job| {
+        let project_meta_path = state.get_project_path(&projectId);
+        let project_dir = state.config.projects_dir.join(&projectId);
 
-    // 写入项目元数据
-    let meta_json = fs::read_to_string(&project_meta_path)
-        .map_err(|e| format!("读取项目元数据失败: {}", e))?;
-    zip_writer
-        .start_file("project.json", options)
-        .map_err(|e| format!("ZIP 写入失败: {}", e))?;
-    zip_writer
-        .write_all(meta_json.as_bytes())
-        .map_err(|e| format!("ZIP 写入失败: {}", e))?;
-
-    // 写入所有文档
-    let docs_dir = project_dir.join("documents");
-    if docs_dir.exists() {
-        let entries = fs::read_dir(&docs_dir).map_err(|e| e.to_string())?;
-        for entry in entries {
-            let entry = entry.map_err(|e| e.to_string())?;
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                let file_name = path.file_name().unwrap().to_string_lossy().to_string();
-                let content = fs::read_to_string(&path)
-                    .map_err(|e| format!("读取文档失败: {}", e))?;
-                zip_writer
-                    .start_file(format!("documents/{}", file_name), options)
-                    .map_err(|e| format!("ZIP 写入失败: {}", e))?;
-                zip_writer
-                    .write_all(content.as_bytes())
-                    .map_err(|e| format!("ZIP 写入失败: {}", e))?;
-            }
+        if !project_meta_path.exists() {
+            return Err(i18n::tf("error.project_not_found", &[&projectId]));
         }
-    }
 
-    // 写入版本历史目录（如果存在）
-    let versions_dir = project_dir.join("versions");
-    if versions_dir.exists() {
-        fn add_dir_to_zip(
-            zip_writer: &mut zip::ZipWriter<fs::File>,
-            dir: &Path,
-            prefix: &str,
-            options: zip::write::FileOptions,
-        ) -> std::result::Result<(), String> {
-            let entries = fs::read_dir(dir).map_err(|e| e.to_string())?;
+        // 先收集要打包的文件，便于计算进度
+        let mut files = vec![("project.json".to_string(), project_meta_path.clone())];
+        let docs_dir = project_dir.join("documents");
+        if docs_dir.exists() {
+            let entries = fs::read_dir(&docs_dir).map_err(|e| e.to_string())?;
             for entry in entries {
                 let entry = entry.map_err(|e| e.to_string())?;
                 let path = entry.path();
-                let name = path.file_name().unwrap().to_string_lossy().to_string();
-                let zip_path = format!("{}/{}", prefix, name);
-                if path.is_dir() {
-                    add_dir_to_zip(zip_writer, &path, &zip_path, options)?;
-                } else {
-                    let content = fs::read_to_string(&path)
-                        .map_err(|e| format!("读取文件失败: {}", e))?;
-                    zip_writer
-                        .start_file(&zip_path, options)
-                        .map_err(|e| format!("ZIP 写入失败: {}", e))?;
-                    zip_writer
-                        .write_all(content.as_bytes())
-                        .map_err(|e| format!("ZIP 写入失败: {}", e))?;
+                let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+                // 文档 JSON 及大文档的独立正文文件
+                if file_name.ends_with(".json") || file_name.ends_with(".content.md") {
+                    files.push((format!("documents/{}", file_name), path));
                 }
             }
-            Ok(())
         }
-        add_dir_to_zip(&mut zip_writer, &versions_dir, "versions", options)?;
+        // 版本历史目录（如果存在）
+        let versions_dir = project_dir.join("versions");
+        if versions_dir.exists() {
+            collect_zip_files(&versions_dir, "versions", &mut files)?;
+        }
+
+        let output = Path::new(&outputPath);
+        let result = write_project_zip(job, output, &files);
+        if result.is_err() {
+            let _ = fs::remove_file(output);
+        }
+        result.map(|_| outputPath)
+    })
+    .await
+}
+
+fn collect_zip_files(dir: &Path, prefix: &str, files: &mut Vec<(String, PathBuf)>) -> Result<()> {
+    let entries = fs::read_dir(dir).map_err(|e| e.to_string())?;
+    for entry in entries {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path();
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let zip_path = format!("{}/{}", prefix, name);
+        if path.is_dir() {
+            collect_zip_files(&path, &zip_path, files)?;
+        } else {
+            files.push((zip_path, path));
+        }
+    }
+    Ok(())
+}
+
+fn write_project_zip(job: &Job, output: &Path, files: &[(String, PathBuf)]) -> Result<()> {
+    let file = fs::File::create(output).map_err(|e| format!("创建 ZIP 文件失败: {}", e))?;
+    let mut zip_writer = zip::ZipWriter::new(file);
+    let options = zip::write::FileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    for (i, (zip_path, path)) in files.iter().enumerate() {
+        job.check()?;
+        job.progress(i as f32 * 100.0 / files.len() as f32, format!("正在打包 {}", zip_path));
+        let content = fs::read_to_string(path).map_err(|e| format!("读取文件失败: {}", e))?;
+        zip_writer
+            .start_file(zip_path.as_str(), options)
+            .map_err(|e| format!("ZIP 写入失败: {}", e))?;
+        zip_writer
+            .write_all(content.as_bytes())
+            .map_err(|e| format!("ZIP 写入失败: {}", e))?;
     }
 
     zip_writer
         .finish()
         .map_err(|e| format!("ZIP 完成失败: {}", e))?;
-
-    Ok(outputPath)
+    Ok(())
 }
 
 /// 从 ZIP 压缩包导入项目
 #[allow(non_snake_case)]
 #[tauri::command]
-pub fn import_project_zip(
+pub async fn import_project_zip(
+    app: AppHandle,
     state: State<'_, AppState>,
     zipPath: String,
+    jobId: Option<String>,
 ) -> Result<Project> {
-    let zip_file = fs::File::open(&zipPath)
-        .map_err(|e| format!("打开 ZIP 文件失败: {}", e))?;
-    let mut archive = zip::ZipArchive::new(zip_file)
-        .map_err(|e| format!("解析 ZIP 文件失败: {}", e))?;
-
-    // 先读取项目元数据
-    let mut meta_json = String::new();
-    {
-        let mut meta_file = archive
-            .by_name("project.json")
-            .map_err(|_| "ZIP 中未找到 project.json，不是有效的项目备份".to_string())?;
-        meta_file
-            .read_to_string(&mut meta_json)
-            .map_err(|e| format!("读取项目元数据失败: {}", e))?;
-    }
+    let state = state.inner().clone();
+    jobs::run(&app, "project_zip_import", jobId, move |job| {
+        let zip_file = fs::File::open(&zipPath)
+            .map_err(|e| format!("打开 ZIP 文件失败: {}", e))?;
+        let mut archive = zip::ZipArchive::new(zip_file)
+            .map_err(|e| format!("解析 ZIP 文件失败: {}", e))?;
+
+        // 先读取项目元数据
+        let mut meta_json = String::new();
+        {
+            let mut meta_file = archive
+                .by_name("project.json")
+                .map_err(|_| "ZIP 中未找到 project.json，不是有效的项目备份".to_string())?;
+            meta_file
+                .read_to_string(&mut meta_json)
+                .map_err(|e| format!("读取项目元数据失败: {}", e))?;
+        }
 
-    let mut project: Project = serde_json::from_str(&meta_json)
-        .map_err(|e| format!("解析项目元数据失败: {}", e))?;
-
-    // 检查 ID 冲突，如果已存在则生成新 ID
-    let existing_path = state.get_project_path(&project.id);
-    let new_id = if existing_path.exists() {
-        let id = Uuid::new_v4().to_string();
-        project.name = format!("{} (导入)", project.name);
-        id
-    } else {
-        project.id.clone()
-    };
+        let mut project: Project = serde_json::from_str(&meta_json)
+            .map_err(|e| format!("解析项目元数据失败: {}", e))?;
 
-    let old_id = project.id.clone();
-    project.id = new_id.clone();
-    project.path = state.get_project_path(&new_id);
-    project.updated_at = chrono::Utc::now().timestamp();
-
-    // 创建项目目录
-    let project_dir = state.config.projects_dir.join(&new_id);
-    fs::create_dir_all(project_dir.join("documents")).map_err(|e| e.to_string())?;
-    fs::create_dir_all(project_dir.join("versions")).map_err(|e| e.to_string())?;
+        // 检查 ID 冲突，如果已存在则生成新 ID
+        let existing_path = state.get_project_path(&project.id);
+        let new_id = if existing_path.exists() {
+            let id = Uuid::new_v4().to_string();
+            project.name = format!("{} (导入)", project.name);
+            id
+        } else {
+            project.id.clone()
+        };
 
-    // 保存项目元数据
-    let project_json = serde_json::to_string_pretty(&project).map_err(|e| e.to_string())?;
-    fs::write(&project.path, &project_json).map_err(|e| e.to_string())?;
+        let old_id = project.id.clone();
+        project.id = new_id.clone();
+        project.path = state.get_project_path(&new_id);
+        project.updated_at = chrono::Utc::now().timestamp();
+
+        // 创建项目目录
+        let project_dir = state.config.projects_dir.join(&new_id);
+        fs::create_dir_all(project_dir.join("documents")).map_err(|e| e.to_string())?;
+        fs::create_dir_all(project_dir.join("versions")).map_err(|e| e.to_string())?;
+
+        // 保存项目元数据
+        let project_json = serde_json::to_string_pretty(&project).map_err(|e| e.to_string())?;
+        fs::write(&project.path, &project_json).map_err(|e| e.to_string())?;
+
+        // 解压文档和版本文件；取消或失败时删除已创建的项目
+        let result = extract_project_files(job, &mut archive, &project_dir, &old_id, &new_id);
+        if result.is_err() {
+            let _ = fs::remove_file(&project.path);
+            let _ = fs::remove_dir_all(&project_dir);
+        }
+        result.map(|_| project)
+    })
+    .await
+}
 
-    // 解压文档和版本文件
-    for i in 0..archive.len() {
+fn extract_project_files(
+    job: &Job,
+    archive: &mut zip::ZipArchive<fs::File>,
+    project_dir: &Path,
+    old_id: &str,
+    new_id: &str,
+) -> Result<()> {
+    let total = archive.len();
+    for i in 0..total {
+        job.check()?;
+        job.progress(i as f32 * 100.0 / total as f32, "正在解压项目文件");
         let mut file = archive.by_index(i).map_err(|e| e.to_string())?;
         let name = file.name().to_string();
 
@@ -303,7 +345,7 @@ pub fn import_project_zip(
             .map_err(|e| format!("读取 ZIP 内文件失败: {}", e))?;
 
         // 如果 ID 变了，需要更新文档中的 projectId
-        if old_id != new_id && name.starts_with("documents/") {
+        if old_id != new_id && name.starts_with("documents/") && name.ends_with(".json") {
             content = content.replace(
                 &format!("\"projectId\":\"{}\"", old_id),
                 &format!("\"projectId\":\"{}\"", new_id),
@@ -317,6 +359,5 @@ pub fn import_project_zip(
 
         fs::write(&target_path, content).map_err(|e| e.to_string())?;
     }
-
-    Ok(project)
+    Ok(())
 }
@@ -1,5 +1,7 @@
-use tauri::State;
-use crate::resource_engine::{ResourceEngineState, ResourceFilter, ResourceSummary, ResourceStats, CategoryInfo};
+use tauri::{AppHandle, State};
+use crate::bundled_resources::{self, MaterializeReport};
+use crate::plugin;
+use crate::resource_engine::{ResourceEngineState, ResourceFilter, ResourceSummary, ResourceStats, CategoryInfo, InstallRecord};
 
 #[tauri::command]
 pub fn resource_list(
@@ -66,6 +68,10 @@ pub fn resource_set_enabled(
     id: String,
     enabled: bool,
 ) -> Result<(), String> {
+    // 插件需同时检查依赖并更新 manifest
+    if plugin::list_plugins(&state).iter().any(|p| p.id == id) {
+        return plugin::set_plugin_enabled(&state, &id, enabled).map(|_| ());
+    }
     state.with_engine(|engine| engine.set_enabled(&id, enabled))
 }
 
@@ -93,3 +99,29 @@ pub fn resource_rebuild_index(
         Ok(())
     })
 }
+
+/// 将内置资源复制 / 合并到本地 builtin 目录并刷新索引；reset 为 true 时恢复为内置版本
+#[tauri::command]
+pub fn resource_materialize_bundled(
+    app: AppHandle,
+    state: State<'_, ResourceEngineState>,
+    reset: Option<bool>,
+) -> Result<MaterializeReport, String> {
+    let resources_root = state.with_engine(|engine| Ok(engine.data_root().to_path_buf()))?;
+    let report = bundled_resources::materialize(
+        &bundled_resources::bundled_dir(),
+        &resources_root,
+        &app.package_info().version.to_string(),
+        reset.unwrap_or(false),
+    )?;
+    state.with_engine(|engine| engine.rebuild_index_from_local())?;
+    Ok(report)
+}
+
+#[tauri::command]
+pub fn resource_install_history(
+    state: State<'_, ResourceEngineState>,
+    resource_id: Option<String>,
+) -> Result<Vec<InstallRecord>, String> {
+    state.with_engine(|engine| engine.list_install_history(resource_id.as_deref()))
+}
@@ -0,0 +1,87 @@
+#![allow(non_snake_case)]
+
+use crate::commands::export::{export_options, prepare_content};
+use crate::config::{self, AppState};
+use crate::error::{run_blocking, Result};
+use crate::native_export;
+use crate::scripting::{self, Script, ScriptRunResult};
+use std::collections::HashMap;
+use std::rc::Rc;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// 自动化脚本列表
+#[tauri::command]
+pub fn list_scripts(app: AppHandle) -> Result<Vec<Script>> {
+    Ok(scripting::load_all(&config::get_scripts_path(&app)))
+}
+
+/// 新增或更新脚本（id 为空时新增）
+#[tauri::command]
+pub fn save_script(app: AppHandle, script: Script) -> Result<Script> {
+    scripting::upsert(&config::get_scripts_path(&app), script)
+}
+
+#[tauri::command]
+pub fn delete_script(app: AppHandle, scriptId: String) -> Result<()> {
+    scripting::delete(&config::get_scripts_path(&app), &scriptId)
+}
+
+/// 立即执行脚本；context 中的变量覆盖脚本保存的默认变量
+#[tauri::command]
+pub async fn run_script(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    scriptId: String,
+    context: Option<HashMap<String, String>>,
+) -> Result<ScriptRunResult> {
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let path = config::get_scripts_path(&app);
+        let script = scripting::get(&path, &scriptId)?;
+        let mut vars = script.context.clone();
+        vars.extend(context.unwrap_or_default());
+        execute(&app, &state, &script, vars)
+    })
+    .await
+}
+
+fn execute(app: &AppHandle, state: &AppState, script: &Script, context: HashMap<String, String>) -> Result<ScriptRunResult> {
+    let (handle, export_state) = (app.clone(), state.clone());
+    let export = move |project_id: &str, title: &str, content: &str, format: &str, output_path: &str| {
+        let content = prepare_content(&handle, &export_state, project_id, content);
+        native_export::export_native(&content, title, output_path, format, &export_options(&handle, format))
+    };
+    let result = scripting::run(state, script, context, Rc::new(export));
+    scripting::record_run(&config::get_scripts_path(app), &script.id, result.as_ref().err().cloned())?;
+    result
+}
+
+/// 定时脚本调度器检查间隔
+const SCHEDULER_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// 启动定时脚本调度器（应用启动时调用），每次执行后发送 script:completed 事件
+pub fn start_script_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let path = config::get_scripts_path(&app);
+            for script in scripting::due(&path, chrono::Local::now()) {
+                let handle = app.clone();
+                let state = app.state::<AppState>().inner().clone();
+                let id = script.id.clone();
+                let result = run_blocking(move || {
+                    let context = script.context.clone();
+                    execute(&handle, &state, &script, context)
+                })
+                .await;
+                if let Err(e) = &result {
+                    tracing::warn!("定时脚本 {} 执行失败: {}", id, e);
+                }
+                let _ = app.emit(
+                    "script:completed",
+                    serde_json::json!({ "scriptId": id, "result": result.as_ref().ok(), "error": result.as_ref().err() }),
+                );
+            }
+            tokio::time::sleep(SCHEDULER_INTERVAL).await;
+        }
+    });
+}
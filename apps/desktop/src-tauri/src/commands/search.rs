@@ -1,6 +1,7 @@
 use crate::config::AppState;
 use crate::document::Document;
-use crate::error::Result;
+use crate::error::{run_blocking, Result};
+use crate::similarity::{self, SimilarDocument};
 use regex::RegexBuilder;
 use serde::{Deserialize, Serialize};
 use tauri::State;
@@ -45,11 +46,17 @@ pub struct SearchOptions {
 }
 
 #[tauri::command]
-pub fn search_documents(
+pub async fn search_documents(
     state: State<'_, AppState>,
     project_id: String,
     options: SearchOptions,
 ) -> Result<Vec<SearchResult>> {
+    let state = state.inner().clone();
+    run_blocking(move || search_project(&state, &project_id, &options)).await
+}
+
+/// 在项目的文档中搜索（标题及可选的正文）
+pub fn search_project(state: &AppState, project_id: &str, options: &SearchOptions) -> Result<Vec<SearchResult>> {
     let query = if options.match_case {
         options.query.clone()
     } else {
@@ -70,7 +77,7 @@ pub fn search_documents(
         None
     };
 
-    let project_dir = state.config.projects_dir.join(&project_id);
+    let project_dir = state.config.projects_dir.join(project_id);
     let docs_dir = project_dir.join("documents");
 
     if !docs_dir.exists() {
@@ -324,3 +331,28 @@ pub fn get_search_suggestions(
 
     Ok(suggestions)
 }
+
+/// 查找与指定文档内容相同或相近的文档（重复导入、复制后的草稿等）
+#[tauri::command]
+pub async fn find_similar_documents(
+    state: State<'_, AppState>,
+    project_id: String,
+    document_id: String,
+    min_similarity: Option<f32>,
+) -> Result<Vec<SimilarDocument>> {
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let doc_path = state.get_document_path(&project_id, &document_id);
+        let target = Document::load(&doc_path).map_err(|e| e.to_string())?;
+        let docs_dir = state.config.projects_dir.join(&project_id).join("documents");
+        let others: Vec<Document> = std::fs::read_dir(&docs_dir)
+            .map_err(|e| e.to_string())?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("json"))
+            .filter_map(|p| Document::load(&p).ok())
+            .collect();
+        Ok(similarity::find_similar(&target, &others, min_similarity.unwrap_or(similarity::DEFAULT_MIN_SIMILARITY)))
+    })
+    .await
+}
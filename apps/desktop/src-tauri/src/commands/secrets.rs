@@ -0,0 +1,51 @@
+use crate::secrets::{self, SecretEntry};
+
+/// 保存密钥（AI Key、同步凭据、WebDAV 令牌等），返回值只包含脱敏信息
+#[tauri::command]
+pub fn set_secret(key: String, value: String, label: Option<String>) -> Result<SecretEntry, String> {
+    secrets::validate_user_key(&key)?;
+    secrets::store(&key, &value, label)
+}
+
+/// 列出已保存的密钥（脱敏），namespace 可选 ai / sync / webdav / custom / email
+#[tauri::command]
+pub fn list_secrets(namespace: Option<String>) -> Result<Vec<SecretEntry>, String> {
+    Ok(secrets::list(namespace.as_deref()))
+}
+
+/// 查询单个密钥是否已保存（返回脱敏信息）
+#[tauri::command]
+pub fn get_secret_info(key: String) -> Result<Option<SecretEntry>, String> {
+    Ok(secrets::entry(&key))
+}
+
+#[tauri::command]
+pub fn delete_secret(key: String) -> Result<(), String> {
+    secrets::validate_user_key(&key)?;
+    secrets::delete(&key)
+}
+
+/// AI 服务商密钥在钥匙串中的名称；get_ai_config 在调用方未传入密钥时按此读取
+fn api_key_name(provider: &str) -> Result<String, String> {
+    let key = format!("ai:{}", provider.trim());
+    secrets::validate_user_key(&key)?;
+    Ok(key)
+}
+
+/// 保存 AI 服务商的 API Key 到系统钥匙串，之后调用 AI 时前端无需再传明文密钥
+#[tauri::command]
+#[allow(non_snake_case)]
+pub fn set_api_key(provider: String, apiKey: String) -> Result<SecretEntry, String> {
+    secrets::store(&api_key_name(&provider)?, apiKey.trim(), Some(format!("{} API Key", provider.trim())))
+}
+
+/// 查询服务商是否已保存 API Key（只返回脱敏信息，不返回明文）
+#[tauri::command]
+pub fn get_api_key(provider: String) -> Result<Option<SecretEntry>, String> {
+    Ok(secrets::entry(&api_key_name(&provider)?))
+}
+
+#[tauri::command]
+pub fn delete_api_key(provider: String) -> Result<(), String> {
+    secrets::delete(&api_key_name(&provider)?)
+}
@@ -0,0 +1,85 @@
+use crate::api_server;
+use crate::config;
+use crate::i18n;
+use crate::logging;
+use crate::menu;
+use crate::plugin_bus;
+use crate::quick_capture;
+use crate::settings::{self, AppSettings};
+use crate::tray;
+use tauri::{AppHandle, Emitter};
+
+#[tauri::command]
+pub fn get_settings(app: AppHandle) -> Result<AppSettings, String> {
+    Ok(settings::load(&config::get_settings_path(&app)))
+}
+
+/// 部分更新设置（如 { "proxy": { "enabled": true } }），变更后广播 settings:changed 事件
+#[tauri::command]
+pub fn update_settings(app: AppHandle, patch: serde_json::Value) -> Result<AppSettings, String> {
+    let (updated, changed) = settings::update(&config::get_settings_path(&app), patch)?;
+    if !changed.is_empty() {
+        notify_changed(&app, &updated, changed);
+    }
+    Ok(updated)
+}
+
+#[tauri::command]
+pub fn reset_settings(app: AppHandle) -> Result<AppSettings, String> {
+    let defaults = settings::reset(&config::get_settings_path(&app))?;
+    // 所有分组都恢复为默认值，分组名取自序列化后的字段名
+    let changed = match serde_json::to_value(&defaults) {
+        Ok(serde_json::Value::Object(map)) => map.keys().cloned().collect(),
+        _ => Vec::new(),
+    };
+    notify_changed(&app, &defaults, changed);
+    Ok(defaults)
+}
+
+fn notify_changed(app: &AppHandle, settings: &AppSettings, changed: Vec<String>) {
+    if changed.iter().any(|k| k == "locale") {
+        i18n::set_locale(&settings.locale);
+    }
+    if changed.iter().any(|k| k == "keybindings" || k == "locale") {
+        if let Err(e) = menu::rebuild(app, &settings.keybindings) {
+            tracing::warn!("快捷键或语言变更后重建菜单失败: {}", e);
+        }
+    }
+    if changed.iter().any(|k| k == "keybindings") {
+        if let Err(e) = quick_capture::register_shortcut(app, &settings.keybindings) {
+            tracing::warn!("{}", e);
+        }
+    }
+    if changed.iter().any(|k| k == "logging") {
+        if let Err(e) = logging::set_levels(&settings.logging.level, &settings.logging.modules) {
+            tracing::warn!("更新日志级别失败: {}", e);
+        }
+    }
+    if changed.iter().any(|k| k == "tray" || k == "locale") {
+        if let Err(e) = tray::refresh(app) {
+            tracing::warn!("更新托盘失败: {}", e);
+        }
+    }
+    if changed.iter().any(|k| k == "apiServer") {
+        let app = app.clone();
+        let api = settings.api_server.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = api_server::apply(&app, api.enabled, api.port).await {
+                tracing::warn!("{}", e);
+            }
+        });
+    }
+    let _ = app.emit("settings:changed", serde_json::json!({
+        "settings": settings,
+        "changedKeys": changed,
+    }));
+    // 插件只收到变更的分组名，不包含具体设置值
+    plugin_bus::publish(app, "app:settings-changed", serde_json::json!({ "changedKeys": changed }));
+}
+
+/// 当前生效的菜单快捷键（默认值与用户配置合并后）
+#[tauri::command]
+pub fn get_menu_keybindings(app: AppHandle) -> Result<std::collections::HashMap<String, String>, String> {
+    let settings = settings::load(&config::get_settings_path(&app));
+    Ok(menu::effective_accelerators(&settings.keybindings))
+}
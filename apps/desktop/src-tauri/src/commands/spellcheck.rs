@@ -0,0 +1,78 @@
+#![allow(non_snake_case)]
+
+use crate::config::{self, AppState};
+use crate::error::{run_blocking, Result};
+use crate::glossary;
+use crate::settings;
+use crate::spellcheck::{self, SpellingIssue, PROJECT_WORDS_FILE};
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+
+/// 自定义词表路径：指定项目时为项目词表，否则为全局词表
+fn words_path(app: &AppHandle, state: &AppState, project_id: Option<&str>) -> PathBuf {
+    match project_id {
+        Some(pid) => state.config.projects_dir.join(pid).join(PROJECT_WORDS_FILE),
+        None => config::get_user_words_path(app),
+    }
+}
+
+/// 检查文本拼写，返回错误位置（UTF-16 偏移）与建议；未指定语言时使用设置中的语言。
+/// 指定项目时，术语表中的术语视为正确，禁用写法作为问题返回并建议改为规范术语
+#[tauri::command]
+pub async fn check_spelling(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    text: String,
+    projectId: Option<String>,
+    language: Option<String>,
+) -> Result<Vec<SpellingIssue>> {
+    let language = language.unwrap_or_else(|| settings::load(&config::get_settings_path(&app)).spellcheck.language);
+    let dict_dir = config::get_dictionaries_dir(&app);
+    let mut custom = spellcheck::load_words(&config::get_user_words_path(&app));
+    let mut terms = Vec::new();
+    if let Some(pid) = projectId.as_deref() {
+        custom.extend(spellcheck::load_words(&words_path(&app, &state, Some(pid))));
+        terms = glossary::load(&state.config.projects_dir.join(pid));
+        custom.extend(glossary::known_words(&terms));
+    }
+    run_blocking(move || {
+        let dict = spellcheck::load(&dict_dir, &language)?;
+        let term_issues = glossary::check(&terms, &text);
+        let mut issues: Vec<SpellingIssue> = spellcheck::check_text(&dict, &text, &custom)
+            .into_iter()
+            .filter(|i| !term_issues.iter().any(|t| i.from < t.to && t.from < i.to))
+            .collect();
+        issues.extend(term_issues);
+        issues.sort_by_key(|i| i.from);
+        Ok(issues)
+    })
+    .await
+}
+
+/// 可用的拼写词典
+#[tauri::command]
+pub fn list_spelling_languages(app: AppHandle) -> Vec<String> {
+    spellcheck::available_languages(&config::get_dictionaries_dir(&app))
+}
+
+/// 获取自定义词表
+#[tauri::command]
+pub fn get_custom_words(app: AppHandle, state: State<'_, AppState>, projectId: Option<String>) -> Vec<String> {
+    let mut words: Vec<String> = spellcheck::load_words(&words_path(&app, &state, projectId.as_deref()))
+        .into_iter()
+        .collect();
+    words.sort();
+    words
+}
+
+/// 添加单词到自定义词表
+#[tauri::command]
+pub fn add_custom_word(app: AppHandle, state: State<'_, AppState>, word: String, projectId: Option<String>) -> Result<()> {
+    spellcheck::add_word(&words_path(&app, &state, projectId.as_deref()), &word)
+}
+
+/// 从自定义词表移除单词
+#[tauri::command]
+pub fn remove_custom_word(app: AppHandle, state: State<'_, AppState>, word: String, projectId: Option<String>) -> Result<()> {
+    spellcheck::remove_word(&words_path(&app, &state, projectId.as_deref()), &word)
+}
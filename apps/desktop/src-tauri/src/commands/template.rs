@@ -2,13 +2,20 @@
 
 use crate::config::AppState;
 use crate::document::Document;
-use crate::template::{self, TemplateManifest, TemplateContent, TemplateCategory};
-use crate::error::Result;
-use tauri::State;
+use crate::i18n;
+use crate::resource_engine::ResourceEngineState;
+use crate::template::{self, PromptSeed, RenderedPrompt, TemplateManifest, TemplateContent, TemplateCategory};
+use crate::error::{run_blocking, Result};
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::{AppHandle, Manager, State};
 
 #[tauri::command]
-pub fn list_templates() -> Result<Vec<TemplateManifest>> {
-    Ok(template::list_templates())
+pub async fn list_templates() -> Result<Vec<TemplateManifest>> {
+    run_blocking(move || {
+        Ok(template::list_templates())
+    })
+    .await
 }
 
 #[tauri::command]
@@ -60,7 +67,7 @@ pub fn save_template_from_document(
     // 加载文档
     let doc_path = state.get_document_path(&projectId, &documentId);
     if !doc_path.exists() {
-        return Err(format!("Document not found: {}", documentId));
+        return Err(i18n::tf("error.document_not_found", &[&documentId]));
     }
     let document = Document::load(&doc_path).map_err(|e| e.to_string())?;
 
@@ -82,6 +89,7 @@ pub fn save_template_from_document(
         enabled_plugins: document.enabled_plugins.clone().unwrap_or_default(),
         plugin_data: if includePluginData { document.plugin_data.clone() } else { None },
         min_app_version: None,
+        prompt_template_id: None,
     };
 
     let content = TemplateContent {
@@ -96,55 +104,133 @@ pub fn save_template_from_document(
 
 /// 从模板创建新文档
 #[tauri::command]
-pub fn create_document_from_template(
+pub async fn create_document_from_template(
+    app: AppHandle,
     state: State<'_, AppState>,
     projectId: String,
     templateId: String,
     title: String,
     author: String,
 ) -> Result<Document> {
-    // 读取模板
-    let templates_dir = template::get_templates_dir();
-    let manifest_path = templates_dir.join(&templateId).join("template.json");
-    if !manifest_path.exists() {
-        return Err(format!("Template not found: {}", templateId));
-    }
+    let state = state.inner().clone();
+    run_blocking(move || {
+        // 读取模板
+        let templates_dir = template::get_templates_dir();
+        let manifest_path = templates_dir.join(&templateId).join("template.json");
+        if !manifest_path.exists() {
+            return Err(i18n::tf("error.template_not_found", &[&templateId]));
+        }
 
-    let manifest_json = std::fs::read_to_string(&manifest_path)
-        .map_err(|e| format!("Failed to read template manifest: {}", e))?;
-    let manifest: TemplateManifest = serde_json::from_str(&manifest_json)
-        .map_err(|e| format!("Failed to parse template manifest: {}", e))?;
+        let manifest_json = std::fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("Failed to read template manifest: {}", e))?;
+        let manifest: TemplateManifest = serde_json::from_str(&manifest_json)
+            .map_err(|e| format!("Failed to parse template manifest: {}", e))?;
 
-    // 读取模板内容
-    let template_content = template::get_template_content(&templateId)?;
+        // 读取模板内容
+        let template_content = template::get_template_content(&templateId)?;
 
-    // 创建新文档
-    let mut document = Document::new(projectId.clone(), title, author);
+        // 创建新文档
+        let mut document = Document::new(projectId.clone(), title, author);
 
-    // 提示词始终继承
-    document.author_notes = template_content.author_notes;
+        // 提示词始终继承；模板没有提示词时使用关联的提示词模板
+        document.author_notes = template_content.author_notes;
+        if let Some(seed) = linked_prompt_seed(&app, &manifest) {
+            if document.author_notes.trim().is_empty() {
+                document.author_notes = seed.author_notes;
+            }
+            document.system_prompt = seed.system_prompt;
+        }
 
-    // 素材内容按选项继承
-    if manifest.include_content {
-        document.content = template_content.content;
-    }
-    if manifest.include_ai_content {
-        document.ai_generated_content = template_content.ai_generated_content;
-    }
+        // 素材内容按选项继承
+        if manifest.include_content {
+            document.content = template_content.content;
+        }
+        if manifest.include_ai_content {
+            document.ai_generated_content = template_content.ai_generated_content;
+        }
+
+        // 应用插件设置
+        if !manifest.enabled_plugins.is_empty() {
+            document.enabled_plugins = Some(manifest.enabled_plugins);
+        }
+        if template_content.plugin_data.is_some() {
+            document.plugin_data = template_content.plugin_data;
+        }
 
-    // 应用插件设置
-    if !manifest.enabled_plugins.is_empty() {
-        document.enabled_plugins = Some(manifest.enabled_plugins);
+        // 保存文档
+        let doc_path = state.get_document_path(&projectId, &document.id);
+        document.save(&doc_path).map_err(|e| e.to_string())?;
+
+        Ok(document)
+    })
+    .await
+}
+
+/// 解析模板（或其分类）关联的提示词模板；资源不存在时忽略
+fn linked_prompt_seed(app: &AppHandle, manifest: &TemplateManifest) -> Option<PromptSeed> {
+    let prompt_id = manifest.prompt_template_id.clone().or_else(|| {
+        template::list_template_categories()
+            .into_iter()
+            .find(|c| c.key == manifest.category)
+            .and_then(|c| c.prompt_template_id)
+    })?;
+    let resource = app
+        .state::<ResourceEngineState>()
+        .with_engine(|engine| engine.get_with_path(&prompt_id));
+    match resource {
+        Ok(Some((manifest_json, data_path))) => template::prompt_seed_from_resource(&manifest_json, Path::new(&data_path)),
+        Ok(None) => {
+            tracing::warn!("模板关联的提示词模板不存在: {}", prompt_id);
+            None
+        }
+        Err(e) => {
+            tracing::warn!("读取提示词模板失败: {}", e);
+            None
+        }
     }
-    if template_content.plugin_data.is_some() {
-        document.plugin_data = template_content.plugin_data;
+}
+
+/// 渲染提示词模板：替换 {{title}}、{{content}}、{{date}} 等变量，缺少变量时返回错误
+/// 指定文档时提供 title / content / authorNotes / author / tags；另有内置的 date / time / datetime，
+/// variables 中的同名变量优先
+#[tauri::command]
+pub fn render_prompt_template(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    variables: Option<HashMap<String, String>>,
+    projectId: Option<String>,
+    documentId: Option<String>,
+) -> Result<RenderedPrompt> {
+    let now = chrono::Local::now();
+    let mut values: HashMap<String, String> = HashMap::from([
+        ("date".to_string(), now.format("%Y-%m-%d").to_string()),
+        ("time".to_string(), now.format("%H:%M").to_string()),
+        ("datetime".to_string(), now.format("%Y-%m-%d %H:%M").to_string()),
+    ]);
+    if let (Some(project_id), Some(document_id)) = (&projectId, &documentId) {
+        let document = Document::load(&state.get_document_path(project_id, document_id)).map_err(|e| e.to_string())?;
+        values.extend([
+            ("title".to_string(), document.title),
+            ("content".to_string(), document.content),
+            ("authorNotes".to_string(), document.author_notes),
+            ("author".to_string(), document.metadata.author),
+            ("tags".to_string(), document.metadata.tags.join(", ")),
+        ]);
     }
+    values.extend(variables.unwrap_or_default());
 
-    // 保存文档
-    let doc_path = state.get_document_path(&projectId, &document.id);
-    document.save(&doc_path).map_err(|e| e.to_string())?;
+    let resource = app
+        .state::<ResourceEngineState>()
+        .with_engine(|engine| engine.get_with_path(&id))?;
+    let (manifest_json, data_path) = resource.ok_or_else(|| format!("提示词模板不存在: {}", id))?;
+    template::render_prompt_resource(&manifest_json, Path::new(&data_path), &values)
+}
 
-    Ok(document)
+/// 设置模板的默认提示词模板
+#[tauri::command]
+pub fn set_template_prompt_template(templateId: String, promptTemplateId: Option<String>) -> Result<TemplateManifest> {
+    template::set_template_prompt_template(&templateId, promptTemplateId)
 }
 
 // ── 模板分类命令 ──
@@ -164,6 +250,12 @@ pub fn update_template_category(key: String, label: Option<String>, newKey: Opti
     template::update_template_category(&key, label, newKey)
 }
 
+/// 设置分类的默认提示词模板
+#[tauri::command]
+pub fn set_template_category_prompt_template(key: String, promptTemplateId: Option<String>) -> Result<Vec<TemplateCategory>> {
+    template::set_category_prompt_template(&key, promptTemplateId)
+}
+
 #[tauri::command]
 pub fn delete_template_category(key: String) -> Result<Vec<TemplateCategory>> {
     template::delete_template_category(&key)
@@ -0,0 +1,37 @@
+use crate::config;
+use crate::settings;
+use crate::updater::{self, UpdateInfo};
+use tauri::{AppHandle, Emitter};
+
+/// 检查是否有新版本（手动检查，不受自动检查开关影响）
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle) -> Result<UpdateInfo, String> {
+    run_check(&app).await
+}
+
+async fn run_check(app: &AppHandle) -> Result<UpdateInfo, String> {
+    let prefs = settings::load(&config::get_settings_path(app)).updates;
+    let feed_url = prefs
+        .feed_url
+        .filter(|u| !u.trim().is_empty())
+        .unwrap_or_else(|| updater::DEFAULT_FEED_URL.to_string());
+    let current = app.package_info().version.clone();
+    updater::check(&feed_url, &current, prefs.skipped_version.as_deref()).await
+}
+
+/// 启动时后台检查更新，有未跳过的新版本时广播 update:available 事件
+pub fn start_update_check(app: AppHandle) {
+    if !settings::load(&config::get_settings_path(&app)).updates.auto_check {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        match run_check(&app).await {
+            Ok(info) if info.available && !info.skipped => {
+                tracing::info!("发现新版本 {}", info.latest_version);
+                let _ = app.emit("update:available", &info);
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("自动检查更新失败: {}", e),
+        }
+    });
+}
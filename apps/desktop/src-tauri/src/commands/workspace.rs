@@ -1,6 +1,11 @@
-use crate::config::get_workspace_state_path;
-use crate::workspace::{clear_workspace_state, load_workspace_state, save_workspace_state, WorkspaceState, WorkspaceTabState, UIState};
-use tauri::AppHandle;
+use crate::config::{get_drafts_dir, get_workspace_state_path, AppState};
+use crate::document::Document;
+use crate::i18n;
+use crate::workspace::{
+    self, clear_workspace_state, load_project_session, load_workspace_state, project_session_path,
+    save_project_session, save_workspace_state, Draft, ProjectSession, WorkspaceState, WorkspaceTabState, UIState,
+};
+use tauri::{AppHandle, State};
 
 #[tauri::command]
 pub fn save_workspace(
@@ -13,6 +18,7 @@ pub fn save_workspace(
     ui_state: UIState,
 ) -> Result<(), String> {
     let workspace_state = WorkspaceState {
+        schema_version: workspace::WORKSPACE_SCHEMA_VERSION,
         current_project_id,
         open_document_ids,
         current_document_id,
@@ -38,3 +44,57 @@ pub fn clear_workspace(handle: AppHandle) -> Result<(), String> {
     let path = get_workspace_state_path(&handle);
     clear_workspace_state(&path)
 }
+
+/// 保存项目会话（切换或关闭项目前调用）
+#[tauri::command]
+pub fn save_project_session_state(
+    state: State<'_, AppState>,
+    project_id: String,
+    mut session: ProjectSession,
+) -> Result<(), String> {
+    if !state.get_project_path(&project_id).exists() {
+        return Err(i18n::tf("error.project_not_found", &[&project_id]));
+    }
+    session.saved_at = chrono::Utc::now().timestamp();
+    save_project_session(&project_session_path(&state.config.projects_dir, &project_id), &session)
+}
+
+#[tauri::command]
+pub fn load_project_session_state(state: State<'_, AppState>, project_id: String) -> Result<Option<ProjectSession>, String> {
+    Ok(load_project_session(&project_session_path(&state.config.projects_dir, &project_id)))
+}
+
+/// 暂存标签页中未保存的内容（前端在编辑后定期调用）
+#[tauri::command]
+pub fn stage_draft(handle: AppHandle, mut draft: Draft) -> Result<(), String> {
+    draft.staged_at = chrono::Utc::now().timestamp();
+    workspace::stage_draft(&get_drafts_dir(&handle), &draft)
+}
+
+/// 丢弃标签页的草稿（保存或不保存关闭后调用）
+#[tauri::command]
+pub fn discard_draft(handle: AppHandle, tab_id: String) -> Result<(), String> {
+    workspace::discard_draft(&get_drafts_dir(&handle), &tab_id)
+}
+
+/// 读取可恢复的草稿：只保留工作区中仍打开的标签页，且文档在草稿之后没有再保存过；其余草稿删除
+#[tauri::command]
+pub fn load_drafts(handle: AppHandle, state: State<'_, AppState>) -> Result<Vec<Draft>, String> {
+    let dir = get_drafts_dir(&handle);
+    let tabs: Vec<WorkspaceTabState> = load_workspace_state(&get_workspace_state_path(&handle))?
+        .map(|w| w.tabs)
+        .unwrap_or_default();
+
+    let mut recoverable = Vec::new();
+    for draft in workspace::list_drafts(&dir) {
+        let in_workspace = tabs.iter().any(|t| t.id == draft.tab_id && t.document_id == draft.document_id);
+        let newer_than_saved = Document::load_meta(&state.get_document_path(&draft.project_id, &draft.document_id))
+            .is_ok_and(|doc| doc.metadata.updated_at <= draft.staged_at);
+        if in_workspace && newer_than_saved {
+            recoverable.push(draft);
+        } else {
+            workspace::discard_draft(&dir, &draft.tab_id)?;
+        }
+    }
+    Ok(recoverable)
+}
@@ -20,6 +20,12 @@ impl Default for AppConfig {
     }
 }
 
+/// ID 只能是单个路径段，防止通过 ../ 访问项目目录之外的文件
+pub fn is_safe_id(id: &str) -> bool {
+    !id.is_empty() && !id.contains(['/', '\\']) && id != "." && id != ".."
+}
+
+#[derive(Clone)]
 pub struct AppState {
     pub config: AppConfig,
 }
@@ -30,7 +36,7 @@ impl AppState {
 
         // Ensure projects directory exists
         if let Err(e) = std::fs::create_dir_all(&config.projects_dir) {
-            eprintln!("Failed to create projects directory: {}", e);
+            tracing::warn!("Failed to create projects directory: {}", e);
         }
 
         Self { config }
@@ -47,7 +53,6 @@ impl AppState {
             .join(format!("{}.json", document_id))
     }
 
-    #[allow(dead_code)]
     pub fn get_versions_path(&self, project_id: &str, document_id: &str) -> PathBuf {
         self.config.projects_dir
             .join(project_id)
@@ -65,7 +70,6 @@ pub fn get_config_dir(handle: &AppHandle) -> PathBuf {
 }
 
 // Helper to get data directory
-#[allow(dead_code)]
 pub fn get_data_dir(handle: &AppHandle) -> PathBuf {
     handle
         .path()
@@ -80,3 +84,92 @@ pub use dirs;
 pub fn get_workspace_state_path(handle: &AppHandle) -> PathBuf {
     get_config_dir(handle).join("workspace-state.json")
 }
+
+// Helper to get unsaved editor drafts directory（按标签页保存）
+pub fn get_drafts_dir(handle: &AppHandle) -> PathBuf {
+    get_data_dir(handle).join("drafts")
+}
+
+// Helper to get backend settings path
+pub fn get_settings_path(handle: &AppHandle) -> PathBuf {
+    get_config_dir(handle).join("settings.json")
+}
+
+// Helper to get log directory（按天轮转的日志文件）
+pub fn get_log_dir(handle: &AppHandle) -> PathBuf {
+    get_data_dir(handle).join("logs")
+}
+
+// Helper to get crash reports directory
+pub fn get_crash_dir(handle: &AppHandle) -> PathBuf {
+    get_data_dir(handle).join("crashes")
+}
+
+// Helper to get user dictionaries directory（Hunspell .aff/.dic）
+pub fn get_dictionaries_dir(handle: &AppHandle) -> PathBuf {
+    get_data_dir(handle).join("dictionaries")
+}
+
+// Helper to get font directories for export embedding（用户字体目录优先，其次安装包内置字体）
+pub fn get_font_dirs(handle: &AppHandle) -> Vec<PathBuf> {
+    let mut dirs = vec![get_data_dir(handle).join("fonts")];
+    if let Ok(resource_dir) = handle.path().resource_dir() {
+        dirs.push(resource_dir.join("fonts"));
+    }
+    dirs
+}
+
+// Helper to get user custom word list path（所有项目共用）
+pub fn get_user_words_path(handle: &AppHandle) -> PathBuf {
+    get_config_dir(handle).join("spelling-words.txt")
+}
+
+// Helper to get writing activity statistics path
+pub fn get_activity_path(handle: &AppHandle) -> PathBuf {
+    get_data_dir(handle).join("activity.json")
+}
+
+// Helper to get focus session history path
+pub fn get_focus_history_path(handle: &AppHandle) -> PathBuf {
+    get_data_dir(handle).join("focus-sessions.json")
+}
+
+// Helper to get AI provider health statistics path
+pub fn get_provider_health_path(handle: &AppHandle) -> PathBuf {
+    get_data_dir(handle).join("provider-health.json")
+}
+
+// Helper to get secrets index path（仅脱敏信息，明文在系统钥匙串）
+pub fn get_secrets_index_path(handle: &AppHandle) -> PathBuf {
+    get_config_dir(handle).join("secrets-index.json")
+}
+
+// Helper to get email accounts path（不含密码，密码保存在系统钥匙串）
+pub fn get_email_accounts_path(handle: &AppHandle) -> PathBuf {
+    get_config_dir(handle).join("email-accounts.json")
+}
+
+// Helper to get email outbox path（待发送队列与发送历史）
+pub fn get_email_outbox_path(handle: &AppHandle) -> PathBuf {
+    get_config_dir(handle).join("email-outbox.json")
+}
+
+// Helper to get email contacts path
+pub fn get_email_contacts_path(handle: &AppHandle) -> PathBuf {
+    get_config_dir(handle).join("email-contacts.json")
+}
+
+// Helper to get imported email record path（IMAP 导入去重）
+pub fn get_email_imported_path(handle: &AppHandle) -> PathBuf {
+    get_config_dir(handle).join("email-imported.json")
+}
+
+// Helper to get AI response cache directory
+pub fn get_ai_cache_dir(handle: &AppHandle) -> PathBuf {
+    get_data_dir(handle).join("ai-cache")
+}
+
+// Helper to get automation scripts path（自动化脚本及定时设置）
+pub fn get_scripts_path(handle: &AppHandle) -> PathBuf {
+    get_config_dir(handle).join("scripts.json")
+}
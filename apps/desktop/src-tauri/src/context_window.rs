@@ -0,0 +1,158 @@
+// 上下文窗口：发送前按模型上下文长度估算 token 数，超出时从最早的历史消息开始裁剪
+// token 数为近似值：中日韩字符约 1 token/字，其他文本约 4 字符/token，每条消息另加固定开销
+
+use crate::ai::ChatMessage;
+use serde::Serialize;
+
+/// 每条消息的格式开销（role、分隔符等）
+const MESSAGE_OVERHEAD: usize = 4;
+/// 每张图片的估算 token 数（各服务商按分辨率计费，取常见截图的近似值）
+const IMAGE_TOKENS: usize = 1000;
+/// 未指定 max_tokens 时为模型输出预留的 token 数
+pub const DEFAULT_OUTPUT_RESERVE: usize = 4096;
+/// 表中没有的模型按此上下文长度处理
+const DEFAULT_CONTEXT_WINDOW: usize = 32_000;
+/// 估算误差留出的余量（占上下文长度的比例）
+const SAFETY_MARGIN: f64 = 0.05;
+
+/// 常见模型的上下文长度：(模型名前缀, token 数)，按最长前缀匹配
+const WINDOWS: &[(&str, usize)] = &[
+    // OpenAI
+    ("gpt-5", 400_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo", 16_385),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4-mini", 200_000),
+    // Anthropic
+    ("claude", 200_000),
+    // Google
+    ("gemini-1.5-pro", 2_000_000),
+    ("gemini", 1_048_576),
+    // xAI
+    ("grok-4", 256_000),
+    ("grok-3", 131_072),
+    // 国内服务商
+    ("deepseek", 128_000),
+    ("qwen-turbo", 1_000_000),
+    ("qwen-long", 10_000_000),
+    ("qwen", 131_072),
+    ("glm-4.5", 128_000),
+    ("glm-4.6", 200_000),
+    ("glm-5", 200_000),
+    ("kimi-k2", 256_000),
+    ("moonshot-v1-8k", 8_192),
+    ("moonshot-v1-32k", 32_768),
+    ("moonshot-v1-128k", 131_072),
+    ("minimax", 204_800),
+    // 本地模型（Ollama 默认上下文）
+    ("llama", 8_192),
+];
+
+/// 裁剪结果，作为 ai:context:truncated 事件的内容
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TruncationInfo {
+    /// 删除的历史消息条数
+    pub dropped_messages: usize,
+    /// 最后一条消息过长被截断时为 true
+    pub content_trimmed: bool,
+    pub estimated_tokens: usize,
+    pub context_window: usize,
+}
+
+/// 模型上下文长度，按内置表前缀匹配
+pub fn window_for(model: &str) -> usize {
+    let model = model.to_lowercase();
+    let model = model.rsplit('/').next().unwrap_or(&model);
+    WINDOWS
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, window)| *window)
+        .unwrap_or(DEFAULT_CONTEXT_WINDOW)
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF | 0xFF00..=0xFFEF)
+}
+
+/// 估算文本的 token 数
+pub fn estimate_tokens(text: &str) -> usize {
+    let (cjk, other) = text.chars().fold((0usize, 0usize), |(cjk, other), c| if is_cjk(c) { (cjk + 1, other) } else { (cjk, other + 1) });
+    cjk + other.div_ceil(4)
+}
+
+fn message_tokens(message: &ChatMessage) -> usize {
+    estimate_tokens(&message.content) + message.images.len() * IMAGE_TOKENS + MESSAGE_OVERHEAD
+}
+
+/// 保留文本末尾约 max_tokens 个 token 的内容
+fn keep_tail(text: &str, max_tokens: usize) -> String {
+    let mut tokens = 0.0;
+    let mut start = text.len();
+    for (i, c) in text.char_indices().rev() {
+        tokens += if is_cjk(c) { 1.0 } else { 0.25 };
+        if tokens > max_tokens as f64 {
+            break;
+        }
+        start = i;
+    }
+    text[start..].to_string()
+}
+
+/// 让消息适配上下文窗口；未裁剪时返回 None
+///
+/// 系统消息和最后一条消息始终保留，从最早的历史消息开始删除，
+/// 并在系统消息中注明；只剩这些消息仍超出时截断最后一条消息的开头部分
+pub fn fit(messages: &mut Vec<ChatMessage>, context_window: usize, output_reserve: usize) -> Option<TruncationInfo> {
+    let budget = (context_window as f64 * (1.0 - SAFETY_MARGIN)) as usize;
+    let budget = budget.saturating_sub(output_reserve).max(budget / 4);
+    let total = |messages: &[ChatMessage]| messages.iter().map(message_tokens).sum::<usize>();
+    if total(messages) <= budget {
+        return None;
+    }
+
+    let system_count = messages.iter().take_while(|m| m.role == "system").count();
+    let history_end = messages.len().saturating_sub(1).max(system_count);
+    // 预留省略说明的位置
+    let note = ChatMessage { role: "system".to_string(), content: "（为适应模型上下文长度，已省略较早的 000 条对话）".to_string(), images: Vec::new() };
+    let mut used = total(messages) + message_tokens(&note);
+    let mut drop_end = system_count;
+    while used > budget && drop_end < history_end {
+        used -= message_tokens(&messages[drop_end]);
+        drop_end += 1;
+    }
+    // 不在助手回复或工具结果前断开，避免对话以非用户消息开头
+    while drop_end < history_end && messages[drop_end].role != "user" {
+        used -= message_tokens(&messages[drop_end]);
+        drop_end += 1;
+    }
+    let dropped = drop_end - system_count;
+    used -= message_tokens(&note);
+    if dropped > 0 {
+        messages.drain(system_count..drop_end);
+        // 说明合并到已有系统消息，部分服务商不接受多条系统消息
+        let note = format!("（为适应模型上下文长度，已省略较早的 {} 条对话）", dropped);
+        used += estimate_tokens(&note) + MESSAGE_OVERHEAD;
+        match system_count.checked_sub(1).map(|i| &mut messages[i]) {
+            Some(system) => system.content = format!("{}\n\n{}", system.content, note),
+            None => messages.insert(0, ChatMessage { role: "system".to_string(), content: note, images: Vec::new() }),
+        }
+    }
+
+    let mut content_trimmed = false;
+    if used > budget {
+        if let Some(last) = messages.last_mut() {
+            let others = used - message_tokens(last);
+            let keep = budget.saturating_sub(others + MESSAGE_OVERHEAD);
+            last.content = keep_tail(&last.content, keep);
+            content_trimmed = true;
+        }
+    }
+    Some(TruncationInfo { dropped_messages: dropped, content_trimmed, estimated_tokens: total(messages), context_window })
+}
@@ -0,0 +1,151 @@
+// AI 对话持久化：每个会话一个 JSON 文件，保存在 Projects/{项目ID}/conversations/{会话ID}.json
+// 列表只读取会话头部信息，消息按需加载
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const CONVERSATIONS_DIR: &str = "conversations";
+/// 自动标题的最大长度（字符）
+const TITLE_CHARS: usize = 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationMessage {
+    pub role: String,
+    pub content: String,
+    #[serde(default)]
+    pub timestamp: i64,
+    /// 前端附加信息（上下文模式、用量等），原样保存
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Conversation {
+    pub id: String,
+    pub project_id: String,
+    /// 会话关联的文档，为空表示项目级会话
+    #[serde(default)]
+    pub document_id: Option<String>,
+    pub title: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    #[serde(default)]
+    pub messages: Vec<ConversationMessage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationSummary {
+    pub id: String,
+    pub document_id: Option<String>,
+    pub title: String,
+    pub message_count: usize,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<&Conversation> for ConversationSummary {
+    fn from(c: &Conversation) -> Self {
+        Self {
+            id: c.id.clone(),
+            document_id: c.document_id.clone(),
+            title: c.title.clone(),
+            message_count: c.messages.len(),
+            created_at: c.created_at,
+            updated_at: c.updated_at,
+        }
+    }
+}
+
+fn conversation_path(project_dir: &Path, id: &str) -> Result<PathBuf, String> {
+    // 会话 ID 由后端生成（UUID），拒绝其他字符以免越出目录
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("无效的会话 ID: {}", id));
+    }
+    Ok(project_dir.join(CONVERSATIONS_DIR).join(format!("{}.json", id)))
+}
+
+fn save(project_dir: &Path, conversation: &Conversation) -> Result<(), String> {
+    let path = conversation_path(project_dir, &conversation.id)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建会话目录失败: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(conversation).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("保存会话失败: {}", e))
+}
+
+pub fn load(project_dir: &Path, id: &str) -> Result<Conversation, String> {
+    let json = fs::read_to_string(conversation_path(project_dir, id)?).map_err(|_| format!("会话不存在: {}", id))?;
+    serde_json::from_str(&json).map_err(|e| format!("会话文件损坏: {}", e))
+}
+
+pub fn create(project_dir: &Path, project_id: &str, title: Option<String>, document_id: Option<String>) -> Result<Conversation, String> {
+    let now = chrono::Utc::now().timestamp();
+    let conversation = Conversation {
+        id: uuid::Uuid::new_v4().to_string(),
+        project_id: project_id.to_string(),
+        document_id: document_id.filter(|d| !d.is_empty()),
+        title: title.map(|t| t.trim().to_string()).unwrap_or_default(),
+        created_at: now,
+        updated_at: now,
+        messages: Vec::new(),
+    };
+    save(project_dir, &conversation)?;
+    Ok(conversation)
+}
+
+/// 追加消息；会话还没有标题时取第一条用户消息开头作为标题
+pub fn append(project_dir: &Path, id: &str, messages: Vec<ConversationMessage>) -> Result<ConversationSummary, String> {
+    let mut conversation = load(project_dir, id)?;
+    let now = chrono::Utc::now().timestamp();
+    conversation.messages.extend(messages.into_iter().map(|mut m| {
+        if m.timestamp == 0 {
+            m.timestamp = now;
+        }
+        m
+    }));
+    if conversation.title.is_empty() {
+        if let Some(first) = conversation.messages.iter().find(|m| m.role == "user") {
+            let line = first.content.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or_default();
+            conversation.title = line.chars().take(TITLE_CHARS).collect();
+        }
+    }
+    conversation.updated_at = now;
+    save(project_dir, &conversation)?;
+    Ok(ConversationSummary::from(&conversation))
+}
+
+pub fn rename(project_dir: &Path, id: &str, title: &str) -> Result<ConversationSummary, String> {
+    let mut conversation = load(project_dir, id)?;
+    conversation.title = title.trim().to_string();
+    conversation.updated_at = chrono::Utc::now().timestamp();
+    save(project_dir, &conversation)?;
+    Ok(ConversationSummary::from(&conversation))
+}
+
+/// 会话列表，最近更新的在前；document_id 不为空时只列出该文档的会话
+pub fn list(project_dir: &Path, document_id: Option<&str>) -> Vec<ConversationSummary> {
+    let Ok(entries) = fs::read_dir(project_dir.join(CONVERSATIONS_DIR)) else { return Vec::new() };
+    let mut summaries: Vec<ConversationSummary> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("json"))
+        .filter_map(|p| fs::read_to_string(p).ok())
+        .filter_map(|json| serde_json::from_str::<Conversation>(&json).ok())
+        .filter(|c| document_id.is_none_or(|d| c.document_id.as_deref() == Some(d)))
+        .map(|c| ConversationSummary::from(&c))
+        .collect();
+    summaries.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+    summaries
+}
+
+pub fn delete(project_dir: &Path, id: &str) -> Result<(), String> {
+    let path = conversation_path(project_dir, id)?;
+    if !path.exists() {
+        return Err(format!("会话不存在: {}", id));
+    }
+    fs::remove_file(path).map_err(|e| format!("删除会话失败: {}", e))
+}
@@ -0,0 +1,169 @@
+// 崩溃报告：panic 时把调用栈、版本信息、最近执行的命令和错误日志写入磁盘，
+// 下次启动时由前端读取并提示用户查看或分享
+
+use crate::logging::{self, LogEntry};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tauri::ipc::Invoke;
+use tauri::Wry;
+use tracing::level_filters::LevelFilter;
+
+/// 报告中保留的最近命令数
+const RECENT_COMMANDS: usize = 30;
+
+/// 报告中附带的最近警告/错误日志数
+const RECENT_LOGS: usize = 50;
+
+/// 磁盘上最多保留的报告数
+const MAX_REPORTS: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub id: String,
+    pub timestamp: i64,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub thread: String,
+    pub message: String,
+    /// panic 发生位置（文件:行:列）
+    pub location: Option<String>,
+    pub backtrace: String,
+    pub recent_commands: Vec<String>,
+    pub recent_logs: Vec<LogEntry>,
+    /// 用户是否已查看
+    #[serde(default)]
+    pub seen: bool,
+}
+
+struct CrashContext {
+    dir: PathBuf,
+    app_version: String,
+}
+
+static CONTEXT: OnceLock<CrashContext> = OnceLock::new();
+static RECENT: OnceLock<Mutex<VecDeque<String>>> = OnceLock::new();
+
+fn recent_commands() -> &'static Mutex<VecDeque<String>> {
+    RECENT.get_or_init(|| Mutex::new(VecDeque::with_capacity(RECENT_COMMANDS)))
+}
+
+/// 安装 panic hook（应用启动时调用一次），保留默认 hook 的输出
+pub fn install(dir: PathBuf, app_version: String) {
+    if CONTEXT.set(CrashContext { dir, app_version }).is_err() {
+        return;
+    }
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "未知错误".to_string());
+        let location = info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+        // 先直接写报告文件；panic 可能发生在日志写入途中，日志只尽力写入，不经过 tracing 加锁分发
+        match write_report(message, location) {
+            Ok(path) => logging::try_error("crash_report", format!("程序崩溃，已保存崩溃报告: {:?}", path)),
+            Err(e) => eprintln!("保存崩溃报告失败: {}", e),
+        }
+        default_hook(info);
+    }));
+}
+
+/// 包装命令分发函数，记录最近调用的命令名
+pub fn track_commands<F>(handler: F) -> impl Fn(Invoke<Wry>) -> bool + Send + Sync + 'static
+where
+    F: Fn(Invoke<Wry>) -> bool + Send + Sync + 'static,
+{
+    move |invoke| {
+        if let Ok(mut recent) = recent_commands().lock() {
+            if recent.len() == RECENT_COMMANDS {
+                recent.pop_front();
+            }
+            recent.push_back(format!("{} {}", chrono::Local::now().format("%H:%M:%S"), invoke.message.command()));
+        }
+        handler(invoke)
+    }
+}
+
+fn write_report(message: String, location: Option<String>) -> Result<PathBuf, String> {
+    let ctx = CONTEXT.get().ok_or("崩溃报告未初始化")?;
+    let now = chrono::Utc::now();
+    let report = CrashReport {
+        id: format!("crash-{}", now.format("%Y%m%d-%H%M%S-%3f")),
+        timestamp: now.timestamp(),
+        app_version: ctx.app_version.clone(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        thread: std::thread::current().name().unwrap_or("unnamed").to_string(),
+        message,
+        location,
+        backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+        // panic 可能发生在持有锁期间，拿不到锁时不阻塞
+        recent_commands: recent_commands()
+            .try_lock()
+            .map(|r| r.iter().cloned().collect())
+            .unwrap_or_default(),
+        recent_logs: logging::try_recent(LevelFilter::WARN, RECENT_LOGS),
+        seen: false,
+    };
+    fs::create_dir_all(&ctx.dir).map_err(|e| format!("创建崩溃报告目录失败: {}", e))?;
+    let path = ctx.dir.join(format!("{}.json", report.id));
+    let json = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("写入崩溃报告失败: {}", e))?;
+    prune(&ctx.dir);
+    Ok(path)
+}
+
+/// 读取所有崩溃报告（最新在前）
+pub fn list(dir: &Path) -> Vec<CrashReport> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    let mut reports: Vec<CrashReport> = entries
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|e| {
+            let json = fs::read_to_string(e.path()).ok()?;
+            serde_json::from_str(&json)
+                .map_err(|err| tracing::warn!("解析崩溃报告 {:?} 失败: {}", e.path(), err))
+                .ok()
+        })
+        .collect();
+    reports.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then_with(|| b.id.cmp(&a.id)));
+    reports
+}
+
+/// 标记报告为已查看
+pub fn mark_seen(dir: &Path, ids: &[String]) -> Result<(), String> {
+    for mut report in list(dir).into_iter().filter(|r| ids.contains(&r.id) && !r.seen) {
+        report.seen = true;
+        let json = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+        fs::write(report_path(dir, &report.id)?, json).map_err(|e| format!("更新崩溃报告失败: {}", e))?;
+    }
+    Ok(())
+}
+
+pub fn delete(dir: &Path, id: &str) -> Result<(), String> {
+    let path = report_path(dir, id)?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("删除崩溃报告失败: {}", e))?;
+    }
+    Ok(())
+}
+
+fn report_path(dir: &Path, id: &str) -> Result<PathBuf, String> {
+    if !id.starts_with("crash-") || id.contains(['/', '\\', '.']) {
+        return Err(format!("无效的崩溃报告 ID: {}", id));
+    }
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+fn prune(dir: &Path) {
+    for report in list(dir).into_iter().skip(MAX_REPORTS) {
+        let _ = fs::remove_file(dir.join(format!("{}.json", report.id)));
+    }
+}
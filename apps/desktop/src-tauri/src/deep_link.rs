@@ -0,0 +1,261 @@
+// aidocplus:// 链接处理
+// - aidocplus://open?project=<项目ID>&doc=<文档ID>
+// - aidocplus://import?url=<网页地址>[&project=<项目ID>]
+// macOS 通过 RunEvent::Opened 传入，Windows / Linux 通过启动参数传入（应用已运行时由 single_instance 转交）
+
+use crate::config::{get_workspace_state_path, is_safe_id, AppState};
+use crate::document::Document;
+use crate::html_markdown;
+use crate::workspace::load_workspace_state;
+use reqwest::Url;
+use serde::Serialize;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+pub const SCHEME: &str = "aidocplus";
+
+/// 导入网页的最大大小
+const MAX_IMPORT_BYTES: usize = 10 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeepLink {
+    Open { project_id: String, document_id: Option<String> },
+    Import { url: String, project_id: Option<String> },
+}
+
+/// 交给前端执行的动作：打开项目（及文档）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeepLinkAction {
+    pub project_id: String,
+    pub document_id: Option<String>,
+}
+
+/// 前端就绪前收到的链接先排队，由前端通过 take_pending_deep_links 取走
+static PENDING: OnceLock<Mutex<Vec<DeepLinkAction>>> = OnceLock::new();
+
+fn pending() -> &'static Mutex<Vec<DeepLinkAction>> {
+    PENDING.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+pub fn take_pending() -> Vec<DeepLinkAction> {
+    pending().lock().map(|mut p| std::mem::take(&mut *p)).unwrap_or_default()
+}
+
+pub fn parse(link: &str) -> Result<DeepLink, String> {
+    let url = Url::parse(link.trim()).map_err(|e| format!("无效的链接 {}: {}", link, e))?;
+    if url.scheme() != SCHEME {
+        return Err(format!("不支持的链接协议: {}", url.scheme()));
+    }
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    // 项目、文档 ID 会拼接为文件路径
+    let id_param = |name: &str| match param(name) {
+        Some(id) if !is_safe_id(&id) => Err(format!("链接中的 {} 参数无效: {}", name, id)),
+        other => Ok(other),
+    };
+    // aidocplus://open?... 中 open 被解析为主机名；兼容 aidocplus:open?... 写法
+    let action = url.host_str().unwrap_or_else(|| url.path().trim_matches('/'));
+    match action {
+        "open" => Ok(DeepLink::Open {
+            project_id: id_param("project")?.ok_or("链接缺少 project 参数")?,
+            document_id: id_param("doc")?,
+        }),
+        "import" => {
+            let target = param("url").ok_or("链接缺少 url 参数")?;
+            let parsed = Url::parse(&target).map_err(|_| format!("无效的导入地址: {}", target))?;
+            if !["http", "https"].contains(&parsed.scheme()) {
+                return Err(format!("只能导入 HTTP(S) 地址: {}", target));
+            }
+            Ok(DeepLink::Import { url: target, project_id: id_param("project")? })
+        }
+        other => Err(format!("不支持的链接操作: {}", other)),
+    }
+}
+
+/// 处理链接：解析后排队并通知前端；导入类链接经用户确认后在后台下载并创建文档
+pub fn handle(app: &AppHandle, link: &str) {
+    tracing::info!("收到链接: {}", link);
+    let parsed = match parse(link) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            tracing::warn!("{}", e);
+            let _ = app.emit("deep-link:error", e);
+            return;
+        }
+    };
+    match parsed {
+        DeepLink::Open { project_id, document_id } => dispatch(app, DeepLinkAction { project_id, document_id }),
+        DeepLink::Import { url, project_id } => {
+            crate::tray::show_main_window(app);
+            let handle = app.clone();
+            // 链接可能来自任意网页，下载前由用户确认
+            app.dialog()
+                .message(format!("是否下载以下网页并导入为文档？\n\n{}", url))
+                .title("导入网页")
+                .kind(MessageDialogKind::Warning)
+                .buttons(MessageDialogButtons::OkCancelCustom("导入".to_string(), "取消".to_string()))
+                .show(move |confirmed| {
+                    if !confirmed {
+                        return;
+                    }
+                    tauri::async_runtime::spawn(async move {
+                        match import_url(&handle, &url, project_id).await {
+                            Ok(action) => dispatch(&handle, action),
+                            Err(e) => {
+                                tracing::warn!("导入 {} 失败: {}", url, e);
+                                let _ = handle.emit("deep-link:error", e);
+                            }
+                        }
+                    });
+                });
+        }
+    }
+}
+
+fn dispatch(app: &AppHandle, action: DeepLinkAction) {
+    if let Ok(mut queue) = pending().lock() {
+        queue.push(action);
+    }
+    crate::tray::show_main_window(app);
+    let _ = app.emit("deep-link:received", ());
+}
+
+/// 下载网页并转换为 Markdown 文档，保存到指定项目（未指定时为当前项目）
+async fn import_url(app: &AppHandle, url: &str, project_id: Option<String>) -> Result<DeepLinkAction, String> {
+    let project_id = match project_id {
+        Some(id) => id,
+        None => load_workspace_state(&get_workspace_state_path(app))?
+            .and_then(|ws| ws.current_project_id)
+            .ok_or("没有打开的项目，请在链接中指定 project 参数")?,
+    };
+    let state = app.state::<AppState>();
+    if !state.get_project_path(&project_id).exists() {
+        return Err(crate::i18n::tf("error.project_not_found", &[&project_id]));
+    }
+
+    let mut resp = reqwest::Client::new()
+        .get(url)
+        .timeout(Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| format!("下载失败: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("下载失败: HTTP {}", resp.status()));
+    }
+    let is_html = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.contains("html"));
+    if resp.content_length().is_some_and(|len| len > MAX_IMPORT_BYTES as u64) {
+        return Err("网页内容过大".to_string());
+    }
+    // 未声明长度时边下载边检查
+    let mut bytes = Vec::new();
+    while let Some(chunk) = resp.chunk().await.map_err(|e| format!("下载失败: {}", e))? {
+        if bytes.len() + chunk.len() > MAX_IMPORT_BYTES {
+            return Err("网页内容过大".to_string());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    let body = String::from_utf8_lossy(&bytes).to_string();
+
+    let (title, content) = if is_html {
+        (html_title(&body), html_markdown::html_to_markdown(&body))
+    } else {
+        (None, body)
+    };
+    let title = title.unwrap_or_else(|| title_from_url(url));
+
+    let mut document = Document::new(project_id.clone(), title, String::new());
+    document.metadata.word_count = content.split_whitespace().count();
+    document.metadata.character_count = content.chars().count();
+    document.content = format!("{}\n\n> 来源: {}\n", content.trim_end(), url);
+    document.save(&state.get_document_path(&project_id, &document.id)).map_err(|e| e.to_string())?;
+    Ok(DeepLinkAction { project_id, document_id: Some(document.id) })
+}
+
+fn html_title(html: &str) -> Option<String> {
+    let lower = html.to_lowercase();
+    let start = lower.find("<title")?;
+    let start = start + lower[start..].find('>')? + 1;
+    let end = start + lower[start..].find("</title>")?;
+    let title = html_markdown::decode_entities(html[start..end].trim());
+    (!title.is_empty()).then_some(title)
+}
+
+fn title_from_url(url: &str) -> String {
+    Url::parse(url)
+        .ok()
+        .and_then(|u| {
+            u.path_segments()
+                .and_then(|mut s| s.next_back().map(str::to_string))
+                .filter(|s| !s.is_empty())
+                .or_else(|| u.host_str().map(str::to_string))
+        })
+        .unwrap_or_else(|| url.to_string())
+}
+
+/// 启动参数中的链接（Windows / Linux 由系统以参数形式传入）
+pub fn links_from_args() -> Vec<String> {
+    std::env::args()
+        .skip(1)
+        .filter(|a| a.starts_with(&format!("{}:", SCHEME)))
+        .collect()
+}
+
+/// 向系统注册 aidocplus:// 协议（macOS 由 Info.plist 声明）
+pub fn register_scheme() {
+    let Ok(exe) = std::env::current_exe() else { return };
+    let exe = exe.to_string_lossy().to_string();
+    if let Err(e) = register_for_platform(&exe) {
+        tracing::warn!("注册 {}:// 协议失败: {}", SCHEME, e);
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn register_for_platform(exe: &str) -> Result<(), String> {
+    let key = format!(r"HKCU\Software\Classes\{}", SCHEME);
+    let command = format!("\"{}\" \"%1\"", exe);
+    let entries: [&[&str]; 3] = [
+        &["add", &key, "/ve", "/d", "URL:AiDocPlus", "/f"],
+        &["add", &key, "/v", "URL Protocol", "/d", "", "/f"],
+        &["add", &format!(r"{}\shell\open\command", key), "/ve", "/d", &command, "/f"],
+    ];
+    for args in entries {
+        let status = std::process::Command::new("reg").args(args).status().map_err(|e| e.to_string())?;
+        if !status.success() {
+            return Err(format!("reg {} 失败", args.join(" ")));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn register_for_platform(exe: &str) -> Result<(), String> {
+    let apps_dir = dirs::data_dir().ok_or("无法获取数据目录")?.join("applications");
+    std::fs::create_dir_all(&apps_dir).map_err(|e| e.to_string())?;
+    let desktop_name = format!("{}-url-handler.desktop", SCHEME);
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName=AiDocPlus\nExec=\"{}\" %u\nNoDisplay=true\nMimeType=x-scheme-handler/{};\n",
+        exe, SCHEME
+    );
+    std::fs::write(apps_dir.join(&desktop_name), entry).map_err(|e| e.to_string())?;
+    std::process::Command::new("xdg-mime")
+        .args(["default", &desktop_name, &format!("x-scheme-handler/{}", SCHEME)])
+        .status()
+        .map_err(|e| format!("xdg-mime 不可用: {}", e))?;
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn register_for_platform(_exe: &str) -> Result<(), String> {
+    Ok(())
+}
@@ -1,11 +1,15 @@
 use crate::error::AppError;
+use crate::integrations::Publication;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// 版本数量限制，防止存储耗尽
 const MAX_VERSIONS: usize = 1000;
 
+/// 正文超过此大小时单独存为 {文档ID}.content.md，文档 JSON 只保留元数据
+pub const LARGE_CONTENT_BYTES: usize = 1024 * 1024;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Attachment {
     pub id: String,
@@ -32,6 +36,9 @@ pub struct Document {
     pub author_notes: String,
     #[serde(rename = "aiGeneratedContent")]
     pub ai_generated_content: String,
+    /// 待写入版本库的新版本；历史版本存放在 versions/{文档ID}/，不再写入文档文件。
+    /// 旧格式文档在加载时迁移
+    #[serde(default, skip_serializing)]
     pub versions: Vec<DocumentVersion>,
     #[serde(rename = "currentVersionId")]
     pub current_version_id: String,
@@ -44,6 +51,18 @@ pub struct Document {
     pub enabled_plugins: Option<Vec<String>>,
     #[serde(default, skip_serializing_if = "Option::is_none", rename = "composedContent")]
     pub composed_content: Option<String>,
+    /// 发布到 Confluence / Notion 的记录
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub publications: Vec<Publication>,
+    /// 正文存放在单独文件中（大文档）；list_documents 返回的正文为空，需分段读取
+    #[serde(default, skip_serializing_if = "std::ops::Not::not", rename = "contentExternal")]
+    pub content_external: bool,
+    /// 已锁定（定稿）的文档不能保存、重命名或删除
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
+    /// 文档级系统提示词（来自模板关联的提示词模板），生成时附加在角色提示词之后
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "systemPrompt")]
+    pub system_prompt: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -125,6 +144,10 @@ impl Document {
             plugin_data: None,
             enabled_plugins: None,
             composed_content: None,
+            publications: Vec::new(),
+            content_external: false,
+            locked: false,
+            system_prompt: None,
         }
     }
 
@@ -132,14 +155,64 @@ impl Document {
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
-        let json = serde_json::to_string_pretty(self)?;
+        if !self.versions.is_empty() {
+            let store = VersionStore::for_document(path, &self.id);
+            for version in &self.versions {
+                store.add(version, &self.current_version_id)?;
+            }
+        }
+        let content_path = content_path(path);
+        let json = if self.content.len() > LARGE_CONTENT_BYTES {
+            fs::write(&content_path, &self.content)?;
+            let mut value = serde_json::to_value(self)?;
+            value["content"] = serde_json::Value::String(String::new());
+            value["contentExternal"] = serde_json::Value::Bool(true);
+            serde_json::to_string_pretty(&value)?
+        } else {
+            if content_path.exists() {
+                fs::remove_file(&content_path)?;
+            }
+            let mut value = serde_json::to_value(self)?;
+            if let Some(obj) = value.as_object_mut() {
+                obj.remove("contentExternal");
+            }
+            serde_json::to_string_pretty(&value)?
+        };
         fs::write(path, json)?;
         Ok(())
     }
 
     pub fn load(path: &PathBuf) -> std::result::Result<Self, AppError> {
+        let mut doc = Self::load_meta(path)?;
+        if doc.content_external {
+            doc.content = fs::read_to_string(content_path(path))?;
+        }
+        Ok(doc)
+    }
+
+    /// 锁定的文档返回 DocumentLocked 错误
+    pub fn ensure_unlocked(&self) -> std::result::Result<(), AppError> {
+        if self.locked {
+            return Err(AppError::DocumentLocked(self.title.clone()));
+        }
+        Ok(())
+    }
+
+    /// 只读取文档 JSON；大文档的正文不加载（content 为空，content_external 为 true）
+    pub fn load_meta(path: &PathBuf) -> std::result::Result<Self, AppError> {
         let json = fs::read_to_string(path)?;
-        let doc: Self = serde_json::from_str(&json)?;
+        let mut doc: Self = serde_json::from_str(&json)?;
+        // 旧格式：版本历史内嵌在文档中，迁移到版本库后重写文档
+        if !doc.versions.is_empty() {
+            if doc.content_external {
+                doc.content = fs::read_to_string(content_path(path))?;
+            }
+            doc.save(path)?;
+            doc.versions.clear();
+            if doc.content_external {
+                doc.content.clear();
+            }
+        }
         Ok(doc)
     }
 
@@ -171,24 +244,149 @@ impl Document {
             composed_content,
         };
 
+        // 版本数量限制在写入版本库时处理
         self.versions.push(version);
 
-        // 版本数量限制：超过限制时删除最旧的非当前版本
-        while self.versions.len() > MAX_VERSIONS {
-            // 找到最旧的非当前版本并删除
-            if let Some(oldest_idx) = self.versions.iter().enumerate()
-                .filter(|(_, v)| v.id != self.current_version_id)
-                .min_by_key(|(_, v)| v.created_at)
+        self.current_version_id = version_id;
+        self.metadata.updated_at = now;
+    }
+}
+
+/// 大文档正文文件：{文档ID}.json 同目录下的 {文档ID}.content.md
+pub fn content_path(doc_path: &Path) -> PathBuf {
+    doc_path.with_extension("content.md")
+}
+
+/// 从 offset（字节）起读取不超过 max_bytes 的一段正文，边界对齐到字符；
+/// 返回该段与下一段的起点（已读完时为 None）
+pub fn content_range(content: &str, offset: usize, max_bytes: usize) -> (&str, Option<usize>) {
+    let mut start = offset.min(content.len());
+    while !content.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = start.saturating_add(max_bytes.max(4)).min(content.len());
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    (&content[start..end], (end < content.len()).then_some(end))
+}
+
+/// 分段上传正文的暂存文件；upload_id 只允许字母、数字和连字符
+fn upload_path(upload_id: &str) -> std::result::Result<PathBuf, AppError> {
+    if upload_id.is_empty() || !upload_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(AppError::InvalidData(format!("invalid upload id: {}", upload_id)));
+    }
+    Ok(std::env::temp_dir().join(format!("aidocplus-upload-{}.part", upload_id)))
+}
+
+/// 追加一段上传中的正文
+pub fn append_upload(upload_id: &str, chunk: &str) -> std::result::Result<(), AppError> {
+    use std::io::Write;
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(upload_path(upload_id)?)?;
+    file.write_all(chunk.as_bytes())?;
+    Ok(())
+}
+
+/// 取出已上传的完整正文并删除暂存文件
+pub fn take_upload(upload_id: &str) -> std::result::Result<String, AppError> {
+    let path = upload_path(upload_id)?;
+    let content = fs::read_to_string(&path)?;
+    let _ = fs::remove_file(&path);
+    Ok(content)
+}
+
+/// 版本列表条目（不含内容）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionEntry {
+    id: String,
+    created_at: i64,
+}
+
+/// 文档版本库：versions/{文档ID}/index.json 记录版本顺序，每个版本单独存为 {版本ID}.json，
+/// 加载文档时不再读取历史内容
+pub struct VersionStore {
+    dir: PathBuf,
+}
+
+impl VersionStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// 由文档路径 {项目}/documents/{ID}.json 推出版本目录，与 AppState::get_versions_path 一致
+    pub fn for_document(doc_path: &Path, document_id: &str) -> Self {
+        let project_dir = doc_path.parent().and_then(Path::parent).unwrap_or(Path::new("."));
+        Self::new(project_dir.join("versions").join(document_id))
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join("index.json")
+    }
+
+    fn version_path(&self, version_id: &str) -> PathBuf {
+        // 版本 ID 来自前端，只取文件名部分
+        let name = Path::new(version_id).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        self.dir.join(format!("{}.json", name))
+    }
+
+    fn load_index(&self) -> Vec<VersionEntry> {
+        fs::read_to_string(self.index_path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// 写入一个版本；超过 MAX_VERSIONS 时删除最旧的非当前版本
+    pub fn add(&self, version: &DocumentVersion, current_version_id: &str) -> std::result::Result<(), AppError> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.version_path(&version.id), serde_json::to_string_pretty(version)?)?;
+
+        let mut index = self.load_index();
+        if !index.iter().any(|e| e.id == version.id) {
+            index.push(VersionEntry { id: version.id.clone(), created_at: version.created_at });
+        }
+        while index.len() > MAX_VERSIONS {
+            let oldest = index
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| e.id != current_version_id)
+                .min_by_key(|(_, e)| e.created_at)
                 .map(|(idx, _)| idx)
-            {
-                self.versions.remove(oldest_idx);
-            } else {
-                // 如果所有版本都是当前版本（不太可能），删除第一个
-                self.versions.remove(0);
-            }
+                .unwrap_or(0);
+            let removed = index.remove(oldest);
+            let _ = fs::remove_file(self.version_path(&removed.id));
         }
+        fs::write(self.index_path(), serde_json::to_string(&index)?)?;
+        Ok(())
+    }
 
-        self.current_version_id = version_id;
-        self.metadata.updated_at = now;
+    /// 全部版本（按创建顺序）
+    pub fn list(&self) -> Vec<DocumentVersion> {
+        self.load_index().iter().filter_map(|e| self.get(&e.id)).collect()
+    }
+
+    pub fn get(&self, version_id: &str) -> Option<DocumentVersion> {
+        let json = fs::read_to_string(self.version_path(version_id)).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// 移动到另一个版本目录（文档移动到其他项目时）
+    pub fn move_to(&self, target: &VersionStore) -> std::result::Result<(), AppError> {
+        if !self.dir.exists() {
+            return Ok(());
+        }
+        if let Some(parent) = target.dir.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(&self.dir, &target.dir)?;
+        Ok(())
+    }
+
+    pub fn remove(&self) -> std::result::Result<(), AppError> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)?;
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,251 @@
+use crate::email_oauth::{self, OAuthTokens};
+use crate::imap_client::ImapSettings;
+use crate::secrets;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// 邮箱账户配置（密码不落盘，保存在系统钥匙串中）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailAccount {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub provider: String,
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub encryption: String,
+    pub email: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// IMAP 收件服务器，为空时由 SMTP 地址推断（smtp.x.com → imap.x.com）
+    #[serde(default)]
+    pub imap_host: Option<String>,
+    #[serde(default)]
+    pub imap_port: Option<u16>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 认证方式："password"（授权码）或 "oauth2"（XOAUTH2）
+    #[serde(default = "default_auth_type")]
+    pub auth_type: String,
+    /// OAuth2 服务商："google" / "microsoft"
+    #[serde(default)]
+    pub oauth_provider: Option<String>,
+    #[serde(default)]
+    pub oauth_client_id: Option<String>,
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub updated_at: i64,
+    /// 钥匙串中是否已保存授权码（OAuth2 账户为令牌），由后端按密钥索引填写，读取时忽略
+    #[serde(default, skip_deserializing)]
+    pub has_password: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_auth_type() -> String {
+    "password".to_string()
+}
+
+impl EmailAccount {
+    fn is_oauth2(&self) -> bool {
+        self.auth_type == "oauth2"
+    }
+}
+
+/// 解析后的 SMTP 连接参数
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub encryption: String,
+    pub email: String,
+    /// 授权码，XOAUTH2 时为 access token
+    pub password: String,
+    pub display_name: Option<String>,
+    pub xoauth2: bool,
+}
+
+fn password_secret_key(account_id: &str) -> String {
+    format!("email:{}", account_id)
+}
+
+fn oauth_secret_key(account_id: &str) -> String {
+    format!("email-oauth:{}", account_id)
+}
+
+pub fn load_accounts(path: &Path) -> Vec<EmailAccount> {
+    if !path.exists() {
+        return Vec::new();
+    }
+    match fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            tracing::warn!("Failed to parse email accounts: {}", e);
+            Vec::new()
+        }),
+        Err(e) => {
+            tracing::warn!("Failed to read email accounts: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// 账户列表，附带是否已保存凭据
+pub fn list_accounts(path: &Path) -> Vec<EmailAccount> {
+    load_accounts(path).into_iter().map(with_credential_flag).collect()
+}
+
+fn with_credential_flag(mut account: EmailAccount) -> EmailAccount {
+    let key = if account.is_oauth2() { oauth_secret_key(&account.id) } else { password_secret_key(&account.id) };
+    account.has_password = secrets::entry(&key).is_some();
+    account
+}
+
+fn save_accounts(path: &Path, accounts: &[EmailAccount]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(accounts)
+        .map_err(|e| format!("Failed to serialize email accounts: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write email accounts: {}", e))
+}
+
+/// 新增或更新账户；password 为 None 时保留钥匙串中已有的密码
+pub fn save_account(path: &Path, mut account: EmailAccount, password: Option<String>) -> Result<EmailAccount, String> {
+    if account.id.trim().is_empty() {
+        account.id = format!("email_{}", uuid::Uuid::new_v4());
+    }
+    if account.smtp_host.trim().is_empty() || account.email.trim().is_empty() {
+        return Err("SMTP 地址和邮箱地址不能为空".to_string());
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let mut accounts = load_accounts(path);
+    match accounts.iter().position(|a| a.id == account.id) {
+        Some(idx) => {
+            account.created_at = accounts[idx].created_at;
+            account.updated_at = now;
+            accounts[idx] = account.clone();
+        }
+        None => {
+            if !account.is_oauth2() && password.as_deref().unwrap_or_default().is_empty() {
+                return Err("新账户必须提供 SMTP 授权码".to_string());
+            }
+            account.created_at = now;
+            account.updated_at = now;
+            accounts.push(account.clone());
+        }
+    }
+
+    if let Some(pw) = password.filter(|p| !p.is_empty()) {
+        secrets::store(&password_secret_key(&account.id), &pw, Some(format!("SMTP 授权码 {}", account.email)))?;
+    }
+    save_accounts(path, &accounts)?;
+    Ok(with_credential_flag(account))
+}
+
+pub fn delete_account(path: &Path, account_id: &str) -> Result<(), String> {
+    let mut accounts = load_accounts(path);
+    let len_before = accounts.len();
+    accounts.retain(|a| a.id != account_id);
+    if accounts.len() == len_before {
+        return Err(format!("邮箱账户不存在: {}", account_id));
+    }
+    secrets::delete(&password_secret_key(account_id))?;
+    secrets::delete(&oauth_secret_key(account_id))?;
+    save_accounts(path, &accounts)
+}
+
+fn find_account(path: &Path, account_id: &str) -> Result<EmailAccount, String> {
+    load_accounts(path)
+        .into_iter()
+        .find(|a| a.id == account_id)
+        .ok_or_else(|| format!("邮箱账户不存在: {}", account_id))
+}
+
+fn oauth_params(account: &EmailAccount) -> Result<(String, String), String> {
+    match (&account.oauth_provider, &account.oauth_client_id) {
+        (Some(provider), Some(client_id)) if !client_id.is_empty() => Ok((provider.clone(), client_id.clone())),
+        _ => Err(format!("账户 {} 未配置 OAuth2 服务商或 Client ID", account.email)),
+    }
+}
+
+/// 为 OAuth2 账户执行浏览器授权，令牌保存到钥匙串
+pub async fn authorize_oauth(path: &Path, account_id: &str, client_secret: Option<String>) -> Result<(), String> {
+    let account = find_account(path, account_id)?;
+    let (provider, client_id) = oauth_params(&account)?;
+    let tokens = email_oauth::authorize(&provider, &client_id, client_secret).await?;
+    save_oauth_tokens(account_id, &tokens)
+}
+
+fn save_oauth_tokens(account_id: &str, tokens: &OAuthTokens) -> Result<(), String> {
+    let json = serde_json::to_string(tokens).map_err(|e| e.to_string())?;
+    secrets::store(&oauth_secret_key(account_id), &json, Some("OAuth2 令牌".to_string())).map(|_| ())
+}
+
+/// 读取 OAuth2 access token，过期时自动刷新并写回钥匙串
+async fn oauth_access_token(account: &EmailAccount) -> Result<String, String> {
+    let (provider, client_id) = oauth_params(account)?;
+    let json = secrets::get(&oauth_secret_key(&account.id))?
+        .ok_or_else(|| format!("账户 {} 尚未完成 OAuth2 授权", account.email))?;
+    let tokens: OAuthTokens = serde_json::from_str(&json)
+        .map_err(|e| format!("钥匙串中的 OAuth2 令牌已损坏，请重新授权: {}", e))?;
+
+    if !tokens.is_expired() {
+        return Ok(tokens.access_token);
+    }
+    let refreshed = email_oauth::refresh(&provider, &client_id, &tokens).await?;
+    save_oauth_tokens(&account.id, &refreshed)?;
+    Ok(refreshed.access_token)
+}
+
+/// 读取账户凭据：授权码，或 OAuth2 账户的 access token
+async fn account_secret(account: &EmailAccount) -> Result<(String, bool), String> {
+    if account.is_oauth2() {
+        return Ok((oauth_access_token(account).await?, true));
+    }
+    let password = secrets::get(&password_secret_key(&account.id))?
+        .ok_or_else(|| format!("钥匙串中未找到账户 {} 的授权码，请重新保存账户", account.email))?;
+    Ok((password, false))
+}
+
+/// 根据账户 ID 读取 SMTP 配置及钥匙串中的密码（OAuth2 账户返回 access token）
+pub async fn resolve_account(path: &Path, account_id: &str) -> Result<SmtpSettings, String> {
+    let account = find_account(path, account_id)?;
+    let (password, xoauth2) = account_secret(&account).await?;
+
+    Ok(SmtpSettings {
+        host: account.smtp_host,
+        port: account.smtp_port,
+        encryption: account.encryption,
+        email: account.email,
+        password,
+        display_name: account.display_name.filter(|n| !n.trim().is_empty()),
+        xoauth2,
+    })
+}
+
+/// 根据账户 ID 读取 IMAP 配置（与 SMTP 共用凭据）
+pub async fn resolve_imap(path: &Path, account_id: &str) -> Result<ImapSettings, String> {
+    let account = find_account(path, account_id)?;
+    let host = match account.imap_host.as_deref().map(str::trim).filter(|h| !h.is_empty()) {
+        Some(host) => host.to_string(),
+        None => match account.smtp_host.strip_prefix("smtp.") {
+            Some(domain) => format!("imap.{}", domain),
+            None => return Err(format!("账户 {} 未配置 IMAP 服务器", account.email)),
+        },
+    };
+    let (password, xoauth2) = account_secret(&account).await?;
+
+    Ok(ImapSettings {
+        host,
+        port: account.imap_port.unwrap_or(993),
+        encryption: if account.imap_port == Some(143) { "starttls" } else { "ssl" }.to_string(),
+        email: account.email,
+        password,
+        xoauth2,
+    })
+}
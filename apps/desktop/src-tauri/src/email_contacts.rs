@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// 邮件联系人
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Contact {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+    #[serde(default)]
+    pub group: Option<String>,
+    /// 作为收件人发送的次数，用于自动补全排序
+    #[serde(default)]
+    pub use_count: u32,
+    #[serde(default)]
+    pub last_used_at: Option<i64>,
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+impl Contact {
+    /// "张三 <zhang@example.com>"，可直接作为收件人地址
+    pub fn mailbox(&self) -> String {
+        if self.name.trim().is_empty() {
+            self.email.clone()
+        } else {
+            format!("{} <{}>", self.name.trim(), self.email)
+        }
+    }
+}
+
+pub fn load_contacts(path: &Path) -> Vec<Contact> {
+    if !path.exists() {
+        return Vec::new();
+    }
+    match fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            tracing::warn!("Failed to parse contacts: {}", e);
+            Vec::new()
+        }),
+        Err(e) => {
+            tracing::warn!("Failed to read contacts: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn save_contacts(path: &Path, contacts: &[Contact]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(contacts)
+        .map_err(|e| format!("Failed to serialize contacts: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write contacts: {}", e))
+}
+
+/// 新增或更新联系人；同一邮箱地址只保留一条
+pub fn save_contact(path: &Path, mut contact: Contact) -> Result<Contact, String> {
+    contact.email = contact.email.trim().to_string();
+    contact.name = contact.name.trim().to_string();
+    contact.group = contact.group.map(|g| g.trim().to_string()).filter(|g| !g.is_empty());
+    if !is_valid_email(&contact.email) {
+        return Err(format!("无效的邮箱地址: {}", contact.email));
+    }
+    if contact.id.trim().is_empty() {
+        contact.id = format!("contact_{}", uuid::Uuid::new_v4());
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let mut contacts = load_contacts(path);
+    if contacts
+        .iter()
+        .any(|c| c.id != contact.id && c.email.eq_ignore_ascii_case(&contact.email))
+    {
+        return Err(format!("联系人 {} 已存在", contact.email));
+    }
+
+    match contacts.iter().position(|c| c.id == contact.id) {
+        Some(idx) => {
+            contact.created_at = contacts[idx].created_at;
+            contact.use_count = contacts[idx].use_count;
+            contact.last_used_at = contacts[idx].last_used_at;
+            contact.updated_at = now;
+            contacts[idx] = contact.clone();
+        }
+        None => {
+            contact.created_at = now;
+            contact.updated_at = now;
+            contacts.push(contact.clone());
+        }
+    }
+    save_contacts(path, &contacts)?;
+    Ok(contact)
+}
+
+pub fn delete_contact(path: &Path, id: &str) -> Result<(), String> {
+    let mut contacts = load_contacts(path);
+    let len_before = contacts.len();
+    contacts.retain(|c| c.id != id);
+    if contacts.len() == len_before {
+        return Err(format!("联系人不存在: {}", id));
+    }
+    save_contacts(path, &contacts)
+}
+
+/// 所有分组名（按名称排序）
+pub fn list_groups(path: &Path) -> Vec<String> {
+    let mut groups: Vec<String> = load_contacts(path).into_iter().filter_map(|c| c.group).collect();
+    groups.sort();
+    groups.dedup();
+    groups
+}
+
+/// 自动补全：按 名称/邮箱 前缀匹配 > 包含匹配 排序，同级按使用次数排序
+pub fn search(path: &Path, query: &str, limit: usize) -> Vec<Contact> {
+    let query = query.trim().to_lowercase();
+    let mut matches: Vec<(u8, Contact)> = load_contacts(path)
+        .into_iter()
+        .filter_map(|c| {
+            let name = c.name.to_lowercase();
+            let email = c.email.to_lowercase();
+            let rank = if query.is_empty() {
+                2
+            } else if name.starts_with(&query) || email.starts_with(&query) {
+                0
+            } else if name.contains(&query) || email.contains(&query) {
+                1
+            } else {
+                return None;
+            };
+            Some((rank, c))
+        })
+        .collect();
+    matches.sort_by(|(ra, a), (rb, b)| {
+        ra.cmp(rb)
+            .then(b.use_count.cmp(&a.use_count))
+            .then(a.name.cmp(&b.name))
+    });
+    matches.into_iter().take(limit).map(|(_, c)| c).collect()
+}
+
+/// 展开收件人列表中的分组引用（"group:项目组"）为分组内所有联系人
+pub fn expand_groups(path: &Path, recipients: &[String]) -> Vec<String> {
+    let mut contacts: Option<Vec<Contact>> = None;
+    let mut result = Vec::new();
+    for r in recipients {
+        match r.trim().strip_prefix("group:") {
+            Some(group) => {
+                let contacts = contacts.get_or_insert_with(|| load_contacts(path));
+                result.extend(
+                    contacts
+                        .iter()
+                        .filter(|c| c.group.as_deref() == Some(group.trim()))
+                        .map(Contact::mailbox),
+                );
+            }
+            None => result.push(r.clone()),
+        }
+    }
+    result
+}
+
+/// 发送后更新联系人的使用次数（只统计已存在的联系人）
+pub fn record_usage(path: &Path, recipients: &[String]) {
+    let mut contacts = load_contacts(path);
+    let now = chrono::Utc::now().timestamp();
+    let mut changed = false;
+    for r in recipients {
+        let addr = extract_address(r);
+        if let Some(c) = contacts.iter_mut().find(|c| c.email.eq_ignore_ascii_case(addr)) {
+            c.use_count += 1;
+            c.last_used_at = Some(now);
+            changed = true;
+        }
+    }
+    if changed {
+        if let Err(e) = save_contacts(path, &contacts) {
+            tracing::warn!("更新联系人使用记录失败: {}", e);
+        }
+    }
+}
+
+/// "张三 <zhang@example.com>" → "zhang@example.com"
+fn extract_address(mailbox: &str) -> &str {
+    match (mailbox.rfind('<'), mailbox.rfind('>')) {
+        (Some(start), Some(end)) if start < end => mailbox[start + 1..end].trim(),
+        _ => mailbox.trim(),
+    }
+}
+
+fn is_valid_email(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((local, domain)) => !local.is_empty() && domain.contains('.') && !email.contains(char::is_whitespace),
+        None => false,
+    }
+}
@@ -0,0 +1,136 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use image::{GenericImageView, ImageFormat};
+use std::io::Cursor;
+use std::path::PathBuf;
+
+/// 所有内嵌图片的总大小上限，超出后保留原引用
+const MAX_TOTAL_BYTES: usize = 10 * 1024 * 1024;
+
+/// 单张图片超过该大小或尺寸时缩放后重新编码
+const DOWNSCALE_THRESHOLD_BYTES: usize = 1024 * 1024;
+const MAX_DIMENSION: u32 = 1600;
+
+/// 以 cid: 引用的内嵌图片
+pub struct InlineImage {
+    pub cid: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// 将 HTML 中引用本地文件（file://、绝对路径）和 data: URI 的图片替换为 cid: 引用
+pub fn embed_local_images(html: &str) -> (String, Vec<InlineImage>) {
+    let re = regex::Regex::new(r#"(<img\b[^>]*?\bsrc\s*=\s*)(["'])([^"']+)(["'])"#).unwrap();
+    let mut images: Vec<InlineImage> = Vec::new();
+    let mut total = 0usize;
+
+    let result = re.replace_all(html, |caps: &regex::Captures| {
+        let original = caps[0].to_string();
+        let src = &caps[3];
+
+        let Some((data, mime)) = load_image_source(src) else { return original };
+        let (data, mime) = downscale_if_needed(data, mime);
+
+        if total + data.len() > MAX_TOTAL_BYTES {
+            tracing::warn!("内嵌图片超出总大小限制，保留原引用: {}", src);
+            return original;
+        }
+        total += data.len();
+
+        let cid = format!("img{}.{}@aidocplus", images.len() + 1, uuid::Uuid::new_v4().simple());
+        images.push(InlineImage { cid: cid.clone(), content_type: mime, data });
+        format!("{}{}cid:{}{}", &caps[1], &caps[2], cid, &caps[4])
+    });
+
+    (result.into_owned(), images)
+}
+
+/// 将引用本地文件的图片替换为 data: URI（粘贴到公众号等网页编辑器时使用）
+pub fn inline_as_data_uris(html: &str) -> String {
+    let re = regex::Regex::new(r#"(<img\b[^>]*?\bsrc\s*=\s*)(["'])([^"']+)(["'])"#).unwrap();
+    let mut total = 0usize;
+
+    re.replace_all(html, |caps: &regex::Captures| {
+        let original = caps[0].to_string();
+        let src = &caps[3];
+        if src.starts_with("data:") {
+            return original;
+        }
+
+        let Some((data, mime)) = load_image_source(src) else { return original };
+        let (data, mime) = downscale_if_needed(data, mime);
+
+        if total + data.len() > MAX_TOTAL_BYTES {
+            tracing::warn!("内嵌图片超出总大小限制，保留原引用: {}", src);
+            return original;
+        }
+        total += data.len();
+        format!("{}{}data:{};base64,{}{}", &caps[1], &caps[2], mime, STANDARD.encode(&data), &caps[4])
+    })
+    .into_owned()
+}
+
+pub(crate) fn load_image_source(src: &str) -> Option<(Vec<u8>, String)> {
+    if let Some(rest) = src.strip_prefix("data:") {
+        // data:image/png;base64,....
+        let (meta, payload) = rest.split_once(',')?;
+        let mime = meta.strip_suffix(";base64")?;
+        if !mime.starts_with("image/") {
+            return None;
+        }
+        let data = STANDARD.decode(payload.trim()).ok()?;
+        return Some((data, mime.to_string()));
+    }
+
+    let path = if src.starts_with("file://") {
+        reqwest::Url::parse(src).ok()?.to_file_path().ok()?
+    } else {
+        let p = PathBuf::from(src);
+        if !p.is_absolute() {
+            // http(s) 等远程图片不处理
+            return None;
+        }
+        p
+    };
+
+    let data = std::fs::read(&path).ok()?;
+    let mime = match image::guess_format(&data).ok()? {
+        ImageFormat::Png => "image/png",
+        ImageFormat::Jpeg => "image/jpeg",
+        ImageFormat::Gif => "image/gif",
+        ImageFormat::WebP => "image/webp",
+        _ => return None,
+    };
+    Some((data, mime.to_string()))
+}
+
+/// 大图缩放到 MAX_DIMENSION 以内；带透明通道的保存为 PNG，其余保存为 JPEG
+fn downscale_if_needed(data: Vec<u8>, mime: String) -> (Vec<u8>, String) {
+    // GIF 可能是动图，保持原样
+    if mime == "image/gif" {
+        return (data, mime);
+    }
+    let Ok(img) = image::load_from_memory(&data) else { return (data, mime) };
+    let (w, h) = img.dimensions();
+    if data.len() <= DOWNSCALE_THRESHOLD_BYTES && w <= MAX_DIMENSION && h <= MAX_DIMENSION {
+        return (data, mime);
+    }
+
+    let resized = if w > MAX_DIMENSION || h > MAX_DIMENSION {
+        img.resize(MAX_DIMENSION, MAX_DIMENSION, image::imageops::FilterType::Lanczos3)
+    } else {
+        img
+    };
+
+    let mut out = Cursor::new(Vec::new());
+    let encoded = if resized.color().has_alpha() {
+        resized.write_to(&mut out, ImageFormat::Png).map(|_| "image/png")
+    } else {
+        resized.to_rgb8().write_to(&mut out, ImageFormat::Jpeg).map(|_| "image/jpeg")
+    };
+
+    match encoded {
+        // 重新编码反而更大时（如已压缩的 PNG 截图）保留原图
+        Ok(new_mime) if out.get_ref().len() < data.len() => (out.into_inner(), new_mime.to_string()),
+        _ => (data, mime),
+    }
+}
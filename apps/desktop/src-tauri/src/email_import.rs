@@ -0,0 +1,398 @@
+use crate::document::{Attachment, Document};
+use crate::html_markdown::html_to_markdown;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 解析后的邮件
+pub struct ParsedEmail {
+    pub subject: String,
+    pub from: String,
+    pub to: String,
+    pub date: Option<String>,
+    pub message_id: Option<String>,
+    pub text: Option<String>,
+    pub html: Option<String>,
+    pub attachments: Vec<MailAttachment>,
+}
+
+pub struct MailAttachment {
+    pub file_name: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// 单封邮件的导入结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedEmail {
+    pub uid: u32,
+    pub subject: String,
+    pub document_id: Option<String>,
+    /// imported / skipped / failed
+    pub status: String,
+    pub error: Option<String>,
+}
+
+// ============================================================
+// MIME 解析
+// ============================================================
+
+struct Part<'a> {
+    headers: Vec<(String, String)>,
+    body: &'a [u8],
+}
+
+impl Part<'_> {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    fn content_type(&self) -> (String, Vec<(String, String)>) {
+        let (value, params) = parse_header_params(self.header("content-type").unwrap_or("text/plain"));
+        (value.to_ascii_lowercase(), params)
+    }
+
+    fn decoded_body(&self) -> Vec<u8> {
+        let encoding = self.header("content-transfer-encoding").unwrap_or("").trim().to_ascii_lowercase();
+        match encoding.as_str() {
+            "base64" => {
+                let cleaned: Vec<u8> = self.body.iter().copied().filter(|b| !b.is_ascii_whitespace()).collect();
+                STANDARD.decode(&cleaned).unwrap_or_else(|_| self.body.to_vec())
+            }
+            "quoted-printable" => {
+                quoted_printable::decode(self.body, quoted_printable::ParseMode::Robust).unwrap_or_else(|_| self.body.to_vec())
+            }
+            _ => self.body.to_vec(),
+        }
+    }
+}
+
+pub fn parse_email(raw: &[u8]) -> ParsedEmail {
+    let root = split_part(raw);
+    let header = |name: &str| root.header(name).map(decode_rfc2047);
+
+    let mut email = ParsedEmail {
+        subject: header("subject").unwrap_or_default().trim().to_string(),
+        from: header("from").unwrap_or_default(),
+        to: header("to").unwrap_or_default(),
+        date: header("date"),
+        message_id: root.header("message-id").map(|s| s.trim().to_string()),
+        text: None,
+        html: None,
+        attachments: Vec::new(),
+    };
+    collect_parts(&root, &mut email, 0);
+    email
+}
+
+fn collect_parts(part: &Part, email: &mut ParsedEmail, depth: usize) {
+    // 防止恶意构造的深层嵌套
+    if depth > 16 {
+        return;
+    }
+    let (mime, params) = part.content_type();
+    let param = |key: &str| params.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+
+    if mime.starts_with("multipart/") {
+        let Some(boundary) = param("boundary") else { return };
+        for child in split_multipart(part.body, &boundary) {
+            collect_parts(&split_part(child), email, depth + 1);
+        }
+        return;
+    }
+
+    let disposition = part.header("content-disposition").map(parse_header_params);
+    let file_name = disposition
+        .as_ref()
+        .and_then(|(_, p)| p.iter().find(|(k, _)| k == "filename").map(|(_, v)| v.clone()))
+        .or_else(|| param("name"))
+        .map(|n| decode_rfc2047(&n));
+    let is_attachment = disposition.as_ref().is_some_and(|(d, _)| d.eq_ignore_ascii_case("attachment"));
+
+    if !is_attachment && (mime == "text/plain" || mime == "text/html") {
+        let charset = param("charset").unwrap_or_else(|| "utf-8".to_string());
+        let text = decode_charset(&part.decoded_body(), &charset);
+        let slot = if mime == "text/html" { &mut email.html } else { &mut email.text };
+        // multipart/alternative 中同类型只取第一个
+        if slot.is_none() {
+            *slot = Some(text);
+        }
+        return;
+    }
+
+    if mime == "message/rfc822" {
+        collect_parts(&split_part(part.body), email, depth + 1);
+        return;
+    }
+
+    let data = part.decoded_body();
+    if data.is_empty() {
+        return;
+    }
+    let file_name = file_name.unwrap_or_else(|| format!("attachment-{}", email.attachments.len() + 1));
+    email.attachments.push(MailAttachment { file_name, content_type: mime, data });
+}
+
+fn split_part(raw: &[u8]) -> Part<'_> {
+    let (head, body) = match find_bytes(raw, b"\r\n\r\n") {
+        Some(pos) => (&raw[..pos], &raw[pos + 4..]),
+        None => match find_bytes(raw, b"\n\n") {
+            Some(pos) => (&raw[..pos], &raw[pos + 2..]),
+            None => (raw, &raw[raw.len()..]),
+        },
+    };
+
+    // 续行（以空白开头）拼接到上一个头部
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in String::from_utf8_lossy(head).lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    Part { headers, body }
+}
+
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{}", boundary);
+    let delimiter = delimiter.as_bytes();
+    let mut parts = Vec::new();
+    let mut rest = body;
+    let mut started = false;
+
+    while let Some(pos) = find_bytes(rest, delimiter) {
+        if started {
+            parts.push(trim_crlf_end(&rest[..pos]));
+        }
+        rest = &rest[pos + delimiter.len()..];
+        if rest.starts_with(b"--") {
+            return parts;
+        }
+        // 跳过分隔行剩余部分
+        rest = match rest.iter().position(|&b| b == b'\n') {
+            Some(nl) => &rest[nl + 1..],
+            None => return parts,
+        };
+        started = true;
+    }
+    parts
+}
+
+fn trim_crlf_end(s: &[u8]) -> &[u8] {
+    let s = s.strip_suffix(b"\n").unwrap_or(s);
+    s.strip_suffix(b"\r").unwrap_or(s)
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// 解析 "value; key=val; key2=\"val2\""，支持 RFC 2231 的 key*=charset''编码值
+fn parse_header_params(header: &str) -> (String, Vec<(String, String)>) {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in header.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => segments.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    segments.push(current);
+
+    let value = segments.first().map(|s| s.trim().to_string()).unwrap_or_default();
+    let mut params: Vec<(String, String)> = Vec::new();
+    for seg in segments.iter().skip(1) {
+        let Some((key, val)) = seg.split_once('=') else { continue };
+        let key = key.trim().to_ascii_lowercase();
+        let val = val.trim().to_string();
+        match key.strip_suffix('*') {
+            Some(base) => {
+                // filename*=UTF-8''%E6%8A%A5%E5%91%8A.pdf
+                let (charset, encoded) = val.split_once("''").unwrap_or(("utf-8", &val));
+                let bytes = percent_decode(encoded);
+                // key* 优先于同名的 key
+                params.retain(|(k, _)| k != base);
+                params.push((base.to_string(), decode_charset(&bytes, charset)));
+            }
+            None => {
+                if !params.iter().any(|(k, _)| k == &key) {
+                    params.push((key, val));
+                }
+            }
+        }
+    }
+    (value, params)
+}
+
+fn percent_decode(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        // 按字节判断，避免在多字节字符中间切片
+        if bytes[i] == b'%' && i + 2 < bytes.len() && bytes[i + 1].is_ascii_hexdigit() && bytes[i + 2].is_ascii_hexdigit() {
+            let hex = |b: u8| (b as char).to_digit(16).unwrap_or(0) as u8;
+            out.push(hex(bytes[i + 1]) << 4 | hex(bytes[i + 2]));
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    out
+}
+
+fn decode_charset(data: &[u8], charset: &str) -> String {
+    let encoding = encoding_rs::Encoding::for_label(charset.trim().as_bytes()).unwrap_or(encoding_rs::UTF_8);
+    encoding.decode(data).0.into_owned()
+}
+
+/// 解码 RFC 2047 编码字（=?UTF-8?B?...?= / =?GBK?Q?...?=）
+pub fn decode_rfc2047(value: &str) -> String {
+    let re = regex::Regex::new(r"=\?([^?]+)\?([BbQq])\?([^?]*)\?=").unwrap();
+    // 相邻编码字之间的空白不显示
+    let gap = regex::Regex::new(r"\?=\s+=\?").unwrap();
+    let value = gap.replace_all(value, "?==?");
+
+    re.replace_all(&value, |c: &regex::Captures| {
+        let charset = c[1].split('*').next().unwrap_or("utf-8");
+        let bytes = if c[2].eq_ignore_ascii_case("b") {
+            STANDARD.decode(c[3].trim_end_matches('=')).or_else(|_| STANDARD.decode(&c[3])).ok()
+        } else {
+            let text = c[3].replace('_', " ");
+            quoted_printable::decode(text.as_bytes(), quoted_printable::ParseMode::Robust).ok()
+        };
+        match bytes {
+            Some(b) => decode_charset(&b, charset),
+            None => c[0].to_string(),
+        }
+    })
+    .into_owned()
+}
+
+// ============================================================
+// 导入为文档
+// ============================================================
+
+/// 邮件正文转为 Markdown（优先 HTML 版本）
+pub fn email_to_markdown(email: &ParsedEmail) -> String {
+    let body = match (&email.html, &email.text) {
+        (Some(html), _) => html_to_markdown(html),
+        (None, Some(text)) => text.replace("\r\n", "\n").trim().to_string(),
+        (None, None) => String::new(),
+    };
+
+    let mut header = vec![format!("**发件人：** {}", email.from)];
+    if !email.to.is_empty() {
+        header.push(format!("**收件人：** {}", email.to));
+    }
+    if let Some(date) = &email.date {
+        header.push(format!("**时间：** {}", date));
+    }
+    format!("{}\n\n---\n\n{}\n", header.join("  \n"), body)
+}
+
+/// 创建文档并将附件保存到项目的 attachments/{文档ID}/ 目录
+pub fn create_document(
+    project_dir: &Path,
+    project_id: &str,
+    email: &ParsedEmail,
+    doc_path: impl Fn(&str) -> PathBuf,
+) -> Result<Document, String> {
+    let title = if email.subject.is_empty() { "（无主题）".to_string() } else { email.subject.clone() };
+    let author = sender_name(&email.from);
+    let mut document = Document::new(project_id.to_string(), title, author);
+
+    let content = email_to_markdown(email);
+    document.metadata.word_count = content.split_whitespace().count();
+    document.metadata.character_count = content.chars().count();
+    document.metadata.tags.push("邮件".to_string());
+    document.content = content;
+
+    if !email.attachments.is_empty() {
+        let dir = project_dir.join("attachments").join(&document.id);
+        fs::create_dir_all(&dir).map_err(|e| format!("创建附件目录失败: {}", e))?;
+        for att in &email.attachments {
+            let path = unique_path(&dir, &sanitize_file_name(&att.file_name));
+            fs::write(&path, &att.data).map_err(|e| format!("保存附件 {} 失败: {}", att.file_name, e))?;
+            document.attachments.push(Attachment {
+                id: uuid::Uuid::new_v4().to_string(),
+                file_name: att.file_name.clone(),
+                file_path: path.to_string_lossy().to_string(),
+                file_size: att.data.len() as u64,
+                file_type: att.content_type.clone(),
+                added_at: chrono::Utc::now().timestamp(),
+            });
+        }
+    }
+
+    document.save(&doc_path(&document.id)).map_err(|e| e.to_string())?;
+    Ok(document)
+}
+
+/// "张三 <zhang@example.com>" → "张三"
+fn sender_name(from: &str) -> String {
+    match from.split_once('<') {
+        Some((name, _)) if !name.trim().is_empty() => name.trim().trim_matches('"').to_string(),
+        Some((_, addr)) => addr.trim_end_matches('>').trim().to_string(),
+        None => from.trim().to_string(),
+    }
+}
+
+pub(crate) fn sanitize_file_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') || c.is_control() { '_' } else { c })
+        .collect();
+    let cleaned = cleaned.trim().trim_start_matches('.').to_string();
+    if cleaned.is_empty() { "attachment".to_string() } else { cleaned }
+}
+
+pub(crate) fn unique_path(dir: &Path, file_name: &str) -> PathBuf {
+    let path = dir.join(file_name);
+    if !path.exists() {
+        return path;
+    }
+    let (stem, ext) = match file_name.rsplit_once('.') {
+        Some((s, e)) => (s.to_string(), format!(".{}", e)),
+        None => (file_name.to_string(), String::new()),
+    };
+    (2..)
+        .map(|i| dir.join(format!("{}-{}{}", stem, i, ext)))
+        .find(|p| !p.exists())
+        .unwrap_or(path)
+}
+
+// ============================================================
+// 已导入记录（按 项目 + Message-ID 去重）
+// ============================================================
+
+fn load_imported(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn is_imported(path: &Path, project_id: &str, message_id: &str) -> bool {
+    let key = format!("{}:{}", project_id, message_id);
+    load_imported(path).contains(&key)
+}
+
+pub fn mark_imported(path: &Path, project_id: &str, message_id: &str) -> Result<(), String> {
+    let mut imported = load_imported(path);
+    imported.push(format!("{}:{}", project_id, message_id));
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let json = serde_json::to_string(&imported).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("Failed to write imported emails: {}", e))
+}
@@ -0,0 +1,207 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// 等待用户在浏览器中完成授权的最长时间
+const AUTHORIZE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// access token 提前刷新的余量（秒）
+const REFRESH_MARGIN_SECS: i64 = 60;
+
+struct OAuthProvider {
+    auth_url: &'static str,
+    token_url: &'static str,
+    scope: &'static str,
+}
+
+fn provider_config(provider: &str) -> Result<OAuthProvider, String> {
+    match provider {
+        "google" => Ok(OAuthProvider {
+            auth_url: "https://accounts.google.com/o/oauth2/v2/auth",
+            token_url: "https://oauth2.googleapis.com/token",
+            scope: "https://mail.google.com/",
+        }),
+        "microsoft" => Ok(OAuthProvider {
+            auth_url: "https://login.microsoftonline.com/common/oauth2/v2.0/authorize",
+            token_url: "https://login.microsoftonline.com/common/oauth2/v2.0/token",
+            scope: "https://outlook.office.com/SMTP.Send https://outlook.office.com/IMAP.AccessAsUser.All offline_access",
+        }),
+        _ => Err(format!("不支持的 OAuth2 服务商: {}", provider)),
+    }
+}
+
+/// 保存在钥匙串中的 OAuth2 令牌
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthTokens {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    pub expires_at: i64,
+    /// 桌面应用的 client_secret（Google 需要，Microsoft 公共客户端为空）
+    #[serde(default)]
+    pub client_secret: Option<String>,
+}
+
+impl OAuthTokens {
+    pub fn is_expired(&self) -> bool {
+        chrono::Utc::now().timestamp() + REFRESH_MARGIN_SECS >= self.expires_at
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<i64>,
+}
+
+/// 回环重定向授权流程（RFC 8252 + PKCE）：打开浏览器，等待 127.0.0.1 回调后换取令牌
+pub async fn authorize(
+    provider: &str,
+    client_id: &str,
+    client_secret: Option<String>,
+) -> Result<OAuthTokens, String> {
+    let config = provider_config(provider)?;
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .map_err(|e| format!("启动本地回调监听失败: {}", e))?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let redirect_uri = format!("http://127.0.0.1:{}/callback", port);
+
+    let verifier = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+    let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+    let state = uuid::Uuid::new_v4().simple().to_string();
+
+    let auth_url = reqwest::Url::parse_with_params(config.auth_url, &[
+        ("response_type", "code"),
+        ("client_id", client_id),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("scope", config.scope),
+        ("state", state.as_str()),
+        ("code_challenge", challenge.as_str()),
+        ("code_challenge_method", "S256"),
+        ("access_type", "offline"),
+        ("prompt", "consent"),
+    ])
+    .map_err(|e| format!("构建授权地址失败: {}", e))?;
+
+    open::that(auth_url.as_str()).map_err(|e| format!("无法打开浏览器: {}", e))?;
+
+    let code = tokio::time::timeout(AUTHORIZE_TIMEOUT, wait_for_callback(&listener, &state))
+        .await
+        .map_err(|_| "等待授权超时，请重试".to_string())??;
+
+    let mut form = vec![
+        ("grant_type", "authorization_code".to_string()),
+        ("code", code),
+        ("redirect_uri", redirect_uri),
+        ("client_id", client_id.to_string()),
+        ("code_verifier", verifier),
+    ];
+    if let Some(secret) = client_secret.as_ref().filter(|s| !s.is_empty()) {
+        form.push(("client_secret", secret.clone()));
+    }
+
+    let resp = request_token(config.token_url, &form).await?;
+    Ok(OAuthTokens {
+        access_token: resp.access_token,
+        refresh_token: resp.refresh_token,
+        expires_at: chrono::Utc::now().timestamp() + resp.expires_in.unwrap_or(3600),
+        client_secret,
+    })
+}
+
+/// 使用 refresh token 刷新 access token
+pub async fn refresh(provider: &str, client_id: &str, tokens: &OAuthTokens) -> Result<OAuthTokens, String> {
+    let config = provider_config(provider)?;
+    let refresh_token = tokens
+        .refresh_token
+        .clone()
+        .ok_or("缺少 refresh token，请重新授权")?;
+
+    let mut form = vec![
+        ("grant_type", "refresh_token".to_string()),
+        ("refresh_token", refresh_token.clone()),
+        ("client_id", client_id.to_string()),
+    ];
+    if let Some(secret) = tokens.client_secret.as_ref().filter(|s| !s.is_empty()) {
+        form.push(("client_secret", secret.clone()));
+    }
+
+    let resp = request_token(config.token_url, &form).await?;
+    Ok(OAuthTokens {
+        access_token: resp.access_token,
+        // 部分服务商刷新时不返回新的 refresh token，沿用旧值
+        refresh_token: resp.refresh_token.or(Some(refresh_token)),
+        expires_at: chrono::Utc::now().timestamp() + resp.expires_in.unwrap_or(3600),
+        client_secret: tokens.client_secret.clone(),
+    })
+}
+
+async fn request_token(token_url: &str, form: &[(&str, String)]) -> Result<TokenResponse, String> {
+    let resp = reqwest::Client::new()
+        .post(token_url)
+        .form(form)
+        .timeout(Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| format!("请求 OAuth2 令牌失败: {}", e))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let err = resp.text().await.unwrap_or_default();
+        return Err(format!("OAuth2 令牌请求被拒绝 ({}): {}", status, err));
+    }
+
+    resp.json().await.map_err(|e| format!("解析 OAuth2 令牌失败: {}", e))
+}
+
+/// 等待浏览器回调，返回授权码
+async fn wait_for_callback(listener: &TcpListener, expected_state: &str) -> Result<String, String> {
+    loop {
+        let (mut stream, _) = listener.accept().await.map_err(|e| e.to_string())?;
+        let mut buf = vec![0u8; 8192];
+        let Ok(n) = stream.read(&mut buf).await else { continue };
+        let request = String::from_utf8_lossy(&buf[..n]);
+
+        // 请求行：GET /callback?code=...&state=... HTTP/1.1
+        let Some(path) = request.lines().next().and_then(|l| l.split_whitespace().nth(1)) else {
+            continue;
+        };
+        if !path.starts_with("/callback") {
+            // 浏览器可能顺带请求 /favicon.ico
+            let _ = stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n").await;
+            continue;
+        }
+
+        let Ok(url) = reqwest::Url::parse(&format!("http://127.0.0.1{}", path)) else { continue };
+        let param = |key: &str| url.query_pairs().find(|(k, _)| k == key).map(|(_, v)| v.to_string());
+
+        // state 不匹配的请求（残留页面、其他来源的请求）不属于本次授权，忽略后继续等待，直到超时
+        if param("state").as_deref() != Some(expected_state) {
+            let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await;
+            continue;
+        }
+        let (ok, message) = match (param("code"), param("error")) {
+            (_, Some(err)) => (Err(format!("授权被拒绝: {}", err)), "授权失败，请返回 AiDocPlus 查看详情。"),
+            (Some(code), None) => (Ok(code), "授权成功，可以关闭此页面并返回 AiDocPlus。"),
+            _ => (Err("授权回调参数无效".to_string()), "授权失败，请返回 AiDocPlus 重试。"),
+        };
+
+        let body = format!("<!DOCTYPE html><html><head><meta charset=\"utf-8\"></head><body><p>{}</p></body></html>", message);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+        return ok;
+    }
+}
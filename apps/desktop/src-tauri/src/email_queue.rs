@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+/// 发件记录最多保留条数（超出时删除最旧的已结束记录）
+const MAX_RECORDS: usize = 500;
+
+/// 已结束（不会再发送）的状态，超出条数上限时可以删除
+const TERMINAL_STATUSES: &[&str] = &["sent", "failed", "cancelled"];
+
+/// 发件箱记录：既是待发送队列，也是发送历史
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailRecord {
+    pub id: String,
+    /// 关联的已保存账户；为空表示调用方直接传入 SMTP 参数，无法在重启后重试
+    #[serde(default)]
+    pub account_id: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+    #[serde(default)]
+    pub cc: Vec<String>,
+    #[serde(default)]
+    pub bcc: Vec<String>,
+    pub subject: String,
+    pub body: String,
+    #[serde(default)]
+    pub is_html: bool,
+    #[serde(default)]
+    pub is_raw_html: bool,
+    /// queued / scheduled / sending / sent / failed / cancelled
+    pub status: String,
+    /// 定时发送时间（Unix 秒），为空表示立即发送
+    #[serde(default)]
+    pub send_at: Option<i64>,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    #[serde(default)]
+    pub sent_at: Option<i64>,
+}
+
+/// 发件箱文件读写锁，避免并发发送时互相覆盖
+static OUTBOX_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+
+fn with_outbox<R>(path: &Path, f: impl FnOnce(&mut Vec<EmailRecord>) -> R) -> Result<R, String> {
+    let _guard = OUTBOX_LOCK
+        .get_or_init(|| Mutex::new(()))
+        .lock()
+        .map_err(|e| format!("锁获取失败: {}", e))?;
+
+    let mut records = match read_records(path) {
+        Ok(records) => records,
+        // 文件损坏时移到一旁保留，避免写回空列表覆盖全部发送历史和待发邮件
+        Err(e) => {
+            let backup = path.with_extension(format!("corrupt-{}.json", chrono::Utc::now().timestamp()));
+            fs::rename(path, &backup).map_err(|re| format!("{}；备份损坏的发件箱失败: {}", e, re))?;
+            tracing::warn!("{}，已将原文件移到 {:?}", e, backup);
+            Vec::new()
+        }
+    };
+    let result = f(&mut records);
+
+    if records.len() > MAX_RECORDS {
+        let mut excess = records.len() - MAX_RECORDS;
+        records.retain(|r| {
+            if excess > 0 && TERMINAL_STATUSES.contains(&r.status.as_str()) {
+                excess -= 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(&records)
+        .map_err(|e| format!("Failed to serialize outbox: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write outbox: {}", e))?;
+    Ok(result)
+}
+
+/// 读取发件箱；文件不存在时为空，读取或解析失败时返回错误
+fn read_records(path: &Path) -> Result<Vec<EmailRecord>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let json = fs::read_to_string(path).map_err(|e| format!("读取发件箱失败: {}", e))?;
+    serde_json::from_str(&json).map_err(|e| format!("解析发件箱失败: {}", e))
+}
+
+/// 只读查询使用，失败时按空列表处理
+fn load_records(path: &Path) -> Vec<EmailRecord> {
+    read_records(path).unwrap_or_else(|e| {
+        tracing::warn!("{}", e);
+        Vec::new()
+    })
+}
+
+/// 加入发件队列（按时间顺序追加）；设置了 send_at 的记录进入定时状态
+pub fn enqueue(path: &Path, mut record: EmailRecord) -> Result<EmailRecord, String> {
+    record.id = uuid::Uuid::new_v4().to_string();
+    record.status = if record.send_at.is_some() { "scheduled" } else { "queued" }.to_string();
+    record.attempts = 0;
+    record.created_at = chrono::Utc::now().timestamp();
+    let saved = record.clone();
+    with_outbox(path, |records| records.push(record))?;
+    Ok(saved)
+}
+
+pub fn get(path: &Path, id: &str) -> Option<EmailRecord> {
+    load_records(path).into_iter().find(|r| r.id == id)
+}
+
+/// 更新记录状态
+pub fn update_status(path: &Path, id: &str, status: &str, attempts: u32, error: Option<String>) -> Result<(), String> {
+    with_outbox(path, |records| {
+        if let Some(r) = records.iter_mut().find(|r| r.id == id) {
+            r.status = status.to_string();
+            r.attempts = attempts;
+            r.last_error = error;
+            if status == "sent" {
+                r.sent_at = Some(chrono::Utc::now().timestamp());
+            }
+        }
+    })
+}
+
+/// 认领一条记录开始发送：状态仍为 from_status 时改为 sending 并返回 true；
+/// 已被取消、删除或由其他任务发送的记录返回 false，调用方应跳过
+pub fn claim(path: &Path, id: &str, from_status: &str) -> Result<bool, String> {
+    with_outbox(path, |records| match records.iter_mut().find(|r| r.id == id) {
+        Some(r) if r.status == from_status => {
+            r.status = "sending".to_string();
+            true
+        }
+        _ => false,
+    })
+}
+
+/// 列出发件记录（最新在前），可按状态过滤
+pub fn list(path: &Path, status: Option<&str>, limit: Option<usize>) -> Vec<EmailRecord> {
+    load_records(path)
+        .into_iter()
+        .rev()
+        .filter(|r| status.is_none_or(|s| r.status == s))
+        .take(limit.unwrap_or(MAX_RECORDS))
+        .collect()
+}
+
+/// 上次退出时未完成的记录（只有关联账户的记录可以恢复发送）
+pub fn pending(path: &Path) -> Vec<EmailRecord> {
+    load_records(path)
+        .into_iter()
+        .filter(|r| (r.status == "queued" || r.status == "sending") && r.account_id.is_some())
+        .collect()
+}
+
+/// 已到发送时间的定时邮件
+pub fn due_scheduled(path: &Path, now: i64) -> Vec<EmailRecord> {
+    load_records(path)
+        .into_iter()
+        .filter(|r| r.status == "scheduled" && r.send_at.is_some_and(|t| t <= now))
+        .collect()
+}
+
+/// 取消定时邮件（仅限尚未开始发送的记录）
+pub fn cancel_scheduled(path: &Path, id: &str) -> Result<(), String> {
+    with_outbox(path, |records| match records.iter_mut().find(|r| r.id == id) {
+        Some(r) if r.status == "scheduled" => {
+            r.status = "cancelled".to_string();
+            Ok(())
+        }
+        Some(r) => Err(format!("邮件当前状态为 {}，无法取消", r.status)),
+        None => Err(format!("发件记录不存在: {}", id)),
+    })?
+}
+
+/// 未关联账户的未完成记录无法恢复（密码未保存），标记为失败
+pub fn fail_orphaned(path: &Path) -> Result<(), String> {
+    with_outbox(path, |records| {
+        for r in records.iter_mut().filter(|r| {
+            (r.status == "queued" || r.status == "sending") && r.account_id.is_none()
+        }) {
+            r.status = "failed".to_string();
+            r.last_error = Some("应用退出前未完成发送".to_string());
+        }
+    })
+}
+
+pub fn delete(path: &Path, id: &str) -> Result<(), String> {
+    with_outbox(path, |records| records.retain(|r| r.id != id))
+}
@@ -24,26 +24,58 @@ pub enum AppError {
     #[error("Version not found: {0}")]
     VersionNotFound(String),
 
+    #[error("Document is locked: {0}")]
+    DocumentLocked(String),
+
     #[error("AI error: {0}")]
     AIError(String),
 }
 
+impl AppError {
+    /// 按当前界面语言生成的错误信息（返回给前端时使用）
+    pub fn localized(&self) -> String {
+        use crate::i18n::tf;
+        match self {
+            AppError::Io(e) => tf("error.io", &[e]),
+            AppError::Serde(e) => tf("error.serde", &[e]),
+            AppError::ProjectNotFound(s) => tf("error.project_not_found", &[s]),
+            AppError::DocumentNotFound(s) => tf("error.document_not_found", &[s]),
+            AppError::InvalidData(s) => tf("error.invalid_data", &[s]),
+            AppError::ExportFailed(s) => tf("error.export_failed", &[s]),
+            AppError::VersionNotFound(s) => tf("error.version_not_found", &[s]),
+            AppError::DocumentLocked(s) => tf("error.document_locked", &[s]),
+            AppError::AIError(s) => tf("error.ai", &[s]),
+        }
+    }
+}
+
 impl Serialize for AppError {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: serde::ser::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        serializer.serialize_str(&self.localized())
     }
 }
 
 // Implement for frontend error handling
 pub type Result<T> = std::result::Result<T, String>;
 
+/// 在阻塞线程池中执行文件读写等耗时操作，避免占用 IPC 命令线程导致界面卡顿
+pub async fn run_blocking<T, F>(f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("后台任务执行失败: {}", e))?
+}
+
 #[allow(dead_code)]
 pub fn to_result<T, F>(f: F) -> Result<T>
 where
     F: FnOnce() -> std::result::Result<T, AppError>,
 {
-    f().map_err(|e| e.to_string())
+    f().map_err(|e| e.localized())
 }
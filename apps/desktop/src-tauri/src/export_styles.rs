@@ -0,0 +1,121 @@
+// 导出样式方案：内置方案（公文、学术论文、商务、简洁）+ 用户自定义方案
+// 用户方案以 export-style 资源保存在 export-styles/local/{id}/manifest.json，由资源引擎索引，导出时按 ID 读取
+
+use crate::native_export::styles::{self, StyleProfile};
+use crate::resource_engine::{ResourceEngineState, ResourceSummary};
+use std::fs;
+use std::path::PathBuf;
+
+pub const RESOURCE_TYPE: &str = "export-style";
+/// 资源目录名（data_root 下）
+pub const DIR_NAME: &str = "export-styles";
+
+fn is_builtin(id: &str) -> bool {
+    styles::builtin_profiles().iter().any(|p| p.id == id)
+}
+
+/// 全部方案：内置方案在前，其后为用户方案
+pub fn list(state: &ResourceEngineState) -> Vec<StyleProfile> {
+    let mut profiles = styles::builtin_profiles();
+    match state.with_engine(|engine| engine.list_manifests(RESOURCE_TYPE)) {
+        Ok(rows) => profiles.extend(
+            rows.iter()
+                .filter_map(|json| match serde_json::from_str::<StyleProfile>(json) {
+                    Ok(profile) => Some(profile),
+                    Err(e) => {
+                        tracing::warn!("解析导出样式失败: {}", e);
+                        None
+                    }
+                })
+                .filter(|p| !is_builtin(&p.id)),
+        ),
+        Err(e) => tracing::warn!("读取自定义导出样式失败: {}", e),
+    }
+    profiles
+}
+
+/// 按 ID 查找方案（内置或用户方案）；为空时使用默认方案
+pub fn find(state: &ResourceEngineState, id: &str) -> Result<StyleProfile, String> {
+    styles::find_profile(&list(state), id)
+}
+
+fn style_dir(state: &ResourceEngineState, id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || id.len() > 64 || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("无效的样式 ID: {}", id));
+    }
+    state.with_engine(|engine| Ok(engine.data_root().join(DIR_NAME).join("local").join(id)))
+}
+
+/// 保存用户方案：ID 为空时新建；内置方案不能修改，需另存为新方案
+pub fn save(state: &ResourceEngineState, mut profile: StyleProfile) -> Result<StyleProfile, String> {
+    profile.name = profile.name.trim().to_string();
+    profile.validate()?;
+    profile.id = profile.id.trim().to_string();
+    if profile.id.is_empty() {
+        profile.id = format!("style-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+    }
+    if is_builtin(&profile.id) {
+        return Err(format!("不能修改内置样式 {}，请另存为新样式", profile.name));
+    }
+    profile.source = "local".to_string();
+
+    let dir = style_dir(state, &profile.id)?;
+    let manifest_path = dir.join("manifest.json");
+    let now = chrono::Utc::now().to_rfc3339();
+    // 保留原创建时间
+    let created_at = fs::read_to_string(&manifest_path)
+        .ok()
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+        .and_then(|v| v.get("createdAt").and_then(|c| c.as_str()).map(str::to_string))
+        .unwrap_or_else(|| now.clone());
+
+    let mut manifest = serde_json::to_value(&profile).map_err(|e| e.to_string())?;
+    manifest["resourceType"] = RESOURCE_TYPE.into();
+    manifest["version"] = "1.0.0".into();
+    manifest["createdAt"] = created_at.clone().into();
+    manifest["updatedAt"] = now.clone().into();
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| format!("创建样式目录失败: {}", e))?;
+    fs::write(&manifest_path, &json).map_err(|e| format!("保存导出样式失败: {}", e))?;
+
+    let resource = ResourceSummary {
+        id: profile.id.clone(),
+        package_name: None,
+        resource_type: RESOURCE_TYPE.to_string(),
+        name: profile.name.clone(),
+        description: profile.description.clone(),
+        icon: String::new(),
+        author: String::new(),
+        version: "1.0.0".to_string(),
+        major_category: String::new(),
+        sub_category: String::new(),
+        tags: Vec::new(),
+        order: 0,
+        enabled: true,
+        source: "local".to_string(),
+        created_at,
+        updated_at: now,
+        data_path: dir.to_string_lossy().to_string(),
+    };
+    state.with_engine(|engine| {
+        engine.upsert(&resource, None, None, &json)?;
+        engine.rebuild_fts()
+    })?;
+    Ok(profile)
+}
+
+/// 删除用户方案
+pub fn delete(state: &ResourceEngineState, id: &str) -> Result<(), String> {
+    if is_builtin(id) {
+        return Err("不能删除内置样式".to_string());
+    }
+    let dir = style_dir(state, id)?;
+    if !dir.join("manifest.json").exists() {
+        return Err(format!("导出样式不存在: {}", id));
+    }
+    fs::remove_dir_all(&dir).map_err(|e| format!("删除导出样式失败: {}", e))?;
+    state.with_engine(|engine| {
+        engine.delete(id)?;
+        engine.rebuild_fts()
+    })
+}
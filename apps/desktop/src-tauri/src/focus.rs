@@ -0,0 +1,161 @@
+// 专注时段（番茄钟 / 字数冲刺）
+// 同一时间只有一个进行中的时段，保存在内存；结束后写入历史文件
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+/// 历史记录最多保留条数
+const MAX_HISTORY: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FocusKind {
+    /// 番茄钟：按时长计
+    Pomodoro,
+    /// 字数冲刺：按目标字数计
+    Sprint,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusSession {
+    pub id: String,
+    pub project_id: String,
+    pub document_id: String,
+    pub kind: FocusKind,
+    pub planned_minutes: Option<u32>,
+    pub word_target: Option<usize>,
+    pub started_at: i64,
+    #[serde(default)]
+    pub ended_at: Option<i64>,
+    /// 开始时文档字数
+    pub start_words: usize,
+    /// 时段内各次保存新增字数之和
+    #[serde(default)]
+    pub words_added: usize,
+    /// 结束时相对开始的净变化
+    #[serde(default)]
+    pub net_words: i64,
+    /// 是否达到计划时长或目标字数
+    #[serde(default)]
+    pub completed: bool,
+}
+
+static ACTIVE: Mutex<Option<FocusSession>> = Mutex::new(None);
+
+/// 历史文件的读写锁
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+impl FocusSession {
+    fn is_completed(&self, now: i64) -> bool {
+        let time_done = self.planned_minutes.is_some_and(|m| now - self.started_at >= i64::from(m) * 60);
+        let words_done = self.word_target.is_some_and(|t| t > 0 && self.words_added >= t);
+        match self.kind {
+            FocusKind::Pomodoro => time_done,
+            FocusKind::Sprint => words_done || (self.word_target.is_none() && time_done),
+        }
+    }
+}
+
+pub fn active() -> Option<FocusSession> {
+    ACTIVE.lock().ok().and_then(|a| a.clone())
+}
+
+pub fn start(
+    project_id: &str,
+    document_id: &str,
+    kind: FocusKind,
+    planned_minutes: Option<u32>,
+    word_target: Option<usize>,
+    start_words: usize,
+) -> Result<FocusSession, String> {
+    if planned_minutes.is_none_or(|m| m == 0) && word_target.is_none_or(|t| t == 0) {
+        return Err("请设置专注时长或目标字数".to_string());
+    }
+    let mut active = ACTIVE.lock().map_err(|e| e.to_string())?;
+    if active.is_some() {
+        return Err("已有进行中的专注时段，请先结束".to_string());
+    }
+    let session = FocusSession {
+        id: uuid::Uuid::new_v4().to_string(),
+        project_id: project_id.to_string(),
+        document_id: document_id.to_string(),
+        kind,
+        planned_minutes: planned_minutes.filter(|m| *m > 0),
+        word_target: word_target.filter(|t| *t > 0),
+        started_at: chrono::Utc::now().timestamp(),
+        ended_at: None,
+        start_words,
+        words_added: 0,
+        net_words: 0,
+        completed: false,
+    };
+    *active = Some(session.clone());
+    Ok(session)
+}
+
+/// 结束进行中的时段并写入历史；end_words 为文档当前字数
+pub fn stop(history_path: &Path, end_words: Option<usize>) -> Result<FocusSession, String> {
+    let mut session = ACTIVE
+        .lock()
+        .map_err(|e| e.to_string())?
+        .take()
+        .ok_or("没有进行中的专注时段")?;
+    let now = chrono::Utc::now().timestamp();
+    session.ended_at = Some(now);
+    if let Some(end) = end_words {
+        session.net_words = end as i64 - session.start_words as i64;
+    }
+    session.completed = session.is_completed(now);
+
+    let _guard = HISTORY_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut history = load_history(history_path);
+    history.push(session.clone());
+    if history.len() > MAX_HISTORY {
+        history.drain(..history.len() - MAX_HISTORY);
+    }
+    if let Some(parent) = history_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string(&history).map_err(|e| e.to_string())?;
+    fs::write(history_path, json).map_err(|e| format!("保存专注记录失败: {}", e))?;
+    Ok(session)
+}
+
+/// 文档保存后累计时段内新增字数，并通知前端进度
+pub fn on_save(app: &AppHandle, document_id: &str, added: usize) {
+    let updated = {
+        let Ok(mut active) = ACTIVE.lock() else { return };
+        match active.as_mut().filter(|s| s.document_id == document_id) {
+            Some(session) => {
+                session.words_added += added;
+                session.completed = session.is_completed(chrono::Utc::now().timestamp());
+                session.clone()
+            }
+            None => return,
+        }
+    };
+    let _ = app.emit("focus:progress", &updated);
+}
+
+fn load_history(path: &Path) -> Vec<FocusSession> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// 历史记录（最近的在前）
+pub fn history(path: &Path, project_id: Option<&str>, limit: usize) -> Vec<FocusSession> {
+    let _guard = HISTORY_LOCK.lock();
+    let mut sessions: Vec<FocusSession> = load_history(path)
+        .into_iter()
+        .filter(|s| project_id.is_none_or(|p| s.project_id == p))
+        .collect();
+    sessions.reverse();
+    sessions.truncate(limit);
+    sessions
+}
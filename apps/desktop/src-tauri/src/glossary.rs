@@ -0,0 +1,144 @@
+// 项目术语表：规范术语、定义、推荐译法和禁用写法
+// 保存在 Projects/{项目ID}/glossary.json；AI 调用时作为系统提示词的一部分，
+// 拼写检查时术语本身不报错，禁用写法作为问题报出并建议改为规范术语
+
+use crate::spellcheck::SpellingIssue;
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 项目术语表文件名
+pub const GLOSSARY_FILE: &str = "glossary.json";
+/// 注入系统提示词的最多条目数，避免占用过多上下文
+const MAX_PROMPT_ENTRIES: usize = 200;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GlossaryEntry {
+    pub id: String,
+    /// 规范术语
+    pub term: String,
+    pub definition: String,
+    /// 推荐译法（如英文对应词）
+    pub translation: String,
+    /// 禁用的写法，出现时应改为 term
+    pub forbidden: Vec<String>,
+    pub updated_at: i64,
+}
+
+pub fn glossary_path(project_dir: &Path) -> PathBuf {
+    project_dir.join(GLOSSARY_FILE)
+}
+
+pub fn load(project_dir: &Path) -> Vec<GlossaryEntry> {
+    fs::read_to_string(glossary_path(project_dir))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save(project_dir: &Path, entries: &[GlossaryEntry]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+    fs::write(glossary_path(project_dir), json).map_err(|e| format!("保存术语表失败: {}", e))
+}
+
+/// 新增或更新术语（id 为空时新增）；同名术语不能重复
+pub fn upsert(project_dir: &Path, mut entry: GlossaryEntry) -> Result<GlossaryEntry, String> {
+    entry.term = entry.term.trim().to_string();
+    if entry.term.is_empty() {
+        return Err("术语不能为空".to_string());
+    }
+    let mut forbidden: Vec<String> = Vec::new();
+    for variant in entry.forbidden.iter().map(|v| v.trim()).filter(|v| !v.is_empty() && *v != entry.term) {
+        if !forbidden.iter().any(|f| f == variant) {
+            forbidden.push(variant.to_string());
+        }
+    }
+    entry.forbidden = forbidden;
+    entry.updated_at = chrono::Utc::now().timestamp();
+
+    let mut entries = load(project_dir);
+    if entries.iter().any(|e| e.id != entry.id && e.term.to_lowercase() == entry.term.to_lowercase()) {
+        return Err(format!("术语已存在: {}", entry.term));
+    }
+    match entries.iter_mut().find(|e| !entry.id.is_empty() && e.id == entry.id) {
+        Some(existing) => *existing = entry.clone(),
+        None => {
+            entry.id = uuid::Uuid::new_v4().to_string();
+            entries.push(entry.clone());
+        }
+    }
+    entries.sort_by(|a, b| a.term.cmp(&b.term));
+    save(project_dir, &entries)?;
+    Ok(entry)
+}
+
+pub fn delete(project_dir: &Path, id: &str) -> Result<(), String> {
+    let mut entries = load(project_dir);
+    let before = entries.len();
+    entries.retain(|e| e.id != id);
+    if entries.len() == before {
+        return Err(format!("术语不存在: {}", id));
+    }
+    save(project_dir, &entries)
+}
+
+/// 系统提示词中的术语说明；术语表为空时返回 None
+pub fn prompt(entries: &[GlossaryEntry]) -> Option<String> {
+    if entries.is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = entries
+        .iter()
+        .take(MAX_PROMPT_ENTRIES)
+        .map(|e| {
+            let mut line = format!("- {}", e.term);
+            if !e.definition.trim().is_empty() {
+                line.push_str(&format!("：{}", e.definition.trim()));
+            }
+            if !e.translation.trim().is_empty() {
+                line.push_str(&format!("（译法：{}）", e.translation.trim()));
+            }
+            if !e.forbidden.is_empty() {
+                line.push_str(&format!("；不要写作：{}", e.forbidden.join("、")));
+            }
+            line
+        })
+        .collect();
+    Some(format!("请严格使用以下项目术语表中的规范术语：\n{}", lines.join("\n")))
+}
+
+/// 规范术语拆出的单词，拼写检查时视为正确
+pub fn known_words(entries: &[GlossaryEntry]) -> Vec<String> {
+    entries
+        .iter()
+        .flat_map(|e| [e.term.as_str(), e.translation.as_str()])
+        .flat_map(|t| t.split(|c: char| !c.is_alphanumeric() && c != '\'' && c != '-'))
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// 查找禁用写法（英文不区分大小写并按词边界匹配），位置为 UTF-16 偏移
+pub fn check(entries: &[GlossaryEntry], text: &str) -> Vec<SpellingIssue> {
+    let mut issues = Vec::new();
+    for entry in entries {
+        for variant in &entry.forbidden {
+            let escaped = regex::escape(variant);
+            let pattern = if variant.is_ascii() { format!(r"\b{}\b", escaped) } else { escaped };
+            let Ok(re) = RegexBuilder::new(&pattern).case_insensitive(true).build() else { continue };
+            for m in re.find_iter(text) {
+                let from = text[..m.start()].encode_utf16().count();
+                issues.push(SpellingIssue {
+                    from,
+                    to: from + m.as_str().encode_utf16().count(),
+                    word: m.as_str().to_string(),
+                    suggestions: vec![entry.term.clone()],
+                });
+            }
+        }
+    }
+    issues.sort_by_key(|i| i.from);
+    issues
+}
@@ -0,0 +1,77 @@
+// 每日写作目标：基于写作统计（activity）的当天新增字数计算进度
+
+use crate::activity::{Counters, DayStats};
+use crate::settings::{self, GoalSettings};
+use crate::{config, plugin_bus};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoalProgress {
+    /// 项目目标时为项目 ID，全局目标为空
+    pub project_id: Option<String>,
+    pub target: usize,
+    pub written: usize,
+    pub remaining: usize,
+    pub reached: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoalStatus {
+    pub date: String,
+    pub global: Option<GoalProgress>,
+    pub project: Option<GoalProgress>,
+}
+
+fn progress(project_id: Option<&str>, target: usize, counters: Option<&Counters>) -> Option<GoalProgress> {
+    if target == 0 {
+        return None;
+    }
+    let written = counters.map_or(0, |c| c.words_added);
+    Some(GoalProgress {
+        project_id: project_id.map(str::to_string),
+        target,
+        written,
+        remaining: target.saturating_sub(written),
+        reached: written >= target,
+    })
+}
+
+/// 当天的目标进度；project_id 为空时只返回全局目标
+pub fn status(goals: &GoalSettings, date: &str, day: &DayStats, project_id: Option<&str>) -> GoalStatus {
+    GoalStatus {
+        date: date.to_string(),
+        global: progress(None, goals.daily_words, Some(&day.total)),
+        project: project_id.and_then(|pid| {
+            progress(Some(pid), goals.projects.get(pid).copied().unwrap_or(0), day.projects.get(pid))
+        }),
+    }
+}
+
+/// 本次保存新增 added 字后刚好达成的目标
+fn newly_reached(goals: &GoalSettings, day: &DayStats, project_id: &str, added: usize) -> Vec<GoalProgress> {
+    let crossed = |p: &GoalProgress| p.reached && p.written.saturating_sub(added) < p.target;
+    [
+        progress(None, goals.daily_words, Some(&day.total)),
+        progress(Some(project_id), goals.projects.get(project_id).copied().unwrap_or(0), day.projects.get(project_id)),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(crossed)
+    .collect()
+}
+
+/// 保存后检查目标，刚达成时发出 goal:reached 事件
+pub fn notify_reached(app: &AppHandle, day: &DayStats, project_id: &str, added: usize) {
+    if added == 0 {
+        return;
+    }
+    let goals = settings::load(&config::get_settings_path(app)).goals;
+    for reached in newly_reached(&goals, day, project_id, added) {
+        tracing::info!("已达成每日写作目标: {} 字", reached.target);
+        let _ = app.emit("goal:reached", &reached);
+        plugin_bus::publish(app, "goal:reached", serde_json::json!(reached));
+    }
+}
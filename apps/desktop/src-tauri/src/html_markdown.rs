@@ -0,0 +1,431 @@
+// HTML → Markdown 转换（邮件正文、剪贴板 HTML 等轻量场景，不追求完整的 HTML 兼容性）
+
+const VOID_TAGS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track", "wbr",
+];
+
+/// 内容整体丢弃的标签
+const SKIP_TAGS: &[&str] = &["script", "style", "head", "title", "noscript", "template"];
+
+enum Node {
+    Text(String),
+    Element {
+        name: String,
+        attrs: Vec<(String, String)>,
+        children: Vec<Node>,
+    },
+}
+
+/// 解析过程中尚未闭合的元素：(标签名, 属性, 子节点)
+type OpenElement = (String, Vec<(String, String)>, Vec<Node>);
+
+impl Node {
+    fn attr(&self, key: &str) -> Option<&str> {
+        match self {
+            Node::Element { attrs, .. } => attrs.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str()),
+            Node::Text(_) => None,
+        }
+    }
+}
+
+pub fn html_to_markdown(html: &str) -> String {
+    let nodes = parse(html);
+    let mut out = String::new();
+    render_children(&nodes, &mut out, &mut Context::default());
+    normalize_blank_lines(&out)
+}
+
+// ============================================================
+// 解析
+// ============================================================
+
+fn parse(html: &str) -> Vec<Node> {
+    // 栈底为根节点，元素闭合时挂到父节点下
+    let mut stack: Vec<OpenElement> = vec![(String::new(), Vec::new(), Vec::new())];
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            push_text(&mut stack, rest);
+            break;
+        };
+        if lt > 0 {
+            push_text(&mut stack, &rest[..lt]);
+        }
+        rest = &rest[lt..];
+
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map_or("", |end| &after[end + 3..]);
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+            continue;
+        }
+
+        let Some(gt) = find_tag_end(rest) else {
+            push_text(&mut stack, rest);
+            break;
+        };
+        let tag = &rest[1..gt];
+        rest = &rest[gt + 1..];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            close_element(&mut stack, &name.trim().to_ascii_lowercase());
+            continue;
+        }
+
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let (name, attrs) = parse_tag(tag);
+        if name.is_empty() {
+            continue;
+        }
+
+        if SKIP_TAGS.contains(&name.as_str()) {
+            let close = format!("</{}", name);
+            rest = find_ignore_case(rest, &close)
+                .map_or("", |pos| rest[pos..].find('>').map_or("", |end| &rest[pos + end + 1..]));
+            continue;
+        }
+
+        if self_closing || VOID_TAGS.contains(&name.as_str()) {
+            if let Some(parent) = stack.last_mut() {
+                parent.2.push(Node::Element { name, attrs, children: Vec::new() });
+            }
+        } else {
+            // <p>、<li> 等允许省略结束标签，遇到同名新标签时先闭合
+            if matches!(name.as_str(), "p" | "li" | "tr" | "td" | "th")
+                && stack.last().is_some_and(|(n, _, _)| n == &name)
+            {
+                close_element(&mut stack, &name);
+            }
+            stack.push((name, attrs, Vec::new()));
+        }
+    }
+
+    while stack.len() > 1 {
+        let name = stack.last().map(|(n, _, _)| n.clone()).unwrap_or_default();
+        close_element(&mut stack, &name);
+    }
+    stack.pop().map(|(_, _, children)| children).unwrap_or_default()
+}
+
+/// 查找标签结束的 '>'，跳过引号内的内容
+fn find_tag_end(s: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (i, c) in s.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack.to_ascii_lowercase().find(&needle.to_ascii_lowercase())
+}
+
+fn parse_tag(tag: &str) -> (String, Vec<(String, String)>) {
+    let tag = tag.trim();
+    let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+    let name = tag[..name_end].to_ascii_lowercase();
+    let attr_re = regex::Regex::new(r#"([a-zA-Z_:][-a-zA-Z0-9_:.]*)(?:\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+)))?"#).unwrap();
+    let attrs = attr_re
+        .captures_iter(&tag[name_end..])
+        .map(|c| {
+            let value = c.get(2).or(c.get(3)).or(c.get(4)).map_or("", |m| m.as_str());
+            (c[1].to_ascii_lowercase(), decode_entities(value))
+        })
+        .collect();
+    (name, attrs)
+}
+
+fn push_text(stack: &mut [OpenElement], text: &str) {
+    if let Some(parent) = stack.last_mut() {
+        parent.2.push(Node::Text(decode_entities(text)));
+    }
+}
+
+fn close_element(stack: &mut Vec<OpenElement>, name: &str) {
+    // 找不到对应开始标签的结束标签直接忽略
+    let Some(pos) = stack.iter().rposition(|(n, _, _)| n == name) else { return };
+    if pos == 0 {
+        return;
+    }
+    while stack.len() > pos {
+        let (name, attrs, children) = stack.pop().unwrap();
+        if let Some(parent) = stack.last_mut() {
+            parent.2.push(Node::Element { name, attrs, children });
+        }
+    }
+}
+
+pub fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    let re = regex::Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap();
+    re.replace_all(s, |c: &regex::Captures| {
+        let e = &c[1];
+        let decoded = if let Some(hex) = e.strip_prefix("#x").or_else(|| e.strip_prefix("#X")) {
+            u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+        } else if let Some(dec) = e.strip_prefix('#') {
+            dec.parse().ok().and_then(char::from_u32)
+        } else {
+            match e {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                "mdash" => Some('—'),
+                "ndash" => Some('–'),
+                "hellip" => Some('…'),
+                "ldquo" => Some('“'),
+                "rdquo" => Some('”'),
+                "lsquo" => Some('‘'),
+                "rsquo" => Some('’'),
+                "copy" => Some('©'),
+                _ => None,
+            }
+        };
+        decoded.map_or_else(|| c[0].to_string(), |ch| ch.to_string())
+    })
+    .into_owned()
+}
+
+// ============================================================
+// 渲染
+// ============================================================
+
+#[derive(Default)]
+struct Context {
+    in_pre: bool,
+    list_depth: usize,
+}
+
+fn render_children(nodes: &[Node], out: &mut String, ctx: &mut Context) {
+    for node in nodes {
+        render_node(node, out, ctx);
+    }
+}
+
+fn render_inner(nodes: &[Node], ctx: &mut Context) -> String {
+    let mut s = String::new();
+    render_children(nodes, &mut s, ctx);
+    s
+}
+
+fn render_node(node: &Node, out: &mut String, ctx: &mut Context) {
+    let (name, children) = match node {
+        Node::Text(text) => {
+            if ctx.in_pre {
+                out.push_str(text);
+            } else {
+                push_collapsed(out, text);
+            }
+            return;
+        }
+        Node::Element { name, children, .. } => (name.as_str(), children),
+    };
+
+    match name {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let level = name[1..].parse::<usize>().unwrap_or(1);
+            let text = render_inner(children, ctx);
+            block(out, &format!("{} {}", "#".repeat(level), text.trim()));
+        }
+        // Word 列表段落：项目符号在 mso-list:Ignore 的 span 中，改为 Markdown 列表
+        "p" if node.attr("class").is_some_and(|c| c.starts_with("MsoListParagraph")) => {
+            let text = render_inner(children, ctx);
+            block(out, &format!("- {}", text.trim()));
+        }
+        "span" if node.attr("style").is_some_and(|s| s.replace(' ', "").contains("mso-list:Ignore")) => {}
+        "p" | "div" | "section" | "article" | "header" | "footer" | "main" | "center" => {
+            let text = render_inner(children, ctx);
+            block(out, text.trim());
+        }
+        "br" => out.push_str("  \n"),
+        "hr" => block(out, "---"),
+        "strong" | "b" => wrap_inline(out, &render_inner(children, ctx), "**"),
+        "em" | "i" => wrap_inline(out, &render_inner(children, ctx), "*"),
+        "del" | "s" | "strike" => wrap_inline(out, &render_inner(children, ctx), "~~"),
+        "code" if !ctx.in_pre => wrap_inline(out, &render_inner(children, ctx), "`"),
+        "pre" => {
+            ctx.in_pre = true;
+            let code = render_inner(children, ctx);
+            ctx.in_pre = false;
+            block(out, &format!("```\n{}\n```", code.trim_end_matches('\n')));
+        }
+        "a" => {
+            let text = render_inner(children, ctx);
+            match node.attr("href").filter(|h| !h.is_empty() && !h.starts_with("javascript:")) {
+                Some(href) if !text.trim().is_empty() => out.push_str(&format!("[{}]({})", text.trim(), href)),
+                _ => out.push_str(&text),
+            }
+        }
+        "img" => {
+            // 公众号文章的图片地址在 data-src 中，src 为占位图
+            let src = node
+                .attr("data-src")
+                .filter(|s| !s.is_empty())
+                .or_else(|| node.attr("src").filter(|s| !s.is_empty()));
+            if let Some(src) = src {
+                out.push_str(&format!("![{}]({})", node.attr("alt").unwrap_or(""), src));
+            }
+        }
+        "ul" | "ol" => {
+            ctx.list_depth += 1;
+            let mut items = Vec::new();
+            let mut index = 1;
+            for child in children {
+                if let Node::Element { name, children: li_children, .. } = child {
+                    if name == "li" {
+                        let marker = if node_name(node) == "ol" { format!("{}.", index) } else { "-".to_string() };
+                        index += 1;
+                        let text = render_inner(li_children, ctx);
+                        let text = normalize_blank_lines(&text);
+                        let indent = "  ".repeat(ctx.list_depth - 1);
+                        let mut lines = text.trim().lines();
+                        let mut item = format!("{}{} {}", indent, marker, lines.next().unwrap_or(""));
+                        for line in lines {
+                            item.push('\n');
+                            item.push_str(line);
+                        }
+                        items.push(item);
+                    }
+                }
+            }
+            ctx.list_depth -= 1;
+            if ctx.list_depth > 0 {
+                out.push('\n');
+                out.push_str(&items.join("\n"));
+            } else {
+                block(out, &items.join("\n"));
+            }
+        }
+        "blockquote" => {
+            let text = normalize_blank_lines(&render_inner(children, ctx));
+            let quoted: Vec<String> = text
+                .trim()
+                .lines()
+                .map(|l| if l.is_empty() { ">".to_string() } else { format!("> {}", l) })
+                .collect();
+            block(out, &quoted.join("\n"));
+        }
+        "table" => block(out, &render_table(children, ctx)),
+        _ => render_children(children, out, ctx),
+    }
+}
+
+fn node_name(node: &Node) -> &str {
+    match node {
+        Node::Element { name, .. } => name,
+        Node::Text(_) => "",
+    }
+}
+
+fn render_table(children: &[Node], ctx: &mut Context) -> String {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    collect_rows(children, ctx, &mut rows);
+    if rows.is_empty() {
+        return String::new();
+    }
+    let cols = rows.iter().map(|r| r.len()).max().unwrap_or(1);
+    let mut lines = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        let mut cells = row.clone();
+        cells.resize(cols, String::new());
+        lines.push(format!("| {} |", cells.join(" | ")));
+        if i == 0 {
+            lines.push(format!("|{}", " --- |".repeat(cols)));
+        }
+    }
+    lines.join("\n")
+}
+
+fn collect_rows(nodes: &[Node], ctx: &mut Context, rows: &mut Vec<Vec<String>>) {
+    for node in nodes {
+        if let Node::Element { name, children, .. } = node {
+            if name == "tr" {
+                let cells = children
+                    .iter()
+                    .filter(|c| matches!(node_name(c), "td" | "th"))
+                    .map(|c| match c {
+                        Node::Element { children, .. } => render_inner(children, ctx)
+                            .split_whitespace()
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                            .replace('|', "\\|"),
+                        Node::Text(_) => String::new(),
+                    })
+                    .collect();
+                rows.push(cells);
+            } else {
+                collect_rows(children, ctx, rows);
+            }
+        }
+    }
+}
+
+/// 折叠空白；行首不输出空格
+fn push_collapsed(out: &mut String, text: &str) {
+    let mut last_space = out.is_empty() || out.ends_with([' ', '\n']);
+    for c in text.chars() {
+        if c.is_whitespace() && c != '\u{a0}' {
+            if !last_space {
+                out.push(' ');
+                last_space = true;
+            }
+        } else {
+            out.push(c);
+            last_space = false;
+        }
+    }
+}
+
+fn wrap_inline(out: &mut String, text: &str, marker: &str) {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        out.push_str(text);
+        return;
+    }
+    out.push_str(&format!("{}{}{}", marker, trimmed, marker));
+}
+
+fn block(out: &mut String, content: &str) {
+    if content.is_empty() {
+        return;
+    }
+    out.push_str("\n\n");
+    out.push_str(content);
+    out.push_str("\n\n");
+}
+
+/// 去掉行尾多余空格（保留 Markdown 硬换行），合并连续空行
+fn normalize_blank_lines(s: &str) -> String {
+    let mut result = String::new();
+    let mut blank = 0;
+    for line in s.lines() {
+        let line = if line.ends_with("  ") && !line.trim().is_empty() {
+            format!("{}  ", line.trim_end())
+        } else {
+            line.trim_end().to_string()
+        };
+        if line.trim().is_empty() {
+            blank += 1;
+            continue;
+        }
+        if !result.is_empty() {
+            result.push_str(if blank > 0 { "\n\n" } else { "\n" });
+        }
+        blank = 0;
+        result.push_str(&line);
+    }
+    result
+}
@@ -0,0 +1,216 @@
+// 后端界面文本（原生菜单、托盘菜单、面向用户的错误信息）的多语言支持
+// 语言跟随设置中的 locale，未翻译的条目回退到中文
+
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    ZhCn,
+    En,
+}
+
+impl Locale {
+    /// 解析 locale 字符串，如 "zh-CN" / "zh" / "en-US"
+    pub fn parse(locale: &str) -> Option<Locale> {
+        let lang = locale.trim().split(['-', '_']).next().unwrap_or_default().to_lowercase();
+        match lang.as_str() {
+            "zh" => Some(Locale::ZhCn),
+            "en" => Some(Locale::En),
+            _ => None,
+        }
+    }
+
+    fn table(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::ZhCn => ZH_CN,
+            Locale::En => EN,
+        }
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_locale(locale: &str) {
+    let value = match Locale::parse(locale).unwrap_or(Locale::ZhCn) {
+        Locale::ZhCn => 0,
+        Locale::En => 1,
+    };
+    CURRENT.store(value, Ordering::Relaxed);
+}
+
+pub fn current() -> Locale {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Locale::En,
+        _ => Locale::ZhCn,
+    }
+}
+
+/// 取当前语言的文本
+pub fn t(key: &str) -> &'static str {
+    lookup(current().table(), key)
+        .or_else(|| lookup(ZH_CN, key))
+        .unwrap_or_else(|| {
+            tracing::warn!("缺少翻译: {}", key);
+            ""
+        })
+}
+
+/// 取文本并依次替换其中的 {} 占位符
+pub fn tf(key: &str, args: &[&dyn Display]) -> String {
+    let mut result = String::new();
+    let mut args = args.iter();
+    let mut rest = t(key);
+    while let Some(pos) = rest.find("{}") {
+        result.push_str(&rest[..pos]);
+        if let Some(arg) = args.next() {
+            result.push_str(&arg.to_string());
+        }
+        rest = &rest[pos + 2..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn lookup(table: &'static [(&'static str, &'static str)], key: &str) -> Option<&'static str> {
+    table.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+const ZH_CN: &[(&str, &str)] = &[
+    // 菜单
+    ("menu.file", "文件"),
+    ("menu.edit", "编辑"),
+    ("menu.view", "视图"),
+    ("menu.help", "帮助"),
+    ("menu.export", "导出"),
+    ("menu.settings", "设置..."),
+    ("menu.new_project", "新建项目"),
+    ("menu.new_document", "新建文档"),
+    ("menu.new_from_template", "从模板新建..."),
+    ("menu.quick_capture", "快速记录..."),
+    ("menu.save", "保存"),
+    ("menu.save_all", "全部保存"),
+    ("menu.import_file", "导入文件..."),
+    ("menu.export_md", "Markdown (.md)"),
+    ("menu.export_html", "HTML (.html)"),
+    ("menu.export_docx", "Word (.docx)"),
+    ("menu.export_pdf", "PDF (.pdf)"),
+    ("menu.export_txt", "纯文本 (.txt)"),
+    ("menu.project_rename", "重命名项目..."),
+    ("menu.project_delete", "删除项目..."),
+    ("menu.project_export_zip", "导出项目 (ZIP)..."),
+    ("menu.project_import_zip", "导入项目 (ZIP)..."),
+    ("menu.project_backup", "备份项目..."),
+    ("menu.save_as_template", "存为模板..."),
+    ("menu.manage_templates", "管理模板..."),
+    ("menu.doc_rename", "重命名文档..."),
+    ("menu.doc_delete", "删除文档..."),
+    ("menu.doc_duplicate", "复制文档"),
+    ("menu.doc_move_to", "移动文档到..."),
+    ("menu.doc_copy_to", "复制文档到..."),
+    ("menu.close_tab", "关闭文档"),
+    ("menu.find", "查找..."),
+    ("menu.toggle_sidebar", "切换侧边栏"),
+    ("menu.toggle_chat", "切换 AI 助手"),
+    ("menu.toggle_layout", "切换布局"),
+    ("menu.version_history", "版本历史"),
+    ("menu.view_editor", "正文区"),
+    ("menu.view_plugins", "插件区"),
+    ("menu.view_composer", "合并区"),
+    ("menu.shortcuts_ref", "快捷键参考"),
+    ("menu.about", "关于 AiDocPlus"),
+    // 托盘
+    ("tray.new_document", "新建文档"),
+    ("tray.resume_document", "继续上次文档"),
+    ("tray.quick_capture", "快速记录"),
+    ("tray.stop_ai", "停止 AI 生成"),
+    ("tray.backup", "立即备份"),
+    ("tray.show", "显示主窗口"),
+    ("tray.quit", "退出 AiDocPlus"),
+    // 错误
+    ("error.io", "文件读写错误: {}"),
+    ("error.serde", "数据格式错误: {}"),
+    ("error.project_not_found", "项目未找到: {}"),
+    ("error.document_not_found", "文档未找到: {}"),
+    ("error.target_project_not_found", "目标项目未找到: {}"),
+    ("error.document_title_exists", "已存在标题为「{}」的文档"),
+    ("error.invalid_data", "数据无效: {}"),
+    ("error.export_failed", "导出失败: {}"),
+    ("error.version_not_found", "版本未找到: {}"),
+    ("error.document_locked", "文档已锁定，请先解锁: {}"),
+    ("error.template_not_found", "模板未找到: {}"),
+    ("error.plugin_not_found", "插件未找到: {}"),
+    ("error.file_not_found", "文件不存在: {}"),
+    ("error.ai", "AI 请求失败: {}"),
+    ("citation.bibliography", "参考文献"),
+    ("export.wechat_links", "参考链接"),
+    ("export.toc", "目录"),
+];
+
+const EN: &[(&str, &str)] = &[
+    ("menu.file", "File"),
+    ("menu.edit", "Edit"),
+    ("menu.view", "View"),
+    ("menu.help", "Help"),
+    ("menu.export", "Export"),
+    ("menu.settings", "Settings..."),
+    ("menu.new_project", "New Project"),
+    ("menu.new_document", "New Document"),
+    ("menu.new_from_template", "New from Template..."),
+    ("menu.quick_capture", "Quick Capture..."),
+    ("menu.save", "Save"),
+    ("menu.save_all", "Save All"),
+    ("menu.import_file", "Import File..."),
+    ("menu.export_md", "Markdown (.md)"),
+    ("menu.export_html", "HTML (.html)"),
+    ("menu.export_docx", "Word (.docx)"),
+    ("menu.export_pdf", "PDF (.pdf)"),
+    ("menu.export_txt", "Plain Text (.txt)"),
+    ("menu.project_rename", "Rename Project..."),
+    ("menu.project_delete", "Delete Project..."),
+    ("menu.project_export_zip", "Export Project (ZIP)..."),
+    ("menu.project_import_zip", "Import Project (ZIP)..."),
+    ("menu.project_backup", "Back Up Project..."),
+    ("menu.save_as_template", "Save as Template..."),
+    ("menu.manage_templates", "Manage Templates..."),
+    ("menu.doc_rename", "Rename Document..."),
+    ("menu.doc_delete", "Delete Document..."),
+    ("menu.doc_duplicate", "Duplicate Document"),
+    ("menu.doc_move_to", "Move Document To..."),
+    ("menu.doc_copy_to", "Copy Document To..."),
+    ("menu.close_tab", "Close Document"),
+    ("menu.find", "Find..."),
+    ("menu.toggle_sidebar", "Toggle Sidebar"),
+    ("menu.toggle_chat", "Toggle AI Assistant"),
+    ("menu.toggle_layout", "Toggle Layout"),
+    ("menu.version_history", "Version History"),
+    ("menu.view_editor", "Editor"),
+    ("menu.view_plugins", "Plugins"),
+    ("menu.view_composer", "Composer"),
+    ("menu.shortcuts_ref", "Keyboard Shortcuts"),
+    ("menu.about", "About AiDocPlus"),
+    ("tray.new_document", "New Document"),
+    ("tray.resume_document", "Continue Last Document"),
+    ("tray.quick_capture", "Quick Capture"),
+    ("tray.stop_ai", "Stop AI Generation"),
+    ("tray.backup", "Back Up Now"),
+    ("tray.show", "Show Main Window"),
+    ("tray.quit", "Quit AiDocPlus"),
+    ("error.io", "IO error: {}"),
+    ("error.serde", "Serialization error: {}"),
+    ("error.project_not_found", "Project not found: {}"),
+    ("error.document_not_found", "Document not found: {}"),
+    ("error.target_project_not_found", "Target project not found: {}"),
+    ("error.document_title_exists", "A document with title '{}' already exists"),
+    ("error.invalid_data", "Invalid data: {}"),
+    ("error.export_failed", "Export failed: {}"),
+    ("error.version_not_found", "Version not found: {}"),
+    ("error.document_locked", "Document is locked, unlock it first: {}"),
+    ("error.template_not_found", "Template not found: {}"),
+    ("error.plugin_not_found", "Plugin not found: {}"),
+    ("error.file_not_found", "File not found: {}"),
+    ("error.ai", "AI error: {}"),
+    ("citation.bibliography", "References"),
+    ("export.wechat_links", "Links"),
+    ("export.toc", "Contents"),
+];
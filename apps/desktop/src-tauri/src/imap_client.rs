@@ -0,0 +1,252 @@
+use base64::{engine::general_purpose::STANDARD, engine::general_purpose::STANDARD_NO_PAD, Engine as _};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::{self, pki_types::ServerName};
+
+/// 单条命令（含读取响应）的超时时间
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 单封邮件最大字节数，超出时拒绝读取
+const MAX_LITERAL_BYTES: usize = 50 * 1024 * 1024;
+
+trait ImapStream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> ImapStream for T {}
+
+/// IMAP 连接参数
+pub struct ImapSettings {
+    pub host: String,
+    pub port: u16,
+    /// "ssl"（隐式 TLS，默认 993 端口）或 "starttls"（143 端口，登录前升级为 TLS）
+    pub encryption: String,
+    pub email: String,
+    /// 授权码，XOAUTH2 时为 access token
+    pub password: String,
+    pub xoauth2: bool,
+}
+
+/// 在已建立的连接上进行 TLS 握手
+async fn tls_connect<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(host: &str, stream: S) -> Result<Box<dyn ImapStream>, String> {
+    let roots = rustls::RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.to_vec() };
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("TLS 配置失败: {}", e))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let server_name = ServerName::try_from(host.to_string()).map_err(|e| format!("无效的 IMAP 服务器地址: {}", e))?;
+    let tls = tokio_rustls::TlsConnector::from(Arc::new(config))
+        .connect(server_name, stream)
+        .await
+        .map_err(|e| format!("TLS 握手失败: {}", e))?;
+    Ok(Box::new(tls))
+}
+
+/// 一行响应及其携带的 literal 数据（{N} 之后的原始字节）
+struct ResponseLine {
+    text: String,
+    literals: Vec<Vec<u8>>,
+}
+
+/// 已登录的 IMAP 会话（仅实现导入邮件需要的命令子集）
+pub struct ImapSession {
+    stream: BufReader<Box<dyn ImapStream>>,
+    tag: u32,
+}
+
+impl ImapSession {
+    pub async fn connect(settings: &ImapSettings) -> Result<Self, String> {
+        let tcp = tokio::time::timeout(COMMAND_TIMEOUT, TcpStream::connect((settings.host.as_str(), settings.port)))
+            .await
+            .map_err(|_| format!("连接 IMAP 服务器超时: {}:{}", settings.host, settings.port))?
+            .map_err(|e| format!("连接 IMAP 服务器失败: {}", e))?;
+
+        let starttls = settings.encryption == "starttls";
+        let stream: Box<dyn ImapStream> = if starttls { Box::new(tcp) } else { tls_connect(&settings.host, tcp).await? };
+
+        let mut session = ImapSession { stream: BufReader::new(stream), tag: 0 };
+        let greeting = session.read_line().await?;
+        if !greeting.text.starts_with("* OK") && !greeting.text.starts_with("* PREAUTH") {
+            return Err(format!("IMAP 服务器拒绝连接: {}", greeting.text));
+        }
+
+        // 明文连接上不发送凭据：服务器不支持 STARTTLS 时直接失败
+        if starttls {
+            session.command("STARTTLS").await.map_err(|e| format!("IMAP 服务器不支持 STARTTLS: {}", e))?;
+            let plain = session.stream.into_inner();
+            session.stream = BufReader::new(tls_connect(&settings.host, plain).await?);
+        }
+
+        session.login(settings).await?;
+        Ok(session)
+    }
+
+    async fn login(&mut self, settings: &ImapSettings) -> Result<(), String> {
+        let result = if settings.xoauth2 {
+            let token = format!("user={}\x01auth=Bearer {}\x01\x01", settings.email, settings.password);
+            self.command(&format!("AUTHENTICATE XOAUTH2 {}", STANDARD.encode(token))).await
+        } else {
+            self.command(&format!("LOGIN {} {}", quote(&settings.email), quote(&settings.password))).await
+        };
+        result.map(|_| ()).map_err(|e| format!("IMAP 登录失败: {}", e))
+    }
+
+    /// 打开文件夹，返回邮件数量；只读模式（EXAMINE）不会改变任何标记
+    pub async fn select(&mut self, folder: &str, read_only: bool) -> Result<u32, String> {
+        let verb = if read_only { "EXAMINE" } else { "SELECT" };
+        let lines = self
+            .command(&format!("{} {}", verb, quote(&encode_folder_name(folder))))
+            .await
+            .map_err(|e| format!("打开文件夹 {} 失败: {}", folder, e))?;
+        Ok(lines
+            .iter()
+            .find_map(|l| l.text.strip_prefix("* ")?.strip_suffix(" EXISTS")?.parse().ok())
+            .unwrap_or(0))
+    }
+
+    /// UID SEARCH，criteria 如 "UNSEEN SINCE 01-Jan-2024"
+    pub async fn uid_search(&mut self, criteria: &str) -> Result<Vec<u32>, String> {
+        let lines = self.command(&format!("UID SEARCH {}", criteria)).await?;
+        let mut uids: Vec<u32> = lines
+            .iter()
+            .filter_map(|l| l.text.strip_prefix("* SEARCH"))
+            .flat_map(|rest| rest.split_whitespace().filter_map(|s| s.parse().ok()))
+            .collect();
+        uids.sort_unstable();
+        Ok(uids)
+    }
+
+    /// 获取完整的 RFC 822 原文（BODY.PEEK 不改变已读状态）
+    pub async fn fetch_raw(&mut self, uid: u32) -> Result<Vec<u8>, String> {
+        let lines = self.command(&format!("UID FETCH {} BODY.PEEK[]", uid)).await?;
+        lines
+            .into_iter()
+            .filter(|l| l.text.contains("FETCH"))
+            .find_map(|l| l.literals.into_iter().next())
+            .ok_or_else(|| format!("邮件 {} 不存在或已被删除", uid))
+    }
+
+    pub async fn mark_seen(&mut self, uid: u32) -> Result<(), String> {
+        self.command(&format!("UID STORE {} +FLAGS.SILENT (\\Seen)", uid)).await.map(|_| ())
+    }
+
+    pub async fn logout(mut self) {
+        let _ = self.command("LOGOUT").await;
+    }
+
+    /// 发送带标签的命令，返回标签 OK 之前的所有未标记响应
+    async fn command(&mut self, cmd: &str) -> Result<Vec<ResponseLine>, String> {
+        self.tag += 1;
+        let tag = format!("A{:04}", self.tag);
+        tokio::time::timeout(COMMAND_TIMEOUT, self.exchange(&tag, cmd))
+            .await
+            .map_err(|_| "IMAP 服务器响应超时".to_string())?
+    }
+
+    async fn exchange(&mut self, tag: &str, cmd: &str) -> Result<Vec<ResponseLine>, String> {
+        self.write(format!("{} {}\r\n", tag, cmd).as_bytes()).await?;
+
+        let mut untagged = Vec::new();
+        loop {
+            let line = self.read_line().await?;
+            if line.text.starts_with('+') {
+                // XOAUTH2 失败时服务器会发送错误详情的续行，回复空行结束认证
+                self.write(b"\r\n").await?;
+                continue;
+            }
+            if let Some(status) = line.text.strip_prefix(tag).map(str::trim_start) {
+                if status.starts_with("OK") {
+                    return Ok(untagged);
+                }
+                return Err(status.to_string());
+            }
+            untagged.push(line);
+        }
+    }
+
+    async fn write(&mut self, data: &[u8]) -> Result<(), String> {
+        let stream = self.stream.get_mut();
+        stream.write_all(data).await.map_err(|e| format!("IMAP 写入失败: {}", e))?;
+        stream.flush().await.map_err(|e| format!("IMAP 写入失败: {}", e))
+    }
+
+    /// 读取一行响应；行尾为 {N} 时继续读取 N 字节 literal 及其后的续行
+    async fn read_line(&mut self) -> Result<ResponseLine, String> {
+        let mut text = String::new();
+        let mut literals = Vec::new();
+        loop {
+            let mut buf = Vec::new();
+            let n = self
+                .stream
+                .read_until(b'\n', &mut buf)
+                .await
+                .map_err(|e| format!("IMAP 读取失败: {}", e))?;
+            if n == 0 {
+                return Err("IMAP 连接已关闭".to_string());
+            }
+            let line = String::from_utf8_lossy(&buf);
+            let line = line.trim_end_matches(['\r', '\n']);
+            text.push_str(line);
+
+            let Some(size) = literal_size(line) else {
+                return Ok(ResponseLine { text, literals });
+            };
+            if size > MAX_LITERAL_BYTES {
+                return Err(format!("邮件过大（{} 字节），已跳过", size));
+            }
+            let mut literal = vec![0u8; size];
+            self.stream
+                .read_exact(&mut literal)
+                .await
+                .map_err(|e| format!("IMAP 读取失败: {}", e))?;
+            literals.push(literal);
+        }
+    }
+}
+
+fn literal_size(line: &str) -> Option<usize> {
+    let open = line.rfind('{')?;
+    line.strip_suffix('}')?[open + 1..].trim_end_matches('+').parse().ok()
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// 文件夹名编码为 IMAP 修改版 UTF-7（RFC 3501 §5.1.3），如 "已发送" → "&XfJT0ZAB-"
+pub fn encode_folder_name(name: &str) -> String {
+    let mut out = String::new();
+    let mut pending: Vec<u16> = Vec::new();
+
+    let flush = |pending: &mut Vec<u16>, out: &mut String| {
+        if pending.is_empty() {
+            return;
+        }
+        let bytes: Vec<u8> = pending.iter().flat_map(|u| u.to_be_bytes()).collect();
+        out.push('&');
+        out.push_str(&STANDARD_NO_PAD.encode(bytes).replace('/', ","));
+        out.push('-');
+        pending.clear();
+    };
+
+    for c in name.chars() {
+        if (' '..='~').contains(&c) {
+            flush(&mut pending, &mut out);
+            if c == '&' {
+                out.push_str("&-");
+            } else {
+                out.push(c);
+            }
+        } else {
+            let mut buf = [0u16; 2];
+            pending.extend_from_slice(c.encode_utf16(&mut buf));
+        }
+    }
+    flush(&mut pending, &mut out);
+    out
+}
+
+/// 日期格式化为 IMAP SEARCH 使用的 "1-Jan-2024"
+pub fn search_date(date: &chrono::NaiveDate) -> String {
+    date.format("%-d-%b-%Y").to_string()
+}
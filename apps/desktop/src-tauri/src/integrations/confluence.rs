@@ -0,0 +1,201 @@
+use super::{read_json, PublishCredentials, TIMEOUT};
+use crate::html_markdown;
+use comrak::{markdown_to_html, Options};
+use regex::{Captures, Regex};
+use reqwest::{RequestBuilder, StatusCode};
+use serde_json::json;
+use std::path::PathBuf;
+
+/// 需要作为页面附件上传的本地图片
+struct LocalImage {
+    file_name: String,
+    path: PathBuf,
+}
+
+/// 将 Markdown 转换为 Confluence 存储格式（XHTML + 宏），返回需要上传的本地图片
+fn to_storage(markdown: &str) -> (String, Vec<LocalImage>) {
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.strikethrough = true;
+    options.extension.tasklist = true;
+    options.extension.autolink = true;
+    // 原始 HTML 不一定是合法 XHTML，不输出
+    options.render.unsafe_ = false;
+
+    let html = markdown_to_html(markdown, &options);
+
+    let pre_re = Regex::new(r#"(?s)<pre[^>]*><code(?: class="language-([^"]+)")?>(.*?)</code></pre>"#).unwrap();
+    let html = pre_re.replace_all(&html, |caps: &Captures| {
+        let code = html_markdown::decode_entities(&caps[2]).replace("]]>", "]]]]><![CDATA[>");
+        let language = caps
+            .get(1)
+            .map(|l| format!(r#"<ac:parameter ac:name="language">{}</ac:parameter>"#, l.as_str()))
+            .unwrap_or_default();
+        format!(
+            r#"<ac:structured-macro ac:name="code">{}<ac:plain-text-body><![CDATA[{}]]></ac:plain-text-body></ac:structured-macro>"#,
+            language,
+            code.trim_end_matches('\n')
+        )
+    });
+
+    let checkbox_re = Regex::new(r#"<input type="checkbox"([^>]*)/?>"#).unwrap();
+    let html = checkbox_re.replace_all(&html, |caps: &Captures| {
+        if caps[1].contains("checked") { "☑" } else { "☐" }.to_string()
+    });
+
+    let mut images: Vec<LocalImage> = Vec::new();
+    let img_re = Regex::new(r#"<img src="([^"]*)" alt="([^"]*)"[^>]*/>"#).unwrap();
+    let html = img_re.replace_all(&html, |caps: &Captures| {
+        let src = html_markdown::decode_entities(&caps[1]);
+        if src.starts_with("http://") || src.starts_with("https://") {
+            return format!(r#"<ac:image><ri:url ri:value="{}" /></ac:image>"#, &caps[1]);
+        }
+        let path = match reqwest::Url::parse(&src).ok().filter(|u| u.scheme() == "file") {
+            Some(url) => url.to_file_path().unwrap_or_default(),
+            None => PathBuf::from(&src),
+        };
+        if !path.is_file() {
+            return caps[2].to_string();
+        }
+        // 同名文件加序号区分
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let file_name = if images.iter().any(|i| i.file_name == name) {
+            format!("{}-{}", images.len() + 1, name)
+        } else {
+            name
+        };
+        let tag = format!(r#"<ac:image><ri:attachment ri:filename="{}" /></ac:image>"#, xml_escape(&file_name));
+        images.push(LocalImage { file_name, path });
+        tag
+    });
+
+    (html.into_owned(), images)
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn api(credentials: &PublishCredentials) -> Result<String, String> {
+    let base = credentials.base_url.as_deref().map(str::trim).filter(|b| !b.is_empty()).ok_or("缺少 Confluence 站点地址")?;
+    Ok(format!("{}/rest/api", base.trim_end_matches('/')))
+}
+
+fn authorize(req: RequestBuilder, credentials: &PublishCredentials, token: &str) -> RequestBuilder {
+    match credentials.email.as_deref().filter(|e| !e.is_empty()) {
+        Some(email) => req.basic_auth(email, Some(token)),
+        None => req.bearer_auth(token),
+    }
+}
+
+/// 发布到 Confluence 空间，返回页面 ID 与链接
+pub async fn publish(
+    credentials: &PublishCredentials,
+    space_key: &str,
+    title: &str,
+    markdown: &str,
+    remote_id: Option<&str>,
+) -> Result<(String, Option<String>), String> {
+    let api = api(credentials)?;
+    let token = credentials.token()?;
+    let client = reqwest::Client::new();
+    let (storage, images) = to_storage(markdown);
+
+    // 没有发布记录时按标题查找同名页面，避免重复创建报错
+    let mut page_id = remote_id.map(str::to_string);
+    if page_id.is_none() {
+        let resp = authorize(client.get(format!("{}/content", api)), credentials, &token)
+            .query(&[("spaceKey", space_key), ("title", title), ("type", "page")])
+            .timeout(TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| format!("连接 Confluence 失败: {}", e))?;
+        let found = read_json(resp, "查询 Confluence 页面").await?;
+        page_id = found["results"][0]["id"].as_str().map(str::to_string);
+    }
+
+    let mut current_version = None;
+    if let Some(id) = &page_id {
+        let resp = authorize(client.get(format!("{}/content/{}", api, id)), credentials, &token)
+            .query(&[("expand", "version")])
+            .timeout(TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| format!("连接 Confluence 失败: {}", e))?;
+        // 远程页面已被删除时重新创建
+        if resp.status() != StatusCode::NOT_FOUND {
+            current_version = read_json(resp, "读取 Confluence 页面").await?["version"]["number"].as_u64();
+        }
+    }
+
+    let body = json!({
+        "type": "page",
+        "title": title,
+        "space": { "key": space_key },
+        "body": { "storage": { "value": storage, "representation": "storage" } },
+    });
+    let page = match (&page_id, current_version) {
+        (Some(id), Some(version)) => {
+            let mut body = body;
+            body["version"] = json!({ "number": version + 1 });
+            let resp = authorize(client.put(format!("{}/content/{}", api, id)), credentials, &token)
+                .json(&body)
+                .timeout(TIMEOUT)
+                .send()
+                .await
+                .map_err(|e| format!("连接 Confluence 失败: {}", e))?;
+            read_json(resp, "更新 Confluence 页面").await?
+        }
+        _ => {
+            let resp = authorize(client.post(format!("{}/content", api)), credentials, &token)
+                .json(&body)
+                .timeout(TIMEOUT)
+                .send()
+                .await
+                .map_err(|e| format!("连接 Confluence 失败: {}", e))?;
+            read_json(resp, "创建 Confluence 页面").await?
+        }
+    };
+
+    let id = page["id"].as_str().ok_or("Confluence 未返回页面 ID")?.to_string();
+    for image in &images {
+        upload_attachment(&client, &api, credentials, &token, &id, image).await?;
+    }
+
+    let url = match (page["_links"]["base"].as_str(), page["_links"]["webui"].as_str()) {
+        (Some(base), Some(webui)) => Some(format!("{}{}", base, webui)),
+        _ => None,
+    };
+    Ok((id, url))
+}
+
+/// 上传（或覆盖同名）页面附件；reqwest 未启用 multipart，手动拼装请求体
+async fn upload_attachment(
+    client: &reqwest::Client,
+    api: &str,
+    credentials: &PublishCredentials,
+    token: &str,
+    page_id: &str,
+    image: &LocalImage,
+) -> Result<(), String> {
+    let data = std::fs::read(&image.path).map_err(|e| format!("读取图片失败: {}", e))?;
+    let boundary = format!("----aidocplus{}", uuid::Uuid::new_v4().simple());
+    let mut body = format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: application/octet-stream\r\n\r\n",
+        boundary,
+        image.file_name.replace('"', "")
+    )
+    .into_bytes();
+    body.extend_from_slice(&data);
+    body.extend_from_slice(format!("\r\n--{}\r\nContent-Disposition: form-data; name=\"minorEdit\"\r\n\r\ntrue\r\n--{}--\r\n", boundary, boundary).as_bytes());
+
+    let resp = authorize(client.put(format!("{}/content/{}/child/attachment", api, page_id)), credentials, token)
+        .header("X-Atlassian-Token", "no-check")
+        .header(reqwest::header::CONTENT_TYPE, format!("multipart/form-data; boundary={}", boundary))
+        .body(body)
+        .timeout(TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("上传附件失败: {}", e))?;
+    read_json(resp, &format!("上传附件 {} ", image.file_name)).await.map(|_| ())
+}
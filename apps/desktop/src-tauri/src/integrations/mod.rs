@@ -0,0 +1,93 @@
+// 发布到第三方知识库（Confluence / Notion）
+// 首次发布创建页面，远程 ID 记录在文档 publications 中，再次发布时更新同一页面
+
+pub mod confluence;
+pub mod notion;
+
+use crate::secrets;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PublishTarget {
+    Confluence,
+    Notion,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PublishCredentials {
+    /// Confluence 站点地址，如 https://example.atlassian.net/wiki
+    pub base_url: Option<String>,
+    /// Confluence Cloud 账户邮箱；为空时令牌按个人访问令牌（Bearer）使用
+    pub email: Option<String>,
+    pub token: Option<String>,
+    /// 从密钥管理读取令牌，如 "custom:notion"
+    pub secret_key: Option<String>,
+}
+
+impl PublishCredentials {
+    fn token(&self) -> Result<String, String> {
+        if let Some(token) = self.token.as_deref().filter(|t| !t.trim().is_empty()) {
+            return Ok(token.trim().to_string());
+        }
+        if let Some(key) = &self.secret_key {
+            return secrets::get(key)?.ok_or_else(|| format!("密钥不存在: {}", key));
+        }
+        Err("缺少访问令牌".to_string())
+    }
+}
+
+/// 文档在远程平台上的发布记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Publication {
+    pub target: PublishTarget,
+    /// Confluence 空间 Key 或 Notion 数据库 / 父页面 ID
+    pub container: String,
+    pub remote_id: String,
+    #[serde(default)]
+    pub url: Option<String>,
+    pub published_at: i64,
+}
+
+/// 发布文档；existing 为同一目标上次的发布记录，存在时更新该页面
+pub async fn publish(
+    target: PublishTarget,
+    credentials: &PublishCredentials,
+    container: &str,
+    title: &str,
+    markdown: &str,
+    existing: Option<&Publication>,
+) -> Result<Publication, String> {
+    let remote_id = existing.map(|p| p.remote_id.as_str());
+    let (remote_id, url) = match target {
+        PublishTarget::Confluence => confluence::publish(credentials, container, title, markdown, remote_id).await?,
+        PublishTarget::Notion => notion::publish(credentials, container, title, markdown, remote_id).await?,
+    };
+    Ok(Publication {
+        target,
+        container: container.to_string(),
+        remote_id,
+        url,
+        published_at: chrono::Utc::now().timestamp(),
+    })
+}
+
+/// 读取响应 JSON，失败时带上远程返回的错误信息
+async fn read_json(resp: reqwest::Response, action: &str) -> Result<serde_json::Value, String> {
+    let status = resp.status();
+    let body: serde_json::Value = resp.json().await.unwrap_or_default();
+    if status.is_success() {
+        return Ok(body);
+    }
+    let detail = body
+        .get("message")
+        .and_then(|m| m.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| status.to_string());
+    Err(format!("{}失败: {}", action, detail))
+}
@@ -0,0 +1,345 @@
+use super::{read_json, PublishCredentials, TIMEOUT};
+use comrak::nodes::{AstNode, ListType, NodeValue};
+use comrak::{parse_document, Arena, Options};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+const API: &str = "https://api.notion.com/v1";
+const NOTION_VERSION: &str = "2022-06-28";
+/// 单个 rich_text 片段的字符上限
+const MAX_TEXT: usize = 2000;
+/// 每次请求最多追加的块数
+const BATCH: usize = 100;
+
+/// Notion 代码块支持的语言（部分），其余归为 plain text
+const LANGUAGES: &[&str] = &[
+    "bash", "c", "c#", "c++", "css", "diff", "docker", "go", "graphql", "html", "java", "javascript", "json", "kotlin",
+    "latex", "lua", "makefile", "markdown", "mermaid", "php", "powershell", "python", "r", "ruby", "rust", "scala",
+    "shell", "sql", "swift", "toml", "typescript", "xml", "yaml",
+];
+
+#[derive(Clone, Default)]
+struct Marks {
+    bold: bool,
+    italic: bool,
+    strike: bool,
+    code: bool,
+    link: Option<String>,
+}
+
+/// 将 Markdown 转换为 Notion 块
+pub fn to_blocks(markdown: &str) -> Vec<Value> {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.strikethrough = true;
+    options.extension.tasklist = true;
+    options.extension.autolink = true;
+
+    let root = parse_document(&arena, markdown, &options);
+    root.children().flat_map(blocks_of).collect()
+}
+
+fn block(kind: &str, content: Value) -> Value {
+    json!({ "object": "block", "type": kind, kind: content })
+}
+
+fn blocks_of<'a>(node: &'a AstNode<'a>) -> Vec<Value> {
+    match &node.data.borrow().value {
+        NodeValue::Paragraph => paragraph(node),
+        NodeValue::Heading(h) => {
+            let kind = format!("heading_{}", h.level.min(3));
+            vec![block(&kind, json!({ "rich_text": rich_text(node) }))]
+        }
+        NodeValue::BlockQuote | NodeValue::MultilineBlockQuote(_) | NodeValue::Alert(_) => {
+            let mut text = Vec::new();
+            for (i, child) in node.children().enumerate() {
+                if i > 0 {
+                    text.push(text_item("\n", &Marks::default()));
+                }
+                text.extend(rich_text(child));
+            }
+            vec![block("quote", json!({ "rich_text": text }))]
+        }
+        NodeValue::List(list) => {
+            let kind = if list.list_type == ListType::Ordered { "numbered_list_item" } else { "bulleted_list_item" };
+            node.children().map(|item| list_item(item, kind)).collect()
+        }
+        NodeValue::CodeBlock(cb) => {
+            let info = cb.info.split_whitespace().next().unwrap_or("").to_lowercase();
+            let language = match info.as_str() {
+                "sh" | "zsh" => "shell",
+                "js" | "jsx" => "javascript",
+                "ts" | "tsx" => "typescript",
+                "py" => "python",
+                "rs" => "rust",
+                "yml" => "yaml",
+                "md" => "markdown",
+                "cpp" => "c++",
+                "cs" | "csharp" => "c#",
+                other if LANGUAGES.contains(&other) => other,
+                _ => "plain text",
+            };
+            let text = split_text(cb.literal.trim_end_matches('\n'), &Marks::default());
+            vec![block("code", json!({ "language": language, "rich_text": text }))]
+        }
+        NodeValue::ThematicBreak => vec![block("divider", json!({}))],
+        NodeValue::Table(table) => {
+            let rows: Vec<Value> = node
+                .children()
+                .map(|row| {
+                    let cells: Vec<Value> = row.children().map(|cell| Value::Array(rich_text(cell))).collect();
+                    block("table_row", json!({ "cells": cells }))
+                })
+                .collect();
+            vec![block(
+                "table",
+                json!({
+                    "table_width": table.num_columns,
+                    "has_column_header": true,
+                    "has_row_header": false,
+                    "children": rows,
+                }),
+            )]
+        }
+        NodeValue::HtmlBlock(html) => {
+            let text = html.literal.trim();
+            if text.is_empty() {
+                Vec::new()
+            } else {
+                vec![block("paragraph", json!({ "rich_text": split_text(text, &Marks::default()) }))]
+            }
+        }
+        NodeValue::FrontMatter(_) => Vec::new(),
+        _ => node.children().flat_map(blocks_of).collect(),
+    }
+}
+
+/// 段落中的网络图片转为独立的图片块，本地图片无法引用，保留替代文字
+fn paragraph<'a>(node: &'a AstNode<'a>) -> Vec<Value> {
+    let images: Vec<String> = node
+        .descendants()
+        .filter_map(|n| match &n.data.borrow().value {
+            NodeValue::Image(link) if link.url.starts_with("http://") || link.url.starts_with("https://") => {
+                Some(link.url.clone())
+            }
+            _ => None,
+        })
+        .collect();
+    let text = rich_text(node);
+    let mut blocks = Vec::new();
+    if !text.is_empty() {
+        blocks.push(block("paragraph", json!({ "rich_text": text })));
+    }
+    for url in images {
+        blocks.push(block("image", json!({ "type": "external", "external": { "url": url } })));
+    }
+    blocks
+}
+
+fn list_item<'a>(item: &'a AstNode<'a>, kind: &str) -> Value {
+    let checked = match &item.data.borrow().value {
+        NodeValue::TaskItem(mark) => Some(mark.is_some()),
+        _ => None,
+    };
+    let mut text = Vec::new();
+    let mut children = Vec::new();
+    for (i, child) in item.children().enumerate() {
+        if i == 0 && matches!(child.data.borrow().value, NodeValue::Paragraph) {
+            text = rich_text(child);
+        } else {
+            children.extend(blocks_of(child));
+        }
+    }
+    let mut content = json!({ "rich_text": text });
+    if let Some(checked) = checked {
+        content["checked"] = json!(checked);
+    }
+    if !children.is_empty() {
+        content["children"] = Value::Array(children);
+    }
+    block(if checked.is_some() { "to_do" } else { kind }, content)
+}
+
+fn rich_text<'a>(node: &'a AstNode<'a>) -> Vec<Value> {
+    let mut out = Vec::new();
+    for child in node.children() {
+        inline(child, &Marks::default(), &mut out);
+    }
+    out
+}
+
+fn inline<'a>(node: &'a AstNode<'a>, marks: &Marks, out: &mut Vec<Value>) {
+    let mut marks = marks.clone();
+    match &node.data.borrow().value {
+        NodeValue::Text(text) => return out.extend(split_text(text, &marks)),
+        NodeValue::Code(code) => {
+            marks.code = true;
+            return out.extend(split_text(&code.literal, &marks));
+        }
+        NodeValue::SoftBreak => return out.push(text_item(" ", &marks)),
+        NodeValue::LineBreak => return out.push(text_item("\n", &marks)),
+        NodeValue::HtmlInline(html) => return out.extend(split_text(html, &marks)),
+        NodeValue::Image(link) if !(link.url.starts_with("http://") || link.url.starts_with("https://")) => {}
+        // 网络图片已转为图片块
+        NodeValue::Image(_) => return,
+        NodeValue::Strong => marks.bold = true,
+        NodeValue::Emph => marks.italic = true,
+        NodeValue::Strikethrough => marks.strike = true,
+        NodeValue::Link(link) if link.url.starts_with("http://") || link.url.starts_with("https://") => {
+            marks.link = Some(link.url.clone())
+        }
+        _ => {}
+    }
+    for child in node.children() {
+        inline(child, &marks, out);
+    }
+}
+
+fn text_item(content: &str, marks: &Marks) -> Value {
+    let link = marks.link.as_ref().map(|url| json!({ "url": url }));
+    json!({
+        "type": "text",
+        "text": { "content": content, "link": link },
+        "annotations": {
+            "bold": marks.bold,
+            "italic": marks.italic,
+            "strikethrough": marks.strike,
+            "code": marks.code,
+        },
+    })
+}
+
+fn split_text(text: &str, marks: &Marks) -> Vec<Value> {
+    let chars: Vec<char> = text.chars().collect();
+    chars.chunks(MAX_TEXT).map(|chunk| text_item(&chunk.iter().collect::<String>(), marks)).collect()
+}
+
+/// 接受页面链接或带连字符的 ID，取出 32 位十六进制 ID
+fn normalize_id(input: &str) -> String {
+    let hex: String = input.split(['?', '#']).next().unwrap_or(input).chars().filter(|c| c.is_ascii_hexdigit()).collect();
+    if hex.len() >= 32 {
+        hex[hex.len() - 32..].to_string()
+    } else {
+        input.trim().to_string()
+    }
+}
+
+fn title_property(properties: &Value) -> Option<String> {
+    properties
+        .as_object()?
+        .iter()
+        .find(|(_, p)| p["type"] == "title")
+        .map(|(name, _)| name.clone())
+}
+
+struct Client {
+    http: reqwest::Client,
+    token: String,
+}
+
+impl Client {
+    async fn send(&self, method: reqwest::Method, path: &str, body: Option<Value>) -> Result<reqwest::Response, String> {
+        let mut req = self
+            .http
+            .request(method, format!("{}{}", API, path))
+            .bearer_auth(&self.token)
+            .header("Notion-Version", NOTION_VERSION)
+            .timeout(TIMEOUT);
+        if let Some(body) = body {
+            req = req.json(&body);
+        }
+        req.send().await.map_err(|e| format!("连接 Notion 失败: {}", e))
+    }
+
+    async fn append(&self, page_id: &str, blocks: &[Value]) -> Result<(), String> {
+        for chunk in blocks.chunks(BATCH) {
+            let resp = self
+                .send(reqwest::Method::PATCH, &format!("/blocks/{}/children", page_id), Some(json!({ "children": chunk })))
+                .await?;
+            read_json(resp, "写入 Notion 内容").await?;
+        }
+        Ok(())
+    }
+
+    async fn clear(&self, page_id: &str) -> Result<(), String> {
+        let mut ids = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut path = format!("/blocks/{}/children?page_size=100", page_id);
+            if let Some(c) = &cursor {
+                path.push_str(&format!("&start_cursor={}", c));
+            }
+            let list = read_json(self.send(reqwest::Method::GET, &path, None).await?, "读取 Notion 内容").await?;
+            ids.extend(list["results"].as_array().into_iter().flatten().filter_map(|b| b["id"].as_str().map(str::to_string)));
+            cursor = list["next_cursor"].as_str().map(str::to_string);
+            if cursor.is_none() {
+                break;
+            }
+        }
+        for id in ids {
+            let resp = self.send(reqwest::Method::DELETE, &format!("/blocks/{}", id), None).await?;
+            read_json(resp, "清空 Notion 页面").await?;
+        }
+        Ok(())
+    }
+}
+
+/// 发布到 Notion 数据库或父页面，返回页面 ID 与链接
+pub async fn publish(
+    credentials: &PublishCredentials,
+    parent: &str,
+    title: &str,
+    markdown: &str,
+    remote_id: Option<&str>,
+) -> Result<(String, Option<String>), String> {
+    let client = Client { http: reqwest::Client::new(), token: credentials.token()? };
+    let blocks = to_blocks(markdown);
+    let title_text = json!([{ "type": "text", "text": { "content": title } }]);
+
+    if let Some(id) = remote_id {
+        let resp = client.send(reqwest::Method::GET, &format!("/pages/{}", id), None).await?;
+        // 远程页面已被删除时重新创建
+        if resp.status() != StatusCode::NOT_FOUND {
+            let page = read_json(resp, "读取 Notion 页面").await?;
+            if page["archived"] != true {
+                let prop = title_property(&page["properties"]).unwrap_or_else(|| "title".to_string());
+                let resp = client
+                    .send(reqwest::Method::PATCH, &format!("/pages/{}", id), Some(json!({ "properties": { prop: { "title": title_text } } })))
+                    .await?;
+                let page = read_json(resp, "更新 Notion 页面").await?;
+                client.clear(id).await?;
+                client.append(id, &blocks).await?;
+                return Ok((id.to_string(), page["url"].as_str().map(str::to_string)));
+            }
+        }
+    }
+
+    // 先按数据库解析，失败再作为父页面
+    let parent_id = normalize_id(parent);
+    let resp = client.send(reqwest::Method::GET, &format!("/databases/{}", parent_id), None).await?;
+    let (parent_value, prop) = if resp.status().is_success() {
+        let db = read_json(resp, "读取 Notion 数据库").await?;
+        let prop = title_property(&db["properties"]).ok_or("Notion 数据库缺少标题属性")?;
+        (json!({ "database_id": parent_id }), prop)
+    } else {
+        (json!({ "page_id": parent_id }), "title".to_string())
+    };
+
+    let (first, rest) = blocks.split_at(blocks.len().min(BATCH));
+    let resp = client
+        .send(
+            reqwest::Method::POST,
+            "/pages",
+            Some(json!({
+                "parent": parent_value,
+                "properties": { prop: { "title": title_text } },
+                "children": first,
+            })),
+        )
+        .await?;
+    let page = read_json(resp, "创建 Notion 页面").await?;
+    let id = page["id"].as_str().ok_or("Notion 未返回页面 ID")?.to_string();
+    client.append(&id, rest).await?;
+    Ok((id, page["url"].as_str().map(str::to_string)))
+}
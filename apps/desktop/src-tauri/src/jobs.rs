@@ -0,0 +1,156 @@
+// 后台任务：导入、导出等耗时操作的统一进度与取消
+// 前端调用命令时传入 jobId（不传则自动生成），执行期间收到 job:progress 事件，结束时收到 job:completed；
+// cancel_job(jobId) 只设置取消标记，任务在下一个检查点停止并清理已写出的部分结果
+
+use crate::error::run_blocking;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tauri::{AppHandle, Emitter};
+
+/// 任务被取消时返回的错误
+pub const CANCELLED: &str = "操作已取消";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+    pub id: String,
+    /// 任务类型，如 "import_file"、"pandoc_export"、"project_zip_export"
+    pub kind: String,
+    pub status: JobStatus,
+    /// 0–100
+    pub progress: f32,
+    pub message: Option<String>,
+    pub started_at: i64,
+    pub error: Option<String>,
+}
+
+struct Entry {
+    info: JobInfo,
+    cancelled: Arc<AtomicBool>,
+}
+
+static JOBS: OnceLock<Mutex<HashMap<String, Entry>>> = OnceLock::new();
+
+fn jobs() -> &'static Mutex<HashMap<String, Entry>> {
+    JOBS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 执行中的任务，传给任务函数用于汇报进度和检查取消
+#[derive(Clone)]
+pub struct Job {
+    id: String,
+    app: AppHandle,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Job {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// 更新进度；进度变化不足 1% 且提示不变时不发送事件，避免刷屏
+    pub fn progress(&self, percent: f32, message: impl Into<String>) {
+        let message = message.into();
+        let Ok(mut jobs) = jobs().lock() else { return };
+        let Some(entry) = jobs.get_mut(&self.id) else { return };
+        let percent = percent.clamp(0.0, 100.0);
+        if percent - entry.info.progress < 1.0 && entry.info.message.as_deref() == Some(message.as_str()) {
+            return;
+        }
+        entry.info.progress = percent;
+        entry.info.message = Some(message);
+        let _ = self.app.emit("job:progress", &entry.info);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// 检查点：已请求取消时返回错误
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(CANCELLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// 登记新任务；id 为空时自动生成
+pub fn start(app: &AppHandle, kind: &str, id: Option<String>) -> Job {
+    let id = id.filter(|i| !i.is_empty()).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let info = JobInfo {
+        id: id.clone(),
+        kind: kind.to_string(),
+        status: JobStatus::Running,
+        progress: 0.0,
+        message: None,
+        started_at: chrono::Utc::now().timestamp(),
+        error: None,
+    };
+    let _ = app.emit("job:progress", &info);
+    if let Ok(mut jobs) = jobs().lock() {
+        jobs.insert(id.clone(), Entry { info, cancelled: cancelled.clone() });
+    }
+    Job { id, app: app.clone(), cancelled }
+}
+
+/// 结束任务并发送 job:completed
+pub fn finish<T>(job: &Job, result: &Result<T, String>) {
+    let Some(mut entry) = jobs().lock().ok().and_then(|mut jobs| jobs.remove(&job.id)) else { return };
+    entry.info.status = match result {
+        Ok(_) => JobStatus::Completed,
+        Err(_) if job.is_cancelled() => JobStatus::Cancelled,
+        Err(_) => JobStatus::Failed,
+    };
+    if result.is_ok() {
+        entry.info.progress = 100.0;
+    }
+    entry.info.error = result.as_ref().err().cloned();
+    let _ = job.app.emit("job:completed", &entry.info);
+}
+
+/// 在后台线程中执行任务，自动登记和结束
+pub async fn run<T, F>(app: &AppHandle, kind: &str, id: Option<String>, f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&Job) -> Result<T, String> + Send + 'static,
+{
+    let job = start(app, kind, id);
+    let worker = job.clone();
+    let result = run_blocking(move || f(&worker)).await;
+    finish(&job, &result);
+    result
+}
+
+/// 请求取消；任务不存在（已结束）时返回 false
+pub fn cancel(id: &str) -> bool {
+    let Ok(jobs) = jobs().lock() else { return false };
+    match jobs.get(id) {
+        Some(entry) => {
+            entry.cancelled.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 正在执行的任务
+pub fn list() -> Vec<JobInfo> {
+    let Ok(jobs) = jobs().lock() else { return Vec::new() };
+    let mut list: Vec<JobInfo> = jobs.values().map(|e| e.info.clone()).collect();
+    list.sort_by_key(|j| j.started_at);
+    list
+}
@@ -0,0 +1,177 @@
+// 系统钥匙串访问：macOS Keychain（security）、Linux Secret Service（secret-tool）、
+// Windows 凭据保管库（PowerShell PasswordVault）
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// 钥匙串中统一使用的服务名
+const SERVICE: &str = "AiDocPlus";
+
+/// 保存密钥（已存在则覆盖）
+pub fn set_secret(account: &str, secret: &str) -> Result<(), String> {
+    // security 的交互模式从 stdin 读取命令，密钥不出现在进程参数中
+    #[cfg(target_os = "macos")]
+    let output = {
+        if secret.contains(['\n', '\r']) {
+            return Err("保存到钥匙串失败: 密钥不能包含换行".to_string());
+        }
+        run_with_stdin(
+            Command::new("security").arg("-i"),
+            &format!(
+                "add-generic-password -U -s {} -a {} -w {}\n",
+                security_quote(SERVICE),
+                security_quote(account),
+                security_quote(secret)
+            ),
+        )
+    };
+
+    #[cfg(target_os = "linux")]
+    let output = run_with_stdin(
+        Command::new("secret-tool").args([
+            "store",
+            &format!("--label={} {}", SERVICE, account),
+            "service",
+            SERVICE,
+            "account",
+            account,
+        ]),
+        secret,
+    );
+
+    #[cfg(target_os = "windows")]
+    let output = run_with_stdin(
+        Command::new("powershell").args(["-NoProfile", "-NonInteractive", "-Command", &format!(
+            "{} $s = [Console]::In.ReadToEnd(); \
+             try {{ $vault.Remove($vault.Retrieve('{}', '{}')) }} catch {{}}; \
+             $vault.Add((New-Object Windows.Security.Credentials.PasswordCredential('{}', '{}', $s)))",
+            VAULT_INIT, SERVICE, ps_escape(account), SERVICE, ps_escape(account)
+        )]),
+        secret,
+    );
+
+    check_status(output, "保存到钥匙串失败")?;
+
+    // 交互模式下命令失败不影响退出状态，读回确认
+    #[cfg(target_os = "macos")]
+    if get_secret(account)?.as_deref() != Some(secret) {
+        return Err("保存到钥匙串失败".to_string());
+    }
+    Ok(())
+}
+
+/// 读取密钥，不存在时返回 None
+pub fn get_secret(account: &str) -> Result<Option<String>, String> {
+    #[cfg(target_os = "macos")]
+    let output = Command::new("security")
+        .args(["find-generic-password", "-s", SERVICE, "-a", account, "-w"])
+        .output();
+
+    #[cfg(target_os = "linux")]
+    let output = Command::new("secret-tool")
+        .args(["lookup", "service", SERVICE, "account", account])
+        .output();
+
+    #[cfg(target_os = "windows")]
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &format!(
+            "{} try {{ $c = $vault.Retrieve('{}', '{}'); $c.RetrievePassword(); [Console]::Out.Write($c.Password) }} catch {{ {} }}",
+            VAULT_INIT, SERVICE, ps_escape(account), VAULT_CATCH
+        )])
+        .output();
+
+    match output {
+        Ok(out) if out.status.success() => {
+            let secret = String::from_utf8_lossy(&out.stdout);
+            // security / secret-tool 输出末尾带换行
+            Ok(Some(secret.strip_suffix('\n').unwrap_or(&secret).to_string()))
+        }
+        // 只有明确的“未找到”才返回 None；钥匙串锁定、用户拒绝授权、服务不可用等都是错误
+        Ok(out) if is_not_found(&out) => Ok(None),
+        output => check_status(output, "读取钥匙串失败").map(|_| None),
+    }
+}
+
+/// 删除密钥（不存在视为成功）
+pub fn delete_secret(account: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    let output = Command::new("security")
+        .args(["delete-generic-password", "-s", SERVICE, "-a", account])
+        .output();
+
+    #[cfg(target_os = "linux")]
+    let output = Command::new("secret-tool")
+        .args(["clear", "service", SERVICE, "account", account])
+        .output();
+
+    #[cfg(target_os = "windows")]
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &format!(
+            "{} try {{ $vault.Remove($vault.Retrieve('{}', '{}')) }} catch {{ {} }}",
+            VAULT_INIT, SERVICE, ps_escape(account), VAULT_CATCH
+        )])
+        .output();
+
+    match output {
+        Ok(out) if !out.status.success() && is_not_found(&out) => Ok(()),
+        output => check_status(output, "从钥匙串删除失败").map(|_| ()),
+    }
+}
+
+/// security 在条目不存在时的退出码（errSecItemNotFound）；Windows 脚本沿用同一退出码
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+const NOT_FOUND_EXIT: i32 = 44;
+
+/// 命令失败是否因为条目不存在
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn is_not_found(out: &std::process::Output) -> bool {
+    out.status.code() == Some(NOT_FOUND_EXIT)
+}
+
+/// secret-tool 未找到条目时不输出任何内容；服务不可用、钥匙串锁定时会在 stderr 中说明
+#[cfg(target_os = "linux")]
+fn is_not_found(out: &std::process::Output) -> bool {
+    out.stdout.is_empty() && out.stderr.iter().all(u8::is_ascii_whitespace)
+}
+
+#[cfg(target_os = "windows")]
+const VAULT_INIT: &str = "[void][Windows.Security.Credentials.PasswordVault,Windows.Security.Credentials,ContentType=WindowsRuntime]; \
+     $vault = New-Object Windows.Security.Credentials.PasswordVault;";
+
+/// 凭据不存在（HRESULT 0x80070490）时以 NOT_FOUND_EXIT 退出，其他异常输出到 stderr
+#[cfg(target_os = "windows")]
+const VAULT_CATCH: &str = "if ($_.Exception.HResult -eq -2147023728) { exit 44 }; [Console]::Error.Write($_.Exception.Message); exit 1";
+
+#[cfg(target_os = "macos")]
+fn security_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(target_os = "windows")]
+fn ps_escape(s: &str) -> String {
+    s.replace('\'', "''")
+}
+
+/// 通过 stdin 传递密钥，避免出现在进程参数中
+fn run_with_stdin(cmd: &mut Command, input: &str) -> std::io::Result<std::process::Output> {
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes())?;
+    }
+    child.wait_with_output()
+}
+
+fn check_status(
+    output: std::io::Result<std::process::Output>,
+    context: &str,
+) -> Result<std::process::Output, String> {
+    match output {
+        Ok(out) if out.status.success() => Ok(out),
+        Ok(out) => Err(format!("{}: {}", context, String::from_utf8_lossy(&out.stderr).trim())),
+        Err(e) => Err(format!("{}: {}", context, e)),
+    }
+}
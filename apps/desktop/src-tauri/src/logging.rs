@@ -0,0 +1,307 @@
+// 日志子系统：tracing 事件写入应用数据目录下按天轮转的日志文件（JSON Lines），
+// 同时保留最近的日志在内存中，供用户在反馈问题时导出
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::Interest;
+use tracing::{Event, Level, Metadata, Subscriber};
+
+/// 内存中保留的日志条数
+const RING_CAPACITY: usize = 2000;
+
+/// 日志文件保留天数
+const RETENTION_DAYS: i64 = 7;
+
+/// crate 名前缀，模块级别配置时可省略
+const CRATE_PREFIX: &str = "aidocplus::";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    /// Unix 毫秒
+    pub timestamp: i64,
+    pub level: String,
+    /// 模块路径，如 commands::email
+    pub target: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub fields: HashMap<String, String>,
+}
+
+/// 日志级别过滤：默认级别 + 按模块前缀覆盖（最长前缀优先）
+struct LogFilter {
+    default: LevelFilter,
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl LogFilter {
+    fn level_for(&self, target: &str) -> LevelFilter {
+        let target = target.strip_prefix(CRATE_PREFIX).unwrap_or(target);
+        self.modules
+            .iter()
+            .filter(|(prefix, _)| target == prefix || target.starts_with(&format!("{}::", prefix)))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+}
+
+struct LogWriter {
+    dir: PathBuf,
+    /// 当前文件对应的日期及句柄
+    current: Option<(String, File)>,
+}
+
+impl LogWriter {
+    fn write(&mut self, line: &str) {
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        if self.current.as_ref().is_none_or(|(date, _)| date != &today) {
+            let path = self.dir.join(format!("aidocplus-{}.log", today));
+            match OpenOptions::new().create(true).append(true).open(&path) {
+                Ok(file) => {
+                    self.current = Some((today, file));
+                    prune_old_logs(&self.dir);
+                }
+                Err(e) => {
+                    eprintln!("无法打开日志文件 {:?}: {}", path, e);
+                    return;
+                }
+            }
+        }
+        if let Some((_, file)) = self.current.as_mut() {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+}
+
+struct AppSubscriber {
+    filter: RwLock<LogFilter>,
+    writer: Mutex<LogWriter>,
+    recent: Mutex<VecDeque<LogEntry>>,
+    next_span_id: AtomicU64,
+}
+
+/// 收集事件字段；message 字段单独保存
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: HashMap<String, String>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields.insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+}
+
+impl Subscriber for AppSubscriber {
+    fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        // 级别可在运行时修改，不能缓存判断结果
+        Interest::sometimes()
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.filter
+            .read()
+            .map(|f| *metadata.level() <= f.level_for(metadata.target()))
+            .unwrap_or(true)
+    }
+
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        Id::from_u64(self.next_span_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        let target = metadata.target();
+        let entry = LogEntry {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            level: metadata.level().to_string(),
+            target: target.strip_prefix(CRATE_PREFIX).unwrap_or(target).to_string(),
+            message: visitor.message,
+            fields: visitor.fields,
+        };
+
+        #[cfg(debug_assertions)]
+        eprintln!("[{}] {}: {}", entry.level, entry.target, entry.message);
+
+        if let Ok(line) = serde_json::to_string(&entry) {
+            if let Ok(mut writer) = self.writer.lock() {
+                writer.write(&line);
+            }
+        }
+        if let Ok(mut recent) = self.recent.lock() {
+            if recent.len() == RING_CAPACITY {
+                recent.pop_front();
+            }
+            recent.push_back(entry);
+        }
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+static SUBSCRIBER: std::sync::OnceLock<&'static AppSubscriber> = std::sync::OnceLock::new();
+
+/// 初始化日志（应用启动时调用一次）
+pub fn init(log_dir: PathBuf, default_level: &str, modules: &HashMap<String, String>) -> Result<(), String> {
+    fs::create_dir_all(&log_dir).map_err(|e| format!("创建日志目录失败: {}", e))?;
+    let subscriber: &'static AppSubscriber = Box::leak(Box::new(AppSubscriber {
+        filter: RwLock::new(build_filter(default_level, modules)?),
+        writer: Mutex::new(LogWriter { dir: log_dir, current: None }),
+        recent: Mutex::new(VecDeque::with_capacity(RING_CAPACITY)),
+        next_span_id: AtomicU64::new(1),
+    }));
+    tracing::subscriber::set_global_default(Forward(subscriber))
+        .map_err(|e| format!("日志已初始化: {}", e))?;
+    let _ = SUBSCRIBER.set(subscriber);
+    Ok(())
+}
+
+/// 运行时修改日志级别
+pub fn set_levels(default_level: &str, modules: &HashMap<String, String>) -> Result<(), String> {
+    let filter = build_filter(default_level, modules)?;
+    let subscriber = SUBSCRIBER.get().ok_or("日志未初始化")?;
+    *subscriber.filter.write().map_err(|e| format!("锁获取失败: {}", e))? = filter;
+    tracing::callsite::rebuild_interest_cache();
+    Ok(())
+}
+
+/// 最近的日志（最新在前），level 为最低级别，如 "warn" 返回 WARN 和 ERROR
+pub fn recent(min_level: Option<&str>, limit: usize) -> Result<Vec<LogEntry>, String> {
+    let min_level = min_level.map(parse_level).transpose()?.unwrap_or(LevelFilter::TRACE);
+    let subscriber = SUBSCRIBER.get().ok_or("日志未初始化")?;
+    let recent = subscriber.recent.lock().map_err(|e| format!("锁获取失败: {}", e))?;
+    Ok(filter_recent(&recent, min_level, limit))
+}
+
+/// 同 recent，但拿不到锁时立即返回空（用于 panic hook，避免在日志写入途中崩溃时死锁）
+pub fn try_recent(min_level: LevelFilter, limit: usize) -> Vec<LogEntry> {
+    SUBSCRIBER
+        .get()
+        .and_then(|s| s.recent.try_lock().ok())
+        .map(|recent| filter_recent(&recent, min_level, limit))
+        .unwrap_or_default()
+}
+
+/// 尽力写入一条 ERROR 日志，拿不到锁时直接放弃（用于 panic hook，不经过 tracing 分发，避免死锁）
+pub fn try_error(target: &str, message: String) {
+    let Some(subscriber) = SUBSCRIBER.get() else { return };
+    let entry = LogEntry {
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        level: Level::ERROR.to_string(),
+        target: target.to_string(),
+        message,
+        fields: HashMap::new(),
+    };
+    if let (Ok(line), Ok(mut writer)) = (serde_json::to_string(&entry), subscriber.writer.try_lock()) {
+        writer.write(&line);
+    }
+    if let Ok(mut recent) = subscriber.recent.try_lock() {
+        if recent.len() == RING_CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(entry);
+    }
+}
+
+fn filter_recent(recent: &VecDeque<LogEntry>, min_level: LevelFilter, limit: usize) -> Vec<LogEntry> {
+    recent
+        .iter()
+        .rev()
+        .filter(|e| e.level.parse::<Level>().is_ok_and(|l| l <= min_level))
+        .take(limit)
+        .cloned()
+        .collect()
+}
+
+/// 校验并解析日志级别（trace / debug / info / warn / error / off）
+pub fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    level
+        .trim()
+        .parse::<LevelFilter>()
+        .map_err(|_| format!("无效的日志级别: {}", level))
+}
+
+fn build_filter(default_level: &str, modules: &HashMap<String, String>) -> Result<LogFilter, String> {
+    let modules = modules
+        .iter()
+        .map(|(module, level)| {
+            let module = module.strip_prefix(CRATE_PREFIX).unwrap_or(module);
+            Ok((module.to_string(), parse_level(level)?))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(LogFilter { default: parse_level(default_level)?, modules })
+}
+
+fn prune_old_logs(dir: &PathBuf) {
+    let cutoff = (chrono::Local::now() - chrono::Duration::days(RETENTION_DAYS))
+        .format("%Y-%m-%d")
+        .to_string();
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(date) = name.strip_prefix("aidocplus-").and_then(|n| n.strip_suffix(".log")) else { continue };
+        // 日期格式固定，可直接按字符串比较
+        if date < cutoff.as_str() {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// set_global_default 需要拥有所有权，转发到静态的订阅者
+struct Forward(&'static AppSubscriber);
+
+impl Subscriber for Forward {
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        self.0.register_callsite(metadata)
+    }
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.0.enabled(metadata)
+    }
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        self.0.new_span(span)
+    }
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        self.0.record(span, values)
+    }
+    fn record_follows_from(&self, span: &Id, follows: &Id) {
+        self.0.record_follows_from(span, follows)
+    }
+    fn event(&self, event: &Event<'_>) {
+        self.0.event(event)
+    }
+    fn enter(&self, span: &Id) {
+        self.0.enter(span)
+    }
+    fn exit(&self, span: &Id) {
+        self.0.exit(span)
+    }
+}
@@ -1,192 +1,273 @@
 // Prevents additional console window on Windows in release builds
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod activity;
 mod ai;
+mod ai_cache;
+mod ai_edit;
+mod api_server;
+mod auto_tag;
+mod backup;
+mod bulk_ops;
+mod bundled_resources;
+mod chat_export;
+mod citations;
+mod clipboard_html;
+mod clipboard_image;
 mod commands;
 mod config;
+mod context_window;
+mod conversations;
+mod crash_report;
+mod deep_link;
 mod document;
+mod email_account;
+mod email_contacts;
+mod email_images;
+mod email_import;
+mod email_oauth;
+mod email_queue;
 mod error;
+mod export_styles;
+mod focus;
+mod glossary;
+mod goals;
+mod html_markdown;
+mod i18n;
+mod imap_client;
+mod integrations;
+mod jobs;
+mod keychain;
+mod logging;
+mod markdown_format;
+mod menu;
 mod native_export;
+mod outline;
 mod plugin;
+mod plugin_bus;
+mod plugin_dev;
+mod plugin_migration;
+mod pricing;
+mod print;
 mod project;
+mod provider_health;
+mod quick_capture;
+mod readability;
 mod resource_engine;
+mod scripting;
+mod secrets;
+mod settings;
+mod similarity;
+mod single_instance;
+mod smart_paste;
+mod spellcheck;
+mod summarize;
 mod template;
+mod thumbnails;
 mod tools;
+mod transcription;
+mod translate;
+mod tray;
+mod updater;
+mod vault_import;
+mod web_search;
 mod workspace;
 
 use commands::{
+    activity::*,
     ai::*,
+    api_server::*,
+    backup::*,
+    capture::*,
+    citation::*,
+    clipboard::*,
+    conversation::*,
+    crash::*,
+    deep_link::*,
     document::*,
     email::*,
     export::*,
     file_system::*,
+    format::*,
+    glossary::*,
     import::*,
+    integrations::*,
+    job::*,
+    logging::*,
+    menu::*,
     pandoc::*,
     plugin::*,
+    print::*,
     project::*,
     resource::*,
+    scripting::*,
     search::*,
+    secrets::*,
+    settings::*,
+    spellcheck::*,
     template::*,
+    updater::*,
     workspace::*,
 };
 use tauri::{Manager, Emitter};
-use tauri::menu::{
-    MenuBuilder, SubmenuBuilder, MenuItem,
-};
 
 fn main() {
+    if single_instance::forward_to_running() {
+        return;
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_window_state::Builder::new().build())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .setup(|app| {
+            // 日志最先初始化，后续各模块的启动错误才能写入日志文件
+            let app_settings = settings::load(&config::get_settings_path(app.handle()));
+            if let Err(e) = logging::init(
+                config::get_log_dir(app.handle()),
+                &app_settings.logging.level,
+                &app_settings.logging.modules,
+            ) {
+                eprintln!("日志初始化失败: {}", e);
+            }
+            i18n::set_locale(&app_settings.locale);
+            crash_report::install(
+                config::get_crash_dir(app.handle()),
+                app.package_info().version.to_string(),
+            );
+
             // Initialize app state
-            app.manage(config::AppState::new());
+            let app_state = config::AppState::new();
+            // 上次批量操作中途退出时，撤销未完成的改动
+            match bulk_ops::recover(&app_state.config.projects_dir) {
+                Ok(0) => {}
+                Ok(n) => tracing::warn!("已回滚 {} 个未完成的批量文档操作", n),
+                Err(e) => tracing::error!("回滚未完成的批量文档操作失败: {}", e),
+            }
+            app.manage(app_state);
 
             // Initialize resource engine
             let resource_state = resource_engine::ResourceEngineState::new();
             let home = dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
             let resources_root = home.join("AiDocPlus").join("Resources");
-            if let Err(e) = resource_state.init(resources_root.clone()) {
-                eprintln!("[ResourceEngine] 初始化失败: {}", e);
-            } else {
-                // 从 bundled-resources 重建索引
-                let bundled_dir = std::env::current_exe()
-                    .ok()
-                    .and_then(|p| p.parent().map(|p| p.to_path_buf()))
-                    .unwrap_or_default()
-                    .join("bundled-resources");
-                if let Err(e) = resource_state.with_engine(|engine| {
-                    engine.rebuild_index_from_bundled(&bundled_dir)?;
-                    engine.rebuild_index_from_local()
-                }) {
-                    eprintln!("[ResourceEngine] 索引重建失败: {}", e);
+            let engine_ready = match resource_state.init(resources_root.clone()) {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::error!("资源引擎初始化失败: {}", e);
+                    false
                 }
-            }
+            };
             app.manage(resource_state);
 
             // Ensure plugins directory exists
             plugin::ensure_plugins_dir();
 
+            // 索引重建需要扫描全部资源目录，放到后台线程，完成后通知前端刷新
+            let index_handle = app.handle().clone();
+            std::thread::spawn(move || {
+                let resource_state = index_handle.state::<resource_engine::ResourceEngineState>();
+                if engine_ready {
+                    let bundled_dir = bundled_resources::bundled_dir();
+                    // 首次启动或升级后把内置资源复制到本地，供用户修改
+                    let app_version = index_handle.package_info().version.to_string();
+                    if !bundled_resources::is_materialized(&resources_root, &app_version) {
+                        match bundled_resources::materialize(&bundled_dir, &resources_root, &app_version, false) {
+                            Ok(report) if !report.conflicts.is_empty() => {
+                                tracing::warn!("内置资源更新存在冲突，已保留本地修改: {:?}", report.conflicts);
+                            }
+                            Ok(_) => {}
+                            Err(e) => tracing::error!("内置资源本地化失败: {}", e),
+                        }
+                    }
+                    // 从 bundled-resources 重建索引
+                    if let Err(e) = resource_state.with_engine(|engine| {
+                        engine.rebuild_index_from_bundled(&bundled_dir)?;
+                        engine.rebuild_index_from_local()
+                    }) {
+                        tracing::error!("资源索引重建失败: {}", e);
+                    }
+                }
+                if let Err(e) = plugin::index_plugins(&resource_state) {
+                    tracing::error!("插件索引失败: {}", e);
+                }
+                let _ = index_handle.emit("resources:indexed", ());
+            });
+
+            // 恢复开发模式插件的源目录监听
+            plugin_dev::restore_dev_watchers(app.handle());
+
+            // 密钥索引（明文保存在系统钥匙串）
+            secrets::init(config::get_secrets_index_path(app.handle()));
+
+            // 恢复上次未完成的邮件发送
+            commands::email::resume_pending_emails(app.handle().clone());
+            commands::email::start_email_scheduler(app.handle().clone());
+
+            // 自动化脚本的定时触发
+            commands::scripting::start_script_scheduler(app.handle().clone());
+
+            // 后台检查更新（可在设置中关闭）
+            commands::updater::start_update_check(app.handle().clone());
+
             // Ensure templates directory exists
             template::ensure_templates_dir();
 
-            // ── 构建原生系统菜单 ──
-            let handle = app.handle();
-
-            // macOS 应用菜单
-            let app_menu = SubmenuBuilder::new(handle, "AiDocPlus")
-                .about(None)
-                .separator()
-                .item(&MenuItem::with_id(handle, "settings", "设置...", true, Some("CmdOrCtrl+,"))?)
-                .separator()
-                .services()
-                .separator()
-                .hide()
-                .hide_others()
-                .show_all()
-                .separator()
-                .quit()
-                .build()?;
-
-            // 文件菜单
-            let export_sub = SubmenuBuilder::new(handle, "导出")
-                .item(&MenuItem::with_id(handle, "export_md", "Markdown (.md)", true, None::<&str>)?)
-                .item(&MenuItem::with_id(handle, "export_html", "HTML (.html)", true, None::<&str>)?)
-                .item(&MenuItem::with_id(handle, "export_docx", "Word (.docx)", true, None::<&str>)?)
-                .item(&MenuItem::with_id(handle, "export_pdf", "PDF (.pdf)", true, None::<&str>)?)
-                .item(&MenuItem::with_id(handle, "export_txt", "纯文本 (.txt)", true, None::<&str>)?)
-                .build()?;
-
-            let file_menu = SubmenuBuilder::new(handle, "文件")
-                // ── 新建 ──
-                .item(&MenuItem::with_id(handle, "new_project", "新建项目", true, Some("CmdOrCtrl+Shift+N"))?)
-                .item(&MenuItem::with_id(handle, "new_document", "新建文档", true, Some("CmdOrCtrl+N"))?)
-                .item(&MenuItem::with_id(handle, "new_from_template", "从模板新建...", true, Some("CmdOrCtrl+Shift+T"))?)
-                .separator()
-                // ── 保存 ──
-                .item(&MenuItem::with_id(handle, "save", "保存", true, Some("CmdOrCtrl+S"))?)
-                .item(&MenuItem::with_id(handle, "save_all", "全部保存", true, Some("CmdOrCtrl+Shift+S"))?)
-                .separator()
-                // ── 导入/导出文件 ──
-                .item(&MenuItem::with_id(handle, "import_file", "导入文件...", true, Some("CmdOrCtrl+I"))?)
-                .item(&export_sub)
-                .separator()
-                // ── 项目管理 ──
-                .item(&MenuItem::with_id(handle, "project_rename", "重命名项目...", true, None::<&str>)?)
-                .item(&MenuItem::with_id(handle, "project_delete", "删除项目...", true, None::<&str>)?)
-                .item(&MenuItem::with_id(handle, "project_export_zip", "导出项目 (ZIP)...", true, None::<&str>)?)
-                .item(&MenuItem::with_id(handle, "project_import_zip", "导入项目 (ZIP)...", true, None::<&str>)?)
-                .item(&MenuItem::with_id(handle, "project_backup", "备份项目...", true, None::<&str>)?)
-                .separator()
-                // ── 模板 ──
-                .item(&MenuItem::with_id(handle, "save_as_template", "存为模板...", true, None::<&str>)?)
-                .item(&MenuItem::with_id(handle, "manage_templates", "管理模板...", true, None::<&str>)?)
-                .separator()
-                // ── 文档管理 ──
-                .item(&MenuItem::with_id(handle, "doc_rename", "重命名文档...", true, None::<&str>)?)
-                .item(&MenuItem::with_id(handle, "doc_delete", "删除文档...", true, None::<&str>)?)
-                .item(&MenuItem::with_id(handle, "doc_duplicate", "复制文档", true, None::<&str>)?)
-                .item(&MenuItem::with_id(handle, "doc_move_to", "移动文档到...", true, None::<&str>)?)
-                .item(&MenuItem::with_id(handle, "doc_copy_to", "复制文档到...", true, None::<&str>)?)
-                .separator()
-                // ── 关闭 ──
-                .item(&MenuItem::with_id(handle, "close_tab", "关闭文档", true, Some("CmdOrCtrl+W"))?)
-                .build()?;
-
-            // 编辑菜单（使用内置 PredefinedMenuItem 以确保剪贴板操作在所有输入框中正常工作）
-            let edit_menu = SubmenuBuilder::new(handle, "编辑")
-                .undo()
-                .redo()
-                .separator()
-                .cut()
-                .copy()
-                .paste()
-                .select_all()
-                .separator()
-                .item(&MenuItem::with_id(handle, "find", "查找...", true, Some("CmdOrCtrl+F"))?)
-                .build()?;
-
-            // 视图菜单
-            let view_menu = SubmenuBuilder::new(handle, "视图")
-                .item(&MenuItem::with_id(handle, "toggle_sidebar", "切换侧边栏", true, Some("CmdOrCtrl+B"))?)
-                .item(&MenuItem::with_id(handle, "toggle_chat", "切换 AI 助手", true, Some("CmdOrCtrl+J"))?)
-                .separator()
-                .item(&MenuItem::with_id(handle, "toggle_layout", "切换布局", true, Some("CmdOrCtrl+L"))?)
-                .item(&MenuItem::with_id(handle, "version_history", "版本历史", true, Some("CmdOrCtrl+H"))?)
-                .separator()
-                .item(&MenuItem::with_id(handle, "view_editor", "正文区", true, None::<&str>)?)
-                .item(&MenuItem::with_id(handle, "view_plugins", "插件区", true, None::<&str>)?)
-                .item(&MenuItem::with_id(handle, "view_composer", "合并区", true, None::<&str>)?)
-                .build()?;
-
-            // 帮助菜单
-            let help_menu = SubmenuBuilder::new(handle, "帮助")
-                .item(&MenuItem::with_id(handle, "shortcuts_ref", "快捷键参考", true, None::<&str>)?)
-                .separator()
-                .item(&MenuItem::with_id(handle, "about", "关于 AiDocPlus", true, None::<&str>)?)
-                .build()?;
-
-            let menu = MenuBuilder::new(handle)
-                .item(&app_menu)
-                .item(&file_menu)
-                .item(&edit_menu)
-                .item(&view_menu)
-                .item(&help_menu)
-                .build()?;
-
+            // ── 构建原生系统菜单（快捷键来自后端设置，可由用户自定义） ──
+            // 菜单项句柄保存在 MenuState 中，供前端控制启用/勾选状态
+            app.manage(menu::MenuState::default());
+            let menu = menu::build_menu(app.handle(), &app_settings.keybindings)?;
             app.set_menu(menu)?;
 
             // 监听菜单事件，转发到前端
             app.on_menu_event(move |app_handle, event| {
                 let id = event.id().0.as_str();
+                // 托盘菜单事件由 tray 模块处理
+                if id.starts_with(tray::TRAY_ITEM_PREFIX) {
+                    return;
+                }
+                // 快速记录窗口由后端直接打开，主窗口隐藏时也可使用
+                if id == "quick_capture" {
+                    if let Err(e) = quick_capture::open_window(app_handle) {
+                        tracing::warn!("{}", e);
+                    }
+                    return;
+                }
                 // 将菜单事件作为自定义事件发送到前端
                 if let Some(window) = app_handle.get_webview_window("main") {
                     let _ = window.emit("menu-event", id);
                 }
             });
 
+            std::thread::spawn(print::cleanup_temp_files);
+
+            // aidocplus:// 链接：注册协议，并处理通过启动参数传入的链接
+            std::thread::spawn(deep_link::register_scheme);
+            for link in deep_link::links_from_args() {
+                deep_link::handle(app.handle(), &link);
+            }
+            single_instance::listen(app.handle());
+
+            // 本地 HTTP API（默认关闭）
+            let api = app_settings.api_server.clone();
+            let api_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = api_server::apply(&api_handle, api.enabled, api.port).await {
+                    tracing::warn!("{}", e);
+                }
+            });
+
+            // 系统托盘
+            if let Err(e) = tray::init(app.handle()) {
+                tracing::warn!("创建系统托盘失败: {}", e);
+            }
+
+            // 快速记录的全局快捷键：其他应用在前台时也能呼出
+            if let Err(e) = quick_capture::register_shortcut(app.handle(), &app_settings.keybindings) {
+                tracing::warn!("{}", e);
+            }
+
             #[cfg(debug_assertions)]
             {
                 let window = app.get_webview_window("main").unwrap();
@@ -195,7 +276,16 @@ fn main() {
 
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
+        .on_window_event(|window, event| {
+            // 开启「关闭时最小化到托盘」后，关闭主窗口只隐藏
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                if window.label() == "main" && tray::should_hide_on_close(window.app_handle()) {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
+            }
+        })
+        .invoke_handler(crash_report::track_commands(tauri::generate_handler![
             // File system commands
             read_directory,
             read_file,
@@ -216,13 +306,21 @@ fn main() {
 
             // Document commands
             create_document,
+            export_chat_to_document,
             save_document,
             delete_document,
             rename_document,
             get_document,
             list_documents,
+            read_document_content,
+            append_document_content,
+            lock_document,
+            unlock_document,
             move_document,
             copy_document,
+            get_attachment_thumbnail,
+            set_document_tags,
+            bulk_document_operation,
 
             // Version commands
             create_version,
@@ -232,36 +330,180 @@ fn main() {
 
             // Export commands
             export_document,
+            export_documents_batch,
+            export_merged_documents,
+            cancel_export,
             export_document_native,
             export_and_open,
+            detect_pdf_fonts,
+            list_export_styles,
+            save_export_style,
+            delete_export_style,
+            list_code_themes,
+            list_pptx_themes,
+            suggest_export_filename,
             write_binary_file,
             open_file_with_app,
             get_temp_dir,
 
+            // Spellcheck commands
+            check_spelling,
+            list_spelling_languages,
+            get_custom_words,
+            add_custom_word,
+            remove_custom_word,
+
+            // Writing activity commands
+            get_writing_activity,
+            get_goal_status,
+            start_focus_session,
+            stop_focus_session,
+            get_active_focus_session,
+            get_focus_history,
+
+            // Backup commands
+            export_app_data,
+            read_app_data_manifest,
+            import_app_data,
+
+            // Integration commands
+            publish_document,
+
+            // Citation commands
+            import_bibtex,
+            search_references,
+            delete_reference,
+            insert_citation,
+            render_bibliography,
+
+            // Glossary commands
+            list_glossary,
+            save_glossary_entry,
+            delete_glossary_entry,
+            check_terminology,
+            create_conversation,
+            append_conversation_messages,
+            get_conversation,
+            list_conversations,
+            rename_conversation,
+            delete_conversation,
+            list_scripts,
+            save_script,
+            delete_script,
+            run_script,
+
+            // Markdown format commands
+            format_markdown,
+            analyze_readability,
+            get_outline,
+            restructure_by_outline,
+
+            // Print commands
+            print_document,
+            list_printers,
+
             // AI commands
             chat,
             chat_stream,
             generate_content,
             generate_content_stream,
+            auto_tag_document,
             stop_ai_stream,
             test_api_connection,
+            list_models,
+            transcribe_audio,
+            summarize_document,
+            translate_document,
+            apply_ai_edit,
+            generate_outline,
+            estimate_request_cost,
+            get_project_ai_spend,
+            clear_ai_cache,
+            test_web_search,
+            get_provider_health,
+            clear_provider_health,
 
             // Import commands
             import_file,
+            import_markdown_vault,
 
             // Search commands
             search_documents,
             get_search_suggestions,
+            find_similar_documents,
 
             // Workspace commands
             save_workspace,
             load_workspace,
             clear_workspace,
+            stage_draft,
+            discard_draft,
+            load_drafts,
+            save_project_session_state,
+            load_project_session_state,
+
+            // Settings commands
+            get_settings,
+            update_settings,
+            reset_settings,
+            get_menu_keybindings,
+
+            // Quick capture commands
+            open_quick_capture,
+            append_to_inbox,
+
+            // Clipboard commands
+            paste_clipboard_image,
+            convert_clipboard_to_markdown,
+            copy_wechat_html,
+
+            // Local API commands
+            get_api_server_status,
+            regenerate_api_token,
+
+            // Deep link commands
+            take_pending_deep_links,
+            open_deep_link,
+
+            // Crash report commands
+            get_crash_reports,
+            mark_crash_reports_seen,
+            delete_crash_report,
+
+            // Update commands
+            check_for_updates,
+
+            // Logging commands
+            get_recent_logs,
+            get_log_directory,
+
+            // Menu state commands
+            set_menu_item_enabled,
+            set_menu_item_checked,
+            update_menu_items,
+
+            // Secrets commands（只返回脱敏值）
+            set_secret,
+            list_secrets,
+            get_secret_info,
+            delete_secret,
+            set_api_key,
+            get_api_key,
+            delete_api_key,
 
             // Plugin commands
             list_plugins,
             set_plugin_enabled,
             sync_plugin_manifests,
+            link_dev_plugin,
+            unlink_dev_plugin,
+            list_dev_plugins,
+            register_plugin_migration,
+            run_plugin_migrations,
+            plugin_subscribe,
+            plugin_unsubscribe,
+            plugin_emit,
+            respond_plugin_tool_call,
 
             // Template commands
             list_templates,
@@ -270,17 +512,37 @@ fn main() {
             update_template,
             delete_template,
             duplicate_template,
+            set_template_prompt_template,
+            render_prompt_template,
             save_template_from_document,
             create_document_from_template,
             list_template_categories,
             create_template_category,
             update_template_category,
+            set_template_category_prompt_template,
             delete_template_category,
             reorder_template_categories,
 
             // Email commands
             test_smtp_connection,
             send_email,
+            save_email_account,
+            list_email_accounts,
+            authorize_email_account,
+            delete_email_account,
+            list_sent_emails,
+            retry_email,
+            delete_sent_email,
+            schedule_email,
+            list_scheduled_emails,
+            cancel_scheduled_email,
+            send_email_batch,
+            import_emails_from_imap,
+            save_email_contact,
+            list_email_contacts,
+            delete_email_contact,
+            list_email_contact_groups,
+            search_email_contacts,
 
             // Pandoc commands
             check_pandoc,
@@ -294,7 +556,23 @@ fn main() {
             resource_stats,
             resource_categories,
             resource_rebuild_index,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+            resource_materialize_bundled,
+            resource_install_history,
+
+            // Background job commands
+            cancel_job,
+            list_jobs,
+        ]))
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app_handle, event| {
+            // macOS 通过系统事件传入 aidocplus:// 链接
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Opened { urls } = &event {
+                for url in urls {
+                    deep_link::handle(app_handle, url.as_str());
+                }
+            }
+            let _ = (app_handle, event);
+        });
 }
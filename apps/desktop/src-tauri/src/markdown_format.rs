@@ -0,0 +1,351 @@
+// Markdown 规范化与检查
+// 标题、列表符号、表格对齐、中英文间距（盘古之白）、行尾空白、空行；
+// 代码块与 YAML front matter 原样保留
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// 启用的规则（默认全部启用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FormatRules {
+    /// `#标题` 补空格、多余空格合并、去掉结尾的 #
+    pub headings: bool,
+    /// 无序列表统一使用 `-`
+    pub list_markers: bool,
+    /// 表格列宽对齐（中日韩字符按双倍宽度计算）
+    pub tables: bool,
+    /// 中日韩文字与英文、数字之间加空格
+    pub cjk_spacing: bool,
+    /// 去掉行尾空白（保留两个空格的硬换行）
+    pub trailing_whitespace: bool,
+    /// 标题前后保留空行，连续空行合并为一行，文末保留一个换行
+    pub blank_lines: bool,
+}
+
+impl Default for FormatRules {
+    fn default() -> Self {
+        Self {
+            headings: true,
+            list_markers: true,
+            tables: true,
+            cjk_spacing: true,
+            trailing_whitespace: true,
+            blank_lines: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatIssue {
+    /// 行号（从 1 开始，对应原文）
+    pub line: usize,
+    pub rule: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatResult {
+    pub content: String,
+    pub changed: bool,
+    pub issues: Vec<FormatIssue>,
+}
+
+fn re(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).expect("invalid markdown_format regex"))
+}
+
+fn heading_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    re(&RE, r"^(#{1,6})[ \t]*(.*?)(?:[ \t]+#+)?[ \t]*$")
+}
+
+fn list_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    re(&RE, r"^(\s*)[*+](\s+)")
+}
+
+fn hr_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    re(&RE, r"^\s*([*_-])(\s*[*_-]){2,}\s*$")
+}
+
+fn table_sep_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    re(&RE, r"^\s*\|?\s*:?-+:?\s*(\|\s*:?-+:?\s*)*\|?\s*$")
+}
+
+/// 盘古之白时需要保护的片段：行内代码、链接地址、尖括号、网址
+fn protected_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    re(&RE, r"`[^`]*`|\]\([^)]*\)|<[^>]*>|[A-Za-z][A-Za-z0-9+.-]*://\S+")
+}
+
+fn cjk_latin_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    re(&RE, r"([\p{Han}\p{Hiragana}\p{Katakana}])([A-Za-z0-9])")
+}
+
+fn latin_cjk_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    re(&RE, r"([A-Za-z0-9])([\p{Han}\p{Hiragana}\p{Katakana}])")
+}
+
+fn issue(issues: &mut Vec<FormatIssue>, line: usize, rule: &str, message: &str) {
+    issues.push(FormatIssue { line: line + 1, rule: rule.to_string(), message: message.to_string() });
+}
+
+fn is_fence(line: &str) -> bool {
+    let t = line.trim_start();
+    t.starts_with("```") || t.starts_with("~~~")
+}
+
+fn is_heading(line: &str) -> bool {
+    heading_re().is_match(line) && !line.starts_with("#######")
+}
+
+/// 规范化 Markdown，并返回每处改动对应的问题
+pub fn format(content: &str, rules: &FormatRules) -> FormatResult {
+    let mut issues = Vec::new();
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    // 不参与处理的行：front matter 与代码块
+    let mut frozen = vec![false; lines.len()];
+
+    let mut in_fence = false;
+    let mut in_front_matter = lines.first().is_some_and(|l| l.trim() == "---");
+    for (i, line) in lines.iter().enumerate() {
+        if in_front_matter {
+            frozen[i] = true;
+            if i > 0 && (line.trim() == "---" || line.trim() == "...") {
+                in_front_matter = false;
+            }
+            continue;
+        }
+        if is_fence(line) {
+            frozen[i] = true;
+            in_fence = !in_fence;
+            continue;
+        }
+        frozen[i] = in_fence;
+    }
+
+    for (i, line) in lines.iter_mut().enumerate() {
+        if frozen[i] {
+            continue;
+        }
+        if rules.trailing_whitespace {
+            let trimmed = line.trim_end();
+            let fixed = if !trimmed.is_empty() && line[trimmed.len()..].starts_with("  ") && !line[trimmed.len()..].contains('\t') {
+                format!("{}  ", trimmed)
+            } else {
+                trimmed.to_string()
+            };
+            if fixed != *line {
+                *line = fixed;
+                issue(&mut issues, i, "trailing-whitespace", "行尾有多余空白");
+            }
+        }
+        if rules.headings && is_heading(line) {
+            if let Some(caps) = heading_re().captures(line) {
+                let text = caps.get(2).map_or("", |m| m.as_str());
+                if !text.is_empty() {
+                    let fixed = format!("{} {}", &caps[1], text);
+                    if fixed != line.trim_end() {
+                        *line = fixed;
+                        issue(&mut issues, i, "heading", "标题格式不规范（# 后保留一个空格，去掉结尾的 #）");
+                    }
+                }
+            }
+        }
+        if rules.list_markers && !hr_re().is_match(line) && list_re().is_match(line) {
+            *line = list_re().replace(line, "${1}-${2}").to_string();
+            issue(&mut issues, i, "list-marker", "无序列表应使用 - 作为列表符号");
+        }
+        if rules.cjk_spacing {
+            let fixed = pangu(line);
+            if fixed != *line {
+                *line = fixed;
+                issue(&mut issues, i, "cjk-spacing", "中英文之间缺少空格");
+            }
+        }
+    }
+
+    if rules.tables {
+        align_tables(&mut lines, &frozen, &mut issues);
+    }
+
+    let mut output: Vec<String> = Vec::with_capacity(lines.len());
+    if rules.blank_lines {
+        for (i, line) in lines.iter().enumerate() {
+            let blank = line.trim().is_empty();
+            let after_heading = i > 0 && !frozen[i - 1] && is_heading(&lines[i - 1]);
+            if after_heading && !blank {
+                output.push(String::new());
+                issue(&mut issues, i, "blank-lines", "标题后应有空行");
+            }
+            let prev_blank = output.last().is_none_or(|l| l.trim().is_empty());
+            if !frozen[i] {
+                if blank && prev_blank {
+                    issue(&mut issues, i, "blank-lines", "多余的空行");
+                    continue;
+                }
+                if is_heading(line) && !prev_blank {
+                    output.push(String::new());
+                    issue(&mut issues, i, "blank-lines", "标题前应有空行");
+                }
+            }
+            output.push(line.clone());
+        }
+        while output.last().is_some_and(|l| l.trim().is_empty()) {
+            output.pop();
+        }
+    } else {
+        output = lines;
+    }
+
+    let mut formatted = output.join("\n");
+    if rules.blank_lines || content.ends_with('\n') {
+        formatted.push('\n');
+    }
+    if content.trim().is_empty() {
+        formatted = content.to_string();
+    }
+    issues.sort_by_key(|i| i.line);
+    FormatResult { changed: formatted != content, content: formatted, issues }
+}
+
+/// 中日韩文字与英文、数字之间加空格，保护行内代码和链接
+fn pangu(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + 8);
+    let mut last = 0;
+    let space = |s: &str| {
+        let s = cjk_latin_re().replace_all(s, "$1 $2");
+        latin_cjk_re().replace_all(&s, "$1 $2").to_string()
+    };
+    for m in protected_re().find_iter(line) {
+        out.push_str(&space(&line[last..m.start()]));
+        out.push_str(m.as_str());
+        last = m.end();
+    }
+    out.push_str(&space(&line[last..]));
+    out
+}
+
+/// 显示宽度：中日韩及全角字符按 2 计
+fn display_width(s: &str) -> usize {
+    s.chars()
+        .map(|c| match c as u32 {
+            0x1100..=0x115F | 0x2E80..=0x303E | 0x3041..=0x33FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF | 0xFE30..=0xFE4F | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6 => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+/// 按未转义的 | 拆分单元格
+fn split_cells(line: &str) -> Vec<String> {
+    let t = line.trim();
+    let t = t.strip_prefix('|').unwrap_or(t);
+    let t = if t.ends_with('|') && !t.ends_with("\\|") { &t[..t.len() - 1] } else { t };
+    let mut cells = Vec::new();
+    let mut current = String::new();
+    let mut chars = t.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                current.push('\\');
+                current.push(chars.next().unwrap_or('|'));
+            }
+            '|' => cells.push(std::mem::take(&mut current).trim().to_string()),
+            _ => current.push(c),
+        }
+    }
+    cells.push(current.trim().to_string());
+    cells
+}
+
+#[derive(Clone, Copy)]
+enum Align {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+fn align_tables(lines: &mut [String], frozen: &[bool], issues: &mut Vec<FormatIssue>) {
+    let mut i = 0;
+    while i + 1 < lines.len() {
+        let is_table_start = !frozen[i]
+            && lines[i].trim_start().starts_with('|')
+            && table_sep_re().is_match(&lines[i + 1])
+            && lines[i + 1].contains('-');
+        if !is_table_start {
+            i += 1;
+            continue;
+        }
+        let mut end = i + 2;
+        while end < lines.len() && !frozen[end] && lines[end].trim_start().starts_with('|') {
+            end += 1;
+        }
+
+        let rows: Vec<Vec<String>> = lines[i..end].iter().map(|l| split_cells(l)).collect();
+        let cols = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let aligns: Vec<Align> = (0..cols)
+            .map(|c| {
+                let sep = rows[1].get(c).map(String::as_str).unwrap_or("");
+                match (sep.starts_with(':'), sep.ends_with(':')) {
+                    (true, true) => Align::Center,
+                    (false, true) => Align::Right,
+                    (true, false) => Align::Left,
+                    _ => Align::None,
+                }
+            })
+            .collect();
+        let widths: Vec<usize> = (0..cols)
+            .map(|c| {
+                rows.iter()
+                    .enumerate()
+                    .filter(|(r, _)| *r != 1)
+                    .map(|(_, row)| row.get(c).map_or(0, |s| display_width(s)))
+                    .max()
+                    .unwrap_or(0)
+                    .max(3)
+            })
+            .collect();
+
+        for (r, row) in rows.iter().enumerate() {
+            let cells: Vec<String> = (0..cols)
+                .map(|c| {
+                    let w = widths[c];
+                    if r == 1 {
+                        return match aligns[c] {
+                            Align::None => "-".repeat(w),
+                            Align::Left => format!(":{}", "-".repeat(w - 1)),
+                            Align::Right => format!("{}:", "-".repeat(w - 1)),
+                            Align::Center => format!(":{}:", "-".repeat(w - 2)),
+                        };
+                    }
+                    let text = row.get(c).map(String::as_str).unwrap_or("");
+                    let pad = w - display_width(text);
+                    match aligns[c] {
+                        Align::Right => format!("{}{}", " ".repeat(pad), text),
+                        Align::Center => format!("{}{}{}", " ".repeat(pad / 2), text, " ".repeat(pad - pad / 2)),
+                        _ => format!("{}{}", text, " ".repeat(pad)),
+                    }
+                })
+                .collect();
+            let fixed = format!("| {} |", cells.join(" | "));
+            if fixed != lines[i + r] {
+                lines[i + r] = fixed;
+                if !issues.iter().any(|x| x.line == i + 1 && x.rule == "table") {
+                    issue(issues, i, "table", "表格列未对齐");
+                }
+            }
+        }
+        i = end;
+    }
+}
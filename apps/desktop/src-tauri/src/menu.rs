@@ -0,0 +1,286 @@
+use crate::i18n;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::menu::{CheckMenuItem, Menu, MenuBuilder, MenuItem, Submenu, SubmenuBuilder};
+use tauri::{AppHandle, Manager, Wry};
+
+/// 可自定义快捷键的菜单项及默认快捷键
+const DEFAULT_ACCELERATORS: &[(&str, &str)] = &[
+    ("settings", "CmdOrCtrl+,"),
+    ("new_project", "CmdOrCtrl+Shift+N"),
+    ("new_document", "CmdOrCtrl+N"),
+    ("new_from_template", "CmdOrCtrl+Shift+T"),
+    ("save", "CmdOrCtrl+S"),
+    ("save_all", "CmdOrCtrl+Shift+S"),
+    ("import_file", "CmdOrCtrl+I"),
+    ("quick_capture", "CmdOrCtrl+Shift+Space"),
+    ("close_tab", "CmdOrCtrl+W"),
+    ("find", "CmdOrCtrl+F"),
+    ("toggle_sidebar", "CmdOrCtrl+B"),
+    ("toggle_chat", "CmdOrCtrl+J"),
+    ("toggle_layout", "CmdOrCtrl+L"),
+    ("version_history", "CmdOrCtrl+H"),
+];
+
+/// 没有默认快捷键、但允许用户绑定的菜单项
+const UNBOUND_ITEMS: &[&str] = &[
+    "export_md", "export_html", "export_docx", "export_pdf", "export_txt",
+    "project_rename", "project_delete", "project_export_zip", "project_import_zip", "project_backup",
+    "save_as_template", "manage_templates",
+    "doc_rename", "doc_delete", "doc_duplicate", "doc_move_to", "doc_copy_to",
+    "view_editor", "view_plugins", "view_composer", "shortcuts_ref", "about",
+];
+
+pub fn is_configurable(id: &str) -> bool {
+    DEFAULT_ACCELERATORS.iter().any(|(k, _)| *k == id) || UNBOUND_ITEMS.contains(&id)
+}
+
+/// 合并默认值与用户配置后的完整快捷键表（空字符串表示不绑定）
+pub fn effective_accelerators(keybindings: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut result: HashMap<String, String> = DEFAULT_ACCELERATORS
+        .iter()
+        .map(|(id, accel)| (id.to_string(), accel.to_string()))
+        .collect();
+    for (id, accel) in keybindings {
+        if is_configurable(id) {
+            result.insert(id.clone(), accel.trim().to_string());
+        }
+    }
+    result.retain(|_, accel| !accel.is_empty());
+    result
+}
+
+/// 菜单项句柄（普通项、勾选项或子菜单）
+#[derive(Clone)]
+pub enum MenuItemHandle {
+    Normal(MenuItem<Wry>),
+    Check(CheckMenuItem<Wry>),
+    Submenu(Submenu<Wry>),
+}
+
+impl MenuItemHandle {
+    fn set_enabled(&self, enabled: bool) -> tauri::Result<()> {
+        match self {
+            MenuItemHandle::Normal(i) => i.set_enabled(enabled),
+            MenuItemHandle::Check(i) => i.set_enabled(enabled),
+            MenuItemHandle::Submenu(i) => i.set_enabled(enabled),
+        }
+    }
+}
+
+/// 前端对菜单项状态的修改；为空的字段保持不变
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MenuItemUpdate {
+    pub id: String,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    pub checked: Option<bool>,
+}
+
+/// 菜单项句柄及前端设置过的状态（重建菜单后重新应用）
+#[derive(Default)]
+pub struct MenuState {
+    items: Mutex<HashMap<String, MenuItemHandle>>,
+    overrides: Mutex<HashMap<String, MenuItemUpdate>>,
+}
+
+impl MenuState {
+    /// 更新菜单项的启用/勾选状态
+    pub fn apply(&self, update: MenuItemUpdate) -> Result<(), String> {
+        {
+            let items = self.items.lock().map_err(|e| format!("锁获取失败: {}", e))?;
+            let item = items.get(&update.id).ok_or_else(|| format!("菜单项不存在: {}", update.id))?;
+            apply_to_item(item, &update)?;
+        }
+        let mut overrides = self.overrides.lock().map_err(|e| format!("锁获取失败: {}", e))?;
+        let entry = overrides.entry(update.id.clone()).or_insert_with(|| MenuItemUpdate { id: update.id.clone(), ..Default::default() });
+        entry.enabled = update.enabled.or(entry.enabled);
+        entry.checked = update.checked.or(entry.checked);
+        Ok(())
+    }
+
+    fn replace_items(&self, items: HashMap<String, MenuItemHandle>) {
+        let overrides = self.overrides.lock().map(|o| o.clone()).unwrap_or_default();
+        for update in overrides.values() {
+            if let Some(item) = items.get(&update.id) {
+                if let Err(e) = apply_to_item(item, update) {
+                    tracing::warn!("恢复菜单项 {} 状态失败: {}", update.id, e);
+                }
+            }
+        }
+        if let Ok(mut guard) = self.items.lock() {
+            *guard = items;
+        }
+    }
+}
+
+fn apply_to_item(item: &MenuItemHandle, update: &MenuItemUpdate) -> Result<(), String> {
+    if let Some(enabled) = update.enabled {
+        item.set_enabled(enabled).map_err(|e| e.to_string())?;
+    }
+    if let Some(checked) = update.checked {
+        match item {
+            MenuItemHandle::Check(i) => i.set_checked(checked).map_err(|e| e.to_string())?,
+            _ => return Err(format!("菜单项 {} 不支持勾选", update.id)),
+        }
+    }
+    Ok(())
+}
+
+/// 按快捷键配置和当前语言构建原生菜单，并把菜单项句柄登记到 MenuState
+pub fn build_menu(handle: &AppHandle, keybindings: &HashMap<String, String>) -> tauri::Result<Menu<Wry>> {
+    let accelerators = effective_accelerators(keybindings);
+    let registry: RefCell<HashMap<String, MenuItemHandle>> = RefCell::new(HashMap::new());
+
+    let item = |id: &str| {
+        register(&registry, &accelerators, id, MenuItemHandle::Normal, |accel| {
+            MenuItem::with_id(handle, id, label(id), true, accel)
+        })
+    };
+    let check = |id: &str| {
+        register(&registry, &accelerators, id, MenuItemHandle::Check, |accel| {
+            CheckMenuItem::with_id(handle, id, label(id), true, false, accel)
+        })
+    };
+
+    // macOS 应用菜单
+    let app_menu = SubmenuBuilder::new(handle, "AiDocPlus")
+        .about(None)
+        .separator()
+        .item(&item("settings")?)
+        .separator()
+        .services()
+        .separator()
+        .hide()
+        .hide_others()
+        .show_all()
+        .separator()
+        .quit()
+        .build()?;
+
+    // 文件菜单
+    let export_sub = SubmenuBuilder::with_id(handle, "export", label("export"))
+        .item(&item("export_md")?)
+        .item(&item("export_html")?)
+        .item(&item("export_docx")?)
+        .item(&item("export_pdf")?)
+        .item(&item("export_txt")?)
+        .build()?;
+    registry.borrow_mut().insert("export".to_string(), MenuItemHandle::Submenu(export_sub.clone()));
+
+    let file_menu = SubmenuBuilder::new(handle, label("file"))
+        // ── 新建 ──
+        .item(&item("new_project")?)
+        .item(&item("new_document")?)
+        .item(&item("new_from_template")?)
+        .item(&item("quick_capture")?)
+        .separator()
+        // ── 保存 ──
+        .item(&item("save")?)
+        .item(&item("save_all")?)
+        .separator()
+        // ── 导入/导出文件 ──
+        .item(&item("import_file")?)
+        .item(&export_sub)
+        .separator()
+        // ── 项目管理 ──
+        .item(&item("project_rename")?)
+        .item(&item("project_delete")?)
+        .item(&item("project_export_zip")?)
+        .item(&item("project_import_zip")?)
+        .item(&item("project_backup")?)
+        .separator()
+        // ── 模板 ──
+        .item(&item("save_as_template")?)
+        .item(&item("manage_templates")?)
+        .separator()
+        // ── 文档管理 ──
+        .item(&item("doc_rename")?)
+        .item(&item("doc_delete")?)
+        .item(&item("doc_duplicate")?)
+        .item(&item("doc_move_to")?)
+        .item(&item("doc_copy_to")?)
+        .separator()
+        // ── 关闭 ──
+        .item(&item("close_tab")?)
+        .build()?;
+
+    // 编辑菜单（使用内置 PredefinedMenuItem 以确保剪贴板操作在所有输入框中正常工作）
+    let edit_menu = SubmenuBuilder::new(handle, label("edit"))
+        .undo()
+        .redo()
+        .separator()
+        .cut()
+        .copy()
+        .paste()
+        .select_all()
+        .separator()
+        .item(&item("find")?)
+        .build()?;
+
+    // 视图菜单
+    let view_menu = SubmenuBuilder::new(handle, label("view"))
+        .item(&check("toggle_sidebar")?)
+        .item(&check("toggle_chat")?)
+        .separator()
+        .item(&item("toggle_layout")?)
+        .item(&check("version_history")?)
+        .separator()
+        .item(&check("view_editor")?)
+        .item(&check("view_plugins")?)
+        .item(&check("view_composer")?)
+        .build()?;
+
+    // 帮助菜单
+    let help_menu = SubmenuBuilder::new(handle, label("help"))
+        .item(&item("shortcuts_ref")?)
+        .separator()
+        .item(&item("about")?)
+        .build()?;
+
+    let menu = MenuBuilder::new(handle)
+        .item(&app_menu)
+        .item(&file_menu)
+        .item(&edit_menu)
+        .item(&view_menu)
+        .item(&help_menu)
+        .build()?;
+
+    if let Some(state) = handle.try_state::<MenuState>() {
+        state.replace_items(registry.into_inner());
+    }
+    Ok(menu)
+}
+
+/// 当前语言的菜单文本
+fn label(id: &str) -> &'static str {
+    i18n::t(&format!("menu.{}", id))
+}
+
+/// 创建菜单项并登记句柄；用户配置的快捷键无法解析时回退到默认值
+fn register<T: Clone>(
+    registry: &RefCell<HashMap<String, MenuItemHandle>>,
+    accelerators: &HashMap<String, String>,
+    id: &str,
+    wrap: fn(T) -> MenuItemHandle,
+    build: impl Fn(Option<&str>) -> tauri::Result<T>,
+) -> tauri::Result<T> {
+    let accel = accelerators.get(id).map(String::as_str);
+    let built = build(accel).or_else(|e| {
+        tracing::warn!("快捷键 {:?} 无效（{}），使用默认值: {}", accel, id, e);
+        build(DEFAULT_ACCELERATORS.iter().find(|(k, _)| *k == id).map(|(_, v)| *v))
+    })?;
+    registry.borrow_mut().insert(id.to_string(), wrap(built.clone()));
+    Ok(built)
+}
+
+/// 快捷键或语言变更后重建菜单（保留前端设置过的启用/勾选状态）
+pub fn rebuild(handle: &AppHandle, keybindings: &HashMap<String, String>) -> Result<(), String> {
+    let menu = build_menu(handle, keybindings).map_err(|e| format!("构建菜单失败: {}", e))?;
+    handle.set_menu(menu).map_err(|e| format!("设置菜单失败: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,71 @@
+/// 封面模块
+/// 按文档属性和封面设置生成封面内容（标题、作者、单位、日期、徽标），供 DOCX 和 HTML / PDF 导出使用
+use super::DocumentInfo;
+use crate::settings::CoverSettings;
+use base64::Engine;
+
+/// 封面徽标（统一转换为 PNG）
+#[derive(Debug, Clone)]
+pub struct Logo {
+    pub png: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cover {
+    pub title: String,
+    pub author: String,
+    pub organization: String,
+    pub date: String,
+    pub logo: Option<Logo>,
+}
+
+/// 读取徽标图片并转换为 PNG
+fn load_logo(path: &str) -> Result<Logo, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("读取封面徽标失败: {}", e))?;
+    let img = image::load_from_memory(&bytes).map_err(|e| format!("无法识别封面徽标图片: {}", e))?;
+    let mut png = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut png, image::ImageFormat::Png).map_err(|e| format!("转换封面徽标失败: {}", e))?;
+    Ok(Logo { png: png.into_inner(), width: img.width(), height: img.height() })
+}
+
+impl Cover {
+    /// 生成封面；未启用封面时返回 None，日期为导出当天
+    pub fn build(title: &str, info: &DocumentInfo, settings: &CoverSettings) -> Result<Option<Cover>, String> {
+        if !settings.enabled {
+            return Ok(None);
+        }
+        let logo = match settings.logo_path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+            Some(path) => Some(load_logo(path)?),
+            None => None,
+        };
+        Ok(Some(Cover {
+            title: title.trim().to_string(),
+            author: info.author.trim().to_string(),
+            organization: settings.organization.trim().to_string(),
+            date: chrono::Local::now().format("%Y年%-m月%-d日").to_string(),
+            logo,
+        }))
+    }
+
+    /// HTML 封面，打印时单独占一页
+    pub fn to_html(&self) -> String {
+        let escape = |s: &str| s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+        let mut html = String::from("<section class=\"cover\">\n");
+        if let Some(logo) = &self.logo {
+            html.push_str(&format!(
+                "<img class=\"cover-logo\" src=\"data:image/png;base64,{}\" alt=\"\">\n",
+                base64::engine::general_purpose::STANDARD.encode(&logo.png)
+            ));
+        }
+        html.push_str(&format!("<p class=\"cover-title\">{}</p>\n", escape(&self.title)));
+        for (class, text) in [("cover-author", &self.author), ("cover-organization", &self.organization), ("cover-date", &self.date)] {
+            if !text.is_empty() {
+                html.push_str(&format!("<p class=\"{}\">{}</p>\n", class, escape(text)));
+            }
+        }
+        html.push_str("</section>\n");
+        html
+    }
+}
@@ -2,10 +2,34 @@ use comrak::{parse_document, Arena, Options};
 use comrak::nodes::{AstNode, NodeValue, NodeHeading, ListType};
 use docx_rs::*;
 use std::fs::File;
-use super::styles;
+use std::io::{Cursor, Read, Write};
+use super::cover::Cover;
+use super::images::{self, Images};
+use super::merge;
+use super::reference::ReferenceDoc;
+use super::mermaid::{self, Diagram, Diagrams};
+use super::styles::{self, StyleProfile};
+use super::watermark::Watermark;
+use super::{toc, DocumentInfo, ExportOptions, ProgressFn};
+use crate::settings::HeaderFooterSettings;
 
-/// 将 Markdown 转换为符合公文排版标准的 DOCX 文件
-pub fn export_to_docx(markdown: &str, output_path: &str) -> Result<(), String> {
+/// 将 Markdown 按导出选项中的样式方案转换为 DOCX 文件；diagrams 中已渲染的 Mermaid 图表以图片插入，
+/// 按 cover、toc_depth 在正文前插入封面和目录，按 watermark 在各页眉中加水印，并把标题、作者、创建时间写入文档属性；
+/// 本地图片按 images 设置压缩后插入；指定参考文档（reference_docx）时套用其中的样式
+pub fn export_to_docx(
+    markdown: &str,
+    title: &str,
+    output_path: &str,
+    export: &ExportOptions,
+    diagrams: &Diagrams,
+    progress: &ProgressFn,
+) -> Result<(), String> {
+    let profile = &export.style;
+    let cover = Cover::build(title, &export.info, &export.cover)?;
+    let watermark = Watermark::build(&export.watermark)?;
+    let images = images::load_all(markdown, &export.images);
+    let reference = export.reference_docx.as_deref().map(ReferenceDoc::load).transpose()?;
+    let ctx = &Context { profile, diagrams, images: &images, reference: reference.as_ref() };
     let arena = Arena::new();
     let mut options = Options::default();
     options.extension.table = true;
@@ -20,179 +44,440 @@ pub fn export_to_docx(markdown: &str, output_path: &str) -> Result<(), String> {
     // 设置页面尺寸 A4 (twip)
     docx = docx.page_size(styles::mm_to_twip(210.0) as u32, styles::mm_to_twip(297.0) as u32);
 
-    // 设置公文标准页边距
+    // 设置页边距
+    let [top, bottom, left, right] = profile.margins;
     docx = docx.page_margin(
         PageMargin::new()
-            .top(styles::mm_to_twip(styles::PAGE_MARGIN_TOP))
-            .bottom(styles::mm_to_twip(styles::PAGE_MARGIN_BOTTOM))
-            .left(styles::mm_to_twip(styles::PAGE_MARGIN_LEFT))
-            .right(styles::mm_to_twip(styles::PAGE_MARGIN_RIGHT))
+            .top(styles::mm_to_twip(top))
+            .bottom(styles::mm_to_twip(bottom))
+            .left(styles::mm_to_twip(left))
+            .right(styles::mm_to_twip(right))
     );
 
     // 设置默认字体
-    docx = docx.default_fonts(
-        RunFonts::new()
-            .east_asia(styles::FONT_FANGSONG[0])
-            .ascii(styles::FONT_WESTERN)
-            .hi_ansi(styles::FONT_WESTERN)
-    );
+    docx = docx.default_fonts(body_fonts(profile));
 
-    // 设置默认字号 (3号 = 16pt = 32 half-points)
-    docx = docx.default_size(styles::pt_to_half_point(styles::FONT_SIZE_BODY));
+    // 设置默认字号（公文为 3号 = 16pt = 32 half-points）
+    docx = docx.default_size(styles::pt_to_half_point(profile.body_size));
 
-    // 设置默认行距 (固定值29pt = 580twip，符合公文标准每页22行)
+    // 设置默认行距（公文为固定值29pt = 580twip，每页22行）
     docx = docx.default_line_spacing(
         LineSpacing::new()
             .line_rule(LineSpacingType::Exact)
-            .line(styles::pt_to_twip(styles::LINE_SPACING_PT))
+            .line(styles::pt_to_twip(profile.line_spacing))
             .before(0)
-            .after(0)
+            .after(styles::pt_to_twip(profile.paragraph_spacing) as u32)
+    );
+
+    docx = add_header_footer(docx, profile, &export.header_footer, watermark.as_ref());
+
+    if let Some(cover) = &cover {
+        docx = add_cover(docx, cover, profile);
+    }
+
+    if let Some(depth) = export.toc_depth {
+        docx = add_toc(docx, markdown, depth, profile);
+    }
+
+    // 遍历 AST 生成 DOCX 元素；顶层标题加书签，供目录跳转和更新页码
+    // 进度从 30% 到 90%，每增加 1% 汇报一次
+    let total = root.children().count().max(1);
+    let mut reported = 0;
+    let mut heading_index = 0;
+    for (i, child) in root.children().enumerate() {
+        let percent = 30 + 60 * i / total;
+        if percent > reported {
+            progress(percent as f32, "生成文档内容")?;
+            reported = percent;
+        }
+        let is_heading = matches!(child.data.borrow().value, NodeValue::Heading(_));
+        if is_heading {
+            docx = docx.add_bookmark_start(heading_index, toc::bookmark_name(heading_index));
+        }
+        process_node(child, &mut docx, ctx);
+        if is_heading {
+            docx = docx.add_bookmark_end(heading_index);
+            heading_index += 1;
+        }
+    }
+
+    progress(90.0, "写入文件")?;
+    // docx-rs 不支持设置标题、作者和水印，打包后替换 docProps/core.xml、修改页眉再写入文件
+    let mut packed = Cursor::new(Vec::new());
+    docx.build().pack(&mut packed).map_err(|e| format!("生成 DOCX 失败: {}", e))?;
+    let file = File::create(output_path).map_err(|e| format!("创建文件失败: {}", e))?;
+    write_package(packed.into_inner(), file, title, &export.info, watermark.as_ref(), reference.as_ref())?;
+
+    Ok(())
+}
+
+/// 复制 DOCX 包内各部件，替换其中的核心文档属性；有水印时在各页眉中插入文字水印、把图片水印置于文字下方；
+/// 有参考文档时换用其样式定义和主题
+fn write_package(
+    packed: Vec<u8>,
+    out: File,
+    title: &str,
+    info: &DocumentInfo,
+    watermark: Option<&Watermark>,
+    reference: Option<&ReferenceDoc>,
+) -> Result<(), String> {
+    const CORE_PART: &str = "docProps/core.xml";
+    const THEME_PART: &str = "word/theme/theme1.xml";
+    let mut archive = zip::ZipArchive::new(Cursor::new(packed)).map_err(|e| format!("生成 DOCX 失败: {}", e))?;
+    let mut writer = zip::ZipWriter::new(out);
+    let write_part = |writer: &mut zip::ZipWriter<File>, name: &str, content: &str| {
+        writer
+            .start_file(name, zip::write::FileOptions::default())
+            .and_then(|_| writer.write_all(content.as_bytes()).map_err(Into::into))
+            .map_err(|e| format!("写入文件失败: {}", e))
+    };
+    let theme = reference.and_then(|r| r.theme_xml.as_deref());
+    let mut header_index = 0;
+    for i in 0..archive.len() {
+        let name = archive.by_index_raw(i).map_err(|e| format!("生成 DOCX 失败: {}", e))?.name().to_string();
+        let is_header = name.starts_with("word/header") && name.ends_with(".xml");
+        let patched = match name.as_str() {
+            CORE_PART => continue,
+            "word/styles.xml" if reference.is_some() => reference.map(|r| r.styles_xml.clone()),
+            "[Content_Types].xml" if theme.is_some() => Some(read_part(&mut archive, i)?.replacen(
+                "</Types>",
+                &format!("<Override PartName=\"/{}\" ContentType=\"application/vnd.openxmlformats-officedocument.theme+xml\"/></Types>", THEME_PART),
+                1,
+            )),
+            "word/_rels/document.xml.rels" if theme.is_some() => Some(read_part(&mut archive, i)?.replacen(
+                "</Relationships>",
+                "<Relationship Id=\"rIdAiDocPlusTheme\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/theme\" Target=\"theme/theme1.xml\"/></Relationships>",
+                1,
+            )),
+            _ if is_header && watermark.is_some() => {
+                let xml = read_part(&mut archive, i)?;
+                header_index += 1;
+                watermark.map(|watermark| match watermark.vml_paragraph(header_index) {
+                    Some(para) => xml.replacen("</w:hdr>", &format!("{}</w:hdr>", para), 1),
+                    // 页眉中的浮动图片只有水印
+                    None => xml.replace("behindDoc=\"0\"", "behindDoc=\"1\""),
+                })
+            }
+            _ => None,
+        };
+        match patched {
+            Some(content) => write_part(&mut writer, &name, &content)?,
+            None => {
+                let entry = archive.by_index_raw(i).map_err(|e| format!("生成 DOCX 失败: {}", e))?;
+                writer.raw_copy_file(entry).map_err(|e| format!("写入文件失败: {}", e))?;
+            }
+        }
+    }
+    if let Some(theme) = theme {
+        write_part(&mut writer, THEME_PART, theme)?;
+    }
+    let escape = |s: &str| s.trim().replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    let created = info.created().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let modified = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let core = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:dcmitype="http://purl.org/dc/dcmitype/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"><dc:title>{title}</dc:title><dc:creator>{author}</dc:creator><cp:lastModifiedBy>{author}</cp:lastModifiedBy><cp:revision>1</cp:revision><dcterms:created xsi:type="dcterms:W3CDTF">{created}</dcterms:created><dcterms:modified xsi:type="dcterms:W3CDTF">{modified}</dcterms:modified></cp:coreProperties>"#,
+        title = escape(title),
+        author = escape(&info.author),
+        created = created,
+        modified = modified
+    );
+    write_part(&mut writer, CORE_PART, &core)?;
+    writer.finish().map(|_| ()).map_err(|e| format!("写入文件失败: {}", e))
+}
+
+fn read_part(archive: &mut zip::ZipArchive<Cursor<Vec<u8>>>, index: usize) -> Result<String, String> {
+    let mut xml = String::new();
+    archive
+        .by_index(index)
+        .and_then(|mut entry| entry.read_to_string(&mut xml).map_err(Into::into))
+        .map_err(|e| format!("生成 DOCX 失败: {}", e))?;
+    Ok(xml)
+}
+
+/// 封面：徽标、标题、作者、单位、日期居中排列，封面后分页
+fn add_cover(mut docx: Docx, cover: &Cover, profile: &StyleProfile) -> Docx {
+    const EMU_PER_MM: u32 = 36000;
+    const LOGO_MAX_MM: u32 = 40;
+    let exact = |pt: f32| LineSpacing::new().line_rule(LineSpacingType::Exact).line(styles::pt_to_twip(pt));
+    let blank = |lines: f32| Paragraph::new().add_run(Run::new().add_text("")).line_spacing(exact(profile.line_spacing * lines));
+    docx = docx.add_paragraph(blank(4.0));
+    if let Some(logo) = &cover.logo {
+        let width = LOGO_MAX_MM * EMU_PER_MM;
+        let height = (logo.height as u64 * width as u64 / logo.width.max(1) as u64) as u32;
+        let pic = Pic::new_with_dimensions(logo.png.clone(), logo.width, logo.height).size(width, height);
+        docx = docx.add_paragraph(Paragraph::new().align(AlignmentType::Center).add_run(Run::new().add_image(pic)));
+        docx = docx.add_paragraph(blank(2.0));
+    }
+    let title_style = styles::HeadingStyle { size: profile.heading(1).size * 1.5, bold: true, ..profile.heading(1) };
+    docx = docx.add_paragraph(
+        Paragraph::new()
+            .align(AlignmentType::Center)
+            .line_spacing(exact(profile.heading_line_spacing(title_style.size)))
+            .add_run(style_heading_run(Run::new().add_text(&cover.title), &title_style, profile)),
     );
+    docx = docx.add_paragraph(blank(6.0));
+    for text in [&cover.author, &cover.organization, &cover.date] {
+        if !text.is_empty() {
+            docx = docx.add_paragraph(Paragraph::new().align(AlignmentType::Center).add_run(Run::new().add_text(text)));
+        }
+    }
+    docx.add_paragraph(Paragraph::new().add_run(Run::new().add_break(BreakType::Page)))
+}
 
-    // 添加页脚页码（公文格式：居中，"— X —"）
-    let page_num_run = Run::new()
+/// 页眉页脚文字和页码的 Run
+fn header_footer_run(text: &str, profile: &StyleProfile) -> Run {
+    Run::new()
+        .add_text(text)
+        .size(styles::pt_to_half_point(profile.code_size()))
+        .fonts(body_fonts(profile))
+}
+
+fn field_run(instr: InstrText, profile: &StyleProfile) -> Run {
+    Run::new()
         .add_field_char(FieldCharType::Begin, false)
-        .add_instr_text(InstrText::PAGE(InstrPAGE {}))
+        .add_instr_text(instr)
         .add_field_char(FieldCharType::Separate, false)
         .add_text("1")
         .add_field_char(FieldCharType::End, false)
-        .size(styles::pt_to_half_point(styles::FONT_SIZE_FOOTNOTE))
-        .fonts(RunFonts::new()
-            .east_asia(styles::FONT_FANGSONG[0])
-            .ascii(styles::FONT_WESTERN)
-            .hi_ansi(styles::FONT_WESTERN));
-    let dash_run_left = Run::new()
-        .add_text("— ")
-        .size(styles::pt_to_half_point(styles::FONT_SIZE_FOOTNOTE))
-        .fonts(RunFonts::new()
-            .east_asia(styles::FONT_FANGSONG[0])
-            .ascii(styles::FONT_WESTERN)
-            .hi_ansi(styles::FONT_WESTERN));
-    let dash_run_right = Run::new()
-        .add_text(" —")
-        .size(styles::pt_to_half_point(styles::FONT_SIZE_FOOTNOTE))
-        .fonts(RunFonts::new()
-            .east_asia(styles::FONT_FANGSONG[0])
-            .ascii(styles::FONT_WESTERN)
-            .hi_ansi(styles::FONT_WESTERN));
-    let footer_para = Paragraph::new()
-        .align(AlignmentType::Center)
-        .add_run(dash_run_left)
-        .add_run(page_num_run)
-        .add_run(dash_run_right);
-    let footer = Footer::new().add_paragraph(footer_para);
-    docx = docx.footer(footer);
-
-    // 遍历 AST 生成 DOCX 元素
-    for child in root.children() {
-        process_node(child, &mut docx);
+        .size(styles::pt_to_half_point(profile.code_size()))
+        .fonts(body_fonts(profile))
+}
+
+/// 页码段落；未指定格式时按样式方案（公文为 "— X —"）
+fn page_number_paragraph(format: &str, align: AlignmentType, profile: &StyleProfile) -> Option<Paragraph> {
+    let format = match format {
+        "" if profile.page_number_dashes => "dashes",
+        "" => "plain",
+        other => other,
+    };
+    let page = field_run(InstrText::PAGE(InstrPAGE {}), profile);
+    let para = Paragraph::new().align(align);
+    match format {
+        "dashes" => Some(
+            para.add_run(header_footer_run("— ", profile))
+                .add_run(page)
+                .add_run(header_footer_run(" —", profile)),
+        ),
+        "total" => Some(
+            para.add_run(page)
+                .add_run(header_footer_run(" / ", profile))
+                .add_run(field_run(InstrText::NUMPAGES(InstrNUMPAGES {}), profile)),
+        ),
+        "none" => None,
+        _ => Some(para.add_run(page)),
     }
+}
 
-    // 写入文件
-    let file = File::create(output_path).map_err(|e| format!("创建文件失败: {}", e))?;
-    docx.build().pack(file).map_err(|e| format!("生成 DOCX 失败: {}", e))?;
+fn header_part(text: &str, profile: &StyleProfile, watermark: Option<&Watermark>) -> Header {
+    let mut header = Header::new();
+    if !text.is_empty() {
+        header = header.add_paragraph(Paragraph::new().align(AlignmentType::Center).add_run(header_footer_run(text, profile)));
+    }
+    // 文字水印在打包后写入页眉
+    if let Some(Watermark::Image { png, width, height }) = watermark {
+        header = header.add_paragraph(watermark_image_paragraph(png, *width, *height, profile));
+    }
+    header
+}
 
-    Ok(())
+/// 图片水印：相对版心居中，宽度为版心的 60%，不占位
+fn watermark_image_paragraph(png: &[u8], width: u32, height: u32, profile: &StyleProfile) -> Paragraph {
+    const EMU_PER_MM: f32 = 36000.0;
+    let [_, _, left, right] = profile.margins;
+    let emu_width = ((210.0 - left - right).max(10.0) * 0.6 * EMU_PER_MM) as u32;
+    let emu_height = (height as u64 * emu_width as u64 / width.max(1) as u64) as u32;
+    let pic = Pic::new_with_dimensions(png.to_vec(), width, height)
+        .size(emu_width, emu_height)
+        .floating()
+        .overlapping()
+        .relative_from_h(RelativeFromHType::Margin)
+        .relative_from_v(RelativeFromVType::Margin)
+        .position_h(DrawingPosition::Align(PicAlign::Center))
+        .position_v(DrawingPosition::Align(PicAlign::Center));
+    Paragraph::new().add_run(Run::new().add_image(pic))
+}
+
+fn footer_part(settings: &HeaderFooterSettings, align: AlignmentType, profile: &StyleProfile) -> Footer {
+    let mut footer = Footer::new();
+    if !settings.footer.trim().is_empty() {
+        footer = footer.add_paragraph(
+            Paragraph::new().align(AlignmentType::Center).add_run(header_footer_run(settings.footer.trim(), profile)),
+        );
+    }
+    if let Some(para) = page_number_paragraph(&settings.page_number, align, profile) {
+        footer = footer.add_paragraph(para);
+    }
+    footer
+}
+
+/// 页眉页脚：页眉文字居中；页码默认居中，奇偶页不同时单页居右、双页居左；首页不同时首页留空（水印保留）
+fn add_header_footer(mut docx: Docx, profile: &StyleProfile, settings: &HeaderFooterSettings, watermark: Option<&Watermark>) -> Docx {
+    let header = settings.header.trim();
+    if !header.is_empty() || watermark.is_some() {
+        docx = docx.header(header_part(header, profile, watermark));
+    }
+    if settings.different_odd_even {
+        docx = docx.footer(footer_part(settings, AlignmentType::Right, profile));
+        let even_header = Some(settings.even_header.trim()).filter(|h| !h.is_empty()).unwrap_or(header);
+        if !even_header.is_empty() || watermark.is_some() {
+            docx = docx.even_header(header_part(even_header, profile, watermark));
+        }
+        docx = docx.even_footer(footer_part(settings, AlignmentType::Left, profile));
+    } else {
+        docx = docx.footer(footer_part(settings, AlignmentType::Center, profile));
+    }
+    if settings.different_first_page {
+        docx = docx.first_header(header_part("", profile, watermark)).first_footer(Footer::new());
+    }
+    docx
+}
+
+/// 目录：TOC 域中预先填入各标题（页码在 Word 中更新域后生效），条目链接到标题书签，目录后分页
+fn add_toc(docx: Docx, markdown: &str, depth: u8, profile: &StyleProfile) -> Docx {
+    let entries: Vec<toc::TocEntry> = toc::collect(markdown, depth).into_iter().filter(|e| e.index.is_some()).collect();
+    if entries.is_empty() {
+        return docx;
+    }
+    let title_style = profile.heading(1);
+    let title = Paragraph::new().align(AlignmentType::Center).add_run(style_heading_run(
+        Run::new().add_text(crate::i18n::t("export.toc")),
+        &title_style,
+        profile,
+    ));
+    let mut table = TableOfContents::with_instr_text(&format!("\\o \"1-{}\" \\h \\z \\u", depth))
+        .dirty()
+        .add_before_paragraph(title)
+        .add_after_paragraph(Paragraph::new().add_run(Run::new().add_break(BreakType::Page)));
+    for entry in entries {
+        let index = entry.index.unwrap_or_default();
+        table = table.add_item(
+            TableOfContentsItem::new()
+                .text(entry.title)
+                .level(entry.level as usize)
+                .toc_key(toc::bookmark_name(index))
+                .page_ref(""),
+        );
+    }
+    docx.add_table_of_contents(table)
+}
+
+/// 正文转换时共用的上下文
+struct Context<'a> {
+    profile: &'a StyleProfile,
+    diagrams: &'a Diagrams,
+    images: &'a Images,
+    /// 有参考文档时段落和表格套用其中的样式，不写入样式方案的字体、字号、行距等直接格式
+    reference: Option<&'a ReferenceDoc>,
+}
+
+/// 正文段落：有参考文档时使用 Normal 样式，否则按样式方案设置格式
+fn body_paragraph(ctx: &Context) -> Paragraph {
+    match ctx.reference {
+        Some(_) => Paragraph::new(),
+        None => apply_standard_para_style(Paragraph::new(), ctx.profile),
+    }
+}
+
+/// 正文字体（中文字体 + 西文字体）
+fn body_fonts(profile: &StyleProfile) -> RunFonts {
+    RunFonts::new()
+        .east_asia(profile.body_font())
+        .ascii(&profile.western_font)
+        .hi_ansi(&profile.western_font)
 }
 
-/// 应用公文标准段落格式：首行缩进2字符 + 固定行距
-fn apply_standard_para_style(para: Paragraph) -> Paragraph {
+/// 应用样式方案的段落格式：首行缩进 + 固定行距 + 段后间距
+fn apply_standard_para_style(para: Paragraph, profile: &StyleProfile) -> Paragraph {
+    let para = para.line_spacing(
+        LineSpacing::new()
+            .line_rule(LineSpacingType::Exact)
+            .line(styles::pt_to_twip(profile.line_spacing))
+            .before(0)
+            .after(styles::pt_to_twip(profile.paragraph_spacing) as u32)
+    );
+    if profile.first_line_indent == 0 {
+        return para.indent(Some(0), None, None, None);
+    }
     para.indent(
         Some(0),
-        Some(SpecialIndentType::FirstLine(styles::chars_to_twip(styles::FIRST_LINE_INDENT))),
+        Some(SpecialIndentType::FirstLine(profile.first_line_indent_twip())),
         None,
         None,
-    ).line_spacing(
-        LineSpacing::new()
-            .line_rule(LineSpacingType::Exact)
-            .line(styles::pt_to_twip(styles::LINE_SPACING_PT))
-            .before(0)
-            .after(0)
     )
 }
 
-fn process_node<'a>(node: &'a AstNode<'a>, docx: &mut Docx) {
+fn process_node<'a>(node: &'a AstNode<'a>, docx: &mut Docx, ctx: &Context) {
+    let profile = ctx.profile;
     match &node.data.borrow().value {
         NodeValue::Paragraph => {
-            let mut para = apply_standard_para_style(Paragraph::new());
-            let runs = collect_inline_runs(node);
+            let mut para = body_paragraph(ctx);
+            let runs = collect_inline_runs(node, ctx);
             for run in runs {
                 para = para.add_run(run);
             }
             *docx = std::mem::take(docx).add_paragraph(para);
         }
+        NodeValue::Heading(NodeHeading { level, .. }) if ctx.reference.and_then(|r| r.heading_style(*level)).is_some() => {
+            // 参考文档的标题样式自带大纲级别
+            let style_id = ctx.reference.and_then(|r| r.heading_style(*level)).unwrap_or_default();
+            let mut para = Paragraph::new().style(style_id);
+            for run in collect_inline_runs(node, ctx) {
+                para = para.add_run(run);
+            }
+            *docx = std::mem::take(docx).add_paragraph(para);
+        }
         NodeValue::Heading(NodeHeading { level, .. }) => {
-            let mut para = if *level == 1 {
-                // 一级标题（文件标题）：居中，不缩进，较大行距
-                Paragraph::new()
-                    .align(AlignmentType::Center)
-                    .line_spacing(
-                        LineSpacing::new()
-                            .line_rule(LineSpacingType::Exact)
-                            .line(styles::pt_to_twip(36.0))
-                            .before(0)
-                            .after(0)
-                    )
+            let style = profile.heading(*level);
+            let mut para = if style.center {
+                // 居中标题（如公文的文件标题）：不缩进
+                Paragraph::new().align(AlignmentType::Center)
             } else {
-                // 其他标题：首行缩进2字符（公文标准）
-                apply_standard_para_style(Paragraph::new())
+                // 其他标题：与正文相同的首行缩进（公文标准）
+                apply_standard_para_style(Paragraph::new(), profile)
             };
+            // 字号较大的标题放宽行距
+            para = para.line_spacing(
+                LineSpacing::new()
+                    .line_rule(LineSpacingType::Exact)
+                    .line(styles::pt_to_twip(profile.heading_line_spacing(style.size)))
+                    .before(0)
+                    .after(styles::pt_to_twip(profile.paragraph_spacing) as u32)
+            );
+            // 大纲级别：Word 导航窗格和更新目录时按此识别标题
+            para = para.outline_lvl((*level as usize).saturating_sub(1));
 
-            let runs = collect_inline_runs(node);
+            let runs = collect_inline_runs(node, ctx);
             for run in runs {
-                let styled_run = style_heading_run(run, *level);
+                let styled_run = style_heading_run(run, &style, profile);
                 para = para.add_run(styled_run);
             }
             *docx = std::mem::take(docx).add_paragraph(para);
         }
+        NodeValue::CodeBlock(cb) if mermaid::is_mermaid(&cb.info) && ctx.diagrams.get(&cb.literal).is_some() => {
+            if let Some(diagram) = ctx.diagrams.get(&cb.literal) {
+                add_diagram(docx, diagram, profile);
+            }
+        }
         NodeValue::CodeBlock(cb) => {
             let code_text = cb.literal.clone();
             for line in code_text.lines() {
                 let run = Run::new()
                     .add_text(line)
                     .fonts(RunFonts::new().ascii("Consolas").east_asia("Consolas").hi_ansi("Consolas"))
-                    .size(styles::pt_to_half_point(styles::FONT_SIZE_FOOTNOTE));
-                let para = apply_standard_para_style(Paragraph::new()).add_run(run);
+                    .size(styles::pt_to_half_point(profile.code_size()));
+                let para = body_paragraph(ctx).add_run(run);
                 *docx = std::mem::take(docx).add_paragraph(para);
             }
         }
-        NodeValue::List(list) => {
-            let is_ordered = list.list_type == ListType::Ordered;
-            let mut index = list.start as usize;
-            for item in node.children() {
-                let prefix = if is_ordered {
-                    let s = format!("{}. ", index);
-                    index += 1;
-                    s
-                } else {
-                    "• ".to_string()
-                };
-                let mut para = apply_standard_para_style(Paragraph::new());
-
-                // 添加列表前缀
-                let prefix_run = Run::new()
-                    .add_text(&prefix)
-                    .fonts(RunFonts::new().east_asia(styles::FONT_FANGSONG[0]).ascii(styles::FONT_WESTERN))
-                    .size(styles::pt_to_half_point(styles::FONT_SIZE_BODY));
-                para = para.add_run(prefix_run);
-
-                // 收集列表项内容
-                for item_child in item.children() {
-                    if let NodeValue::Paragraph = &item_child.data.borrow().value {
-                        let runs = collect_inline_runs(item_child);
-                        for run in runs {
-                            para = para.add_run(run);
-                        }
-                    }
-                }
-                *docx = std::mem::take(docx).add_paragraph(para);
-            }
+        NodeValue::List(_) => {
+            process_list(node, docx, 0, ctx);
         }
         NodeValue::BlockQuote => {
             for child in node.children() {
                 if let NodeValue::Paragraph = &child.data.borrow().value {
-                    let mut para = apply_standard_para_style(Paragraph::new());
-                    let runs = collect_inline_runs(child);
+                    let mut para = body_paragraph(ctx);
+                    let runs = collect_inline_runs(child, ctx);
                     for run in runs {
                         let run = run.italic();
                         para = para.add_run(run);
@@ -201,93 +486,265 @@ fn process_node<'a>(node: &'a AstNode<'a>, docx: &mut Docx) {
                 }
             }
         }
+        NodeValue::HtmlBlock(block) if merge::is_page_break(&block.literal) => {
+            let para = Paragraph::new().add_run(Run::new().add_break(BreakType::Page));
+            *docx = std::mem::take(docx).add_paragraph(para);
+        }
         NodeValue::ThematicBreak => {
             // 分隔线 - 用空段落表示
             let para = Paragraph::new();
             *docx = std::mem::take(docx).add_paragraph(para);
         }
         NodeValue::Table(_) => {
-            process_table(node, docx);
+            process_table(node, docx, ctx);
         }
         _ => {
             // 递归处理其他块级元素
             for child in node.children() {
-                process_node(child, docx);
+                process_node(child, docx, ctx);
             }
         }
     }
 }
 
-/// 收集节点内的所有内联元素为 Run 列表
-fn collect_inline_runs<'a>(node: &'a AstNode<'a>) -> Vec<Run> {
-    let mut runs = Vec::new();
-    collect_inline_runs_recursive(node, &mut runs, false, false, false);
-    runs
+/// 按 96 DPI 换算的图片尺寸 (EMU)，宽度不超过版心
+fn fit_to_page(width_px: u32, height_px: u32, profile: &StyleProfile) -> (u32, u32) {
+    const EMU_PER_PX: u32 = 9525;
+    const EMU_PER_MM: f32 = 36000.0;
+    let [_, _, left, right] = profile.margins;
+    let max_width = ((210.0 - left - right).max(10.0) * EMU_PER_MM) as u32;
+    let width = width_px * EMU_PER_PX;
+    let height = height_px * EMU_PER_PX;
+    if width > max_width {
+        (max_width, (height as u64 * max_width as u64 / width as u64) as u32)
+    } else {
+        (width, height)
+    }
+}
+
+/// 插入 Mermaid 图表图片：居中，宽度不超过版心
+fn add_diagram(docx: &mut Docx, diagram: &Diagram, profile: &StyleProfile) {
+    let (width, height) = fit_to_page(diagram.width, diagram.height, profile);
+    let pic = Pic::new_with_dimensions(diagram.png.clone(), diagram.width * 2, diagram.height * 2).size(width, height);
+    let para = Paragraph::new().align(AlignmentType::Center).add_run(Run::new().add_image(pic));
+    *docx = std::mem::take(docx).add_paragraph(para);
+}
+
+/// 列表项编号：各级依次为 1. / (1) / a.
+fn list_marker(ordered: bool, index: usize, depth: usize) -> String {
+    if !ordered {
+        return ["•", "◦", "▪"][depth % 3].to_string();
+    }
+    match depth % 3 {
+        0 => format!("{}.", index),
+        1 => format!("({})", index),
+        _ => {
+            let letter = (b'a' + ((index.max(1) - 1) % 26) as u8) as char;
+            format!("{}.", letter)
+        }
+    }
+}
+
+/// 处理列表（含嵌套列表和任务列表）：每级多缩进 2 字符，任务项用 ☑ / ☐ 表示
+fn process_list<'a>(node: &'a AstNode<'a>, docx: &mut Docx, depth: usize, ctx: &Context) {
+    let profile = ctx.profile;
+    let (ordered, start) = match &node.data.borrow().value {
+        NodeValue::List(list) => (list.list_type == ListType::Ordered, list.start),
+        _ => return,
+    };
+    let indent = profile.chars_to_twip(styles::FIRST_LINE_INDENT * depth as u32);
+    let list_para = || {
+        body_paragraph(ctx).indent(
+            Some(indent),
+            Some(SpecialIndentType::FirstLine(profile.chars_to_twip(styles::FIRST_LINE_INDENT))),
+            None,
+            None,
+        )
+    };
+
+    for (i, item) in node.children().enumerate() {
+        let checked = match &item.data.borrow().value {
+            NodeValue::TaskItem(mark) => Some(mark.is_some()),
+            _ => None,
+        };
+        let mut prefix = match checked {
+            // 无序任务项只显示复选框
+            Some(_) if !ordered => String::new(),
+            _ => format!("{} ", list_marker(ordered, start + i, depth)),
+        };
+        if let Some(checked) = checked {
+            prefix.push_str(if checked { "☑ " } else { "☐ " });
+        }
+
+        // 列表前缀与第一个段落放在同一段，其余段落、代码块和子列表依次输出
+        let mut para = Some(list_para().add_run(
+            Run::new()
+                .add_text(&prefix)
+                .fonts(body_fonts(profile))
+                .size(styles::pt_to_half_point(profile.body_size)),
+        ));
+        for item_child in item.children() {
+            match &item_child.data.borrow().value {
+                NodeValue::Paragraph => {
+                    let mut current = para.take().unwrap_or_else(list_para);
+                    for run in collect_inline_runs(item_child, ctx) {
+                        current = current.add_run(run);
+                    }
+                    *docx = std::mem::take(docx).add_paragraph(current);
+                }
+                NodeValue::List(_) => {
+                    if let Some(current) = para.take() {
+                        *docx = std::mem::take(docx).add_paragraph(current);
+                    }
+                    process_list(item_child, docx, depth + 1, ctx);
+                }
+                _ => {
+                    if let Some(current) = para.take() {
+                        *docx = std::mem::take(docx).add_paragraph(current);
+                    }
+                    process_node(item_child, docx, ctx);
+                }
+            }
+        }
+        // 空列表项
+        if let Some(current) = para {
+            *docx = std::mem::take(docx).add_paragraph(current);
+        }
+    }
 }
 
-fn collect_inline_runs_recursive<'a>(
-    node: &'a AstNode<'a>,
-    runs: &mut Vec<Run>,
+/// 内联文字格式
+#[derive(Debug, Clone, Copy, Default)]
+struct InlineStyle {
     bold: bool,
     italic: bool,
     code: bool,
-) {
+    strike: bool,
+    underline: bool,
+    highlight: bool,
+}
+
+/// 收集节点内的所有内联元素为 Run 列表
+fn collect_inline_runs<'a>(node: &'a AstNode<'a>, ctx: &Context) -> Vec<Run> {
+    let mut runs = Vec::new();
+    collect_inline_runs_recursive(node, &mut runs, InlineStyle::default(), ctx);
+    runs
+}
+
+/// 文本按 ==高亮== 标记切分为 (文本, 是否高亮)；comrak 没有高亮扩展，在文本节点内识别
+fn split_highlights(text: &str) -> Vec<(&str, bool)> {
+    static RE: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    let re = RE.get_or_init(|| regex::Regex::new(r"==([^=\s](?:[^=]*[^=\s])?)==").unwrap());
+    let mut parts = Vec::new();
+    let mut last = 0;
+    for caps in re.captures_iter(text) {
+        let (whole, inner) = (caps.get(0).unwrap(), caps.get(1).unwrap());
+        if whole.start() > last {
+            parts.push((&text[last..whole.start()], false));
+        }
+        parts.push((inner.as_str(), true));
+        last = whole.end();
+    }
+    if last < text.len() {
+        parts.push((&text[last..], false));
+    }
+    parts
+}
+
+fn styled_run(text: &str, style: InlineStyle, ctx: &Context) -> Run {
+    let profile = ctx.profile;
+    let mut run = Run::new().add_text(text);
+    // 有参考文档时字体、字号由段落样式决定
+    if ctx.reference.is_none() {
+        run = run.fonts(body_fonts(profile)).size(styles::pt_to_half_point(profile.body_size));
+    }
+    if style.bold { run = run.bold(); }
+    if style.italic { run = run.italic(); }
+    if style.strike { run = run.strike(); }
+    if style.underline { run = run.underline("single"); }
+    if style.highlight { run = run.highlight("yellow"); }
+    if style.code {
+        run = run.fonts(RunFonts::new().ascii("Consolas").east_asia("Consolas").hi_ansi("Consolas"));
+    }
+    run
+}
+
+fn collect_inline_runs_recursive<'a>(node: &'a AstNode<'a>, runs: &mut Vec<Run>, style: InlineStyle, ctx: &Context) {
+    let profile = ctx.profile;
+    // 内联 HTML 的 <u>、<mark> 是独立的开始、结束节点，在同级节点间切换格式
+    let mut style = style;
     for child in node.children() {
         match &child.data.borrow().value {
             NodeValue::Text(text) => {
-                let mut run = Run::new()
-                    .add_text(text)
-                    .fonts(RunFonts::new().east_asia(styles::FONT_FANGSONG[0]).ascii(styles::FONT_WESTERN))
-                    .size(styles::pt_to_half_point(styles::FONT_SIZE_BODY));
-                if bold { run = run.bold(); }
-                if italic { run = run.italic(); }
-                if code {
-                    run = run.fonts(RunFonts::new().ascii("Consolas").east_asia("Consolas").hi_ansi("Consolas"));
+                for (part, highlight) in split_highlights(text) {
+                    runs.push(styled_run(part, InlineStyle { highlight: style.highlight || highlight, ..style }, ctx));
                 }
-                runs.push(run);
             }
+            NodeValue::HtmlInline(html) => match html.trim().to_ascii_lowercase().as_str() {
+                "<u>" | "<ins>" => style.underline = true,
+                "</u>" | "</ins>" => style.underline = false,
+                "<mark>" => style.highlight = true,
+                "</mark>" => style.highlight = false,
+                "<s>" | "<del>" => style.strike = true,
+                "</s>" | "</del>" => style.strike = false,
+                _ => {}
+            },
             NodeValue::SoftBreak | NodeValue::LineBreak => {
                 runs.push(Run::new().add_break(BreakType::TextWrapping));
             }
             NodeValue::Code(c) => {
                 let text = c.literal.clone();
-                let run = Run::new()
+                let mut run = Run::new()
                     .add_text(&text)
-                    .fonts(RunFonts::new().ascii("Consolas").east_asia("Consolas").hi_ansi("Consolas"))
-                    .size(styles::pt_to_half_point(styles::FONT_SIZE_BODY));
+                    .fonts(RunFonts::new().ascii("Consolas").east_asia("Consolas").hi_ansi("Consolas"));
+                if ctx.reference.is_none() {
+                    run = run.size(styles::pt_to_half_point(profile.body_size));
+                }
+                if style.strike { run = run.strike(); }
+                if style.underline { run = run.underline("single"); }
+                if style.highlight { run = run.highlight("yellow"); }
                 runs.push(run);
             }
             NodeValue::Strong => {
-                collect_inline_runs_recursive(child, runs, true, italic, code);
+                collect_inline_runs_recursive(child, runs, InlineStyle { bold: true, ..style }, ctx);
             }
             NodeValue::Emph => {
-                collect_inline_runs_recursive(child, runs, bold, true, code);
+                collect_inline_runs_recursive(child, runs, InlineStyle { italic: true, ..style }, ctx);
             }
             NodeValue::Strikethrough => {
-                // docx-rs 不直接支持删除线，用普通文本代替
-                collect_inline_runs_recursive(child, runs, bold, italic, code);
+                collect_inline_runs_recursive(child, runs, InlineStyle { strike: true, ..style }, ctx);
             }
+            NodeValue::Image(link) => match ctx.images.get(&link.url) {
+                Some(image) => {
+                    let (width, height) = fit_to_page(image.width, image.height, profile);
+                    let pic = Pic::new_with_dimensions(image.data.clone(), image.width, image.height).size(width, height);
+                    runs.push(Run::new().add_image(pic));
+                }
+                // 网络图片和无法读取的图片输出替代文字
+                None => collect_inline_runs_recursive(child, runs, style, ctx),
+            },
             NodeValue::Link(link) => {
                 // 先输出链接文本，再输出 URL
-                collect_inline_runs_recursive(child, runs, bold, italic, code);
+                collect_inline_runs_recursive(child, runs, style, ctx);
                 let url = link.url.clone();
                 if !url.is_empty() {
                     let url_run = Run::new()
                         .add_text(&format!(" ({})", url))
-                        .size(styles::pt_to_half_point(styles::FONT_SIZE_SMALL))
+                        .size(styles::pt_to_half_point(profile.small_size()))
                         .color("0066CC");
                     runs.push(url_run);
                 }
             }
             _ => {
-                collect_inline_runs_recursive(child, runs, bold, italic, code);
+                collect_inline_runs_recursive(child, runs, style, ctx);
             }
         }
     }
 }
 
 /// 处理表格
-fn process_table<'a>(node: &'a AstNode<'a>, docx: &mut Docx) {
+fn process_table<'a>(node: &'a AstNode<'a>, docx: &mut Docx, ctx: &Context) {
+    let profile = ctx.profile;
     let mut rows: Vec<TableRow> = Vec::new();
     let mut is_header = true;
 
@@ -298,9 +755,11 @@ fn process_table<'a>(node: &'a AstNode<'a>, docx: &mut Docx) {
                 for cell_node in child.children() {
                     if let NodeValue::TableCell = &cell_node.data.borrow().value {
                         let mut para = Paragraph::new();
-                        let inline_runs = collect_inline_runs(cell_node);
+                        let inline_runs = collect_inline_runs(cell_node, ctx);
                         for mut run in inline_runs {
-                            run = run.size(styles::pt_to_half_point(styles::FONT_SIZE_SMALL));
+                            if ctx.reference.is_none() {
+                                run = run.size(styles::pt_to_half_point(profile.small_size()));
+                            }
                             if is_header {
                                 run = run.bold();
                             }
@@ -319,42 +778,20 @@ fn process_table<'a>(node: &'a AstNode<'a>, docx: &mut Docx) {
     }
 
     if !rows.is_empty() {
-        let table = Table::new(rows)
+        let mut table = Table::new(rows)
             .set_grid(vec![])
             .indent(0);
+        if let Some(style_id) = ctx.reference.and_then(|r| r.table_style()) {
+            table = table.style(style_id);
+        }
         *docx = std::mem::take(docx).add_table(table);
     }
 }
 
-/// 为标题 Run 设置公文标准字体样式
-fn style_heading_run(run: Run, level: u8) -> Run {
-    match level {
-        1 => {
-            // 文件标题: 2号宋体加粗居中
-            run.fonts(RunFonts::new().east_asia(styles::FONT_SONGTI[0]).ascii(styles::FONT_WESTERN))
-                .size(styles::pt_to_half_point(styles::FONT_SIZE_TITLE))
-                .bold()
-        }
-        2 => {
-            // 一级标题: 3号黑体
-            run.fonts(RunFonts::new().east_asia(styles::FONT_HEITI[0]).ascii(styles::FONT_WESTERN))
-                .size(styles::pt_to_half_point(styles::FONT_SIZE_BODY))
-        }
-        3 => {
-            // 二级标题: 3号楷体
-            run.fonts(RunFonts::new().east_asia(styles::FONT_KAITI[0]).ascii(styles::FONT_WESTERN))
-                .size(styles::pt_to_half_point(styles::FONT_SIZE_BODY))
-        }
-        4 => {
-            // 三级标题: 3号仿宋加粗
-            run.fonts(RunFonts::new().east_asia(styles::FONT_FANGSONG[0]).ascii(styles::FONT_WESTERN))
-                .size(styles::pt_to_half_point(styles::FONT_SIZE_BODY))
-                .bold()
-        }
-        _ => {
-            // 四级及以下: 3号仿宋
-            run.fonts(RunFonts::new().east_asia(styles::FONT_FANGSONG[0]).ascii(styles::FONT_WESTERN))
-                .size(styles::pt_to_half_point(styles::FONT_SIZE_BODY))
-        }
-    }
+/// 为标题 Run 设置样式方案中的标题字体、字号和粗细
+fn style_heading_run(run: Run, style: &styles::HeadingStyle, profile: &StyleProfile) -> Run {
+    let run = run
+        .fonts(RunFonts::new().east_asia(style.font()).ascii(&profile.western_font).hi_ansi(&profile.western_font))
+        .size(styles::pt_to_half_point(style.size));
+    if style.bold { run.bold() } else { run }
 }
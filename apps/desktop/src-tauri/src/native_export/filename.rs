@@ -0,0 +1,79 @@
+/// 导出文件名模板
+/// 如 "{project}-{title}-{date}"，渲染后去除文件系统不允许的字符，不含扩展名
+use regex::{Captures, Regex};
+
+/// 模板中可用的变量
+pub const PLACEHOLDERS: &[&str] = &["project", "title", "author", "date", "time", "format", "suffix"];
+
+/// 文件名（不含扩展名）最大字符数
+const MAX_STEM_CHARS: usize = 150;
+
+/// Windows 保留的设备名，不能作为文件名
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+pub struct FilenameVars<'a> {
+    pub project: &'a str,
+    pub title: &'a str,
+    pub author: &'a str,
+    pub format: &'a str,
+    /// 调用方附加的后缀，如 "_AI"
+    pub suffix: &'a str,
+}
+
+fn placeholder_re() -> Regex {
+    Regex::new(r"\{([^{}]*)\}").unwrap()
+}
+
+/// 检查模板是否只包含已知变量
+pub fn validate(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("导出文件名模板不能为空".to_string());
+    }
+    for caps in placeholder_re().captures_iter(template) {
+        if !PLACEHOLDERS.contains(&&caps[1]) {
+            return Err(format!("导出文件名模板包含未知变量: {{{}}}，可用变量: {}", &caps[1], PLACEHOLDERS.join(", ")));
+        }
+    }
+    Ok(())
+}
+
+/// 按模板生成文件名（不含扩展名）
+pub fn render(template: &str, vars: &FilenameVars) -> String {
+    let now = chrono::Local::now();
+    let rendered = placeholder_re().replace_all(template, |caps: &Captures| match &caps[1] {
+        "project" => vars.project.to_string(),
+        "title" => vars.title.to_string(),
+        "author" => vars.author.to_string(),
+        "date" => now.format("%Y-%m-%d").to_string(),
+        "time" => now.format("%H%M").to_string(),
+        "format" => vars.format.to_string(),
+        "suffix" => vars.suffix.to_string(),
+        other => other.to_string(),
+    });
+    let stem = sanitize(&rendered);
+    if stem.is_empty() {
+        // 模板变量全部为空时退回标题
+        let title = sanitize(vars.title);
+        return if title.is_empty() { "untitled".to_string() } else { title };
+    }
+    stem
+}
+
+/// 替换各平台文件名中不允许的字符，合并连续空白，去掉首尾的点和空格
+pub fn sanitize(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') || c.is_control() { '_' } else { c })
+        .collect();
+    let cleaned = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut cleaned: String = cleaned.trim_matches(|c: char| c == '.' || c == ' ').chars().take(MAX_STEM_CHARS).collect();
+    cleaned = cleaned.trim_end_matches(['.', ' ']).to_string();
+    let base = cleaned.split('.').next().unwrap_or_default();
+    if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(base)) {
+        cleaned.insert(0, '_');
+    }
+    cleaned
+}
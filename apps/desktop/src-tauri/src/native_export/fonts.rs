@@ -0,0 +1,264 @@
+/// PDF 导出字体
+/// 按角色（仿宋 / 黑体 / 楷体 / 宋体）查找字体文件：设置中的路径 → 用户 / 内置字体目录 → 系统字体，
+/// 找到的字体以 @font-face 内嵌进打印页，浏览器另存为 PDF 时随文件一起嵌入
+use super::styles;
+use crate::settings::PdfFontSettings;
+use base64::Engine;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// 单个字体超过该大小不内嵌（打印页过大时浏览器加载很慢）
+const MAX_EMBED_BYTES: u64 = 64 * 1024 * 1024;
+const FONT_EXTENSIONS: &[&str] = &["ttf", "otf", "ttc", "woff", "woff2"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FontRole {
+    Fangsong,
+    Heiti,
+    Kaiti,
+    Songti,
+}
+
+impl FontRole {
+    pub const ALL: [FontRole; 4] = [FontRole::Fangsong, FontRole::Heiti, FontRole::Kaiti, FontRole::Songti];
+
+    /// 用户 / 内置字体目录中的文件名（不含扩展名）
+    fn key(self) -> &'static str {
+        match self {
+            FontRole::Fangsong => "fangsong",
+            FontRole::Heiti => "heiti",
+            FontRole::Kaiti => "kaiti",
+            FontRole::Songti => "songti",
+        }
+    }
+
+    /// 样式表中该角色使用的字体名，第一个即 @font-face 覆盖的名称
+    fn families(self) -> &'static [&'static str] {
+        match self {
+            FontRole::Fangsong => styles::FONT_FANGSONG,
+            FontRole::Heiti => styles::FONT_HEITI,
+            FontRole::Kaiti => styles::FONT_KAITI,
+            FontRole::Songti => styles::FONT_SONGTI,
+        }
+    }
+
+    fn configured(self, settings: &PdfFontSettings) -> Option<&str> {
+        let path = match self {
+            FontRole::Fangsong => &settings.fangsong,
+            FontRole::Heiti => &settings.heiti,
+            FontRole::Kaiti => &settings.kaiti,
+            FontRole::Songti => &settings.songti,
+        };
+        path.as_deref().map(str::trim).filter(|p| !p.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FontSource {
+    /// 设置中指定的文件
+    Configured,
+    /// 用户字体目录或安装包内置字体
+    Bundled,
+    /// 系统已安装的对应字体
+    System,
+    /// 系统没有对应字体，使用其他中文字体代替
+    Substitute,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedFont {
+    pub role: FontRole,
+    pub family: String,
+    /// 未找到可用字体时为空，导出时回退到浏览器的字体匹配
+    pub path: Option<String>,
+    pub source: Option<FontSource>,
+}
+
+/// 查找各角色的字体文件
+pub fn resolve(settings: &PdfFontSettings, font_dirs: &[PathBuf]) -> Vec<ResolvedFont> {
+    let system = system_fonts();
+    FontRole::ALL
+        .iter()
+        .map(|&role| {
+            let found = role
+                .configured(settings)
+                .map(PathBuf::from)
+                .filter(|p| {
+                    // 设置中的字体文件被移走时警告并改用内置或系统字体
+                    p.is_file() || {
+                        tracing::warn!("字体文件不存在，已忽略: {}", p.display());
+                        false
+                    }
+                })
+                .map(|p| (p, FontSource::Configured))
+                .or_else(|| find_in_dirs(role, font_dirs).map(|p| (p, FontSource::Bundled)))
+                .or_else(|| system.iter().find(|(r, ..)| *r == role).map(|(_, p, s)| (p.clone(), *s)));
+            ResolvedFont {
+                role,
+                family: role.families()[0].to_string(),
+                path: found.as_ref().map(|(p, _)| p.to_string_lossy().to_string()),
+                source: found.map(|(_, s)| s),
+            }
+        })
+        .collect()
+}
+
+fn find_in_dirs(role: FontRole, dirs: &[PathBuf]) -> Option<PathBuf> {
+    dirs.iter().find_map(|dir| {
+        FONT_EXTENSIONS
+            .iter()
+            .map(|ext| dir.join(format!("{}.{}", role.key(), ext)))
+            .find(|p| p.is_file())
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn system_fonts() -> Vec<(FontRole, PathBuf, FontSource)> {
+    let fonts_dir = std::env::var("WINDIR")
+        .map(|w| PathBuf::from(w).join("Fonts"))
+        .unwrap_or_else(|_| PathBuf::from(r"C:\Windows\Fonts"));
+    let candidates: &[(FontRole, &[&str])] = &[
+        (FontRole::Fangsong, &["simfang.ttf"]),
+        (FontRole::Heiti, &["simhei.ttf"]),
+        (FontRole::Kaiti, &["simkai.ttf"]),
+        (FontRole::Songti, &["simsun.ttc"]),
+    ];
+    let mut found = Vec::new();
+    for (role, files) in candidates {
+        if let Some(path) = files.iter().map(|f| fonts_dir.join(f)).find(|p| p.is_file()) {
+            found.push((*role, path, FontSource::System));
+        }
+    }
+    // 精简版系统缺少仿宋 / 楷体时用宋体代替
+    if let Some((_, songti, _)) = found.iter().find(|(r, ..)| *r == FontRole::Songti).cloned() {
+        for role in [FontRole::Fangsong, FontRole::Kaiti] {
+            if !found.iter().any(|(r, ..)| *r == role) {
+                found.push((role, songti.clone(), FontSource::Substitute));
+            }
+        }
+    }
+    found
+}
+
+#[cfg(target_os = "macos")]
+fn system_fonts() -> Vec<(FontRole, PathBuf, FontSource)> {
+    let home = dirs::home_dir().unwrap_or_default();
+    let candidates: Vec<(FontRole, Vec<PathBuf>)> = vec![
+        (FontRole::Fangsong, vec![
+            PathBuf::from("/Library/Fonts/Microsoft/FangSong.ttf"),
+            home.join("Library/Fonts/simfang.ttf"),
+            PathBuf::from("/Library/Fonts/simfang.ttf"),
+        ]),
+        (FontRole::Heiti, vec![
+            PathBuf::from("/System/Library/Fonts/STHeiti Medium.ttc"),
+            PathBuf::from("/System/Library/Fonts/STHeiti Light.ttc"),
+        ]),
+        (FontRole::Kaiti, vec![
+            PathBuf::from("/Library/Fonts/Microsoft/KaiTi.ttf"),
+            home.join("Library/Fonts/simkai.ttf"),
+            PathBuf::from("/Library/Fonts/simkai.ttf"),
+        ]),
+        (FontRole::Songti, vec![
+            PathBuf::from("/System/Library/Fonts/Supplemental/Songti.ttc"),
+            PathBuf::from("/Library/Fonts/Songti.ttc"),
+        ]),
+    ];
+    let mut found: Vec<(FontRole, PathBuf, FontSource)> = candidates
+        .into_iter()
+        .filter_map(|(role, paths)| paths.into_iter().find(|p| p.is_file()).map(|p| (role, p, FontSource::System)))
+        .collect();
+    // 仿宋、楷体需另行安装，缺少时用宋体代替
+    if let Some((_, songti, _)) = found.iter().find(|(r, ..)| *r == FontRole::Songti).cloned() {
+        for role in [FontRole::Fangsong, FontRole::Kaiti] {
+            if !found.iter().any(|(r, ..)| *r == role) {
+                found.push((role, songti.clone(), FontSource::Substitute));
+            }
+        }
+    }
+    found
+}
+
+/// Linux 通过 fontconfig 列出中文字体，按字体名匹配，找不到时用 Noto CJK / 文泉驿等代替
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn system_fonts() -> Vec<(FontRole, PathBuf, FontSource)> {
+    let Ok(output) = std::process::Command::new("fc-list").args([":lang=zh", "file", "family"]).output() else {
+        return Vec::new();
+    };
+    // 每行形如 "/usr/share/fonts/xxx.ttc: Noto Serif CJK SC,Noto Serif CJK SC Bold"
+    let fonts: Vec<(PathBuf, String)> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (path, families) = line.split_once(": ")?;
+            Some((PathBuf::from(path.trim()), families.to_string()))
+        })
+        .collect();
+
+    let substitutes = |role: FontRole| -> &'static [&'static str] {
+        match role {
+            FontRole::Heiti => &["Sans CJK", "WenQuanYi", "Droid Sans Fallback"],
+            _ => &["Serif CJK", "AR PL", "Sans CJK", "WenQuanYi"],
+        }
+    };
+    FontRole::ALL
+        .iter()
+        .filter_map(|&role| {
+            let exact = fonts
+                .iter()
+                .find(|(_, fam)| fam.split(',').any(|f| role.families().contains(&f.trim())))
+                .map(|(p, _)| (role, p.clone(), FontSource::System));
+            exact.or_else(|| {
+                substitutes(role).iter().find_map(|keyword| {
+                    fonts
+                        .iter()
+                        .find(|(_, fam)| fam.contains(keyword))
+                        .map(|(p, _)| (role, p.clone(), FontSource::Substitute))
+                })
+            })
+        })
+        .collect()
+}
+
+fn mime_type(path: &Path) -> &'static str {
+    match path.extension().map(|e| e.to_string_lossy().to_lowercase()).as_deref() {
+        Some("otf") => "font/otf",
+        Some("ttc") => "font/collection",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        _ => "font/ttf",
+    }
+}
+
+/// 生成内嵌字体的 @font-face 规则；同一文件被多个角色使用时只读取一次
+pub fn font_face_css(fonts: &[ResolvedFont]) -> String {
+    let mut css = String::new();
+    let mut encoded: Vec<(String, String)> = Vec::new();
+    for font in fonts {
+        let Some(path) = font.path.as_deref() else { continue };
+        let data = match encoded.iter().find(|(p, _)| p == path) {
+            Some((_, data)) => data.clone(),
+            None => {
+                let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+                if size == 0 || size > MAX_EMBED_BYTES {
+                    tracing::warn!("字体文件过大或不可读，不内嵌: {}", path);
+                    continue;
+                }
+                let Ok(bytes) = std::fs::read(path) else { continue };
+                let data = format!(
+                    "data:{};base64,{}",
+                    mime_type(Path::new(path)),
+                    base64::engine::general_purpose::STANDARD.encode(bytes)
+                );
+                encoded.push((path.to_string(), data.clone()));
+                data
+            }
+        };
+        css.push_str(&format!(
+            "@font-face {{ font-family: \"{}\"; src: url(\"{}\"); }}\n",
+            font.family, data
+        ));
+    }
+    css
+}
@@ -1,17 +1,68 @@
-use comrak::{markdown_to_html, Options};
-use super::styles;
+use comrak::plugins::syntect::SyntectAdapter;
+use comrak::{markdown_to_html, markdown_to_html_with_plugins, Options, Plugins};
+use super::cover::Cover;
+use super::images;
+use super::watermark::Watermark;
+use super::{styles, toc, ExportOptions};
 
-/// 将 Markdown 转换为带公文样式的完整 HTML 文档
-pub fn export_to_html(markdown: &str, title: &str) -> Result<String, String> {
+/// 可选的代码高亮主题（syntect 内置主题）
+pub const CODE_THEMES: &[&str] = &[
+    "InspiredGitHub",
+    "Solarized (light)",
+    "base16-ocean.light",
+    "Solarized (dark)",
+    "base16-ocean.dark",
+    "base16-eighties.dark",
+    "base16-mocha.dark",
+];
+/// 默认代码高亮主题：浅色背景，适合打印
+pub const DEFAULT_CODE_THEME: &str = "InspiredGitHub";
+
+/// 将 Markdown 转换为按样式方案排版的完整 HTML 文档；
+/// 按导出选项高亮代码块（code_theme）、在正文前插入封面（cover）和目录（toc_depth）、加水印（watermark），
+/// 并写入作者、创建时间元数据；本地图片按 images 设置压缩后以 data: URI 内嵌
+pub fn export_to_html(markdown: &str, title: &str, export: &ExportOptions) -> Result<String, String> {
     let mut options = Options::default();
     options.extension.table = true;
     options.extension.strikethrough = true;
     options.extension.tasklist = true;
     options.extension.autolink = true;
     options.render.unsafe_ = true;
+    let embedded = images::load_all(markdown, &export.images);
+    if !embedded.is_empty() {
+        options.extension.image_url_rewriter = Some(std::sync::Arc::new(move |url: &str| {
+            embedded.get(url).map(|image| image.data_uri()).unwrap_or_else(|| url.to_string())
+        }));
+    }
+    // 目录链接到标题锚点
+    let toc_html = match export.toc_depth {
+        Some(depth) => {
+            options.extension.header_ids = Some(String::new());
+            toc::to_html(&toc::collect(markdown, depth))
+        }
+        None => String::new(),
+    };
 
-    let html_body = markdown_to_html(markdown, &options);
-    let css = styles::get_html_css();
+    let html_body = match export.code_theme.as_deref().filter(|t| !t.is_empty()) {
+        Some(theme) => {
+            // 未知主题会让 syntect 直接 panic，先校验
+            if !CODE_THEMES.contains(&theme) {
+                return Err(format!("不支持的代码高亮主题: {}", theme));
+            }
+            let adapter = SyntectAdapter::new(Some(theme));
+            let mut plugins = Plugins::default();
+            plugins.render.codefence_syntax_highlighter = Some(&adapter);
+            markdown_to_html_with_plugins(markdown, &options, &plugins)
+        }
+        None => markdown_to_html(markdown, &options),
+    };
+    let css = styles::get_html_css(&export.style);
+    let watermark_html = Watermark::build(&export.watermark)?.map(|w| w.to_html()).unwrap_or_default();
+    let cover_html = Cover::build(title, &export.info, &export.cover)?.map(|c| c.to_html()).unwrap_or_default();
+    let author_meta = match export.info.author.trim() {
+        "" => String::new(),
+        author => format!("\n    <meta name=\"author\" content=\"{}\">", html_escape(author)),
+    };
 
     let full_html = format!(
         r#"<!DOCTYPE html>
@@ -19,15 +70,22 @@ pub fn export_to_html(markdown: &str, title: &str) -> Result<String, String> {
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <meta name="generator" content="AiDocPlus">{author_meta}
+    <meta name="dcterms.created" content="{created}">
     <title>{title}</title>
     <style>{css}</style>
 </head>
 <body>
-{html_body}
+{watermark_html}{cover_html}{toc_html}{html_body}
 </body>
 </html>"#,
         title = html_escape(title),
+        author_meta = author_meta,
+        created = export.info.created().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
         css = css,
+        watermark_html = watermark_html,
+        cover_html = cover_html,
+        toc_html = toc_html,
         html_body = html_body
     );
 
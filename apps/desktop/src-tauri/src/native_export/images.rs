@@ -0,0 +1,111 @@
+/// 导出图片模块
+/// 读取文档引用的本地图片（绝对路径、file:// 地址）和 data: URI，按导出设置缩小尺寸、重新压缩后
+/// 插入 DOCX 或以 data: URI 内嵌到 HTML / PDF；网络图片和无法识别的图片保持原链接
+use crate::email_images::load_image_source;
+use crate::settings::ImageSettings;
+use base64::Engine;
+use comrak::nodes::NodeValue;
+use comrak::{parse_document, Arena, Options};
+use image::{DynamicImage, GenericImageView, ImageFormat};
+use std::collections::HashMap;
+use std::io::Cursor;
+
+/// 可嵌入的图片；width / height 为像素
+#[derive(Debug, Clone)]
+pub struct EmbeddedImage {
+    pub data: Vec<u8>,
+    pub mime: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl EmbeddedImage {
+    pub fn data_uri(&self) -> String {
+        format!("data:{};base64,{}", self.mime, base64::engine::general_purpose::STANDARD.encode(&self.data))
+    }
+}
+
+/// 按图片地址查找已处理的图片
+#[derive(Debug, Clone, Default)]
+pub struct Images {
+    loaded: HashMap<String, EmbeddedImage>,
+}
+
+impl Images {
+    pub fn get(&self, url: &str) -> Option<&EmbeddedImage> {
+        self.loaded.get(url)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.loaded.is_empty()
+    }
+}
+
+/// 读取并处理文档中引用的图片
+pub fn load_all(markdown: &str, settings: &ImageSettings) -> Images {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.strikethrough = true;
+    options.extension.tasklist = true;
+    let root = parse_document(&arena, markdown, &options);
+
+    let mut loaded = HashMap::new();
+    for node in root.descendants() {
+        let url = match &node.data.borrow().value {
+            NodeValue::Image(link) => link.url.clone(),
+            _ => continue,
+        };
+        if loaded.contains_key(&url) {
+            continue;
+        }
+        let Some((data, mime)) = load_image_source(&url) else { continue };
+        match process(data, mime, settings) {
+            Ok(image) => {
+                loaded.insert(url, image);
+            }
+            Err(e) => tracing::warn!("处理图片失败，保留原链接 {}: {}", url, e),
+        }
+    }
+    Images { loaded }
+}
+
+/// 按设置缩小、重新编码；compress 关闭或重新编码后反而更大时保留原图数据
+fn process(data: Vec<u8>, mime: String, settings: &ImageSettings) -> Result<EmbeddedImage, String> {
+    let img = image::load_from_memory(&data).map_err(|e| format!("无法识别图片: {}", e))?;
+    let (width, height) = img.dimensions();
+    // GIF 可能是动图，保持原样
+    if !settings.compress || mime == "image/gif" {
+        return Ok(EmbeddedImage { data, mime, width, height });
+    }
+
+    let max_width = if settings.max_width == 0 { width } else { settings.max_width };
+    let max_height = if settings.max_height == 0 { height } else { settings.max_height };
+    let resized = width > max_width || height > max_height;
+    let img = if resized { img.resize(max_width, max_height, image::imageops::FilterType::Lanczos3) } else { img };
+
+    let to_jpeg = mime == "image/jpeg" || (settings.png_to_jpeg && !has_transparency(&img));
+    let (encoded, new_mime) = if to_jpeg {
+        (encode_jpeg(&img, settings.jpeg_quality)?, "image/jpeg")
+    } else {
+        let mut out = Cursor::new(Vec::new());
+        img.write_to(&mut out, ImageFormat::Png).map_err(|e| format!("编码 PNG 失败: {}", e))?;
+        (out.into_inner(), "image/png")
+    };
+    let (width, height) = img.dimensions();
+    if !resized && encoded.len() >= data.len() {
+        return Ok(EmbeddedImage { data, mime, width, height });
+    }
+    Ok(EmbeddedImage { data: encoded, mime: new_mime.to_string(), width, height })
+}
+
+fn has_transparency(img: &DynamicImage) -> bool {
+    img.color().has_alpha() && img.to_rgba8().pixels().any(|p| p[3] < 255)
+}
+
+fn encode_jpeg(img: &DynamicImage, quality: u8) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut out, quality.clamp(1, 100));
+    img.to_rgb8().write_with_encoder(encoder).map_err(|e| format!("编码 JPEG 失败: {}", e))?;
+    Ok(out)
+}
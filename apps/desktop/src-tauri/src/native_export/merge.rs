@@ -0,0 +1,51 @@
+/// 合并导出模块
+/// 按指定顺序拼接多篇文档：可为每篇文档加一级标题（文档内原有标题依次降一级），可在文档之间分页
+use crate::outline;
+
+/// 分页标记：HTML / PDF 由 CSS 分页，DOCX 转换为分页符
+pub const PAGE_BREAK: &str = "<div class=\"page-break\"></div>";
+
+/// 参与合并的文档
+#[derive(Debug, Clone)]
+pub struct MergePart<'a> {
+    pub title: &'a str,
+    pub content: &'a str,
+}
+
+/// HTML 块是否为分页标记
+pub fn is_page_break(html: &str) -> bool {
+    html.trim() == PAGE_BREAK
+}
+
+/// 文档内的顶层标题降一级（六级标题保持不变）
+fn demote_headings(markdown: &str) -> String {
+    let sections = outline::split_sections(markdown);
+    if sections.sections.is_empty() {
+        return markdown.trim().to_string();
+    }
+    let demoted: Vec<outline::OutlineItem> = outline::current_outline(&sections)
+        .into_iter()
+        .map(|item| outline::OutlineItem { level: (item.level + 1).min(6), ..item })
+        .collect();
+    outline::restructure(markdown, &demoted).trim().to_string()
+}
+
+/// 拼接文档；with_titles 为 true 时每篇文档以其标题作为一级标题，page_breaks 为 true 时每篇文档从新页开始
+pub fn merge(parts: &[MergePart], with_titles: bool, page_breaks: bool) -> String {
+    let mut blocks: Vec<String> = Vec::new();
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 && page_breaks {
+            blocks.push(PAGE_BREAK.to_string());
+        }
+        let title = part.title.trim();
+        if with_titles && !title.is_empty() {
+            blocks.push(format!("# {}", title));
+            blocks.push(demote_headings(part.content));
+        } else {
+            blocks.push(part.content.trim().to_string());
+        }
+    }
+    let mut result = blocks.into_iter().filter(|b| !b.is_empty()).collect::<Vec<_>>().join("\n\n");
+    result.push('\n');
+    result
+}
@@ -0,0 +1,260 @@
+/// Mermaid 图表渲染模块
+/// 导出 HTML / DOCX / PDF 时，用本机 Chromium 内核浏览器无头加载安装包附带的 mermaid.js，把 ```mermaid 代码块渲染为
+/// SVG（HTML / PDF 内嵌）和 PNG（DOCX 插图）；找不到浏览器、未附带 mermaid.js 或渲染失败时保留原代码块
+use super::pdf;
+use base64::Engine;
+use comrak::nodes::NodeValue;
+use comrak::{parse_document, Arena, Options};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+/// 等待页面内异步渲染完成的虚拟时间 (ms)
+const RENDER_BUDGET_MS: u32 = 20000;
+
+/// 渲染好的图表；width / height 为 CSS 像素，PNG 按 2 倍分辨率输出
+#[derive(Debug, Clone)]
+pub struct Diagram {
+    pub svg: String,
+    pub png: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// 按图表源码查找渲染结果
+#[derive(Debug, Clone, Default)]
+pub struct Diagrams {
+    rendered: HashMap<String, Diagram>,
+}
+
+impl Diagrams {
+    pub fn get(&self, code: &str) -> Option<&Diagram> {
+        self.rendered.get(code.trim())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rendered.is_empty()
+    }
+}
+
+/// 代码块的语言是否为 mermaid
+pub fn is_mermaid(info: &str) -> bool {
+    info.split_whitespace().next().is_some_and(|lang| lang.eq_ignore_ascii_case("mermaid"))
+}
+
+/// 文档中所有 mermaid 代码块：(源码, 起始行, 结束行)，行号从 1 开始
+fn mermaid_blocks(markdown: &str) -> Vec<(String, usize, usize)> {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.tasklist = true;
+    let root = parse_document(&arena, markdown, &options);
+    root.descendants()
+        .filter_map(|node| {
+            let data = node.data.borrow();
+            match &data.value {
+                NodeValue::CodeBlock(cb) if cb.fenced && is_mermaid(&cb.info) && !cb.literal.trim().is_empty() => {
+                    Some((cb.literal.trim().to_string(), data.sourcepos.start.line, data.sourcepos.end.line))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// 文档中是否有 mermaid 代码块
+pub fn has_diagrams(markdown: &str) -> bool {
+    !mermaid_blocks(markdown).is_empty()
+}
+
+/// 渲染文档中的 mermaid 图表；没有图表、找不到浏览器或渲染失败时返回空结果
+pub fn render(markdown: &str, browser: Option<&Path>) -> Diagrams {
+    let mut sources: Vec<String> = Vec::new();
+    for (code, _, _) in mermaid_blocks(markdown) {
+        if !sources.contains(&code) {
+            sources.push(code);
+        }
+    }
+    if sources.is_empty() {
+        return Diagrams::default();
+    }
+    let Some(browser) = browser.map(Path::to_path_buf).or_else(pdf::find_browser) else {
+        tracing::warn!("未找到 Chromium 内核浏览器，Mermaid 图表按代码块导出");
+        return Diagrams::default();
+    };
+    // 只使用随安装包附带、版本已锁定的 mermaid.js，不从网络加载脚本
+    let Some(script) = bundled_script() else {
+        tracing::warn!("安装包未附带 mermaid.js，Mermaid 图表按代码块导出");
+        return Diagrams::default();
+    };
+
+    let work_dir = std::env::temp_dir().join(format!("aidocplus-mermaid-{}", uuid::Uuid::new_v4().simple()));
+    let result = std::fs::create_dir_all(&work_dir)
+        .map_err(|e| format!("创建临时目录失败: {}", e))
+        .and_then(|_| render_with_browser(&browser, &script, &sources, &work_dir));
+    let _ = std::fs::remove_dir_all(&work_dir);
+    match result {
+        Ok(diagrams) => diagrams,
+        Err(e) => {
+            tracing::warn!("渲染 Mermaid 图表失败，按代码块导出: {}", e);
+            Diagrams::default()
+        }
+    }
+}
+
+/// 页面输出的单个图表结果
+#[derive(Deserialize)]
+struct RenderOutput {
+    #[serde(default)]
+    svg: String,
+    #[serde(default)]
+    png: String,
+    #[serde(default)]
+    width: u32,
+    #[serde(default)]
+    height: u32,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// 安装包附带的 mermaid.js（bundled-resources/vendor/mermaid.min.js，由 scripts/vendor-mermaid.sh 从锁定版本的前端依赖复制）
+fn bundled_script() -> Option<PathBuf> {
+    let path = crate::bundled_resources::bundled_dir().join("vendor").join("mermaid.min.js");
+    path.is_file().then_some(path)
+}
+
+fn render_with_browser(browser: &Path, script: &Path, sources: &[String], work_dir: &Path) -> Result<Diagrams, String> {
+    // 与页面同目录的普通 <script> 引用，无需放开 file:// 跨文件访问
+    std::fs::copy(script, work_dir.join("mermaid.min.js")).map_err(|e| format!("复制 mermaid.js 失败: {}", e))?;
+    // 源码以 JSON 放在 <script> 中，转义 </ 防止提前结束标签
+    let sources_json = serde_json::to_string(sources).map_err(|e| e.to_string())?.replace("</", "<\\/");
+    let page = RENDER_PAGE.replace("{sources}", &sources_json);
+    let page_path = work_dir.join("render.html");
+    std::fs::write(&page_path, page).map_err(|e| format!("写入文件失败: {}", e))?;
+    let url = reqwest::Url::from_file_path(&page_path).map_err(|_| format!("无效的文件路径: {}", page_path.display()))?;
+
+    // --dump-dom 把渲染后的 DOM 输出到 stdout，结果 JSON 在 <pre id="result"> 中
+    let dom_path = work_dir.join("dom.html");
+    let dom_file = File::create(&dom_path).map_err(|e| format!("创建临时文件失败: {}", e))?;
+    let args = [
+        format!("--virtual-time-budget={}", RENDER_BUDGET_MS),
+        "--dump-dom".to_string(),
+        url.to_string(),
+    ];
+    pdf::run_headless(browser, work_dir, &args, Stdio::from(dom_file))?;
+
+    let dom = std::fs::read_to_string(&dom_path).map_err(|e| format!("读取渲染结果失败: {}", e))?;
+    let start = dom.find("<pre id=\"result\">").ok_or("页面未输出渲染结果（mermaid.js 加载失败？）")?;
+    let json = &dom[start + "<pre id=\"result\">".len()..];
+    let json = &json[..json.find("</pre>").ok_or("渲染结果不完整")?];
+    let json = json.replace("&lt;", "<").replace("&gt;", ">").replace("&nbsp;", "\u{a0}").replace("&amp;", "&");
+    let outputs: Vec<RenderOutput> = serde_json::from_str(&json).map_err(|e| format!("解析渲染结果失败: {}", e))?;
+
+    let mut rendered = HashMap::new();
+    for (source, output) in sources.iter().zip(outputs) {
+        if let Some(error) = output.error {
+            tracing::warn!("Mermaid 图表语法错误，按代码块导出: {}", error);
+            continue;
+        }
+        let Ok(png) = base64::engine::general_purpose::STANDARD.decode(output.png.as_bytes()) else { continue };
+        if output.svg.is_empty() || png.is_empty() || output.width == 0 || output.height == 0 {
+            continue;
+        }
+        rendered.insert(source.clone(), Diagram { svg: output.svg, png, width: output.width, height: output.height });
+    }
+    Ok(Diagrams { rendered })
+}
+
+/// 把 mermaid 代码块替换为内嵌 SVG（HTML / PDF 使用）；未渲染成功的代码块保持不变
+pub fn embed_svg(markdown: &str, diagrams: &Diagrams) -> String {
+    if diagrams.is_empty() {
+        return markdown.to_string();
+    }
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut out: Vec<String> = Vec::new();
+    let mut next = 0;
+    for (code, start, end) in mermaid_blocks(markdown) {
+        let Some(diagram) = diagrams.get(&code) else { continue };
+        // 列表、引用中的代码块带有前缀，只替换顶层代码块
+        if start <= next || !lines.get(start - 1).is_some_and(|l| l.starts_with("```") || l.starts_with("~~~")) {
+            continue;
+        }
+        out.extend(lines[next..start - 1].iter().map(|l| l.to_string()));
+        // HTML 块遇到空行结束，SVG 压成一行
+        let svg = diagram.svg.replace(['\r', '\n'], " ");
+        out.push(String::new());
+        out.push(format!("<figure class=\"mermaid-diagram\">{}</figure>", svg));
+        out.push(String::new());
+        next = end.min(lines.len());
+    }
+    out.extend(lines[next..].iter().map(|l| l.to_string()));
+    let mut result = out.join("\n");
+    if markdown.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// 渲染页：逐个调用 mermaid.render，再把 SVG 画到 canvas 得到 PNG；
+/// 关闭 htmlLabels，避免 foreignObject 导致 canvas 无法导出
+const RENDER_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="UTF-8">
+<script src="mermaid.min.js"></script>
+</head>
+<body>
+<script id="sources" type="application/json">{sources}</script>
+<script>
+function toPng(svg) {
+  return new Promise(function (resolve, reject) {
+    var el = new DOMParser().parseFromString(svg, 'image/svg+xml').documentElement;
+    var box = el.viewBox && el.viewBox.baseVal;
+    var width = Math.ceil(box && box.width ? box.width : 800);
+    var height = Math.ceil(box && box.height ? box.height : 600);
+    el.setAttribute('width', width);
+    el.setAttribute('height', height);
+    var img = new Image();
+    img.onload = function () {
+      var canvas = document.createElement('canvas');
+      canvas.width = width * 2;
+      canvas.height = height * 2;
+      var ctx = canvas.getContext('2d');
+      ctx.fillStyle = '#ffffff';
+      ctx.fillRect(0, 0, canvas.width, canvas.height);
+      ctx.drawImage(img, 0, 0, canvas.width, canvas.height);
+      resolve({ png: canvas.toDataURL('image/png').split(',')[1], width: width, height: height });
+    };
+    img.onerror = function () { reject(new Error('PNG 转换失败')); };
+    var xml = new XMLSerializer().serializeToString(el);
+    img.src = 'data:image/svg+xml;base64,' + btoa(unescape(encodeURIComponent(xml)));
+  });
+}
+
+(async function () {
+  var sources = JSON.parse(document.getElementById('sources').textContent);
+  var results = [];
+  try {
+    mermaid.initialize({ startOnLoad: false, securityLevel: 'strict', htmlLabels: false, flowchart: { htmlLabels: false } });
+    for (var i = 0; i < sources.length; i++) {
+      try {
+        var rendered = await mermaid.render('mermaid-diagram-' + i, sources[i]);
+        var png = await toPng(rendered.svg);
+        results.push({ svg: rendered.svg, png: png.png, width: png.width, height: png.height });
+      } catch (e) {
+        results.push({ error: String((e && e.message) || e) });
+      }
+    }
+  } catch (e) {
+    results = sources.map(function () { return { error: String((e && e.message) || e) }; });
+  }
+  var out = document.createElement('pre');
+  out.id = 'result';
+  out.textContent = JSON.stringify(results);
+  document.body.appendChild(out);
+})();
+</script>
+</body>
+</html>"#;
@@ -3,44 +3,153 @@ pub mod html;
 pub mod txt;
 pub mod docx;
 pub mod pdf;
+pub mod wechat;
+pub mod fonts;
+pub mod images;
+pub mod filename;
+pub mod cover;
+pub mod merge;
+pub mod mermaid;
+pub mod pptx;
+pub mod reference;
+pub mod toc;
+pub mod watermark;
+pub mod xlsx;
 
 use std::fs;
 use std::path::Path;
 
+/// 导出选项（由应用设置生成）
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// PDF 打印页内嵌的中文字体
+    pub pdf_fonts: Vec<fonts::ResolvedFont>,
+    /// 打印 PDF、渲染 Mermaid 图表使用的浏览器，为空时自动查找
+    pub pdf_browser: Option<std::path::PathBuf>,
+    /// HTML / DOCX / PDF 的样式方案，默认为公文样式
+    pub style: styles::StyleProfile,
+    /// HTML / PDF 代码块的高亮主题，为空时不高亮
+    pub code_theme: Option<String>,
+    /// 目录包含的标题层级（1–6），为空时不生成目录
+    pub toc_depth: Option<u8>,
+    /// PPTX 幻灯片主题 ID，为空时使用默认主题
+    pub pptx_theme: String,
+    /// DOCX 页眉页脚
+    pub header_footer: crate::settings::HeaderFooterSettings,
+    /// DOCX / HTML / PDF 封面
+    pub cover: crate::settings::CoverSettings,
+    /// DOCX / HTML / PDF 水印
+    pub watermark: crate::settings::WatermarkSettings,
+    /// DOCX / HTML / PDF 内嵌图片的压缩
+    pub images: crate::settings::ImageSettings,
+    /// DOCX 参考文档（Word 模板），沿用其中的样式定义
+    pub reference_docx: Option<std::path::PathBuf>,
+    /// 写入 DOCX 文档属性和 HTML 元数据的文档信息
+    pub info: DocumentInfo,
+}
+
+/// 导出文档的属性
+#[derive(Debug, Clone, Default)]
+pub struct DocumentInfo {
+    pub author: String,
+    /// 创建时间（Unix 秒），为 0 时使用导出时间
+    pub created_at: i64,
+}
+
+impl DocumentInfo {
+    /// 创建时间（UTC）
+    pub fn created(&self) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::from_timestamp(self.created_at, 0)
+            .filter(|_| self.created_at > 0)
+            .unwrap_or_else(chrono::Utc::now)
+    }
+}
+
+/// 导出进度回调：(进度 0–100, 阶段说明)；返回错误时中止导出（用于取消）
+pub type ProgressFn<'a> = dyn Fn(f32, &str) -> Result<(), String> + 'a;
+
 /// 原生导出入口
 pub fn export_native(
     markdown: &str,
     title: &str,
     output_path: &str,
     format: &str,
+    options: &ExportOptions,
+) -> Result<String, String> {
+    export_native_with_progress(markdown, title, output_path, format, options, &|_, _| Ok(()))
+}
+
+/// 原生导出，按阶段汇报进度；progress 返回错误时在下一阶段前中止
+pub fn export_native_with_progress(
+    markdown: &str,
+    title: &str,
+    output_path: &str,
+    format: &str,
+    options: &ExportOptions,
+    progress: &ProgressFn,
 ) -> Result<String, String> {
+    progress(0.0, "准备导出")?;
     // 确保输出目录存在
     if let Some(parent) = Path::new(output_path).parent() {
         fs::create_dir_all(parent).map_err(|e| format!("创建输出目录失败: {}", e))?;
     }
+    let profile = &options.style;
+    // 样式方案中的标题自动编号只用于排版格式
+    let numbered = styles::number_headings(markdown, profile);
+    // Mermaid 图表只在 HTML / DOCX / PDF 中渲染为图片
+    let diagrams = match format {
+        "html" | "docx" | "pdf" if mermaid::has_diagrams(&numbered) => {
+            progress(10.0, "渲染 Mermaid 图表")?;
+            mermaid::render(&numbered, options.pdf_browser.as_deref())
+        }
+        _ => mermaid::Diagrams::default(),
+    };
+    progress(30.0, "生成文档")?;
 
-    match format {
+    let written = match format {
         "md" => {
             fs::write(output_path, markdown).map_err(|e| format!("写入文件失败: {}", e))?;
             Ok(output_path.to_string())
         }
         "html" => {
-            let html_content = html::export_to_html(markdown, title)?;
+            let html_content = html::export_to_html(&mermaid::embed_svg(&numbered, &diagrams), title, options)?;
+            fs::write(output_path, html_content).map_err(|e| format!("写入文件失败: {}", e))?;
+            Ok(output_path.to_string())
+        }
+        "wechat" => {
+            let html_content = wechat::export_to_wechat_html(markdown)?;
             fs::write(output_path, html_content).map_err(|e| format!("写入文件失败: {}", e))?;
             Ok(output_path.to_string())
         }
         "docx" => {
-            docx::export_to_docx(markdown, output_path)?;
+            docx::export_to_docx(&numbered, title, output_path, options, &diagrams, progress)?;
             Ok(output_path.to_string())
         }
         "pdf" => {
-            pdf::export_to_pdf(markdown, title, output_path)
+            pdf::export_to_pdf(&mermaid::embed_svg(&numbered, &diagrams), title, output_path, options, progress)
+        }
+        "pptx" => {
+            let theme = Some(options.pptx_theme.as_str()).filter(|t| !t.is_empty()).unwrap_or(pptx::DEFAULT_THEME);
+            pptx::export_to_pptx(markdown, title, output_path, theme)?;
+            Ok(output_path.to_string())
+        }
+        "xlsx" => {
+            xlsx::export_to_xlsx(markdown, title, output_path)?;
+            Ok(output_path.to_string())
         }
         "txt" => {
-            let text = txt::export_to_txt(markdown)?;
+            let mut text = txt::export_to_txt(markdown)?;
+            if let Some(depth) = options.toc_depth {
+                let toc_text = toc::to_text(&toc::collect(markdown, depth));
+                if !toc_text.is_empty() {
+                    text = format!("{}\n\n{}", toc_text, text);
+                }
+            }
             fs::write(output_path, text).map_err(|e| format!("写入文件失败: {}", e))?;
             Ok(output_path.to_string())
         }
         _ => Err(format!("不支持的导出格式: {}", format)),
-    }
+    }?;
+    progress(100.0, "导出完成")?;
+    Ok(written)
 }
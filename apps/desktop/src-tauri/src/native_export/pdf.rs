@@ -1,19 +1,163 @@
 /// PDF 导出模块
-/// 生成公文排版 HTML 文件并自动用系统浏览器打开，用户可通过浏览器打印为 PDF
-use super::html;
+/// 按样式方案生成 HTML，用本机 Chromium 内核浏览器（Chrome / Edge / Chromium）无头模式打印为 PDF；
+/// 找不到浏览器时退回旧方式：生成带自动打印脚本的 HTML 并用系统浏览器打开，由用户另存为 PDF
+use super::fonts;
+use super::{html, ExportOptions, ProgressFn};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
 
-/// 将 Markdown 导出为可打印 PDF 的 HTML 文件
-/// 生成的 HTML 包含 @page CSS 规则，浏览器打印时自动应用公文排版
-pub fn export_to_pdf(markdown: &str, title: &str, output_path: &str) -> Result<String, String> {
+/// 无头浏览器运行的最长等待时间
+const HEADLESS_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// 将 Markdown 导出为 PDF，返回生成的文件路径
+/// 生成的 HTML 包含 @page CSS 规则，打印时自动应用样式方案的页面设置；
+/// 导出选项中的 pdf_fonts 以 @font-face 内嵌，PDF 不依赖本机是否安装公文字体
+pub fn export_to_pdf(
+    markdown: &str,
+    title: &str,
+    output_path: &str,
+    options: &ExportOptions,
+    progress: &ProgressFn,
+) -> Result<String, String> {
     // 确保输出目录存在
-    if let Some(parent) = std::path::Path::new(output_path).parent() {
+    if let Some(parent) = Path::new(output_path).parent() {
         std::fs::create_dir_all(parent).map_err(|e| format!("创建输出目录失败: {}", e))?;
     }
 
-    // 生成公文样式 HTML（已包含 @page 打印规则）
-    let html_content = html::export_to_html(markdown, title)?;
+    // 按样式方案生成 HTML（已包含 @page 打印规则）
+    let html_content = html::export_to_html(markdown, title, options)?;
+
+    let font_css = fonts::font_face_css(&options.pdf_fonts);
+    let html_content = if font_css.is_empty() {
+        html_content
+    } else {
+        html_content.replacen("</head>", &format!("<style>\n{}</style>\n</head>", font_css), 1)
+    };
+
+    progress(50.0, "打印 PDF")?;
+    match options.pdf_browser.clone().or_else(find_browser) {
+        Some(browser) => print_with_browser(&browser, &html_content, output_path),
+        None => {
+            tracing::warn!("未找到 Chromium 内核浏览器，改为打开打印页");
+            export_print_page(&html_content, output_path)
+        }
+    }
+}
+
+/// 用无头浏览器把 HTML 打印为 PDF
+fn print_with_browser(browser: &Path, html_content: &str, output_path: &str) -> Result<String, String> {
+    let work_dir = std::env::temp_dir().join(format!("aidocplus-pdf-{}", uuid::Uuid::new_v4().simple()));
+    std::fs::create_dir_all(&work_dir).map_err(|e| format!("创建临时目录失败: {}", e))?;
+    let result = run_browser(browser, html_content, output_path, &work_dir);
+    let _ = std::fs::remove_dir_all(&work_dir);
+    result
+}
+
+fn run_browser(browser: &Path, html_content: &str, output_path: &str, work_dir: &Path) -> Result<String, String> {
+    let html_path = work_dir.join("document.html");
+    std::fs::write(&html_path, html_content).map_err(|e| format!("写入文件失败: {}", e))?;
+    let url = reqwest::Url::from_file_path(&html_path).map_err(|_| format!("无效的文件路径: {}", html_path.display()))?;
+
+    // 先打印到临时文件，成功后再替换输出路径，失败时不影响已有文件
+    let pdf_path = work_dir.join("document.pdf");
+    let args = [
+        // 新旧版本去掉页眉页脚的参数不同，都传入
+        "--no-pdf-header-footer".to_string(),
+        "--print-to-pdf-no-header".to_string(),
+        "--run-all-compositor-stages-before-draw".to_string(),
+        format!("--print-to-pdf={}", pdf_path.display()),
+        url.to_string(),
+    ];
+    run_headless(browser, work_dir, &args, Stdio::null()).map_err(|e| format!("生成 PDF 失败: {}", e))?;
+
+    let bytes = std::fs::read(&pdf_path).map_err(|_| "浏览器未生成 PDF 文件".to_string())?;
+    if !bytes.starts_with(b"%PDF") {
+        return Err("浏览器生成的 PDF 文件无效".to_string());
+    }
+    // 临时目录可能与输出路径不在同一文件系统，先写到输出目录再原子替换
+    let tmp = Path::new(output_path).with_extension("pdf.tmp");
+    std::fs::write(&tmp, bytes).map_err(|e| format!("写入文件失败: {}", e))?;
+    std::fs::rename(&tmp, output_path).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp);
+        format!("写入文件失败: {}", e)
+    })?;
+    Ok(output_path.to_string())
+}
+
+/// 以无头模式运行浏览器直到退出，超时则结束进程；stdout 用于接收 --dump-dom 的输出
+pub(super) fn run_headless(browser: &Path, work_dir: &Path, args: &[String], stdout: Stdio) -> Result<(), String> {
+    let mut child = Command::new(browser)
+        .args([
+            "--headless=new",
+            "--disable-gpu",
+            "--disable-extensions",
+            "--no-first-run",
+            "--no-default-browser-check",
+        ])
+        // 独立的用户目录，不受已打开的浏览器实例影响
+        .arg(format!("--user-data-dir={}", work_dir.join("profile").display()))
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(stdout)
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("启动浏览器失败（{}）: {}", browser.display(), e))?;
+
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => return Ok(()),
+            Ok(None) if started.elapsed() > HEADLESS_TIMEOUT => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err("浏览器运行超时".to_string());
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(200)),
+            Err(e) => return Err(format!("等待浏览器退出失败: {}", e)),
+        }
+    }
+}
+
+/// 查找本机的 Chromium 内核浏览器
+pub fn find_browser() -> Option<PathBuf> {
+    browser_candidates().into_iter().find(|p| p.is_file())
+}
+
+#[cfg(target_os = "macos")]
+fn browser_candidates() -> Vec<PathBuf> {
+    [
+        "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+        "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+        "/Applications/Chromium.app/Contents/MacOS/Chromium",
+        "/Applications/Brave Browser.app/Contents/MacOS/Brave Browser",
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn browser_candidates() -> Vec<PathBuf> {
+    let roots: Vec<PathBuf> = ["ProgramFiles", "ProgramFiles(x86)", "LocalAppData"]
+        .iter()
+        .filter_map(|var| std::env::var_os(var).map(PathBuf::from))
+        .collect();
+    ["Microsoft\\Edge\\Application\\msedge.exe", "Google\\Chrome\\Application\\chrome.exe", "Chromium\\Application\\chrome.exe"]
+        .iter()
+        .flat_map(|exe| roots.iter().map(move |root| root.join(exe)))
+        .collect()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn browser_candidates() -> Vec<PathBuf> {
+    let names = ["google-chrome", "google-chrome-stable", "chromium", "chromium-browser", "microsoft-edge", "microsoft-edge-stable", "brave-browser"];
+    let dirs: Vec<PathBuf> = std::env::var_os("PATH").map(|p| std::env::split_paths(&p).collect()).unwrap_or_default();
+    names.iter().flat_map(|name| dirs.iter().map(move |dir| dir.join(name))).collect()
+}
 
-    // 添加自动打印脚本的 HTML
+/// 旧方式：写入带自动打印脚本的 HTML 并用系统浏览器打开，返回 HTML 路径
+fn export_print_page(html_content: &str, output_path: &str) -> Result<String, String> {
     let print_html = html_content.replace(
         "</body>",
         r#"<script>
@@ -0,0 +1,478 @@
+/// PPTX 导出模块
+/// 一级、二级标题生成幻灯片，标题下的段落、列表和表格行生成要点；直接写出 OOXML 包（16:9）
+/// - 文档标题生成封面，第一个标题之前的第一段作为副标题
+/// - 下面有二级标题的一级标题生成章节页，其余一级、二级标题生成内容页
+/// - 要点过多时拆分为多页（标题加“（续）”）
+use comrak::nodes::{AstNode, NodeValue};
+use comrak::{parse_document, Arena, Options};
+use serde::Serialize;
+use std::io::Write;
+
+/// 每页最多的要点数
+const MAX_BULLETS: usize = 8;
+/// 单个要点的最大字符数
+const MAX_BULLET_CHARS: usize = 150;
+/// 幻灯片尺寸 16:9 (EMU)
+const SLIDE_WIDTH: i64 = 12192000;
+const SLIDE_HEIGHT: i64 = 6858000;
+
+/// 幻灯片配色与字体
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PptxTheme {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub background: &'static str,
+    pub text: &'static str,
+    /// 标题颜色、章节页背景
+    pub primary: &'static str,
+    /// 装饰条颜色
+    pub accent: &'static str,
+    pub title_font: &'static str,
+    pub body_font: &'static str,
+}
+
+pub const THEMES: &[PptxTheme] = &[
+    PptxTheme {
+        id: "default",
+        name: "简洁蓝",
+        background: "FFFFFF",
+        text: "333333",
+        primary: "1F4E79",
+        accent: "2E75B6",
+        title_font: "微软雅黑",
+        body_font: "微软雅黑",
+    },
+    PptxTheme {
+        id: "business",
+        name: "商务",
+        background: "F7F7F7",
+        text: "262626",
+        primary: "203864",
+        accent: "C00000",
+        title_font: "黑体",
+        body_font: "微软雅黑",
+    },
+    PptxTheme {
+        id: "fresh",
+        name: "清新绿",
+        background: "FFFFFF",
+        text: "2F3B2F",
+        primary: "2E7D32",
+        accent: "81C784",
+        title_font: "微软雅黑",
+        body_font: "微软雅黑",
+    },
+    PptxTheme {
+        id: "dark",
+        name: "深色",
+        background: "1E1E1E",
+        text: "E6E6E6",
+        primary: "4FC3F7",
+        accent: "F0A30A",
+        title_font: "微软雅黑",
+        body_font: "微软雅黑",
+    },
+];
+
+pub const DEFAULT_THEME: &str = "default";
+
+/// 按 ID 查找主题
+pub fn theme(id: &str) -> Result<&'static PptxTheme, String> {
+    THEMES.iter().find(|t| t.id == id).ok_or_else(|| format!("不支持的幻灯片主题: {}", id))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SlideKind {
+    Cover,
+    Section,
+    Content,
+}
+
+#[derive(Debug, Clone)]
+struct Slide {
+    kind: SlideKind,
+    title: String,
+    subtitle: String,
+    /// (层级, 文字)
+    bullets: Vec<(usize, String)>,
+}
+
+impl Slide {
+    fn new(kind: SlideKind, title: String) -> Self {
+        Self { kind, title, subtitle: String::new(), bullets: Vec::new() }
+    }
+}
+
+fn collect_text<'a>(node: &'a AstNode<'a>, out: &mut String) {
+    match &node.data.borrow().value {
+        NodeValue::Text(text) => out.push_str(text),
+        NodeValue::Code(code) => out.push_str(&code.literal),
+        NodeValue::SoftBreak | NodeValue::LineBreak => out.push(' '),
+        _ => {
+            for child in node.children() {
+                collect_text(child, out);
+            }
+        }
+    }
+}
+
+fn text_of<'a>(node: &'a AstNode<'a>) -> String {
+    let mut text = String::new();
+    collect_text(node, &mut text);
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() > MAX_BULLET_CHARS {
+        format!("{}…", text.chars().take(MAX_BULLET_CHARS).collect::<String>())
+    } else {
+        text
+    }
+}
+
+fn heading_level<'a>(node: &'a AstNode<'a>) -> Option<u8> {
+    match &node.data.borrow().value {
+        NodeValue::Heading(heading) => Some(heading.level),
+        _ => None,
+    }
+}
+
+/// 列表项转为要点，子列表层级加一
+fn collect_list<'a>(node: &'a AstNode<'a>, depth: usize, bullets: &mut Vec<(usize, String)>) {
+    for item in node.children() {
+        for child in item.children() {
+            match &child.data.borrow().value {
+                NodeValue::List(_) => collect_list(child, depth + 1, bullets),
+                _ => {
+                    let text = text_of(child);
+                    if !text.is_empty() {
+                        bullets.push((depth.min(4), text));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 块级元素转为要点；代码块和 HTML 不放入幻灯片
+fn collect_bullets<'a>(node: &'a AstNode<'a>, bullets: &mut Vec<(usize, String)>) {
+    match &node.data.borrow().value {
+        NodeValue::List(_) => collect_list(node, 0, bullets),
+        NodeValue::Table(_) => {
+            for row in node.children() {
+                let cells: Vec<String> = row.children().map(text_of).collect();
+                if cells.iter().any(|c| !c.is_empty()) {
+                    bullets.push((0, cells.join(" | ")));
+                }
+            }
+        }
+        NodeValue::BlockQuote => {
+            for child in node.children() {
+                collect_bullets(child, bullets);
+            }
+        }
+        NodeValue::Paragraph | NodeValue::Heading(_) => {
+            let text = text_of(node);
+            if !text.is_empty() {
+                bullets.push((0, text));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 按标题拆分幻灯片
+fn build_slides(markdown: &str, title: &str) -> Vec<Slide> {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.strikethrough = true;
+    options.extension.tasklist = true;
+    options.extension.front_matter_delimiter = Some("---".to_string());
+    let root = parse_document(&arena, markdown, &options);
+    let nodes: Vec<_> = root.children().collect();
+
+    let mut slides = vec![Slide::new(SlideKind::Cover, title.to_string())];
+    let mut current: Option<Slide> = None;
+    for (i, node) in nodes.iter().enumerate() {
+        match heading_level(node) {
+            Some(level) if level <= 2 => {
+                slides.extend(current.take());
+                let text = text_of(node);
+                // 一级标题下面紧接着有二级标题时作为章节页
+                let next = nodes[i + 1..].iter().find_map(|n| heading_level(n).filter(|l| *l <= 2));
+                if level == 1 && next == Some(2) {
+                    slides.push(Slide::new(SlideKind::Section, text));
+                } else {
+                    current = Some(Slide::new(SlideKind::Content, text));
+                }
+            }
+            _ => {
+                let mut bullets = Vec::new();
+                collect_bullets(node, &mut bullets);
+                if bullets.is_empty() {
+                    continue;
+                }
+                // 第一个标题之前的第一段作为封面副标题
+                let is_paragraph = matches!(node.data.borrow().value, NodeValue::Paragraph);
+                if current.is_none() && slides.len() == 1 && slides[0].subtitle.is_empty() && is_paragraph {
+                    slides[0].subtitle = bullets.remove(0).1;
+                    continue;
+                }
+                current.get_or_insert_with(|| Slide::new(SlideKind::Content, title.to_string())).bullets.extend(bullets);
+            }
+        }
+    }
+    slides.extend(current);
+
+    // 要点过多的内容页拆分为多页
+    slides
+        .into_iter()
+        .flat_map(|slide| {
+            if slide.kind != SlideKind::Content || slide.bullets.len() <= MAX_BULLETS {
+                return vec![slide];
+            }
+            slide
+                .bullets
+                .chunks(MAX_BULLETS)
+                .enumerate()
+                .map(|(i, chunk)| Slide {
+                    kind: SlideKind::Content,
+                    title: if i == 0 { slide.title.clone() } else { format!("{}（续）", slide.title) },
+                    subtitle: String::new(),
+                    bullets: chunk.to_vec(),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+const NS: &str = r#"xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main""#;
+const XML_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#;
+const EMPTY_TREE: &str = r#"<p:nvGrpSpPr><p:cNvPr id="1" name=""/><p:cNvGrpSpPr/><p:nvPr/></p:nvGrpSpPr><p:grpSpPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="0" cy="0"/><a:chOff x="0" y="0"/><a:chExt cx="0" cy="0"/></a:xfrm></p:grpSpPr>"#;
+
+/// 文字 Run
+fn run(text: &str, size: u32, bold: bool, color: &str, font: &str) -> String {
+    format!(
+        r#"<a:r><a:rPr lang="zh-CN" sz="{}" b="{}" dirty="0"><a:solidFill><a:srgbClr val="{}"/></a:solidFill><a:latin typeface="{}"/><a:ea typeface="{}"/></a:rPr><a:t>{}</a:t></a:r>"#,
+        size * 100,
+        if bold { 1 } else { 0 },
+        color,
+        escape(font),
+        escape(font),
+        escape(text)
+    )
+}
+
+/// 文本框
+fn text_box(id: usize, (x, y, cx, cy): (i64, i64, i64, i64), anchor: &str, paragraphs: &str) -> String {
+    format!(
+        r#"<p:sp><p:nvSpPr><p:cNvPr id="{id}" name="TextBox {id}"/><p:cNvSpPr txBox="1"/><p:nvPr/></p:nvSpPr><p:spPr><a:xfrm><a:off x="{x}" y="{y}"/><a:ext cx="{cx}" cy="{cy}"/></a:xfrm><a:prstGeom prst="rect"><a:avLst/></a:prstGeom><a:noFill/></p:spPr><p:txBody><a:bodyPr wrap="square" anchor="{anchor}"><a:normAutofit/></a:bodyPr><a:lstStyle/>{paragraphs}</p:txBody></p:sp>"#
+    )
+}
+
+/// 纯色矩形（装饰条）
+fn rect(id: usize, (x, y, cx, cy): (i64, i64, i64, i64), color: &str) -> String {
+    format!(
+        r#"<p:sp><p:nvSpPr><p:cNvPr id="{id}" name="Rectangle {id}"/><p:cNvSpPr/><p:nvPr/></p:nvSpPr><p:spPr><a:xfrm><a:off x="{x}" y="{y}"/><a:ext cx="{cx}" cy="{cy}"/></a:xfrm><a:prstGeom prst="rect"><a:avLst/></a:prstGeom><a:solidFill><a:srgbClr val="{color}"/></a:solidFill><a:ln><a:noFill/></a:ln></p:spPr></p:sp>"#
+    )
+}
+
+fn slide_xml(slide: &Slide, theme: &PptxTheme) -> String {
+    const MARGIN: i64 = 685800;
+    let width = SLIDE_WIDTH - MARGIN * 2;
+    let (background, shapes) = match slide.kind {
+        SlideKind::Cover => {
+            let title = format!(r#"<a:p><a:pPr algn="ctr"/>{}</a:p>"#, run(&slide.title, 40, true, theme.primary, theme.title_font));
+            let mut shapes = vec![
+                text_box(2, (MARGIN, 2057400, width, 1371600), "b", &title),
+                rect(3, (SLIDE_WIDTH / 2 - 914400, 3543300, 1828800, 45720), theme.accent),
+            ];
+            if !slide.subtitle.is_empty() {
+                let subtitle = format!(r#"<a:p><a:pPr algn="ctr"/>{}</a:p>"#, run(&slide.subtitle, 20, false, theme.text, theme.body_font));
+                shapes.push(text_box(4, (MARGIN, 3771900, width, 1143000), "t", &subtitle));
+            }
+            (theme.background, shapes)
+        }
+        SlideKind::Section => {
+            let title = format!(r#"<a:p><a:pPr algn="ctr"/>{}</a:p>"#, run(&slide.title, 36, true, theme.background, theme.title_font));
+            (theme.primary, vec![text_box(2, (MARGIN, 2400300, width, 2057400), "ctr", &title)])
+        }
+        SlideKind::Content => {
+            let title = format!("<a:p>{}</a:p>", run(&slide.title, 28, true, theme.primary, theme.title_font));
+            let bullets: String = slide
+                .bullets
+                .iter()
+                .map(|(level, text)| {
+                    format!(
+                        r#"<a:p><a:pPr marL="{}" lvl="{}" indent="-285750"><a:spcBef><a:spcPts val="600"/></a:spcBef><a:buClr><a:srgbClr val="{}"/></a:buClr><a:buFont typeface="Arial"/><a:buChar char="{}"/></a:pPr>{}</a:p>"#,
+                        285750 + *level as i64 * 457200,
+                        level,
+                        theme.accent,
+                        if *level == 0 { "•" } else { "–" },
+                        run(text, if *level == 0 { 20 } else { 18 }, false, theme.text, theme.body_font)
+                    )
+                })
+                .collect();
+            let mut shapes = vec![
+                text_box(2, (MARGIN, 342900, width, 914400), "b", &title),
+                rect(3, (MARGIN, 1295400, width, 27432), theme.accent),
+            ];
+            if !bullets.is_empty() {
+                shapes.push(text_box(4, (MARGIN, 1485900, width, SLIDE_HEIGHT - 1485900 - 457200), "t", &bullets));
+            }
+            (theme.background, shapes)
+        }
+    };
+    format!(
+        r#"{XML_HEADER}<p:sld {NS}><p:cSld><p:bg><p:bgPr><a:solidFill><a:srgbClr val="{background}"/></a:solidFill><a:effectLst/></p:bgPr></p:bg><p:spTree>{EMPTY_TREE}{}</p:spTree></p:cSld><p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sld>"#,
+        shapes.join("")
+    )
+}
+
+fn theme_xml(theme: &PptxTheme) -> String {
+    let fill = r#"<a:solidFill><a:schemeClr val="phClr"/></a:solidFill>"#;
+    let line = r#"<a:ln w="9525"><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:ln>"#;
+    let effect = "<a:effectStyle><a:effectLst/></a:effectStyle>";
+    format!(
+        r#"{XML_HEADER}<a:theme xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" name="AiDocPlus"><a:themeElements><a:clrScheme name="AiDocPlus"><a:dk1><a:srgbClr val="{text}"/></a:dk1><a:lt1><a:srgbClr val="{background}"/></a:lt1><a:dk2><a:srgbClr val="{primary}"/></a:dk2><a:lt2><a:srgbClr val="E7E6E6"/></a:lt2><a:accent1><a:srgbClr val="{accent}"/></a:accent1><a:accent2><a:srgbClr val="{primary}"/></a:accent2><a:accent3><a:srgbClr val="A5A5A5"/></a:accent3><a:accent4><a:srgbClr val="FFC000"/></a:accent4><a:accent5><a:srgbClr val="5B9BD5"/></a:accent5><a:accent6><a:srgbClr val="70AD47"/></a:accent6><a:hlink><a:srgbClr val="0563C1"/></a:hlink><a:folHlink><a:srgbClr val="954F72"/></a:folHlink></a:clrScheme><a:fontScheme name="AiDocPlus"><a:majorFont><a:latin typeface="{title_font}"/><a:ea typeface="{title_font}"/><a:cs typeface=""/></a:majorFont><a:minorFont><a:latin typeface="{body_font}"/><a:ea typeface="{body_font}"/><a:cs typeface=""/></a:minorFont></a:fontScheme><a:fmtScheme name="AiDocPlus"><a:fillStyleLst>{fill}{fill}{fill}</a:fillStyleLst><a:lnStyleLst>{line}{line}{line}</a:lnStyleLst><a:effectStyleLst>{effect}{effect}{effect}</a:effectStyleLst><a:bgFillStyleLst>{fill}{fill}{fill}</a:bgFillStyleLst></a:fmtScheme></a:themeElements><a:objectDefaults/><a:extraClrSchemeLst/></a:theme>"#,
+        text = theme.text,
+        background = theme.background,
+        primary = theme.primary,
+        accent = theme.accent,
+        title_font = escape(theme.title_font),
+        body_font = escape(theme.body_font),
+    )
+}
+
+fn relationships(rels: &[(String, &str, String)]) -> String {
+    let items: String = rels
+        .iter()
+        .map(|(id, kind, target)| {
+            format!(r#"<Relationship Id="{}" Type="http://schemas.openxmlformats.org/{}" Target="{}"/>"#, id, kind, target)
+        })
+        .collect();
+    format!(r#"{XML_HEADER}<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{}</Relationships>"#, items)
+}
+
+const REL_OFFICE: &str = "officeDocument/2006/relationships";
+
+/// 生成 PPTX 包中的全部文件
+fn package(title: &str, slides: &[Slide], theme: &PptxTheme) -> Vec<(String, String)> {
+    let rel = |kind: &str| format!("{}/{}", REL_OFFICE, kind);
+    let mut files: Vec<(String, String)> = Vec::new();
+
+    let slide_overrides: String = (1..=slides.len())
+        .map(|i| format!(r#"<Override PartName="/ppt/slides/slide{}.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.slide+xml"/>"#, i))
+        .collect();
+    files.push((
+        "[Content_Types].xml".to_string(),
+        format!(
+            r#"{XML_HEADER}<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/ppt/presentation.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.presentation.main+xml"/><Override PartName="/ppt/slideMasters/slideMaster1.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.slideMaster+xml"/><Override PartName="/ppt/slideLayouts/slideLayout1.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.slideLayout+xml"/><Override PartName="/ppt/theme/theme1.xml" ContentType="application/vnd.openxmlformats-officedocument.theme+xml"/><Override PartName="/ppt/presProps.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.presProps+xml"/><Override PartName="/ppt/viewProps.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.viewProps+xml"/><Override PartName="/ppt/tableStyles.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.tableStyles+xml"/><Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/><Override PartName="/docProps/app.xml" ContentType="application/vnd.openxmlformats-officedocument.extended-properties+xml"/>{slide_overrides}</Types>"#
+        ),
+    ));
+    files.push((
+        "_rels/.rels".to_string(),
+        relationships(&[
+            ("rId1".to_string(), &rel("officeDocument"), "ppt/presentation.xml".to_string()),
+            ("rId2".to_string(), "package/2006/relationships/metadata/core-properties", "docProps/core.xml".to_string()),
+            ("rId3".to_string(), &rel("extended-properties"), "docProps/app.xml".to_string()),
+        ]),
+    ));
+    files.push((
+        "docProps/core.xml".to_string(),
+        format!(
+            r#"{XML_HEADER}<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"><dc:title>{}</dc:title><dc:creator>AiDocPlus</dc:creator><dcterms:created xsi:type="dcterms:W3CDTF">{}</dcterms:created></cp:coreProperties>"#,
+            escape(title),
+            chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ")
+        ),
+    ));
+    files.push((
+        "docProps/app.xml".to_string(),
+        format!(
+            r#"{XML_HEADER}<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/extended-properties"><Application>AiDocPlus</Application><Slides>{}</Slides></Properties>"#,
+            slides.len()
+        ),
+    ));
+
+    // 演示文稿：rId1 为母版，rId2 起为各页，其后为属性、主题和表格样式
+    let slide_ids: String = (0..slides.len())
+        .map(|i| format!(r#"<p:sldId id="{}" r:id="rId{}"/>"#, 256 + i, i + 2))
+        .collect();
+    files.push((
+        "ppt/presentation.xml".to_string(),
+        format!(
+            r#"{XML_HEADER}<p:presentation {NS} saveSubsetFonts="1"><p:sldMasterIdLst><p:sldMasterId id="2147483648" r:id="rId1"/></p:sldMasterIdLst><p:sldIdLst>{slide_ids}</p:sldIdLst><p:sldSz cx="{SLIDE_WIDTH}" cy="{SLIDE_HEIGHT}"/><p:notesSz cx="6858000" cy="9144000"/></p:presentation>"#
+        ),
+    ));
+    let mut rels = vec![("rId1".to_string(), rel("slideMaster"), "slideMasters/slideMaster1.xml".to_string())];
+    for i in 1..=slides.len() {
+        rels.push((format!("rId{}", i + 1), rel("slide"), format!("slides/slide{}.xml", i)));
+    }
+    let n = slides.len() + 2;
+    rels.push((format!("rId{}", n), rel("presProps"), "presProps.xml".to_string()));
+    rels.push((format!("rId{}", n + 1), rel("viewProps"), "viewProps.xml".to_string()));
+    rels.push((format!("rId{}", n + 2), rel("theme"), "theme/theme1.xml".to_string()));
+    rels.push((format!("rId{}", n + 3), rel("tableStyles"), "tableStyles.xml".to_string()));
+    let rels: Vec<(String, &str, String)> = rels.iter().map(|(id, kind, target)| (id.clone(), kind.as_str(), target.clone())).collect();
+    files.push(("ppt/_rels/presentation.xml.rels".to_string(), relationships(&rels)));
+
+    files.push(("ppt/presProps.xml".to_string(), format!("{XML_HEADER}<p:presentationPr {NS}/>")));
+    files.push(("ppt/viewProps.xml".to_string(), format!("{XML_HEADER}<p:viewPr {NS}/>")));
+    files.push((
+        "ppt/tableStyles.xml".to_string(),
+        format!(r#"{XML_HEADER}<a:tblStyleLst xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" def="{{5C22544A-7EE6-4342-B048-85BDC9FD1C3A}}"/>"#),
+    ));
+    files.push(("ppt/theme/theme1.xml".to_string(), theme_xml(theme)));
+
+    files.push((
+        "ppt/slideMasters/slideMaster1.xml".to_string(),
+        format!(
+            r#"{XML_HEADER}<p:sldMaster {NS}><p:cSld><p:bg><p:bgRef idx="1001"><a:schemeClr val="bg1"/></p:bgRef></p:bg><p:spTree>{EMPTY_TREE}</p:spTree></p:cSld><p:clrMap bg1="lt1" tx1="dk1" bg2="lt2" tx2="dk2" accent1="accent1" accent2="accent2" accent3="accent3" accent4="accent4" accent5="accent5" accent6="accent6" hlink="hlink" folHlink="folHlink"/><p:sldLayoutIdLst><p:sldLayoutId id="2147483649" r:id="rId1"/></p:sldLayoutIdLst><p:txStyles><p:titleStyle><a:lvl1pPr><a:defRPr sz="4400"/></a:lvl1pPr></p:titleStyle><p:bodyStyle><a:lvl1pPr><a:defRPr sz="2000"/></a:lvl1pPr></p:bodyStyle><p:otherStyle><a:lvl1pPr><a:defRPr sz="1800"/></a:lvl1pPr></p:otherStyle></p:txStyles></p:sldMaster>"#
+        ),
+    ));
+    files.push((
+        "ppt/slideMasters/_rels/slideMaster1.xml.rels".to_string(),
+        relationships(&[
+            ("rId1".to_string(), &rel("slideLayout"), "../slideLayouts/slideLayout1.xml".to_string()),
+            ("rId2".to_string(), &rel("theme"), "../theme/theme1.xml".to_string()),
+        ]),
+    ));
+    files.push((
+        "ppt/slideLayouts/slideLayout1.xml".to_string(),
+        format!(
+            r#"{XML_HEADER}<p:sldLayout {NS} preserve="1"><p:cSld name="Blank"><p:spTree>{EMPTY_TREE}</p:spTree></p:cSld><p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sldLayout>"#
+        ),
+    ));
+    files.push((
+        "ppt/slideLayouts/_rels/slideLayout1.xml.rels".to_string(),
+        relationships(&[("rId1".to_string(), &rel("slideMaster"), "../slideMasters/slideMaster1.xml".to_string())]),
+    ));
+
+    for (i, slide) in slides.iter().enumerate() {
+        files.push((format!("ppt/slides/slide{}.xml", i + 1), slide_xml(slide, theme)));
+        files.push((
+            format!("ppt/slides/_rels/slide{}.xml.rels", i + 1),
+            relationships(&[("rId1".to_string(), &rel("slideLayout"), "../slideLayouts/slideLayout1.xml".to_string())]),
+        ));
+    }
+    files
+}
+
+/// 将 Markdown 导出为 PPTX 文件
+pub fn export_to_pptx(markdown: &str, title: &str, output_path: &str, theme_id: &str) -> Result<(), String> {
+    let theme = theme(theme_id)?;
+    let slides = build_slides(markdown, title);
+
+    let file = std::fs::File::create(output_path).map_err(|e| format!("创建文件失败: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, content) in package(title, &slides, theme) {
+        zip.start_file(name, options).map_err(|e| format!("ZIP 写入失败: {}", e))?;
+        zip.write_all(content.as_bytes()).map_err(|e| format!("ZIP 写入失败: {}", e))?;
+    }
+    zip.finish().map_err(|e| format!("生成 PPTX 失败: {}", e))?;
+    Ok(())
+}
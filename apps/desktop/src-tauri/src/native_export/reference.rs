@@ -0,0 +1,65 @@
+/// 参考文档模块
+/// 读取用户提供的参考 DOCX（如单位的 Word 模板），DOCX 导出沿用其样式定义（styles.xml 和主题），
+/// 标题、表格按样式名（heading 1–9、Table Grid）套用对应样式，正文使用 Normal 样式，类似 pandoc --reference-doc
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+/// 参考文档中的样式
+#[derive(Debug, Clone)]
+pub struct ReferenceDoc {
+    pub styles_xml: String,
+    pub theme_xml: Option<String>,
+    /// heading 1–9 对应的样式 ID
+    headings: [Option<String>; 9],
+    /// 表格样式 ID（Table Grid）
+    table: Option<String>,
+}
+
+fn read_part(archive: &mut zip::ZipArchive<Cursor<Vec<u8>>>, name: &str) -> Option<String> {
+    let mut entry = archive.by_name(name).ok()?;
+    let mut xml = String::new();
+    entry.read_to_string(&mut xml).ok()?;
+    Some(xml)
+}
+
+/// 样式名（w:name）到样式 ID 的映射：(类型, 名称小写, ID)
+fn style_ids(styles_xml: &str) -> Vec<(String, String, String)> {
+    let style_re = regex::Regex::new(r#"(?s)<w:style\b([^>]*)>(.*?)</w:style>"#).unwrap();
+    let attr = |text: &str, name: &str| {
+        regex::Regex::new(&format!(r#"\b{}="([^"]*)""#, name)).unwrap().captures(text).map(|c| c[1].to_string())
+    };
+    style_re
+        .captures_iter(styles_xml)
+        .filter_map(|caps| {
+            let kind = attr(&caps[1], "w:type")?;
+            let id = attr(&caps[1], "w:styleId")?;
+            let name_tag = regex::Regex::new(r#"<w:name\b[^>]*/>"#).unwrap().find(&caps[2])?.as_str().to_string();
+            let name = attr(&name_tag, "w:val")?;
+            Some((kind, name.to_lowercase(), id))
+        })
+        .collect()
+}
+
+impl ReferenceDoc {
+    pub fn load(path: &Path) -> Result<ReferenceDoc, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("读取参考文档失败: {}", e))?;
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|_| format!("参考文档不是有效的 DOCX 文件: {}", path.display()))?;
+        let styles_xml = read_part(&mut archive, "word/styles.xml").ok_or("参考文档中没有样式定义 (word/styles.xml)")?;
+        let theme_xml = read_part(&mut archive, "word/theme/theme1.xml");
+
+        let ids = style_ids(&styles_xml);
+        let find = |kind: &str, name: &str| ids.iter().find(|(k, n, _)| k == kind && n == name).map(|(_, _, id)| id.clone());
+        let headings = std::array::from_fn(|i| find("paragraph", &format!("heading {}", i + 1)));
+        let table = find("table", "table grid");
+        Ok(ReferenceDoc { styles_xml, theme_xml, headings, table })
+    }
+
+    /// 标题样式 ID；参考文档中没有该级标题样式时返回 None
+    pub fn heading_style(&self, level: u8) -> Option<&str> {
+        self.headings.get((level.max(1) - 1) as usize)?.as_deref()
+    }
+
+    pub fn table_style(&self) -> Option<&str> {
+        self.table.as_deref()
+    }
+}
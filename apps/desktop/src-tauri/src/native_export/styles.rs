@@ -1,5 +1,8 @@
 #![allow(dead_code)]
 
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
 /// 中国公文排版标准常量 (GB/T 9704-2012)
 /// 页边距 (mm)
 pub const PAGE_MARGIN_TOP: f32 = 37.0;
@@ -43,6 +46,9 @@ pub const FONT_KAITI: &[&str] = &["KaiTi", "STKaiti", "楷体", "Kaiti SC"];
 /// 宋体 (文件标题、页码)
 pub const FONT_SONGTI: &[&str] = &["SimSun", "STSong", "宋体", "Songti SC"];
 
+/// 微软雅黑 (商务文档)
+pub const FONT_YAHEI: &[&str] = &["Microsoft YaHei", "PingFang SC", "微软雅黑", "Hiragino Sans GB"];
+
 /// 西文字体
 pub const FONT_WESTERN: &str = "Times New Roman";
 
@@ -66,80 +72,342 @@ pub fn chars_to_twip(chars: u32) -> i32 {
     (chars as f32 * FONT_SIZE_BODY * 20.0).round() as i32
 }
 
-/// HTML 导出用的 CSS 模板
-pub fn get_html_css() -> &'static str {
-    r#"
-    @page {
-        size: A4;
-        margin: 37mm 26mm 35mm 28mm;
+/// 标题样式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeadingStyle {
+    pub fonts: Vec<String>,
+    /// 字号 (pt)
+    pub size: f32,
+    #[serde(default)]
+    pub bold: bool,
+    #[serde(default)]
+    pub center: bool,
+}
+
+impl HeadingStyle {
+    fn new(fonts: &[&str], size: f32, bold: bool) -> Self {
+        Self { fonts: fonts.iter().map(|f| f.to_string()).collect(), size, bold, center: false }
     }
-    * {
-        margin: 0;
-        padding: 0;
-        box-sizing: border-box;
+
+    fn centered(self) -> Self {
+        Self { center: true, ..self }
     }
-    body {
-        font-family: "FangSong", "STFangsong", "仿宋", "仿宋_GB2312", "PingFang SC", "Microsoft YaHei", sans-serif;
-        font-size: 16pt;
-        line-height: 29pt;
-        color: #000;
-        max-width: 156mm;
-        margin: 0 auto;
-        padding: 37mm 26mm 35mm 28mm;
+
+    /// 首选中文字体
+    pub fn font(&self) -> &str {
+        self.fonts.first().map(String::as_str).unwrap_or(FONT_FANGSONG[0])
     }
-    p {
-        text-indent: 2em;
-        margin: 0;
-        padding: 0;
+}
+
+/// 导出样式方案：字体、页边距、行距、各级标题样式和编号，HTML / DOCX / PDF 共用；
+/// 内置方案见 builtin_profiles，用户方案以 export-style 资源保存（缺少的字段取公文方案的值）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StyleProfile {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// 页边距 (mm)：上、下、左、右
+    pub margins: [f32; 4],
+    /// 正文中文字体（依次回退）
+    pub body_fonts: Vec<String>,
+    pub western_font: String,
+    /// 正文字号 (pt)
+    pub body_size: f32,
+    /// 固定行距 (pt)
+    pub line_spacing: f32,
+    /// 段后间距 (pt)
+    pub paragraph_spacing: f32,
+    /// 首行缩进（字符数），0 表示不缩进
+    pub first_line_indent: u32,
+    /// 各级标题样式，级别超出时使用最后一项
+    pub headings: Vec<HeadingStyle>,
+    /// 各级标题的自动编号格式，{n} 为阿拉伯数字、{cn} 为中文数字，如 "{cn}、"；为空表示不编号
+    pub heading_numbers: Vec<String>,
+    /// 页码两侧加破折号（"— 1 —"）
+    pub page_number_dashes: bool,
+    /// builtin 为内置方案，local 为用户方案
+    pub source: String,
+}
+
+impl Default for StyleProfile {
+    fn default() -> Self {
+        gongwen()
     }
-    /* 文件标题 - 2号宋体居中 */
-    h1 {
-        font-family: "SimSun", "STSong", "宋体", "Songti SC", serif;
-        font-size: 22pt;
-        font-weight: bold;
-        text-align: center;
-        line-height: 1.4;
-        margin: 0.5em 0;
-        text-indent: 0;
+}
+
+fn fonts(list: &[&str]) -> Vec<String> {
+    list.iter().map(|f| f.to_string()).collect()
+}
+
+/// 党政机关公文 (GB/T 9704-2012)
+pub fn gongwen() -> StyleProfile {
+    StyleProfile {
+        id: "gongwen".to_string(),
+        name: "公文".to_string(),
+        description: "GB/T 9704-2012：3号仿宋正文，每页22行，黑体/楷体分级标题".to_string(),
+        margins: [PAGE_MARGIN_TOP, PAGE_MARGIN_BOTTOM, PAGE_MARGIN_LEFT, PAGE_MARGIN_RIGHT],
+        body_fonts: fonts(FONT_FANGSONG),
+        western_font: FONT_WESTERN.to_string(),
+        body_size: FONT_SIZE_BODY,
+        line_spacing: LINE_SPACING_PT,
+        paragraph_spacing: 0.0,
+        first_line_indent: FIRST_LINE_INDENT,
+        headings: vec![
+            HeadingStyle::new(FONT_SONGTI, FONT_SIZE_TITLE, true).centered(),
+            HeadingStyle::new(FONT_HEITI, FONT_SIZE_BODY, false),
+            HeadingStyle::new(FONT_KAITI, FONT_SIZE_BODY, false),
+            HeadingStyle::new(FONT_FANGSONG, FONT_SIZE_BODY, true),
+            HeadingStyle::new(FONT_FANGSONG, FONT_SIZE_BODY, false),
+        ],
+        heading_numbers: Vec::new(),
+        page_number_dashes: true,
+        source: "builtin".to_string(),
     }
-    /* 一级标题 - 3号黑体 */
-    h2 {
-        font-family: "SimHei", "STHeiti", "黑体", "Heiti SC", sans-serif;
-        font-size: 16pt;
-        font-weight: normal;
-        line-height: 29pt;
-        margin: 0.3em 0;
-        text-indent: 0;
+}
+
+/// 学术论文
+pub fn academic() -> StyleProfile {
+    StyleProfile {
+        id: "academic".to_string(),
+        name: "学术论文".to_string(),
+        description: "小四宋体正文，1.5 倍行距，黑体标题居中".to_string(),
+        margins: [25.4, 25.4, 31.8, 31.8],
+        body_fonts: fonts(FONT_SONGTI),
+        line_spacing: 20.0,
+        body_size: 12.0,
+        first_line_indent: 2,
+        headings: vec![
+            HeadingStyle::new(FONT_HEITI, 16.0, true).centered(),
+            HeadingStyle::new(FONT_HEITI, 14.0, true),
+            HeadingStyle::new(FONT_HEITI, 12.0, true),
+            HeadingStyle::new(FONT_SONGTI, 12.0, true),
+            HeadingStyle::new(FONT_SONGTI, 12.0, false),
+        ],
+        page_number_dashes: false,
+        ..gongwen()
     }
-    /* 二级标题 - 3号楷体 */
-    h3 {
-        font-family: "KaiTi", "STKaiti", "楷体", "Kaiti SC", serif;
-        font-size: 16pt;
-        font-weight: normal;
-        line-height: 29pt;
-        margin: 0.3em 0;
-        text-indent: 0;
+}
+
+/// 商务文档
+pub fn business() -> StyleProfile {
+    StyleProfile {
+        id: "business".to_string(),
+        name: "商务".to_string(),
+        description: "微软雅黑 + Arial，段落不缩进、段间留白，标题左对齐".to_string(),
+        margins: [25.4, 25.4, 25.4, 25.4],
+        body_fonts: fonts(FONT_YAHEI),
+        western_font: "Arial".to_string(),
+        body_size: 11.0,
+        line_spacing: 18.0,
+        paragraph_spacing: 6.0,
+        first_line_indent: 0,
+        headings: [20.0, 16.0, 14.0, 12.0, 11.0].iter().map(|size| HeadingStyle::new(FONT_YAHEI, *size, true)).collect(),
+        page_number_dashes: false,
+        ..gongwen()
     }
-    /* 三级标题 - 3号仿宋加粗 */
-    h4 {
-        font-family: "FangSong", "STFangsong", "仿宋", "仿宋_GB2312", sans-serif;
-        font-size: 16pt;
-        font-weight: bold;
-        line-height: 29pt;
-        margin: 0.3em 0;
-        text-indent: 0;
+}
+
+/// 通用简洁样式
+pub fn plain() -> StyleProfile {
+    StyleProfile {
+        id: "plain".to_string(),
+        name: "简洁".to_string(),
+        description: "小四宋体正文，段落不缩进，标题加粗".to_string(),
+        margins: [25.4, 25.4, 25.4, 25.4],
+        body_fonts: fonts(FONT_SONGTI),
+        body_size: 12.0,
+        line_spacing: 18.0,
+        paragraph_spacing: 6.0,
+        first_line_indent: 0,
+        headings: vec![
+            HeadingStyle::new(FONT_HEITI, 18.0, true),
+            HeadingStyle::new(FONT_HEITI, 16.0, true),
+            HeadingStyle::new(FONT_HEITI, 14.0, true),
+            HeadingStyle::new(FONT_SONGTI, 12.0, true),
+            HeadingStyle::new(FONT_SONGTI, 12.0, true),
+        ],
+        page_number_dashes: false,
+        ..gongwen()
     }
-    /* 四级标题 - 3号仿宋 */
-    h5, h6 {
-        font-family: "FangSong", "STFangsong", "仿宋", "仿宋_GB2312", sans-serif;
-        font-size: 16pt;
-        font-weight: normal;
-        line-height: 29pt;
-        margin: 0.3em 0;
-        text-indent: 0;
+}
+
+/// 内置样式方案
+pub fn builtin_profiles() -> Vec<StyleProfile> {
+    vec![gongwen(), academic(), business(), plain()]
+}
+
+/// 默认样式方案
+pub const DEFAULT_PROFILE: &str = "gongwen";
+
+/// 在给定方案中按 ID 查找；为空时使用默认方案
+pub fn find_profile(profiles: &[StyleProfile], id: &str) -> Result<StyleProfile, String> {
+    let id = if id.trim().is_empty() { DEFAULT_PROFILE } else { id.trim() };
+    profiles.iter().find(|p| p.id == id).cloned().ok_or_else(|| format!("未知的导出样式: {}", id))
+}
+
+impl StyleProfile {
+    /// 校验用户方案的取值范围
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("样式名称不能为空".to_string());
+        }
+        if self.margins.iter().any(|m| !(0.0..=100.0).contains(m)) {
+            return Err("页边距必须在 0 到 100 毫米之间".to_string());
+        }
+        if !(6.0..=72.0).contains(&self.body_size) || self.headings.iter().any(|h| !(6.0..=72.0).contains(&h.size)) {
+            return Err("字号必须在 6 到 72 磅之间".to_string());
+        }
+        if !(self.body_size..=200.0).contains(&self.line_spacing) {
+            return Err("行距不能小于正文字号".to_string());
+        }
+        if self.body_fonts.iter().all(|f| f.trim().is_empty()) || self.western_font.trim().is_empty() {
+            return Err("正文字体不能为空".to_string());
+        }
+        if self.headings.is_empty() || self.headings.len() > 6 {
+            return Err("标题样式应为 1 到 6 级".to_string());
+        }
+        if self.first_line_indent > 8 {
+            return Err("首行缩进不能超过 8 个字符".to_string());
+        }
+        Ok(())
+    }
+
+    /// 首选正文中文字体
+    pub fn body_font(&self) -> &str {
+        self.body_fonts.first().map(String::as_str).unwrap_or(FONT_FANGSONG[0])
+    }
+
+    /// 第 level 级标题样式（1 起）
+    pub fn heading(&self, level: u8) -> HeadingStyle {
+        let index = (level.max(1) as usize - 1).min(self.headings.len().saturating_sub(1));
+        self.headings
+            .get(index)
+            .cloned()
+            .unwrap_or_else(|| HeadingStyle { fonts: self.body_fonts.clone(), size: self.body_size, bold: true, center: false })
+    }
+
+    /// 表格、链接等较小的字号
+    pub fn small_size(&self) -> f32 {
+        self.body_size * 0.875
+    }
+
+    /// 代码字号
+    pub fn code_size(&self) -> f32 {
+        self.body_size * 0.75
+    }
+
+    /// 标题行距：字号较大时放宽，不小于正文行距
+    pub fn heading_line_spacing(&self, size: f32) -> f32 {
+        (size * 1.65).round().max(self.line_spacing)
+    }
+
+    /// 首行缩进 (twip)，按正文字号计算字符宽度
+    pub fn first_line_indent_twip(&self) -> i32 {
+        self.chars_to_twip(self.first_line_indent)
+    }
+
+    pub fn chars_to_twip(&self, chars: u32) -> i32 {
+        (chars as f32 * self.body_size * 20.0).round() as i32
+    }
+}
+
+/// 中文数字（1–99）
+fn chinese_number(n: usize) -> String {
+    const DIGITS: [&str; 10] = ["零", "一", "二", "三", "四", "五", "六", "七", "八", "九"];
+    match n {
+        0..=9 => DIGITS[n].to_string(),
+        10..=19 => format!("十{}", DIGITS[n - 10].trim_start_matches('零')),
+        20..=99 => format!("{}十{}", DIGITS[n / 10], DIGITS[n % 10].trim_start_matches('零')),
+        _ => n.to_string(),
+    }
+}
+
+/// 按方案的 heading_numbers 为标题加编号；未设置编号时原样返回
+pub fn number_headings<'a>(markdown: &'a str, profile: &StyleProfile) -> Cow<'a, str> {
+    if profile.heading_numbers.iter().all(|f| f.trim().is_empty()) {
+        return Cow::Borrowed(markdown);
     }
-    /* 代码块 */
-    pre {
+    let sections = crate::outline::split_sections(markdown);
+    let mut counters = [0usize; 6];
+    let outline: Vec<crate::outline::OutlineItem> = sections
+        .sections
+        .iter()
+        .map(|section| {
+            let level = section.level.clamp(1, 6) as usize;
+            counters[level - 1] += 1;
+            counters[level..].iter_mut().for_each(|c| *c = 0);
+            let n = counters[level - 1];
+            let prefix = profile
+                .heading_numbers
+                .get(level - 1)
+                .map(|f| f.replace("{n}", &n.to_string()).replace("{cn}", &chinese_number(n)))
+                .unwrap_or_default();
+            crate::outline::OutlineItem {
+                id: Some(section.id.clone()),
+                level: section.level,
+                title: format!("{}{}", prefix, section.raw_title),
+            }
+        })
+        .collect();
+    Cow::Owned(crate::outline::restructure(markdown, &outline))
+}
+
+fn css_fonts(western: &str, fonts: &[String], generic: &str) -> String {
+    let mut families: Vec<String> = std::iter::once(western).chain(fonts.iter().map(String::as_str)).map(|f| format!("\"{}\"", f)).collect();
+    families.push(generic.to_string());
+    families.join(", ")
+}
+
+/// HTML 导出用的 CSS，按样式方案生成
+pub fn get_html_css(profile: &StyleProfile) -> String {
+    let [top, bottom, left, right] = profile.margins;
+    let headings: String = (1..=6u8)
+        .map(|level| {
+            let h = profile.heading(level);
+            let selector = format!("h{}", level);
+            let line_height = if level == 1 { "1.4".to_string() } else { format!("{}pt", profile.heading_line_spacing(h.size)) };
+            format!(
+                "    {} {{\n        font-family: {};\n        font-size: {}pt;\n        font-weight: {};\n        text-align: {};\n        line-height: {};\n        margin: {};\n        text-indent: 0;\n    }}\n",
+                selector,
+                css_fonts(&profile.western_font, &h.fonts, "sans-serif"),
+                h.size,
+                if h.bold { "bold" } else { "normal" },
+                if h.center { "center" } else { "left" },
+                line_height,
+                if level == 1 { "0.5em 0" } else { "0.3em 0" },
+            )
+        })
+        .collect();
+
+    format!(
+        r#"
+    @page {{
+        size: A4;
+        margin: {top}mm {right}mm {bottom}mm {left}mm;
+    }}
+    * {{
+        margin: 0;
+        padding: 0;
+        box-sizing: border-box;
+    }}
+    body {{
+        font-family: {body_fonts};
+        font-size: {body_size}pt;
+        line-height: {line_spacing}pt;
+        color: #000;
+        max-width: {content_width}mm;
+        margin: 0 auto;
+        padding: {top}mm {right}mm {bottom}mm {left}mm;
+    }}
+    p {{
+        text-indent: {indent}em;
+        margin: 0 0 {paragraph_spacing}pt;
+        padding: 0;
+    }}
+{headings}    /* 代码块 */
+    pre {{
         background-color: #f5f5f5;
         border: 1px solid #ddd;
         border-radius: 4px;
@@ -147,92 +415,182 @@ pub fn get_html_css() -> &'static str {
         margin: 0.5em 0;
         overflow-x: auto;
         font-family: "Consolas", "Monaco", "Courier New", monospace;
-        font-size: 12pt;
+        font-size: {code_size}pt;
         line-height: 1.5;
         text-indent: 0;
-    }
-    code {
+    }}
+    code {{
         font-family: "Consolas", "Monaco", "Courier New", monospace;
         font-size: 0.9em;
         background-color: #f0f0f0;
         padding: 2px 4px;
         border-radius: 3px;
-    }
-    pre code {
+    }}
+    pre code {{
         background: none;
         padding: 0;
         border-radius: 0;
-    }
+    }}
+    /* 封面 */
+    section.cover {{
+        page-break-after: always;
+        min-height: 200mm;
+        padding-top: 60mm;
+        text-align: center;
+        text-indent: 0;
+    }}
+    section.cover p {{ text-indent: 0; }}
+    section.cover .cover-logo {{
+        max-width: 40mm;
+        max-height: 30mm;
+        margin-bottom: 20mm;
+    }}
+    section.cover .cover-title {{
+        font-size: 2em;
+        font-weight: bold;
+        line-height: 1.5;
+        margin-bottom: 40mm;
+    }}
+    /* 水印：固定定位，打印时每页重复 */
+    div.watermark {{
+        position: fixed;
+        top: 0;
+        left: 0;
+        width: 100%;
+        height: 100%;
+        display: flex;
+        align-items: center;
+        justify-content: center;
+        pointer-events: none;
+        z-index: 1000;
+        text-indent: 0;
+    }}
+    div.watermark span {{
+        transform: rotate(-45deg);
+        font-family: {heading_font};
+        font-size: 72pt;
+        color: #808080;
+        white-space: nowrap;
+    }}
+    div.watermark img {{
+        max-width: 60%;
+        max-height: 60%;
+    }}
+    /* 合并导出的分页 */
+    div.page-break {{ page-break-after: always; }}
+    /* 目录 */
+    nav.toc {{
+        page-break-after: always;
+        text-indent: 0;
+    }}
+    nav.toc .toc-title {{
+        text-align: center;
+        font-weight: bold;
+        text-indent: 0;
+    }}
+    nav.toc ul {{
+        list-style: none;
+        padding: 0;
+        margin: 0;
+    }}
+    nav.toc li {{ text-indent: 0; }}
+    nav.toc a {{ color: inherit; text-decoration: none; }}
+    nav.toc .toc-level-2 {{ padding-left: 2em; }}
+    nav.toc .toc-level-3 {{ padding-left: 4em; }}
+    nav.toc .toc-level-4, nav.toc .toc-level-5, nav.toc .toc-level-6 {{ padding-left: 6em; }}
+    /* 标题锚点（生成目录时） */
+    a.anchor {{ display: none; }}
+    /* Mermaid 图表 */
+    figure.mermaid-diagram {{
+        margin: 0.5em 0;
+        text-align: center;
+        text-indent: 0;
+        page-break-inside: avoid;
+    }}
+    figure.mermaid-diagram svg {{
+        max-width: 100%;
+        height: auto;
+    }}
     /* 表格 */
-    table {
+    table {{
         border-collapse: collapse;
         width: 100%;
         margin: 0.5em 0;
-        font-size: 14pt;
-    }
-    th, td {
+        font-size: {small_size}pt;
+    }}
+    th, td {{
         border: 1px solid #000;
         padding: 6px 10px;
         text-align: left;
         text-indent: 0;
-    }
-    th {
+    }}
+    th {{
         background-color: #f0f0f0;
         font-weight: bold;
-    }
-    tr:nth-child(even) {
+    }}
+    tr:nth-child(even) {{
         background-color: #fafafa;
-    }
+    }}
     /* 列表 */
-    ul, ol {
+    ul, ol {{
         margin: 0.3em 0;
         padding-left: 2em;
-    }
-    li {
+    }}
+    li {{
         text-indent: 0;
-        line-height: 29pt;
-    }
+        line-height: {line_spacing}pt;
+    }}
     /* 引用块 */
-    blockquote {
+    blockquote {{
         border-left: 4px solid #ccc;
         margin: 0.5em 0;
         padding: 0.5em 1em;
         color: #555;
         text-indent: 0;
-    }
+    }}
     /* 分隔线 */
-    hr {
+    hr {{
         border: none;
         border-top: 1px solid #ccc;
         margin: 1em 0;
-    }
+    }}
     /* 链接 */
-    a {
+    a {{
         color: #0066cc;
         text-decoration: underline;
-    }
+    }}
     /* 图片 */
-    img {
+    img {{
         max-width: 100%;
         height: auto;
         display: block;
         margin: 0.5em auto;
-    }
+    }}
     /* 强调 */
-    strong { font-weight: bold; }
-    em { font-style: italic; }
+    strong {{ font-weight: bold; }}
+    em {{ font-style: italic; }}
     /* 打印样式 */
-    @media print {
-        body {
+    @media print {{
+        body {{
             padding: 0;
             max-width: none;
-        }
-        pre {
+        }}
+        pre {{
             white-space: pre-wrap;
             word-wrap: break-word;
-        }
-        a { color: #000; text-decoration: none; }
-        a::after { content: " (" attr(href) ")"; font-size: 0.8em; color: #666; }
-    }
-    "#
+        }}
+        a {{ color: #000; text-decoration: none; }}
+        a::after {{ content: " (" attr(href) ")"; font-size: 0.8em; color: #666; }}
+    }}
+    "#,
+        body_fonts = css_fonts(&profile.western_font, &profile.body_fonts, "\"PingFang SC\", \"Microsoft YaHei\", sans-serif"),
+        body_size = profile.body_size,
+        line_spacing = profile.line_spacing,
+        content_width = 210.0 - left - right,
+        indent = profile.first_line_indent,
+        paragraph_spacing = profile.paragraph_spacing,
+        code_size = profile.code_size(),
+        heading_font = css_fonts(&profile.western_font, &profile.heading(1).fonts, "sans-serif"),
+        small_size = profile.small_size(),
+    )
 }
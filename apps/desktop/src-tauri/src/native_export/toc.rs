@@ -0,0 +1,109 @@
+/// 目录生成模块
+/// 从标题生成目录条目，供 HTML（锚点列表）、DOCX（书签 + TOC 域）和 TXT（编号条目）导出使用
+use comrak::nodes::{AstNode, NodeValue};
+use comrak::{parse_document, Anchorizer, Arena, Options};
+
+/// 默认目录层级
+pub const DEFAULT_DEPTH: u8 = 3;
+
+/// 目录条目
+#[derive(Debug, Clone)]
+pub struct TocEntry {
+    pub level: u8,
+    pub title: String,
+    /// HTML 标题锚点，与 comrak header_ids 生成的 ID 一致
+    pub anchor: String,
+    /// 顶层标题的序号（DOCX 书签 _Toc{n}）；引用、列表中的标题为 None
+    pub index: Option<usize>,
+}
+
+/// DOCX 中顶层标题的书签名
+pub fn bookmark_name(index: usize) -> String {
+    format!("_Toc{}", index)
+}
+
+fn collect_text<'a>(node: &'a AstNode<'a>, out: &mut String) {
+    match &node.data.borrow().value {
+        NodeValue::Text(text) => out.push_str(text),
+        NodeValue::Code(code) => out.push_str(&code.literal),
+        NodeValue::SoftBreak | NodeValue::LineBreak => out.push(' '),
+        _ => {
+            for child in node.children() {
+                collect_text(child, out);
+            }
+        }
+    }
+}
+
+/// 收集 1..=depth 级标题；锚点按全部标题依次生成，保证与 HTML 中的 ID 对应
+pub fn collect(markdown: &str, depth: u8) -> Vec<TocEntry> {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.strikethrough = true;
+    options.extension.tasklist = true;
+    let root = parse_document(&arena, markdown, &options);
+
+    let mut anchorizer = Anchorizer::new();
+    let mut top_level = 0;
+    let mut entries = Vec::new();
+    for node in root.descendants() {
+        let level = match &node.data.borrow().value {
+            NodeValue::Heading(heading) => heading.level,
+            _ => continue,
+        };
+        let mut text = String::new();
+        collect_text(node, &mut text);
+        let anchor = anchorizer.anchorize(text.clone());
+        let index = node.parent().is_some_and(|p| p.same_node(root)).then(|| {
+            top_level += 1;
+            top_level - 1
+        });
+        let title = text.trim().to_string();
+        if level <= depth && !title.is_empty() {
+            entries.push(TocEntry { level, title, anchor, index });
+        }
+    }
+    entries
+}
+
+/// HTML 目录：锚点链接列表
+pub fn to_html(entries: &[TocEntry]) -> String {
+    if entries.is_empty() {
+        return String::new();
+    }
+    let items: Vec<String> = entries
+        .iter()
+        .map(|e| {
+            format!(
+                "<li class=\"toc-level-{}\"><a href=\"#{}\">{}</a></li>",
+                e.level,
+                e.anchor,
+                e.title.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+            )
+        })
+        .collect();
+    format!(
+        "<nav class=\"toc\">\n<p class=\"toc-title\">{}</p>\n<ul>\n{}\n</ul>\n</nav>\n",
+        crate::i18n::t("export.toc"),
+        items.join("\n")
+    )
+}
+
+/// 纯文本目录：按层级编号（1、1.1、1.1.1）并缩进
+pub fn to_text(entries: &[TocEntry]) -> String {
+    if entries.is_empty() {
+        return String::new();
+    }
+    let min_level = entries.iter().map(|e| e.level).min().unwrap_or(1);
+    let mut counters = [0usize; 6];
+    let mut lines = vec![crate::i18n::t("export.toc").to_string()];
+    for entry in entries {
+        let depth = (entry.level - min_level) as usize;
+        counters[depth] += 1;
+        counters[depth + 1..].iter_mut().for_each(|c| *c = 0);
+        let number: Vec<String> = counters[..=depth].iter().map(|c| c.max(&1).to_string()).collect();
+        lines.push(format!("{}{} {}", "  ".repeat(depth), number.join("."), entry.title));
+    }
+    lines.join("\n")
+}
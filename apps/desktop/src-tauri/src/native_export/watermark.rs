@@ -0,0 +1,71 @@
+/// 水印模块
+/// 按水印设置生成文字水印（斜向 45°）或图片水印（居中）：HTML / PDF 为固定定位的半透明元素，
+/// DOCX 写入各页眉（文字为 VML 艺术字，图片衬于文字下方），因而每页都会显示
+use crate::settings::WatermarkSettings;
+use base64::Engine;
+
+#[derive(Debug, Clone)]
+pub enum Watermark {
+    Text { text: String, opacity: f32 },
+    /// 已按透明度处理的 PNG
+    Image { png: Vec<u8>, width: u32, height: u32 },
+}
+
+/// 读取水印图片，按透明度缩放 alpha 通道后转换为 PNG
+fn load_image(path: &str, opacity: f32) -> Result<Watermark, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("读取水印图片失败: {}", e))?;
+    let img = image::load_from_memory(&bytes).map_err(|e| format!("无法识别水印图片: {}", e))?;
+    let mut rgba = img.to_rgba8();
+    for pixel in rgba.pixels_mut() {
+        pixel[3] = (pixel[3] as f32 * opacity).round() as u8;
+    }
+    let mut png = std::io::Cursor::new(Vec::new());
+    rgba.write_to(&mut png, image::ImageFormat::Png).map_err(|e| format!("转换水印图片失败: {}", e))?;
+    Ok(Watermark::Image { png: png.into_inner(), width: rgba.width(), height: rgba.height() })
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+impl Watermark {
+    /// 生成水印；图片优先，文字和图片都为空时返回 None
+    pub fn build(settings: &WatermarkSettings) -> Result<Option<Watermark>, String> {
+        let opacity = settings.opacity.clamp(0.0, 1.0);
+        if let Some(path) = settings.image_path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+            return load_image(path, opacity).map(Some);
+        }
+        let text = settings.text.trim();
+        Ok((!text.is_empty()).then(|| Watermark::Text { text: text.to_string(), opacity }))
+    }
+
+    /// HTML 水印：固定定位，打印时每页重复
+    pub fn to_html(&self) -> String {
+        match self {
+            Watermark::Text { text, opacity } => format!(
+                "<div class=\"watermark\" style=\"opacity: {}\"><span>{}</span></div>\n",
+                opacity,
+                escape(text)
+            ),
+            Watermark::Image { png, .. } => format!(
+                "<div class=\"watermark\"><img src=\"data:image/png;base64,{}\" alt=\"\"></div>\n",
+                base64::engine::general_purpose::STANDARD.encode(png)
+            ),
+        }
+    }
+
+    /// DOCX 页眉中的文字水印段落（与 Word 插入的水印相同的 VML 艺术字），index 用于区分各页眉中的形状 ID
+    pub fn vml_paragraph(&self, index: usize) -> Option<String> {
+        let Watermark::Text { text, opacity } = self else { return None };
+        Some(format!(
+            r##"<w:p><w:pPr><w:jc w:val="center"/></w:pPr><w:r><w:pict><v:shapetype id="_x0000_t136" coordsize="21600,21600" o:spt="136" adj="10800" path="m@7,l@8,m@5,21600l@6,21600e"><v:formulas><v:f eqn="sum #0 0 10800"/><v:f eqn="prod #0 2 1"/><v:f eqn="sum 21600 0 @1"/><v:f eqn="sum 0 0 @2"/><v:f eqn="sum 21600 0 @3"/><v:f eqn="if @0 @3 0"/><v:f eqn="if @0 21600 @1"/><v:f eqn="if @0 0 @2"/><v:f eqn="if @0 @4 21600"/><v:f eqn="mid @5 @6"/><v:f eqn="mid @8 @5"/><v:f eqn="mid @7 @8"/><v:f eqn="mid @6 @7"/><v:f eqn="sum @6 0 @5"/></v:formulas><v:path textpathok="t" o:connecttype="custom" o:connectlocs="@9,0;@10,10800;@11,21600;@12,10800" o:connectangles="270,180,90,0"/><v:textpath on="t" fitshape="t"/><o:lock v:ext="edit" text="t" shapetype="t"/></v:shapetype><v:shape id="AiDocPlusWatermark{index}" o:spid="_x0000_s{spid}" type="#_x0000_t136" style="position:absolute;margin-left:0;margin-top:0;width:{width}pt;height:{height}pt;rotation:315;z-index:-251657216;mso-position-horizontal:center;mso-position-horizontal-relative:margin;mso-position-vertical:center;mso-position-vertical-relative:margin" o:allowincell="f" fillcolor="#808080" stroked="f"><v:fill opacity="{opacity}"/><v:textpath style="font-family:&quot;SimHei&quot;;font-size:1pt" string="{text}"/></v:shape></w:pict></w:r></w:p>"##,
+            index = index,
+            spid = 2049 + index,
+            // 艺术字按形状大小拉伸，宽度随字数增加
+            width = (text.chars().count() as f32 * 100.0).clamp(200.0, 460.0),
+            height = 100,
+            opacity = opacity,
+            text = escape(text)
+        ))
+    }
+}
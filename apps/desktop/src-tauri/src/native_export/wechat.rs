@@ -0,0 +1,149 @@
+use comrak::{markdown_to_html, Options};
+use regex::{Captures, Regex};
+
+// 微信公众号编辑器会丢弃 <style>、class 和大部分外链，粘贴时只保留行内 style，
+// 因此所有样式写进 style 属性；代码块用 <br> / &nbsp; 保留换行和缩进，
+// 外部链接改为文末编号引用，本地图片内嵌为 data URI
+
+const ROOT_STYLE: &str = "font-size:16px;color:#333;line-height:1.75;letter-spacing:0.5px;word-break:break-word;\
+font-family:-apple-system,BlinkMacSystemFont,'PingFang SC','Microsoft YaHei',sans-serif;";
+const CODE_FONT: &str = "font-family:Menlo,Consolas,'Courier New',monospace;";
+
+fn tag_style(tag: &str) -> &'static str {
+    match tag {
+        "h1" => "font-size:22px;font-weight:bold;text-align:center;margin:1.2em 0 0.8em;color:#222;",
+        "h2" => "font-size:20px;font-weight:bold;margin:1.2em 0 0.8em;padding-left:10px;border-left:4px solid #07c160;color:#222;",
+        "h3" => "font-size:18px;font-weight:bold;margin:1em 0 0.6em;color:#222;",
+        "h4" | "h5" | "h6" => "font-size:16px;font-weight:bold;margin:1em 0 0.6em;color:#222;",
+        "p" => "margin:0 0 1em;",
+        "blockquote" => "margin:1em 0;padding:0.6em 1em;border-left:4px solid #ddd;background:#f7f7f7;color:#666;",
+        "ul" | "ol" => "margin:0 0 1em;padding-left:2em;",
+        "li" => "margin:0.3em 0;",
+        "table" => "border-collapse:collapse;width:100%;margin:1em 0;font-size:14px;",
+        "th" => "border:1px solid #ddd;padding:6px 10px;background:#f5f5f5;font-weight:bold;",
+        "td" => "border:1px solid #ddd;padding:6px 10px;",
+        "hr" => "border:none;border-top:1px solid #e5e5e5;margin:1.5em 0;",
+        "img" => "display:block;max-width:100%;height:auto;margin:1em auto;border-radius:4px;",
+        "code" => "font-size:90%;color:#c7254e;background:#f6f8fa;padding:2px 4px;border-radius:3px;",
+        "strong" => "font-weight:bold;color:#000;",
+        "em" => "font-style:italic;",
+        "del" => "text-decoration:line-through;",
+        "a" => "color:#576b95;text-decoration:none;",
+        _ => "",
+    }
+}
+
+/// 将 Markdown 转换为可粘贴到公众号编辑器的 HTML 片段
+pub fn export_to_wechat_html(markdown: &str) -> Result<String, String> {
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.strikethrough = true;
+    options.extension.tasklist = true;
+    options.extension.autolink = true;
+    options.render.unsafe_ = true;
+
+    let html = markdown_to_html(markdown, &options);
+
+    // 代码块先取出，避免内部的 <code> 被套上行内代码样式
+    let mut blocks = Vec::new();
+    let pre_re = Regex::new(r#"(?s)<pre[^>]*><code[^>]*>(.*?)</code></pre>"#).unwrap();
+    let html = pre_re.replace_all(&html, |caps: &Captures| {
+        blocks.push(code_block(&caps[1]));
+        format!("\u{0}CODE{}\u{0}", blocks.len() - 1)
+    });
+
+    let (html, links) = convert_links(&html);
+
+    let checkbox_re = Regex::new(r#"<input type="checkbox"([^>]*)/?>"#).unwrap();
+    let html = checkbox_re.replace_all(&html, |caps: &Captures| {
+        if caps[1].contains("checked") { "☑" } else { "☐" }.to_string()
+    });
+
+    let html = crate::email_images::inline_as_data_uris(&html);
+    let mut html = style_tags(&html);
+
+    for (i, block) in blocks.iter().enumerate() {
+        html = html.replace(&format!("\u{0}CODE{}\u{0}", i), block);
+    }
+
+    if !links.is_empty() {
+        html.push_str(&format!(
+            r#"<h3 style="{}">{}</h3>"#,
+            tag_style("h3"),
+            crate::i18n::t("export.wechat_links")
+        ));
+        for (i, (text, url)) in links.iter().enumerate() {
+            html.push_str(&format!(
+                r#"<p style="margin:0 0 0.5em;font-size:13px;color:#888;word-break:break-all;">[{}] {}: {}</p>"#,
+                i + 1,
+                text,
+                url
+            ));
+        }
+    }
+
+    Ok(format!(r#"<section style="{}">{}</section>"#, ROOT_STYLE, html))
+}
+
+/// 代码块：逐行以 <br> 连接，空格转为 &nbsp;，公众号会折叠 white-space:pre
+fn code_block(escaped: &str) -> String {
+    let body = escaped
+        .trim_end_matches('\n')
+        .lines()
+        .map(|line| line.replace('\t', "    ").replace(' ', "&nbsp;"))
+        .collect::<Vec<_>>()
+        .join("<br>");
+    format!(
+        r#"<section style="margin:1em 0;padding:1em;background:#f6f8fa;border-radius:6px;overflow-x:auto;"><code style="display:block;{}font-size:13px;line-height:1.6;color:#333;white-space:nowrap;">{}</code></section>"#,
+        CODE_FONT, body
+    )
+}
+
+/// 公众号正文只允许链接到公众号文章，其他链接改为“文字[n]”并收集到文末
+fn convert_links(html: &str) -> (String, Vec<(String, String)>) {
+    let re = Regex::new(r#"(?s)<a href="([^"]*)"[^>]*>(.*?)</a>"#).unwrap();
+    let tag_re = Regex::new(r"<[^>]+>").unwrap();
+    let mut links: Vec<(String, String)> = Vec::new();
+    let result = re.replace_all(html, |caps: &Captures| {
+        let url = &caps[1];
+        let inner = &caps[2];
+        if url.starts_with("https://mp.weixin.qq.com/") {
+            return caps[0].to_string();
+        }
+        let text = tag_re.replace_all(inner, "").to_string();
+        // 锚点链接和自动链接（文字即网址）不需要文末引用
+        if url.starts_with('#') || text == url || url.is_empty() {
+            return inner.to_string();
+        }
+        let n = match links.iter().position(|(_, u)| u == url) {
+            Some(i) => i + 1,
+            None => {
+                links.push((text, url.to_string()));
+                links.len()
+            }
+        };
+        format!(r#"{}<sup style="font-size:12px;color:#888;">[{}]</sup>"#, inner, n)
+    });
+    (result.into_owned(), links)
+}
+
+/// 为开始标签加上行内样式；表格的 align 属性转为 text-align
+fn style_tags(html: &str) -> String {
+    let re = Regex::new(r"<(h[1-6]|p|blockquote|ul|ol|li|table|th|td|hr|img|code|strong|em|del|a)(\s[^>]*?)?\s*(/?)>").unwrap();
+    let align_re = Regex::new(r#"\s*align="(\w+)""#).unwrap();
+    re.replace_all(html, |caps: &Captures| {
+        let tag = &caps[1];
+        let attrs = caps.get(2).map_or("", |m| m.as_str());
+        let mut style = tag_style(tag).to_string();
+        if tag == "code" {
+            style.insert_str(0, CODE_FONT);
+        }
+        if let Some(align) = align_re.captures(attrs) {
+            style.push_str(&format!("text-align:{};", &align[1]));
+        }
+        let attrs = align_re.replace_all(attrs, "");
+        let close = if caps[3].is_empty() { "" } else { " /" };
+        format!(r#"<{}{} style="{}"{}>"#, tag, attrs, style, close)
+    })
+    .into_owned()
+}
@@ -0,0 +1,246 @@
+/// XLSX 导出模块
+/// 提取文档中的全部 Markdown 表格，每个表格一个工作表（以前面最近的标题命名）；
+/// 表头加粗并冻结，数字单元格按数值写入，列宽按内容自动调整
+use comrak::nodes::{AstNode, NodeValue};
+use comrak::{parse_document, Arena, Options};
+use std::io::Write;
+
+/// 列宽范围（字符）
+const MIN_COLUMN_WIDTH: usize = 6;
+const MAX_COLUMN_WIDTH: usize = 60;
+
+struct Sheet {
+    name: String,
+    rows: Vec<Vec<String>>,
+}
+
+fn collect_text<'a>(node: &'a AstNode<'a>, out: &mut String) {
+    match &node.data.borrow().value {
+        NodeValue::Text(text) => out.push_str(text),
+        NodeValue::Code(code) => out.push_str(&code.literal),
+        NodeValue::SoftBreak | NodeValue::LineBreak => out.push(' '),
+        _ => {
+            for child in node.children() {
+                collect_text(child, out);
+            }
+        }
+    }
+}
+
+fn text_of<'a>(node: &'a AstNode<'a>) -> String {
+    let mut text = String::new();
+    collect_text(node, &mut text);
+    text.trim().to_string()
+}
+
+/// 工作表名：去掉 Excel 不允许的字符，最长 31 个字符，重名时加序号
+fn sheet_name(title: &str, used: &[String]) -> String {
+    let base: String = title
+        .chars()
+        .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+        .collect::<String>()
+        .trim()
+        .trim_matches('\'')
+        .chars()
+        .take(31)
+        .collect();
+    let base = if base.is_empty() { format!("表格{}", used.len() + 1) } else { base };
+    let mut name = base.clone();
+    let mut n = 2;
+    while used.iter().any(|u| u.eq_ignore_ascii_case(&name)) {
+        let suffix = format!(" ({})", n);
+        name = format!("{}{}", base.chars().take(31 - suffix.chars().count()).collect::<String>(), suffix);
+        n += 1;
+    }
+    name
+}
+
+/// 提取表格，按文档顺序
+fn collect_sheets(markdown: &str) -> Vec<Sheet> {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.strikethrough = true;
+    options.extension.tasklist = true;
+    let root = parse_document(&arena, markdown, &options);
+
+    let mut sheets: Vec<Sheet> = Vec::new();
+    let mut heading = String::new();
+    for node in root.descendants() {
+        match &node.data.borrow().value {
+            NodeValue::Heading(_) => heading = text_of(node),
+            NodeValue::Table(_) => {
+                let rows: Vec<Vec<String>> = node.children().map(|row| row.children().map(text_of).collect()).collect();
+                if rows.is_empty() {
+                    continue;
+                }
+                let used: Vec<String> = sheets.iter().map(|s| s.name.clone()).collect();
+                sheets.push(Sheet { name: sheet_name(&heading, &used), rows });
+            }
+            _ => {}
+        }
+    }
+    sheets
+}
+
+/// 列号转字母：0 → A，26 → AA
+fn column_name(mut index: usize) -> String {
+    let mut name = String::new();
+    loop {
+        name.insert(0, (b'A' + (index % 26) as u8) as char);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name
+}
+
+/// 显示宽度：中文等全角字符按 2 计
+fn display_width(text: &str) -> usize {
+    text.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum()
+}
+
+/// 可按数值写入的单元格（不含前导零的编号、百分号等）
+fn as_number(text: &str) -> Option<f64> {
+    let plain = text.replace(',', "");
+    if plain.is_empty() || plain.len() > 15 || (plain.starts_with('0') && plain.len() > 1 && !plain.starts_with("0.")) {
+        return None;
+    }
+    plain.parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+/// 转义 XML 特殊字符，并去掉 XML 1.0 不允许的控制字符（制表、换行、回车除外），否则 Excel 会报文件损坏
+fn escape(text: &str) -> String {
+    text.chars()
+        .filter(|&c| c >= ' ' || matches!(c, '\t' | '\n' | '\r'))
+        .collect::<String>()
+        .replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+const XML_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#;
+const NS_MAIN: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+const NS_REL: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+/// 样式：0 默认，1 表头（加粗、灰底、细边框），2 正文（细边框）
+const STYLES_XML: &str = r#"<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><fonts count="2"><font><sz val="11"/><name val="等线"/></font><font><b/><sz val="11"/><name val="等线"/></font></fonts><fills count="3"><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill><fill><patternFill patternType="solid"><fgColor rgb="FFD9E1F2"/><bgColor indexed="64"/></patternFill></fill></fills><borders count="2"><border><left/><right/><top/><bottom/><diagonal/></border><border><left style="thin"><color rgb="FFBFBFBF"/></left><right style="thin"><color rgb="FFBFBFBF"/></right><top style="thin"><color rgb="FFBFBFBF"/></top><bottom style="thin"><color rgb="FFBFBFBF"/></bottom><diagonal/></border></borders><cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs><cellXfs count="3"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/><xf numFmtId="0" fontId="1" fillId="2" borderId="1" xfId="0" applyFont="1" applyFill="1" applyBorder="1"><alignment vertical="center" wrapText="1"/></xf><xf numFmtId="0" fontId="0" fillId="0" borderId="1" xfId="0" applyBorder="1"><alignment vertical="top" wrapText="1"/></xf></cellXfs><cellStyles count="1"><cellStyle name="Normal" xfId="0" builtinId="0"/></cellStyles></styleSheet>"#;
+
+fn sheet_xml(sheet: &Sheet) -> String {
+    let columns = sheet.rows.iter().map(Vec::len).max().unwrap_or(0);
+    let cols: String = (0..columns)
+        .map(|c| {
+            let width = sheet
+                .rows
+                .iter()
+                .filter_map(|row| row.get(c))
+                .map(|cell| display_width(cell))
+                .max()
+                .unwrap_or(0)
+                .clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH)
+                + 2;
+            format!(r#"<col min="{0}" max="{0}" width="{1}" customWidth="1"/>"#, c + 1, width)
+        })
+        .collect();
+    let rows: String = sheet
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(r, row)| {
+            let style = if r == 0 { 1 } else { 2 };
+            let cells: String = row
+                .iter()
+                .enumerate()
+                .map(|(c, text)| {
+                    let reference = format!("{}{}", column_name(c), r + 1);
+                    match as_number(text).filter(|_| r > 0) {
+                        Some(number) => format!(r#"<c r="{}" s="{}"><v>{}</v></c>"#, reference, style, number),
+                        None => format!(
+                            r#"<c r="{}" s="{}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+                            reference,
+                            style,
+                            escape(text)
+                        ),
+                    }
+                })
+                .collect();
+            format!(r#"<row r="{}">{}</row>"#, r + 1, cells)
+        })
+        .collect();
+    // 冻结表头行
+    let views = if sheet.rows.len() > 1 {
+        r#"<sheetViews><sheetView workbookViewId="0"><pane ySplit="1" topLeftCell="A2" activePane="bottomLeft" state="frozen"/></sheetView></sheetViews>"#
+    } else {
+        r#"<sheetViews><sheetView workbookViewId="0"/></sheetViews>"#
+    };
+    let cols = if cols.is_empty() { String::new() } else { format!("<cols>{}</cols>", cols) };
+    format!(r#"{XML_HEADER}<worksheet xmlns="{NS_MAIN}" xmlns:r="{NS_REL}">{views}<sheetFormatPr defaultRowHeight="15"/>{cols}<sheetData>{rows}</sheetData></worksheet>"#)
+}
+
+/// 生成 XLSX 包中的全部文件
+fn package(title: &str, sheets: &[Sheet]) -> Vec<(String, String)> {
+    let mut files: Vec<(String, String)> = Vec::new();
+    let sheet_overrides: String = (1..=sheets.len())
+        .map(|i| format!(r#"<Override PartName="/xl/worksheets/sheet{}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#, i))
+        .collect();
+    files.push((
+        "[Content_Types].xml".to_string(),
+        format!(
+            r#"{XML_HEADER}<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/><Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/>{sheet_overrides}</Types>"#
+        ),
+    ));
+    files.push((
+        "_rels/.rels".to_string(),
+        format!(
+            r#"{XML_HEADER}<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="{NS_REL}/officeDocument" Target="xl/workbook.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/></Relationships>"#
+        ),
+    ));
+    files.push((
+        "docProps/core.xml".to_string(),
+        format!(
+            r#"{XML_HEADER}<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"><dc:title>{}</dc:title><dc:creator>AiDocPlus</dc:creator><dcterms:created xsi:type="dcterms:W3CDTF">{}</dcterms:created></cp:coreProperties>"#,
+            escape(title),
+            chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ")
+        ),
+    ));
+
+    let sheet_entries: String = sheets
+        .iter()
+        .enumerate()
+        .map(|(i, s)| format!(r#"<sheet name="{}" sheetId="{}" r:id="rId{}"/>"#, escape(&s.name), i + 1, i + 1))
+        .collect();
+    files.push((
+        "xl/workbook.xml".to_string(),
+        format!(r#"{XML_HEADER}<workbook xmlns="{NS_MAIN}" xmlns:r="{NS_REL}"><bookViews><workbookView/></bookViews><sheets>{sheet_entries}</sheets></workbook>"#),
+    ));
+    let mut rels: String = (1..=sheets.len())
+        .map(|i| format!(r#"<Relationship Id="rId{0}" Type="{NS_REL}/worksheet" Target="worksheets/sheet{0}.xml"/>"#, i))
+        .collect();
+    rels.push_str(&format!(r#"<Relationship Id="rId{}" Type="{NS_REL}/styles" Target="styles.xml"/>"#, sheets.len() + 1));
+    files.push((
+        "xl/_rels/workbook.xml.rels".to_string(),
+        format!(r#"{XML_HEADER}<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{rels}</Relationships>"#),
+    ));
+    files.push(("xl/styles.xml".to_string(), format!("{XML_HEADER}{STYLES_XML}")));
+    for (i, sheet) in sheets.iter().enumerate() {
+        files.push((format!("xl/worksheets/sheet{}.xml", i + 1), sheet_xml(sheet)));
+    }
+    files
+}
+
+/// 将文档中的表格导出为 XLSX 文件；文档中没有表格时返回错误
+pub fn export_to_xlsx(markdown: &str, title: &str, output_path: &str) -> Result<(), String> {
+    let sheets = collect_sheets(markdown);
+    if sheets.is_empty() {
+        return Err("文档中没有表格，无法导出为 Excel".to_string());
+    }
+
+    let file = std::fs::File::create(output_path).map_err(|e| format!("创建文件失败: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, content) in package(title, &sheets) {
+        zip.start_file(name, options).map_err(|e| format!("ZIP 写入失败: {}", e))?;
+        zip.write_all(content.as_bytes()).map_err(|e| format!("ZIP 写入失败: {}", e))?;
+    }
+    zip.finish().map_err(|e| format!("生成 XLSX 失败: {}", e))?;
+    Ok(())
+}
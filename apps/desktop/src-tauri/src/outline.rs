@@ -0,0 +1,205 @@
+// 大纲重组：用 comrak 解析标题把文档切成章节（标题 + 到下一个标题之前的内容），
+// AI 给出新的标题结构（引用原章节 ID），再按新结构重新拼接，章节正文原样保留
+
+use crate::ai::ChatMessage;
+use comrak::nodes::{AstNode, NodeValue};
+use comrak::{parse_document, Arena, Options};
+use serde::{Deserialize, Serialize};
+
+/// 发送给模型的每个章节正文预览长度（字符）
+const PREVIEW_CHARS: usize = 200;
+
+/// 大纲条目；id 引用原文中的章节（如 "s3"），为空表示新增的标题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutlineItem {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub level: u8,
+    pub title: String,
+}
+
+/// 原文中的章节
+#[derive(Debug, Clone)]
+pub struct Section {
+    pub id: String,
+    pub level: u8,
+    pub title: String,
+    /// 原文中的标题文字（保留行内格式），标题未改动时使用
+    pub raw_title: String,
+    /// 标题之后的正文（不含标题行）
+    pub body: String,
+}
+
+/// 拆分结果：第一个标题之前的内容和各章节
+#[derive(Debug, Clone, Default)]
+pub struct Sections {
+    pub preamble: String,
+    pub sections: Vec<Section>,
+}
+
+fn collect_text<'a>(node: &'a AstNode<'a>, out: &mut String) {
+    match &node.data.borrow().value {
+        NodeValue::Text(text) => out.push_str(text),
+        NodeValue::Code(code) => out.push_str(&code.literal),
+        NodeValue::SoftBreak | NodeValue::LineBreak => out.push(' '),
+        _ => {}
+    }
+    for child in node.children() {
+        collect_text(child, out);
+    }
+}
+
+/// 按顶层标题拆分章节（代码块和引用中的 # 不算标题）
+pub fn split_sections(markdown: &str) -> Sections {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.strikethrough = true;
+    options.extension.tasklist = true;
+    options.extension.front_matter_delimiter = Some("---".to_string());
+    let root = parse_document(&arena, markdown, &options);
+
+    // (级别, 标题, 起始行, 结束行)，行号从 1 开始
+    let headings: Vec<(u8, String, usize, usize)> = root
+        .children()
+        .filter_map(|node| {
+            let data = node.data.borrow();
+            let NodeValue::Heading(heading) = &data.value else { return None };
+            let mut title = String::new();
+            collect_text(node, &mut title);
+            Some((heading.level, title.trim().to_string(), data.sourcepos.start.line, data.sourcepos.end.line))
+        })
+        .collect();
+
+    let lines: Vec<&str> = markdown.lines().collect();
+    let slice = |from: usize, to: usize| lines[from.min(lines.len())..to.min(lines.len())].join("\n").trim_matches('\n').to_string();
+    let preamble = slice(0, headings.first().map_or(lines.len(), |h| h.2 - 1));
+    // ATX 标题去掉前后的 #，Setext 标题取第一行
+    let raw_title = |line: usize| {
+        let text = lines.get(line - 1).copied().unwrap_or_default().trim();
+        match text.strip_prefix('#') {
+            Some(_) => text.trim_start_matches('#').trim_end_matches('#').trim().to_string(),
+            None => text.to_string(),
+        }
+    };
+    let sections = headings
+        .iter()
+        .enumerate()
+        .map(|(i, (level, title, start, end))| Section {
+            id: format!("s{}", i + 1),
+            level: *level,
+            title: title.clone(),
+            raw_title: raw_title(*start),
+            body: slice(*end, headings.get(i + 1).map_or(lines.len(), |next| next.2 - 1)),
+        })
+        .collect();
+    Sections { preamble, sections }
+}
+
+/// 原文的标题结构
+pub fn current_outline(sections: &Sections) -> Vec<OutlineItem> {
+    sections
+        .sections
+        .iter()
+        .map(|s| OutlineItem { id: Some(s.id.clone()), level: s.level, title: s.title.clone() })
+        .collect()
+}
+
+/// 构造请求消息：列出各章节的 ID、级别、标题和正文开头
+pub fn build_messages(sections: &Sections, instruction: Option<&str>) -> Vec<ChatMessage> {
+    let listing: Vec<String> = sections
+        .sections
+        .iter()
+        .map(|s| {
+            let preview: String = s.body.chars().take(PREVIEW_CHARS).collect();
+            format!("[{}] {} {}\n{}", s.id, "#".repeat(s.level as usize), s.title, preview.replace('\n', " "))
+        })
+        .collect();
+    let system = "你是文档结构编辑。根据各章节的内容为文档设计更合理的标题结构：可以调整顺序、层级和标题文字，\
+                  可以新增用于归类的标题（id 为 null），每个已有章节必须且只能出现一次。\
+                  只输出 JSON 对象，格式为 {\"outline\": [{\"id\": \"s1\", \"level\": 1, \"title\": \"标题\"}]}，不要输出其他内容。";
+    let mut user = format!("文档共 {} 个章节：\n\n{}", sections.sections.len(), listing.join("\n\n"));
+    if let Some(instruction) = instruction.map(str::trim).filter(|i| !i.is_empty()) {
+        user = format!("调整要求：{}\n\n{}", instruction, user);
+    }
+    vec![
+        ChatMessage { role: "system".to_string(), content: system.to_string(), images: Vec::new() },
+        ChatMessage { role: "user".to_string(), content: user, images: Vec::new() },
+    ]
+}
+
+/// 解析并校验模型给出的大纲：未知 ID 视为新标题，重复的 ID 只保留第一次，遗漏的章节按原级别补在末尾
+pub fn parse_outline(output: &str, sections: &Sections) -> Result<Vec<OutlineItem>, String> {
+    let start = output.find('{').ok_or("AI 返回的大纲不是 JSON")?;
+    let end = output.rfind('}').filter(|&e| e > start).ok_or("AI 返回的大纲不是 JSON")?;
+    let value: serde_json::Value =
+        serde_json::from_str(&output[start..=end]).map_err(|e| format!("解析 AI 大纲失败: {}", e))?;
+    let items: Vec<OutlineItem> = value
+        .get("outline")
+        .and_then(|o| o.as_array())
+        .ok_or("AI 返回的大纲缺少 outline 字段")?
+        .iter()
+        .filter_map(|item| serde_json::from_value(item.clone()).ok())
+        .collect();
+
+    let known = |id: &str| sections.sections.iter().any(|s| s.id == id);
+    let mut seen: Vec<String> = Vec::new();
+    let mut outline: Vec<OutlineItem> = Vec::new();
+    for mut item in items {
+        item.level = item.level.clamp(1, 6);
+        item.title = item.title.trim().to_string();
+        match item.id.take().filter(|id| known(id)) {
+            Some(id) if seen.contains(&id) => continue,
+            Some(id) => {
+                seen.push(id.clone());
+                item.id = Some(id);
+            }
+            None if item.title.is_empty() => continue,
+            None => {}
+        }
+        outline.push(item);
+    }
+    for section in sections.sections.iter().filter(|s| !seen.contains(&s.id)) {
+        outline.push(OutlineItem { id: Some(section.id.clone()), level: section.level, title: section.title.clone() });
+    }
+    Ok(outline)
+}
+
+/// 按大纲重新拼接文档：章节正文原样保留，大纲中没有引用的章节按原顺序追加在末尾
+pub fn restructure(markdown: &str, outline: &[OutlineItem]) -> String {
+    let sections = split_sections(markdown);
+    let mut parts: Vec<String> = Vec::new();
+    if !sections.preamble.is_empty() {
+        parts.push(sections.preamble.clone());
+    }
+    let mut used: Vec<&str> = Vec::new();
+    for item in outline {
+        let section = item.id.as_deref().and_then(|id| sections.sections.iter().find(|s| s.id == id));
+        if let Some(section) = section {
+            if used.contains(&section.id.as_str()) {
+                continue;
+            }
+            used.push(&section.id);
+        }
+        let title = match section {
+            Some(s) if item.title.trim().is_empty() || item.title.trim() == s.title => s.raw_title.as_str(),
+            _ => item.title.as_str(),
+        };
+        push_section(&mut parts, item.level, title, section.map(|s| s.body.as_str()));
+    }
+    for section in sections.sections.iter().filter(|s| !used.contains(&s.id.as_str())) {
+        push_section(&mut parts, section.level, &section.raw_title, Some(&section.body));
+    }
+    let mut result = parts.join("\n\n");
+    result.push('\n');
+    result
+}
+
+fn push_section(parts: &mut Vec<String>, level: u8, title: &str, body: Option<&str>) {
+    let heading = format!("{} {}", "#".repeat(level.clamp(1, 6) as usize), title.trim());
+    match body.filter(|b| !b.is_empty()) {
+        Some(body) => parts.push(format!("{}\n\n{}", heading, body)),
+        None => parts.push(heading),
+    }
+}
@@ -1,6 +1,9 @@
+use crate::plugin_migration::{self, PluginMigration};
+use crate::resource_engine::{ResourceEngine, ResourceEngineState, ResourceSummary};
+use crate::tools::PluginToolDeclaration;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginManifest {
@@ -42,8 +45,17 @@ pub struct PluginManifest {
     pub dependencies: Option<Vec<String>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub conflicts: Option<Vec<String>>,
+    /// 主版本升级时对文档 pluginData 执行的数据迁移
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub migrations: Option<Vec<PluginMigration>>,
+    /// 插件提供给 AI 调用的工具
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<PluginToolDeclaration>>,
 }
 
+/// 插件在资源引擎中的 resource_type
+const RESOURCE_TYPE: &str = "plugin";
+
 fn default_plugin_type() -> String {
     "external".to_string()
 }
@@ -62,18 +74,23 @@ pub fn get_plugins_dir() -> PathBuf {
 pub fn ensure_plugins_dir() {
     let plugins_dir = get_plugins_dir();
     if let Err(e) = fs::create_dir_all(&plugins_dir) {
-        eprintln!("Failed to create plugins directory: {}", e);
+        tracing::warn!("Failed to create plugins directory: {}", e);
     }
 }
 
 /// 同步插件 manifest 到磁盘（幂等）
 /// 前端发现插件后调用，将 manifest 写入 ~/AiDocPlus/Plugins/{id}/manifest.json
 /// 如果已存在，保留用户修改的 enabled 状态，只更新元数据
-pub fn sync_plugin_manifests(manifests: Vec<PluginManifest>) -> Result<(), String> {
+/// 首次安装的插件若依赖未满足则以禁用状态写入；返回依赖/冲突警告
+pub fn sync_plugin_manifests(state: &ResourceEngineState, manifests: Vec<PluginManifest>) -> Result<Vec<String>, String> {
     let plugins_dir = get_plugins_dir();
     fs::create_dir_all(&plugins_dir).map_err(|e| format!("Failed to create plugins dir: {}", e))?;
 
     let now = chrono::Utc::now().timestamp();
+    let mut newly_installed = Vec::new();
+    let mut upgraded = Vec::new();
+    let mut written = Vec::new();
+    let mut history = Vec::new();
 
     for mut manifest in manifests {
         let plugin_dir = plugins_dir.join(&manifest.id);
@@ -89,25 +106,160 @@ pub fn sync_plugin_manifests(manifests: Vec<PluginManifest>) -> Result<(), Strin
                     manifest.enabled = existing.enabled;
                     manifest.created_at = existing.created_at;
                     manifest.updated_at = existing.updated_at;
+                    if existing.version != manifest.version {
+                        history.push((manifest.id.clone(), "upgrade", Some(existing.version.clone()), manifest.version.clone()));
+                    }
+                    if plugin_migration::major_version(&existing.version)
+                        < plugin_migration::major_version(&manifest.version)
+                    {
+                        manifest.updated_at = now;
+                        upgraded.push(manifest.clone());
+                    }
                 }
             }
         } else {
             // 首次写入：设置时间戳
             manifest.created_at = now;
             manifest.updated_at = now;
+            newly_installed.push(manifest.id.clone());
+            history.push((manifest.id.clone(), "install", None, manifest.version.clone()));
+        }
+
+        write_manifest(&manifest_path, &manifest)?;
+        written.push(manifest);
+    }
+
+    update_index(state, |engine| {
+        for manifest in &written {
+            index_manifest(engine, manifest)?;
+        }
+        for (id, action, from, to) in &history {
+            engine.record_install(id, action, from.as_deref(), Some(to))?;
+        }
+        engine.rebuild_fts()
+    });
+
+    // 依赖检查放在全部写入之后，同一批次内的插件可以互相满足依赖
+    let mut warnings = Vec::new();
+    let mut installed = list_plugins(state);
+    for id in &newly_installed {
+        let Some(idx) = installed.iter().position(|p| &p.id == id) else { continue };
+        if !installed[idx].enabled {
+            continue;
+        }
+        if let Err(e) = check_dependencies(&installed[idx], &installed) {
+            installed[idx].enabled = false;
+            let manifest_path = plugins_dir.join(id).join("manifest.json");
+            write_manifest(&manifest_path, &installed[idx])?;
+            update_index(state, |engine| {
+                index_manifest(engine, &installed[idx])?;
+                engine.rebuild_fts()
+            });
+            warnings.push(format!("{}，已自动禁用", e));
+        }
+    }
+
+    // 主版本升级：迁移所有文档中该插件的数据
+    for manifest in &upgraded {
+        match plugin_migration::run_migrations(manifest) {
+            Ok(report) => {
+                if report.migrated_documents > 0 {
+                    warnings.push(format!(
+                        "插件 {} 升级到 v{}，已迁移 {} 个文档的插件数据",
+                        manifest.id, report.to_major, report.migrated_documents
+                    ));
+                }
+                for f in report.failed {
+                    warnings.push(format!("插件 {} 数据迁移失败: {}", manifest.id, f));
+                }
+            }
+            Err(e) => warnings.push(format!("插件 {} 数据迁移失败: {}", manifest.id, e)),
+        }
+    }
+
+    for (i, manifest) in installed.iter().enumerate() {
+        if !manifest.enabled {
+            continue;
+        }
+        // 每对冲突只报告一次
+        for other_id in find_enabled_conflicts(manifest, &installed) {
+            if installed.iter().position(|p| p.id == other_id).is_some_and(|j| j > i) {
+                warnings.push(conflict_warning(&manifest.id, &other_id));
+            }
         }
+    }
 
-        let json = serde_json::to_string_pretty(&manifest)
-            .map_err(|e| format!("Failed to serialize manifest {}: {}", manifest.id, e))?;
-        fs::write(&manifest_path, json)
-            .map_err(|e| format!("Failed to write manifest {}: {}", manifest.id, e))?;
+    for w in &warnings {
+        tracing::warn!("{}", w);
     }
 
+    Ok(warnings)
+}
+
+fn write_manifest(manifest_path: &Path, manifest: &PluginManifest) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize manifest {}: {}", manifest.id, e))?;
+    fs::write(manifest_path, json)
+        .map_err(|e| format!("Failed to write manifest {}: {}", manifest.id, e))
+}
+
+/// 检查插件依赖：所有依赖插件必须已安装且处于启用状态
+pub fn check_dependencies(manifest: &PluginManifest, installed: &[PluginManifest]) -> Result<(), String> {
+    let Some(deps) = &manifest.dependencies else { return Ok(()) };
+    for dep_id in deps {
+        match installed.iter().find(|p| &p.id == dep_id) {
+            None => {
+                return Err(format!("插件 {} 缺少依赖插件: {}", manifest.id, dep_id));
+            }
+            Some(dep) if !dep.enabled => {
+                return Err(format!("插件 {} 依赖的插件 {} 未启用", manifest.id, dep_id));
+            }
+            Some(_) => {}
+        }
+    }
     Ok(())
 }
 
+/// 返回与指定插件冲突且当前已启用的插件 ID（双向检查 conflicts 声明）
+pub fn find_enabled_conflicts(manifest: &PluginManifest, installed: &[PluginManifest]) -> Vec<String> {
+    installed
+        .iter()
+        .filter(|other| other.id != manifest.id && other.enabled)
+        .filter(|other| {
+            let declared = manifest.conflicts.as_ref().is_some_and(|c| c.contains(&other.id));
+            let reverse = other.conflicts.as_ref().is_some_and(|c| c.contains(&manifest.id));
+            declared || reverse
+        })
+        .map(|other| other.id.clone())
+        .collect()
+}
+
+fn conflict_warning(a: &str, b: &str) -> String {
+    format!("插件 {} 与 {} 存在冲突，同时启用可能导致异常", a, b)
+}
+
+/// 从资源引擎索引读取所有插件 manifest；引擎不可用时回退到目录扫描
+pub fn list_plugins(state: &ResourceEngineState) -> Vec<PluginManifest> {
+    match state.with_engine(|engine| engine.list_manifests(RESOURCE_TYPE)) {
+        Ok(rows) => rows
+            .iter()
+            .filter_map(|json| match serde_json::from_str::<PluginManifest>(json) {
+                Ok(manifest) => Some(manifest),
+                Err(e) => {
+                    tracing::warn!("Failed to parse indexed plugin manifest: {}", e);
+                    None
+                }
+            })
+            .collect(),
+        Err(e) => {
+            tracing::warn!("资源引擎不可用，回退到目录扫描: {}", e);
+            scan_plugins_dir()
+        }
+    }
+}
+
 /// 扫描插件目录，返回所有 manifest
-pub fn list_plugins() -> Vec<PluginManifest> {
+fn scan_plugins_dir() -> Vec<PluginManifest> {
     let plugins_dir = get_plugins_dir();
     if !plugins_dir.exists() {
         return Vec::new();
@@ -127,9 +279,9 @@ pub fn list_plugins() -> Vec<PluginManifest> {
             match fs::read_to_string(&manifest_path) {
                 Ok(json) => match serde_json::from_str::<PluginManifest>(&json) {
                     Ok(manifest) => plugins.push(manifest),
-                    Err(e) => eprintln!("Failed to parse manifest {:?}: {}", manifest_path, e),
+                    Err(e) => tracing::warn!("Failed to parse manifest {:?}: {}", manifest_path, e),
                 },
-                Err(e) => eprintln!("Failed to read manifest {:?}: {}", manifest_path, e),
+                Err(e) => tracing::warn!("Failed to read manifest {:?}: {}", manifest_path, e),
             }
         }
     }
@@ -137,8 +289,71 @@ pub fn list_plugins() -> Vec<PluginManifest> {
     plugins
 }
 
+/// 以磁盘上的 manifest 重建插件索引，并移除目录已不存在的插件（应用启动时调用）
+pub fn index_plugins(state: &ResourceEngineState) -> Result<(), String> {
+    let on_disk = scan_plugins_dir();
+    let indexed = list_plugins(state);
+    state.with_engine(|engine| {
+        for manifest in &on_disk {
+            index_manifest(engine, manifest)?;
+        }
+        for stale in indexed.iter().filter(|p| !on_disk.iter().any(|d| d.id == p.id)) {
+            engine.delete(&stale.id)?;
+            engine.record_install(&stale.id, "uninstall", Some(&stale.version), None)?;
+        }
+        engine.rebuild_fts()
+    })
+}
+
+/// 更新资源引擎索引；失败只记录日志，磁盘上的 manifest 仍是完整数据
+fn update_index<F>(state: &ResourceEngineState, f: F)
+where
+    F: FnOnce(&ResourceEngine) -> rusqlite::Result<()>,
+{
+    if let Err(e) = state.with_engine(f) {
+        tracing::warn!("更新资源索引失败: {}", e);
+    }
+}
+
+/// 将 manifest 写入资源引擎（resources / categories / dependencies 表）
+fn index_manifest(engine: &ResourceEngine, manifest: &PluginManifest) -> rusqlite::Result<()> {
+    let to_rfc3339 = |ts: i64| {
+        chrono::DateTime::from_timestamp(ts, 0)
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default()
+    };
+    let resource = ResourceSummary {
+        id: manifest.id.clone(),
+        package_name: None,
+        resource_type: RESOURCE_TYPE.to_string(),
+        name: manifest.name.clone(),
+        description: manifest.description.clone(),
+        icon: manifest.icon.clone(),
+        author: manifest.author.clone(),
+        version: manifest.version.clone(),
+        major_category: manifest.major_category.clone(),
+        sub_category: manifest.sub_category.clone(),
+        tags: manifest.tags.clone(),
+        order: 0,
+        enabled: manifest.enabled,
+        source: if manifest.plugin_type == "builtin" { "builtin" } else { "local" }.to_string(),
+        created_at: to_rfc3339(manifest.created_at),
+        updated_at: to_rfc3339(manifest.updated_at),
+        data_path: get_plugins_dir().join(&manifest.id).to_string_lossy().to_string(),
+    };
+    let extra = serde_json::to_string(manifest).unwrap_or_default();
+    engine.upsert(&resource, None, manifest.min_app_version.as_deref(), &extra)?;
+
+    engine.ensure_category(RESOURCE_TYPE, &manifest.major_category, None)?;
+    if !manifest.sub_category.is_empty() {
+        engine.ensure_category(RESOURCE_TYPE, &manifest.sub_category, Some(&manifest.major_category))?;
+    }
+    engine.set_dependencies(&manifest.id, manifest.dependencies.as_deref().unwrap_or_default())
+}
+
 /// 修改指定插件的 enabled 状态
-pub fn set_plugin_enabled(plugin_id: &str, enabled: bool) -> Result<(), String> {
+/// 启用时依赖未满足则拒绝；返回冲突警告及禁用后受影响的依赖方
+pub fn set_plugin_enabled(state: &ResourceEngineState, plugin_id: &str, enabled: bool) -> Result<Vec<String>, String> {
     let plugins_dir = get_plugins_dir();
     let manifest_path = plugins_dir.join(plugin_id).join("manifest.json");
 
@@ -151,13 +366,30 @@ pub fn set_plugin_enabled(plugin_id: &str, enabled: bool) -> Result<(), String>
     let mut manifest: PluginManifest = serde_json::from_str(&json)
         .map_err(|e| format!("Failed to parse manifest: {}", e))?;
 
+    let installed = list_plugins(state);
+    let mut warnings = Vec::new();
+    if enabled {
+        check_dependencies(&manifest, &installed)?;
+        for other_id in find_enabled_conflicts(&manifest, &installed) {
+            warnings.push(conflict_warning(plugin_id, &other_id));
+        }
+    } else {
+        for dependent in installed.iter().filter(|p| {
+            p.enabled && p.dependencies.as_ref().is_some_and(|d| d.iter().any(|id| id == plugin_id))
+        }) {
+            warnings.push(format!("插件 {} 依赖 {}，禁用后可能无法正常工作", dependent.id, plugin_id));
+        }
+    }
+
     manifest.enabled = enabled;
     manifest.updated_at = chrono::Utc::now().timestamp();
 
-    let updated_json = serde_json::to_string_pretty(&manifest)
-        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
-    fs::write(&manifest_path, updated_json)
-        .map_err(|e| format!("Failed to write manifest: {}", e))?;
+    write_manifest(&manifest_path, &manifest)?;
+    update_index(state, |engine| {
+        index_manifest(engine, &manifest)?;
+        engine.record_install(plugin_id, if enabled { "enable" } else { "disable" }, None, None)?;
+        engine.rebuild_fts()
+    });
 
-    Ok(())
+    Ok(warnings)
 }
@@ -0,0 +1,130 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Emitter};
+
+/// 仅允许后端发布的系统命名空间
+const SYSTEM_NAMESPACES: &[&str] = &["app", "ai", "document", "project", "plugin"];
+
+/// 总线消息，投递给订阅插件的 `plugin-bus:{pluginId}` 事件（ID 中的 '.' 等字符替换为 ':'，见 event_name）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusMessage {
+    pub topic: String,
+    pub source: String,
+    pub payload: Value,
+    pub timestamp: i64,
+}
+
+/// 订阅表：topic 模式 → 订阅的插件 ID。模式支持 "ns:*" 前缀通配
+static SUBSCRIPTIONS: OnceLock<Mutex<HashMap<String, HashSet<String>>>> = OnceLock::new();
+
+fn get_subscriptions() -> &'static Mutex<HashMap<String, HashSet<String>>> {
+    SUBSCRIPTIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 校验 topic 格式："namespace:event"，仅允许字母、数字、'-'、'_'、'.'、':'
+fn validate_topic(topic: &str, allow_wildcard: bool) -> Result<(), String> {
+    let body = match topic.strip_suffix('*') {
+        Some(prefix) if allow_wildcard => prefix,
+        _ => topic,
+    };
+    let valid_chars = body
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+    match body.split_once(':') {
+        Some((ns, _)) if !ns.is_empty() && valid_chars => Ok(()),
+        _ => Err(format!("无效的事件主题: {}（格式应为 命名空间:事件名）", topic)),
+    }
+}
+
+fn is_system_namespace(ns: &str) -> bool {
+    SYSTEM_NAMESPACES.contains(&ns)
+}
+
+fn topic_matches(pattern: &str, topic: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => topic.starts_with(prefix),
+        None => pattern == topic,
+    }
+}
+
+/// 插件接收总线消息的事件名；Tauri 事件名只允许字母、数字、'-'、'/'、':'、'_'，
+/// 反向域名形式的插件 ID（如 com.example.tool）中的 '.' 等字符替换为 ':'
+pub fn event_name(plugin_id: &str) -> String {
+    let id: String = plugin_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '/' | ':' | '_') { c } else { ':' })
+        .collect();
+    format!("plugin-bus:{}", id)
+}
+
+/// 订阅主题，返回插件应监听的事件名
+pub fn subscribe(plugin_id: &str, topic: &str) -> Result<String, String> {
+    validate_topic(topic, true)?;
+    let mut subs = get_subscriptions().lock().map_err(|e| e.to_string())?;
+    subs.entry(topic.to_string()).or_default().insert(plugin_id.to_string());
+    Ok(event_name(plugin_id))
+}
+
+/// 取消订阅；topic 为空时取消该插件的全部订阅
+pub fn unsubscribe(plugin_id: &str, topic: Option<&str>) -> Result<(), String> {
+    let mut subs = get_subscriptions().lock().map_err(|e| e.to_string())?;
+    match topic {
+        Some(t) => {
+            if let Some(set) = subs.get_mut(t) {
+                set.remove(plugin_id);
+            }
+        }
+        None => {
+            for set in subs.values_mut() {
+                set.remove(plugin_id);
+            }
+        }
+    }
+    subs.retain(|_, set| !set.is_empty());
+    Ok(())
+}
+
+/// 插件发布事件：只能发布到自身命名空间（"{pluginId}:..."）
+pub fn emit_from_plugin(app: &AppHandle, plugin_id: &str, topic: &str, payload: Value) -> Result<usize, String> {
+    validate_topic(topic, false)?;
+    let ns = topic.split(':').next().unwrap_or_default();
+    if is_system_namespace(ns) || ns != plugin_id {
+        return Err(format!("插件 {} 只能发布 {}:* 命名空间的事件: {}", plugin_id, plugin_id, topic));
+    }
+    Ok(dispatch(app, plugin_id, topic, payload))
+}
+
+/// 后端发布系统事件（如 ai:generation-completed）
+pub fn publish(app: &AppHandle, topic: &str, payload: Value) -> usize {
+    debug_assert!(topic.split(':').next().is_some_and(is_system_namespace));
+    dispatch(app, "system", topic, payload)
+}
+
+/// 投递给所有匹配的订阅插件，返回投递数量
+fn dispatch(app: &AppHandle, source: &str, topic: &str, payload: Value) -> usize {
+    let targets: HashSet<String> = match get_subscriptions().lock() {
+        Ok(subs) => subs
+            .iter()
+            .filter(|(pattern, _)| topic_matches(pattern, topic))
+            .flat_map(|(_, ids)| ids.iter().cloned())
+            .filter(|id| id != source)
+            .collect(),
+        Err(_) => return 0,
+    };
+
+    let message = BusMessage {
+        topic: topic.to_string(),
+        source: source.to_string(),
+        payload,
+        timestamp: chrono::Utc::now().timestamp_millis(),
+    };
+    for id in &targets {
+        if let Err(e) = app.emit(&event_name(id), &message) {
+            tracing::warn!("投递 {} 到 {} 失败: {}", topic, id, e);
+        }
+    }
+    targets.len()
+}
@@ -0,0 +1,224 @@
+use crate::plugin::{self, PluginManifest};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+use crate::resource_engine::ResourceEngineState;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// 源目录轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(800);
+
+/// 扫描源目录时跳过的子目录
+const IGNORED_DIRS: &[&str] = &["node_modules", ".git", "target"];
+
+/// 开发模式下链接的插件源目录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevPluginLink {
+    pub plugin_id: String,
+    pub source_dir: String,
+    pub linked_at: i64,
+}
+
+/// 监听任务停止标志：使用 plugin_id 作为 key
+static WATCHERS: OnceLock<Mutex<HashMap<String, Arc<AtomicBool>>>> = OnceLock::new();
+
+fn get_watchers() -> &'static Mutex<HashMap<String, Arc<AtomicBool>>> {
+    WATCHERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn links_path() -> PathBuf {
+    plugin::get_plugins_dir().join("dev-links.json")
+}
+
+/// 读取已链接的开发插件列表
+pub fn list_dev_links() -> Vec<DevPluginLink> {
+    let path = links_path();
+    if !path.exists() {
+        return Vec::new();
+    }
+    match fs::read_to_string(&path) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            tracing::warn!("Failed to parse dev-links.json: {}", e);
+            Vec::new()
+        }),
+        Err(e) => {
+            tracing::warn!("Failed to read dev-links.json: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn save_dev_links(links: &[DevPluginLink]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(links)
+        .map_err(|e| format!("Failed to serialize dev links: {}", e))?;
+    fs::write(links_path(), json).map_err(|e| format!("Failed to write dev links: {}", e))
+}
+
+fn read_source_manifest(source_dir: &Path) -> Result<PluginManifest, String> {
+    let manifest_path = source_dir.join("manifest.json");
+    let json = fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("读取 {:?} 失败: {}", manifest_path, e))?;
+    serde_json::from_str(&json).map_err(|e| format!("解析 {:?} 失败: {}", manifest_path, e))
+}
+
+/// 链接插件源目录：同步 manifest 并开始监听变更
+pub fn link_dev_plugin(app: &AppHandle, source_dir: &str) -> Result<DevPluginLink, String> {
+    let dir = PathBuf::from(source_dir);
+    if !dir.is_dir() {
+        return Err(format!("插件源目录不存在: {}", source_dir));
+    }
+
+    let manifest = read_source_manifest(&dir)?;
+    plugin::sync_plugin_manifests(&app.state::<ResourceEngineState>(), vec![manifest.clone()])?;
+
+    let link = DevPluginLink {
+        plugin_id: manifest.id,
+        source_dir: dir.to_string_lossy().to_string(),
+        linked_at: chrono::Utc::now().timestamp(),
+    };
+
+    let mut links = list_dev_links();
+    links.retain(|l| l.plugin_id != link.plugin_id);
+    links.push(link.clone());
+    save_dev_links(&links)?;
+
+    start_watcher(app.clone(), link.clone());
+    Ok(link)
+}
+
+/// 取消链接并停止监听（已同步的 manifest 保留）
+pub fn unlink_dev_plugin(plugin_id: &str) -> Result<(), String> {
+    stop_watcher(plugin_id);
+
+    let mut links = list_dev_links();
+    let len_before = links.len();
+    links.retain(|l| l.plugin_id != plugin_id);
+    if links.len() == len_before {
+        return Err(format!("开发插件未链接: {}", plugin_id));
+    }
+    save_dev_links(&links)
+}
+
+/// 应用启动时恢复所有已链接插件的监听
+pub fn restore_dev_watchers(app: &AppHandle) {
+    for link in list_dev_links() {
+        if Path::new(&link.source_dir).is_dir() {
+            start_watcher(app.clone(), link);
+        } else {
+            tracing::warn!("源目录已不存在，跳过: {}", link.source_dir);
+        }
+    }
+}
+
+fn stop_watcher(plugin_id: &str) {
+    if let Ok(mut watchers) = get_watchers().lock() {
+        if let Some(stop) = watchers.remove(plugin_id) {
+            stop.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+fn start_watcher(app: AppHandle, link: DevPluginLink) {
+    let stop = Arc::new(AtomicBool::new(false));
+    if let Ok(mut watchers) = get_watchers().lock() {
+        // 重复链接时停止旧的监听任务
+        if let Some(old) = watchers.insert(link.plugin_id.clone(), stop.clone()) {
+            old.store(true, Ordering::SeqCst);
+        }
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let source = PathBuf::from(&link.source_dir);
+        let mut last = snapshot_dir(&source);
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            if stop.load(Ordering::SeqCst) {
+                break;
+            }
+            let current = snapshot_dir(&source);
+            if current == last {
+                continue;
+            }
+            last = current;
+            reload_plugin(&app, &link);
+        }
+    });
+}
+
+/// 目录快照：(文件数, 总字节数, 最新修改时间)，任一变化即视为需要重载
+fn snapshot_dir(dir: &Path) -> (usize, u64, Option<SystemTime>) {
+    let mut snapshot = (0, 0, None);
+    snapshot_recursive(dir, &mut snapshot);
+    snapshot
+}
+
+fn snapshot_recursive(dir: &Path, snapshot: &mut (usize, u64, Option<SystemTime>)) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            let name = entry.file_name();
+            if IGNORED_DIRS.iter().any(|d| name == *d) {
+                continue;
+            }
+            snapshot_recursive(&path, snapshot);
+        } else if let Ok(meta) = entry.metadata() {
+            snapshot.0 += 1;
+            snapshot.1 += meta.len();
+            if let Ok(modified) = meta.modified() {
+                if snapshot.2.is_none_or(|latest| modified > latest) {
+                    snapshot.2 = Some(modified);
+                }
+            }
+        }
+    }
+}
+
+/// 收集源目录中的 WASM 模块，前端据此重新实例化
+fn find_wasm_modules(dir: &Path, modules: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            let name = entry.file_name();
+            if !IGNORED_DIRS.iter().any(|d| name == *d) {
+                find_wasm_modules(&path, modules);
+            }
+        } else if path.extension().and_then(|e| e.to_str()) == Some("wasm") {
+            modules.push(path.to_string_lossy().to_string());
+        }
+    }
+}
+
+fn reload_plugin(app: &AppHandle, link: &DevPluginLink) {
+    let source = Path::new(&link.source_dir);
+    let result = read_source_manifest(source)
+        .and_then(|manifest| plugin::sync_plugin_manifests(&app.state::<ResourceEngineState>(), vec![manifest]));
+
+    match result {
+        Ok(warnings) => {
+            let mut wasm_modules = Vec::new();
+            find_wasm_modules(source, &mut wasm_modules);
+            let _ = app.emit("plugin:reloaded", json!({
+                "pluginId": link.plugin_id,
+                "sourceDir": link.source_dir,
+                "wasmModules": wasm_modules,
+                "warnings": warnings,
+                "reloadedAt": chrono::Utc::now().timestamp(),
+            }));
+        }
+        Err(e) => {
+            tracing::warn!("重载 {} 失败: {}", link.plugin_id, e);
+            let _ = app.emit("plugin:reload-error", json!({
+                "pluginId": link.plugin_id,
+                "error": e,
+            }));
+        }
+    }
+}
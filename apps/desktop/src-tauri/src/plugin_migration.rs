@@ -0,0 +1,270 @@
+use crate::config::AppConfig;
+use crate::document::Document;
+use crate::plugin::{self, PluginManifest};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+/// 插件数据迁移：将 pluginData[pluginId] 从 fromMajor 版本格式转换为 toMajor 版本格式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginMigration {
+    pub from_major: u64,
+    pub to_major: u64,
+    #[serde(default)]
+    pub operations: Vec<MigrationOp>,
+}
+
+/// 声明式迁移操作，path 为插件数据对象内以 "." 分隔的键路径
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum MigrationOp {
+    /// 移动/重命名字段
+    Rename { from: String, to: String },
+    /// 无条件设置字段
+    Set { path: String, value: Value },
+    /// 字段不存在时设置默认值
+    SetDefault { path: String, value: Value },
+    /// 删除字段
+    Remove { path: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationReport {
+    pub plugin_id: String,
+    pub to_major: u64,
+    pub migrated_documents: usize,
+    pub failed: Vec<String>,
+}
+
+/// 插件数据中记录格式版本的字段（保存文档时由后端按已安装插件的主版本写入）
+const VERSION_KEY: &str = "_version";
+
+/// 解析语义化版本号的主版本
+pub fn major_version(version: &str) -> u64 {
+    version
+        .trim_start_matches('v')
+        .split('.')
+        .next()
+        .and_then(|m| m.parse().ok())
+        .unwrap_or(0)
+}
+
+fn runtime_migrations_path(plugin_id: &str) -> PathBuf {
+    plugin::get_plugins_dir().join(plugin_id).join("migrations.json")
+}
+
+fn load_runtime_migrations(plugin_id: &str) -> Vec<PluginMigration> {
+    let path = runtime_migrations_path(plugin_id);
+    fs::read_to_string(&path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// 迁移必须提升主版本，否则迁移循环无法结束
+fn validate_migration(migration: &PluginMigration) -> Result<(), String> {
+    if migration.to_major <= migration.from_major {
+        return Err(format!(
+            "迁移版本无效: {} -> {}",
+            migration.from_major, migration.to_major
+        ));
+    }
+    Ok(())
+}
+
+/// 注册运行时迁移（持久化到插件目录的 migrations.json，同一 fromMajor 覆盖）
+pub fn register_migration(plugin_id: &str, migration: PluginMigration) -> Result<(), String> {
+    if !crate::config::is_safe_id(plugin_id) {
+        return Err(format!("无效的插件 ID: {}", plugin_id));
+    }
+    validate_migration(&migration)?;
+    let plugin_dir = plugin::get_plugins_dir().join(plugin_id);
+    if !plugin_dir.join("manifest.json").exists() {
+        return Err(format!("Plugin not found: {}", plugin_id));
+    }
+
+    let mut migrations = load_runtime_migrations(plugin_id);
+    migrations.retain(|m| m.from_major != migration.from_major);
+    migrations.push(migration);
+    migrations.sort_by_key(|m| m.from_major);
+
+    let json = serde_json::to_string_pretty(&migrations)
+        .map_err(|e| format!("Failed to serialize migrations: {}", e))?;
+    fs::write(runtime_migrations_path(plugin_id), json)
+        .map_err(|e| format!("Failed to write migrations: {}", e))
+}
+
+/// 为未记录版本的插件数据写入已安装插件的主版本；插件未安装时保持原样
+pub fn stamp_versions(plugin_data: &mut Value) {
+    let Some(map) = plugin_data.as_object_mut() else { return };
+    for (plugin_id, data) in map.iter_mut() {
+        let Some(obj) = data.as_object_mut() else { continue };
+        if obj.contains_key(VERSION_KEY) || !crate::config::is_safe_id(plugin_id) {
+            continue;
+        }
+        let manifest_path = plugin::get_plugins_dir().join(plugin_id).join("manifest.json");
+        let manifest = fs::read_to_string(&manifest_path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<PluginManifest>(&json).ok());
+        if let Some(manifest) = manifest {
+            obj.insert(VERSION_KEY.to_string(), Value::from(major_version(&manifest.version)));
+        }
+    }
+}
+
+/// 合并 manifest 声明的迁移与运行时注册的迁移（运行时优先）；含无效迁移时拒绝整个插件的迁移
+fn collect_migrations(manifest: &PluginManifest) -> Result<Vec<PluginMigration>, String> {
+    let mut migrations = load_runtime_migrations(&manifest.id);
+    for m in manifest.migrations.iter().flatten() {
+        if !migrations.iter().any(|r| r.from_major == m.from_major) {
+            migrations.push(m.clone());
+        }
+    }
+    for m in &migrations {
+        validate_migration(m).map_err(|e| format!("插件 {} 的{}", manifest.id, e))?;
+    }
+    Ok(migrations)
+}
+
+/// 对所有项目中含该插件数据的文档执行迁移，直至数据版本达到插件当前主版本
+/// 每个被修改的文档会先创建一个备份版本
+pub fn run_migrations(manifest: &PluginManifest) -> Result<MigrationReport, String> {
+    let target = major_version(&manifest.version);
+    let migrations = collect_migrations(manifest)?;
+    let mut report = MigrationReport {
+        plugin_id: manifest.id.clone(),
+        to_major: target,
+        migrated_documents: 0,
+        failed: Vec::new(),
+    };
+    if migrations.is_empty() {
+        return Ok(report);
+    }
+
+    let projects_dir = AppConfig::default().projects_dir;
+    let Ok(projects) = fs::read_dir(&projects_dir) else { return Ok(report) };
+
+    for project in projects.flatten() {
+        let docs_dir = project.path().join("documents");
+        let Ok(entries) = fs::read_dir(&docs_dir) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            match migrate_document(&path, &manifest.id, target, &migrations) {
+                Ok(true) => report.migrated_documents += 1,
+                Ok(false) => {}
+                Err(e) => report.failed.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+fn migrate_document(
+    path: &PathBuf,
+    plugin_id: &str,
+    target: u64,
+    migrations: &[PluginMigration],
+) -> Result<bool, String> {
+    let mut document = Document::load(path).map_err(|e| e.to_string())?;
+    let Some(data) = document.plugin_data.as_ref().and_then(|pd| pd.get(plugin_id)) else {
+        return Ok(false);
+    };
+
+    let mut data = data.clone();
+    // 没有版本号的数据无法判断格式，不做迁移
+    let Some(mut current) = data.get(VERSION_KEY).and_then(|v| v.as_u64()) else {
+        return Ok(false);
+    };
+    let start = current;
+
+    while current < target {
+        let Some(step) = migrations.iter().find(|m| m.from_major == current) else { break };
+        // 版本未提升时停止，避免死循环
+        if step.to_major <= current {
+            break;
+        }
+        for op in &step.operations {
+            apply_op(&mut data, op);
+        }
+        current = step.to_major;
+    }
+
+    if current == start {
+        return Ok(false);
+    }
+    if let Some(obj) = data.as_object_mut() {
+        obj.insert(VERSION_KEY.to_string(), Value::from(current));
+    }
+
+    // 迁移前备份当前状态
+    document.create_version(
+        document.content.clone(),
+        document.author_notes.clone(),
+        document.ai_generated_content.clone(),
+        "system".to_string(),
+        Some(format!("插件 {} 数据迁移前备份 (v{} → v{})", plugin_id, start, current)),
+        document.plugin_data.clone(),
+        document.enabled_plugins.clone(),
+        document.composed_content.clone(),
+    );
+
+    if let Some(Value::Object(map)) = document.plugin_data.as_mut() {
+        map.insert(plugin_id.to_string(), data);
+    }
+    document.save(path).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+fn apply_op(data: &mut Value, op: &MigrationOp) {
+    match op {
+        MigrationOp::Rename { from, to } => {
+            if let Some(value) = take_path(data, from) {
+                set_path(data, to, value);
+            }
+        }
+        MigrationOp::Set { path, value } => set_path(data, path, value.clone()),
+        MigrationOp::SetDefault { path, value } => {
+            if get_path(data, path).is_none() {
+                set_path(data, path, value.clone());
+            }
+        }
+        MigrationOp::Remove { path } => {
+            take_path(data, path);
+        }
+    }
+}
+
+fn get_path<'a>(data: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(data, |v, key| v.get(key))
+}
+
+fn take_path(data: &mut Value, path: &str) -> Option<Value> {
+    let (parent, last) = match path.rsplit_once('.') {
+        Some((parent, last)) => (parent.split('.').try_fold(data, |v, key| v.get_mut(key))?, last),
+        None => (data, path),
+    };
+    parent.as_object_mut()?.remove(last)
+}
+
+fn set_path(data: &mut Value, path: &str, value: Value) {
+    let mut current = data;
+    let mut keys = path.split('.').peekable();
+    while let Some(key) = keys.next() {
+        if !current.is_object() {
+            *current = Value::Object(Default::default());
+        }
+        let obj = current.as_object_mut().expect("just ensured object");
+        if keys.peek().is_none() {
+            obj.insert(key.to_string(), value);
+            return;
+        }
+        current = obj.entry(key.to_string()).or_insert_with(|| Value::Object(Default::default()));
+    }
+}
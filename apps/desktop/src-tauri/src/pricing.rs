@@ -0,0 +1,147 @@
+// AI 调用费用估算：按模型名前缀匹配内置价格表（美元 / 百万 token），设置中可覆盖
+// 价格为公开标价的近似值，仅用于估算；本地模型（Ollama）按 0 计
+// 项目累计花费保存在 Projects/{项目ID}/ai-usage.json
+
+use crate::ai::Usage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const USAGE_FILE: &str = "ai-usage.json";
+
+/// 每百万 token 的价格（美元）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+}
+
+/// 内置价格表：(模型名前缀, 输入, 输出)，按最长前缀匹配
+const PRICES: &[(&str, f64, f64)] = &[
+    // OpenAI
+    ("gpt-5-nano", 0.05, 0.4),
+    ("gpt-5-mini", 0.25, 2.0),
+    ("gpt-5", 1.25, 10.0),
+    ("gpt-4.1-nano", 0.1, 0.4),
+    ("gpt-4.1-mini", 0.4, 1.6),
+    ("gpt-4.1", 2.0, 8.0),
+    ("gpt-4o-mini", 0.15, 0.6),
+    ("gpt-4o", 2.5, 10.0),
+    ("o4-mini", 1.1, 4.4),
+    ("o3-mini", 1.1, 4.4),
+    ("o3", 2.0, 8.0),
+    // Anthropic
+    ("claude-opus-4-6", 5.0, 25.0),
+    ("claude-opus-4-5", 5.0, 25.0),
+    ("claude-opus-4", 15.0, 75.0),
+    ("claude-sonnet-4", 3.0, 15.0),
+    ("claude-3-7-sonnet", 3.0, 15.0),
+    ("claude-haiku-4", 1.0, 5.0),
+    ("claude-3-5-haiku", 0.8, 4.0),
+    // Google
+    ("gemini-2.5-pro", 1.25, 10.0),
+    ("gemini-2.5-flash-lite", 0.1, 0.4),
+    ("gemini-2.5-flash", 0.3, 2.5),
+    ("gemini-3-pro", 2.0, 12.0),
+    ("gemini-3-flash", 0.5, 3.0),
+    // xAI
+    ("grok-4", 3.0, 15.0),
+    ("grok-3-mini", 0.3, 0.5),
+    ("grok-3", 3.0, 15.0),
+    // 国内服务商（按官方人民币价格折算）
+    ("deepseek-reasoner", 0.55, 2.19),
+    ("deepseek-chat", 0.27, 1.1),
+    ("qwen3-max", 1.2, 6.0),
+    ("qwen-plus", 0.4, 1.2),
+    ("qwen-turbo", 0.05, 0.2),
+    ("glm-4.5-air", 0.2, 1.1),
+    ("glm-4.5", 0.6, 2.2),
+    ("glm-4.6", 0.6, 2.2),
+    ("glm-5", 1.0, 3.2),
+    ("kimi-k2", 0.6, 2.5),
+    ("minimax-m2", 0.3, 1.2),
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostEstimate {
+    pub input_cost: f64,
+    pub output_cost: f64,
+    pub total_cost: f64,
+    pub currency: &'static str,
+    /// 价格表中没有该模型时为 false，费用按 0 计
+    pub priced: bool,
+}
+
+/// 查找模型价格：设置中的覆盖优先（精确或前缀匹配），其次内置价格表
+pub fn price_for(provider: &str, model: &str, overrides: &HashMap<String, ModelPrice>) -> Option<ModelPrice> {
+    if provider == "ollama" {
+        return Some(ModelPrice { input: 0.0, output: 0.0 });
+    }
+    let model = model.to_lowercase();
+    // 去掉 "openai/gpt-4.1" 这类路由前缀
+    let model = model.rsplit('/').next().unwrap_or(&model);
+    let matches = |prefix: &str| model.starts_with(&prefix.to_lowercase());
+    overrides
+        .iter()
+        .filter(|(prefix, _)| matches(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, price)| *price)
+        .or_else(|| {
+            PRICES
+                .iter()
+                .filter(|(prefix, _, _)| matches(prefix))
+                .max_by_key(|(prefix, _, _)| prefix.len())
+                .map(|(_, input, output)| ModelPrice { input: *input, output: *output })
+        })
+}
+
+pub fn estimate(price: Option<ModelPrice>, prompt_tokens: u32, completion_tokens: u32) -> CostEstimate {
+    let p = price.unwrap_or(ModelPrice { input: 0.0, output: 0.0 });
+    let input_cost = prompt_tokens as f64 * p.input / 1_000_000.0;
+    let output_cost = completion_tokens as f64 * p.output / 1_000_000.0;
+    CostEstimate { input_cost, output_cost, total_cost: input_cost + output_cost, currency: "USD", priced: price.is_some() }
+}
+
+/// 项目累计用量
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProjectSpend {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_cost: f64,
+    /// 模型 → 累计费用
+    pub by_model: HashMap<String, f64>,
+    pub updated_at: i64,
+}
+
+fn usage_path(project_dir: &Path) -> PathBuf {
+    project_dir.join(USAGE_FILE)
+}
+
+pub fn load_spend(project_dir: &Path) -> ProjectSpend {
+    fs::read_to_string(usage_path(project_dir))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// 累加一次调用的用量和费用
+pub fn record_spend(project_dir: &Path, model: &str, usage: &Usage, cost: &CostEstimate) -> Result<ProjectSpend, String> {
+    if !project_dir.is_dir() {
+        return Err(format!("项目目录不存在: {}", project_dir.display()));
+    }
+    let mut spend = load_spend(project_dir);
+    spend.requests += 1;
+    spend.prompt_tokens += usage.prompt_tokens as u64;
+    spend.completion_tokens += usage.completion_tokens as u64;
+    spend.total_cost += cost.total_cost;
+    *spend.by_model.entry(model.to_string()).or_default() += cost.total_cost;
+    spend.updated_at = chrono::Utc::now().timestamp();
+    let json = serde_json::to_string_pretty(&spend).map_err(|e| e.to_string())?;
+    fs::write(usage_path(project_dir), json).map_err(|e| format!("保存用量失败: {}", e))?;
+    Ok(spend)
+}
@@ -0,0 +1,132 @@
+// 原生打印
+// - 未指定打印机：在打印预览窗口中加载导出 HTML，加载完成后弹出系统打印对话框
+// - 指定打印机：交给系统打印队列静默打印（Windows PrintTo，macOS / Linux 用 lp 打印原生导出的 PDF）
+
+use crate::native_export::{self, html, pdf, ExportOptions};
+use reqwest::Url;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use tauri::webview::PageLoadEvent;
+use tauri::{AppHandle, WebviewUrl, WebviewWindowBuilder};
+
+/// 打印预览窗口标签前缀
+pub const WINDOW_LABEL_PREFIX: &str = "print-";
+
+/// 生成打印用的临时文件（公文排版 HTML）
+fn write_print_html(markdown: &str, title: &str) -> Result<PathBuf, String> {
+    let options = ExportOptions { code_theme: Some(html::DEFAULT_CODE_THEME.to_string()), ..Default::default() };
+    let content = html::export_to_html(markdown, title, &options)?;
+    let dir = std::env::temp_dir().join("aidocplus-print");
+    fs::create_dir_all(&dir).map_err(|e| format!("创建临时目录失败: {}", e))?;
+    let path = dir.join(format!("{}.html", uuid::Uuid::new_v4()));
+    fs::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))?;
+    Ok(path)
+}
+
+/// 打开打印预览窗口并弹出系统打印对话框
+pub fn print_with_dialog(app: &AppHandle, markdown: &str, title: &str) -> Result<(), String> {
+    let path = write_print_html(markdown, title)?;
+    let url = Url::from_file_path(&path).map_err(|_| format!("无效的文件路径: {}", path.display()))?;
+    let label = format!("{}{}", WINDOW_LABEL_PREFIX, uuid::Uuid::new_v4().simple());
+    WebviewWindowBuilder::new(app, label, WebviewUrl::External(url))
+        .title(format!("打印 - {}", title))
+        .inner_size(820.0, 900.0)
+        .center()
+        .on_page_load(|window, payload| {
+            if payload.event() == PageLoadEvent::Finished {
+                if let Err(e) = window.print() {
+                    tracing::warn!("打开打印对话框失败: {}", e);
+                }
+            }
+        })
+        .build()
+        .map_err(|e| format!("打开打印窗口失败: {}", e))?;
+    Ok(())
+}
+
+/// 直接发送到指定打印机，不弹出对话框；options 为 PDF 导出选项（macOS / Linux 先导出 PDF 再打印）
+pub fn print_silent(markdown: &str, title: &str, printer: &str, options: &ExportOptions) -> Result<(), String> {
+    let printer = printer.trim();
+    if printer.is_empty() {
+        return Err("打印机名称不能为空".to_string());
+    }
+    if !list_printers().iter().any(|p| p == printer) {
+        return Err(format!("找不到打印机: {}", printer));
+    }
+    send_to_printer(markdown, title, printer, options)
+}
+
+#[cfg(target_os = "windows")]
+fn send_to_printer(markdown: &str, title: &str, printer: &str, _options: &ExportOptions) -> Result<(), String> {
+    let path = write_print_html(markdown, title)?;
+    let script = format!(
+        "Start-Process -FilePath '{}' -Verb PrintTo -ArgumentList '\"{}\"' -WindowStyle Hidden",
+        path.to_string_lossy().replace('\'', "''"),
+        printer.replace('\'', "''")
+    );
+    run_print_command(Command::new("powershell").args(["-NoProfile", "-NonInteractive", "-Command", &script]))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn send_to_printer(markdown: &str, title: &str, printer: &str, options: &ExportOptions) -> Result<(), String> {
+    // CUPS 默认没有 HTML 过滤器，用原生 PDF 导出保留标题、表格和图片
+    if options.pdf_browser.clone().or_else(pdf::find_browser).is_none() {
+        return Err("未找到 Chromium 内核浏览器，无法生成打印用的 PDF，请不指定打印机改用打印对话框".to_string());
+    }
+    let dir = std::env::temp_dir().join("aidocplus-print");
+    fs::create_dir_all(&dir).map_err(|e| format!("创建临时目录失败: {}", e))?;
+    let path = dir.join(format!("{}.pdf", uuid::Uuid::new_v4()));
+    let result = native_export::export_native(markdown, title, &path.to_string_lossy(), "pdf", options)
+        .and_then(|_| run_print_command(Command::new("lp").args(["-d", printer, "-t", title]).arg(&path)));
+    let _ = fs::remove_file(&path);
+    result
+}
+
+fn run_print_command(cmd: &mut Command) -> Result<(), String> {
+    let output = cmd.output().map_err(|e| format!("调用系统打印失败: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("打印失败: {}", String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+/// 系统已安装的打印机名称
+#[cfg(target_os = "windows")]
+pub fn list_printers() -> Vec<String> {
+    command_lines(Command::new("powershell").args([
+        "-NoProfile",
+        "-NonInteractive",
+        "-Command",
+        "Get-Printer | Select-Object -ExpandProperty Name",
+    ]))
+}
+
+/// 系统已安装的打印机名称
+#[cfg(not(target_os = "windows"))]
+pub fn list_printers() -> Vec<String> {
+    command_lines(Command::new("lpstat").arg("-e"))
+}
+
+fn command_lines(cmd: &mut Command) -> Vec<String> {
+    cmd.output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .lines()
+                .map(|l| l.trim().to_string())
+                .filter(|l| !l.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 清理上次运行遗留的打印临时文件
+pub fn cleanup_temp_files() {
+    let Ok(entries) = fs::read_dir(std::env::temp_dir().join("aidocplus-print")) else { return };
+    for entry in entries.flatten() {
+        let _ = fs::remove_file(entry.path());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::workspace::ProjectSession;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -12,6 +13,9 @@ pub struct Project {
     pub updated_at: i64,
     pub settings: ProjectSettings,
     pub path: PathBuf,
+    /// 上次打开时的会话，仅由 open_project 填充，不写入项目文件
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub session: Option<ProjectSession>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +29,21 @@ pub struct ProjectSettings {
     #[serde(rename = "versionHistoryLimit")]
     pub version_history_limit: usize,
     pub theme: String,
+    /// 项目级 AI 配置，优先于全局设置
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ai: Option<ProjectAiSettings>,
+}
+
+/// 项目级 AI 配置；未设置的字段使用全局设置（前端当前服务 → 后端 AI 默认值 → 环境变量）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProjectAiSettings {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub base_url: Option<String>,
+    pub temperature: Option<f64>,
+    /// 附加在该项目所有 AI 对话最前面的系统提示词
+    pub system_prompt: Option<String>,
 }
 
 impl Default for ProjectSettings {
@@ -35,6 +54,7 @@ impl Default for ProjectSettings {
             autosave_interval: 30,
             version_history_limit: 50,
             theme: "dark".to_string(),
+            ai: None,
         }
     }
 }
@@ -0,0 +1,201 @@
+// AI 服务健康度统计
+// 每次调用记录成功 / 失败与耗时，按服务商 + 接口地址分组滚动保留最近的样本，
+// 供设置页展示哪个端点变慢或频繁出错
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// 每个端点最多保留的样本数
+const MAX_SAMPLES: usize = 100;
+/// 超过该时长的样本不再计入统计（7 天）
+const MAX_AGE_SECS: i64 = 7 * 24 * 3600;
+/// 错误信息最多保留字符数
+const MAX_ERROR_CHARS: usize = 300;
+/// p95 超过该值视为响应缓慢
+const SLOW_LATENCY_MS: u64 = 30_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CallKind {
+    Chat,
+    Stream,
+    Test,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Sample {
+    at: i64,
+    kind: CallKind,
+    model: String,
+    success: bool,
+    latency_ms: u64,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Endpoint {
+    provider: String,
+    base_url: String,
+    samples: Vec<Sample>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthStatus {
+    Healthy,
+    /// 成功率偏低或响应缓慢
+    Degraded,
+    /// 最近几次调用全部失败
+    Failing,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderHealth {
+    pub provider: String,
+    pub base_url: String,
+    pub status: HealthStatus,
+    pub samples: usize,
+    pub failures: usize,
+    pub success_rate: f64,
+    /// 以下耗时只统计成功的调用；流式调用为完整生成耗时
+    pub avg_latency_ms: Option<u64>,
+    pub p50_latency_ms: Option<u64>,
+    pub p95_latency_ms: Option<u64>,
+    pub last_model: String,
+    pub last_used_at: i64,
+    pub last_success_at: Option<i64>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<i64>,
+}
+
+/// 内存中的统计，首次使用时从文件加载
+static STORE: Mutex<Option<Vec<Endpoint>>> = Mutex::new(None);
+
+fn load(path: &Path) -> Vec<Endpoint> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// 记录一次调用；写文件失败只打日志，不影响 AI 调用本身
+pub fn record(
+    path: &Path,
+    provider: &str,
+    base_url: &str,
+    model: &str,
+    kind: CallKind,
+    started: Instant,
+    error: Option<&str>,
+) {
+    let Ok(mut store) = STORE.lock() else { return };
+    let endpoints = store.get_or_insert_with(|| load(path));
+    let now = chrono::Utc::now().timestamp();
+    let base_url = base_url.trim_end_matches('/');
+
+    let index = match endpoints.iter().position(|e| e.provider == provider && e.base_url == base_url) {
+        Some(i) => i,
+        None => {
+            endpoints.push(Endpoint { provider: provider.to_string(), base_url: base_url.to_string(), samples: Vec::new() });
+            endpoints.len() - 1
+        }
+    };
+    let samples = &mut endpoints[index].samples;
+    samples.retain(|s| now - s.at <= MAX_AGE_SECS);
+    samples.push(Sample {
+        at: now,
+        kind,
+        model: model.to_string(),
+        success: error.is_none(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        error: error.map(|e| e.chars().take(MAX_ERROR_CHARS).collect()),
+    });
+    if samples.len() > MAX_SAMPLES {
+        let excess = samples.len() - MAX_SAMPLES;
+        samples.drain(..excess);
+    }
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let result = serde_json::to_string(&*endpoints)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(path, json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        tracing::warn!("保存 AI 服务健康度失败: {}", e);
+    }
+}
+
+fn percentile(sorted: &[u64], p: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((sorted.len() as f64) * p).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+fn summarize(endpoint: &Endpoint, now: i64) -> Option<ProviderHealth> {
+    let samples: Vec<&Sample> = endpoint.samples.iter().filter(|s| now - s.at <= MAX_AGE_SECS).collect();
+    let last = samples.last()?;
+    let failures = samples.iter().filter(|s| !s.success).count();
+    let success_rate = (samples.len() - failures) as f64 / samples.len() as f64;
+
+    let mut latencies: Vec<u64> = samples.iter().filter(|s| s.success).map(|s| s.latency_ms).collect();
+    latencies.sort_unstable();
+    let avg = (!latencies.is_empty()).then(|| latencies.iter().sum::<u64>() / latencies.len() as u64);
+    let p95 = percentile(&latencies, 0.95);
+
+    let last_failure = samples.iter().rev().find(|s| !s.success);
+    let recent_all_failed = samples.len() >= 3 && samples.iter().rev().take(3).all(|s| !s.success);
+    let status = if recent_all_failed || failures == samples.len() {
+        HealthStatus::Failing
+    } else if success_rate < 0.8 || p95.is_some_and(|p| p > SLOW_LATENCY_MS) {
+        HealthStatus::Degraded
+    } else {
+        HealthStatus::Healthy
+    };
+
+    Some(ProviderHealth {
+        provider: endpoint.provider.clone(),
+        base_url: endpoint.base_url.clone(),
+        status,
+        samples: samples.len(),
+        failures,
+        success_rate,
+        avg_latency_ms: avg,
+        p50_latency_ms: percentile(&latencies, 0.5),
+        p95_latency_ms: p95,
+        last_model: last.model.clone(),
+        last_used_at: last.at,
+        last_success_at: samples.iter().rev().find(|s| s.success).map(|s| s.at),
+        last_error: last_failure.and_then(|s| s.error.clone()),
+        last_error_at: last_failure.map(|s| s.at),
+    })
+}
+
+/// 各端点的健康度汇总（最近使用的在前）
+pub fn summary(path: &Path) -> Vec<ProviderHealth> {
+    let Ok(mut store) = STORE.lock() else { return Vec::new() };
+    let endpoints = store.get_or_insert_with(|| load(path));
+    let now = chrono::Utc::now().timestamp();
+    let mut result: Vec<ProviderHealth> = endpoints.iter().filter_map(|e| summarize(e, now)).collect();
+    result.sort_by_key(|h| std::cmp::Reverse(h.last_used_at));
+    result
+}
+
+/// 清空统计
+pub fn clear(path: &Path) -> Result<(), String> {
+    let mut store = STORE.lock().map_err(|e| e.to_string())?;
+    *store = Some(Vec::new());
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| format!("清除 AI 服务健康度失败: {}", e))?;
+    }
+    Ok(())
+}
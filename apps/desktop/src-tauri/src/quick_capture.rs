@@ -0,0 +1,99 @@
+use crate::document::Document;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// 快速记录窗口标签
+pub const WINDOW_LABEL: &str = "quick-capture";
+
+/// 快速记录追加到的文档标题
+pub const INBOX_TITLE: &str = "Inbox";
+
+/// 当前注册的全局快捷键，快捷键变更时先注销
+static SHORTCUT: Mutex<Option<Shortcut>> = Mutex::new(None);
+
+/// 按快捷键设置注册快速记录的全局快捷键（与菜单项 quick_capture 相同，默认 CmdOrCtrl+Shift+Space）；
+/// 用户清空该快捷键时只注销
+pub fn register_shortcut(handle: &AppHandle, keybindings: &HashMap<String, String>) -> Result<(), String> {
+    let mut current = SHORTCUT.lock().map_err(|e| format!("锁获取失败: {}", e))?;
+    if let Some(old) = current.take() {
+        let _ = handle.global_shortcut().unregister(old);
+    }
+    let Some(accel) = crate::menu::effective_accelerators(keybindings).remove("quick_capture") else {
+        return Ok(());
+    };
+    let shortcut: Shortcut = accel.parse().map_err(|e| format!("快速记录快捷键无效（{}）: {}", accel, e))?;
+    handle
+        .global_shortcut()
+        .on_shortcut(shortcut, |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                if let Err(e) = open_window(app) {
+                    tracing::warn!("{}", e);
+                }
+            }
+        })
+        .map_err(|e| format!("注册快速记录全局快捷键 {} 失败（可能已被其他应用占用）: {}", accel, e))?;
+    *current = Some(shortcut);
+    Ok(())
+}
+
+/// 打开（或聚焦已打开的）快速记录窗口
+pub fn open_window(handle: &AppHandle) -> Result<(), String> {
+    if let Some(window) = handle.get_webview_window(WINDOW_LABEL) {
+        let _ = window.show();
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+    WebviewWindowBuilder::new(handle, WINDOW_LABEL, WebviewUrl::App("index.html#/quick-capture".into()))
+        .title("Quick Capture")
+        .inner_size(520.0, 240.0)
+        .resizable(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .center()
+        .build()
+        .map_err(|e| format!("打开快速记录窗口失败: {}", e))?;
+    Ok(())
+}
+
+/// 把文本追加到项目的 Inbox 文档末尾（不存在时创建），每条记录带时间标题
+pub fn append_to_inbox(projects_dir: &Path, project_id: &str, text: &str) -> Result<Document, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("记录内容不能为空".to_string());
+    }
+    let docs_dir = projects_dir.join(project_id).join("documents");
+    if !projects_dir.join(format!("{}.json", project_id)).exists() {
+        return Err(crate::i18n::tf("error.project_not_found", &[&project_id]));
+    }
+
+    let mut document = find_inbox(&docs_dir).unwrap_or_else(|| {
+        Document::new(project_id.to_string(), INBOX_TITLE.to_string(), String::new())
+    });
+    document.ensure_unlocked().map_err(|e| e.localized())?;
+    let entry = format!("### {}\n\n{}\n", chrono::Local::now().format("%Y-%m-%d %H:%M"), text);
+    if document.content.trim().is_empty() {
+        document.content = entry;
+    } else {
+        document.content = format!("{}\n\n{}", document.content.trim_end(), entry);
+    }
+    document.metadata.updated_at = chrono::Utc::now().timestamp();
+    document.metadata.word_count = document.content.split_whitespace().count();
+    document.metadata.character_count = document.content.chars().count();
+
+    let path = docs_dir.join(format!("{}.json", document.id));
+    document.save(&path).map_err(|e| e.to_string())?;
+    Ok(document)
+}
+
+fn find_inbox(docs_dir: &Path) -> Option<Document> {
+    fs::read_dir(docs_dir)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("json"))
+        .filter_map(|p| Document::load(&p).ok())
+        .find(|d| d.title == INBOX_TITLE)
+}
@@ -0,0 +1,269 @@
+// 可读性分析：阅读时长、句长分布、被动句和冗余词检测（中英文）
+// 分析前去掉 Markdown 标记，代码块和 front matter 不计入
+
+use crate::activity::{count_words, is_cjk};
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use std::sync::OnceLock;
+
+/// 阅读速度：中日韩文字每分钟字数、其他语言每分钟词数
+const CJK_CHARS_PER_MINUTE: f32 = 300.0;
+const WORDS_PER_MINUTE: f32 = 200.0;
+/// 超过该长度（中文字 / 英文词）视为长句
+const LONG_SENTENCE: usize = 35;
+/// 每类问题最多列出的条数
+const MAX_ISSUES: usize = 50;
+/// 句长分布的分段上限（不含），最后一段无上限
+const BUCKET_BOUNDS: &[usize] = &[10, 20, LONG_SENTENCE];
+
+/// 中文冗余词
+const ZH_FILLERS: &[&str] = &[
+    "其实", "基本上", "一般来说", "总的来说", "可以说", "某种程度上", "事实上", "显然", "非常", "真的", "就是说", "的话",
+    "进行了", "进行一个",
+];
+/// 英文冗余词
+const EN_FILLERS: &[&str] = &[
+    "very", "really", "basically", "actually", "just", "quite", "literally", "simply", "somewhat", "totally",
+    "in order to", "kind of", "sort of", "a lot of",
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LengthBucket {
+    pub min: usize,
+    /// 不含；最后一段为空
+    pub max: Option<usize>,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextIssue {
+    /// 行号（从 1 开始）
+    pub line: usize,
+    /// 命中的词，长句为空
+    pub text: String,
+    /// 所在句子（过长时截断）
+    pub excerpt: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadabilityReport {
+    pub characters: usize,
+    pub cjk_characters: usize,
+    pub words: usize,
+    pub sentences: usize,
+    pub paragraphs: usize,
+    /// 预计阅读分钟数
+    pub reading_minutes: f32,
+    /// 平均句长（中文按字、英文按词计）
+    pub avg_sentence_length: f32,
+    pub sentence_lengths: Vec<LengthBucket>,
+    pub long_sentences: Vec<TextIssue>,
+    pub passive_count: usize,
+    pub passive: Vec<TextIssue>,
+    pub filler_count: usize,
+    pub fillers: Vec<TextIssue>,
+}
+
+struct Sentence {
+    line: usize,
+    text: String,
+}
+
+fn re(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| RegexBuilder::new(pattern).case_insensitive(true).build().expect("invalid readability regex"))
+}
+
+fn zh_passive_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    // 排除“被子”“被褥”等名词
+    re(&RE, r"被[^子褥单套窝，。！？]|受到|遭到|为[^，。！？]{1,8}所")
+}
+
+fn en_passive_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    re(
+        &RE,
+        r"\b(?:am|is|are|was|were|be|been|being)\s+(?:\w+ly\s+)?(?:\w+ed|\w+en|built|made|done|known|given|taken|seen|found|held|kept|left|told|shown|sent|brought|bought|thought|caught|taught|set|put|read|paid|sold|understood)\b",
+    )
+}
+
+fn en_filler_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    re(&RE, &format!(r"\b(?:{})\b", EN_FILLERS.iter().map(|f| f.replace(' ', r"\s+")).collect::<Vec<_>>().join("|")))
+}
+
+/// 分析文本可读性
+pub fn analyze(content: &str) -> ReadabilityReport {
+    let (plain, paragraphs) = strip_markdown(content);
+    let sentences = split_sentences(&plain);
+
+    let cjk_characters = plain.iter().flat_map(|(_, l)| l.chars()).filter(|c| is_cjk(*c)).count();
+    let words: usize = plain.iter().map(|(_, l)| count_words(l)).sum();
+    let other_words = words.saturating_sub(cjk_characters);
+    let reading_minutes = cjk_characters as f32 / CJK_CHARS_PER_MINUTE + other_words as f32 / WORDS_PER_MINUTE;
+
+    let mut sentence_lengths: Vec<LengthBucket> = BUCKET_BOUNDS
+        .iter()
+        .scan(0, |min, &max| {
+            let bucket = LengthBucket { min: *min, max: Some(max), count: 0 };
+            *min = max;
+            Some(bucket)
+        })
+        .collect();
+    sentence_lengths.push(LengthBucket { min: LONG_SENTENCE, max: None, count: 0 });
+
+    let mut long_sentences = Vec::new();
+    let (mut passive, mut passive_count) = (Vec::new(), 0);
+    let (mut fillers, mut filler_count) = (Vec::new(), 0);
+    let mut total_length = 0;
+
+    for sentence in &sentences {
+        let length = count_words(&sentence.text);
+        total_length += length;
+        let bucket = BUCKET_BOUNDS.iter().position(|&b| length < b).unwrap_or(BUCKET_BOUNDS.len());
+        sentence_lengths[bucket].count += 1;
+        if length >= LONG_SENTENCE && long_sentences.len() < MAX_ISSUES {
+            long_sentences.push(issue(sentence, ""));
+        }
+
+        for m in zh_passive_re().find_iter(&sentence.text).chain(en_passive_re().find_iter(&sentence.text)) {
+            passive_count += 1;
+            if passive.len() < MAX_ISSUES {
+                passive.push(issue(sentence, m.as_str()));
+            }
+        }
+
+        let zh = ZH_FILLERS
+            .iter()
+            .flat_map(|f| sentence.text.match_indices(f).map(|(_, s)| s.to_string()))
+            .collect::<Vec<_>>();
+        let en = en_filler_re().find_iter(&sentence.text).map(|m| m.as_str().to_string());
+        for word in zh.into_iter().chain(en) {
+            filler_count += 1;
+            if fillers.len() < MAX_ISSUES {
+                fillers.push(issue(sentence, &word));
+            }
+        }
+    }
+
+    ReadabilityReport {
+        characters: plain.iter().map(|(_, l)| l.chars().filter(|c| !c.is_whitespace()).count()).sum(),
+        cjk_characters,
+        words,
+        sentences: sentences.len(),
+        paragraphs,
+        reading_minutes: (reading_minutes * 10.0).round() / 10.0,
+        avg_sentence_length: if sentences.is_empty() { 0.0 } else { total_length as f32 / sentences.len() as f32 },
+        sentence_lengths,
+        long_sentences,
+        passive_count,
+        passive,
+        filler_count,
+        fillers,
+    }
+}
+
+fn issue(sentence: &Sentence, text: &str) -> TextIssue {
+    const MAX_EXCERPT: usize = 80;
+    let mut excerpt: String = sentence.text.chars().take(MAX_EXCERPT).collect();
+    if sentence.text.chars().count() > MAX_EXCERPT {
+        excerpt.push('…');
+    }
+    TextIssue { line: sentence.line, text: text.to_string(), excerpt }
+}
+
+/// 去掉 Markdown 标记，返回 (行号, 纯文本) 和段落数
+fn strip_markdown(content: &str) -> (Vec<(usize, String)>, usize) {
+    static LINK: OnceLock<Regex> = OnceLock::new();
+    static IMAGE: OnceLock<Regex> = OnceLock::new();
+    static INLINE: OnceLock<Regex> = OnceLock::new();
+    static PREFIX: OnceLock<Regex> = OnceLock::new();
+    let image = re(&IMAGE, r"!\[[^\]]*\]\([^)]*\)");
+    let link = re(&LINK, r"\[([^\]]*)\]\([^)]*\)");
+    let inline = re(&INLINE, r"`[^`]*`|<[^>]+>|[*_~]{1,3}");
+    let prefix = re(&PREFIX, r"^\s*(?:#{1,6}\s+|>\s*|[-*+]\s+(?:\[[ x]\]\s+)?|\d+[.)]\s+)+");
+
+    let mut lines = Vec::new();
+    let mut paragraphs = 0;
+    let mut in_paragraph = false;
+    let mut fence: Option<&str> = None;
+    let mut in_front_matter = false;
+
+    for (i, raw) in content.lines().enumerate() {
+        let trimmed = raw.trim();
+        if i == 0 && trimmed == "---" {
+            in_front_matter = true;
+            continue;
+        }
+        if in_front_matter {
+            in_front_matter = trimmed != "---" && trimmed != "...";
+            continue;
+        }
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+            in_paragraph = false;
+            continue;
+        }
+        // 表格分隔行、分隔线
+        if trimmed.is_empty() || trimmed.chars().all(|c| matches!(c, '-' | '|' | ':' | ' ' | '*' | '_' | '=')) {
+            in_paragraph = false;
+            continue;
+        }
+
+        let text = image.replace_all(trimmed, "");
+        let text = link.replace_all(&text, "$1");
+        let text = inline.replace_all(&text, "");
+        let text = prefix.replace(&text, "");
+        let text = text.replace('|', " ");
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        if !in_paragraph {
+            paragraphs += 1;
+            in_paragraph = true;
+        }
+        lines.push((i + 1, text.to_string()));
+    }
+    (lines, paragraphs)
+}
+
+/// 按句末标点切分句子；每行单独切分，不处理跨行的句子
+fn split_sentences(lines: &[(usize, String)]) -> Vec<Sentence> {
+    let mut sentences = Vec::new();
+    for (line, text) in lines {
+        let chars: Vec<char> = text.chars().collect();
+        let mut current = String::new();
+        for (i, &c) in chars.iter().enumerate() {
+            current.push(c);
+            let end = match c {
+                '。' | '！' | '？' | '；' | '…' => true,
+                // 英文句号后须为空白或行尾，避免切开小数和缩写
+                '.' | '!' | '?' | ';' => chars.get(i + 1).is_none_or(|n| n.is_whitespace() || matches!(n, '"' | '\'' | '”' | ')')),
+                _ => false,
+            };
+            if end {
+                push_sentence(&mut sentences, *line, &mut current);
+            }
+        }
+        push_sentence(&mut sentences, *line, &mut current);
+    }
+    sentences
+}
+
+fn push_sentence(sentences: &mut Vec<Sentence>, line: usize, current: &mut String) {
+    let text = std::mem::take(current);
+    let text = text.trim();
+    if text.chars().any(|c| c.is_alphanumeric()) {
+        sentences.push(Sentence { line, text: text.to_string() });
+    }
+}
@@ -59,6 +59,15 @@ pub struct CategoryInfo {
     pub resource_type: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallRecord {
+    pub resource_id: String,
+    pub action: String,
+    pub from_version: Option<String>,
+    pub to_version: Option<String>,
+    pub timestamp: String,
+}
+
 /// 通用 manifest 结构（从 JSON 文件读取）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GenericManifest {
@@ -292,6 +301,7 @@ impl ResourceEngine {
             ("project-templates", "project-template"),
             ("ai-providers", "ai-provider"),
             ("plugins", "plugin"),
+            ("export-styles", "export-style"),
         ];
 
         for (dir_name, resource_type) in &resource_types {
@@ -342,48 +352,32 @@ impl ResourceEngine {
                         _ => String::new(),
                     };
 
-                    let tags_json = serde_json::to_string(&manifest.tags).unwrap_or_default();
-                    let data_path = path.to_string_lossy().to_string();
-                    let now = chrono::Utc::now().to_rfc3339();
-
-                    // 读取完整 manifest 作为 extra JSON
-                    let extra = content.clone();
-
-                    self.db.execute(
-                        "INSERT OR REPLACE INTO resources (
-                            id, package_name, resource_type, name, description, icon,
-                            author, version, major_category, sub_category, tags,
-                            sort_order, enabled, source, created_at, updated_at,
-                            installed_at, data_path, checksum, min_app_version, extra
-                        ) VALUES (
-                            ?1, ?2, ?3, ?4, ?5, ?6,
-                            ?7, ?8, ?9, ?10, ?11,
-                            ?12, ?13, ?14, ?15, ?16,
-                            ?17, ?18, ?19, ?20, ?21
-                        )",
-                        params![
-                            manifest.id,
-                            manifest.package_name,
-                            if manifest.resource_type.is_empty() { resource_type } else { &manifest.resource_type },
-                            manifest.name,
-                            manifest.description,
-                            manifest.icon,
-                            author_str,
-                            manifest.version,
-                            manifest.major_category,
-                            manifest.sub_category,
-                            tags_json,
-                            manifest.order,
-                            manifest.enabled as i32,
-                            if manifest.source == "builtin" { source } else { &manifest.source },
-                            manifest.created_at,
-                            manifest.updated_at,
-                            now,
-                            data_path,
-                            manifest.checksum,
-                            manifest.min_app_version,
-                            extra,
-                        ],
+                    let resource = ResourceSummary {
+                        id: manifest.id,
+                        package_name: manifest.package_name,
+                        resource_type: if manifest.resource_type.is_empty() { resource_type.to_string() } else { manifest.resource_type },
+                        name: manifest.name,
+                        description: manifest.description,
+                        icon: manifest.icon,
+                        author: author_str,
+                        version: manifest.version,
+                        major_category: manifest.major_category,
+                        sub_category: manifest.sub_category,
+                        tags: manifest.tags,
+                        order: manifest.order,
+                        enabled: manifest.enabled,
+                        source: if manifest.source == "builtin" { source.to_string() } else { manifest.source },
+                        created_at: manifest.created_at,
+                        updated_at: manifest.updated_at,
+                        data_path: path.to_string_lossy().to_string(),
+                    };
+
+                    // 完整 manifest 作为 extra JSON
+                    self.upsert(
+                        &resource,
+                        manifest.checksum.as_deref(),
+                        manifest.min_app_version.as_deref(),
+                        &content,
                     )?;
                 }
             }
@@ -392,6 +386,102 @@ impl ResourceEngine {
         Ok(())
     }
 
+    /// 写入或更新单个资源索引（不重建 FTS，批量写入后调用 rebuild_fts）
+    pub fn upsert(
+        &self,
+        resource: &ResourceSummary,
+        checksum: Option<&str>,
+        min_app_version: Option<&str>,
+        extra: &str,
+    ) -> SqlResult<()> {
+        let tags_json = serde_json::to_string(&resource.tags).unwrap_or_default();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        self.db.execute(
+            "INSERT OR REPLACE INTO resources (
+                id, package_name, resource_type, name, description, icon,
+                author, version, major_category, sub_category, tags,
+                sort_order, enabled, source, created_at, updated_at,
+                installed_at, data_path, checksum, min_app_version, extra
+            ) VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6,
+                ?7, ?8, ?9, ?10, ?11,
+                ?12, ?13, ?14, ?15, ?16,
+                ?17, ?18, ?19, ?20, ?21
+            )",
+            params![
+                resource.id,
+                resource.package_name,
+                resource.resource_type,
+                resource.name,
+                resource.description,
+                resource.icon,
+                resource.author,
+                resource.version,
+                resource.major_category,
+                resource.sub_category,
+                tags_json,
+                resource.order,
+                resource.enabled as i32,
+                resource.source,
+                resource.created_at,
+                resource.updated_at,
+                now,
+                resource.data_path,
+                checksum,
+                min_app_version,
+                extra,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 确保分类存在（资源自带分类而无 _meta.json 时使用，名称默认取 key）
+    pub fn ensure_category(&self, resource_type: &str, key: &str, parent_key: Option<&str>) -> SqlResult<()> {
+        if key.is_empty() {
+            return Ok(());
+        }
+        // parent_key 为 NULL 时 UNIQUE 约束不生效，需手动判断
+        self.db.execute(
+            "INSERT INTO categories (resource_type, key, name, parent_key, sort_order)
+             SELECT ?1, ?2, ?2, ?3, 0
+             WHERE NOT EXISTS (
+                 SELECT 1 FROM categories
+                 WHERE resource_type = ?1 AND key = ?2 AND parent_key IS ?3
+             )",
+            params![resource_type, key, parent_key],
+        )?;
+        Ok(())
+    }
+
+    /// 记录安装历史
+    pub fn record_install(
+        &self,
+        resource_id: &str,
+        action: &str,
+        from_version: Option<&str>,
+        to_version: Option<&str>,
+    ) -> SqlResult<()> {
+        self.db.execute(
+            "INSERT INTO install_history (resource_id, action, from_version, to_version, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![resource_id, action, from_version, to_version, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// 替换资源的依赖声明
+    pub fn set_dependencies(&self, resource_id: &str, depends_on: &[String]) -> SqlResult<()> {
+        self.db.execute("DELETE FROM dependencies WHERE resource_id = ?1", params![resource_id])?;
+        for dep in depends_on {
+            self.db.execute(
+                "INSERT OR REPLACE INTO dependencies (resource_id, depends_on) VALUES (?1, ?2)",
+                params![resource_id, dep],
+            )?;
+        }
+        Ok(())
+    }
+
     /// 从 _meta.json 加载分类定义
     fn load_categories_from_meta(&self, meta_path: &Path, resource_type: &str) -> SqlResult<()> {
         let content = match fs::read_to_string(meta_path) {
@@ -428,7 +518,7 @@ impl ResourceEngine {
     }
 
     /// 重建 FTS 索引
-    fn rebuild_fts(&self) -> SqlResult<()> {
+    pub fn rebuild_fts(&self) -> SqlResult<()> {
         self.db.execute_batch(
             "
             DELETE FROM resources_fts;
@@ -610,6 +700,48 @@ impl ResourceEngine {
         }
     }
 
+    /// 获取资源的完整 manifest JSON 及数据目录
+    pub fn get_with_path(&self, id: &str) -> SqlResult<Option<(String, String)>> {
+        let mut stmt = self.db.prepare(
+            "SELECT extra, data_path FROM resources WHERE id = ?1"
+        )?;
+        let mut rows = stmt.query(params![id])?;
+        if let Some(row) = rows.next()? {
+            Ok(Some((row.get(0)?, row.get(1)?)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// 列出指定类型所有资源的完整 manifest JSON
+    pub fn list_manifests(&self, resource_type: &str) -> SqlResult<Vec<String>> {
+        let mut stmt = self.db.prepare(
+            "SELECT extra FROM resources WHERE resource_type = ?1 AND extra IS NOT NULL
+             ORDER BY sort_order ASC, name ASC"
+        )?;
+        let rows = stmt.query_map(params![resource_type], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    /// 列出安装历史（最新在前）
+    pub fn list_install_history(&self, resource_id: Option<&str>) -> SqlResult<Vec<InstallRecord>> {
+        let mut stmt = self.db.prepare(
+            "SELECT resource_id, action, from_version, to_version, timestamp FROM install_history
+             WHERE ?1 IS NULL OR resource_id = ?1
+             ORDER BY id DESC LIMIT 500"
+        )?;
+        let rows = stmt.query_map(params![resource_id], |row| {
+            Ok(InstallRecord {
+                resource_id: row.get(0)?,
+                action: row.get(1)?,
+                from_version: row.get(2)?,
+                to_version: row.get(3)?,
+                timestamp: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+
     /// 设置资源启用/禁用
     pub fn set_enabled(&self, id: &str, enabled: bool) -> SqlResult<()> {
         self.db.execute(
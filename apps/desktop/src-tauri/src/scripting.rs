@@ -0,0 +1,324 @@
+// 自动化脚本：内嵌 Rhai 脚本引擎，脚本只能通过注册的函数访问文档、模板和导出，
+// 引擎不提供文件、网络和 import，限制运算次数、调用深度、字符串长度和运行时间
+// 脚本可设置每天或每周的定时触发，由后台调度器检查执行，错过的触发在启动后补执行一次
+//
+// 脚本可用的函数：
+//   load_document(项目ID, 文档ID) -> #{ id, title, content }
+//   render(文本, 变量表) -> 替换 {{变量}} 后的文本
+//   template(模板ID, 变量表) -> 套用模板正文
+//   create_document(项目ID, 标题, 正文) -> 新文档 ID
+//   export(项目ID, 标题, 内容, 格式, 绝对路径) -> 实际写入的路径
+//   print(文本) -> 写入运行日志
+// 常量 context 为上下文变量表，含内置的 date、time、week、weekday
+
+use crate::config::{is_safe_id, AppState};
+use crate::document::Document;
+use crate::template;
+use chrono::{Datelike, NaiveTime, TimeZone};
+use rhai::{Dynamic, Engine, EvalAltResult, Map, Scope};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// 脚本源码的最大长度（字符）
+pub const MAX_SOURCE_CHARS: usize = 100_000;
+/// 单个字符串的最大长度（字节），防止拼接失控占满内存
+const MAX_STRING_SIZE: usize = 20_000_000;
+/// 单次运行的最多运算次数
+const MAX_OPERATIONS: u64 = 50_000_000;
+/// 单次运行的最长时间
+const MAX_RUN_TIME: Duration = Duration::from_secs(120);
+/// 保留的运行日志条数
+const MAX_LOG_LINES: usize = 200;
+
+type ScriptError = Box<EvalAltResult>;
+
+/// 定时触发：weekday 为空时每天执行，1–7 表示周一到周日
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptSchedule {
+    #[serde(default)]
+    pub weekday: Option<u32>,
+    pub hour: u32,
+    pub minute: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Script {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// Rhai 脚本源码
+    pub source: String,
+    pub schedule: Option<ScriptSchedule>,
+    pub enabled: bool,
+    /// 定时执行时使用的上下文变量
+    pub context: HashMap<String, String>,
+    pub updated_at: i64,
+    pub last_run_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptRunResult {
+    pub script_id: String,
+    /// 新建的文档 ID
+    pub created_documents: Vec<String>,
+    /// 导出的文件路径
+    pub exported: Vec<String>,
+    pub log: Vec<String>,
+}
+
+pub fn load_all(path: &Path) -> Vec<Script> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_all(path: &Path, scripts: &[Script]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(scripts).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("保存脚本失败: {}", e))
+}
+
+pub fn get(path: &Path, id: &str) -> Result<Script, String> {
+    load_all(path).into_iter().find(|s| s.id == id).ok_or_else(|| format!("脚本不存在: {}", id))
+}
+
+/// 新增或更新脚本（id 为空时新增），保存前校验步骤和定时设置
+pub fn upsert(path: &Path, mut script: Script) -> Result<Script, String> {
+    script.name = script.name.trim().to_string();
+    if script.name.is_empty() {
+        return Err("脚本名称不能为空".to_string());
+    }
+    validate(&script)?;
+    script.updated_at = chrono::Utc::now().timestamp();
+
+    let mut scripts = load_all(path);
+    match scripts.iter_mut().find(|s| !script.id.is_empty() && s.id == script.id) {
+        Some(existing) => {
+            // 运行记录由后端维护
+            script.last_run_at = existing.last_run_at;
+            script.last_error = existing.last_error.clone();
+            *existing = script.clone();
+        }
+        None => {
+            script.id = uuid::Uuid::new_v4().to_string();
+            scripts.push(script.clone());
+        }
+    }
+    save_all(path, &scripts)?;
+    Ok(script)
+}
+
+pub fn delete(path: &Path, id: &str) -> Result<(), String> {
+    let mut scripts = load_all(path);
+    let before = scripts.len();
+    scripts.retain(|s| s.id != id);
+    if scripts.len() == before {
+        return Err(format!("脚本不存在: {}", id));
+    }
+    save_all(path, &scripts)
+}
+
+fn validate(script: &Script) -> Result<(), String> {
+    if script.source.trim().is_empty() {
+        return Err("脚本内容不能为空".to_string());
+    }
+    if script.source.chars().count() > MAX_SOURCE_CHARS {
+        return Err(format!("脚本不能超过 {} 个字符", MAX_SOURCE_CHARS));
+    }
+    if let Some(schedule) = &script.schedule {
+        if schedule.hour > 23 || schedule.minute > 59 || schedule.weekday.is_some_and(|d| !(1..=7).contains(&d)) {
+            return Err("定时设置无效".to_string());
+        }
+    }
+    sandboxed_engine().compile(&script.source).map_err(|e| format!("脚本语法错误: {}", e))?;
+    Ok(())
+}
+
+/// 记录一次运行结果
+pub fn record_run(path: &Path, id: &str, error: Option<String>) -> Result<(), String> {
+    let mut scripts = load_all(path);
+    let Some(script) = scripts.iter_mut().find(|s| s.id == id) else { return Ok(()) };
+    script.last_run_at = Some(chrono::Utc::now().timestamp());
+    script.last_error = error;
+    save_all(path, &scripts)
+}
+
+/// 最近一次应触发的时间（不晚于 now）
+fn last_occurrence(schedule: &ScriptSchedule, now: chrono::DateTime<chrono::Local>) -> Option<i64> {
+    let time = NaiveTime::from_hms_opt(schedule.hour, schedule.minute, 0)?;
+    let days_back = match schedule.weekday {
+        Some(weekday) => (now.weekday().number_from_monday() + 7 - weekday) % 7,
+        None => 0,
+    };
+    let mut date = now.date_naive() - chrono::Duration::days(days_back as i64);
+    if date.and_time(time) > now.naive_local() {
+        date -= chrono::Duration::days(if schedule.weekday.is_some() { 7 } else { 1 });
+    }
+    chrono::Local.from_local_datetime(&date.and_time(time)).earliest().map(|t| t.timestamp())
+}
+
+/// 到期的定时脚本：最近一次触发时间晚于上次运行和最后修改时间
+pub fn due(path: &Path, now: chrono::DateTime<chrono::Local>) -> Vec<Script> {
+    load_all(path)
+        .into_iter()
+        .filter(|s| s.enabled)
+        .filter(|s| {
+            let Some(occurrence) = s.schedule.as_ref().and_then(|schedule| last_occurrence(schedule, now)) else {
+                return false;
+            };
+            occurrence > s.last_run_at.unwrap_or(0).max(s.updated_at)
+        })
+        .collect()
+}
+
+/// 导出回调：(项目 ID, 标题, 内容, 格式, 输出路径) -> 实际写入的路径
+pub type Exporter = dyn Fn(&str, &str, &str, &str, &str) -> Result<String, String>;
+
+/// 沙箱化的引擎：不加载外部模块、不允许 eval，并限制资源占用
+fn sandboxed_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(MAX_STRING_SIZE);
+    engine.set_max_array_size(100_000);
+    engine.set_max_map_size(10_000);
+    engine
+}
+
+/// 执行脚本；context 中的变量以常量 context 提供给脚本，另有内置变量 date、time、week、weekday
+pub fn run(
+    state: &AppState,
+    script: &Script,
+    context: HashMap<String, String>,
+    export: Rc<Exporter>,
+) -> Result<ScriptRunResult, String> {
+    validate(script)?;
+    let now = chrono::Local::now();
+    let mut vars: HashMap<String, String> = HashMap::from([
+        ("date".to_string(), now.format("%Y-%m-%d").to_string()),
+        ("time".to_string(), now.format("%H:%M").to_string()),
+        ("week".to_string(), now.format("%G-W%V").to_string()),
+        ("weekday".to_string(), now.weekday().number_from_monday().to_string()),
+    ]);
+    vars.extend(context);
+
+    let result = Rc::new(RefCell::new(ScriptRunResult { script_id: script.id.clone(), ..Default::default() }));
+    let mut engine = sandboxed_engine();
+    register_api(&mut engine, state, &result, export);
+    let started = Instant::now();
+    engine.on_progress(move |_| (started.elapsed() > MAX_RUN_TIME).then_some(Dynamic::UNIT));
+
+    let mut scope = Scope::new();
+    let context: Map = vars.into_iter().map(|(k, v)| (k.into(), Dynamic::from(v))).collect();
+    scope.push_constant("context", context);
+    engine.run_with_scope(&mut scope, &script.source).map_err(|e| match *e {
+        EvalAltResult::ErrorTerminated(..) => format!("脚本运行超过 {} 秒，已中止", MAX_RUN_TIME.as_secs()),
+        _ => format!("脚本执行失败: {}", e),
+    })?;
+    Ok(result.take())
+}
+
+/// 注册脚本可调用的函数
+fn register_api(engine: &mut Engine, state: &AppState, result: &Rc<RefCell<ScriptRunResult>>, export: Rc<Exporter>) {
+    let log = {
+        let result = result.clone();
+        move |line: String| {
+            let mut result = result.borrow_mut();
+            if result.log.len() < MAX_LOG_LINES {
+                result.log.push(line);
+            }
+        }
+    };
+    let print_log = log.clone();
+    engine.on_print(move |text| print_log(text.to_string()));
+    let debug_log = log.clone();
+    engine.on_debug(move |text, _, pos| debug_log(format!("[{}] {}", pos, text)));
+
+    let loader = state.clone();
+    let load_log = log.clone();
+    engine.register_fn("load_document", move |project_id: &str, document_id: &str| -> Result<Map, ScriptError> {
+        let document = load_document(&loader, project_id, document_id)?;
+        load_log(format!("读取《{}》", document.title));
+        let mut map = Map::new();
+        map.insert("id".into(), Dynamic::from(document.id.clone()));
+        map.insert("title".into(), Dynamic::from(document.title.clone()));
+        map.insert("content".into(), Dynamic::from(body(&document).to_string()));
+        Ok(map)
+    });
+
+    engine.register_fn("render", |text: &str, vars: Map| template::render_variables(text, &to_vars(&vars)));
+
+    engine.register_fn("template", |template_id: &str, vars: Map| -> Result<String, ScriptError> {
+        let content = template::get_template_content(template_id)?;
+        let source = if content.ai_generated_content.trim().is_empty() { content.content } else { content.ai_generated_content };
+        Ok(template::render_variables(&source, &to_vars(&vars)))
+    });
+
+    let creator = state.clone();
+    let created = result.clone();
+    let create_log = log.clone();
+    engine.register_fn("create_document", move |project_id: &str, title: &str, content: &str| -> Result<String, ScriptError> {
+        let project_id = checked_id(project_id)?;
+        if !creator.get_project_path(project_id).exists() {
+            return Err(format!("项目不存在: {}", project_id).into());
+        }
+        let mut document = Document::new(project_id.to_string(), title.to_string(), String::new());
+        document.content = content.to_string();
+        document.metadata.word_count = document.content.split_whitespace().count();
+        document.metadata.character_count = document.content.chars().count();
+        document
+            .save(&creator.get_document_path(project_id, &document.id))
+            .map_err(|e| format!("保存文档失败: {}", e))?;
+        create_log(format!("新建文档《{}》", document.title));
+        created.borrow_mut().created_documents.push(document.id.clone());
+        Ok(document.id)
+    });
+
+    let exported = result.clone();
+    engine.register_fn(
+        "export",
+        move |project_id: &str, title: &str, content: &str, format: &str, output_path: &str| -> Result<String, ScriptError> {
+            if !Path::new(output_path).is_absolute() {
+                return Err(format!("导出路径必须为绝对路径: {}", output_path).into());
+            }
+            let path = export(checked_id(project_id)?, title, content, format, output_path)?;
+            log(format!("导出 {}", path));
+            exported.borrow_mut().exported.push(path.clone());
+            Ok(path)
+        },
+    );
+}
+
+/// 脚本变量表转为模板变量
+fn to_vars(map: &Map) -> HashMap<String, String> {
+    map.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+/// 项目、文档 ID 会拼接为文件路径，只允许单个路径段
+fn checked_id(id: &str) -> Result<&str, String> {
+    if is_safe_id(id) { Ok(id) } else { Err(format!("无效的 ID: {}", id)) }
+}
+
+fn load_document(state: &AppState, project_id: &str, document_id: &str) -> Result<Document, String> {
+    if !is_safe_id(project_id) || !is_safe_id(document_id) {
+        return Err(format!("无效的 ID: {}/{}", project_id, document_id));
+    }
+    Document::load(&state.get_document_path(project_id, document_id)).map_err(|_| format!("文档不存在: {}", document_id))
+}
+
+/// 文档正文：优先使用 AI 生成内容
+fn body(document: &Document) -> &str {
+    if document.ai_generated_content.trim().is_empty() { &document.content } else { &document.ai_generated_content }
+}
@@ -0,0 +1,167 @@
+use crate::keychain;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+/// 前端可直接管理的密钥命名空间；email / email-oauth 由邮箱账户模块维护，api-server 由本地 API 维护
+const USER_NAMESPACES: &[&str] = &["ai", "sync", "webdav", "custom"];
+
+/// 密钥索引条目：钥匙串无法枚举，另存一份不含明文的索引用于展示
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretEntry {
+    /// 形如 "ai:openai"、"email:{账户ID}"、"webdav:{配置ID}"
+    pub key: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// 脱敏后的值，如 "sk-****34"
+    pub masked: String,
+    pub updated_at: i64,
+}
+
+static INDEX_PATH: OnceLock<PathBuf> = OnceLock::new();
+static INDEX_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+/// get_cached 读过的明文，store / delete 时失效
+static VALUE_CACHE: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+
+fn value_cache() -> &'static Mutex<HashMap<String, Option<String>>> {
+    VALUE_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn invalidate(key: &str) {
+    if let Ok(mut cache) = value_cache().lock() {
+        cache.remove(key);
+    }
+}
+
+/// 设置索引文件位置（应用启动时调用）
+pub fn init(index_path: PathBuf) {
+    let _ = INDEX_PATH.set(index_path);
+    // 旧版本索引保留了前后各 4 位，重新脱敏
+    if load_index().iter().any(|e| e.masked.chars().filter(|&c| c != '*').count() > MASK_VISIBLE) {
+        let _ = with_index(|entries| {
+            for e in entries.iter_mut().filter(|e| e.masked.chars().filter(|&c| c != '*').count() > MASK_VISIBLE) {
+                e.masked = "*".repeat(8);
+            }
+        });
+    }
+}
+
+fn with_index<R>(f: impl FnOnce(&mut Vec<SecretEntry>) -> R) -> Result<R, String> {
+    let path = INDEX_PATH.get().ok_or("密钥管理未初始化")?;
+    let _guard = INDEX_LOCK
+        .get_or_init(|| Mutex::new(()))
+        .lock()
+        .map_err(|e| format!("锁获取失败: {}", e))?;
+
+    let mut entries = load_index();
+    let result = f(&mut entries);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(&entries)
+        .map_err(|e| format!("Failed to serialize secrets index: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write secrets index: {}", e))?;
+    Ok(result)
+}
+
+fn load_index() -> Vec<SecretEntry> {
+    INDEX_PATH
+        .get()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// 校验前端传入的密钥名：只允许 USER_NAMESPACES 下的 "命名空间:名称"
+pub fn validate_user_key(key: &str) -> Result<(), String> {
+    let (namespace, name) = key.split_once(':').ok_or_else(|| format!("密钥名格式应为 命名空间:名称: {}", key))?;
+    if !USER_NAMESPACES.contains(&namespace) {
+        return Err(format!("不允许的密钥命名空间: {}", namespace));
+    }
+    if name.is_empty()
+        || name.len() > 128
+        || !name.chars().all(|c| c.is_ascii_alphanumeric() || "._@-".contains(c))
+    {
+        return Err(format!("无效的密钥名称: {}", name));
+    }
+    Ok(())
+}
+
+/// 保存密钥到系统钥匙串并更新索引
+pub fn store(key: &str, value: &str, label: Option<String>) -> Result<SecretEntry, String> {
+    if value.is_empty() {
+        return Err("密钥值不能为空".to_string());
+    }
+    keychain::set_secret(key, value)?;
+    invalidate(key);
+
+    let entry = SecretEntry {
+        key: key.to_string(),
+        label,
+        masked: mask(value),
+        updated_at: chrono::Utc::now().timestamp(),
+    };
+    let saved = entry.clone();
+    with_index(|entries| match entries.iter_mut().find(|e| e.key == key) {
+        Some(existing) => {
+            // 未提供新标签时保留原标签
+            let label = entry.label.clone().or(existing.label.take());
+            *existing = SecretEntry { label, ..entry };
+        }
+        None => entries.push(entry),
+    })?;
+    Ok(saved)
+}
+
+/// 读取密钥明文（仅供后端使用，不得通过命令返回给前端）
+pub fn get(key: &str) -> Result<Option<String>, String> {
+    keychain::get_secret(key)
+}
+
+/// 同 get，结果缓存在内存中，用于每次请求都要读取的密钥（如代理密码），避免反复调用钥匙串
+pub fn get_cached(key: &str) -> Result<Option<String>, String> {
+    if let Some(value) = value_cache().lock().ok().and_then(|cache| cache.get(key).cloned()) {
+        return Ok(value);
+    }
+    let value = keychain::get_secret(key)?;
+    if let Ok(mut cache) = value_cache().lock() {
+        cache.insert(key.to_string(), value.clone());
+    }
+    Ok(value)
+}
+
+pub fn delete(key: &str) -> Result<(), String> {
+    keychain::delete_secret(key)?;
+    invalidate(key);
+    with_index(|entries| entries.retain(|e| e.key != key))
+}
+
+/// 列出索引中的密钥（只含脱敏值），可按命名空间过滤
+pub fn list(namespace: Option<&str>) -> Vec<SecretEntry> {
+    load_index()
+        .into_iter()
+        .filter(|e| namespace.is_none_or(|ns| e.key.split(':').next() == Some(ns)))
+        .collect()
+}
+
+pub fn entry(key: &str) -> Option<SecretEntry> {
+    load_index().into_iter().find(|e| e.key == key)
+}
+
+/// 脱敏后最多保留的明文字符数
+const MASK_VISIBLE: usize = 5;
+
+/// 脱敏：20 位以上的值保留前 3 位（密钥前缀）和后 2 位，其余全部隐藏且不暴露长度
+pub fn mask(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() < 20 {
+        return "*".repeat(8);
+    }
+    let head: String = chars[..3].iter().collect();
+    let tail: String = chars[chars.len() - 2..].iter().collect();
+    format!("{}****{}", head, tail)
+}
@@ -0,0 +1,552 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+/// 后端持久化的应用级设置（编辑器、界面等纯前端设置仍由前端保存）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppSettings {
+    /// 界面及后端提示语言，如 "zh-CN" / "en"
+    pub locale: String,
+    pub ai: AiDefaults,
+    pub export: ExportPreferences,
+    pub proxy: ProxySettings,
+    /// 菜单快捷键覆盖：菜单项 ID → 快捷键（如 "CmdOrCtrl+Shift+E"），空字符串表示取消绑定
+    pub keybindings: HashMap<String, String>,
+    pub logging: LoggingSettings,
+    pub updates: UpdateSettings,
+    pub tray: TraySettings,
+    pub api_server: ApiServerSettings,
+    pub spellcheck: SpellcheckSettings,
+    pub goals: GoalSettings,
+    pub web_search: WebSearchSettings,
+    pub updated_at: i64,
+}
+
+/// AI 请求的默认参数（调用方未指定时使用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AiDefaults {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+    /// 单次请求超时（秒）
+    pub request_timeout_secs: u64,
+    /// 费用估算的价格覆盖：模型名（或前缀）→ 每百万 token 价格（美元）
+    pub pricing: HashMap<String, crate::pricing::ModelPrice>,
+    /// 非流式请求的响应缓存有效期（秒），0 表示不缓存
+    pub cache_ttl_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportPreferences {
+    /// 默认导出格式：md / html / docx / pdf / pptx / xlsx / txt
+    pub default_format: String,
+    /// 默认导出目录，为空时每次询问
+    pub output_dir: Option<String>,
+    /// 自定义 Pandoc 可执行文件路径
+    pub pandoc_path: Option<String>,
+    /// 导出后是否打开所在文件夹
+    pub reveal_after_export: bool,
+    /// 导出前先规范化 Markdown（format_markdown 默认规则）
+    pub format_before_export: bool,
+    /// 默认导出文件名模板，可用 {project} {title} {author} {date} {time} {format} {suffix}
+    pub filename_template: String,
+    pub pdf_fonts: PdfFontSettings,
+    /// 打印 PDF、渲染 Mermaid 图表使用的 Chromium 内核浏览器（Chrome / Edge / Chromium）路径，为空时自动查找
+    pub pdf_browser_path: Option<String>,
+    /// HTML / DOCX / PDF 导出的默认样式方案：内置的 gongwen / academic / business / plain 或自定义样式 ID
+    pub style_profile: String,
+    /// HTML / PDF 导出的代码高亮主题（syntect 内置主题），为空时不高亮
+    pub code_theme: String,
+    /// PPTX 导出的幻灯片主题
+    pub pptx_theme: String,
+    pub header_footer: HeaderFooterSettings,
+    pub cover: CoverSettings,
+    pub watermark: WatermarkSettings,
+    pub images: ImageSettings,
+    /// DOCX 导出的参考文档（如单位的 Word 模板），标题、正文、表格套用其中的样式；为空时按样式方案排版
+    pub reference_docx: Option<String>,
+}
+
+/// DOCX / HTML / PDF 导出时内嵌图片的压缩
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ImageSettings {
+    /// 是否缩小、重新压缩图片；关闭时按原图嵌入
+    pub compress: bool,
+    /// 最大宽度（像素），超出时按比例缩小，0 表示不限
+    pub max_width: u32,
+    /// 最大高度（像素），0 表示不限
+    pub max_height: u32,
+    /// JPEG 质量（1–100）
+    pub jpeg_quality: u8,
+    /// 把不含透明像素的 PNG / GIF 静态图转为 JPEG
+    pub png_to_jpeg: bool,
+}
+
+impl Default for ImageSettings {
+    fn default() -> Self {
+        Self { compress: false, max_width: 1600, max_height: 1600, jpeg_quality: 85, png_to_jpeg: false }
+    }
+}
+
+/// DOCX / HTML / PDF 导出的水印；文字和图片都为空时不加水印
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WatermarkSettings {
+    /// 水印文字，如“草稿”“机密”
+    pub text: String,
+    /// 水印图片路径，设置后优先于文字
+    pub image_path: Option<String>,
+    /// 不透明度（0–1）
+    pub opacity: f32,
+}
+
+impl Default for WatermarkSettings {
+    fn default() -> Self {
+        Self { text: String::new(), image_path: None, opacity: 0.2 }
+    }
+}
+
+/// DOCX / HTML / PDF 导出的封面
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CoverSettings {
+    /// 是否在正文前插入封面（标题、作者、单位、日期、徽标）
+    pub enabled: bool,
+    /// 单位名称，为空时不显示
+    pub organization: String,
+    /// 徽标图片路径（PNG / JPEG 等），为空时不显示
+    pub logo_path: Option<String>,
+}
+
+/// DOCX 导出的页眉页脚
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HeaderFooterSettings {
+    /// 页眉文字，为空时不加页眉
+    pub header: String,
+    /// 页脚文字，显示在页码上方
+    pub footer: String,
+    /// 页码格式：dashes（— X —）/ plain（X）/ total（X / Y）/ none；为空时按样式方案
+    pub page_number: String,
+    /// 首页不同：首页不显示页眉页脚和页码
+    pub different_first_page: bool,
+    /// 奇偶页不同：单页页码居右、双页页码居左（公文格式），偶数页可单独设置页眉
+    pub different_odd_even: bool,
+    /// 偶数页页眉，为空时与奇数页相同
+    pub even_header: String,
+}
+
+/// 页码格式
+pub const PAGE_NUMBER_FORMATS: &[&str] = &["dashes", "plain", "total", "none"];
+
+/// PDF 导出内嵌的中文字体；未指定路径时依次查找用户字体目录、内置字体和系统字体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PdfFontSettings {
+    /// 是否把字体嵌入打印页，使未安装中文字体的机器也能得到相同的 PDF
+    pub embed: bool,
+    /// 仿宋（正文）字体文件路径
+    pub fangsong: Option<String>,
+    /// 黑体（一级标题）
+    pub heiti: Option<String>,
+    /// 楷体（二级标题）
+    pub kaiti: Option<String>,
+    /// 宋体（文件标题）
+    pub songti: Option<String>,
+}
+
+impl Default for PdfFontSettings {
+    fn default() -> Self {
+        Self { embed: true, fangsong: None, heiti: None, kaiti: None, songti: None }
+    }
+}
+
+/// 网络代理（AI 请求、更新检查等出站 HTTP 请求使用）
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProxySettings {
+    pub enabled: bool,
+    /// http://host:port 或 socks5://host:port
+    pub url: Option<String>,
+    /// 不走代理的主机，如 localhost、127.0.0.1
+    pub no_proxy: Vec<String>,
+    /// 代理认证用户名；密码保存在钥匙串的 PROXY_PASSWORD_KEY 下
+    pub username: Option<String>,
+}
+
+/// 代理密码在钥匙串中的名称（前端用 set_secret 保存）
+pub const PROXY_PASSWORD_KEY: &str = "custom:proxy";
+
+/// 联网搜索后备：没有内置搜索的服务商（DeepSeek、MiniMax 等）通过 web_search 工具调用搜索服务
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WebSearchSettings {
+    /// searxng / bing / tavily
+    pub provider: String,
+    /// 搜索服务地址；SearXNG 必填，Bing 和 Tavily 为空时使用官方地址
+    pub endpoint: Option<String>,
+    /// 每次搜索返回的结果数
+    pub max_results: usize,
+}
+
+impl Default for WebSearchSettings {
+    fn default() -> Self {
+        Self { provider: "searxng".to_string(), endpoint: None, max_results: 5 }
+    }
+}
+
+/// 搜索服务 API Key 在钥匙串中的名称（前端用 set_secret 保存）
+pub const WEB_SEARCH_KEY: &str = "ai:web-search";
+
+/// 日志级别：trace / debug / info / warn / error / off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LoggingSettings {
+    pub level: String,
+    /// 按模块覆盖级别，如 { "commands::ai": "debug" }
+    pub modules: HashMap<String, String>,
+}
+
+impl Default for LoggingSettings {
+    fn default() -> Self {
+        Self { level: "info".to_string(), modules: HashMap::new() }
+    }
+}
+
+/// 更新检查
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UpdateSettings {
+    /// 启动时自动检查更新（关闭后仍可手动检查）
+    pub auto_check: bool,
+    /// 自定义发布源，为空时使用官方 GitHub Releases
+    pub feed_url: Option<String>,
+    /// 用户选择跳过的版本，不再提示
+    pub skipped_version: Option<String>,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self { auto_check: true, feed_url: None, skipped_version: None }
+    }
+}
+
+/// 系统托盘
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TraySettings {
+    /// 显示托盘图标
+    pub enabled: bool,
+    /// 关闭主窗口时隐藏到托盘而不是退出
+    pub close_to_tray: bool,
+}
+
+impl Default for TraySettings {
+    fn default() -> Self {
+        Self { enabled: true, close_to_tray: false }
+    }
+}
+
+/// 本地 HTTP API（仅监听 127.0.0.1，需令牌）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ApiServerSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for ApiServerSettings {
+    fn default() -> Self {
+        Self { enabled: false, port: 27183 }
+    }
+}
+
+/// 每日写作目标（字数，0 表示不设目标）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GoalSettings {
+    /// 所有项目合计
+    pub daily_words: usize,
+    /// 项目 ID → 该项目的每日目标
+    pub projects: HashMap<String, usize>,
+}
+
+/// 拼写检查
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SpellcheckSettings {
+    /// Hunspell 词典名称，如 en_US
+    pub language: String,
+}
+
+impl Default for SpellcheckSettings {
+    fn default() -> Self {
+        Self { language: "en_US".to_string() }
+    }
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            locale: "zh-CN".to_string(),
+            ai: AiDefaults::default(),
+            export: ExportPreferences::default(),
+            proxy: ProxySettings::default(),
+            keybindings: HashMap::new(),
+            logging: LoggingSettings::default(),
+            updates: UpdateSettings::default(),
+            tray: TraySettings::default(),
+            api_server: ApiServerSettings::default(),
+            spellcheck: SpellcheckSettings::default(),
+            goals: GoalSettings::default(),
+            web_search: WebSearchSettings::default(),
+            updated_at: 0,
+        }
+    }
+}
+
+impl Default for AiDefaults {
+    fn default() -> Self {
+        Self {
+            provider: None,
+            model: None,
+            temperature: None,
+            max_tokens: None,
+            request_timeout_secs: 120,
+            pricing: HashMap::new(),
+            cache_ttl_secs: 0,
+        }
+    }
+}
+
+impl Default for ExportPreferences {
+    fn default() -> Self {
+        Self {
+            default_format: "docx".to_string(),
+            output_dir: None,
+            pandoc_path: None,
+            reveal_after_export: false,
+            format_before_export: false,
+            filename_template: "{title}{suffix}".to_string(),
+            pdf_fonts: PdfFontSettings::default(),
+            pdf_browser_path: None,
+            style_profile: crate::native_export::styles::DEFAULT_PROFILE.to_string(),
+            code_theme: crate::native_export::html::DEFAULT_CODE_THEME.to_string(),
+            pptx_theme: crate::native_export::pptx::DEFAULT_THEME.to_string(),
+            header_footer: HeaderFooterSettings::default(),
+            cover: CoverSettings::default(),
+            watermark: WatermarkSettings::default(),
+            images: ImageSettings::default(),
+            reference_docx: None,
+        }
+    }
+}
+
+/// 文件路径只在本次更新修改它时检查是否存在，文件后来被移走不影响保存其他设置
+fn check_changed_file(new: Option<&str>, old: Option<&str>, what: &str) -> Result<(), String> {
+    match new.filter(|p| !p.trim().is_empty()) {
+        Some(path) if new != old && !Path::new(path).is_file() => Err(format!("{}不存在: {}", what, path)),
+        _ => Ok(()),
+    }
+}
+
+impl AppSettings {
+    fn validate(&self, previous: &AppSettings) -> Result<(), String> {
+        if crate::i18n::Locale::parse(&self.locale).is_none() {
+            return Err(format!("不支持的语言: {}", self.locale));
+        }
+        if let Some(t) = self.ai.temperature {
+            if !(0.0..=2.0).contains(&t) {
+                return Err(format!("temperature 必须在 0 到 2 之间: {}", t));
+            }
+        }
+        if self.ai.request_timeout_secs == 0 {
+            return Err("AI 请求超时时间必须大于 0".to_string());
+        }
+        if !["md", "html", "docx", "pdf", "pptx", "xlsx", "txt"].contains(&self.export.default_format.as_str()) {
+            return Err(format!("不支持的默认导出格式: {}", self.export.default_format));
+        }
+        crate::native_export::filename::validate(&self.export.filename_template)?;
+        if self.export.style_profile.trim().is_empty() {
+            return Err("默认导出样式不能为空".to_string());
+        }
+        if !self.export.code_theme.is_empty() && !crate::native_export::html::CODE_THEMES.contains(&self.export.code_theme.as_str()) {
+            return Err(format!("不支持的代码高亮主题: {}", self.export.code_theme));
+        }
+        crate::native_export::pptx::theme(&self.export.pptx_theme)?;
+        let page_number = &self.export.header_footer.page_number;
+        if !page_number.is_empty() && !PAGE_NUMBER_FORMATS.contains(&page_number.as_str()) {
+            return Err(format!("不支持的页码格式: {}", page_number));
+        }
+        let (export, old) = (&self.export, &previous.export);
+        check_changed_file(export.cover.logo_path.as_deref(), old.cover.logo_path.as_deref(), "封面徽标文件")?;
+        let watermark = &self.export.watermark;
+        if !(watermark.opacity > 0.0 && watermark.opacity <= 1.0) {
+            return Err(format!("水印不透明度必须在 0 到 1 之间: {}", watermark.opacity));
+        }
+        check_changed_file(watermark.image_path.as_deref(), old.watermark.image_path.as_deref(), "水印图片")?;
+        if !(1..=100).contains(&self.export.images.jpeg_quality) {
+            return Err(format!("JPEG 质量必须在 1 到 100 之间: {}", self.export.images.jpeg_quality));
+        }
+        check_changed_file(export.reference_docx.as_deref(), old.reference_docx.as_deref(), "参考文档")?;
+        let (fonts, old_fonts) = (&export.pdf_fonts, &old.pdf_fonts);
+        for (path, old_path) in [
+            (&fonts.fangsong, &old_fonts.fangsong),
+            (&fonts.heiti, &old_fonts.heiti),
+            (&fonts.kaiti, &old_fonts.kaiti),
+            (&fonts.songti, &old_fonts.songti),
+        ] {
+            check_changed_file(path.as_deref(), old_path.as_deref(), "字体文件")?;
+        }
+        check_changed_file(export.pdf_browser_path.as_deref(), old.pdf_browser_path.as_deref(), "浏览器程序")?;
+        if let Some(id) = self.keybindings.keys().find(|id| !crate::menu::is_configurable(id)) {
+            return Err(format!("未知的菜单项: {}", id));
+        }
+        let mut seen: HashMap<String, String> = HashMap::new();
+        for (id, accel) in crate::menu::effective_accelerators(&self.keybindings) {
+            if let Some(other) = seen.insert(accel.to_lowercase().replace(' ', ""), id.clone()) {
+                return Err(format!("快捷键 {} 同时绑定了 {} 和 {}", accel, other, id));
+            }
+        }
+        crate::logging::parse_level(&self.logging.level)?;
+        for level in self.logging.modules.values() {
+            crate::logging::parse_level(level)?;
+        }
+        if let Some(url) = self.updates.feed_url.as_deref().filter(|u| !u.trim().is_empty()) {
+            let parsed = reqwest::Url::parse(url).map_err(|_| format!("更新源地址无效: {}", url))?;
+            if !["http", "https"].contains(&parsed.scheme()) {
+                return Err(format!("更新源必须是 HTTP(S) 地址: {}", url));
+            }
+        }
+        if self.spellcheck.language.trim().is_empty() {
+            return Err("拼写检查语言不能为空".to_string());
+        }
+        if self.api_server.port < 1024 {
+            return Err(format!("本地 API 端口必须在 1024 到 65535 之间: {}", self.api_server.port));
+        }
+        if !["searxng", "bing", "tavily"].contains(&self.web_search.provider.as_str()) {
+            return Err(format!("不支持的搜索服务: {}", self.web_search.provider));
+        }
+        if !(1..=20).contains(&self.web_search.max_results) {
+            return Err(format!("搜索结果数必须在 1 到 20 之间: {}", self.web_search.max_results));
+        }
+        if let Some(url) = self.web_search.endpoint.as_deref().filter(|u| !u.trim().is_empty()) {
+            let parsed = reqwest::Url::parse(url).map_err(|_| format!("搜索服务地址无效: {}", url))?;
+            if !["http", "https"].contains(&parsed.scheme()) {
+                return Err(format!("搜索服务必须是 HTTP(S) 地址: {}", url));
+            }
+        }
+        if self.proxy.enabled {
+            let url = self.proxy.url.as_deref().unwrap_or_default();
+            let parsed = reqwest::Url::parse(url).map_err(|_| format!("代理地址无效: {}", url))?;
+            if !["http", "https", "socks5", "socks5h"].contains(&parsed.scheme()) {
+                return Err(format!("不支持的代理协议: {}", parsed.scheme()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 内存缓存，避免每次读取设置都访问磁盘
+static SETTINGS_CACHE: OnceLock<Mutex<Option<AppSettings>>> = OnceLock::new();
+
+fn cache() -> &'static Mutex<Option<AppSettings>> {
+    SETTINGS_CACHE.get_or_init(|| Mutex::new(None))
+}
+
+fn read_file(path: &Path) -> AppSettings {
+    if !path.exists() {
+        return AppSettings::default();
+    }
+    match fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            tracing::warn!("设置文件解析失败，使用默认值: {}", e);
+            AppSettings::default()
+        }),
+        Err(e) => {
+            tracing::warn!("读取设置失败: {}", e);
+            AppSettings::default()
+        }
+    }
+}
+
+/// 读取当前设置（首次读取时从磁盘加载）
+pub fn load(path: &Path) -> AppSettings {
+    let mut guard = cache().lock().unwrap_or_else(|e| e.into_inner());
+    guard.get_or_insert_with(|| read_file(path)).clone()
+}
+
+/// 以 JSON 片段深度合并更新设置，返回新设置及发生变化的顶层键
+pub fn update(path: &Path, patch: Value) -> Result<(AppSettings, Vec<String>), String> {
+    if !patch.is_object() {
+        return Err("设置更新必须是 JSON 对象".to_string());
+    }
+    let mut guard = cache().lock().map_err(|e| format!("锁获取失败: {}", e))?;
+    let current = guard.get_or_insert_with(|| read_file(path)).clone();
+
+    let old_value = serde_json::to_value(&current).map_err(|e| e.to_string())?;
+    let mut merged = old_value.clone();
+    merge(&mut merged, patch);
+    let mut updated: AppSettings =
+        serde_json::from_value(merged).map_err(|e| format!("设置格式错误: {}", e))?;
+    updated.validate(&current)?;
+
+    let new_value = serde_json::to_value(&updated).map_err(|e| e.to_string())?;
+    let changed: Vec<String> = new_value
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(k, v)| k.as_str() != "updatedAt" && old_value.get(k.as_str()) != Some(v))
+        .map(|(k, _)| k.clone())
+        .collect();
+    if changed.is_empty() {
+        return Ok((current, changed));
+    }
+
+    updated.updated_at = chrono::Utc::now().timestamp();
+    write_file(path, &updated)?;
+    *guard = Some(updated.clone());
+    Ok((updated, changed))
+}
+
+/// 恢复默认设置
+pub fn reset(path: &Path) -> Result<AppSettings, String> {
+    let mut guard = cache().lock().map_err(|e| format!("锁获取失败: {}", e))?;
+    let defaults = AppSettings { updated_at: chrono::Utc::now().timestamp(), ..AppSettings::default() };
+    write_file(path, &defaults)?;
+    *guard = Some(defaults.clone());
+    Ok(defaults)
+}
+
+fn write_file(path: &Path, settings: &AppSettings) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write settings: {}", e))
+}
+
+/// 对象逐层合并，其余类型（含数组）整体替换；值为 null 的键被删除（恢复默认）
+fn merge(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(t), Value::Object(p)) => {
+            for (k, v) in p {
+                if v.is_null() {
+                    t.remove(&k);
+                } else {
+                    merge(t.entry(k).or_insert(Value::Null), v);
+                }
+            }
+        }
+        (t, p) => *t = p,
+    }
+}
@@ -0,0 +1,114 @@
+// 相似文档检测：找出重复导入、复制后略作修改等产生的近似文档
+// 先比较规范化正文的哈希找出完全相同的文档，再按词片段（shingle）集合的 Jaccard 相似度找近似文档
+
+use crate::activity::is_cjk;
+use crate::document::Document;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+/// 每个片段包含的词数
+const SHINGLE_SIZE: usize = 3;
+/// 默认的相似度下限
+pub const DEFAULT_MIN_SIMILARITY: f32 = 0.5;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarDocument {
+    pub document_id: String,
+    pub title: String,
+    /// 0–1，完全相同为 1
+    pub similarity: f32,
+    /// 规范化后正文完全相同
+    pub identical: bool,
+    pub updated_at: i64,
+}
+
+/// 文档的比较指纹
+pub struct Fingerprint {
+    hash: [u8; 32],
+    shingles: HashSet<u64>,
+}
+
+impl Fingerprint {
+    /// 素材和正文一起参与比较
+    pub fn of(document: &Document) -> Self {
+        let tokens = tokenize(&format!("{}\n{}", document.content, document.ai_generated_content));
+        let hash = Sha256::digest(tokens.join(" ").as_bytes()).into();
+        let shingles = if tokens.len() < SHINGLE_SIZE {
+            // 过短的文档整体作为一个片段
+            HashSet::from_iter((!tokens.is_empty()).then(|| hash_of(&tokens)))
+        } else {
+            tokens.windows(SHINGLE_SIZE).map(hash_of).collect()
+        };
+        Fingerprint { hash, shingles }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shingles.is_empty()
+    }
+
+    /// 返回 (相似度, 是否完全相同)
+    pub fn compare(&self, other: &Fingerprint) -> (f32, bool) {
+        if self.hash == other.hash {
+            return (1.0, true);
+        }
+        let common = self.shingles.intersection(&other.shingles).count();
+        let union = self.shingles.len() + other.shingles.len() - common;
+        if union == 0 { (0.0, false) } else { (common as f32 / union as f32, false) }
+    }
+}
+
+/// 规范化分词：中日韩字符每字一个词，其余按字母数字连续段切分并转小写，标点和空白忽略
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    for c in text.chars() {
+        if is_cjk(c) || !c.is_alphanumeric() {
+            if !word.is_empty() {
+                tokens.push(std::mem::take(&mut word));
+            }
+            if is_cjk(c) {
+                tokens.push(c.to_string());
+            }
+        } else {
+            word.extend(c.to_lowercase());
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(word);
+    }
+    tokens
+}
+
+fn hash_of(tokens: &[String]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    tokens.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 与目标文档相似的文档，按相似度从高到低排列
+pub fn find_similar(target: &Document, others: &[Document], min_similarity: f32) -> Vec<SimilarDocument> {
+    let fingerprint = Fingerprint::of(target);
+    if fingerprint.is_empty() {
+        return Vec::new();
+    }
+    let mut similar: Vec<SimilarDocument> = others
+        .iter()
+        .filter(|d| d.id != target.id)
+        .filter_map(|d| {
+            let (similarity, identical) = fingerprint.compare(&Fingerprint::of(d));
+            (similarity >= min_similarity).then(|| SimilarDocument {
+                document_id: d.id.clone(),
+                title: d.title.clone(),
+                similarity,
+                identical,
+                updated_at: d.metadata.updated_at,
+            })
+        })
+        .collect();
+    similar.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    similar
+}
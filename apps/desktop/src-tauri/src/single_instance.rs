@@ -0,0 +1,93 @@
+// 单实例：应用已在运行时，新启动的进程把启动参数中的 aidocplus:// 链接转交给已有实例后退出，
+// 避免重复启动邮件 / 脚本定时任务和本地 API。
+// 首个实例占用固定的本机端口作为互斥标记，并在该端口上接收转交的链接
+
+use crate::deep_link;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::AppHandle;
+
+const PORT: u16 = 27180;
+
+/// 握手标记，用于区分占用该端口的其他程序
+const MAGIC: &str = "AIDOCPLUS-INSTANCE/1";
+
+const TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 首个实例在启动早期占用的端口，setup 中交给 listen
+static LISTENER: Mutex<Option<TcpListener>> = Mutex::new(None);
+
+/// 检查是否已有实例在运行：有则转交启动参数中的链接并返回 true，当前进程应直接退出
+pub fn forward_to_running() -> bool {
+    match TcpListener::bind((Ipv4Addr::LOCALHOST, PORT)) {
+        Ok(listener) => {
+            if let Ok(mut slot) = LISTENER.lock() {
+                *slot = Some(listener);
+            }
+            false
+        }
+        Err(_) => match forward(&deep_link::links_from_args()) {
+            Ok(()) => true,
+            // 端口被其他程序占用：按普通方式启动
+            Err(e) => {
+                eprintln!("无法连接已运行的实例，继续启动: {}", e);
+                false
+            }
+        },
+    }
+}
+
+fn forward(links: &[String]) -> Result<(), String> {
+    let mut stream = TcpStream::connect_timeout(&(Ipv4Addr::LOCALHOST, PORT).into(), TIMEOUT).map_err(|e| e.to_string())?;
+    stream.set_read_timeout(Some(TIMEOUT)).map_err(|e| e.to_string())?;
+    let mut message = format!("{}\n", MAGIC);
+    for link in links {
+        message.push_str(link.trim());
+        message.push('\n');
+    }
+    stream.write_all(message.as_bytes()).map_err(|e| e.to_string())?;
+    stream.shutdown(std::net::Shutdown::Write).map_err(|e| e.to_string())?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply).map_err(|e| e.to_string())?;
+    if reply.trim() != MAGIC {
+        return Err("端口被其他程序占用".to_string());
+    }
+    Ok(())
+}
+
+/// 接收后续启动的进程转交的链接（没有链接时只显示主窗口）
+pub fn listen(app: &AppHandle) {
+    let Some(listener) = LISTENER.lock().ok().and_then(|mut slot| slot.take()) else { return };
+    let app = app.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            match receive(stream) {
+                Ok(links) if links.is_empty() => crate::tray::show_main_window(&app),
+                Ok(links) => {
+                    for link in links {
+                        deep_link::handle(&app, &link);
+                    }
+                }
+                Err(e) => tracing::warn!("接收转交的链接失败: {}", e),
+            }
+        }
+    });
+}
+
+fn receive(mut stream: TcpStream) -> Result<Vec<String>, String> {
+    stream.set_read_timeout(Some(TIMEOUT)).map_err(|e| e.to_string())?;
+    let mut lines = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?).lines();
+    match lines.next() {
+        Some(Ok(line)) if line == MAGIC => {}
+        _ => return Err("无效的请求".to_string()),
+    }
+    let links = lines
+        .map_while(Result::ok)
+        .filter(|l| l.starts_with(&format!("{}:", deep_link::SCHEME)))
+        .collect();
+    stream.write_all(format!("{}\n", MAGIC).as_bytes()).map_err(|e| e.to_string())?;
+    Ok(links)
+}
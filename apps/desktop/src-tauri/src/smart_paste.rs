@@ -0,0 +1,286 @@
+// 智能粘贴：把剪贴板中的 HTML（Word、网页、公众号）或 RTF 转为干净的 Markdown，
+// 引用的图片（data: URI、本地文件、网络地址）保存为文档附件
+
+use crate::clipboard_html::{self, RichFormat};
+use crate::document::{Attachment, Document};
+use crate::email_images::load_image_source;
+use crate::html_markdown::html_to_markdown;
+use regex::Regex;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 单张网络图片的大小上限
+const MAX_REMOTE_IMAGE_BYTES: usize = 20 * 1024 * 1024;
+/// 单次粘贴最多处理的图片数
+const MAX_IMAGES: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PasteSource {
+    Html,
+    Rtf,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmartPaste {
+    pub markdown: String,
+    pub source: PasteSource,
+    /// 保存为附件的图片
+    pub attachments: Vec<Attachment>,
+    /// 无法下载或识别的图片地址，Markdown 中保留原引用
+    pub failed_images: Vec<String>,
+}
+
+/// 读取剪贴板并转为 Markdown（优先 HTML，其次 RTF）
+pub fn read_clipboard() -> Result<(PasteSource, String), String> {
+    if let Some(html) = clipboard_html::read_rich(RichFormat::Html) {
+        return Ok((PasteSource::Html, html_to_markdown(&html)));
+    }
+    if let Some(rtf) = clipboard_html::read_rich(RichFormat::Rtf) {
+        return Ok((PasteSource::Rtf, rtf_to_markdown(&rtf)));
+    }
+    Err("剪贴板中没有 HTML 或 RTF 内容".to_string())
+}
+
+/// Markdown 中引用的图片地址（去重，按出现顺序）
+pub fn image_sources(markdown: &str) -> Vec<String> {
+    let mut sources: Vec<String> = Vec::new();
+    for caps in image_re().captures_iter(markdown) {
+        let src = caps[2].to_string();
+        if !sources.contains(&src) {
+            sources.push(src);
+        }
+    }
+    sources.truncate(MAX_IMAGES);
+    sources
+}
+
+fn image_re() -> Regex {
+    Regex::new(r"!\[([^\]\n]*)\]\(([^)\s]+)\)").unwrap()
+}
+
+/// 读取图片数据：data: URI 和本地文件直接读取，网络图片下载
+pub async fn fetch_image(src: &str) -> Option<Vec<u8>> {
+    if !src.starts_with("http://") && !src.starts_with("https://") {
+        return load_image_source(src).map(|(data, _)| data);
+    }
+    let resp = reqwest::Client::new()
+        .get(src)
+        .timeout(Duration::from_secs(20))
+        .send()
+        .await
+        .ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    let bytes = resp.bytes().await.ok()?;
+    (bytes.len() <= MAX_REMOTE_IMAGE_BYTES && image::guess_format(&bytes).is_ok()).then(|| bytes.to_vec())
+}
+
+/// 将取到的图片保存到 attachments/{文档ID}/ 并登记为附件，Markdown 中的地址替换为本地路径
+pub fn save_images(
+    project_dir: &Path,
+    doc_path: &PathBuf,
+    markdown: &str,
+    images: Vec<(String, Vec<u8>)>,
+) -> Result<(String, Vec<Attachment>), String> {
+    if images.is_empty() {
+        return Ok((markdown.to_string(), Vec::new()));
+    }
+    let mut document = Document::load(doc_path).map_err(|e| e.to_string())?;
+    let dir = project_dir.join("attachments").join(&document.id);
+    fs::create_dir_all(&dir).map_err(|e| format!("创建附件目录失败: {}", e))?;
+
+    let stem = format!("paste-{}", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+    let mut saved: Vec<(String, String)> = Vec::new();
+    let mut attachments = Vec::new();
+    for (src, data) in images {
+        let ext = image::guess_format(&data)
+            .ok()
+            .and_then(|f| f.extensions_str().first().copied())
+            .unwrap_or("png");
+        let path = (1..)
+            .map(|i| dir.join(format!("{}-{}.{}", stem, i, ext)))
+            .find(|p| !p.exists())
+            .unwrap_or_else(|| dir.join(format!("{}.{}", stem, ext)));
+        fs::write(&path, &data).map_err(|e| format!("保存图片失败: {}", e))?;
+
+        let file_path = path.to_string_lossy().to_string();
+        let attachment = Attachment {
+            id: uuid::Uuid::new_v4().to_string(),
+            file_name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            file_path: file_path.clone(),
+            file_size: data.len() as u64,
+            file_type: ext.to_string(),
+            added_at: chrono::Utc::now().timestamp(),
+        };
+        document.attachments.push(attachment.clone());
+        attachments.push(attachment);
+        saved.push((src, file_path.replace('\\', "/")));
+    }
+    document.metadata.updated_at = chrono::Utc::now().timestamp();
+    document.save(doc_path).map_err(|e| e.to_string())?;
+
+    // 尖括号包裹路径，兼容含空格的路径
+    let markdown = image_re()
+        .replace_all(markdown, |caps: &regex::Captures| match saved.iter().find(|(src, _)| *src == caps[2]) {
+            Some((_, path)) => format!("![{}](<{}>)", &caps[1], path),
+            None => caps[0].to_string(),
+        })
+        .into_owned();
+    Ok((markdown, attachments))
+}
+
+// ============================================================
+// RTF
+// ============================================================
+
+/// 内容整体忽略的 RTF 目标组
+const RTF_SKIP_DESTINATIONS: &[&str] = &[
+    "fonttbl", "colortbl", "stylesheet", "info", "pict", "header", "footer", "headerl", "headerr", "footerl",
+    "footerr", "listtable", "listoverridetable", "generator", "themedata", "colorschememapping", "latentstyles",
+    "datastore", "xmlnsdecl", "rsidtbl", "mmathPr", "pgdsctbl", "fldinst", "object", "shppict", "nonshppict",
+];
+
+/// RTF 转为按段落分隔的纯文本 Markdown（只保留文字，不保留格式）
+pub fn rtf_to_markdown(rtf: &str) -> String {
+    let text = rtf_to_text(rtf);
+    text.lines().map(str::trim).filter(|l| !l.is_empty()).collect::<Vec<_>>().join("\n\n")
+}
+
+fn rtf_to_text(rtf: &str) -> String {
+    let chars: Vec<char> = rtf.chars().collect();
+    let mut out = String::new();
+    // 以 \'hh 表示的字节按文档代码页解码，连续的字节一起解码（GBK 等多字节编码）
+    let mut pending: Vec<u8> = Vec::new();
+    let mut encoding = encoding_rs::WINDOWS_1252;
+    // 每层分组：(是否忽略, \uc 值)
+    let mut stack: Vec<(bool, usize)> = Vec::new();
+    let (mut skip, mut uc) = (false, 1usize);
+    // \uN 之后需要跳过的替代字符数
+    let mut fallback = 0usize;
+    let mut i = 0;
+
+    let flush = |pending: &mut Vec<u8>, out: &mut String, encoding: &'static encoding_rs::Encoding| {
+        if !pending.is_empty() {
+            out.push_str(&encoding.decode(pending).0);
+            pending.clear();
+        }
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '{' => {
+                stack.push((skip, uc));
+                i += 1;
+            }
+            '}' => {
+                flush(&mut pending, &mut out, encoding);
+                (skip, uc) = stack.pop().unwrap_or((false, 1));
+                i += 1;
+            }
+            '\\' => {
+                let Some(&next) = chars.get(i + 1) else { break };
+                if next == '\'' {
+                    let hex: String = chars.iter().skip(i + 2).take(2).collect();
+                    i += 4;
+                    if fallback > 0 {
+                        fallback -= 1;
+                    } else if let (false, Ok(b)) = (skip, u8::from_str_radix(&hex, 16)) {
+                        pending.push(b);
+                    }
+                    continue;
+                }
+                flush(&mut pending, &mut out, encoding);
+                if !next.is_ascii_alphabetic() {
+                    i += 2;
+                    match next {
+                        '*' => skip = true,
+                        '\\' | '{' | '}' if !skip => out.push(next),
+                        '~' if !skip => out.push('\u{a0}'),
+                        '_' if !skip => out.push('-'),
+                        '\n' | '\r' if !skip => out.push('\n'),
+                        _ => {}
+                    }
+                    continue;
+                }
+                let start = i + 1;
+                let mut end = start;
+                while end < chars.len() && chars[end].is_ascii_alphabetic() {
+                    end += 1;
+                }
+                let word: String = chars[start..end].iter().collect();
+                let mut param_end = end;
+                if param_end < chars.len() && chars[param_end] == '-' {
+                    param_end += 1;
+                }
+                while param_end < chars.len() && chars[param_end].is_ascii_digit() {
+                    param_end += 1;
+                }
+                let param: Option<i32> = chars[end..param_end].iter().collect::<String>().parse().ok();
+                i = param_end;
+                if chars.get(i) == Some(&' ') {
+                    i += 1;
+                }
+
+                match word.as_str() {
+                    w if RTF_SKIP_DESTINATIONS.contains(&w) => skip = true,
+                    "ansicpg" => {
+                        if let Some(enc) = param.and_then(codepage_encoding) {
+                            encoding = enc;
+                        }
+                    }
+                    "uc" => uc = param.unwrap_or(1).max(0) as usize,
+                    _ if skip => {}
+                    "u" => {
+                        if let Some(code) = param {
+                            let code = if code < 0 { code + 65536 } else { code };
+                            if let Some(ch) = char::from_u32(code as u32) {
+                                out.push(ch);
+                            }
+                            fallback = uc;
+                        }
+                    }
+                    "par" | "line" | "sect" | "row" => out.push('\n'),
+                    "tab" | "cell" => out.push('\t'),
+                    "emdash" => out.push('—'),
+                    "endash" => out.push('–'),
+                    "bullet" => out.push('•'),
+                    "lquote" => out.push('‘'),
+                    "rquote" => out.push('’'),
+                    "ldblquote" => out.push('“'),
+                    "rdblquote" => out.push('”'),
+                    _ => {}
+                }
+            }
+            '\r' | '\n' => i += 1,
+            _ => {
+                flush(&mut pending, &mut out, encoding);
+                i += 1;
+                if fallback > 0 {
+                    fallback -= 1;
+                } else if !skip {
+                    out.push(c);
+                }
+            }
+        }
+    }
+    flush(&mut pending, &mut out, encoding);
+    out
+}
+
+fn codepage_encoding(codepage: i32) -> Option<&'static encoding_rs::Encoding> {
+    let label = match codepage {
+        936 => "gbk".to_string(),
+        950 => "big5".to_string(),
+        932 => "shift_jis".to_string(),
+        949 => "euc-kr".to_string(),
+        65001 => "utf-8".to_string(),
+        cp => format!("windows-{}", cp),
+    };
+    encoding_rs::Encoding::for_label(label.as_bytes())
+}
@@ -0,0 +1,230 @@
+// 拼写检查
+// 用 spellbook（纯 Rust 的 Hunspell 兼容实现）加载 .aff + .dic 词典，按需检查词形（支持复合词），不预先展开词表；
+// 词典来源：用户词典目录、系统 hunspell / myspell 目录
+// 自定义词表：全局一份（配置目录），每个项目一份（项目目录下 dictionary.txt）
+
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// 每个拼写错误最多给出的建议数
+const MAX_SUGGESTIONS: usize = 6;
+
+/// 项目自定义词表文件名
+pub const PROJECT_WORDS_FILE: &str = "dictionary.txt";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpellingIssue {
+    /// 起止位置（UTF-16 偏移，与编辑器一致）
+    pub from: usize,
+    pub to: usize,
+    pub word: String,
+    pub suggestions: Vec<String>,
+}
+
+/// 已加载的词典
+pub struct Dictionary {
+    inner: spellbook::Dictionary,
+}
+
+static CACHE: OnceLock<Mutex<HashMap<String, Arc<Dictionary>>>> = OnceLock::new();
+
+/// 词典搜索目录：用户目录优先，其次系统目录
+pub fn search_dirs(user_dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![user_dir.to_path_buf()];
+    if let Some(home) = dirs::home_dir() {
+        dirs.push(home.join("Library/Spelling"));
+        dirs.push(home.join(".local/share/hunspell"));
+    }
+    for dir in ["/Library/Spelling", "/usr/share/hunspell", "/usr/share/myspell", "/usr/share/myspell/dicts"] {
+        dirs.push(PathBuf::from(dir));
+    }
+    dirs
+}
+
+/// 可用的词典语言（同时存在 .aff 与 .dic）
+pub fn available_languages(user_dir: &Path) -> Vec<String> {
+    let mut langs: Vec<String> = search_dirs(user_dir)
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten().map(|e| e.path()))
+        .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("dic") && p.with_extension("aff").exists())
+        .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .collect();
+    langs.sort();
+    langs.dedup();
+    langs
+}
+
+/// 加载词典（带缓存）
+pub fn load(user_dir: &Path, language: &str) -> Result<Arc<Dictionary>, String> {
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(dict) = cache.lock().map_err(|e| e.to_string())?.get(language) {
+        return Ok(dict.clone());
+    }
+    let dic_path = search_dirs(user_dir)
+        .into_iter()
+        .map(|dir| dir.join(format!("{}.dic", language)))
+        .find(|p| p.exists() && p.with_extension("aff").exists())
+        .ok_or_else(|| format!("找不到拼写词典: {}（请将 {}.aff / {}.dic 放入 {}）", language, language, language, user_dir.display()))?;
+    let dict = Arc::new(Dictionary::parse(&dic_path.with_extension("aff"), &dic_path)?);
+    tracing::info!("已加载拼写词典 {}", language);
+    cache.lock().map_err(|e| e.to_string())?.insert(language.to_string(), dict.clone());
+    Ok(dict)
+}
+
+/// 按词典声明的编码读取文件（UTF-8 或 ISO8859-1 系）
+fn read_text(path: &Path, encoding: &str) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|e| format!("读取 {} 失败: {}", path.display(), e))?;
+    if encoding.to_uppercase().starts_with("ISO8859") || encoding.to_uppercase().starts_with("ISO-8859") {
+        Ok(bytes.iter().map(|&b| b as char).collect())
+    } else {
+        Ok(String::from_utf8_lossy(&bytes).trim_start_matches('\u{feff}').to_string())
+    }
+}
+
+impl Dictionary {
+    pub fn parse(aff_path: &Path, dic_path: &Path) -> Result<Self, String> {
+        let encoding = fs::read(aff_path)
+            .ok()
+            .and_then(|b| {
+                String::from_utf8_lossy(&b)
+                    .lines()
+                    .find_map(|l| l.strip_prefix("SET ").map(|s| s.trim().to_string()))
+            })
+            .unwrap_or_else(|| "UTF-8".to_string());
+        let aff = read_text(aff_path, &encoding)?;
+        let dic = read_text(dic_path, &encoding)?;
+        let inner = spellbook::Dictionary::new(&aff, &dic)
+            .map_err(|e| format!("解析拼写词典 {} 失败: {}", dic_path.display(), e))?;
+        Ok(Self { inner })
+    }
+
+    /// 判断单词是否正确（大小写变体、复合词由词典规则处理）
+    pub fn check(&self, word: &str) -> bool {
+        self.inner.check(word)
+    }
+
+    /// 拼写建议，最多 MAX_SUGGESTIONS 个
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let mut out = Vec::new();
+        self.inner.suggest(word, &mut out);
+        out.truncate(MAX_SUGGESTIONS);
+        out
+    }
+}
+
+fn skip_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(concat!(
+            r"(?ms)^(?:```|~~~).*?^(?:```|~~~)[^\n]*$",
+            r"|`[^`\n]+`",
+            r"|\]\([^)\n]*\)",
+            r"|<[^>\n]+>",
+            r"|[A-Za-z][A-Za-z0-9+.-]*://\S+",
+            r"|\S+@\S+\.\S+",
+        ))
+        .expect("invalid skip regex")
+    })
+}
+
+fn word_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"[\p{L}\p{M}]+(?:['’][\p{L}\p{M}]+)*").expect("invalid word regex"))
+}
+
+fn cjk_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"[\p{Han}\p{Hiragana}\p{Katakana}\p{Hangul}]").expect("invalid cjk regex"))
+}
+
+/// 检查 Markdown 文本：跳过代码、链接地址、HTML 标签、网址；中日韩文字不参与检查
+pub fn check_text(dict: &Dictionary, text: &str, custom: &HashSet<String>) -> Vec<SpellingIssue> {
+    let skipped: Vec<(usize, usize)> = skip_regex().find_iter(text).map(|m| (m.start(), m.end())).collect();
+    let mut issues = Vec::new();
+    let mut suggestions_cache: HashMap<String, Vec<String>> = HashMap::new();
+    let (mut byte_pos, mut utf16_pos) = (0, 0);
+
+    for m in word_regex().find_iter(text) {
+        if skipped.iter().any(|&(s, e)| m.start() < e && m.end() > s) {
+            continue;
+        }
+        let word = m.as_str().trim_end_matches(['\'', '’']);
+        let before = text[..m.start()].chars().next_back();
+        let after = text[m.end()..].chars().next();
+        let glued = |c: Option<char>| c.is_some_and(|c| c.is_ascii_digit() || c == '_');
+        let is_acronym = word.chars().count() > 1 && word.chars().all(|c| !c.is_lowercase());
+        if word.chars().count() < 2
+            || glued(before)
+            || glued(after)
+            || is_acronym
+            || cjk_regex().is_match(word)
+            || custom.contains(word)
+            || custom.contains(&word.to_lowercase())
+            || dict.check(word)
+            || dict.check(&word.replace('’', "'"))
+        {
+            continue;
+        }
+
+        utf16_pos += text[byte_pos..m.start()].encode_utf16().count();
+        byte_pos = m.start();
+        let from = utf16_pos;
+        let to = from + word.encode_utf16().count();
+        let suggestions = suggestions_cache
+            .entry(word.to_string())
+            .or_insert_with(|| dict.suggest(word))
+            .clone();
+        issues.push(SpellingIssue { from, to, word: word.to_string(), suggestions });
+    }
+    issues
+}
+
+/// 读取自定义词表（每行一个词，# 开头为注释）
+pub fn load_words(path: &Path) -> HashSet<String> {
+    fs::read_to_string(path)
+        .map(|s| {
+            s.lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 写回自定义词表（排序去重）
+fn save_words(path: &Path, words: &HashSet<String>) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut list: Vec<&String> = words.iter().collect();
+    list.sort();
+    let content: String = list.iter().map(|w| format!("{}\n", w)).collect();
+    fs::write(path, content).map_err(|e| format!("保存词表失败: {}", e))
+}
+
+pub fn add_word(path: &Path, word: &str) -> Result<(), String> {
+    let word = word.trim();
+    if word.is_empty() || word.contains(char::is_whitespace) {
+        return Err(format!("无效的单词: {}", word));
+    }
+    let mut words = load_words(path);
+    if words.insert(word.to_string()) {
+        save_words(path, &words)?;
+    }
+    Ok(())
+}
+
+pub fn remove_word(path: &Path, word: &str) -> Result<(), String> {
+    let mut words = load_words(path);
+    if words.remove(word.trim()) {
+        save_words(path, &words)?;
+    }
+    Ok(())
+}
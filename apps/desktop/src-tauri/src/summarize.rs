@@ -0,0 +1,104 @@
+// 文档摘要：长文档按段落切块，先逐块提炼要点（map），再合并为最终摘要（reduce）
+// 块大小按模型上下文长度估算，合并后的要点仍然过长时再分组合并一轮
+
+use crate::ai::ChatMessage;
+use crate::context_window;
+
+/// 单块的 token 上限（上下文很大的模型也不宜一次发送过长的内容）
+const MAX_CHUNK_TOKENS: usize = 12_000;
+/// 单块不少于此 token 数，避免小上下文模型切出过多的块
+const MIN_CHUNK_TOKENS: usize = 1_000;
+
+/// 按模型上下文长度确定块大小：留出系统提示和输出的空间
+pub fn chunk_tokens(model: &str) -> usize {
+    (context_window::window_for(model) / 3).clamp(MIN_CHUNK_TOKENS, MAX_CHUNK_TOKENS)
+}
+
+/// 按段落切块；单个段落过长时按行切分，仍然过长时按字符硬切
+pub fn split_chunks(text: &str, max_tokens: usize) -> Vec<String> {
+    let mut pieces: Vec<String> = Vec::new();
+    for paragraph in text.split("\n\n").filter(|p| !p.trim().is_empty()) {
+        if context_window::estimate_tokens(paragraph) <= max_tokens {
+            pieces.push(paragraph.to_string());
+            continue;
+        }
+        for line in paragraph.lines() {
+            if context_window::estimate_tokens(line) <= max_tokens {
+                pieces.push(line.to_string());
+                continue;
+            }
+            // 中文约 1 token/字，按字符数切分偏保守
+            let chars: Vec<char> = line.chars().collect();
+            pieces.extend(chars.chunks(max_tokens).map(|c| c.iter().collect::<String>()));
+        }
+    }
+
+    let mut chunks: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut current_tokens = 0;
+    for piece in pieces {
+        let tokens = context_window::estimate_tokens(&piece);
+        if current_tokens + tokens > max_tokens && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            current_tokens = 0;
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(&piece);
+        current_tokens += tokens;
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// 摘要篇幅要求：short / medium / long
+fn length_rule(length: &str) -> &'static str {
+    match length {
+        "short" => "篇幅控制在 150 字以内",
+        "long" => "篇幅约 800–1200 字，保留重要的细节和数据",
+        _ => "篇幅约 300–500 字",
+    }
+}
+
+/// 摘要形式：bullets 要点列表 / paragraph 连贯段落 / executive 结论先行；其他值作为自定义要求
+fn style_rule(style: &str) -> String {
+    match style {
+        "" | "bullets" => "使用 Markdown 要点列表，每条一句话".to_string(),
+        "paragraph" => "使用连贯的段落，不要使用列表".to_string(),
+        "executive" => "先用一段话给出核心结论，再用要点列出关键依据和待办事项".to_string(),
+        custom => custom.to_string(),
+    }
+}
+
+fn messages(system: String, user: String) -> Vec<ChatMessage> {
+    vec![
+        ChatMessage { role: "system".to_string(), content: system, images: Vec::new() },
+        ChatMessage { role: "user".to_string(), content: user, images: Vec::new() },
+    ]
+}
+
+/// map 阶段：提炼单块的要点
+pub fn chunk_messages(title: &str, chunk: &str, index: usize, total: usize) -> Vec<ChatMessage> {
+    messages(
+        "你是文档摘要助手。提炼给定片段中的关键信息（观点、结论、数据、人物和时间），\
+         使用简洁的 Markdown 要点列表输出，不要添加片段中没有的内容，不要输出开场白。"
+            .to_string(),
+        format!("文档《{}》第 {}/{} 部分：\n\n{}", title, index + 1, total, chunk),
+    )
+}
+
+/// reduce 阶段：由全文或各部分要点生成最终摘要
+pub fn final_messages(title: &str, source: &str, partial: bool, length: &str, style: &str) -> Vec<ChatMessage> {
+    let source_desc = if partial { "以下是按顺序提炼的各部分要点" } else { "以下是文档全文" };
+    messages(
+        format!(
+            "你是文档摘要助手。为文档撰写摘要：{}；{}。只输出摘要本身（Markdown），不要输出标题和开场白。",
+            length_rule(length),
+            style_rule(style)
+        ),
+        format!("文档《{}》。{}：\n\n{}", title, source_desc, source),
+    )
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// 模板 Manifest — 轻量元数据，用于列表展示
@@ -33,6 +34,9 @@ pub struct TemplateManifest {
     pub plugin_data: Option<serde_json::Value>,
     #[serde(default, skip_serializing_if = "Option::is_none", rename = "minAppVersion")]
     pub min_app_version: Option<String>,
+    /// 默认提示词模板（prompt-template 资源 ID），从模板创建文档时用于填充提示词和系统提示词
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "promptTemplateId")]
+    pub prompt_template_id: Option<String>,
 }
 
 /// 模板内容 — 按需加载
@@ -62,7 +66,7 @@ pub fn get_templates_dir() -> PathBuf {
 pub fn ensure_templates_dir() {
     let templates_dir = get_templates_dir();
     if let Err(e) = fs::create_dir_all(&templates_dir) {
-        eprintln!("Failed to create templates directory: {}", e);
+        tracing::warn!("Failed to create templates directory: {}", e);
     }
 }
 
@@ -90,9 +94,9 @@ pub fn list_templates() -> Vec<TemplateManifest> {
                             seen_ids.insert(manifest.id.clone());
                             templates.push(manifest);
                         }
-                        Err(e) => eprintln!("Failed to parse template manifest {:?}: {}", manifest_path, e),
+                        Err(e) => tracing::warn!("Failed to parse template manifest {:?}: {}", manifest_path, e),
                     },
-                    Err(e) => eprintln!("Failed to read template manifest {:?}: {}", manifest_path, e),
+                    Err(e) => tracing::warn!("Failed to read template manifest {:?}: {}", manifest_path, e),
                 }
             }
         }
@@ -157,6 +161,7 @@ fn scan_manifests_recursive(dir: &std::path::Path, templates: &mut Vec<TemplateM
                             enabled_plugins: Vec::new(),
                             plugin_data: None,
                             min_app_version: None,
+                            prompt_template_id: value.get("promptTemplateId").and_then(|v| v.as_str()).map(String::from),
                         };
                         if !manifest.id.is_empty() {
                             templates.push(manifest);
@@ -231,6 +236,29 @@ fn find_content_recursive(dir: &std::path::Path, template_id: &str) -> Option<Te
     None
 }
 
+fn variable_regex() -> &'static regex::Regex {
+    static RE: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    RE.get_or_init(|| regex::Regex::new(r"\{\{\s*([\w.\-\p{Han}]+)\s*\}\}").unwrap())
+}
+
+/// 替换文本中的 {{变量}} 占位符；未提供的变量替换为空字符串
+pub fn render_variables(text: &str, variables: &HashMap<String, String>) -> String {
+    variable_regex()
+        .replace_all(text, |c: &regex::Captures| variables.get(&c[1]).cloned().unwrap_or_default())
+        .into_owned()
+}
+
+/// 文本中出现的变量名（去重，保持出现顺序）
+pub fn placeholders(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for c in variable_regex().captures_iter(text) {
+        if !names.iter().any(|n| n == &c[1]) {
+            names.push(c[1].to_string());
+        }
+    }
+    names
+}
+
 /// 创建模板（写入 manifest 和 content）
 pub fn create_template(manifest: TemplateManifest, content: TemplateContent) -> Result<TemplateManifest, String> {
     let templates_dir = get_templates_dir();
@@ -306,6 +334,124 @@ pub fn update_template(
     Ok(manifest)
 }
 
+/// 设置模板的默认提示词模板（None 表示改用分类默认值）
+pub fn set_template_prompt_template(template_id: &str, prompt_template_id: Option<String>) -> Result<TemplateManifest, String> {
+    let manifest_path = get_templates_dir().join(template_id).join("template.json");
+    if !manifest_path.exists() {
+        return Err(format!("Template not found: {}", template_id));
+    }
+    let json = fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("Failed to read template manifest: {}", e))?;
+    let mut manifest: TemplateManifest = serde_json::from_str(&json)
+        .map_err(|e| format!("Failed to parse template manifest: {}", e))?;
+
+    manifest.prompt_template_id = prompt_template_id.filter(|id| !id.trim().is_empty());
+    manifest.updated_at = chrono::Utc::now().timestamp();
+
+    let manifest_json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize template manifest: {}", e))?;
+    fs::write(&manifest_path, manifest_json)
+        .map_err(|e| format!("Failed to write template manifest: {}", e))?;
+    Ok(manifest)
+}
+
+/// 提示词模板为新文档提供的初始内容
+#[derive(Debug, Clone, Default)]
+pub struct PromptSeed {
+    pub author_notes: String,
+    pub system_prompt: Option<String>,
+}
+
+/// prompt-template 资源中声明的变量
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptVariable {
+    pub name: String,
+    #[serde(default)]
+    pub default: Option<String>,
+    /// 必填变量的值不能为空
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// 渲染后的提示词
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderedPrompt {
+    pub prompt: String,
+    pub system_prompt: Option<String>,
+}
+
+/// 解析 prompt-template 资源：(manifest, 提示词, 系统提示词, 声明的变量)
+fn parse_prompt_resource(
+    manifest_json: &str,
+    data_dir: &std::path::Path,
+) -> Option<(String, Option<String>, Vec<PromptVariable>)> {
+    let manifest: serde_json::Value = serde_json::from_str(manifest_json).ok()?;
+    let text = |key: &str| manifest.get(key).and_then(|v| v.as_str()).map(str::to_string).filter(|s| !s.trim().is_empty());
+    let read_file = |name: &str| fs::read_to_string(data_dir.join(name)).ok().filter(|s| !s.trim().is_empty());
+    let content = text("content").or_else(|| text("prompt")).or_else(|| read_file("content.md"))?;
+    let system_prompt = text("systemPrompt").or_else(|| read_file("system.md"));
+    let variables = manifest
+        .get("variables")
+        .and_then(|v| v.as_array())
+        .map(|vars| vars.iter().filter_map(|v| serde_json::from_value(v.clone()).ok()).collect())
+        .unwrap_or_default();
+    Some((content, system_prompt, variables))
+}
+
+/// 从 prompt-template 资源（资源索引中的完整 manifest 及其数据目录）解析提示词；
+/// 变量按 manifest 中声明的默认值替换
+pub fn prompt_seed_from_resource(manifest_json: &str, data_dir: &std::path::Path) -> Option<PromptSeed> {
+    let (content, system_prompt, variables) = parse_prompt_resource(manifest_json, data_dir)?;
+    let defaults: HashMap<String, String> = variables
+        .into_iter()
+        .map(|v| (v.name, v.default.unwrap_or_default()))
+        .collect();
+    // 没有声明变量时保留占位符，由用户自行填写
+    let render = |text: String| if defaults.is_empty() { text } else { render_variables(&text, &defaults) };
+
+    Some(PromptSeed {
+        author_notes: render(content),
+        system_prompt: system_prompt.map(render),
+    })
+}
+
+/// 渲染 prompt-template 资源：传入的变量优先，其次声明的默认值；
+/// 有占位符没有取值（或必填变量为空）时返回错误，列出缺少的变量
+pub fn render_prompt_resource(
+    manifest_json: &str,
+    data_dir: &std::path::Path,
+    variables: &HashMap<String, String>,
+) -> Result<RenderedPrompt, String> {
+    let (content, system_prompt, declared) =
+        parse_prompt_resource(manifest_json, data_dir).ok_or_else(|| "提示词模板内容为空".to_string())?;
+    let mut values: HashMap<String, String> = declared
+        .iter()
+        .filter_map(|v| Some((v.name.clone(), v.default.clone()?)))
+        .collect();
+    values.extend(variables.iter().filter(|(_, v)| !v.is_empty()).map(|(k, v)| (k.clone(), v.clone())));
+
+    let used = placeholders(&content).into_iter().chain(system_prompt.iter().flat_map(|s| placeholders(s)));
+    let mut missing: Vec<String> = used.filter(|name| !values.contains_key(name)).collect();
+    missing.extend(
+        declared
+            .iter()
+            .filter(|v| v.required && values.get(&v.name).is_none_or(|value| value.trim().is_empty()))
+            .map(|v| v.name.clone()),
+    );
+    missing.sort();
+    missing.dedup();
+    if !missing.is_empty() {
+        return Err(format!("提示词模板缺少变量: {}", missing.join(", ")));
+    }
+
+    Ok(RenderedPrompt {
+        prompt: render_variables(&content, &values),
+        system_prompt: system_prompt.map(|s| render_variables(&s, &values)),
+    })
+}
+
 /// 删除模板
 pub fn delete_template(template_id: &str) -> Result<(), String> {
     let templates_dir = get_templates_dir();
@@ -377,6 +523,9 @@ pub struct TemplateCategory {
     pub order: i32,
     #[serde(rename = "type", default = "default_category_type")]
     pub category_type: String,
+    /// 该分类模板的默认提示词模板，模板自身未指定时使用
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "promptTemplateId")]
+    pub prompt_template_id: Option<String>,
 }
 
 fn default_category_type() -> String {
@@ -393,13 +542,13 @@ fn default_categories() -> Vec<TemplateCategory> {
     }
     // Fallback: 硬编码默认分类
     vec![
-        TemplateCategory { key: "report".into(),      label: "报告".into(),     order: 0, category_type: "builtin".into() },
-        TemplateCategory { key: "article".into(),      label: "文章".into(),     order: 1, category_type: "builtin".into() },
-        TemplateCategory { key: "email-draft".into(),  label: "邮件草稿".into(), order: 2, category_type: "builtin".into() },
-        TemplateCategory { key: "meeting".into(),      label: "会议纪要".into(), order: 3, category_type: "builtin".into() },
-        TemplateCategory { key: "creative".into(),     label: "创意写作".into(), order: 4, category_type: "builtin".into() },
-        TemplateCategory { key: "technical".into(),    label: "技术文档".into(), order: 5, category_type: "builtin".into() },
-        TemplateCategory { key: "general".into(),      label: "通用".into(),     order: 6, category_type: "builtin".into() },
+        TemplateCategory { key: "report".into(),      label: "报告".into(),     order: 0, category_type: "builtin".into(), prompt_template_id: None },
+        TemplateCategory { key: "article".into(),      label: "文章".into(),     order: 1, category_type: "builtin".into(), prompt_template_id: None },
+        TemplateCategory { key: "email-draft".into(),  label: "邮件草稿".into(), order: 2, category_type: "builtin".into(), prompt_template_id: None },
+        TemplateCategory { key: "meeting".into(),      label: "会议纪要".into(), order: 3, category_type: "builtin".into(), prompt_template_id: None },
+        TemplateCategory { key: "creative".into(),     label: "创意写作".into(), order: 4, category_type: "builtin".into(), prompt_template_id: None },
+        TemplateCategory { key: "technical".into(),    label: "技术文档".into(), order: 5, category_type: "builtin".into(), prompt_template_id: None },
+        TemplateCategory { key: "general".into(),      label: "通用".into(),     order: 6, category_type: "builtin".into(), prompt_template_id: None },
     ]
 }
 
@@ -423,6 +572,7 @@ fn load_categories_from_bundled() -> Option<Vec<TemplateCategory>> {
             label: name,
             order,
             category_type: "builtin".to_string(),
+            prompt_template_id: cat.get("promptTemplateId").and_then(|v| v.as_str()).map(String::from),
         });
     }
     Some(result)
@@ -442,9 +592,9 @@ pub fn list_template_categories() -> Vec<TemplateCategory> {
                     cats.sort_by_key(|c| c.order);
                     return cats;
                 }
-                Err(e) => eprintln!("Failed to parse categories.json: {}", e),
+                Err(e) => tracing::warn!("Failed to parse categories.json: {}", e),
             },
-            Err(e) => eprintln!("Failed to read categories.json: {}", e),
+            Err(e) => tracing::warn!("Failed to read categories.json: {}", e),
         }
     }
     // 首次使用，写入默认分类
@@ -474,6 +624,7 @@ pub fn create_template_category(key: &str, label: &str) -> Result<Vec<TemplateCa
         label: label.to_string(),
         order: max_order + 1,
         category_type: "custom".to_string(),
+        prompt_template_id: None,
     });
     save_categories(&cats)?;
     Ok(cats)
@@ -504,6 +655,16 @@ pub fn update_template_category(key: &str, label: Option<String>, new_key: Optio
     Ok(cats)
 }
 
+/// 设置分类的默认提示词模板（None 表示清除）
+pub fn set_category_prompt_template(key: &str, prompt_template_id: Option<String>) -> Result<Vec<TemplateCategory>, String> {
+    let mut cats = list_template_categories();
+    let cat = cats.iter_mut().find(|c| c.key == key)
+        .ok_or_else(|| format!("Category not found: {}", key))?;
+    cat.prompt_template_id = prompt_template_id.filter(|id| !id.trim().is_empty());
+    save_categories(&cats)?;
+    Ok(cats)
+}
+
 /// 删除分类
 pub fn delete_template_category(key: &str) -> Result<Vec<TemplateCategory>, String> {
     let mut cats = list_template_categories();
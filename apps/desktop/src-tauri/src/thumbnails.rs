@@ -0,0 +1,120 @@
+// 附件缩略图：图片和 PDF 附件生成缩小的预览图，缓存在项目的 thumbnails/ 目录，
+// 附件面板只需传输几十 KB 的缩略图而不是原文件的 base64
+// PDF 首页借助系统工具渲染（macOS qlmanage，其他平台 pdftoppm / mutool）
+
+use crate::document::Attachment;
+use base64::Engine;
+use image::{DynamicImage, ImageFormat};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub const MIN_SIZE: u32 = 32;
+pub const MAX_SIZE: u32 = 1024;
+
+const IMAGE_TYPES: &[&str] = &["png", "jpg", "jpeg", "gif"];
+
+/// 是否可以生成缩略图
+pub fn is_supported(attachment: &Attachment) -> bool {
+    let ext = file_ext(attachment);
+    ext == "pdf" || IMAGE_TYPES.contains(&ext.as_str())
+}
+
+fn file_ext(attachment: &Attachment) -> String {
+    let ext = Path::new(&attachment.file_path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if ext.is_empty() { attachment.file_type.to_lowercase() } else { ext }
+}
+
+/// 取得缩略图（data URL）；缓存比原文件旧时重新生成
+pub fn thumbnail(project_dir: &Path, attachment: &Attachment, size: u32) -> Result<String, String> {
+    let size = size.clamp(MIN_SIZE, MAX_SIZE);
+    let source = Path::new(&attachment.file_path);
+    let source_modified = fs::metadata(source)
+        .and_then(|m| m.modified())
+        .map_err(|_| format!("附件文件不存在: {}", attachment.file_path))?;
+
+    let ext = file_ext(attachment);
+    // JPEG 原图的缩略图仍用 JPEG，其他（含 PDF 渲染结果）用 PNG 以保留透明
+    let (format, mime, cache_ext) = if ext == "jpg" || ext == "jpeg" {
+        (ImageFormat::Jpeg, "image/jpeg", "jpg")
+    } else {
+        (ImageFormat::Png, "image/png", "png")
+    };
+    let dir = project_dir.join("thumbnails");
+    let cache = dir.join(format!("{}-{}.{}", attachment.id, size, cache_ext));
+
+    let fresh = fs::metadata(&cache).and_then(|m| m.modified()).is_ok_and(|t| t >= source_modified);
+    if !fresh {
+        let image = match ext.as_str() {
+            "pdf" => render_pdf(source, size)?,
+            e if IMAGE_TYPES.contains(&e) => image::open(source).map_err(|e| format!("读取图片失败: {}", e))?,
+            _ => return Err(format!("不支持为 .{} 文件生成缩略图", ext)),
+        };
+        let thumb = image.thumbnail(size, size);
+        // JPEG 不支持透明通道
+        let thumb = if format == ImageFormat::Jpeg { DynamicImage::ImageRgb8(thumb.to_rgb8()) } else { thumb };
+        fs::create_dir_all(&dir).map_err(|e| format!("创建缩略图目录失败: {}", e))?;
+        thumb.save_with_format(&cache, format).map_err(|e| format!("保存缩略图失败: {}", e))?;
+    }
+
+    let data = fs::read(&cache).map_err(|e| format!("读取缩略图失败: {}", e))?;
+    Ok(format!("data:{};base64,{}", mime, base64::engine::general_purpose::STANDARD.encode(data)))
+}
+
+/// 用系统工具渲染 PDF 首页
+fn render_pdf(source: &Path, size: u32) -> Result<DynamicImage, String> {
+    let temp_dir = std::env::temp_dir().join("aidocplus_thumbnails").join(uuid::Uuid::new_v4().to_string());
+    fs::create_dir_all(&temp_dir).map_err(|e| format!("创建临时目录失败: {}", e))?;
+    let result = render_pdf_to(source, size, &temp_dir)
+        .ok_or_else(|| "无法生成 PDF 预览：未找到 pdftoppm 或 mutool".to_string())
+        .and_then(|png| image::open(png).map_err(|e| format!("读取 PDF 预览失败: {}", e)));
+    let _ = fs::remove_dir_all(&temp_dir);
+    result
+}
+
+fn render_pdf_to(source: &Path, size: u32, temp_dir: &Path) -> Option<PathBuf> {
+    let size = size.to_string();
+
+    #[cfg(target_os = "macos")]
+    {
+        // Quick Look 输出为 {文件名}.png
+        let ok = Command::new("qlmanage")
+            .args(["-t", "-s", &size, "-o"])
+            .arg(temp_dir)
+            .arg(source)
+            .output()
+            .is_ok_and(|o| o.status.success());
+        let file_name = source.file_name()?.to_string_lossy().to_string();
+        let png = temp_dir.join(format!("{}.png", file_name));
+        if ok && png.exists() {
+            return Some(png);
+        }
+    }
+
+    let prefix = temp_dir.join("page");
+    let ok = Command::new("pdftoppm")
+        .args(["-png", "-singlefile", "-f", "1", "-l", "1", "-scale-to", &size])
+        .arg(source)
+        .arg(&prefix)
+        .output()
+        .is_ok_and(|o| o.status.success());
+    let png = prefix.with_extension("png");
+    if ok && png.exists() {
+        return Some(png);
+    }
+
+    let png = temp_dir.join("page-mutool.png");
+    let ok = Command::new("mutool")
+        .arg("draw")
+        .arg("-o")
+        .arg(&png)
+        .args(["-w", &size, "-h", &size])
+        .arg(source)
+        .arg("1")
+        .output()
+        .is_ok_and(|o| o.status.success());
+    (ok && png.exists()).then_some(png)
+}
@@ -1,5 +1,13 @@
+use crate::ai::AIConfig;
+use crate::plugin::PluginManifest;
+use crate::web_search;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::Emitter;
+use tokio::sync::oneshot;
 
 /// 工具定义（OpenAI Function Calling 格式）
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,9 +47,9 @@ pub struct ToolResult {
     pub content: String,
 }
 
-/// 获取所有内置工具的定义（OpenAI tools 格式）
-pub fn get_builtin_tool_definitions() -> Vec<ToolDefinition> {
-    vec![
+/// 获取所有内置工具的定义（OpenAI tools 格式）；web_search 为 true 时包含联网搜索工具
+pub fn get_builtin_tool_definitions(web_search: bool) -> Vec<ToolDefinition> {
+    let mut definitions = vec![
         ToolDefinition {
             tool_type: "function".to_string(),
             function: FunctionDefinition {
@@ -88,7 +96,32 @@ pub fn get_builtin_tool_definitions() -> Vec<ToolDefinition> {
                 }),
             },
         },
-    ]
+    ];
+    if web_search {
+        definitions.push(web_search_tool_definition());
+    }
+    definitions
+}
+
+/// 联网搜索工具（供没有内置搜索的服务商使用）
+pub fn web_search_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        tool_type: "function".to_string(),
+        function: FunctionDefinition {
+            name: web_search::TOOL_NAME.to_string(),
+            description: "在互联网上搜索最新信息，返回相关网页的标题、链接和摘要。回答时请注明引用的来源链接".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "搜索关键词"
+                    }
+                },
+                "required": ["query"]
+            }),
+        },
+    }
 }
 
 /// 执行内置工具调用
@@ -202,3 +235,147 @@ fn execute_get_document_stats(documents: &[Value]) -> String {
         "documents": doc_list
     }).to_string()
 }
+
+// ── 插件工具 ──
+// 插件在 manifest.json 的 tools 中声明工具（名称、说明、JSON Schema 参数），
+// 模型调用时通过 ai:tool:call 事件交给前端插件执行，前端用 respond_plugin_tool_call 返回结果
+
+/// 插件工具函数名前缀
+const PLUGIN_TOOL_PREFIX: &str = "plugin__";
+/// 等待前端插件返回结果的时长
+const PLUGIN_TOOL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// manifest.json 中声明的插件工具
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginToolDeclaration {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// 参数的 JSON Schema，缺省为无参数
+    #[serde(default = "empty_parameters")]
+    pub parameters: Value,
+}
+
+fn empty_parameters() -> Value {
+    json!({ "type": "object", "properties": {} })
+}
+
+/// 可供模型调用的插件工具
+#[derive(Debug, Clone)]
+pub struct PluginTool {
+    pub plugin_id: String,
+    pub name: String,
+    pub definition: ToolDefinition,
+}
+
+/// 插件工具暴露给模型的函数名：plugin__{插件ID}__{工具名}，
+/// 函数名只允许字母、数字、'_'、'-' 且不超过 64 个字符
+fn plugin_tool_name(plugin_id: &str, name: &str) -> String {
+    let sanitize = |s: &str| s.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' }).collect::<String>();
+    let mut full = format!("{}{}__{}", PLUGIN_TOOL_PREFIX, sanitize(plugin_id), sanitize(name));
+    full.truncate(64);
+    full
+}
+
+/// 已启用插件声明的工具
+pub fn plugin_tools(manifests: &[PluginManifest]) -> Vec<PluginTool> {
+    manifests
+        .iter()
+        .filter(|m| m.enabled)
+        .flat_map(|m| m.tools.iter().flatten().map(move |t| (m, t)))
+        .filter(|(_, t)| !t.name.trim().is_empty() && t.parameters.is_object())
+        .map(|(m, t)| PluginTool {
+            plugin_id: m.id.clone(),
+            name: t.name.clone(),
+            definition: ToolDefinition {
+                tool_type: "function".to_string(),
+                function: FunctionDefinition {
+                    name: plugin_tool_name(&m.id, &t.name),
+                    description: format!("[{}] {}", m.name, t.description),
+                    parameters: t.parameters.clone(),
+                },
+            },
+        })
+        .collect()
+}
+
+type PendingCalls = Mutex<HashMap<String, oneshot::Sender<Result<String, String>>>>;
+
+static PENDING_CALLS: OnceLock<PendingCalls> = OnceLock::new();
+
+fn pending_calls() -> &'static PendingCalls {
+    PENDING_CALLS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 执行联网搜索工具调用
+pub async fn execute_web_search(tool_call: &ToolCall, config: &AIConfig) -> ToolResult {
+    let query = serde_json::from_str::<Value>(&tool_call.function.arguments)
+        .ok()
+        .and_then(|args| args.get("query").and_then(|q| q.as_str()).map(str::to_string))
+        .unwrap_or_default();
+    let result = match config.http_client() {
+        Ok(client) => web_search::search(&client, &config.search, &query).await,
+        Err(e) => Err(e),
+    };
+    ToolResult {
+        tool_call_id: tool_call.id.clone(),
+        role: "tool".to_string(),
+        content: web_search::tool_output(result),
+    }
+}
+
+/// 执行工具调用：内置工具直接执行，插件工具转交前端插件并等待结果
+pub async fn execute_tool_call(
+    window: &tauri::Window,
+    tool_call: &ToolCall,
+    project_documents: &[Value],
+    plugin_tools: &[PluginTool],
+    config: &AIConfig,
+) -> ToolResult {
+    if tool_call.function.name == web_search::TOOL_NAME {
+        return execute_web_search(tool_call, config).await;
+    }
+    let Some(tool) = plugin_tools.iter().find(|t| t.definition.function.name == tool_call.function.name) else {
+        return execute_tool(tool_call, project_documents);
+    };
+
+    let call_id = uuid::Uuid::new_v4().to_string();
+    let (tx, rx) = oneshot::channel();
+    if let Ok(mut pending) = pending_calls().lock() {
+        pending.insert(call_id.clone(), tx);
+    }
+    let arguments: Value = serde_json::from_str(&tool_call.function.arguments).unwrap_or(json!({}));
+    let _ = window.emit("ai:tool:call", json!({
+        "callId": call_id,
+        "pluginId": tool.plugin_id,
+        "tool": tool.name,
+        "arguments": arguments
+    }));
+
+    let content = match tokio::time::timeout(PLUGIN_TOOL_TIMEOUT, rx).await {
+        Ok(Ok(Ok(content))) => content,
+        Ok(Ok(Err(e))) => json!({ "error": e }).to_string(),
+        Ok(Err(_)) => json!({ "error": "插件未返回结果" }).to_string(),
+        Err(_) => json!({ "error": format!("插件工具 {} 执行超时", tool.name) }).to_string(),
+    };
+    if let Ok(mut pending) = pending_calls().lock() {
+        pending.remove(&call_id);
+    }
+
+    ToolResult {
+        tool_call_id: tool_call.id.clone(),
+        role: "tool".to_string(),
+        content,
+    }
+}
+
+/// 前端插件返回工具执行结果
+pub fn resolve_plugin_tool_call(call_id: &str, result: Result<String, String>) -> Result<(), String> {
+    let sender = pending_calls()
+        .lock()
+        .map_err(|e| format!("锁获取失败: {}", e))?
+        .remove(call_id)
+        .ok_or_else(|| format!("工具调用不存在或已超时: {}", call_id))?;
+    let _ = sender.send(result);
+    Ok(())
+}
@@ -0,0 +1,78 @@
+// 语音转写：上传音频到 OpenAI Whisper 兼容接口（/audio/transcriptions），结果整理为 Markdown
+// reqwest 未启用 multipart 特性，请求体按 multipart/form-data 格式手工拼接
+
+use std::path::Path;
+
+/// 默认转写模型
+pub const DEFAULT_MODEL: &str = "whisper-1";
+/// OpenAI 接口的单文件上限
+pub const MAX_AUDIO_BYTES: u64 = 25 * 1024 * 1024;
+
+/// 按扩展名判断音频 MIME 类型；不支持的格式返回 None
+pub fn audio_mime(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    Some(match ext.as_str() {
+        "mp3" | "mpga" | "mpeg" => "audio/mpeg",
+        "mp4" => "audio/mp4",
+        "m4a" => "audio/m4a",
+        "wav" => "audio/wav",
+        "webm" => "audio/webm",
+        "ogg" | "oga" => "audio/ogg",
+        "flac" => "audio/flac",
+        _ => return None,
+    })
+}
+
+/// 拼接 multipart/form-data 请求体，返回 (Content-Type, 请求体)
+pub fn multipart_body(fields: &[(&str, &str)], file_name: &str, mime: &str, data: &[u8]) -> (String, Vec<u8>) {
+    let boundary = format!("----AiDocPlus{}", uuid::Uuid::new_v4().simple());
+    let mut body = Vec::with_capacity(data.len() + 1024);
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n", boundary, name, value).as_bytes(),
+        );
+    }
+    let file_name = file_name.replace('"', "_");
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+            boundary, file_name, mime
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(data);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    (format!("multipart/form-data; boundary={}", boundary), body)
+}
+
+fn timestamp(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    let (h, m, s) = (total / 3600, total / 60 % 60, total % 60);
+    if h > 0 {
+        format!("{:02}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{:02}:{:02}", m, s)
+    }
+}
+
+/// verbose_json 结果转为 Markdown：有分段时每段一行并带时间戳，否则按原文输出
+pub fn to_markdown(value: &serde_json::Value) -> String {
+    let segments: Vec<String> = value
+        .get("segments")
+        .and_then(|s| s.as_array())
+        .map(|segments| {
+            segments
+                .iter()
+                .filter_map(|seg| {
+                    let text = seg.get("text")?.as_str()?.trim();
+                    let start = seg.get("start").and_then(|s| s.as_f64()).unwrap_or(0.0);
+                    (!text.is_empty()).then(|| format!("**[{}]** {}", timestamp(start), text))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if !segments.is_empty() {
+        return segments.join("\n\n");
+    }
+    value.get("text").and_then(|t| t.as_str()).unwrap_or_default().trim().to_string()
+}
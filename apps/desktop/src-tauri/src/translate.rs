@@ -0,0 +1,137 @@
+// 文档翻译：按标题和段落把 Markdown 切成若干片段逐段翻译，保留原有结构
+// 代码块原样保留不发送给模型；每段不超过设定的 token 数，遇到标题另起一段
+
+use crate::ai::ChatMessage;
+use crate::context_window;
+use crate::document::Document;
+use serde::Serialize;
+
+/// 单段的 token 上限：翻译的输出和输入长度相当，段落不宜过长
+pub const MAX_SEGMENT_TOKENS: usize = 2_000;
+
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub text: String,
+    /// 代码块等不需要翻译的内容为 false
+    pub translate: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslationResult {
+    pub content: String,
+    pub segments: usize,
+    /// 另存为新文档时为新文档
+    pub document: Option<Document>,
+}
+
+/// 语言代码转为提示词中的语言名称；未知代码原样使用
+pub fn language_name(code: &str) -> &str {
+    match code.to_lowercase().as_str() {
+        "zh" | "zh-cn" | "zh-hans" => "简体中文",
+        "zh-tw" | "zh-hk" | "zh-hant" => "繁体中文",
+        "en" => "英语",
+        "ja" => "日语",
+        "ko" => "韩语",
+        "fr" => "法语",
+        "de" => "德语",
+        "es" => "西班牙语",
+        "ru" => "俄语",
+        "pt" => "葡萄牙语",
+        "it" => "意大利语",
+        "ar" => "阿拉伯语",
+        _ => code,
+    }
+}
+
+/// 按空行切分为块，围栏代码块整体作为一个块
+fn blocks(markdown: &str) -> Vec<(String, bool)> {
+    let mut blocks = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut fence: Option<&str> = None;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            current.push(line);
+            if trimmed.starts_with(marker) {
+                blocks.push((current.join("\n"), false));
+                current.clear();
+                fence = None;
+            }
+            continue;
+        }
+        let marker = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m));
+        if marker.is_some() || line.trim().is_empty() {
+            if !current.is_empty() {
+                blocks.push((current.join("\n"), true));
+                current.clear();
+            }
+            if let Some(marker) = marker {
+                current.push(line);
+                fence = Some(marker);
+            }
+            continue;
+        }
+        current.push(line);
+    }
+    if !current.is_empty() {
+        // 未闭合的代码块也原样保留
+        blocks.push((current.join("\n"), fence.is_none()));
+    }
+    blocks
+}
+
+/// 切分为翻译片段：连续的可翻译块合并到 max_tokens 以内，标题处另起一段
+pub fn split_segments(markdown: &str, max_tokens: usize) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut current = String::new();
+    let flush = |current: &mut String, segments: &mut Vec<Segment>| {
+        if !current.is_empty() {
+            segments.push(Segment { text: std::mem::take(current), translate: true });
+        }
+    };
+    for (block, translate) in blocks(markdown) {
+        if !translate {
+            flush(&mut current, &mut segments);
+            segments.push(Segment { text: block, translate: false });
+            continue;
+        }
+        let too_long = context_window::estimate_tokens(&current) + context_window::estimate_tokens(&block) > max_tokens;
+        if block.starts_with('#') || too_long {
+            flush(&mut current, &mut segments);
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(&block);
+    }
+    flush(&mut current, &mut segments);
+    segments
+}
+
+/// 单段翻译的请求消息；glossary 为项目术语表提示
+pub fn build_messages(segment: &str, target_lang: &str, glossary: Option<String>) -> Vec<ChatMessage> {
+    let mut system = format!(
+        "你是专业翻译。把用户给出的 Markdown 片段翻译为{}。\n\
+         要求：保留全部 Markdown 结构（标题级别、列表、表格、引用、链接和图片地址、行内代码、公式）不变；\
+         专有名词和代码标识符不翻译；只输出译文，不要解释，不要用代码块包裹。",
+        language_name(target_lang)
+    );
+    if let Some(glossary) = glossary {
+        system = format!("{}\n\n{}", system, glossary);
+    }
+    vec![
+        ChatMessage { role: "system".to_string(), content: system, images: Vec::new() },
+        ChatMessage { role: "user".to_string(), content: segment.to_string(), images: Vec::new() },
+    ]
+}
+
+/// 去掉模型偶尔添加的外层代码块
+pub fn clean_output(text: &str) -> String {
+    let trimmed = text.trim();
+    let unwrapped = trimmed
+        .strip_prefix("```markdown")
+        .or_else(|| trimmed.strip_prefix("```md"))
+        .and_then(|rest| rest.strip_suffix("```"));
+    unwrapped.unwrap_or(trimmed).trim().to_string()
+}
@@ -0,0 +1,100 @@
+use crate::config;
+use crate::i18n;
+use crate::settings;
+use tauri::menu::{Menu, MenuBuilder, MenuEvent};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+const TRAY_ID: &str = "main";
+
+/// 托盘菜单项 ID 前缀，主菜单的事件转发会跳过这些 ID
+pub const TRAY_ITEM_PREFIX: &str = "tray_";
+
+fn build_tray_menu(handle: &AppHandle) -> tauri::Result<Menu<Wry>> {
+    MenuBuilder::new(handle)
+        .text("tray_new_document", i18n::t("tray.new_document"))
+        .text("tray_resume_document", i18n::t("tray.resume_document"))
+        .text("tray_quick_capture", i18n::t("tray.quick_capture"))
+        .separator()
+        .text("tray_stop_ai", i18n::t("tray.stop_ai"))
+        .text("tray_backup", i18n::t("tray.backup"))
+        .separator()
+        .text("tray_show", i18n::t("tray.show"))
+        .text("tray_quit", i18n::t("tray.quit"))
+        .build()
+}
+
+/// 创建系统托盘图标（设置中关闭托盘时不创建）
+pub fn init(handle: &AppHandle) -> tauri::Result<()> {
+    if !settings::load(&config::get_settings_path(handle)).tray.enabled {
+        return Ok(());
+    }
+    let menu = build_tray_menu(handle)?;
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("AiDocPlus")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(handle_menu_event)
+        .on_tray_icon_event(handle_icon_event);
+    if let Some(icon) = handle.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(handle)?;
+    Ok(())
+}
+
+/// 语言变更后刷新托盘菜单；托盘开关变更后创建或隐藏托盘
+pub fn refresh(handle: &AppHandle) -> Result<(), String> {
+    let enabled = settings::load(&config::get_settings_path(handle)).tray.enabled;
+    match handle.tray_by_id(TRAY_ID) {
+        Some(tray) => {
+            let menu = build_tray_menu(handle).map_err(|e| format!("构建托盘菜单失败: {}", e))?;
+            tray.set_menu(Some(menu)).map_err(|e| e.to_string())?;
+            tray.set_visible(enabled).map_err(|e| e.to_string())
+        }
+        None if enabled => init(handle).map_err(|e| format!("创建托盘失败: {}", e)),
+        None => Ok(()),
+    }
+}
+
+/// 窗口关闭时是否隐藏到托盘而不是退出
+pub fn should_hide_on_close(handle: &AppHandle) -> bool {
+    let tray = settings::load(&config::get_settings_path(handle)).tray;
+    tray.enabled && tray.close_to_tray && handle.tray_by_id(TRAY_ID).is_some()
+}
+
+pub fn show_main_window(handle: &AppHandle) {
+    if let Some(window) = handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn handle_menu_event(handle: &AppHandle, event: MenuEvent) {
+    // 需要前端处理的动作复用主菜单的 menu-event 通道
+    let forward = |menu_id: &str| {
+        show_main_window(handle);
+        let _ = handle.emit("menu-event", menu_id);
+    };
+    match event.id().0.as_str() {
+        "tray_new_document" => forward("new_document"),
+        "tray_resume_document" => forward("resume_last_document"),
+        "tray_backup" => forward("project_backup"),
+        "tray_quick_capture" => {
+            if let Err(e) = crate::quick_capture::open_window(handle) {
+                tracing::warn!("{}", e);
+            }
+        }
+        "tray_stop_ai" => crate::commands::ai::stop_ai_stream(None),
+        "tray_show" => show_main_window(handle),
+        "tray_quit" => handle.exit(0),
+        _ => {}
+    }
+}
+
+fn handle_icon_event(tray: &TrayIcon, event: TrayIconEvent) {
+    if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+        show_main_window(tray.app_handle());
+    }
+}
@@ -0,0 +1,138 @@
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 默认发布源（GitHub Releases 最新版本）
+pub const DEFAULT_FEED_URL: &str = "https://api.github.com/repos/AiDocPlus/AiDocPlus-Main/releases/latest";
+
+/// 更新检查结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    pub current_version: String,
+    pub latest_version: String,
+    pub available: bool,
+    /// 用户已选择跳过该版本
+    pub skipped: bool,
+    pub release_notes: String,
+    /// 当前平台的安装包下载地址，找不到对应安装包时为空
+    pub download_url: Option<String>,
+    /// 发布页地址
+    pub release_url: Option<String>,
+    pub published_at: Option<String>,
+}
+
+/// GitHub Release 格式
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    html_url: Option<String>,
+    #[serde(default)]
+    published_at: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// 自建发布源的简单格式：{ "version", "notes", "url", "pubDate" }
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SimpleFeed {
+    version: String,
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    pub_date: Option<String>,
+}
+
+/// 请求发布源并与当前版本比较
+pub async fn check(feed_url: &str, current: &Version, skipped_version: Option<&str>) -> Result<UpdateInfo, String> {
+    let resp = reqwest::Client::new()
+        .get(feed_url)
+        .header("User-Agent", format!("AiDocPlus/{}", current))
+        .header("Accept", "application/json")
+        .timeout(Duration::from_secs(20))
+        .send()
+        .await
+        .map_err(|e| format!("请求更新信息失败: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("更新服务返回错误: {}", resp.status()));
+    }
+    let body: serde_json::Value = resp.json().await.map_err(|e| format!("解析更新信息失败: {}", e))?;
+
+    let (latest, notes, download_url, release_url, published_at) = if body.get("tag_name").is_some() {
+        let release: GithubRelease =
+            serde_json::from_value(body).map_err(|e| format!("解析更新信息失败: {}", e))?;
+        if release.draft || release.prerelease {
+            return Err("最新发布不是正式版本".to_string());
+        }
+        let download_url = pick_asset(&release.assets).map(|a| a.browser_download_url.clone());
+        (release.tag_name, release.body, download_url, release.html_url, release.published_at)
+    } else {
+        let feed: SimpleFeed = serde_json::from_value(body).map_err(|e| format!("解析更新信息失败: {}", e))?;
+        (feed.version, feed.notes, feed.url.clone(), feed.url, feed.pub_date)
+    };
+
+    let latest_version = parse_version(&latest)?;
+    Ok(UpdateInfo {
+        current_version: current.to_string(),
+        latest_version: latest_version.to_string(),
+        available: latest_version > *current,
+        skipped: skipped_version
+            .and_then(|v| parse_version(v).ok())
+            .is_some_and(|v| v == latest_version),
+        release_notes: notes.unwrap_or_default(),
+        download_url,
+        release_url,
+        published_at,
+    })
+}
+
+/// 解析版本号，兼容 "v1.2.3" 形式的标签
+pub fn parse_version(tag: &str) -> Result<Version, String> {
+    let trimmed = tag.trim();
+    let trimmed = trimmed.strip_prefix(['v', 'V']).unwrap_or(trimmed);
+    Version::parse(trimmed).map_err(|e| format!("无效的版本号 {}: {}", tag, e))
+}
+
+/// 按当前平台挑选安装包
+fn pick_asset(assets: &[GithubAsset]) -> Option<&GithubAsset> {
+    let extensions: &[&str] = match std::env::consts::OS {
+        "macos" => &[".dmg", ".app.tar.gz"],
+        "windows" => &[".msi", "-setup.exe", ".exe"],
+        _ => &[".AppImage", ".deb", ".rpm"],
+    };
+    let arch_aliases: &[&str] = match std::env::consts::ARCH {
+        "aarch64" => &["aarch64", "arm64"],
+        "x86_64" => &["x86_64", "x64", "amd64"],
+        _ => &[],
+    };
+    let other_arch = |name: &str| {
+        ["aarch64", "arm64", "x86_64", "x64", "amd64"]
+            .iter()
+            .any(|a| name.contains(a) && !arch_aliases.contains(a))
+    };
+    for ext in extensions {
+        let mut candidates = assets.iter().filter(|a| a.name.ends_with(ext));
+        // 优先匹配当前架构，其次选不带架构标记的通用包（如 universal.dmg）
+        let by_arch = candidates.clone().find(|a| arch_aliases.iter().any(|arch| a.name.contains(arch)));
+        if let Some(asset) = by_arch.or_else(|| candidates.find(|a| !other_arch(&a.name))) {
+            return Some(asset);
+        }
+    }
+    None
+}
@@ -0,0 +1,479 @@
+// Obsidian / Typora 笔记库导入
+// 递归读取文件夹中的 Markdown 文件，每篇笔记创建一个文档：
+// - 前置元数据（front matter）中的 title / tags / aliases 写入文档标题、标签，并用于链接匹配
+// - [[笔记]]、[[笔记|显示文字]]、[[笔记#标题]] 及指向 .md 文件的普通链接转为 aidocplus://open 文档链接
+// - ![[图片.png]]、![](相对路径)、<img src> 引用的本地文件复制到 attachments/{文档ID}/
+
+use crate::deep_link::SCHEME;
+use crate::document::{Attachment, Document};
+use crate::email_import::{sanitize_file_name, unique_path};
+use regex::{Captures, Regex};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 单次导入的笔记数上限
+const MAX_NOTES: usize = 5000;
+/// 单篇笔记最大大小
+const MAX_NOTE_BYTES: u64 = 10 * 1024 * 1024;
+/// 单个附件最大大小
+const MAX_ASSET_BYTES: u64 = 100 * 1024 * 1024;
+const MARKDOWN_EXTENSIONS: &[&str] = &["md", "markdown"];
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultImportReport {
+    pub documents: Vec<ImportedNote>,
+    pub links_resolved: usize,
+    /// 找不到目标笔记的链接，原样保留
+    pub unresolved_links: Vec<String>,
+    pub assets_copied: usize,
+    /// 找不到或无法复制的本地文件引用
+    pub missing_assets: Vec<String>,
+    /// 读取失败而跳过的笔记（相对路径）
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedNote {
+    pub document_id: String,
+    pub title: String,
+    /// 笔记在库中的相对路径
+    pub source: String,
+}
+
+#[derive(Default)]
+struct FrontMatter {
+    title: Option<String>,
+    tags: Vec<String>,
+    aliases: Vec<String>,
+}
+
+struct Note {
+    rel: PathBuf,
+    body: String,
+    aliases: Vec<String>,
+    document: Document,
+}
+
+/// 导入笔记库到项目
+pub fn import_vault(
+    vault: &Path,
+    project_dir: &Path,
+    project_id: &str,
+    doc_path: impl Fn(&str) -> PathBuf,
+) -> Result<VaultImportReport, String> {
+    if !vault.is_dir() {
+        return Err(format!("文件夹不存在: {}", vault.display()));
+    }
+    let (note_paths, asset_paths) = walk(vault);
+    if note_paths.is_empty() {
+        return Err("文件夹中没有 Markdown 文件".to_string());
+    }
+    if note_paths.len() > MAX_NOTES {
+        return Err(format!("笔记数量过多 ({} > {})，请分批导入", note_paths.len(), MAX_NOTES));
+    }
+
+    let mut report = VaultImportReport::default();
+    let mut notes = Vec::new();
+    for rel in note_paths {
+        let path = vault.join(&rel);
+        let text = match fs::metadata(&path) {
+            Ok(m) if m.len() > MAX_NOTE_BYTES => None,
+            _ => fs::read_to_string(&path).ok(),
+        };
+        let Some(text) = text else {
+            report.skipped.push(display(&rel));
+            continue;
+        };
+        let (front, body) = split_front_matter(&text);
+        let stem = rel.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let title = front.title.filter(|t| !t.is_empty()).unwrap_or(stem);
+        let mut document = Document::new(project_id.to_string(), title, String::new());
+        document.metadata.tags = front.tags;
+        notes.push(Note { rel, body: body.to_string(), aliases: front.aliases, document });
+    }
+
+    // 链接匹配：文件名、库内相对路径（不含扩展名）、别名、标题，均不区分大小写
+    let mut index: HashMap<String, String> = HashMap::new();
+    for note in &notes {
+        let without_ext = note.rel.with_extension("");
+        let keys = [display(&without_ext), without_ext.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()]
+            .into_iter()
+            .chain(note.aliases.iter().cloned())
+            .chain([note.document.title.clone()]);
+        for key in keys.filter(|k| !k.is_empty()) {
+            index.entry(key.to_lowercase()).or_insert_with(|| note.document.id.clone());
+        }
+    }
+    // Obsidian 的附件引用通常只写文件名，按文件名在整个库中查找
+    let mut assets_by_name: HashMap<String, PathBuf> = HashMap::new();
+    for rel in &asset_paths {
+        if let Some(name) = rel.file_name() {
+            assets_by_name.entry(name.to_string_lossy().to_lowercase()).or_insert_with(|| vault.join(rel));
+        }
+    }
+
+    for mut note in notes {
+        let note_dir = vault.join(&note.rel).parent().map(Path::to_path_buf).unwrap_or_else(|| vault.to_path_buf());
+        let mut ctx = Rewriter {
+            vault,
+            note_dir: &note_dir,
+            project_id,
+            attachments_dir: project_dir.join("attachments").join(&note.document.id),
+            index: &index,
+            assets_by_name: &assets_by_name,
+            copied: HashMap::new(),
+            attachments: Vec::new(),
+            report: &mut report,
+        };
+        let content = map_outside_code(&note.body, |text| ctx.rewrite(text));
+        note.document.attachments = ctx.attachments;
+        note.document.metadata.word_count = content.split_whitespace().count();
+        note.document.metadata.character_count = content.chars().count();
+        note.document.content = content;
+        note.document.save(&doc_path(&note.document.id)).map_err(|e| e.to_string())?;
+        report.documents.push(ImportedNote {
+            document_id: note.document.id.clone(),
+            title: note.document.title.clone(),
+            source: display(&note.rel),
+        });
+    }
+    Ok(report)
+}
+
+/// 列出库中的 Markdown 文件和其他文件（相对路径），跳过 .obsidian、.trash 等隐藏目录
+fn walk(root: &Path) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let mut notes = Vec::new();
+    let mut assets = Vec::new();
+    let mut stack = vec![PathBuf::new()];
+    while let Some(rel) = stack.pop() {
+        let Ok(entries) = fs::read_dir(root.join(&rel)) else { continue };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            if name.to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let child = rel.join(&name);
+            if entry.path().is_dir() {
+                stack.push(child);
+            } else if is_markdown(&child) {
+                notes.push(child);
+            } else {
+                assets.push(child);
+            }
+        }
+    }
+    notes.sort();
+    (notes, assets)
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|e| MARKDOWN_EXTENSIONS.contains(&e.as_str()))
+}
+
+fn display(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+// ============================================================
+// 前置元数据
+// ============================================================
+
+/// 拆出开头 --- 之间的前置元数据，返回元数据和正文
+fn split_front_matter(text: &str) -> (FrontMatter, &str) {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let Some(rest) = text.strip_prefix("---\n").or_else(|| text.strip_prefix("---\r\n")) else {
+        return (FrontMatter::default(), text);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            return (parse_front_matter(&rest[..offset]), rest[offset + line.len()..].trim_start_matches(['\r', '\n']));
+        }
+        offset += line.len();
+    }
+    (FrontMatter::default(), text)
+}
+
+/// 只解析需要的字段，支持 `tags: [a, b]`、`tags: a, b` 和多行 `- a` 列表
+fn parse_front_matter(yaml: &str) -> FrontMatter {
+    let mut front = FrontMatter::default();
+    let mut current = String::new();
+    for line in yaml.lines() {
+        let trimmed = line.trim_start();
+        if let Some(item) = trimmed.strip_prefix("- ") {
+            push_list_item(&mut front, &current, item);
+            continue;
+        }
+        if line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            current.clear();
+            continue;
+        };
+        current = key.trim().to_lowercase();
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        if current == "title" {
+            front.title = Some(unquote(value).to_string());
+        } else {
+            for item in value.trim_start_matches('[').trim_end_matches(']').split(',') {
+                push_list_item(&mut front, &current, item);
+            }
+        }
+    }
+    front
+}
+
+fn push_list_item(front: &mut FrontMatter, key: &str, item: &str) {
+    let item = unquote(item);
+    if item.is_empty() {
+        return;
+    }
+    match key {
+        "tags" | "tag" => front.tags.push(item.trim_start_matches('#').to_string()),
+        "aliases" | "alias" => front.aliases.push(item.to_string()),
+        _ => {}
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim().trim_matches('"').trim_matches('\'').trim()
+}
+
+// ============================================================
+// 链接与附件改写
+// ============================================================
+
+/// 只改写代码块之外的内容
+fn map_outside_code(text: &str, mut f: impl FnMut(&str) -> String) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut pending = String::new();
+    let mut fence: Option<&str> = None;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        match fence {
+            Some(marker) => {
+                out.push_str(line);
+                if trimmed.starts_with(marker) {
+                    fence = None;
+                }
+            }
+            None if trimmed.starts_with("```") || trimmed.starts_with("~~~") => {
+                out.push_str(&f(&std::mem::take(&mut pending)));
+                out.push_str(line);
+                fence = Some(&trimmed[..3]);
+            }
+            None => pending.push_str(line),
+        }
+    }
+    out.push_str(&f(&pending));
+    out
+}
+
+struct Rewriter<'a> {
+    vault: &'a Path,
+    note_dir: &'a Path,
+    project_id: &'a str,
+    attachments_dir: PathBuf,
+    index: &'a HashMap<String, String>,
+    assets_by_name: &'a HashMap<String, PathBuf>,
+    /// 同一篇笔记中重复引用的文件只复制一次
+    copied: HashMap<PathBuf, String>,
+    attachments: Vec<Attachment>,
+    report: &'a mut VaultImportReport,
+}
+
+impl Rewriter<'_> {
+    fn rewrite(&mut self, text: &str) -> String {
+        if text.is_empty() {
+            return String::new();
+        }
+        let wikilink = Regex::new(r"(!?)\[\[([^\[\]\n]+?)\]\]").unwrap();
+        let text = wikilink.replace_all(text, |caps: &Captures| self.wikilink(&caps[0], !caps[1].is_empty(), &caps[2]));
+
+        let image = Regex::new(r#"!\[([^\]\n]*)\]\((?:<([^>\n]+)>|([^)\s]+))(?:\s+"[^"\n]*")?\)"#).unwrap();
+        let text = image.replace_all(&text, |caps: &Captures| {
+            let target = caps.get(2).or(caps.get(3)).map(|m| m.as_str()).unwrap_or_default();
+            match self.copy_local(target) {
+                Some(path) => format!("![{}](<{}>)", &caps[1], path),
+                None => caps[0].to_string(),
+            }
+        });
+
+        let html_image = Regex::new(r#"(<img\s[^>]*?src=["'])([^"']+)(["'])"#).unwrap();
+        let text = html_image.replace_all(&text, |caps: &Captures| match self.copy_local(&caps[2]) {
+            Some(path) => format!("{}{}{}", &caps[1], path, &caps[3]),
+            None => caps[0].to_string(),
+        });
+
+        // Typora 等使用的普通相对链接 [文字](其他笔记.md)
+        let md_link = Regex::new(r"(^|[^!])\[([^\]\n]*)\]\((?:<([^>\n]+)>|([^)\s]+))\)").unwrap();
+        md_link
+            .replace_all(&text, |caps: &Captures| {
+                let target = caps.get(3).or(caps.get(4)).map(|m| m.as_str()).unwrap_or_default();
+                match self.resolve_relative_note(target) {
+                    Some(id) => {
+                        self.report.links_resolved += 1;
+                        format!("{}[{}]({})", &caps[1], &caps[2], self.doc_link(&id))
+                    }
+                    None => caps[0].to_string(),
+                }
+            })
+            .to_string()
+    }
+
+    fn wikilink(&mut self, original: &str, embed: bool, inner: &str) -> String {
+        let (target, alias) = match inner.split_once('|') {
+            Some((t, a)) => (t.trim(), Some(a.trim())),
+            None => (inner.trim(), None),
+        };
+        let note = target.split('#').next().unwrap_or_default().trim();
+
+        // ![[图片.png|300]]：嵌入的非 Markdown 文件作为附件
+        if embed && !note.is_empty() && Path::new(note).extension().is_some() && !is_markdown(Path::new(note)) {
+            return match self.copy_local(note) {
+                Some(path) => format!("![{}](<{}>)", Path::new(note).file_stem().map(|s| s.to_string_lossy()).unwrap_or_default(), path),
+                None => original.to_string(),
+            };
+        }
+        // [[#标题]] 指向本文内的标题
+        if note.is_empty() {
+            return alias.unwrap_or(target.trim_start_matches('#')).to_string();
+        }
+        let key = note.trim_end_matches(".md").replace('\\', "/").to_lowercase();
+        match self.index.get(&key) {
+            Some(id) => {
+                self.report.links_resolved += 1;
+                format!("[{}]({})", alias.unwrap_or(target), self.doc_link(id))
+            }
+            None => {
+                self.report.unresolved_links.push(target.to_string());
+                original.to_string()
+            }
+        }
+    }
+
+    fn doc_link(&self, document_id: &str) -> String {
+        format!("{}://open?project={}&doc={}", SCHEME, self.project_id, document_id)
+    }
+
+    fn resolve_relative_note(&self, target: &str) -> Option<String> {
+        if target.contains("://") || !is_markdown(Path::new(target.split('#').next().unwrap_or_default())) {
+            return None;
+        }
+        let decoded = percent_decode(target.split('#').next().unwrap_or_default());
+        let path = normalize(&self.note_dir.join(&decoded));
+        let rel = path.strip_prefix(self.vault).ok()?.with_extension("");
+        self.index.get(&display(&rel).to_lowercase()).cloned()
+    }
+
+    /// 复制本地文件到附件目录，返回 Markdown 中使用的路径；网络地址和找不到的文件返回 None
+    fn copy_local(&mut self, target: &str) -> Option<String> {
+        let target = target.trim();
+        if target.is_empty() || target.contains("://") || target.starts_with("data:") || target.starts_with('#') {
+            return None;
+        }
+        let decoded = percent_decode(target);
+        let Some(source) = self.find_asset(&decoded) else {
+            self.report.missing_assets.push(target.to_string());
+            return None;
+        };
+        if let Some(path) = self.copied.get(&source) {
+            return Some(path.clone());
+        }
+        match self.copy_asset(&source) {
+            Ok(path) => {
+                self.report.assets_copied += 1;
+                self.copied.insert(source, path.clone());
+                Some(path)
+            }
+            Err(e) => {
+                tracing::warn!("复制附件 {} 失败: {}", source.display(), e);
+                self.report.missing_assets.push(target.to_string());
+                None
+            }
+        }
+    }
+
+    /// 依次按笔记所在目录、库根目录、库中同名文件查找；只接受库内的文件
+    fn find_asset(&self, target: &str) -> Option<PathBuf> {
+        let candidates = [normalize(&self.note_dir.join(target)), normalize(&self.vault.join(target.trim_start_matches('/')))];
+        candidates
+            .into_iter()
+            .find(|p| p.starts_with(self.vault) && p.is_file())
+            .or_else(|| {
+                let name = Path::new(target).file_name()?.to_string_lossy().to_lowercase();
+                self.assets_by_name.get(&name).cloned()
+            })
+    }
+
+    fn copy_asset(&mut self, source: &Path) -> Result<String, String> {
+        let size = fs::metadata(source).map_err(|e| e.to_string())?.len();
+        if size > MAX_ASSET_BYTES {
+            return Err(format!("文件过大 ({} 字节)", size));
+        }
+        fs::create_dir_all(&self.attachments_dir).map_err(|e| format!("创建附件目录失败: {}", e))?;
+        let file_name = sanitize_file_name(&source.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default());
+        let dest = unique_path(&self.attachments_dir, &file_name);
+        fs::copy(source, &dest).map_err(|e| e.to_string())?;
+
+        let file_path = dest.to_string_lossy().to_string();
+        self.attachments.push(Attachment {
+            id: uuid::Uuid::new_v4().to_string(),
+            file_name: dest.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or(file_name),
+            file_path: file_path.clone(),
+            file_size: size,
+            file_type: dest.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default(),
+            added_at: chrono::Utc::now().timestamp(),
+        });
+        Ok(file_path.replace('\\', "/"))
+    }
+}
+
+/// 去掉路径中的 . 和 ..（不访问文件系统）
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Typora 会把路径中的空格等字符写成 %20
+fn percent_decode(value: &str) -> String {
+    if !value.contains('%') {
+        return value.to_string();
+    }
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8(out).unwrap_or_else(|_| value.to_string())
+}
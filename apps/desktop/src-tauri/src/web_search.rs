@@ -0,0 +1,134 @@
+// 联网搜索后备：为没有内置搜索的服务商提供 web_search 工具，模型通过 Function Calling 调用
+// 支持 SearXNG（自建实例，JSON 接口）、Bing Web Search API 和 Tavily，API Key 保存在钥匙串中
+
+use crate::settings::WebSearchSettings;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// 工具函数名
+pub const TOOL_NAME: &str = "web_search";
+
+const BING_ENDPOINT: &str = "https://api.bing.microsoft.com/v7.0/search";
+const TAVILY_ENDPOINT: &str = "https://api.tavily.com/search";
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// 搜索服务配置（含 API Key，不序列化）
+#[derive(Debug, Clone, Default)]
+pub struct SearchConfig {
+    pub provider: String,
+    pub endpoint: Option<String>,
+    pub api_key: Option<String>,
+    pub max_results: usize,
+}
+
+impl SearchConfig {
+    pub fn from_settings(settings: WebSearchSettings, api_key: Option<String>) -> Self {
+        Self {
+            provider: settings.provider,
+            endpoint: settings.endpoint.filter(|e| !e.trim().is_empty()),
+            api_key: api_key.filter(|k| !k.trim().is_empty()),
+            max_results: settings.max_results,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+}
+
+fn require_key(config: &SearchConfig) -> Result<&str, String> {
+    config.api_key.as_deref().ok_or_else(|| format!("未设置 {} 搜索的 API Key", config.provider))
+}
+
+fn hits(items: Option<&Value>, title: &str, snippet: &str, limit: usize) -> Vec<SearchHit> {
+    let text = |item: &Value, key: &str| item.get(key).and_then(|v| v.as_str()).unwrap_or_default().trim().to_string();
+    items
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .map(|item| SearchHit { title: text(item, title), url: text(item, "url"), snippet: text(item, snippet) })
+        .filter(|hit| !hit.url.is_empty())
+        .take(limit)
+        .collect()
+}
+
+async fn read_json(response: reqwest::Response) -> Result<Value, String> {
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("搜索服务返回错误 ({}): {}", status, body.chars().take(200).collect::<String>()));
+    }
+    response.json().await.map_err(|e| format!("解析搜索结果失败: {}", e))
+}
+
+/// 执行一次搜索
+pub async fn search(client: &reqwest::Client, config: &SearchConfig, query: &str) -> Result<Vec<SearchHit>, String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err("搜索关键词不能为空".to_string());
+    }
+    let limit = config.max_results.max(1);
+    let send = |request: reqwest::RequestBuilder| async move {
+        let response = request.timeout(SEARCH_TIMEOUT).send().await.map_err(|e| format!("连接搜索服务失败: {}", e))?;
+        read_json(response).await
+    };
+
+    match config.provider.as_str() {
+        "searxng" => {
+            let endpoint = config.endpoint.as_deref().ok_or("请先在设置中填写 SearXNG 实例地址")?;
+            let url = reqwest::Url::parse_with_params(
+                &format!("{}/search", endpoint.trim_end_matches('/')),
+                &[("q", query), ("format", "json")],
+            )
+            .map_err(|e| format!("SearXNG 地址无效: {}", e))?;
+            let mut request = client.get(url);
+            if let Some(key) = &config.api_key {
+                request = request.bearer_auth(key);
+            }
+            let body = send(request).await?;
+            Ok(hits(body.get("results"), "title", "content", limit))
+        }
+        "bing" => {
+            let key = require_key(config)?;
+            let count = limit.to_string();
+            let url = reqwest::Url::parse_with_params(
+                config.endpoint.as_deref().unwrap_or(BING_ENDPOINT),
+                &[("q", query), ("count", count.as_str()), ("textDecorations", "false")],
+            )
+            .map_err(|e| format!("Bing 搜索地址无效: {}", e))?;
+            let body = send(client.get(url).header("Ocp-Apim-Subscription-Key", key)).await?;
+            Ok(hits(body.pointer("/webPages/value"), "name", "snippet", limit))
+        }
+        "tavily" => {
+            let key = require_key(config)?;
+            let request = client
+                .post(config.endpoint.as_deref().unwrap_or(TAVILY_ENDPOINT))
+                .bearer_auth(key)
+                .json(&json!({ "query": query, "max_results": limit }));
+            let body = send(request).await?;
+            Ok(hits(body.get("results"), "title", "content", limit))
+        }
+        other => Err(format!("不支持的搜索服务: {}", other)),
+    }
+}
+
+/// 工具调用的返回内容：编号的结果列表，便于模型在回答中按编号引用来源
+pub fn tool_output(result: Result<Vec<SearchHit>, String>) -> String {
+    match result {
+        Ok(hits) if hits.is_empty() => json!({ "results": [], "message": "没有找到相关结果" }).to_string(),
+        Ok(hits) => {
+            let results: Vec<Value> = hits
+                .iter()
+                .enumerate()
+                .map(|(i, hit)| json!({ "index": i + 1, "title": hit.title, "url": hit.url, "snippet": hit.snippet }))
+                .collect();
+            json!({ "results": results }).to_string()
+        }
+        Err(e) => json!({ "error": e }).to_string(),
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -24,9 +26,15 @@ pub struct WorkspaceTabState {
     pub panel_state: TabPanelState,
 }
 
+/// 当前工作区文件格式版本；修改 WorkspaceState 结构时加一，并在 MIGRATIONS 中补充迁移
+pub const WORKSPACE_SCHEMA_VERSION: u32 = 2;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkspaceState {
+    /// 文件格式版本，旧文件没有该字段（视为 0）
+    #[serde(default)]
+    pub schema_version: u32,
     pub current_project_id: Option<String>,
     pub open_document_ids: Vec<String>,
     pub current_document_id: Option<String>,
@@ -58,6 +66,7 @@ pub struct UIState {
 impl Default for WorkspaceState {
     fn default() -> Self {
         Self {
+            schema_version: WORKSPACE_SCHEMA_VERSION,
             current_project_id: None,
             open_document_ids: Vec::new(),
             current_document_id: None,
@@ -77,6 +86,7 @@ impl Default for WorkspaceState {
     }
 }
 
+/// 写入工作区状态；先把上一份文件保留为 .bak，再通过临时文件原子替换
 pub fn save_workspace_state(state: &WorkspaceState, path: &PathBuf) -> Result<(), String> {
     // Ensure parent directory exists
     if let Some(parent) = path.parent() {
@@ -85,11 +95,96 @@ pub fn save_workspace_state(state: &WorkspaceState, path: &PathBuf) -> Result<()
 
     let json = serde_json::to_string_pretty(state)
         .map_err(|e| format!("Failed to serialize workspace state: {}", e))?;
-    fs::write(path, json)
+    if path.exists() {
+        let _ = fs::copy(path, path.with_extension("json.bak"));
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json)
+        .map_err(|e| format!("Failed to write workspace state: {}", e))?;
+    fs::rename(&tmp, path)
         .map_err(|e| format!("Failed to write workspace state: {}", e))?;
     Ok(())
 }
 
+/// 按版本顺序排列的迁移：第 i 项把版本 i 的文件升级为版本 i + 1
+const MIGRATIONS: &[fn(&mut Value)] = &[migrate_v0_tabs, migrate_v1_required_fields];
+
+/// v0：只有 openDocumentIds / currentDocumentId，没有标签页
+fn migrate_v0_tabs(value: &mut Value) {
+    let has_tabs = value.get("tabs").and_then(Value::as_array).is_some_and(|t| !t.is_empty());
+    if has_tabs {
+        return;
+    }
+    let doc_ids: Vec<String> = value
+        .get("openDocumentIds")
+        .and_then(Value::as_array)
+        .map(|ids| ids.iter().filter_map(|id| id.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+    let current = value.get("currentDocumentId").and_then(Value::as_str).map(str::to_string);
+    let tabs: Vec<Value> = doc_ids
+        .iter()
+        .map(|doc_id| json!({ "id": format!("tab-{}", doc_id), "documentId": doc_id, "panelState": {} }))
+        .collect();
+    value["activeTabId"] = match current.filter(|c| doc_ids.contains(c)) {
+        Some(doc_id) => json!(format!("tab-{}", doc_id)),
+        None => Value::Null,
+    };
+    value["tabs"] = Value::Array(tabs);
+}
+
+/// v1：必填字段缺失时整个文件解析失败，补上默认值
+fn migrate_v1_required_fields(value: &mut Value) {
+    let fill = |obj: &mut Value, key: &str, default: Value| {
+        if let Some(map) = obj.as_object_mut() {
+            map.entry(key).or_insert(default);
+        }
+    };
+    fill(value, "openDocumentIds", json!([]));
+    fill(value, "currentProjectId", Value::Null);
+    fill(value, "currentDocumentId", Value::Null);
+    fill(value, "lastSavedAt", json!(0));
+    if !value.get("uiState").is_some_and(Value::is_object) {
+        value["uiState"] = json!({});
+    }
+    fill(&mut value["uiState"], "sidebarOpen", json!(true));
+    fill(&mut value["uiState"], "chatOpen", json!(true));
+    if let Some(tabs) = value.get_mut("tabs").and_then(Value::as_array_mut) {
+        tabs.retain(|t| t.get("id").is_some_and(Value::is_string) && t.get("documentId").is_some_and(Value::is_string));
+        for tab in tabs {
+            if !tab.get("panelState").is_some_and(Value::is_object) {
+                tab["panelState"] = json!({});
+            }
+            let panel = &mut tab["panelState"];
+            fill(panel, "versionHistoryOpen", json!(false));
+            fill(panel, "chatOpen", json!(true));
+            fill(panel, "rightSidebarOpen", json!(true));
+        }
+    }
+}
+
+/// 把旧版本的工作区 JSON 依次迁移到当前版本，返回原版本号
+fn migrate_workspace(value: &mut Value) -> u32 {
+    let version = value.get("schemaVersion").and_then(Value::as_u64).unwrap_or(0) as u32;
+    for migration in MIGRATIONS.iter().skip(version as usize) {
+        migration(value);
+    }
+    if version < WORKSPACE_SCHEMA_VERSION {
+        value["schemaVersion"] = json!(WORKSPACE_SCHEMA_VERSION);
+    }
+    version
+}
+
+fn parse_workspace(json: &str) -> Result<(WorkspaceState, u32), String> {
+    let mut value: Value = serde_json::from_str(json)
+        .map_err(|e| format!("Failed to parse workspace state: {}", e))?;
+    let version = migrate_workspace(&mut value);
+    let state = serde_json::from_value(value)
+        .map_err(|e| format!("Failed to parse workspace state: {}", e))?;
+    Ok((state, version))
+}
+
+/// 读取工作区状态，旧版本文件迁移后写回（原文件保留为 .v{版本}.bak）；
+/// 文件损坏时回退到上一次保存的 .bak
 pub fn load_workspace_state(path: &PathBuf) -> Result<Option<WorkspaceState>, String> {
     if !path.exists() {
         return Ok(None);
@@ -97,8 +192,21 @@ pub fn load_workspace_state(path: &PathBuf) -> Result<Option<WorkspaceState>, St
 
     let json = fs::read_to_string(path)
         .map_err(|e| format!("Failed to read workspace state: {}", e))?;
-    let state: WorkspaceState = serde_json::from_str(&json)
-        .map_err(|e| format!("Failed to parse workspace state: {}", e))?;
+    let (state, version) = match parse_workspace(&json) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            let backup = fs::read_to_string(path.with_extension("json.bak")).map_err(|_| e.clone())?;
+            tracing::warn!("{}, restoring previous workspace state", e);
+            parse_workspace(&backup).map_err(|_| e)?
+        }
+    };
+
+    if version < WORKSPACE_SCHEMA_VERSION {
+        let backup = path.with_extension(format!("v{}.bak", version));
+        fs::copy(path, &backup).map_err(|e| format!("Failed to back up workspace state: {}", e))?;
+        save_workspace_state(&state, path)?;
+        tracing::info!("Migrated workspace state from schema v{} to v{}", version, WORKSPACE_SCHEMA_VERSION);
+    }
     Ok(Some(state))
 }
 
@@ -109,3 +217,118 @@ pub fn clear_workspace_state(path: &PathBuf) -> Result<(), String> {
     }
     Ok(())
 }
+
+/// 文档的编辑位置（光标、选区、滚动）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentViewState {
+    pub cursor_offset: usize,
+    #[serde(default)]
+    pub selection_end: Option<usize>,
+    #[serde(default)]
+    pub scroll_top: f64,
+}
+
+/// 项目会话：切换项目时保存，重新打开项目时恢复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSession {
+    #[serde(default)]
+    pub tabs: Vec<WorkspaceTabState>,
+    #[serde(default)]
+    pub active_tab_id: Option<String>,
+    /// 按文档 ID 记录的编辑位置
+    #[serde(default)]
+    pub view_states: HashMap<String, DocumentViewState>,
+    #[serde(default)]
+    pub ui_state: Option<UIState>,
+    #[serde(default)]
+    pub saved_at: i64,
+}
+
+/// 项目会话文件：Projects/{项目ID}/session.json
+pub fn project_session_path(projects_dir: &Path, project_id: &str) -> PathBuf {
+    projects_dir.join(project_id).join("session.json")
+}
+
+pub fn save_project_session(path: &Path, session: &ProjectSession) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(session)
+        .map_err(|e| format!("Failed to serialize project session: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write project session: {}", e))
+}
+
+/// 读取项目会话，并去掉已被删除的文档对应的标签页和编辑位置
+pub fn load_project_session(path: &Path) -> Option<ProjectSession> {
+    let json = fs::read_to_string(path).ok()?;
+    let mut session: ProjectSession = serde_json::from_str(&json)
+        .map_err(|e| tracing::warn!("Failed to parse project session: {}", e))
+        .ok()?;
+
+    let documents_dir = path.parent()?.join("documents");
+    let exists = |doc_id: &str| documents_dir.join(format!("{}.json", doc_id)).exists();
+    session.tabs.retain(|t| exists(&t.document_id));
+    session.view_states.retain(|doc_id, _| exists(doc_id));
+    if session
+        .active_tab_id
+        .as_ref()
+        .is_some_and(|id| !session.tabs.iter().any(|t| &t.id == id))
+    {
+        session.active_tab_id = session.tabs.first().map(|t| t.id.clone());
+    }
+    Some(session)
+}
+
+/// 未保存的编辑内容草稿：按标签页写入 drafts/{标签页ID}.json，崩溃或强制退出后恢复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Draft {
+    pub tab_id: String,
+    pub project_id: String,
+    pub document_id: String,
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub author_notes: String,
+    #[serde(default)]
+    pub ai_generated_content: String,
+    #[serde(default)]
+    pub staged_at: i64,
+}
+
+fn draft_path(dir: &Path, tab_id: &str) -> Result<PathBuf, String> {
+    if tab_id.is_empty() || !tab_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid tab id: {}", tab_id));
+    }
+    Ok(dir.join(format!("{}.json", tab_id)))
+}
+
+pub fn stage_draft(dir: &Path, draft: &Draft) -> Result<(), String> {
+    let path = draft_path(dir, &draft.tab_id)?;
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+    let json = serde_json::to_string(draft).map_err(|e| format!("Failed to serialize draft: {}", e))?;
+    // 先写临时文件再改名，避免写入中途崩溃留下损坏的草稿
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| format!("Failed to write draft: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to write draft: {}", e))
+}
+
+pub fn discard_draft(dir: &Path, tab_id: &str) -> Result<(), String> {
+    let path = draft_path(dir, tab_id)?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to remove draft: {}", e))?;
+    }
+    Ok(())
+}
+
+pub fn list_drafts(dir: &Path) -> Vec<Draft> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    entries
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|e| fs::read_to_string(e.path()).ok())
+        .filter_map(|json| serde_json::from_str(&json).ok())
+        .collect()
+}
@@ -1,5 +1,5 @@
 import { useState, useRef, useEffect } from 'react';
-import { Send, Sparkles, X, ChevronDown, ChevronUp, FileText, BookOpen, Square, Eraser, Trash2, Copy, Check, ArrowUpToLine, MessageSquareText, PenLine, Wand2 } from 'lucide-react';
+import { Send, Sparkles, X, ChevronDown, ChevronUp, FileText, BookOpen, Square, Eraser, Trash2, Copy, Check, ArrowUpToLine, MessageSquareText, PenLine, Wand2, FilePlus2 } from 'lucide-react';
 import { Button } from '../ui/button';
 import { useAppStore } from '@/stores/useAppStore';
 import { useSettingsStore } from '@/stores/useSettingsStore';
@@ -164,6 +164,7 @@ export function ChatPanel({ tabId, onClose, simpleMode }: ChatPanelProps) {
     setAiStreaming,
     saveDocument,
     clearAiMessages,
+    exportChatToDocument,
     createVersion,
     updateDocumentInMemory
   } = useAppStore();
@@ -336,7 +337,7 @@ export function ChatPanel({ tabId, onClose, simpleMode }: ChatPanelProps) {
     // Check if API key is configured
     const aiSettings = useSettingsStore.getState().ai;
     const activeService = aiSettings.services.find(s => s.id === aiSettings.activeServiceId && s.enabled);
-    if (!activeService?.apiKey) {
+    if (!activeService?.hasApiKey) {
       const errorMessage = {
         role: 'assistant' as const,
         content: t('chat.configureApiKeyMsg', { defaultValue: '请先配置 API Key 才能使用 AI 生成功能。\n\n请点击聊天面板下方的“设置”按钮，在设置面板的 AI 标签页中配置您的 API Key。' }),
@@ -778,7 +779,7 @@ export function ChatPanel({ tabId, onClose, simpleMode }: ChatPanelProps) {
                     </Button>
                   )}
                 </div>
-                {!settingsStore.ai.services.some(s => s.id === settingsStore.ai.activeServiceId && s.enabled && s.apiKey) && (
+                {!settingsStore.ai.services.some(s => s.id === settingsStore.ai.activeServiceId && s.enabled && s.hasApiKey) && (
                   <div className="text-xs text-amber-500 dark:text-amber-400">{t('chat.configureApiWarning', { defaultValue: '⚠️ 请先配置 API 服务' })}</div>
                 )}
               </div>
@@ -796,16 +797,28 @@ export function ChatPanel({ tabId, onClose, simpleMode }: ChatPanelProps) {
               <span className="text-xs font-medium text-muted-foreground">
                 {t('chat.chatHistory', { defaultValue: '对话记录 ({{count}})', count: aiMessages.length })}
               </span>
-              <Button
-                variant="ghost"
-                size="icon"
-                className="h-6 w-6 text-muted-foreground"
-                onClick={() => clearAiMessages(effectiveTabId)}
-                disabled={isCurrentTabStreaming}
-                title={t('chat.clearChat', { defaultValue: '清空对话' })}
-              >
-                <Eraser className="h-3 w-3" />
-              </Button>
+              <div className="flex items-center gap-1">
+                <Button
+                  variant="ghost"
+                  size="icon"
+                  className="h-6 w-6 text-muted-foreground"
+                  onClick={() => exportChatToDocument(effectiveTabId).catch(err => console.error('[Chat] Export to document failed:', err))}
+                  disabled={isCurrentTabStreaming}
+                  title={t('chat.saveAsDocument', { defaultValue: '保存为文档' })}
+                >
+                  <FilePlus2 className="h-3 w-3" />
+                </Button>
+                <Button
+                  variant="ghost"
+                  size="icon"
+                  className="h-6 w-6 text-muted-foreground"
+                  onClick={() => clearAiMessages(effectiveTabId)}
+                  disabled={isCurrentTabStreaming}
+                  title={t('chat.clearChat', { defaultValue: '清空对话' })}
+                >
+                  <Eraser className="h-3 w-3" />
+                </Button>
+              </div>
             </div>
           </div>
         )}
@@ -814,7 +827,7 @@ export function ChatPanel({ tabId, onClose, simpleMode }: ChatPanelProps) {
           {aiMessages.length === 0 ? (
             <div className="text-center text-muted-foreground py-8">
               <p className="text-sm">{t('chat.startChat', { defaultValue: '与 AI 助手开始对话' })}</p>
-              {!settingsStore.ai.services.some(s => s.id === settingsStore.ai.activeServiceId && s.enabled && s.apiKey) && (
+              {!settingsStore.ai.services.some(s => s.id === settingsStore.ai.activeServiceId && s.enabled && s.hasApiKey) && (
                 <p className="text-xs mt-2 text-amber-500 dark:text-amber-400">{t('chat.configureApiFirst', { defaultValue: '请先在设置中配置 API 服务' })}</p>
               )}
             </div>
@@ -1,4 +1,4 @@
-import { useState, useCallback } from 'react';
+import { useState, useCallback, useEffect } from 'react';
 import { Paperclip, Plus, X, ChevronDown, ChevronUp, Eye, Loader2 } from 'lucide-react';
 import { invoke } from '@tauri-apps/api/core';
 import { open } from '@tauri-apps/plugin-dialog';
@@ -10,9 +10,30 @@ import type { Attachment } from '@aidocplus/shared-types';
 interface AttachmentPanelProps {
   attachments: Attachment[];
   onAttachmentsChange: (attachments: Attachment[]) => void;
+  /** 提供时为图片 / PDF 附件显示缩略图 */
+  projectId?: string;
 }
 
-export function AttachmentPanel({ attachments, onAttachmentsChange }: AttachmentPanelProps) {
+const THUMBNAIL_TYPES = ['png', 'jpg', 'jpeg', 'gif', 'pdf'];
+
+/** 附件缩略图：由后端生成并缓存，失败（如附件尚未保存）时回退为图标 */
+function AttachmentThumbnail({ projectId, attachment, fallback }: { projectId: string; attachment: Attachment; fallback: string }) {
+  const [src, setSrc] = useState<string | null>(null);
+
+  useEffect(() => {
+    let cancelled = false;
+    setSrc(null);
+    invoke<string>('get_attachment_thumbnail', { projectId, attachmentId: attachment.id, size: 64 })
+      .then(data => { if (!cancelled) setSrc(data); })
+      .catch(() => {});
+    return () => { cancelled = true; };
+  }, [projectId, attachment.id, attachment.filePath]);
+
+  if (!src) return <span>{fallback}</span>;
+  return <img src={src} alt="" className="h-6 w-6 rounded object-cover flex-shrink-0" />;
+}
+
+export function AttachmentPanel({ attachments, onAttachmentsChange, projectId }: AttachmentPanelProps) {
   const { t } = useTranslation();
   const [collapsed, setCollapsed] = useState(attachments.length === 0);
   const [adding, setAdding] = useState(false);
@@ -155,7 +176,11 @@ export function AttachmentPanel({ attachments, onAttachmentsChange }: Attachment
             attachments.map((att) => (
               <div key={att.id}>
                 <div className="flex items-center gap-2 py-1 px-2 rounded hover:bg-accent/50 group text-xs">
-                  <span>{fileIcon(att.fileType)}</span>
+                  {projectId && THUMBNAIL_TYPES.includes(att.fileType.toLowerCase()) ? (
+                    <AttachmentThumbnail projectId={projectId} attachment={att} fallback={fileIcon(att.fileType)} />
+                  ) : (
+                    <span>{fileIcon(att.fileType)}</span>
+                  )}
                   <span className="flex-1 truncate font-medium" title={att.filePath}>
                     {att.fileName}
                   </span>
@@ -10,6 +10,7 @@ import { useState, useEffect, useRef, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { message, save } from '@tauri-apps/plugin-dialog';
 import { useSettingsStore } from '@/stores/useSettingsStore';
+import { reportActiveView } from '@/hooks/useMenuCheckState';
 import { useTranslation } from '@/i18n';
 import {
   DropdownMenu,
@@ -84,6 +85,12 @@ export function EditorPanel({
     if (isAiStreaming) setEditorViewState('ai-max');
   }, [isAiStreaming]);
 
+  // 活动标签的视图同步到视图菜单的勾选项
+  const isActiveTab = useAppStore(s => s.activeTabId) === tabId;
+  useEffect(() => {
+    if (isActiveTab) reportActiveView(activeView);
+  }, [isActiveTab, activeView]);
+
   // 监听系统菜单的视图切换事件
   useEffect(() => {
     const isActive = () => tabId === useAppStore.getState().activeTabId;
@@ -141,7 +148,8 @@ export function EditorPanel({
   };
 
   const handleSave = async () => {
-    if (!document || isSaving) return;
+    // 锁定的文档不可保存（后端同样会拒绝）
+    if (!document || isSaving || document.locked) return;
 
     setIsSaving(true);
     try {
@@ -200,7 +208,12 @@ export function EditorPanel({
 
     try {
       const suffix = contentOverride ? t('editor.composedSuffix', { defaultValue: '_合并' }) : t('editor.aiSuffix', { defaultValue: '_AI' });
-      const defaultFileName = `${document.title}${suffix}.${format}`;
+      const defaultFileName = await invoke<string>('suggest_export_filename', {
+        documentId: document.id,
+        projectId: document.projectId,
+        format,
+        suffix,
+      }).catch(() => `${document.title}${suffix}.${format}`);
       const filePath = await save({
         defaultPath: defaultFileName,
         filters: [
@@ -259,6 +272,69 @@ export function EditorPanel({
   const handleExport = (format: string) => doExport('export_document', format);
   const handleNativeExportComposed = (format: string) => doExport('export_document_native', format, composedContent);
 
+  const handlePrint = async () => {
+    if (!document) return;
+    await handleSave();
+    try {
+      await invoke('print_document', { documentId: document.id, projectId: document.projectId });
+    } catch (error) {
+      console.error('Print error:', error);
+      const errorMessage = typeof error === 'string' ? error : error instanceof Error ? error.message : 'Unknown error';
+      await message(t('editor.printFailed', { defaultValue: '打印失败: {{error}}', error: errorMessage }), { title: t('editor.printErrorTitle', { defaultValue: '打印错误' }), kind: 'error' });
+    }
+  };
+
+  // 公众号编辑器只保留行内样式，由后端转换后直接写入剪贴板
+  const handleCopyWechat = async () => {
+    if (!document) return;
+    await handleSave();
+    try {
+      await invoke('copy_wechat_html', { documentId: document.id, projectId: document.projectId });
+      await message(t('editor.copiedForWechat', { defaultValue: '已复制，可直接粘贴到公众号编辑器' }), { kind: 'info' });
+    } catch (error) {
+      console.error('Copy for WeChat error:', error);
+      const errorMessage = typeof error === 'string' ? error : error instanceof Error ? error.message : 'Unknown error';
+      await message(t('editor.exportFailedMsg', { defaultValue: '导出失败: {{error}}', error: errorMessage }), { title: t('editor.exportErrorTitle', { defaultValue: '导出错误' }), kind: 'error' });
+    }
+  };
+
+  // 剪贴板图片由后端直接读取并存为附件，避免 base64 经 IPC 传输
+  const handlePasteImage = async (): Promise<string | null> => {
+    if (!document) return null;
+    try {
+      const result = await invoke<{ markdown: string; attachment: Attachment }>('paste_clipboard_image', {
+        projectId: document.projectId,
+        documentId: document.id,
+      });
+      onAttachmentsChange([...attachments, result.attachment]);
+      return result.markdown;
+    } catch (error) {
+      console.error('Paste image error:', error);
+      return null;
+    }
+  };
+
+  // 智能粘贴：Word / 网页 / 公众号内容由后端转为 Markdown，图片存为附件
+  const handleSmartPaste = async (): Promise<string | null> => {
+    if (!document) return null;
+    try {
+      const result = await invoke<{ markdown: string; attachments: Attachment[]; failedImages: string[] }>('convert_clipboard_to_markdown', {
+        projectId: document.projectId,
+        documentId: document.id,
+      });
+      if (result.attachments.length > 0) {
+        onAttachmentsChange([...attachments, ...result.attachments]);
+      }
+      if (result.failedImages.length > 0) {
+        console.warn('[SmartPaste] Images kept as remote references:', result.failedImages);
+      }
+      return result.markdown;
+    } catch (error) {
+      console.error('Smart paste error:', error);
+      return null;
+    }
+  };
+
   const handleExportAndOpen = async (format: string, appName?: string) => {
     if (!document) return;
     // 先保存当前文档
@@ -678,13 +754,15 @@ export function EditorPanel({
                     onChange={onAiContentChange}
                     placeholder={t('editor.aiContentPlaceholder', { defaultValue: '正文内容将出现在这里...' })}
                     theme={effectiveTheme}
-                    editable={!isAiStreaming}
+                    editable={!isAiStreaming && !document.locked}
                     editorId={`ai-content-${document.id}`}
                     importSources={{ document }}
                     exportCallbacks={{
                       onNativeExport: handleNativeExport,
                       onNativeExportComposed: handleNativeExportComposed,
                       onExportAndOpen: handleExportAndOpen,
+                      onPrint: handlePrint,
+                      onCopyWechat: handleCopyWechat,
                       composedContent,
                     }}
                   />
@@ -744,8 +822,11 @@ export function EditorPanel({
                     onChange={onContentChange}
                     placeholder={t('editor.originalContentPlaceholder', { defaultValue: '在此输入素材内容... (支持 Markdown)' })}
                     theme={effectiveTheme}
+                    editable={!document.locked}
                     editorId={`original-content-${document.id}`}
                     importSources={{ aiContent, document }}
+                    onPasteImage={handlePasteImage}
+                    onSmartPaste={handleSmartPaste}
                   />
                 </div>
               )}
@@ -756,6 +837,7 @@ export function EditorPanel({
           <AttachmentPanel
             attachments={attachments}
             onAttachmentsChange={onAttachmentsChange}
+            projectId={document.projectId}
           />
           </div>
         )}
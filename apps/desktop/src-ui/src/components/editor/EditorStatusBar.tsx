@@ -1,4 +1,5 @@
 import { useTranslation } from '@/i18n';
+import type { ReadabilityReport } from '@aidocplus/shared-types';
 
 interface EditorStatusBarProps {
   lines: number;
@@ -7,14 +8,26 @@ interface EditorStatusBarProps {
   cursorLine?: number;
   cursorCol?: number;
   selectionChars?: number;
+  /** 后端可读性分析结果，未返回前按字符数估算阅读时长 */
+  readability?: ReadabilityReport | null;
 }
 
-export function EditorStatusBar({ lines, words, chars, cursorLine, cursorCol, selectionChars }: EditorStatusBarProps) {
+export function EditorStatusBar({ lines, words, chars, cursorLine, cursorCol, selectionChars, readability }: EditorStatusBarProps) {
   const { t } = useTranslation();
-  const readingTime = Math.max(1, Math.ceil(chars / 300));
+  const readingTime = Math.max(1, Math.ceil(readability ? readability.readingMinutes : chars / 300));
+  const detail = readability
+    ? t('editor.statusBar.readability', {
+        defaultValue: '{{sentences}} 句 · 平均句长 {{avg}} · 长句 {{long}} · 被动句 {{passive}} · 冗余词 {{fillers}}',
+        sentences: readability.sentences,
+        avg: readability.avgSentenceLength.toFixed(1),
+        long: readability.longSentences.length,
+        passive: readability.passiveCount,
+        fillers: readability.fillerCount,
+      })
+    : undefined;
   return (
     <div className="flex items-center justify-between px-4 py-2 border-t text-xs bg-background text-muted-foreground flex-shrink-0">
-      <div>
+      <div title={detail}>
         {t('editor.statusBar.stats', { defaultValue: '{{lines}} 行 · {{words}} 词 · {{chars}} 字符 · 约 {{readingTime}} 分钟', lines, words, chars, readingTime })}
         {selectionChars ? t('editor.statusBar.selected', { defaultValue: ' · 选中 {{count}}', count: selectionChars }) : ''}
       </div>
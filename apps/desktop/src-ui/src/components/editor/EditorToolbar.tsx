@@ -41,6 +41,8 @@ import {
   AArrowUp,
   Download,
   ExternalLink,
+  Printer,
+  Wand2,
 } from 'lucide-react';
 import React, { useState, useCallback } from 'react';
 import { EditorView } from '@codemirror/view';
@@ -132,6 +134,15 @@ function cmGetSelection(view: EditorView): string {
 }
 
 // 清除选中文本中的 Markdown 格式标记
+/** 规范化整篇 Markdown（标题、列表、表格、中英文间距、空白） */
+async function cmFormatDocument(view: EditorView) {
+  const content = view.state.doc.toString();
+  const result = await invoke<{ content: string; changed: boolean }>('format_markdown', { content });
+  // 格式化期间内容已变化则放弃，避免覆盖用户输入
+  if (!result.changed || view.state.doc.toString() !== content) return;
+  view.dispatch({ changes: { from: 0, to: view.state.doc.length, insert: result.content } });
+}
+
 function cmClearFormat(view: EditorView) {
   try {
     const { from, to } = view.state.selection.main;
@@ -189,6 +200,8 @@ export interface ExportCallbacks {
   onNativeExport: (format: string) => void;
   onNativeExportComposed: (format: string) => void;
   onExportAndOpen: (format: string, appName?: string) => void;
+  onPrint?: () => void;
+  onCopyWechat?: () => void;
   composedContent?: string;
 }
 
@@ -311,7 +324,9 @@ export function EditorToolbar({ cmViewRef, outlineOpen, onToggleOutline, viewMod
       {s('strikethrough') && <FeedbackButton onClick={() => doWrap('~~', '~~', t('editor.toolbar.strikethroughPlaceholder', { defaultValue: '删除线文本' }))} icon={<Strikethrough className="h-4 w-4" />} tooltip={t('editor.toolbar.strikethroughCmd', { defaultValue: '删除线 (Cmd+Shift+X)' })} doneTooltip={t('editor.toolbar.strikethroughDone', { defaultValue: '已添加删除线' })} />}
       {s('clearFormat') && <FeedbackButton onClick={() => runAction((v) => cmClearFormat(v))} icon={<RemoveFormatting className="h-4 w-4" />} tooltip={t('editor.toolbar.clearFormat', { defaultValue: '清除格式' })} doneTooltip={t('editor.toolbar.clearFormatDone', { defaultValue: '已清除' })} />}
 
-      <Sep left={[s('bold'), s('italic'), s('strikethrough'), s('clearFormat')]} right={[s('unorderedList'), s('orderedList'), s('taskList'), s('quote'), s('horizontalRule')]} />
+      {s('formatDocument') && <FeedbackButton onClick={() => runAction((v) => { cmFormatDocument(v).catch((e) => console.error('Format error:', e)); })} icon={<Wand2 className="h-4 w-4" />} tooltip={t('editor.toolbar.formatDocument', { defaultValue: '规范化格式' })} doneTooltip={t('editor.toolbar.formatDocumentDone', { defaultValue: '已规范化' })} />}
+
+      <Sep left={[s('bold'), s('italic'), s('strikethrough'), s('clearFormat'), s('formatDocument')]} right={[s('unorderedList'), s('orderedList'), s('taskList'), s('quote'), s('horizontalRule')]} />
 
       {/* ── 5. 段落结构 ── */}
       {s('unorderedList') && <FeedbackButton onClick={() => doPrefix('- ')} icon={<List className="h-4 w-4" />} tooltip={t('editor.toolbar.unorderedList', { defaultValue: '无序列表' })} doneTooltip={t('editor.toolbar.inserted', { defaultValue: '已插入' })} />}
@@ -469,6 +484,21 @@ export function EditorToolbar({ cmViewRef, outlineOpen, onToggleOutline, viewMod
                 <FileText className="h-4 w-4 mr-2" />
                 {t('editor.exportAsTxt', { defaultValue: '导出为纯文本 (.txt)' })}
               </DropdownMenuItem>
+              {exportCallbacks.onCopyWechat && (
+                <DropdownMenuItem onClick={() => exportCallbacks.onCopyWechat?.()}>
+                  <FileText className="h-4 w-4 mr-2" />
+                  {t('editor.copyForWechat', { defaultValue: '复制为公众号格式' })}
+                </DropdownMenuItem>
+              )}
+              {exportCallbacks.onPrint && (
+                <>
+                  <DropdownMenuSeparator />
+                  <DropdownMenuItem onClick={() => exportCallbacks.onPrint?.()}>
+                    <Printer className="h-4 w-4 mr-2" />
+                    {t('editor.print', { defaultValue: '打印...' })}
+                  </DropdownMenuItem>
+                </>
+              )}
               {exportCallbacks.composedContent?.trim() && (
                 <>
                   <DropdownMenuSeparator />
@@ -18,6 +18,7 @@ import { highlightSelectionMatches, searchKeymap } from '@codemirror/search';
 import { history, historyKeymap, defaultKeymap } from '@codemirror/commands';
 import { closeBrackets, closeBracketsKeymap, autocompletion } from '@codemirror/autocomplete';
 import { oneDark } from '@codemirror/theme-one-dark';
+import { invoke } from '@tauri-apps/api/core';
 import { cn } from '@/lib/utils';
 import { useEditorSettings } from '@/stores/useSettingsStore';
 import { EditorToolbar } from './EditorToolbar';
@@ -27,8 +28,9 @@ import { markdownCompletions } from './markdownCompletions';
 import { checkboxWidgetExtension } from './extensions/checkboxWidget';
 import { linkHoverTooltip } from './extensions/linkTooltip';
 import { markdownLinterExtension } from './extensions/markdownLinter';
+import { spellCheckerExtension } from './extensions/spellChecker';
 import { lintKeymap } from '@codemirror/lint';
-import type { Document } from '@aidocplus/shared-types';
+import type { Document, ReadabilityReport } from '@aidocplus/shared-types';
 import { DocumentOutline } from './DocumentOutline';
 
 // 自定义高亮样式：基于 defaultHighlightStyle，去掉 heading 下划线，标题分级字号
@@ -81,6 +83,17 @@ interface MarkdownEditorProps {
   editorId?: string;
   importSources?: ImportSources;
   exportCallbacks?: import('./EditorToolbar').ExportCallbacks;
+  /** 粘贴剪贴板图片：由后端保存为附件，返回要插入的 Markdown */
+  onPasteImage?: () => Promise<string | null>;
+  /** 智能粘贴（Mod-Shift-V）：剪贴板中的 HTML / RTF 转为 Markdown */
+  onSmartPaste?: () => Promise<string | null>;
+}
+
+// 拼写检查由后端词典完成，关闭浏览器自带检查避免重复标记
+const NATIVE_SPELLCHECK_OFF = EditorView.contentAttributes.of({ spellcheck: 'false', autocorrect: 'off', autocapitalize: 'off' });
+
+function spellCheckExt(enabled: boolean | undefined, projectId?: string) {
+  return enabled ? [NATIVE_SPELLCHECK_OFF, spellCheckerExtension(projectId)] : NATIVE_SPELLCHECK_OFF;
 }
 
 // 创建一组 Compartment 实例（每个编辑器实例独立）
@@ -118,6 +131,8 @@ export function MarkdownEditor({
   editorId: _editorId,
   importSources,
   exportCallbacks,
+  onPasteImage,
+  onSmartPaste,
 }: MarkdownEditorProps) {
   const editorDivRef = useRef<HTMLDivElement>(null);
   const previewRef = useRef<HTMLDivElement>(null);
@@ -128,6 +143,10 @@ export function MarkdownEditor({
   onChangeRef.current = onChange;
   const onCursorLineChangeRef = useRef(onCursorLineChange);
   onCursorLineChangeRef.current = onCursorLineChange;
+  const onPasteImageRef = useRef(onPasteImage);
+  onPasteImageRef.current = onPasteImage;
+  const onSmartPasteRef = useRef(onSmartPaste);
+  onSmartPasteRef.current = onSmartPaste;
   const lastEmittedRef = useRef(value);
   const docContentTimerRef = useRef<ReturnType<typeof setTimeout> | null>(null);
 
@@ -224,6 +243,19 @@ export function MarkdownEditor({
         return true;
       },
     },
+    {
+      key: 'Mod-Shift-v',
+      run: (view) => {
+        const smartPaste = onSmartPasteRef.current;
+        if (!smartPaste) return false;
+        smartPaste().then(md => {
+          if (!md) return;
+          const { from, to } = view.state.selection.main;
+          view.dispatch({ changes: { from, to, insert: md }, selection: { anchor: from + md.length } });
+        });
+        return true;
+      },
+    },
   ]), [editorSettings.tabSize]);
 
   // 创建 EditorView（组件挂载时执行一次，通过 key prop 在文档切换时重新挂载）
@@ -267,11 +299,7 @@ export function MarkdownEditor({
       compRef.current.editable.of(EditorView.editable.of(editable)),
       compRef.current.theme.of(theme === 'dark' ? oneDark : []),
       compRef.current.tabSize.of(EditorState.tabSize.of(editorSettings.tabSize)),
-      compRef.current.spellCheck.of(
-        editorSettings.spellCheck
-          ? EditorView.contentAttributes.of({ spellcheck: 'true' })
-          : EditorView.contentAttributes.of({ spellcheck: 'false', autocorrect: 'off', autocapitalize: 'off' })
-      ),
+      compRef.current.spellCheck.of(spellCheckExt(editorSettings.spellCheck, importSources?.document?.projectId)),
       compRef.current.highlightActiveLine.of(
         editorSettings.highlightActiveLine !== false
           ? [highlightActiveLine(), highlightActiveLineGutter()]
@@ -311,6 +339,17 @@ export function MarkdownEditor({
       // --- 粘贴 URL 自动转链接 ---
       EditorView.domEventHandlers({
         paste(event, view) {
+          const items = Array.from(event.clipboardData?.items ?? []);
+          const pasteImage = onPasteImageRef.current;
+          if (pasteImage && items.some(item => item.type.startsWith('image/'))) {
+            event.preventDefault();
+            pasteImage().then(md => {
+              if (!md) return;
+              const { from, to } = view.state.selection.main;
+              view.dispatch({ changes: { from, to, insert: md }, selection: { anchor: from + md.length } });
+            });
+            return true;
+          }
           const clipText = event.clipboardData?.getData('text/plain')?.trim();
           if (!clipText || !/^https?:\/\/\S+$/.test(clipText)) return false;
           const { from, to } = view.state.selection.main;
@@ -399,11 +438,7 @@ export function MarkdownEditor({
       c.lineWrapping.reconfigure(editorSettings.wordWrap ? EditorView.lineWrapping : []),
       c.editable.reconfigure(EditorView.editable.of(editable)),
       c.theme.reconfigure(theme === 'dark' ? oneDark : []),
-      c.spellCheck.reconfigure(
-        editorSettings.spellCheck
-          ? EditorView.contentAttributes.of({ spellcheck: 'true' })
-          : EditorView.contentAttributes.of({ spellcheck: 'false', autocorrect: 'off', autocapitalize: 'off' })
-      ),
+      c.spellCheck.reconfigure(spellCheckExt(editorSettings.spellCheck, importSources?.document?.projectId)),
       c.highlightActiveLine.reconfigure(
         editorSettings.highlightActiveLine !== false
           ? [highlightActiveLine(), highlightActiveLineGutter()]
@@ -449,6 +484,17 @@ export function MarkdownEditor({
   const wordCount = docContent.split(/\s+/).filter((w: string) => w).length;
   const lineCount = docContent.split('\n').length;
 
+  // 可读性统计（后端计算，输入停止 1 秒后刷新）
+  const [readability, setReadability] = useState<ReadabilityReport | null>(null);
+  useEffect(() => {
+    const timer = setTimeout(() => {
+      invoke<ReadabilityReport>('analyze_readability', { content: docContent })
+        .then(setReadability)
+        .catch(() => setReadability(null));
+    }, 1000);
+    return () => clearTimeout(timer);
+  }, [docContent]);
+
   const showPreview = viewMode === 'preview' || viewMode === 'split';
 
   // 分屏滚动同步：编辑区 → 预览区
@@ -536,6 +582,7 @@ export function MarkdownEditor({
         cursorLine={cursorInfo.line}
         cursorCol={cursorInfo.col}
         selectionChars={cursorInfo.selChars}
+        readability={readability}
       />
     </div>
   );
@@ -0,0 +1,55 @@
+import { forceLinting, linter } from '@codemirror/lint';
+import { invoke } from '@tauri-apps/api/core';
+import i18n from '@/i18n';
+import type { Diagnostic } from '@codemirror/lint';
+import type { EditorView } from '@codemirror/view';
+
+interface SpellingIssue {
+  from: number;
+  to: number;
+  word: string;
+  suggestions: string[];
+}
+
+/**
+ * 拼写检查（词典在后端加载，前端只接收错误位置与建议）
+ * 后端没有可用词典时静默不标记
+ */
+export function spellCheckerExtension(projectId?: string) {
+  const source = async (view: EditorView): Promise<Diagnostic[]> => {
+    const text = view.state.doc.toString();
+    if (!text.trim()) return [];
+    let issues: SpellingIssue[];
+    try {
+      issues = await invoke<SpellingIssue[]>('check_spelling', { text, projectId: projectId ?? null });
+    } catch {
+      return [];
+    }
+    // 检查期间文档已变化则丢弃结果，等待下一轮
+    if (view.state.doc.toString() !== text) return [];
+    return issues.map((issue) => ({
+      from: issue.from,
+      to: issue.to,
+      severity: 'info' as const,
+      source: 'spellcheck',
+      message: i18n.t('editor.spellingIssue', { defaultValue: '拼写可能有误: {{word}}', word: issue.word }),
+      actions: [
+        ...issue.suggestions.map((s) => ({
+          name: s,
+          apply: (v: EditorView, from: number, to: number) => {
+            v.dispatch({ changes: { from, to, insert: s } });
+          },
+        })),
+        {
+          name: i18n.t('editor.addToDictionary', { defaultValue: '添加到词典' }),
+          apply: (v: EditorView) => {
+            invoke('add_custom_word', { word: issue.word, projectId: projectId ?? null })
+              .then(() => forceLinting(v))
+              .catch(() => {});
+          },
+        },
+      ],
+    }));
+  };
+  return linter(source, { delay: 800 });
+}
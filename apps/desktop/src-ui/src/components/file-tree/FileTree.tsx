@@ -1,5 +1,5 @@
 import { useAppStore } from '@/stores/useAppStore';
-import { File, Folder, FolderOpen, Plus, Trash2, X, Check, Edit2, Download, FilePlus, Copy, ArrowUpDown, ArrowUp, ArrowDown, GripVertical, ChevronsDownUp, ChevronsUpDown, LayoutTemplate } from 'lucide-react';
+import { File, Folder, FolderOpen, Plus, Trash2, X, Check, Edit2, Download, FilePlus, Copy, ArrowUpDown, ArrowUp, ArrowDown, GripVertical, ChevronsDownUp, ChevronsUpDown, LayoutTemplate, Lock, Unlock } from 'lucide-react';
 import {
   DndContext,
   closestCenter,
@@ -38,7 +38,7 @@ type SortDirection = 'asc' | 'desc';
 
 export function FileTree({ sidebarOpen }: FileTreeProps) {
   const { t } = useTranslation();
-  const { projects, currentProject, documents, currentDocument, openProject, createProject, deleteProject, renameProject, openTab, renameDocument, deleteDocument, createDocument, loadDocuments, error, isLoading } = useAppStore();
+  const { projects, currentProject, documents, currentDocument, openProject, createProject, deleteProject, renameProject, openTab, renameDocument, deleteDocument, setDocumentLocked, createDocument, loadDocuments, error, isLoading } = useAppStore();
   const [expandedProjects, setExpandedProjects] = useState<Set<string>>(new Set());
   const [isCreating, setIsCreating] = useState(false);
   const [newProjectName, setNewProjectName] = useState('');
@@ -745,7 +745,20 @@ export function FileTree({ sidebarOpen }: FileTreeProps) {
                                 >
                                   {doc.title}
                                 </span>
+                                {doc.locked && <Lock className="h-3 w-3 text-muted-foreground shrink-0 group-hover:hidden" />}
                                 <div className="flex items-center gap-0.5 opacity-0 group-hover:opacity-100">
+                                  <Button
+                                    variant="ghost"
+                                    size="icon"
+                                    onClick={(e) => {
+                                      e.stopPropagation();
+                                      setDocumentLocked(project.id, doc.id, !doc.locked);
+                                    }}
+                                    className="h-6 w-6 p-0"
+                                    title={doc.locked ? t('fileTree.unlockDocument', { defaultValue: '解锁文档' }) : t('fileTree.lockDocument', { defaultValue: '锁定文档' })}
+                                  >
+                                    {doc.locked ? <Unlock className="h-3 w-3" /> : <Lock className="h-3 w-3" />}
+                                  </Button>
                                   <Button
                                     variant="ghost"
                                     size="icon"
@@ -2,6 +2,9 @@ import { useState, useCallback, useEffect } from 'react';
 import { useAppStore } from '@/stores/useAppStore';
 import { useTranslation } from '@/i18n';
 import { useMenuEvents } from '@/hooks/useMenuEvents';
+import { useMenuCheckState } from '@/hooks/useMenuCheckState';
+import { useDeepLinks } from '@/hooks/useDeepLinks';
+import { usePluginTools } from '@/hooks/usePluginTools';
 import { FileTree } from '../file-tree/FileTree';
 import { TabArea } from '../tabs/TabArea';
 import { SettingsPanel } from '../settings/SettingsPanel';
@@ -30,6 +33,9 @@ export function MainLayout() {
 
   // 监听原生系统菜单事件
   useMenuEvents(useCallback(() => setSettingsOpen(true), []));
+  useMenuCheckState();
+  useDeepLinks();
+  usePluginTools();
 
   // 监听文档移动/复制和快捷键参考事件
   useEffect(() => {
@@ -0,0 +1,61 @@
+import { useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { getCurrentWindow } from '@tauri-apps/api/window';
+import { Textarea } from '../ui/textarea';
+import { Button } from '../ui/button';
+import { useTranslation } from '@/i18n';
+
+/**
+ * 快速记录窗口：输入内容后追加到当前项目的 Inbox 文档
+ */
+export function QuickCapture() {
+  const { t } = useTranslation();
+  const [text, setText] = useState('');
+  const [saving, setSaving] = useState(false);
+  const [error, setError] = useState('');
+
+  const close = () => getCurrentWindow().close();
+
+  const submit = async () => {
+    if (!text.trim() || saving) return;
+    setSaving(true);
+    setError('');
+    try {
+      await invoke('append_to_inbox', { text });
+      setText('');
+      await close();
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  const handleKeyDown = (e: React.KeyboardEvent) => {
+    if (e.key === 'Enter' && (e.metaKey || e.ctrlKey)) {
+      e.preventDefault();
+      submit();
+    } else if (e.key === 'Escape') {
+      close();
+    }
+  };
+
+  return (
+    <div className="flex h-screen flex-col gap-2 bg-background p-3">
+      <Textarea
+        autoFocus
+        className="flex-1 resize-none"
+        value={text}
+        onChange={(e) => setText(e.target.value)}
+        onKeyDown={handleKeyDown}
+        placeholder={t('quickCapture.placeholder', { defaultValue: '记录想法，Ctrl/⌘+Enter 保存到 Inbox，Esc 关闭' })}
+      />
+      <div className="flex items-center justify-between gap-2">
+        <span className="truncate text-xs text-destructive">{error}</span>
+        <Button size="sm" onClick={submit} disabled={saving || !text.trim()}>
+          {t('quickCapture.save', { defaultValue: '保存' })}
+        </Button>
+      </div>
+    </div>
+  );
+}
@@ -119,6 +119,18 @@ export function SettingsPanel({ open, onClose }: SettingsPanelProps) {
   const [editingService, setEditingService] = useState<AIServiceConfig | null>(null);
   const [isCreatingService, setIsCreatingService] = useState(false);
   const editingProviderConfig = editingService ? getProviderConfig(editingService.provider) : null;
+  // 各服务商是否已在钥匙串保存 API Key（API Key 本身不回传前端）
+  const [savedApiKeys, setSavedApiKeys] = useState<Record<string, boolean>>({});
+
+  useEffect(() => {
+    if (!open) return;
+    const providers = [...new Set(ai.services.map(s => s.provider))];
+    Promise.all(providers.map(provider => invoke<unknown | null>('get_api_key', { provider }).then(entry => [provider, !!entry] as const)))
+      .then((entries) => setSavedApiKeys(Object.fromEntries(entries)))
+      .catch((err) => console.error('[Settings] 读取 API Key 状态失败:', err));
+  }, [open]);
+
+  const hasApiKey = (svc: AIServiceConfig) => !!svc.apiKey || (savedApiKeys[svc.provider] ?? !!svc.hasApiKey);
 
   const handleCreateService = () => {
     const defaultProvider: AIProvider = 'glm';
@@ -137,19 +149,32 @@ export function SettingsPanel({ open, onClose }: SettingsPanelProps) {
   };
 
   const handleEditService = (svc: AIServiceConfig) => {
-    setEditingService({ ...svc });
+    // 已保存的 API Key 不回显，留空表示保持不变
+    setEditingService({ ...svc, apiKey: '' });
     setIsCreatingService(false);
     setTestResult(null);
   };
 
-  const handleSaveService = () => {
+  const handleSaveService = async () => {
     if (!editingService) return;
     // 自动命名：如果用户没填名称，用服务商名称
     const providerCfg = getProviderConfig(editingService.provider);
     const svcName = editingService.name.trim() || providerCfg?.name || editingService.provider;
-    const svc = { ...editingService, name: svcName };
+    const { apiKey, ...profile } = editingService;
+    // API Key 按服务商存入钥匙串，不写入设置
+    if (apiKey?.trim()) {
+      try {
+        await invoke('set_api_key', { provider: profile.provider, apiKey });
+      } catch (err) {
+        setTestResult({ ok: false, msg: String(err) });
+        return;
+      }
+      setSavedApiKeys(prev => ({ ...prev, [profile.provider]: true }));
+    }
+    const svc = { ...profile, name: svcName, hasApiKey: hasApiKey(editingService) };
 
-    const services = [...tempSettings.ai.services];
+    // 同一服务商的其他服务共用钥匙串中的 API Key
+    const services = tempSettings.ai.services.map(s => s.provider === svc.provider ? { ...s, hasApiKey: svc.hasApiKey } : s);
     const idx = services.findIndex(s => s.id === svc.id);
     if (idx >= 0) {
       services[idx] = svc;
@@ -188,9 +213,15 @@ export function SettingsPanel({ open, onClose }: SettingsPanelProps) {
     setEditingService({
       ...editingService,
       provider: newProvider,
+      hasApiKey: undefined,
       model: config?.defaultModel || '',
       baseUrl: config?.baseUrl || '',
     });
+    if (!(newProvider in savedApiKeys)) {
+      invoke<unknown | null>('get_api_key', { provider: newProvider })
+        .then((entry) => setSavedApiKeys(prev => ({ ...prev, [newProvider]: !!entry })))
+        .catch((err) => console.error('[Settings] 读取 API Key 状态失败:', err));
+    }
   };
 
   const handleTestConnection = async () => {
@@ -220,6 +251,25 @@ export function SettingsPanel({ open, onClose }: SettingsPanelProps) {
   const [isCreatingEmailAccount, setIsCreatingEmailAccount] = useState(false);
   const [testingSmtp, setTestingSmtp] = useState(false);
   const [smtpTestResult, setSmtpTestResult] = useState<{ ok: boolean; msg: string } | null>(null);
+  // 后端账户中是否已在钥匙串保存授权码（授权码本身不回传前端）
+  const [savedEmailPasswords, setSavedEmailPasswords] = useState<Record<string, boolean>>({});
+
+  useEffect(() => {
+    if (!open) return;
+    invoke<{ id: string; hasPassword: boolean }[]>('list_email_accounts')
+      .then((profiles) => setSavedEmailPasswords(Object.fromEntries(profiles.map(p => [p.id, p.hasPassword]))))
+      .catch((err) => console.error('[Settings] 读取邮箱账户失败:', err));
+  }, [open]);
+
+  const hasEmailPassword = (acct: EmailAccountConfig) => !!acct.password || !!savedEmailPasswords[acct.id];
+
+  /** 把账户写入后端：授权码只在填写了新值时随此调用存入钥匙串 */
+  const saveEmailAccountToBackend = async (acct: EmailAccountConfig) => {
+    const { password, ...profile } = acct;
+    const saved = await invoke<{ id: string; hasPassword: boolean }>('save_email_account', { account: profile, password: password || null });
+    setSavedEmailPasswords(prev => ({ ...prev, [saved.id]: saved.hasPassword }));
+    return profile;
+  };
 
   const handleCreateEmailAccount = () => {
     const defaultPreset = EMAIL_PROVIDER_PRESETS[0];
@@ -240,25 +290,35 @@ export function SettingsPanel({ open, onClose }: SettingsPanelProps) {
   };
 
   const handleEditEmailAccount = (acct: EmailAccountConfig) => {
-    setEditingEmailAccount({ ...acct });
+    // 已保存的授权码不回填，留空表示保持不变
+    setEditingEmailAccount({ ...acct, password: '' });
     setIsCreatingEmailAccount(false);
     setSmtpTestResult(null);
   };
 
-  const handleSaveEmailAccount = () => {
+  const handleSaveEmailAccount = async () => {
     if (!editingEmailAccount) return;
     const preset = getEmailPreset(editingEmailAccount.provider);
     const acctName = editingEmailAccount.name.trim() || preset?.name || editingEmailAccount.email;
-    const acct = { ...editingEmailAccount, name: acctName };
+
+    // 同步到后端账户配置（授权码存入系统钥匙串，发送时只需传 accountId）；前端设置中不保留授权码
+    let profile: EmailAccountConfig;
+    try {
+      profile = await saveEmailAccountToBackend({ ...editingEmailAccount, name: acctName });
+    } catch (err) {
+      console.error('[Settings] 保存邮箱账户到钥匙串失败:', err);
+      setSmtpTestResult({ ok: false, msg: String(err) });
+      return;
+    }
 
     const accounts = [...tempSettings.email.accounts];
-    const idx = accounts.findIndex(a => a.id === acct.id);
+    const idx = accounts.findIndex(a => a.id === profile.id);
     if (idx >= 0) {
-      accounts[idx] = acct;
+      accounts[idx] = profile;
     } else {
-      accounts.push(acct);
+      accounts.push(profile);
     }
-    const activeId = tempSettings.email.activeAccountId || acct.id;
+    const activeId = tempSettings.email.activeAccountId || profile.id;
     updateTempEmail({ accounts, activeAccountId: activeId });
     setEditingEmailAccount(null);
   };
@@ -270,6 +330,7 @@ export function SettingsPanel({ open, onClose }: SettingsPanelProps) {
       activeId = accounts.find(a => a.enabled)?.id || '';
     }
     updateTempEmail({ accounts, activeAccountId: activeId });
+    invoke('delete_email_account', { accountId: id }).catch(() => {});
   };
 
   const handleToggleEmailAccount = (id: string) => {
@@ -300,13 +361,10 @@ export function SettingsPanel({ open, onClose }: SettingsPanelProps) {
     setTestingSmtp(true);
     setSmtpTestResult(null);
     try {
-      const result = await invoke<string>('test_smtp_connection', {
-        smtpHost: editingEmailAccount.smtpHost,
-        smtpPort: editingEmailAccount.smtpPort,
-        encryption: editingEmailAccount.encryption,
-        email: editingEmailAccount.email,
-        password: editingEmailAccount.password,
-      });
+      // 先保存账户（新填写的授权码存入钥匙串），再按 accountId 测试，授权码不经前端传给测试命令
+      await saveEmailAccountToBackend(editingEmailAccount);
+      setEditingEmailAccount(prev => prev ? { ...prev, password: '' } : prev);
+      const result = await invoke<string>('test_smtp_connection', { accountId: editingEmailAccount.id });
       setSmtpTestResult({ ok: true, msg: result });
       setEditingEmailAccount(prev => prev ? { ...prev, lastTestOk: true } : prev);
     } catch (err: any) {
@@ -881,6 +939,7 @@ export function SettingsPanel({ open, onClose }: SettingsPanelProps) {
                     ['strikethrough', t('settings.toolbar.strikethrough', { defaultValue: '删除线' })],
                     ['inlineCode', t('settings.toolbar.inlineCode', { defaultValue: '行内代码' })],
                     ['clearFormat', t('settings.toolbar.clearFormat', { defaultValue: '清除格式' })],
+                    ['formatDocument', t('settings.toolbar.formatDocument', { defaultValue: '规范化格式' })],
                     ['unorderedList', t('settings.toolbar.unorderedList', { defaultValue: '无序列表' })],
                     ['orderedList', t('settings.toolbar.orderedList', { defaultValue: '有序列表' })],
                     ['taskList', t('settings.toolbar.taskList', { defaultValue: '任务列表' })],
@@ -1109,13 +1168,13 @@ export function SettingsPanel({ open, onClose }: SettingsPanelProps) {
                               )}
                             </div>
                             <div className="text-xs text-muted-foreground truncate mt-0.5">
-                              {t('settings.modelLabel', { defaultValue: '模型: {{model}}', model: svc.model || t('settings.defaultModel', { defaultValue: '默认模型' }) })} {svc.apiKey ? '' : `• ${t('settings.noKeyWarning', { defaultValue: '⚠️ 未配置 Key' })}`}
+                              {t('settings.modelLabel', { defaultValue: '模型: {{model}}', model: svc.model || t('settings.defaultModel', { defaultValue: '默认模型' }) })} {hasApiKey(svc) ? '' : `• ${t('settings.noKeyWarning', { defaultValue: '⚠️ 未配置 Key' })}`}
                             </div>
                           </div>
                           {/* 操作按钮 */}
                           <div className="flex items-center gap-1 flex-shrink-0" onClick={(e) => e.stopPropagation()}>
                             <Button variant="ghost" size="icon" className="h-7 w-7" onClick={() => handleToggleService(svc.id)} title={svc.enabled ? t('settings.disable', { defaultValue: '禁用' }) : t('settings.enable', { defaultValue: '启用' })}>
-                              <Power className={`h-3.5 w-3.5 ${!svc.enabled ? 'text-muted-foreground' : !svc.apiKey ? 'text-red-500' : svc.lastTestOk === true ? 'text-green-500' : svc.lastTestOk === false ? 'text-red-500' : 'text-orange-500'}`} />
+                              <Power className={`h-3.5 w-3.5 ${!svc.enabled ? 'text-muted-foreground' : !hasApiKey(svc) ? 'text-red-500' : svc.lastTestOk === true ? 'text-green-500' : svc.lastTestOk === false ? 'text-red-500' : 'text-orange-500'}`} />
                             </Button>
                             <Button variant="ghost" size="icon" className="h-7 w-7" onClick={() => handleEditService(svc)} title={t('settings.edit', { defaultValue: '编辑' })}>
                               <Pencil className="h-3.5 w-3.5" />
@@ -1270,9 +1329,11 @@ export function SettingsPanel({ open, onClose }: SettingsPanelProps) {
                     <div className="space-y-2">
                       <Label className="text-red-500">{t('settings.apiKeyRequired', { defaultValue: 'API Key' })} <span className="text-xs text-red-500">{t('settings.apiKeyRequiredMark', { defaultValue: '*必填' })}</span></Label>
                       <Input
-                        value={editingService.apiKey}
+                        value={editingService.apiKey ?? ''}
                         onChange={(e) => setEditingService({ ...editingService, apiKey: e.target.value })}
-                        placeholder="sk-..."
+                        placeholder={hasApiKey({ ...editingService, apiKey: '' })
+                          ? t('settings.apiKeySaved', { defaultValue: '已保存在系统钥匙串，留空则保持不变' })
+                          : 'sk-...'}
                         className="font-mono text-sm"
                       />
                     </div>
@@ -1323,7 +1384,7 @@ export function SettingsPanel({ open, onClose }: SettingsPanelProps) {
                       <Button
                         variant="outline"
                         onClick={handleTestConnection}
-                        disabled={testingApi || !editingService.apiKey}
+                        disabled={testingApi || !hasApiKey(editingService)}
                         className="w-full"
                       >
                         {testingApi ? (
@@ -1342,7 +1403,7 @@ export function SettingsPanel({ open, onClose }: SettingsPanelProps) {
                     {/* 保存/取消 */}
                     <div className="flex gap-2 pt-2">
                       <Button variant="outline" className="flex-1" onClick={() => setEditingService(null)}>{t('settings.cancel', { defaultValue: '取消' })}</Button>
-                      <Button className="flex-1" onClick={handleSaveService} disabled={!editingService.apiKey}>
+                      <Button className="flex-1" onClick={handleSaveService} disabled={!hasApiKey(editingService)}>
                         <Check className="h-4 w-4 mr-1" />{t('settings.save', { defaultValue: '保存' })}
                       </Button>
                     </div>
@@ -1394,12 +1455,12 @@ export function SettingsPanel({ open, onClose }: SettingsPanelProps) {
                               )}
                             </div>
                             <div className="text-xs text-muted-foreground truncate mt-0.5">
-                              {acct.email || t('settings.noEmailConfigured', { defaultValue: '未配置邮箱地址' })} {acct.password ? '' : `• ${t('settings.noAuthCodeWarning', { defaultValue: '⚠️ 未配置授权码' })}`}
+                              {acct.email || t('settings.noEmailConfigured', { defaultValue: '未配置邮箱地址' })} {hasEmailPassword(acct) ? '' : `• ${t('settings.noAuthCodeWarning', { defaultValue: '⚠️ 未配置授权码' })}`}
                             </div>
                           </div>
                           <div className="flex items-center gap-1 flex-shrink-0" onClick={(e) => e.stopPropagation()}>
                             <Button variant="ghost" size="icon" className="h-7 w-7" onClick={() => handleToggleEmailAccount(acct.id)} title={acct.enabled ? t('settings.disable', { defaultValue: '禁用' }) : t('settings.enable', { defaultValue: '启用' })}>
-                              <Power className={`h-3.5 w-3.5 ${!acct.enabled ? 'text-muted-foreground' : !acct.password ? 'text-red-500' : acct.lastTestOk === true ? 'text-green-500' : acct.lastTestOk === false ? 'text-red-500' : 'text-orange-500'}`} />
+                              <Power className={`h-3.5 w-3.5 ${!acct.enabled ? 'text-muted-foreground' : !hasEmailPassword(acct) ? 'text-red-500' : acct.lastTestOk === true ? 'text-green-500' : acct.lastTestOk === false ? 'text-red-500' : 'text-orange-500'}`} />
                             </Button>
                             <Button variant="ghost" size="icon" className="h-7 w-7" onClick={() => handleEditEmailAccount(acct)} title={t('settings.edit', { defaultValue: '编辑' })}>
                               <Pencil className="h-3.5 w-3.5" />
@@ -1476,9 +1537,11 @@ export function SettingsPanel({ open, onClose }: SettingsPanelProps) {
                       <Label className="text-red-500">{t('settings.smtpAuthCodeLabel', { defaultValue: 'SMTP 授权码' })} <span className="text-xs text-red-500">{t('settings.smtpAuthCodeRequired', { defaultValue: '*必填' })}</span></Label>
                       <Input
                         type="password"
-                        value={editingEmailAccount.password}
+                        value={editingEmailAccount.password || ''}
                         onChange={(e) => setEditingEmailAccount({ ...editingEmailAccount, password: e.target.value })}
-                        placeholder={t('settings.smtpAuthCodePlaceholder', { defaultValue: 'SMTP 授权码（非登录密码）' })}
+                        placeholder={savedEmailPasswords[editingEmailAccount.id]
+                          ? t('settings.smtpAuthCodeSaved', { defaultValue: '已保存在系统钥匙串，留空则保持不变' })
+                          : t('settings.smtpAuthCodePlaceholder', { defaultValue: 'SMTP 授权码（非登录密码）' })}
                         className="font-mono text-sm"
                       />
                     </div>
@@ -1540,7 +1603,7 @@ export function SettingsPanel({ open, onClose }: SettingsPanelProps) {
                       <Button
                         variant="outline"
                         onClick={handleTestSmtpConnection}
-                        disabled={testingSmtp || !editingEmailAccount.email || !editingEmailAccount.password || !editingEmailAccount.smtpHost}
+                        disabled={testingSmtp || !editingEmailAccount.email || !hasEmailPassword(editingEmailAccount) || !editingEmailAccount.smtpHost}
                         className="w-full"
                       >
                         {testingSmtp ? (
@@ -1558,7 +1621,7 @@ export function SettingsPanel({ open, onClose }: SettingsPanelProps) {
 
                     <div className="flex gap-2 pt-2">
                       <Button variant="outline" className="flex-1" onClick={() => setEditingEmailAccount(null)}>{t('settings.cancel', { defaultValue: '取消' })}</Button>
-                      <Button className="flex-1" onClick={handleSaveEmailAccount} disabled={!editingEmailAccount.email || !editingEmailAccount.password}>
+                      <Button className="flex-1" onClick={handleSaveEmailAccount} disabled={!editingEmailAccount.email || !hasEmailPassword(editingEmailAccount)}>
                         <Check className="h-4 w-4 mr-1" />{t('settings.save', { defaultValue: '保存' })}
                       </Button>
                     </div>
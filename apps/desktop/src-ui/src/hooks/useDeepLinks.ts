@@ -0,0 +1,47 @@
+import { useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { message } from '@tauri-apps/plugin-dialog';
+import { useAppStore } from '@/stores/useAppStore';
+
+interface DeepLinkAction {
+  projectId: string;
+  documentId?: string;
+}
+
+/**
+ * 处理 aidocplus:// 链接：打开指定项目及文档
+ */
+export function useDeepLinks() {
+  useEffect(() => {
+    const processPending = async () => {
+      const actions = await invoke<DeepLinkAction[]>('take_pending_deep_links');
+      for (const action of actions) {
+        const { currentProject, openProject, openTab } = useAppStore.getState();
+        try {
+          if (currentProject?.id !== action.projectId) {
+            await openProject(action.projectId);
+          }
+          if (action.documentId) {
+            await openTab(action.documentId);
+          }
+        } catch (err) {
+          console.error('[DeepLink] 打开链接失败:', err);
+        }
+      }
+    };
+
+    // 启动前收到的链接
+    processPending();
+
+    const unlistenReceived = listen('deep-link:received', () => processPending());
+    const unlistenError = listen<string>('deep-link:error', (event) => {
+      message(event.payload, { title: 'AiDocPlus', kind: 'error' });
+    });
+
+    return () => {
+      unlistenReceived.then(fn => fn());
+      unlistenError.then(fn => fn());
+    };
+  }, []);
+}
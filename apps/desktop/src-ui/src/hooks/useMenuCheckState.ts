@@ -0,0 +1,55 @@
+import { useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { useAppStore } from '@/stores/useAppStore';
+
+/** 视图菜单中的勾选项 */
+export const MENU_CHECK_IDS = ['toggle_sidebar', 'toggle_chat', 'version_history', 'view_editor', 'view_plugins', 'view_composer'];
+
+/** 活动标签当前的视图（由 EditorPanel 上报） */
+let activeView: string | null = null;
+
+function currentChecks() {
+  const { sidebarOpen, tabs, activeTabId } = useAppStore.getState();
+  const tab = tabs.find(t => t.id === activeTabId);
+  return [
+    { id: 'toggle_sidebar', checked: sidebarOpen },
+    { id: 'toggle_chat', checked: !!tab?.panelState.chatOpen },
+    { id: 'version_history', checked: !!tab?.panelState.versionHistoryOpen },
+    { id: 'view_editor', checked: !!tab && activeView === 'editor' },
+    { id: 'view_plugins', checked: !!tab && activeView === 'plugins' },
+    { id: 'view_composer', checked: !!tab && activeView === 'composer' },
+  ];
+}
+
+/**
+ * 把勾选项同步为界面的实际状态
+ * 原生勾选项被点击时会自行切换，处理完菜单事件后需要再同步一次
+ */
+export function syncMenuChecks() {
+  invoke<string[]>('update_menu_items', { items: currentChecks() })
+    .then(errors => errors.forEach(e => console.warn('[Menu] 同步勾选状态失败:', e)))
+    .catch(err => console.warn('[Menu] 同步勾选状态失败:', err));
+}
+
+/** 活动标签切换视图时调用 */
+export function reportActiveView(view: string) {
+  activeView = view;
+  syncMenuChecks();
+}
+
+/**
+ * 启动时及侧边栏、聊天、版本历史状态变化时同步视图菜单的勾选项
+ */
+export function useMenuCheckState() {
+  useEffect(() => {
+    let last = JSON.stringify(currentChecks());
+    syncMenuChecks();
+    return useAppStore.subscribe(() => {
+      const next = JSON.stringify(currentChecks());
+      if (next !== last) {
+        last = next;
+        syncMenuChecks();
+      }
+    });
+  }, []);
+}
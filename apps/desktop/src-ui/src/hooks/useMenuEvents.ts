@@ -3,6 +3,7 @@ import { listen } from '@tauri-apps/api/event';
 import { open, save, message, confirm } from '@tauri-apps/plugin-dialog';
 import { useAppStore } from '@/stores/useAppStore';
 import i18n from '@/i18n';
+import { MENU_CHECK_IDS, syncMenuChecks } from '@/hooks/useMenuCheckState';
 
 /**
  * 监听 Tauri 原生系统菜单事件，分发到前端操作
@@ -20,6 +21,12 @@ export function useMenuEvents(onSettingsOpen: () => void) {
         case 'new_document':
           window.dispatchEvent(new CustomEvent('editor-new-document'));
           break;
+        case 'resume_last_document':
+          // 托盘「继续上次文档」：没有打开的文档时恢复上次的工作区
+          if (!useAppStore.getState().activeTabId) {
+            await useAppStore.getState().restoreWorkspace();
+          }
+          break;
         case 'save':
           window.dispatchEvent(new CustomEvent('save-active-tab'));
           break;
@@ -141,10 +148,27 @@ export function useMenuEvents(onSettingsOpen: () => void) {
         default:
           console.log('[MenuEvent] 未处理的菜单事件:', menuId);
       }
+
+      // 勾选项点击后已自行切换，按处理后的实际状态纠正（状态未变时订阅不会触发）
+      if (MENU_CHECK_IDS.includes(menuId)) {
+        syncMenuChecks();
+      }
     });
 
+    // 快速记录或本地 API 修改文档后刷新当前项目的文档列表
+    const reloadIfCurrent = (event: { payload: { projectId: string } }) => {
+      const { currentProject, loadDocuments } = useAppStore.getState();
+      if (currentProject?.id === event.payload.projectId) {
+        loadDocuments(currentProject.id);
+      }
+    };
+    const unlistenCapture = listen<{ projectId: string }>('quick-capture:appended', reloadIfCurrent);
+    const unlistenApi = listen<{ projectId: string }>('api:document-changed', reloadIfCurrent);
+
     return () => {
       unlisten.then(fn => fn());
+      unlistenCapture.then(fn => fn());
+      unlistenApi.then(fn => fn());
     };
   }, [onSettingsOpen]);
 }
@@ -0,0 +1,17 @@
+import { useEffect } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { handlePluginToolCall, type PluginToolCall } from '@/plugins/_framework/pluginTools';
+
+/**
+ * 响应 AI 对插件工具的调用（ai:tool:call）
+ */
+export function usePluginTools() {
+  useEffect(() => {
+    const unlisten = listen<PluginToolCall>('ai:tool:call', (event) => {
+      handlePluginToolCall(event.payload);
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+}
@@ -2,6 +2,7 @@ import { StrictMode } from 'react'
 import { createRoot } from 'react-dom/client'
 import './index.css'
 import App from './App.tsx'
+import { QuickCapture } from './components/quick-capture/QuickCapture'
 
 // 全局禁用拼写检查和自动纠正
 document.documentElement.setAttribute('spellcheck', 'false');
@@ -39,6 +40,6 @@ new MutationObserver((mutations) => {
 
 createRoot(document.getElementById('root')!).render(
   <StrictMode>
-    <App />
+    {window.location.hash === '#/quick-capture' ? <QuickCapture /> : <App />}
   </StrictMode>,
 )
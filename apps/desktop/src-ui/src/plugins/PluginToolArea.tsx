@@ -242,11 +242,8 @@ export function PluginToolArea({ document, tabId, aiContent, isMaximized, onMaxi
   }, [document.id, saveDocument]);
 
   const handlePluginDataChange = (pluginId: string) => (data: unknown) => {
-    // 自动注入 _version 字段（插件数据版本约定）
-    const versioned = (data != null && typeof data === 'object' && !Array.isArray(data))
-      ? { _version: 1, ...(data as Record<string, unknown>) }
-      : data;
-    updatePluginData(document.id, pluginId, versioned);
+    // _version 字段由后端保存文档时按已安装插件的主版本写入
+    updatePluginData(document.id, pluginId, data);
     markTabAsDirty(tabId);
   };
 
@@ -1,6 +1,6 @@
 import { createContext, useContext } from 'react';
-import type { Document } from '@aidocplus/shared-types';
-import { getActiveRole } from '@aidocplus/shared-types';
+import type { Document, StreamResult } from '@aidocplus/shared-types';
+import { getActiveRole, getActiveService } from '@aidocplus/shared-types';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { save, open } from '@tauri-apps/plugin-dialog';
@@ -8,6 +8,7 @@ import { getAIInvokeParams, useSettingsStore } from '@/stores/useSettingsStore';
 import { usePluginStorageStore } from '@/stores/usePluginStorageStore';
 import { getFragmentsGroupedByPlugin } from '../fragments';
 import { parseThinkTags } from '@/utils/thinkTagParser';
+import { registerPluginTool, type PluginToolHandler } from './pluginTools';
 import i18next from 'i18next';
 
 /** 为插件 AI 调用注入角色 system prompt */
@@ -51,7 +52,12 @@ const ALLOWED_PLUGIN_COMMANDS = new Set([
 
   // 邮件功能
   'test_smtp_connection',   // 测试 SMTP 连接
-  'send_email',             // 发送邮件
+  'send_email',             // 发送邮件（可传 accountId，授权码由后端从钥匙串读取）
+  'list_email_accounts',    // 列出已保存的邮箱账户（不含授权码）
+  'list_sent_emails',       // 发送历史
+  'retry_email',            // 重试发送失败的邮件
+  'search_email_contacts',  // 收件人自动补全
+  'list_email_contact_groups', // 联系人分组（收件人可写 group:分组名）
 
   // Pandoc 导出功能
   'check_pandoc',           // 检测 Pandoc 是否安装及版本
@@ -60,6 +66,11 @@ const ALLOWED_PLUGIN_COMMANDS = new Set([
   // 版本管理（版本时间线插件）
   'list_versions',          // 列出文档版本
   'get_version',            // 获取指定版本详情
+
+  // 后端事件总线（插件间协作，消息经 plugin_subscribe 返回的 plugin-bus:{pluginId} 事件投递）
+  'plugin_subscribe',       // 订阅主题
+  'plugin_unsubscribe',     // 取消订阅
+  'plugin_emit',            // 发布自身命名空间的事件
 ]);
 
 /**
@@ -113,6 +124,11 @@ export interface AIAPI {
   truncateContent(text: string): string;
   /** 获取最近一次 AI 调用中的思考内容（<think> 标签内文本） */
   getLastThinking(): string;
+  /**
+   * 注册 manifest.json 中声明的 AI 工具的处理函数，返回取消注册函数。
+   * 处理函数的返回值为字符串时原样交给模型，其他值序列化为 JSON
+   */
+  registerTool(name: string, handler: PluginToolHandler): () => void;
 }
 
 /** 插件独立存储 API（按 pluginId 命名空间隔离） */
@@ -399,6 +415,7 @@ export function createPluginHostAPI(opts: CreatePluginHostAPIOptions): PluginHos
       const rawResult = await invoke<string>('chat', {
         messages: injectRolePrompt(messages),
         ...aiParams,
+        projectId: opts.getDocument().projectId || undefined,
         maxTokens: options?.maxTokens ?? 4096,
       });
 
@@ -464,9 +481,10 @@ export function createPluginHostAPI(opts: CreatePluginHostAPIOptions): PluginHos
         }
 
         // 调用后端流式接口
-        await invoke<string>('chat_stream', {
+        await invoke<StreamResult>('chat_stream', {
           messages: injectRolePrompt(messages),
           ...aiParams,
+          projectId: opts.getDocument().projectId || undefined,
           maxTokens: options?.maxTokens ?? 4096,
           requestId,
         });
@@ -485,8 +503,8 @@ export function createPluginHostAPI(opts: CreatePluginHostAPIOptions): PluginHos
       }
     },
     isAvailable: () => {
-      const aiParams = getAIInvokeParams();
-      return !!(aiParams.provider && aiParams.apiKey && aiParams.model);
+      const service = getActiveService(useSettingsStore.getState().ai);
+      return !!(service?.provider && service.hasApiKey && service.model);
     },
     truncateContent: (text: string) => {
       const maxLen = useSettingsStore.getState().ai?.maxContentLength || 0;
@@ -496,6 +514,7 @@ export function createPluginHostAPI(opts: CreatePluginHostAPIOptions): PluginHos
       return text;
     },
     getLastThinking: () => lastThinking,
+    registerTool: (name, handler) => registerPluginTool(pluginId, name, handler),
   };
 
   // ── Storage API ──
@@ -0,0 +1,42 @@
+import { invoke } from '@tauri-apps/api/core';
+
+/**
+ * 插件 AI 工具：工具在 manifest.json 的 tools 中声明，处理函数由插件运行时注册。
+ * 模型调用工具时后端发出 ai:tool:call 事件，这里找到对应处理函数执行并回传结果。
+ */
+export type PluginToolHandler = (args: Record<string, unknown>) => unknown | Promise<unknown>;
+
+export interface PluginToolCall {
+  callId: string;
+  pluginId: string;
+  tool: string;
+  arguments: Record<string, unknown>;
+}
+
+const handlers = new Map<string, PluginToolHandler>();
+
+const handlerKey = (pluginId: string, tool: string) => `${pluginId}:${tool}`;
+
+/** 注册插件工具处理函数，返回取消注册函数 */
+export function registerPluginTool(pluginId: string, tool: string, handler: PluginToolHandler): () => void {
+  const key = handlerKey(pluginId, tool);
+  handlers.set(key, handler);
+  return () => {
+    if (handlers.get(key) === handler) handlers.delete(key);
+  };
+}
+
+/** 执行一次工具调用并把结果返回后端 */
+export async function handlePluginToolCall(call: PluginToolCall): Promise<void> {
+  const handler = handlers.get(handlerKey(call.pluginId, call.tool));
+  try {
+    if (!handler) {
+      throw new Error(`插件 ${call.pluginId} 未注册工具 ${call.tool}（插件可能尚未加载）`);
+    }
+    const result = await handler(call.arguments ?? {});
+    await invoke('respond_plugin_tool_call', { callId: call.callId, result: result ?? null });
+  } catch (err) {
+    const error = err instanceof Error ? err.message : String(err);
+    await invoke('respond_plugin_tool_call', { callId: call.callId, error }).catch(() => {});
+  }
+}
@@ -1,5 +1,5 @@
 import { create } from 'zustand';
-import type { Project, Document, DocumentVersion, AIMessage, ChatContextMode, WorkspaceState, EditorTab, PluginManifest, TemplateManifest, TemplateCategory } from '@aidocplus/shared-types';
+import type { Project, Document, DocumentVersion, AIMessage, ChatContextMode, WorkspaceState, EditorTab, Draft, VaultImportReport, AutoTagResult, BulkOp, BulkResult, StreamResult, PluginManifest, TemplateManifest, TemplateCategory } from '@aidocplus/shared-types';
 import { getActiveRole } from '@aidocplus/shared-types';
 import { buildPluginList, setPlugins } from '@/plugins/registry';
 import { syncManifestsToBackend } from '@/plugins/loader';
@@ -9,6 +9,47 @@ import { useSettingsStore, getAIInvokeParams } from './useSettingsStore';
 import { isTauri } from '@/lib/isTauri';
 import i18n from '@/i18n';
 
+// 未保存内容草稿：编辑停顿后写入磁盘，崩溃后启动时恢复
+const DRAFT_STAGE_DELAY = 2000;
+const draftTimers = new Map<string, ReturnType<typeof setTimeout>>();
+
+function scheduleDraftStage(documentId: string, getState: () => AppState) {
+  if (!isTauri()) return;
+  const existing = draftTimers.get(documentId);
+  if (existing) clearTimeout(existing);
+  draftTimers.set(documentId, setTimeout(() => {
+    draftTimers.delete(documentId);
+    const { tabs, documents } = getState();
+    const doc = documents.find(d => d.id === documentId);
+    if (!doc) return;
+    for (const tab of tabs.filter(t => t.documentId === documentId && t.isDirty)) {
+      invoke('stage_draft', {
+        draft: {
+          tabId: tab.id,
+          projectId: doc.projectId,
+          documentId: doc.id,
+          content: doc.content,
+          authorNotes: doc.authorNotes || '',
+          aiGeneratedContent: doc.aiGeneratedContent || '',
+          stagedAt: 0,
+        },
+      }).catch(err => console.warn('[Draft] Failed to stage draft:', err));
+    }
+  }, DRAFT_STAGE_DELAY));
+}
+
+function discardDraft(tabId: string, documentId?: string) {
+  if (!isTauri()) return;
+  if (documentId) {
+    const timer = draftTimers.get(documentId);
+    if (timer) {
+      clearTimeout(timer);
+      draftTimers.delete(documentId);
+    }
+  }
+  invoke('discard_draft', { tabId }).catch(err => console.warn('[Draft] Failed to discard draft:', err));
+}
+
 // Markdown 格式约束提示词：从设置中读取（用户可编辑）
 function getMarkdownModePrompt(): string {
   const ai = useSettingsStore.getState().ai;
@@ -22,6 +63,22 @@ function getRoleSystemPrompt(): string {
   return activeRole?.systemPrompt?.trim() || '';
 }
 
+// 超过此长度的正文分段上传 / 读取（后端大文档阈值按字节计，这里按字符保守取值）
+const LARGE_CONTENT_CHARS = 256 * 1024;
+const CONTENT_CHUNK_CHARS = 256 * 1024;
+
+// 分段读取大文档正文
+async function readDocumentContent(projectId: string, documentId: string): Promise<string> {
+  const parts: string[] = [];
+  let offset: number | null = 0;
+  while (offset !== null) {
+    const chunk: { text: string; nextOffset: number | null } = await invoke('read_document_content', { projectId, documentId, offset });
+    parts.push(chunk.text);
+    offset = chunk.nextOffset;
+  }
+  return parts.join('');
+}
+
 // 标签页面板状态类型
 type TabPanelKey = 'versionHistoryOpen' | 'chatOpen' | 'rightSidebarOpen' | 'layoutMode' | 'splitRatio' | 'chatPanelWidth' | 'activePluginId';
 
@@ -144,12 +201,18 @@ interface AppState {
   loadDocuments: (projectId: string) => Promise<void>;
   createDocument: (projectId: string, title: string, author?: string) => Promise<Document>;
   saveDocument: (document: Document) => Promise<void>;
+  setDocumentLocked: (projectId: string, documentId: string, locked: boolean) => Promise<void>;
   deleteDocument: (projectId: string, documentId: string) => Promise<void>;
   renameDocument: (projectId: string, documentId: string, newTitle: string) => Promise<void>;
 
   // 项目导入/导出/备份
   exportProjectZip: (projectId: string, outputPath: string) => Promise<string>;
   importProjectZip: (zipPath: string) => Promise<Project>;
+  importMarkdownVault: (projectId: string, vaultPath: string) => Promise<VaultImportReport>;
+  exportChatToDocument: (tabId: string, title?: string) => Promise<Document>;
+  autoTagDocument: (projectId: string, documentId: string, apply?: boolean) => Promise<AutoTagResult>;
+  setDocumentTags: (projectId: string, documentId: string, tags: string[]) => Promise<Document>;
+  bulkDocumentOperation: (projectId: string, op: BulkOp, documentIds: string[], options?: { targetProjectId?: string; tags?: string[] }) => Promise<BulkResult>;
 
   // 文档跨项目移动/复制
   moveDocumentToProject: (documentId: string, fromProjectId: string, toProjectId: string) => Promise<Document>;
@@ -395,11 +458,23 @@ export const useAppStore = create<AppState>((set, get) => ({
   saveDocument: async (document) => {
     try {
       set({ isLoading: true, error: null });
+      // 大文档分段上传正文，避免单次 IPC 负载过大
+      let contentUploadId: string | undefined;
+      if (document.content.length > LARGE_CONTENT_CHARS) {
+        contentUploadId = crypto.randomUUID();
+        for (let i = 0; i < document.content.length; i += CONTENT_CHUNK_CHARS) {
+          await invoke('append_document_content', {
+            uploadId: contentUploadId,
+            chunk: document.content.slice(i, i + CONTENT_CHUNK_CHARS),
+          });
+        }
+      }
       const updated = await invoke<Document>('save_document', {
         documentId: document.id,
         projectId: document.projectId,
         title: document.title,
-        content: document.content,
+        content: contentUploadId ? undefined : document.content,
+        contentUploadId,
         authorNotes: document.authorNotes,
         aiGeneratedContent: document.aiGeneratedContent,
         attachments: document.attachments || undefined,
@@ -407,9 +482,11 @@ export const useAppStore = create<AppState>((set, get) => ({
         enabledPlugins: document.enabledPlugins || undefined,
         composedContent: document.composedContent || undefined
       });
+      // 大文档的返回值不含正文，沿用本地正文
+      const saved = updated.contentExternal && !updated.content ? { ...updated, content: document.content } : updated;
       set((state) => ({
-        documents: state.documents.map(d => d.id === updated.id ? updated : d),
-        currentDocument: state.currentDocument?.id === updated.id ? updated : state.currentDocument
+        documents: state.documents.map(d => d.id === saved.id ? saved : d),
+        currentDocument: state.currentDocument?.id === saved.id ? saved : state.currentDocument
       }));
     } catch (error) {
       set({ error: error instanceof Error ? error.message : 'Failed to save document' });
@@ -418,6 +495,19 @@ export const useAppStore = create<AppState>((set, get) => ({
     }
   },
 
+  setDocumentLocked: async (projectId, documentId, locked) => {
+    try {
+      await invoke<Document>(locked ? 'lock_document' : 'unlock_document', { projectId, documentId });
+      // 只合并锁定状态，避免覆盖未保存的编辑和大文档正文
+      set((state) => ({
+        documents: state.documents.map(d => d.id === documentId ? { ...d, locked } : d),
+        currentDocument: state.currentDocument?.id === documentId ? { ...state.currentDocument, locked } : state.currentDocument
+      }));
+    } catch (error) {
+      set({ error: error instanceof Error ? error.message : String(error) });
+    }
+  },
+
   deleteDocument: async (projectId, documentId) => {
     try {
       set({ isLoading: true, error: null });
@@ -488,6 +578,86 @@ export const useAppStore = create<AppState>((set, get) => ({
     }
   },
 
+  // 导入 Obsidian / Typora 笔记库
+  importMarkdownVault: async (projectId, vaultPath) => {
+    try {
+      set({ isLoading: true, error: null });
+      const report = await invoke<VaultImportReport>('import_markdown_vault', { projectId, vaultPath });
+      set({ isLoading: false });
+      await get().loadDocuments(projectId);
+      return report;
+    } catch (error) {
+      set({ error: error instanceof Error ? error.message : String(error), isLoading: false });
+      throw error;
+    }
+  },
+
+  // 将标签页的 AI 对话保存为新文档并打开
+  exportChatToDocument: async (tabId, title) => {
+    const { tabs, documents, aiMessagesByTab } = get();
+    const tab = tabs.find(t => t.id === tabId);
+    const projectId = documents.find(d => d.id === tab?.documentId)?.projectId || get().currentProject?.id;
+    if (!projectId) throw new Error(i18n.t('store.noProjectOpen', { defaultValue: '没有打开的项目' }));
+    const messages = (aiMessagesByTab[tabId] || []).map(m => ({ role: m.role, content: m.content, timestamp: m.timestamp }));
+    const document = await invoke<Document>('export_chat_to_document', { projectId, messages, title, stripThinking: true });
+    set((state) => ({ documents: [...state.documents, document] }));
+    await get().openTab(document.id);
+    return document;
+  },
+
+  // AI 自动标签；apply 为 false 时只返回建议，确认后用 setDocumentTags 写入
+  autoTagDocument: async (projectId, documentId, apply = true) => {
+    const result = await invoke<AutoTagResult>('auto_tag_document', {
+      projectId,
+      documentId,
+      apply,
+      ...getAIInvokeParams(),
+    });
+    if (result.applied) {
+      const document = await invoke<Document>('get_document', { projectId, documentId });
+      set((state) => ({
+        documents: state.documents.map(d => d.id === documentId ? { ...d, metadata: document.metadata } : d),
+      }));
+    }
+    return result;
+  },
+
+  setDocumentTags: async (projectId, documentId, tags) => {
+    const document = await invoke<Document>('set_document_tags', { projectId, documentId, tags });
+    set((state) => ({
+      documents: state.documents.map(d => d.id === documentId ? { ...d, metadata: document.metadata } : d),
+    }));
+    return document;
+  },
+
+  // 批量操作：后端保证全部成功或全部撤销
+  bulkDocumentOperation: async (projectId, op, documentIds, options) => {
+    const result = await invoke<BulkResult>('bulk_document_operation', {
+      projectId,
+      op,
+      documentIds,
+      targetProjectId: options?.targetProjectId,
+      tags: options?.tags,
+    });
+    // 删除、移动后关闭相关标签页（不保存）
+    if (op === 'delete' || op === 'move') {
+      const { tabs, closeTab } = get();
+      for (const tab of tabs.filter(t => documentIds.includes(t.documentId))) {
+        await closeTab(tab.id, false);
+      }
+    }
+    set((state) => {
+      const changed = new Map(result.documents.map(d => [d.id, d]));
+      const removed = op === 'delete' || op === 'move' ? new Set(documentIds) : new Set<string>();
+      const kept = state.documents
+        .filter(d => !removed.has(d.id))
+        .map(d => changed.get(d.id) ?? d);
+      const added = result.documents.filter(d => !kept.some(k => k.id === d.id));
+      return { documents: [...kept, ...added] };
+    });
+    return result;
+  },
+
   // 移动文档到另一个项目
   moveDocumentToProject: async (documentId, fromProjectId, toProjectId) => {
     try {
@@ -715,7 +885,7 @@ export const useAppStore = create<AppState>((set, get) => ({
       const aiSettings = useSettingsStore.getState().ai;
 
       // 构建消息列表，包含可选的 角色prompt + 用户prompt + markdownMode 格式约束
-      const messages: { role: string; content: string }[] = [];
+      const messages: { role: string; content: string; images?: string[] }[] = [];
       const rolePrompt = getRoleSystemPrompt();
       const userSystemPrompt = aiSettings.systemPrompt?.trim() || '';
       const mdPrompt = aiSettings.markdownMode ? getMarkdownModePrompt() : '';
@@ -738,7 +908,8 @@ export const useAppStore = create<AppState>((set, get) => ({
       }
       messages.push(...tabMessages.map((m: AIMessage) => ({
         role: m.role,
-        content: m.content
+        content: m.content,
+        ...(m.images?.length ? { images: m.images } : {})
       })));
 
       // 累积流式内容
@@ -783,15 +954,26 @@ export const useAppStore = create<AppState>((set, get) => ({
       }));
 
       const aiParams = getAIInvokeParams();
-      await invoke<string>('chat_stream', {
+      const result = await invoke<StreamResult>('chat_stream', {
         messages,
         ...aiParams,
+        projectId: get().currentProject?.id,
         enableWebSearch: enableWebSearch || undefined,
         enableThinking: aiSettings.enableThinking || undefined,
         enableTools: enableTools || undefined,
         requestId
       });
 
+      // 记录本次回复的用量，供界面显示 token 计数
+      if (result?.usage) {
+        const usage = result.usage;
+        set((state) => {
+          const msgs = [...(state.aiMessagesByTab[tabId] || [])];
+          if (msgs.length > 0) msgs[msgs.length - 1] = { ...msgs[msgs.length - 1], usage };
+          return { aiMessagesByTab: { ...state.aiMessagesByTab, [tabId]: msgs } };
+        });
+      }
+
       set({ isAiStreaming: false, aiStreamingTabId: null });
       return accumulatedContent;
     } catch (error) {
@@ -830,6 +1012,7 @@ export const useAppStore = create<AppState>((set, get) => ({
         authorNotes,
         currentContent,
         ...aiParams,
+        projectId: get().currentProject?.id,
       });
 
       return generated;
@@ -915,12 +1098,16 @@ export const useAppStore = create<AppState>((set, get) => ({
         authorNotes,
         currentContent,
         ...aiParams,
+        projectId: get().documents.find(d => d.id === get().tabs.find(t => t.id === tabId)?.documentId)?.projectId || get().currentProject?.id,
         conversationHistory: historyForBackend || undefined,
         systemPrompt: (() => {
           const roleSp = getRoleSystemPrompt();
           const userSp = aiSettings.systemPrompt?.trim() || '';
+          // 文档级系统提示词（来自模板关联的提示词模板）
+          const tab = get().tabs.find(t => t.id === tabId);
+          const docSp = get().documents.find(d => d.id === tab?.documentId)?.systemPrompt?.trim() || '';
           const mdSp = aiSettings.markdownMode ? getMarkdownModePrompt() : '';
-          const combined = [roleSp, userSp, mdSp].filter(Boolean).join('\n\n');
+          const combined = [roleSp, userSp, docSp, mdSp].filter(Boolean).join('\n\n');
           return combined || undefined;
         })(),
         enableWebSearch: enableWebSearch || undefined,
@@ -929,7 +1116,7 @@ export const useAppStore = create<AppState>((set, get) => ({
       };
 
       // Invoke the streaming command with conversation history
-      await invoke<string>('generate_content_stream', invokeParams);
+      await invoke<StreamResult>('generate_content_stream', invokeParams);
 
       set({ isAiStreaming: false, aiStreamingTabId: null });
       return '';
@@ -980,6 +1167,10 @@ export const useAppStore = create<AppState>((set, get) => ({
 
       return { documents, currentDocument };
     });
+
+    if ('content' in fields || 'authorNotes' in fields || 'aiGeneratedContent' in fields) {
+      scheduleDraftStage(documentId, get);
+    }
   },
 
   // Convenience method to update AI generated content without overwriting other fields
@@ -1396,6 +1587,22 @@ export const useAppStore = create<AppState>((set, get) => ({
               currentDocument: activeDocument
             });
 
+            // 恢复上次未保存的内容（崩溃或强制退出时）
+            try {
+              const drafts = await invoke<Draft[]>('load_drafts');
+              for (const draft of drafts) {
+                if (!restoredTabs.some(t => t.id === draft.tabId)) continue;
+                get().updateDocumentInMemory(draft.documentId, {
+                  content: draft.content,
+                  authorNotes: draft.authorNotes,
+                  aiGeneratedContent: draft.aiGeneratedContent,
+                });
+                get().markTabAsDirty(draft.tabId);
+              }
+            } catch (err) {
+              console.warn('[Draft] Failed to load drafts:', err);
+            }
+
           } else if (state.currentDocumentId) {
             const allDocuments = get().documents;
             const currentDoc = allDocuments.find(d => d.id === state.currentDocumentId);
@@ -1476,12 +1683,24 @@ export const useAppStore = create<AppState>((set, get) => ({
     }
 
     // 查找文档
-    const document = documents.find(d => d.id === documentId);
+    let document = documents.find(d => d.id === documentId);
     if (!document) {
       console.error('[Tabs] Document not found:', documentId);
       return;
     }
 
+    // 大文档列表中只有元数据，打开时分段读取正文
+    if (document.contentExternal && !document.content) {
+      try {
+        document = { ...document, content: await readDocumentContent(document.projectId, document.id) };
+        const loaded = document;
+        set((state) => ({ documents: state.documents.map(d => d.id === loaded.id ? loaded : d) }));
+      } catch (error) {
+        console.error('[Tabs] Failed to load document content:', error);
+        return;
+      }
+    }
+
     // 创建新标签
     const newTab: EditorTab = {
       id: `tab-${Date.now()}-${Math.random().toString(36).substring(2, 9)}`,
@@ -1529,6 +1748,7 @@ export const useAppStore = create<AppState>((set, get) => ({
       }
     }
     // 注意：saveBeforeClose=false 时直接关闭不保存
+    discardDraft(tabId, tab.documentId);
 
     // 移除标签
     const newTabs = tabs.filter(t => t.id !== tabId);
@@ -1662,6 +1882,8 @@ export const useAppStore = create<AppState>((set, get) => ({
   },
 
   markTabAsClean: (tabId) => {
+    const tab = get().tabs.find(t => t.id === tabId);
+    if (tab?.isDirty) discardDraft(tabId, tab.documentId);
     set((state) => ({
       tabs: state.tabs.map(t =>
         t.id === tabId ? { ...t, isDirty: false } : t
@@ -1,5 +1,6 @@
 import { create } from 'zustand';
 import { persist, createJSONStorage } from 'zustand/middleware';
+import { invoke } from '@tauri-apps/api/core';
 import type { AppSettings } from '@aidocplus/shared-types';
 import {
   DEFAULT_SETTINGS,
@@ -406,14 +407,39 @@ export const useSettingsStore = create<SettingsState>()(
         editor: state.editor,
         ui: state.ui,
         file: state.file,
-        ai: state.ai,
-        email: state.email,
+        // AI API Key 与邮箱授权码只存于系统钥匙串
+        ai: { ...state.ai, services: state.ai.services.map(({ apiKey: _apiKey, ...svc }) => svc) },
+        email: { ...state.email, accounts: state.email.accounts.map(({ password: _password, ...acct }) => acct) },
         shortcuts: state.shortcuts,
         plugins: state.plugins,
         role: state.role,
       }),
-      version: 10,
+      version: 12,
       migrate: (persistedState: any, version: number) => {
+        if (version < 12) {
+          // 迁移: 旧版保存在本地设置中的 AI API Key 按服务商转存到系统钥匙串，并从设置中删除
+          for (const svc of persistedState.ai?.services ?? []) {
+            if (svc.apiKey) {
+              invoke('set_api_key', { provider: svc.provider, apiKey: svc.apiKey }).catch((err) => {
+                console.error('[Settings] 迁移 API Key 到钥匙串失败:', err);
+              });
+              svc.hasApiKey = true;
+            }
+            delete svc.apiKey;
+          }
+        }
+        if (version < 11) {
+          // 迁移: 旧版保存在本地设置中的邮箱授权码转存到系统钥匙串，并从设置中删除
+          for (const acct of persistedState.email?.accounts ?? []) {
+            const { password, ...profile } = acct;
+            if (password) {
+              invoke('save_email_account', { account: profile, password }).catch((err) => {
+                console.error('[Settings] 迁移邮箱授权码到钥匙串失败:', err);
+              });
+            }
+            delete acct.password;
+          }
+        }
         if (version < 10) {
           // 迁移: 新增 role 字段
           if (!persistedState.role) {
@@ -461,11 +487,15 @@ export const useSettingsStore = create<SettingsState>()(
             const baseUrl = oldAi.baseUrl || '';
             const services: any[] = [];
             if (apiKey) {
+              // 该步骤在 v12 迁移之后执行，API Key 需在此直接转存到钥匙串
+              invoke('set_api_key', { provider, apiKey }).catch((err) => {
+                console.error('[Settings] 迁移 API Key 到钥匙串失败:', err);
+              });
               services.push({
                 id: `svc_${Date.now()}`,
                 name: provider,
                 provider,
-                apiKey,
+                hasApiKey: true,
                 model,
                 baseUrl,
                 enabled: true,
@@ -511,17 +541,17 @@ export const usePluginsSettings = () => useSettingsStore((state) => state.plugin
 
 /**
  * 获取当前激活的 AI 服务的 invoke 调用参数。
+ * API Key 不经前端传递，由后端按服务商从系统钥匙串读取。
  */
 export function getAIInvokeParams() {
   const ai = useSettingsStore.getState().ai;
   const service = getActiveService(ai);
   if (!service) {
-    return { provider: undefined, apiKey: undefined, model: undefined, baseUrl: undefined };
+    return { provider: undefined, model: undefined, baseUrl: undefined };
   }
   const providerConfig = getProviderConfig(service.provider);
   return {
     provider: service.provider || undefined,
-    apiKey: service.apiKey || undefined,
     model: service.model || undefined,
     baseUrl: service.baseUrl || providerConfig?.baseUrl || undefined,
   };
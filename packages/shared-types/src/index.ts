@@ -15,6 +15,7 @@ export interface Project {
   updatedAt: number; // Unix timestamp in seconds
   settings: ProjectSettings;
   path: string;
+  session?: ProjectSession; // 仅 open_project 返回，不写入项目文件
 }
 
 export interface ProjectSettings {
@@ -23,6 +24,18 @@ export interface ProjectSettings {
   autoSaveInterval: number; // in seconds
   versionHistoryLimit: number;
   theme: 'light' | 'dark' | 'auto';
+  /** 项目级 AI 配置，优先于全局设置 */
+  ai?: ProjectAiSettings;
+}
+
+/** 项目级 AI 配置；未设置的字段使用全局设置 */
+export interface ProjectAiSettings {
+  provider?: string;
+  model?: string;
+  baseUrl?: string;
+  temperature?: number;
+  /** 附加在该项目所有 AI 对话最前面的系统提示词 */
+  systemPrompt?: string;
 }
 
 // ============================================================
@@ -45,13 +58,27 @@ export interface Document {
   content: string;
   authorNotes: string; // 作者输入
   aiGeneratedContent: string; // AI生成内容
-  versions: DocumentVersion[]; // 历史版本
+  versions?: DocumentVersion[]; // 历史版本已移至版本库，通过 list_versions 获取
   currentVersionId: string;
   metadata: DocumentMetadata;
   attachments?: Attachment[]; // 附件列表
   pluginData?: Record<string, unknown>;  // 插件数据，key = 插件 UUID
   enabledPlugins?: string[];  // 该文档启用的插件 UUID 列表（顺序即标签栏顺序）
   composedContent?: string;  // 合并内容（Markdown），汇集正文+插件片段+外部导入
+  publications?: Publication[];  // 发布到 Confluence / Notion 的记录
+  locked?: boolean;  // 已锁定（定稿），禁止保存、重命名、删除
+  contentExternal?: boolean;  // 大文档：正文单独存储，列表中为空，需通过 read_document_content 分段读取
+  systemPrompt?: string;  // 文档级系统提示词（来自模板关联的提示词模板）
+}
+
+export type PublishTarget = 'confluence' | 'notion';
+
+export interface Publication {
+  target: PublishTarget;
+  container: string;  // Confluence 空间 Key 或 Notion 数据库 / 父页面 ID
+  remoteId: string;
+  url?: string;
+  publishedAt: number;
 }
 
 // ============================================================
@@ -80,6 +107,14 @@ export interface PluginManifest {
   permissions?: string[];        // 所需权限
   dependencies?: string[];       // 依赖的其他插件 UUID
   conflicts?: string[];          // 互斥的插件 UUID
+  tools?: PluginToolDeclaration[]; // 提供给 AI 调用的工具，处理函数通过 host.ai.registerTool 注册
+}
+
+/** 插件声明的 AI 工具，parameters 为 JSON Schema */
+export interface PluginToolDeclaration {
+  name: string;
+  description: string;
+  parameters?: Record<string, unknown>;
 }
 
 // ============================================================
@@ -102,6 +137,7 @@ export interface TemplateManifest {
   enabledPlugins: string[];      // 预设插件列表
   pluginData?: Record<string, unknown>;
   minAppVersion?: string;
+  promptTemplateId?: string;     // 默认提示词模板（prompt-template 资源 ID）
 }
 
 export interface TemplateContent {
@@ -116,6 +152,7 @@ export interface TemplateCategory {
   label: string;
   order: number;
   type: 'builtin' | 'custom';
+  promptTemplateId?: string;     // 分类默认提示词模板
 }
 
 // ============================================================
@@ -159,7 +196,7 @@ export function dateToTimestamp(date: Date): number {
 // AI Provider Types
 // ============================================================
 
-export type AIProvider = 'openai' | 'anthropic' | 'gemini' | 'xai' | 'deepseek' | 'qwen' | 'glm' | 'glm-code' | 'minimax' | 'minimax-code' | 'kimi' | 'kimi-code' | 'custom';
+export type AIProvider = 'openai' | 'anthropic' | 'gemini' | 'xai' | 'deepseek' | 'qwen' | 'glm' | 'glm-code' | 'minimax' | 'minimax-code' | 'kimi' | 'kimi-code' | 'ollama' | 'azure' | 'custom';
 
 export interface AIProviderCapabilities {
   webSearch: boolean;
@@ -178,6 +215,14 @@ export interface AIProviderConfig {
   capabilities: AIProviderCapabilities;
 }
 
+/** list_models 返回的模型；上下文长度仅在服务商接口提供时才有 */
+export interface ModelInfo {
+  id: string;
+  name?: string | null;
+  contextWindow?: number | null;
+  maxOutputTokens?: number | null;
+}
+
 // AI_PROVIDERS 已外部化到 AiDocPlus-AIProviders
 export { AI_PROVIDERS, getProviderConfig } from './generated/ai-providers.generated';
 
@@ -188,6 +233,8 @@ export interface AIMessage {
   content: string;
   timestamp?: number; // Unix timestamp in seconds
   contextMode?: ChatContextMode; // 聊天上下文模式（仅 assistant 消息使用）
+  usage?: TokenUsage; // 本次回复的 token 用量（仅 assistant 消息，服务商返回时才有）
+  images?: string[]; // 附带的图片（data URI，仅 user 消息），发送给视觉模型
 }
 
 export interface AIRequestOptions {
@@ -213,6 +260,150 @@ export interface AIStreamChunk {
   done: boolean;
 }
 
+/** 单次调用的 token 用量，随 ai:stream:usage 事件推送（字段与后端一致为 snake_case） */
+export interface TokenUsage {
+  prompt_tokens: number;
+  completion_tokens: number;
+  total_tokens: number;
+}
+
+/** 费用估算（美元），随 ai:stream:usage 事件的 cost 字段推送 */
+export interface CostEstimate {
+  inputCost: number;
+  outputCost: number;
+  totalCost: number;
+  currency: string;
+  /** 价格表中没有该模型时为 false */
+  priced: boolean;
+}
+
+/** 项目累计 AI 用量（get_project_ai_spend） */
+export interface ProjectSpend {
+  requests: number;
+  promptTokens: number;
+  completionTokens: number;
+  totalCost: number;
+  byModel: Record<string, number>;
+  updatedAt: number;
+}
+
+/** 持久化的 AI 会话消息 */
+export interface ConversationMessage {
+  role: 'user' | 'assistant' | 'system';
+  content: string;
+  timestamp: number;
+  meta?: Record<string, unknown>;
+}
+
+/** 保存在项目目录下的 AI 会话 */
+export interface Conversation {
+  id: string;
+  projectId: string;
+  documentId?: string | null;
+  title: string;
+  createdAt: number;
+  updatedAt: number;
+  messages: ConversationMessage[];
+}
+
+/** list_conversations 返回的会话摘要 */
+export interface ConversationSummary {
+  id: string;
+  documentId: string | null;
+  title: string;
+  messageCount: number;
+  createdAt: number;
+  updatedAt: number;
+}
+
+/** ai:context:truncated 事件：发送前为适应模型上下文长度裁剪了历史消息 */
+export interface ContextTruncation {
+  droppedMessages: number;
+  contentTrimmed: boolean;
+  estimatedTokens: number;
+  contextWindow: number;
+}
+
+/** render_prompt_template 的返回值 */
+export interface RenderedPrompt {
+  prompt: string;
+  systemPrompt: string | null;
+}
+
+/** translate_document 的返回值 */
+export interface TranslationResult {
+  content: string;
+  segments: number;
+  document: Document | null;
+}
+
+/** AI 局部编辑修改后的范围（行号从 1 开始，偏移量按字符计） */
+export interface ChangedRange {
+  startLine: number;
+  endLine: number;
+  start: number;
+  end: number;
+  removed: string;
+  inserted: string;
+}
+
+/** apply_ai_edit 的返回值 */
+export interface AiEditResult {
+  content: string;
+  changes: ChangedRange[];
+  applied: boolean;
+  unchanged: boolean;
+}
+
+/** 大纲条目：id 引用原文章节（如 "s3"），为 null 表示新增的标题 */
+export interface OutlineItem {
+  id: string | null;
+  level: number;
+  title: string;
+}
+
+/** test_web_search 返回的搜索结果（联网搜索后备） */
+export interface WebSearchHit {
+  title: string;
+  url: string;
+  snippet: string;
+}
+
+/** 导出样式方案中的标题样式 */
+export interface ExportHeadingStyle {
+  fonts: string[];
+  size: number;
+  bold: boolean;
+  center: boolean;
+}
+
+/** list_export_styles 返回的导出样式方案（HTML / DOCX / PDF 共用）；save_export_style 保存自定义方案 */
+export interface ExportStyleProfile {
+  /** 内置方案为 gongwen / academic / business / plain；新建自定义方案时留空 */
+  id: string;
+  name: string;
+  description: string;
+  /** 页边距 (mm)：上、下、左、右 */
+  margins: [number, number, number, number];
+  bodyFonts: string[];
+  westernFont: string;
+  bodySize: number;
+  lineSpacing: number;
+  paragraphSpacing: number;
+  firstLineIndent: number;
+  headings: ExportHeadingStyle[];
+  /** 各级标题的编号格式，{n} 为阿拉伯数字、{cn} 为中文数字；空字符串表示不编号 */
+  headingNumbers: string[];
+  pageNumberDashes: boolean;
+  source: 'builtin' | 'local';
+}
+
+/** chat_stream / generate_content_stream 的返回值 */
+export interface StreamResult {
+  content: string;
+  usage: TokenUsage | null;
+}
+
 // ============================================================
 // Slides / PPT Types
 // ============================================================
@@ -411,6 +602,7 @@ export interface ToolbarButtons {
   strikethrough: boolean;  // 删除线
   inlineCode: boolean;     // 行内代码
   clearFormat: boolean;    // 清除格式
+  formatDocument: boolean; // 规范化 Markdown
   unorderedList: boolean;  // 无序列表
   orderedList: boolean;    // 有序列表
   taskList: boolean;       // 任务列表
@@ -473,7 +665,10 @@ export interface AIServiceConfig {
   id: string;
   name: string;
   provider: AIProvider;
-  apiKey: string;
+  /** 仅在设置面板编辑时填写；保存后存入系统钥匙串（按服务商），不持久化到本地设置 */
+  apiKey?: string;
+  /** 钥匙串中是否已保存该服务商的 API Key */
+  hasApiKey?: boolean;
   model: string;
   baseUrl: string;
   enabled: boolean;
@@ -540,7 +735,8 @@ export interface EmailAccountConfig {
   smtpPort: number;
   encryption: 'tls' | 'starttls' | 'none';
   email: string;
-  password: string;
+  /** 仅编辑时暂存新填写的授权码，保存后存入系统钥匙串，不写入设置 */
+  password?: string;
   displayName?: string;
   enabled: boolean;
   lastTestOk?: boolean;
@@ -657,6 +853,7 @@ export const DEFAULT_EDITOR_SETTINGS: EditorSettings = {
     strikethrough: true,
     inlineCode: true,
     clearFormat: true,
+    formatDocument: true,
     unorderedList: true,
     orderedList: true,
     taskList: true,
@@ -804,6 +1001,76 @@ export interface SearchOptions {
   limit?: number;
 }
 
+/** 项目术语表条目（list_glossary / save_glossary_entry） */
+export interface GlossaryEntry {
+  /** 新增时为空 */
+  id: string;
+  /** 规范术语 */
+  term: string;
+  definition: string;
+  /** 推荐译法 */
+  translation: string;
+  /** 禁用写法，拼写检查时提示改为规范术语 */
+  forbidden: string[];
+  updatedAt: number;
+}
+
+/** bulk_document_operation 的操作类型 */
+export type BulkOp = 'move' | 'copy' | 'delete' | 'tag' | 'untag';
+
+/** bulk_document_operation 的结果 */
+export interface BulkResult {
+  affected: number;
+  /** 操作后的文档（删除时为空；复制时为新文档） */
+  documents: Document[];
+}
+
+/** auto_tag_document 的结果 */
+export interface AutoTagResult {
+  tags: string[];
+  /** 不在项目已有标签中的新标签 */
+  newTags: string[];
+  /** 是否已写入文档 */
+  applied: boolean;
+}
+
+/** analyze_readability 中的问题位置 */
+export interface ReadabilityIssue {
+  line: number;
+  /** 命中的词，长句为空 */
+  text: string;
+  excerpt: string;
+}
+
+/** analyze_readability 的结果 */
+export interface ReadabilityReport {
+  characters: number;
+  cjkCharacters: number;
+  words: number;
+  sentences: number;
+  paragraphs: number;
+  readingMinutes: number;
+  avgSentenceLength: number;
+  /** 句长分布，max 为空表示无上限 */
+  sentenceLengths: { min: number; max?: number; count: number }[];
+  longSentences: ReadabilityIssue[];
+  passiveCount: number;
+  passive: ReadabilityIssue[];
+  fillerCount: number;
+  fillers: ReadabilityIssue[];
+}
+
+/** find_similar_documents 的结果 */
+export interface SimilarDocument {
+  documentId: string;
+  title: string;
+  /** 0–1，完全相同为 1 */
+  similarity: number;
+  /** 规范化后正文完全相同 */
+  identical: boolean;
+  updatedAt: number;
+}
+
 export interface SearchHistoryEntry {
   query: string;
   timestamp: number;
@@ -903,6 +1170,8 @@ export interface WorkspaceTabState {
 }
 
 export interface WorkspaceState {
+  /** 文件格式版本，由后端写入并负责迁移 */
+  schemaVersion?: number;
   currentProjectId: string | null;
   openDocumentIds: string[]; // 保持兼容性
   currentDocumentId: string | null; // 保持兼容性
@@ -925,3 +1194,115 @@ export interface WorkspaceState {
   };
   lastSavedAt: number;
 }
+
+/** 标签页未保存内容的草稿，崩溃或强制退出后恢复 */
+export interface Draft {
+  tabId: string;
+  projectId: string;
+  documentId: string;
+  content: string;
+  authorNotes: string;
+  aiGeneratedContent: string;
+  stagedAt: number;
+}
+
+/** 文档编辑位置 */
+export interface DocumentViewState {
+  cursorOffset: number;
+  selectionEnd?: number;
+  scrollTop: number;
+}
+
+/** 项目会话：切换项目时保存，open_project 时恢复 */
+export interface ProjectSession {
+  tabs: WorkspaceTabState[];
+  activeTabId: string | null;
+  viewStates: Record<string, DocumentViewState>;
+  uiState?: WorkspaceState['uiState'];
+  savedAt: number;
+}
+
+// ============================================================
+// Markdown Vault Import Types
+// ============================================================
+
+/** Obsidian / Typora 笔记库导入结果 */
+export interface VaultImportReport {
+  documents: { documentId: string; title: string; source: string }[];
+  linksResolved: number;
+  /** 找不到目标笔记的链接，原样保留 */
+  unresolvedLinks: string[];
+  assetsCopied: number;
+  missingAssets: string[];
+  skipped: string[];
+}
+
+// ============================================================
+// 后台任务
+// ============================================================
+
+export type JobStatus = 'running' | 'completed' | 'failed' | 'cancelled';
+
+/** 后台任务状态，随 job:progress / job:completed 事件推送 */
+export interface JobInfo {
+  id: string;
+  /** 任务类型：import_file / pandoc_export / project_zip_export / project_zip_import / batch_export / native_export */
+  kind: string;
+  status: JobStatus;
+  /** 0–100 */
+  progress: number;
+  message?: string;
+  startedAt: number;
+  error?: string;
+}
+
+/** export:progress 事件（export_document_native） */
+export interface ExportProgress {
+  taskId: string;
+  /** 当前阶段说明 */
+  stage: string;
+  /** 0–100 */
+  progress: number;
+}
+
+// ============================================================
+// 自动化脚本
+// ============================================================
+
+/** 脚本步骤；文本字段中可用 {{变量}} 引用上下文和前面步骤的结果 */
+export type ScriptStep =
+  | { op: 'load'; projectId: string; documentId: string; into: string }
+  | { op: 'concat'; projectId: string; documentIds: string[]; into: string; separator?: string; heading?: boolean }
+  | { op: 'set'; name: string; value: string }
+  | { op: 'template'; templateId?: string; text?: string; into: string }
+  | { op: 'createDocument'; projectId: string; title: string; content: string; into?: string }
+  | { op: 'export'; projectId: string; title: string; content: string; format: string; outputPath: string };
+
+/** 定时触发：weekday 为空时每天执行，1–7 表示周一到周日 */
+export interface ScriptSchedule {
+  weekday?: number;
+  hour: number;
+  minute: number;
+}
+
+export interface AutomationScript {
+  id: string;
+  name: string;
+  description: string;
+  steps: ScriptStep[];
+  schedule?: ScriptSchedule;
+  enabled: boolean;
+  /** 定时执行时使用的变量 */
+  context: Record<string, string>;
+  updatedAt: number;
+  lastRunAt?: number;
+  lastError?: string;
+}
+
+/** run_script 的结果，定时执行时随 script:completed 事件推送 */
+export interface ScriptRunResult {
+  scriptId: string;
+  createdDocuments: string[];
+  exported: string[];
+  log: string[];
+}
@@ -40,4 +40,4 @@ done
 
 echo ""
 echo "================================"
-echo "Assembly complete! Run pnpm install && pnpm build in AiDocPlus/ to build."
+echo "Assembly complete! Run pnpm install && bash scripts/vendor-mermaid.sh && pnpm build in AiDocPlus/ to build."
@@ -0,0 +1,19 @@
+#!/bin/bash
+# 将前端依赖中的 mermaid.js 复制到 bundled-resources/vendor/，供原生导出渲染 Mermaid 图表
+# 版本由 pnpm-lock.yaml 锁定（安装时已校验完整性），需先执行 pnpm install
+set -e
+
+SCRIPT_DIR="$(cd "$(dirname "$0")" && pwd)"
+PROJECT_ROOT="$(dirname "$SCRIPT_DIR")"
+SOURCE="${PROJECT_ROOT}/apps/desktop/src-ui/node_modules/mermaid/dist/mermaid.min.js"
+VENDOR_DIR="${PROJECT_ROOT}/apps/desktop/src-tauri/bundled-resources/vendor"
+
+if [ ! -f "$SOURCE" ]; then
+    echo "未找到 $SOURCE，请先执行 pnpm install"
+    exit 1
+fi
+
+VERSION="$(node -p "require('${PROJECT_ROOT}/apps/desktop/src-ui/node_modules/mermaid/package.json').version")"
+mkdir -p "$VENDOR_DIR"
+cp "$SOURCE" "${VENDOR_DIR}/mermaid.min.js"
+echo "mermaid ${VERSION} -> ${VENDOR_DIR}/mermaid.min.js"